@@ -0,0 +1,51 @@
+//! End-to-end latency of one host collection against each captured procfs tree
+//! in `tests/fixtures/procfs`
+
+use std::path::Path;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+
+use nanomon::adapters::{ProcfsAdapter, ProcfsConfig};
+use nanomon::ports::{ProcessSource, SystemSource};
+
+const SCENARIOS: &[&str] = &["x86-nas", "container", "raspberry-pi", "legacy-kernel"];
+
+fn collection(c: &mut Criterion) {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    let mut group = c.benchmark_group("collect");
+
+    for scenario in SCENARIOS {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/procfs")
+            .join(scenario);
+        let adapter = ProcfsAdapter::new(
+            ProcfsConfig::new(root.join("proc"), root.join("sys")).with_etc_path(root.join("etc")),
+        );
+        let system = adapter.system_source();
+        let processes = adapter.process_source();
+
+        // What a poll reads from procfs, disks aside (statvfs on real mounts)
+        group.bench_function(*scenario, |b| {
+            b.iter(|| {
+                runtime.block_on(async {
+                    system.get_host_info().await.unwrap();
+                    system.get_cpu_metrics().await.unwrap();
+                    system.get_memory_metrics().await.unwrap();
+                    system.get_load_average().await.unwrap();
+                    system.list_network_interfaces().await.unwrap();
+                    system.get_temperatures().await.unwrap();
+                    system.get_kernel_limits().await.unwrap();
+                    processes.list_processes().await.unwrap();
+                })
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, collection);
+criterion_main!(benches);
@@ -0,0 +1,42 @@
+//! Throughput of the procfs parsers on files captured from the x86 NAS fixture
+
+use std::fs;
+use std::path::Path;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+
+use nanomon::adapters::procfs::parser;
+
+fn fixture(file: &str) -> String {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/procfs/x86-nas/proc")
+        .join(file);
+    fs::read_to_string(&path).unwrap_or_else(|e| panic!("{}: {}", path.display(), e))
+}
+
+fn parsers(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+
+    let stat = fixture("4100/stat");
+    group.throughput(Throughput::Bytes(stat.len() as u64));
+    group.bench_function("proc_stat", |b| {
+        b.iter(|| parser::parse_proc_stat(&stat).unwrap())
+    });
+
+    let meminfo = fixture("meminfo");
+    group.throughput(Throughput::Bytes(meminfo.len() as u64));
+    group.bench_function("meminfo", |b| {
+        b.iter(|| parser::parse_meminfo(&meminfo).unwrap())
+    });
+
+    let mounts = fixture("mounts");
+    group.throughput(Throughput::Bytes(mounts.len() as u64));
+    group.bench_function("mounts", |b| {
+        b.iter(|| parser::parse_mounts(&mounts).unwrap())
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, parsers);
+criterion_main!(benches);
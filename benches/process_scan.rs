@@ -0,0 +1,114 @@
+//! Process scan over a generated procfs tree of a busy host. Also prints the
+//! allocations one scan makes, the number buffer reuse keeps down.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use futures::executor::block_on;
+
+use nanomon::adapters::{ProcfsAdapter, ProcfsConfig};
+use nanomon::ports::ProcessSource;
+
+const PROCESSES: u32 = 2000;
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// `<tmp>/proc` with `PROCESSES` PIDs and `<tmp>/etc/passwd`
+fn fixture() -> PathBuf {
+    let root = std::env::temp_dir().join(format!("nanomon-bench-{}", std::process::id()));
+    let proc = root.join("proc");
+    fs::create_dir_all(root.join("etc")).unwrap();
+    fs::write(
+        root.join("etc/passwd"),
+        "root:x:0:0:root:/root:/bin/sh\nnas:x:1000:1000::/home/nas:/bin/sh\n",
+    )
+    .unwrap();
+    fs::create_dir_all(&proc).unwrap();
+    fs::write(proc.join("uptime"), "864000.00 800000.00\n").unwrap();
+    fs::write(proc.join("meminfo"), "MemTotal: 8000000 kB\n").unwrap();
+    fs::write(proc.join("stat"), "btime 1700000000\n").unwrap();
+
+    for pid in 1..=PROCESSES {
+        let dir = proc.join(pid.to_string());
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("stat"),
+            format!(
+                "{} (worker-{}) S 1 {} {} 0 -1 4194560 120 0 0 0 {} {} 0 0 20 0 1 0 {} 10000000 {} 18446744073709551615 0 0 0 0 0 0 0 0 0 0 0 0 17 1 0 0 0 0 0\n",
+                pid,
+                pid,
+                pid,
+                pid,
+                pid * 7,
+                pid * 3,
+                pid * 11,
+                pid % 5000
+            ),
+        )
+        .unwrap();
+        fs::write(
+            dir.join("status"),
+            format!("Name:\tworker-{}\nUid:\t1000\t1000\t1000\t1000\n", pid),
+        )
+        .unwrap();
+        fs::write(
+            dir.join("cmdline"),
+            format!(
+                "/usr/bin/worker\0--id\0{}\0--config\0/etc/worker.toml\0",
+                pid
+            ),
+        )
+        .unwrap();
+        fs::write(dir.join("comm"), format!("worker-{}\n", pid)).unwrap();
+        fs::write(
+            dir.join("cgroup"),
+            "0::/system.slice/docker-4f1c2a9e8b7d.scope\n",
+        )
+        .unwrap();
+    }
+    root
+}
+
+fn process_scan(c: &mut Criterion) {
+    let root = fixture();
+    let source = ProcfsAdapter::new(
+        ProcfsConfig::new(root.join("proc"), Path::new("/sys")).with_etc_path(root.join("etc")),
+    )
+    .process_source();
+
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    let processes = block_on(source.list_processes()).unwrap();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+    assert_eq!(processes.len(), PROCESSES as usize);
+    eprintln!(
+        "process scan: {} allocations for {} processes",
+        allocations, PROCESSES
+    );
+
+    c.bench_function("process_scan_2000", |b| {
+        b.iter(|| block_on(source.list_processes()).unwrap())
+    });
+    fs::remove_dir_all(&root).unwrap();
+}
+
+criterion_group!(benches, process_scan);
+criterion_main!(benches);
@@ -0,0 +1,232 @@
+use std::io::{Read, Seek, SeekFrom};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Datelike, Local, NaiveDateTime, TimeZone, Utc};
+
+use crate::domain::AuthFailure;
+use crate::ports::AuthLogSource;
+
+/// History read on the first call, so a restart does not hide an ongoing attack
+const INITIAL_HISTORY_HOURS: i64 = 24;
+
+/// Never read more than this from the tail of a large auth log
+const MAX_INITIAL_READ: u64 = 16 * 1024 * 1024;
+
+enum Backend {
+    /// Offset of the next unread byte
+    File {
+        path: PathBuf,
+        offset: Mutex<Option<u64>>,
+    },
+    /// Cursor of the last journal entry read
+    Journald { cursor: Mutex<Option<String>> },
+}
+
+/// Reads sshd failures from a syslog auth log or journald,
+/// optionally asking fail2ban for banned addresses
+pub struct AuthLogReader {
+    backend: Backend,
+    fail2ban: bool,
+}
+
+impl AuthLogReader {
+    /// `log` is a file path or `journald`
+    pub fn new(log: &str, fail2ban: bool) -> Self {
+        let backend = if log == "journald" {
+            Backend::Journald {
+                cursor: Mutex::new(None),
+            }
+        } else {
+            Backend::File {
+                path: PathBuf::from(log),
+                offset: Mutex::new(None),
+            }
+        };
+        Self { backend, fail2ban }
+    }
+
+    fn read_file(
+        path: &PathBuf,
+        offset: &Mutex<Option<u64>>,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let mut file = std::fs::File::open(path)?;
+        let len = file.metadata()?.len();
+
+        let mut offset = offset.lock().unwrap();
+        let start = match *offset {
+            // Rotated or truncated: start over
+            Some(o) if o > len => 0,
+            Some(o) => o,
+            None => len.saturating_sub(MAX_INITIAL_READ),
+        };
+
+        file.seek(SeekFrom::Start(start))?;
+        let mut buf = Vec::new();
+        file.take(len - start).read_to_end(&mut buf)?;
+        *offset = Some(len);
+
+        Ok(String::from_utf8_lossy(&buf).into_owned())
+    }
+
+    async fn read_journal(
+        cursor: &Mutex<Option<String>>,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let mut command = tokio::process::Command::new("journalctl");
+        command.args([
+            "_COMM=sshd",
+            "--output=short-iso",
+            "--no-pager",
+            "--quiet",
+            "--show-cursor",
+        ]);
+        let previous = cursor.lock().unwrap().clone();
+        match previous {
+            Some(c) => command.arg(format!("--after-cursor={}", c)),
+            None => command.arg(format!("--since=-{}h", INITIAL_HISTORY_HOURS)),
+        };
+
+        let output = command.output().await?;
+        if !output.status.success() {
+            return Err(format!(
+                "journalctl failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )
+            .into());
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+        if let Some(c) = stdout
+            .lines()
+            .rev()
+            .find_map(|l| l.strip_prefix("-- cursor: "))
+        {
+            *cursor.lock().unwrap() = Some(c.to_string());
+        }
+        Ok(stdout)
+    }
+}
+
+#[async_trait]
+impl AuthLogSource for AuthLogReader {
+    async fn read_failures(
+        &self,
+    ) -> Result<Vec<AuthFailure>, Box<dyn std::error::Error + Send + Sync>> {
+        let content = match &self.backend {
+            Backend::File { path, offset } => Self::read_file(path, offset)?,
+            Backend::Journald { cursor } => Self::read_journal(cursor).await?,
+        };
+
+        let now = Utc::now();
+        let oldest = now - chrono::Duration::hours(INITIAL_HISTORY_HOURS);
+        Ok(content
+            .lines()
+            .filter_map(|line| parse_line(line, now))
+            .filter(|f| f.timestamp >= oldest)
+            .collect())
+    }
+
+    async fn banned_ips(
+        &self,
+    ) -> Result<Option<Vec<String>>, Box<dyn std::error::Error + Send + Sync>> {
+        if !self.fail2ban {
+            return Ok(None);
+        }
+
+        let output = tokio::process::Command::new("fail2ban-client")
+            .args(["status", "sshd"])
+            .output()
+            .await?;
+        if !output.status.success() {
+            return Err(format!(
+                "fail2ban-client failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )
+            .into());
+        }
+
+        // `- Banned IP list:	203.0.113.7 198.51.100.2
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let banned = stdout
+            .lines()
+            .find_map(|l| l.split_once("Banned IP list:"))
+            .map(|(_, ips)| ips.split_whitespace().map(str::to_string).collect())
+            .unwrap_or_default();
+        Ok(Some(banned))
+    }
+}
+
+/// Parse a syslog/journal line, returning a failure for
+/// `sshd[..]: Failed <method> for [invalid user ]<user> from <ip> port ..`
+fn parse_line(line: &str, now: DateTime<Utc>) -> Option<AuthFailure> {
+    let message_start = line.find("sshd[")?;
+    let message = line[message_start..].split_once("]: ")?.1;
+
+    let rest = message.strip_prefix("Failed ")?;
+    let (_, rest) = rest.split_once(" for ")?;
+    let (user, rest) = rest.rsplit_once(" from ")?;
+    let ip = rest.split_whitespace().next()?;
+    let user = user.strip_prefix("invalid user ").unwrap_or(user);
+
+    Some(AuthFailure {
+        timestamp: parse_timestamp(&line[..message_start], now)?,
+        ip: ip.to_string(),
+        user: user.to_string(),
+    })
+}
+
+/// Timestamps: RFC 3339 (rsyslog high precision), journal short-iso
+/// (`2026-01-07T14:32:00+0100`), or classic `Jan  7 14:32:00` in local time
+fn parse_timestamp(prefix: &str, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let first = prefix.split_whitespace().next()?;
+    if let Ok(ts) = DateTime::parse_from_rfc3339(first) {
+        return Some(ts.with_timezone(&Utc));
+    }
+    if let Ok(ts) = DateTime::parse_from_str(first, "%Y-%m-%dT%H:%M:%S%z") {
+        return Some(ts.with_timezone(&Utc));
+    }
+
+    // Classic syslog has no year: assume the current one unless that lands in the future
+    let stamp: String = prefix
+        .split_whitespace()
+        .take(3)
+        .collect::<Vec<_>>()
+        .join(" ");
+    let year = now.with_timezone(&Local).year();
+    for y in [year, year - 1] {
+        let naive =
+            NaiveDateTime::parse_from_str(&format!("{} {}", y, stamp), "%Y %b %d %H:%M:%S").ok()?;
+        let ts = Local
+            .from_local_datetime(&naive)
+            .earliest()?
+            .with_timezone(&Utc);
+        if ts <= now + chrono::Duration::days(1) {
+            return Some(ts);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_failed_login_lines() {
+        let now = Utc::now();
+        let iso = "2026-01-07T14:32:00+0100 nas sshd[812]: Failed password for invalid user admin from 203.0.113.7 port 52144 ssh2";
+        let failure = parse_line(iso, now).unwrap();
+        assert_eq!(failure.ip, "203.0.113.7");
+        assert_eq!(failure.user, "admin");
+        assert_eq!(failure.timestamp.to_rfc3339(), "2026-01-07T13:32:00+00:00");
+
+        let classic = "Jan  7 14:32:00 nas sshd[812]: Failed publickey for root from 198.51.100.2 port 40022 ssh2: RSA SHA256:abc";
+        let failure = parse_line(classic, now).unwrap();
+        assert_eq!(failure.ip, "198.51.100.2");
+        assert_eq!(failure.user, "root");
+
+        let accepted = "Jan  7 14:32:00 nas sshd[812]: Accepted publickey for root from 192.168.1.5 port 40022 ssh2";
+        assert!(parse_line(accepted, now).is_none());
+    }
+}
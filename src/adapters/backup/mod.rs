@@ -0,0 +1,123 @@
+use std::fs;
+use std::path::Path;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use crate::domain::{BackupArtifact, BackupJob, BackupKind};
+use crate::ports::BackupSource;
+
+/// Inspects backup artifacts on the local filesystem.
+///
+/// - `files`: newest file matching a glob in the last path component
+/// - `restic`: newest entry in `<repo>/snapshots/`
+/// - `borg`: newest `<repo>/index.*` (rewritten on every successful archive)
+pub struct FsBackupSource;
+
+impl Default for FsBackupSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FsBackupSource {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn newest_in(
+        dir: &Path,
+        pattern: &str,
+    ) -> Result<Option<BackupArtifact>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut newest: Option<BackupArtifact> = None;
+
+        for entry in fs::read_dir(dir)?.flatten() {
+            let name = entry.file_name();
+            if !wildcard_match(pattern, &name.to_string_lossy()) {
+                continue;
+            }
+
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            let Ok(modified) = metadata.modified() else {
+                continue;
+            };
+            let modified = DateTime::<Utc>::from(modified);
+
+            if newest.as_ref().is_none_or(|n| modified > n.modified) {
+                newest = Some(BackupArtifact {
+                    path: entry.path().to_string_lossy().to_string(),
+                    modified,
+                    size_bytes: metadata.len(),
+                });
+            }
+        }
+
+        Ok(newest)
+    }
+}
+
+#[async_trait]
+impl BackupSource for FsBackupSource {
+    async fn latest_artifact(
+        &self,
+        job: &BackupJob,
+    ) -> Result<Option<BackupArtifact>, Box<dyn std::error::Error + Send + Sync>> {
+        let path = Path::new(&job.path);
+
+        match job.kind {
+            BackupKind::Files => {
+                let dir = path.parent().unwrap_or(Path::new("/"));
+                let pattern = path
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_else(|| "*".to_string());
+                Self::newest_in(dir, &pattern)
+            }
+            BackupKind::Restic => Self::newest_in(&path.join("snapshots"), "*"),
+            BackupKind::Borg => Self::newest_in(path, "index.*"),
+        }
+    }
+}
+
+/// Minimal glob matching supporting `*` and `?`
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let p: Vec<char> = pattern.chars().collect();
+    let n: Vec<char> = name.chars().collect();
+    let (mut pi, mut ni) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while ni < n.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == n[ni]) {
+            pi += 1;
+            ni += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            backtrack = Some((pi, ni));
+            pi += 1;
+        } else if let Some((star_pi, star_ni)) = backtrack {
+            pi = star_pi + 1;
+            ni = star_ni + 1;
+            backtrack = Some((star_pi, star_ni + 1));
+        } else {
+            return false;
+        }
+    }
+
+    p[pi..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wildcard_match() {
+        assert!(wildcard_match("db-*.sql.gz", "db-2026-01-07.sql.gz"));
+        assert!(wildcard_match("index.*", "index.1234"));
+        assert!(wildcard_match("*", "anything"));
+        assert!(wildcard_match("file?.tar", "file1.tar"));
+        assert!(!wildcard_match("db-*.sql.gz", "db-2026.sql"));
+        assert!(!wildcard_match("index.*", "hints.12"));
+    }
+}
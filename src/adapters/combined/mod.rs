@@ -0,0 +1,94 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use async_trait::async_trait;
+
+use crate::domain::{Container, ContainerId, ContainerMount};
+use crate::ports::{ContainerSource, ContainerStats};
+
+/// Merges the containers of several runtimes (Docker, LXC) into one list,
+/// sending per-container calls to the source that listed the container
+pub struct CombinedContainerSource {
+    sources: Vec<(&'static str, Arc<dyn ContainerSource>)>,
+    owners: RwLock<HashMap<ContainerId, usize>>,
+}
+
+impl CombinedContainerSource {
+    pub fn new(sources: Vec<(&'static str, Arc<dyn ContainerSource>)>) -> Self {
+        Self {
+            sources,
+            owners: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// The owner when known, otherwise every source in order
+    fn candidates(&self, id: &ContainerId) -> Vec<&Arc<dyn ContainerSource>> {
+        match self.owners.read().unwrap().get(id) {
+            Some(&index) => vec![&self.sources[index].1],
+            None => self.sources.iter().map(|(_, s)| s).collect(),
+        }
+    }
+}
+
+#[async_trait]
+impl ContainerSource for CombinedContainerSource {
+    /// Fails only if every runtime does, so a stopped Docker daemon still
+    /// leaves the LXC containers visible
+    async fn list_containers(
+        &self,
+    ) -> Result<Vec<Container>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut containers = Vec::new();
+        let mut owners = HashMap::new();
+        let mut failures = Vec::new();
+
+        for (index, (name, source)) in self.sources.iter().enumerate() {
+            match source.list_containers().await {
+                Ok(listed) => {
+                    owners.extend(listed.iter().map(|c| (c.id.clone(), index)));
+                    containers.extend(listed);
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to list {} containers: {}", name, e);
+                    failures.push(e);
+                }
+            }
+        }
+
+        if failures.len() == self.sources.len() {
+            if let Some(e) = failures.pop() {
+                return Err(e);
+            }
+        }
+        *self.owners.write().unwrap() = owners;
+        Ok(containers)
+    }
+
+    async fn get_container_stats(
+        &self,
+        id: &ContainerId,
+    ) -> Result<ContainerStats, Box<dyn std::error::Error + Send + Sync>> {
+        let mut last_error = None;
+        for source in self.candidates(id) {
+            match source.get_container_stats(id).await {
+                Ok(stats) => return Ok(stats),
+                Err(e) => last_error = Some(e),
+            }
+        }
+        Err(last_error.unwrap_or_else(|| format!("container {} not found", id.as_str()).into()))
+    }
+
+    async fn list_mounts(
+        &self,
+        id: &ContainerId,
+    ) -> Result<Option<Vec<ContainerMount>>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut last_error = None;
+        for source in self.candidates(id) {
+            match source.list_mounts(id).await {
+                Ok(Some(mounts)) => return Ok(Some(mounts)),
+                Ok(None) => {}
+                Err(e) => last_error = Some(e),
+            }
+        }
+        last_error.map_or(Ok(None), Err)
+    }
+}
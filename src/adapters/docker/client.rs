@@ -1,23 +1,74 @@
 use async_trait::async_trait;
-use bollard::container::{ListContainersOptions, StatsOptions};
+use bollard::container::{
+    InspectContainerOptions, ListContainersOptions, MemoryStatsStats, RestartContainerOptions,
+    StatsOptions,
+};
+use bollard::errors::Error as DockerError;
+use bollard::image::ListImagesOptions;
+use bollard::models::{MountPointTypeEnum, Port};
 use bollard::Docker;
 use chrono::{DateTime, Utc};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
 
 use crate::domain::{
-    Container, ContainerId, ContainerState, CpuMetrics, IoMetrics, MemoryMetrics, NetworkMetrics,
+    Capability, Container, ContainerHealth, ContainerId, ContainerMount, ContainerPids,
+    ContainerState, CpuMetrics, IoMetrics, MemoryMetrics, MountKind, NetworkMetrics, PublishedPort,
+    ReclaimKind, ReclaimSuggestion,
+};
+use crate::ports::{
+    CapabilityProbe, ContainerControl, ContainerSource, ContainerStats, ReclaimSource,
 };
-use crate::ports::{ContainerSource, ContainerStats};
+
+/// Seconds Docker waits for a container to stop before killing it on restart
+const RESTART_STOP_TIMEOUT: isize = 10;
+
+use super::identity::IdentityCache;
 
 /// Docker adapter using bollard client
 pub struct DockerAdapter {
     client: Docker,
+    /// cgroup v2 mount, for the swap usage the stats API leaves out
+    cgroup_root: Option<PathBuf>,
+    identities: IdentityCache,
 }
 
 impl DockerAdapter {
     pub fn new() -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
         let client = Docker::connect_with_local_defaults()?;
-        Ok(Self { client })
+        Ok(Self {
+            client,
+            cgroup_root: None,
+            identities: IdentityCache::default(),
+        })
+    }
+
+    pub fn with_cgroup_root(mut self, cgroup_root: PathBuf) -> Self {
+        self.cgroup_root = Some(cgroup_root);
+        self
+    }
+
+    /// `memory.swap.current` of a container under the systemd or cgroupfs driver
+    fn swap_usage(&self, id: &ContainerId) -> Option<u64> {
+        let root = self.cgroup_root.as_ref()?;
+        [
+            root.join(format!("system.slice/docker-{}.scope", id.as_str())),
+            root.join("docker").join(id.as_str()),
+        ]
+        .iter()
+        .find_map(|dir| std::fs::read_to_string(dir.join("memory.swap.current")).ok())
+        .and_then(|s| s.trim().parse().ok())
+    }
+
+    /// Engine and API version reported by the daemon
+    pub async fn version(
+        &self,
+    ) -> Result<(String, String), Box<dyn std::error::Error + Send + Sync>> {
+        let version = self.client.version().await?;
+        Ok((
+            version.version.unwrap_or_default(),
+            version.api_version.unwrap_or_default(),
+        ))
     }
 
     #[allow(dead_code)]
@@ -25,7 +76,11 @@ impl DockerAdapter {
         socket_path: &str,
     ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
         let client = Docker::connect_with_socket(socket_path, 120, bollard::API_DEFAULT_VERSION)?;
-        Ok(Self { client })
+        Ok(Self {
+            client,
+            cgroup_root: None,
+            identities: IdentityCache::default(),
+        })
     }
 
     fn map_container_state(state: &Option<String>) -> ContainerState {
@@ -51,12 +106,44 @@ impl DockerAdapter {
             })
     }
 
-    fn parse_container_name(names: &Option<Vec<String>>) -> String {
+    /// Host-published ports, once each although Docker lists IPv4 and IPv6 bindings apart
+    fn published_ports(ports: Vec<Port>) -> Vec<PublishedPort> {
+        let mut published: Vec<PublishedPort> = ports
+            .into_iter()
+            .filter_map(|port| {
+                Some(PublishedPort {
+                    host_port: port.public_port?,
+                    container_port: port.private_port,
+                    protocol: port
+                        .typ
+                        .map(|t| t.to_string())
+                        .filter(|t| !t.is_empty())
+                        .unwrap_or_else(|| "tcp".to_string()),
+                    connections: None,
+                })
+            })
+            .collect();
+        published.sort_by(|a, b| (a.host_port, &a.protocol).cmp(&(b.host_port, &b.protocol)));
+        published.dedup();
+        published
+    }
+
+    fn parse_container_name(names: &Option<Vec<String>>) -> Option<String> {
         names
             .as_ref()
             .and_then(|n| n.first())
             .map(|s| s.trim_start_matches('/').to_string())
-            .unwrap_or_else(|| "unknown".to_string())
+    }
+
+    /// The container was removed since it was listed
+    fn is_gone(error: &(dyn std::error::Error + Send + Sync + 'static)) -> bool {
+        matches!(
+            error.downcast_ref::<DockerError>(),
+            Some(DockerError::DockerResponseServerError {
+                status_code: 404,
+                ..
+            })
+        )
     }
 
     async fn calculate_stats_from_stream(
@@ -92,12 +179,30 @@ impl DockerAdapter {
         let memory_used = stats.memory_stats.usage.unwrap_or(0);
         let memory_limit = stats.memory_stats.limit.unwrap_or(memory_used);
         let memory_available = memory_limit.saturating_sub(memory_used);
+        // The cgroup's memory.stat: anonymous memory vs page cache
+        let (memory_rss, memory_cache) = match stats.memory_stats.stats {
+            Some(MemoryStatsStats::V1(v1)) => (Some(v1.total_rss), Some(v1.total_cache)),
+            Some(MemoryStatsStats::V2(v2)) => (Some(v2.anon), Some(v2.file)),
+            None => (None, None),
+        };
+        let mut memory = MemoryMetrics::new(memory_used, memory_limit, memory_available);
+        if let Some(rss) = memory_rss {
+            memory = memory.with_rss(rss);
+        }
+        if let Some(cache) = memory_cache {
+            memory = memory.with_cache(cache);
+        }
+        if let Some(swap) = self.swap_usage(id) {
+            memory = memory.with_swap(swap);
+        }
 
         // Network I/O
         let mut rx_bytes = 0u64;
         let mut tx_bytes = 0u64;
         let mut rx_errors = 0u64;
         let mut tx_errors = 0u64;
+        let mut rx_dropped = 0u64;
+        let mut tx_dropped = 0u64;
 
         if let Some(networks) = stats.networks {
             for (_name, net_stats) in networks {
@@ -105,6 +210,8 @@ impl DockerAdapter {
                 tx_bytes += net_stats.tx_bytes;
                 rx_errors += net_stats.rx_errors;
                 tx_errors += net_stats.tx_errors;
+                rx_dropped += net_stats.rx_dropped;
+                tx_dropped += net_stats.tx_dropped;
             }
         }
 
@@ -124,9 +231,14 @@ impl DockerAdapter {
 
         Ok(ContainerStats {
             cpu: CpuMetrics::new(cpu_percent, 0.0, 0.0),
-            memory: MemoryMetrics::new(memory_used, memory_limit, memory_available),
-            network: NetworkMetrics::new(rx_bytes, tx_bytes, rx_errors, tx_errors),
+            memory,
+            network: NetworkMetrics::new(rx_bytes, tx_bytes, rx_errors, tx_errors)
+                .with_drops(rx_dropped, tx_dropped),
             block_io: IoMetrics::new(read_bytes, write_bytes),
+            pids: stats
+                .pids_stats
+                .current
+                .map(|current| ContainerPids::new(current, stats.pids_stats.limit)),
         })
     }
 }
@@ -143,32 +255,52 @@ impl ContainerSource for DockerAdapter {
 
         let containers_list = self.client.list_containers(options).await?;
         let mut containers = Vec::new();
+        let listed: HashSet<ContainerId> = containers_list
+            .iter()
+            .filter_map(|c| c.id.clone().map(ContainerId::new))
+            .collect();
+        self.identities.retain(&listed);
 
         for container_summary in containers_list {
             let id = ContainerId::new(container_summary.id.unwrap_or_default());
-            let name = Self::parse_container_name(&container_summary.names);
-            let image = container_summary
-                .image
-                .unwrap_or_else(|| "unknown".to_string());
+            let (name, image) = self.identities.resolve(
+                &id,
+                Self::parse_container_name(&container_summary.names),
+                container_summary.image,
+            );
             let state = Self::map_container_state(&container_summary.state);
             let created = container_summary.created.unwrap_or(0);
             let created_at = DateTime::<Utc>::from_timestamp(created, 0).unwrap_or_else(Utc::now);
 
             let labels = container_summary.labels.unwrap_or_default();
             let stack = Self::extract_stack_name(&labels);
+            let service = labels.get("com.docker.compose.service").cloned();
+            let ports = Self::published_ports(container_summary.ports.unwrap_or_default());
+            let health = container_summary
+                .status
+                .as_deref()
+                .and_then(ContainerHealth::from_status);
 
-            let mut container =
-                Container::new(id.clone(), name, image, state, created_at).with_stack(stack);
+            let mut container = Container::new(id.clone(), name, image, state, created_at)
+                .with_stack(stack)
+                .with_service(service)
+                .with_ports(ports)
+                .with_health(health);
 
             // Get stats for running containers only
             if state.is_running() {
-                if let Ok(stats) = self.get_container_stats(&id).await {
-                    container = container.with_metrics(
-                        stats.cpu,
-                        stats.memory,
-                        stats.network,
-                        stats.block_io,
-                    );
+                match self.get_container_stats(&id).await {
+                    Ok(stats) => {
+                        container = container
+                            .with_metrics(stats.cpu, stats.memory, stats.network, stats.block_io)
+                            .with_pids(stats.pids);
+                    }
+                    // Deleted between the list and the stats call: no longer there
+                    Err(e) if Self::is_gone(e.as_ref()) => {
+                        tracing::debug!("Container {} removed while collecting", id.as_str());
+                        continue;
+                    }
+                    Err(e) => tracing::debug!("No stats for container {}: {}", id.as_str(), e),
                 }
             }
 
@@ -184,4 +316,129 @@ impl ContainerSource for DockerAdapter {
     ) -> Result<ContainerStats, Box<dyn std::error::Error + Send + Sync>> {
         self.calculate_stats_from_stream(id).await
     }
+
+    async fn list_mounts(
+        &self,
+        id: &ContainerId,
+    ) -> Result<Option<Vec<ContainerMount>>, Box<dyn std::error::Error + Send + Sync>> {
+        let details = match self
+            .client
+            .inspect_container(id.as_str(), None::<InspectContainerOptions>)
+            .await
+        {
+            Ok(details) => details,
+            Err(DockerError::DockerResponseServerError {
+                status_code: 404, ..
+            }) => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mounts = details
+            .mounts
+            .unwrap_or_default()
+            .into_iter()
+            .map(|mount| ContainerMount {
+                kind: match mount.typ {
+                    Some(MountPointTypeEnum::VOLUME) => MountKind::Volume,
+                    Some(MountPointTypeEnum::BIND) => MountKind::Bind,
+                    Some(MountPointTypeEnum::TMPFS) => MountKind::Tmpfs,
+                    _ => MountKind::Other,
+                },
+                name: mount.name,
+                source: mount.source.unwrap_or_default(),
+                destination: mount.destination.unwrap_or_default(),
+                read_only: !mount.rw.unwrap_or(true),
+            })
+            .collect();
+
+        Ok(Some(mounts))
+    }
+}
+
+#[async_trait]
+impl ContainerControl for DockerAdapter {
+    async fn restart(
+        &self,
+        id: &ContainerId,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let options = RestartContainerOptions {
+            t: RESTART_STOP_TIMEOUT,
+        };
+        self.client
+            .restart_container(id.as_str(), Some(options))
+            .await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ReclaimSource for DockerAdapter {
+    /// Dangling images, and the writable layers of stopped containers
+    async fn suggestions(
+        &self,
+    ) -> Result<Vec<ReclaimSuggestion>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut suggestions = Vec::new();
+
+        let images = self
+            .client
+            .list_images(Some(ListImagesOptions::<String> {
+                filters: HashMap::from([("dangling".to_string(), vec!["true".to_string()])]),
+                ..Default::default()
+            }))
+            .await?;
+        for image in images {
+            let id = image.id.trim_start_matches("sha256:");
+            suggestions.push(ReclaimSuggestion {
+                kind: ReclaimKind::DanglingImage,
+                target: id.chars().take(12).collect(),
+                savings_bytes: image.size.max(0) as u64,
+                advice: "docker image prune".to_string(),
+            });
+        }
+
+        let stopped = self
+            .client
+            .list_containers(Some(ListContainersOptions::<String> {
+                all: true,
+                size: true,
+                filters: HashMap::from([(
+                    "status".to_string(),
+                    vec![
+                        "exited".to_string(),
+                        "created".to_string(),
+                        "dead".to_string(),
+                    ],
+                )]),
+                ..Default::default()
+            }))
+            .await?;
+        for container in stopped {
+            let Some(name) = Self::parse_container_name(&container.names) else {
+                continue;
+            };
+            let savings_bytes = container.size_rw.unwrap_or(0).max(0) as u64;
+            if savings_bytes == 0 {
+                continue;
+            }
+            suggestions.push(ReclaimSuggestion {
+                kind: ReclaimKind::StoppedContainer,
+                advice: format!("docker rm {}, if it is not coming back", name),
+                target: name,
+                savings_bytes,
+            });
+        }
+
+        Ok(suggestions)
+    }
+}
+
+#[async_trait]
+impl CapabilityProbe for DockerAdapter {
+    async fn probe(&self) -> Vec<(&'static str, Capability)> {
+        let containers = match self.client.ping().await {
+            Ok(_) => Capability::available(),
+            Err(e) => Capability::unavailable(format!("Docker daemon unreachable: {}", e)),
+        };
+        vec![("containers", containers)]
+    }
 }
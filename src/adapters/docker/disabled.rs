@@ -0,0 +1,76 @@
+use async_trait::async_trait;
+
+use crate::domain::{Capability, Container, ContainerId, ContainerMount, ReclaimSuggestion};
+use crate::ports::{
+    CapabilityProbe, ContainerControl, ContainerSource, ContainerStats, ReclaimSource,
+};
+
+const DISABLED: &str = "built without the docker feature";
+
+/// Stand-in for binaries built without bollard: reports no containers
+pub struct DockerAdapter;
+
+impl DockerAdapter {
+    pub fn new() -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(Self)
+    }
+
+    pub fn with_cgroup_root(self, _cgroup_root: std::path::PathBuf) -> Self {
+        self
+    }
+
+    pub async fn version(
+        &self,
+    ) -> Result<(String, String), Box<dyn std::error::Error + Send + Sync>> {
+        Err(DISABLED.into())
+    }
+}
+
+#[async_trait]
+impl ContainerSource for DockerAdapter {
+    async fn list_containers(
+        &self,
+    ) -> Result<Vec<Container>, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(Vec::new())
+    }
+
+    async fn get_container_stats(
+        &self,
+        _id: &ContainerId,
+    ) -> Result<ContainerStats, Box<dyn std::error::Error + Send + Sync>> {
+        Err(DISABLED.into())
+    }
+
+    async fn list_mounts(
+        &self,
+        _id: &ContainerId,
+    ) -> Result<Option<Vec<ContainerMount>>, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(None)
+    }
+}
+
+#[async_trait]
+impl ContainerControl for DockerAdapter {
+    async fn restart(
+        &self,
+        _id: &ContainerId,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        Err(DISABLED.into())
+    }
+}
+
+#[async_trait]
+impl ReclaimSource for DockerAdapter {
+    async fn suggestions(
+        &self,
+    ) -> Result<Vec<ReclaimSuggestion>, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(Vec::new())
+    }
+}
+
+#[async_trait]
+impl CapabilityProbe for DockerAdapter {
+    async fn probe(&self) -> Vec<(&'static str, Capability)> {
+        vec![("containers", Capability::unavailable(DISABLED))]
+    }
+}
@@ -0,0 +1,76 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+use crate::domain::ContainerId;
+
+/// Last name and image seen for each listed container, filled in when the
+/// engine lists a container without them (it does while restarting) so the
+/// UI keeps showing it instead of an "unknown" entry
+#[derive(Default)]
+pub struct IdentityCache {
+    known: Mutex<HashMap<ContainerId, (String, String)>>,
+}
+
+impl IdentityCache {
+    /// Name and image of a listed container, remembered when reported and
+    /// recalled when missing
+    pub fn resolve(
+        &self,
+        id: &ContainerId,
+        name: Option<String>,
+        image: Option<String>,
+    ) -> (String, String) {
+        let name = name.filter(|n| !n.is_empty());
+        let image = image.filter(|i| !i.is_empty());
+        let mut known = self.known.lock().unwrap();
+        let previous = known.get(id);
+        let name = name
+            .or_else(|| previous.map(|(name, _)| name.clone()))
+            .unwrap_or_else(|| "unknown".to_string());
+        let image = image
+            .or_else(|| previous.map(|(_, image)| image.clone()))
+            .unwrap_or_else(|| "unknown".to_string());
+        known.insert(id.clone(), (name.clone(), image.clone()));
+        (name, image)
+    }
+
+    /// Forgets containers no longer listed, so the cache stays as small as the list
+    pub fn retain(&self, listed: &HashSet<ContainerId>) {
+        self.known
+            .lock()
+            .unwrap()
+            .retain(|id, _| listed.contains(id));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_identity_is_recalled() {
+        let cache = IdentityCache::default();
+        let id = ContainerId::new("abc".to_string());
+        let identity = |name: &str, image: &str| (name.to_string(), image.to_string());
+
+        assert_eq!(
+            cache.resolve(&id, Some("web".into()), Some("nginx".into())),
+            identity("web", "nginx")
+        );
+        assert_eq!(
+            cache.resolve(&id, None, Some(String::new())),
+            identity("web", "nginx")
+        );
+
+        cache.retain(&HashSet::new());
+        assert_eq!(
+            cache.resolve(&id, None, None),
+            identity("unknown", "unknown")
+        );
+        // A placeholder does not hide the real name once it is reported again
+        assert_eq!(
+            cache.resolve(&id, Some("web".into()), None),
+            identity("web", "unknown")
+        );
+    }
+}
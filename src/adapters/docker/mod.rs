@@ -1,3 +1,11 @@
+#[cfg(feature = "docker")]
 mod client;
+#[cfg(not(feature = "docker"))]
+mod disabled;
+#[cfg(feature = "docker")]
+mod identity;
 
+#[cfg(feature = "docker")]
 pub use client::DockerAdapter;
+#[cfg(not(feature = "docker"))]
+pub use disabled::DockerAdapter;
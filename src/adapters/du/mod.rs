@@ -0,0 +1,179 @@
+use std::fs;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use chrono::Utc;
+
+use crate::domain::{DirectoryUsage, ReclaimSuggestion, LARGE_LOG_BYTES};
+use crate::ports::{DirectoryScanner, ReclaimSource};
+
+const LOG_DIR: &str = "/var/log";
+const JOURNAL_DIR: &str = "/var/log/journal";
+
+/// du-style directory walker.
+/// When NanoMon runs in a container, host paths are resolved under `host_root`
+/// (the host `/` mounted read-only).
+pub struct FsDirectoryScanner {
+    host_root: Option<PathBuf>,
+}
+
+impl FsDirectoryScanner {
+    pub fn new(host_root: Option<PathBuf>) -> Self {
+        Self { host_root }
+    }
+
+    fn resolve(&self, path: &str) -> PathBuf {
+        match &self.host_root {
+            Some(root) => root.join(path.trim_start_matches('/')),
+            None => PathBuf::from(path),
+        }
+    }
+}
+
+#[async_trait]
+impl DirectoryScanner for FsDirectoryScanner {
+    async fn usage(
+        &self,
+        path: &str,
+        max_entries: u64,
+    ) -> Result<DirectoryUsage, Box<dyn std::error::Error + Send + Sync>> {
+        let root = self.resolve(path);
+        let usage = tokio::task::spawn_blocking(move || walk(&root, max_entries)).await??;
+        Ok(usage)
+    }
+}
+
+/// Finds an oversized systemd journal and large files under `/var/log`
+pub struct LogReclaimSource {
+    scanner: FsDirectoryScanner,
+    max_entries: u64,
+}
+
+impl LogReclaimSource {
+    /// Visits at most `max_entries` entries per directory tree
+    pub fn new(host_root: Option<PathBuf>, max_entries: u64) -> Self {
+        Self {
+            scanner: FsDirectoryScanner::new(host_root),
+            max_entries,
+        }
+    }
+}
+
+#[async_trait]
+impl ReclaimSource for LogReclaimSource {
+    async fn suggestions(
+        &self,
+    ) -> Result<Vec<ReclaimSuggestion>, Box<dyn std::error::Error + Send + Sync>> {
+        let logs = self.scanner.resolve(LOG_DIR);
+        let journal = self.scanner.resolve(JOURNAL_DIR);
+        let max_entries = self.max_entries;
+        tokio::task::spawn_blocking(move || {
+            let mut suggestions = Vec::new();
+            if journal.is_dir() {
+                let usage = walk(&journal, max_entries)?;
+                suggestions.extend(ReclaimSuggestion::journal(JOURNAL_DIR, usage.used_bytes));
+            }
+            for (path, bytes) in large_files(&logs, &journal, LARGE_LOG_BYTES, max_entries)? {
+                let relative = path.strip_prefix(&logs).unwrap_or(&path);
+                let host_path = Path::new(LOG_DIR).join(relative);
+                suggestions.push(ReclaimSuggestion::log_file(
+                    &host_path.display().to_string(),
+                    bytes,
+                ));
+            }
+            Ok(suggestions)
+        })
+        .await?
+    }
+}
+
+/// Files below `root` allocating at least `min_bytes`, leaving out `skip`
+fn large_files(
+    root: &Path,
+    skip: &Path,
+    min_bytes: u64,
+    max_entries: u64,
+) -> Result<Vec<(PathBuf, u64)>, Box<dyn std::error::Error + Send + Sync>> {
+    let device = fs::symlink_metadata(root)?.dev();
+    let mut found = Vec::new();
+    let mut entries = 0u64;
+    let mut pending = vec![root.to_path_buf()];
+
+    while let Some(dir) = pending.pop() {
+        let Ok(read_dir) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in read_dir.flatten() {
+            entries += 1;
+            if entries > max_entries {
+                return Ok(found);
+            }
+            let Ok(meta) = entry.metadata() else {
+                continue;
+            };
+            let path = entry.path();
+            if meta.dev() != device || path == skip {
+                continue;
+            }
+            if meta.is_dir() {
+                pending.push(path);
+            } else if meta.blocks() * 512 >= min_bytes {
+                found.push((path, meta.blocks() * 512));
+            }
+        }
+    }
+    Ok(found)
+}
+
+/// Sum st_blocks of everything below `root` without following symlinks or
+/// crossing into other filesystems
+fn walk(
+    root: &Path,
+    max_entries: u64,
+) -> Result<DirectoryUsage, Box<dyn std::error::Error + Send + Sync>> {
+    let root_meta = fs::symlink_metadata(root)?;
+    let device = root_meta.dev();
+
+    let mut used_bytes = root_meta.blocks() * 512;
+    let mut files = 0u64;
+    let mut entries = 0u64;
+    let mut truncated = false;
+    let mut partial = false;
+    let mut pending = vec![root.to_path_buf()];
+
+    'walk: while let Some(dir) = pending.pop() {
+        let Ok(read_dir) = fs::read_dir(&dir) else {
+            partial = true;
+            continue;
+        };
+        for entry in read_dir.flatten() {
+            entries += 1;
+            if entries > max_entries {
+                truncated = true;
+                break 'walk;
+            }
+
+            let Ok(meta) = entry.metadata() else {
+                continue;
+            };
+            if meta.dev() != device {
+                continue;
+            }
+            used_bytes += meta.blocks() * 512;
+            if meta.is_dir() {
+                pending.push(entry.path());
+            } else {
+                files += 1;
+            }
+        }
+    }
+
+    Ok(DirectoryUsage {
+        used_bytes,
+        files,
+        truncated,
+        partial,
+        measured_at: Utc::now(),
+    })
+}
@@ -0,0 +1,356 @@
+use std::collections::{BTreeSet, HashMap};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+use serde_json::Value;
+
+use crate::domain::{FirewallChain, FirewallRuleset, ListeningSocket, RuleCounter};
+use crate::ports::FirewallSource;
+
+/// Reads nftables (`nft -j list ruleset`) or, failing that, `iptables-save`.
+/// Needs CAP_NET_ADMIN in the host network namespace (`network_mode: host`).
+pub struct NetfilterFirewall {
+    proc_path: PathBuf,
+}
+
+impl NetfilterFirewall {
+    pub fn new(proc_path: PathBuf) -> Self {
+        Self { proc_path }
+    }
+
+    async fn run(program: &str, args: &[&str]) -> Option<String> {
+        let output = tokio::process::Command::new(program)
+            .args(args)
+            .output()
+            .await
+            .ok()?;
+        if !output.status.success() {
+            tracing::debug!(
+                "{} failed: {}",
+                program,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+            return None;
+        }
+        Some(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+#[async_trait]
+impl FirewallSource for NetfilterFirewall {
+    async fn ruleset(
+        &self,
+    ) -> Result<Option<FirewallRuleset>, Box<dyn std::error::Error + Send + Sync>> {
+        if let Some(json) = Self::run("nft", &["-j", "list", "ruleset"]).await {
+            let ruleset = parse_nft_json(&serde_json::from_str(&json)?);
+            // iptables-legacy rules are invisible to nft
+            if !ruleset.chains.is_empty() {
+                return Ok(Some(ruleset));
+            }
+        }
+        Ok(Self::run("iptables-save", &[])
+            .await
+            .map(|out| parse_iptables_save(&out)))
+    }
+
+    async fn rule_counters(
+        &self,
+    ) -> Result<Option<Vec<RuleCounter>>, Box<dyn std::error::Error + Send + Sync>> {
+        match Self::run("nft", &["-j", "list", "ruleset"]).await {
+            Some(json) => Ok(Some(parse_nft_counters(&serde_json::from_str(&json)?))),
+            None => Ok(None),
+        }
+    }
+
+    async fn listening_sockets(
+        &self,
+    ) -> Result<Vec<ListeningSocket>, Box<dyn std::error::Error + Send + Sync>> {
+        // PID 1 lives in the host network namespace, even when NanoMon does not (pid: host)
+        let mut sockets = Vec::new();
+        for file in ["tcp", "tcp6"] {
+            let path = self.proc_path.join("1/net").join(file);
+            if let Ok(content) = tokio::fs::read_to_string(&path).await {
+                sockets.extend(parse_proc_net_tcp(&content));
+            }
+        }
+        Ok(sockets)
+    }
+}
+
+/// Parse `nft -j list ruleset`
+fn parse_nft_json(json: &Value) -> FirewallRuleset {
+    let mut chains: Vec<FirewallChain> = Vec::new();
+    let mut index: HashMap<(String, String), usize> = HashMap::new();
+    let mut input_ports = BTreeSet::new();
+
+    let table_name = |obj: &Value| {
+        format!(
+            "{} {}",
+            obj["family"].as_str().unwrap_or_default(),
+            obj["table"].as_str().unwrap_or_default()
+        )
+    };
+
+    for item in json["nftables"].as_array().into_iter().flatten() {
+        if let Some(chain) = item.get("chain") {
+            let table = table_name(chain);
+            let name = chain["name"].as_str().unwrap_or_default().to_string();
+            // Only filter chains decide what gets in
+            let hook = chain["hook"]
+                .as_str()
+                .filter(|_| chain["type"].as_str() == Some("filter"))
+                .map(str::to_string);
+            index.insert((table.clone(), name.clone()), chains.len());
+            chains.push(FirewallChain {
+                table,
+                name,
+                policy: hook
+                    .as_ref()
+                    .map(|_| chain["policy"].as_str().unwrap_or("accept").to_string()),
+                hook,
+                rules: 0,
+            });
+        } else if let Some(rule) = item.get("rule") {
+            let key = (
+                table_name(rule),
+                rule["chain"].as_str().unwrap_or_default().to_string(),
+            );
+            let Some(&i) = index.get(&key) else {
+                continue;
+            };
+            chains[i].rules += 1;
+            if matches!(chains[i].hook.as_deref(), Some("input") | None) {
+                collect_nft_dports(&rule["expr"], &mut input_ports);
+            }
+        }
+    }
+
+    FirewallRuleset {
+        backend: "nftables".to_string(),
+        chains,
+        input_ports,
+    }
+}
+
+/// Counters worth a name from `nft -j list ruleset`: rules with both a
+/// `counter` statement and a comment, and named counter objects
+fn parse_nft_counters(json: &Value) -> Vec<RuleCounter> {
+    let table_name = |obj: &Value| {
+        format!(
+            "{} {}",
+            obj["family"].as_str().unwrap_or_default(),
+            obj["table"].as_str().unwrap_or_default()
+        )
+    };
+    let totals = |counter: &Value| Some((counter["packets"].as_u64()?, counter["bytes"].as_u64()?));
+
+    json["nftables"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|item| {
+            if let Some(counter) = item.get("counter") {
+                let (packets, bytes) = totals(counter)?;
+                return Some(RuleCounter {
+                    table: table_name(counter),
+                    chain: None,
+                    name: counter["name"].as_str()?.to_string(),
+                    packets,
+                    bytes,
+                });
+            }
+            let rule = item.get("rule")?;
+            let name = rule["comment"].as_str()?;
+            // `{"counter": "name"}` feeds a named counter, reported on its own
+            let (packets, bytes) = rule["expr"]
+                .as_array()?
+                .iter()
+                .find_map(|statement| totals(&statement["counter"]))?;
+            Some(RuleCounter {
+                table: table_name(rule),
+                chain: rule["chain"].as_str().map(String::from),
+                name: name.to_string(),
+                packets,
+                bytes,
+            })
+        })
+        .collect()
+}
+
+/// Find `{"match": {"left": {"payload": {"field": "dport"}}, "right": ...}}`
+fn collect_nft_dports(expr: &Value, ports: &mut BTreeSet<u16>) {
+    for statement in expr.as_array().into_iter().flatten() {
+        let m = &statement["match"];
+        if m["left"]["payload"]["field"].as_str() != Some("dport") {
+            continue;
+        }
+        let right = &m["right"];
+        let values: Vec<&Value> = match right["set"].as_array() {
+            Some(set) => set.iter().collect(),
+            None => vec![right],
+        };
+        for value in values {
+            if let Some(port) = value.as_u64() {
+                ports.insert(port as u16);
+            } else if let Some([lo, hi]) = value["range"].as_array().map(Vec::as_slice) {
+                if let (Some(lo), Some(hi)) = (lo.as_u64(), hi.as_u64()) {
+                    ports.extend((lo as u16)..=(hi as u16));
+                }
+            }
+        }
+    }
+}
+
+/// Parse `iptables-save` output
+fn parse_iptables_save(output: &str) -> FirewallRuleset {
+    let mut chains: Vec<FirewallChain> = Vec::new();
+    let mut input_ports = BTreeSet::new();
+    let mut table = String::new();
+
+    for line in output.lines() {
+        if let Some(name) = line.strip_prefix('*') {
+            table = name.to_string();
+        } else if let Some(decl) = line.strip_prefix(':') {
+            // :INPUT DROP [0:0]   or   :ufw-user-input - [0:0]
+            let mut parts = decl.split_whitespace();
+            let name = parts.next().unwrap_or_default().to_string();
+            let policy = parts
+                .next()
+                .filter(|p| *p != "-")
+                .map(|p| p.to_ascii_lowercase());
+            let hook = policy
+                .as_ref()
+                .filter(|_| table == "filter")
+                .map(|_| name.to_ascii_lowercase());
+            chains.push(FirewallChain {
+                table: table.clone(),
+                name,
+                hook,
+                policy,
+                rules: 0,
+            });
+        } else if let Some(rule) = line.strip_prefix("-A ") {
+            let chain_name = rule.split_whitespace().next().unwrap_or_default();
+            let Some(chain) = chains
+                .iter_mut()
+                .find(|c| c.table == table && c.name == chain_name)
+            else {
+                continue;
+            };
+            chain.rules += 1;
+            if table == "filter" && matches!(chain.hook.as_deref(), Some("input") | None) {
+                let mut words = rule.split_whitespace();
+                while let Some(word) = words.next() {
+                    if word == "--dport" || word == "--dports" {
+                        for spec in words.next().unwrap_or_default().split(',') {
+                            let (lo, hi) = spec.split_once(':').unwrap_or((spec, spec));
+                            if let (Ok(lo), Ok(hi)) = (lo.parse::<u16>(), hi.parse::<u16>()) {
+                                input_ports.extend(lo..=hi);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    FirewallRuleset {
+        backend: "iptables".to_string(),
+        chains,
+        input_ports,
+    }
+}
+
+/// Parse /proc/net/tcp{,6}, keeping sockets in LISTEN (0A) state
+fn parse_proc_net_tcp(content: &str) -> Vec<ListeningSocket> {
+    content
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            if parts.get(3) != Some(&"0A") {
+                return None;
+            }
+            let (addr, port) = parts.get(1)?.split_once(':')?;
+            let port = u16::from_str_radix(port, 16).ok()?;
+            let address = match addr.len() {
+                8 => IpAddr::V4(Ipv4Addr::from(
+                    u32::from_str_radix(addr, 16).ok()?.swap_bytes(),
+                )),
+                32 => {
+                    // Four 32-bit words, each in host (little-endian) order
+                    let mut bytes = [0u8; 16];
+                    for (i, chunk) in bytes.chunks_mut(4).enumerate() {
+                        let word = u32::from_str_radix(&addr[i * 8..i * 8 + 8], 16).ok()?;
+                        chunk.copy_from_slice(&word.swap_bytes().to_be_bytes());
+                    }
+                    IpAddr::V6(Ipv6Addr::from(bytes))
+                }
+                _ => return None,
+            };
+            Some(ListeningSocket { address, port })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_iptables_save() {
+        let output = "*filter\n\
+            :INPUT DROP [0:0]\n\
+            :FORWARD DROP [0:0]\n\
+            :OUTPUT ACCEPT [0:0]\n\
+            :ufw-user-input - [0:0]\n\
+            -A INPUT -j ufw-user-input\n\
+            -A ufw-user-input -p tcp -m tcp --dport 22 -j ACCEPT\n\
+            -A ufw-user-input -p tcp -m multiport --dports 80,443,8000:8002 -j ACCEPT\n\
+            COMMIT\n";
+        let ruleset = parse_iptables_save(output);
+        assert_eq!(ruleset.chains.len(), 4);
+        assert_eq!(ruleset.chains[0].hook.as_deref(), Some("input"));
+        assert_eq!(ruleset.chains[0].policy.as_deref(), Some("drop"));
+        assert_eq!(ruleset.chains[3].rules, 2);
+        assert!(ruleset.input_ports.contains(&22));
+        assert!(ruleset.input_ports.contains(&8001));
+    }
+
+    #[test]
+    fn test_parse_nft_counters() {
+        let json = serde_json::json!({"nftables": [
+            {"metainfo": {"json_schema_version": 1}},
+            {"chain": {"family": "inet", "table": "filter", "name": "input", "type": "filter", "hook": "input", "policy": "drop"}},
+            {"rule": {"family": "inet", "table": "filter", "chain": "input", "comment": "geo-block",
+                      "expr": [{"match": {"op": "==", "left": {"meta": {"key": "iif"}}, "right": "eth0"}},
+                               {"counter": {"packets": 1200, "bytes": 72000}}, {"drop": null}]}},
+            {"rule": {"family": "inet", "table": "filter", "chain": "input",
+                      "expr": [{"counter": {"packets": 5, "bytes": 300}}, {"accept": null}]}},
+            {"rule": {"family": "inet", "table": "filter", "chain": "input", "comment": "ssh-ratelimit",
+                      "expr": [{"counter": "ssh_limited"}, {"drop": null}]}},
+            {"counter": {"family": "inet", "table": "filter", "name": "ssh_limited", "packets": 42, "bytes": 2520}}
+        ]});
+        let counters = parse_nft_counters(&json);
+        assert_eq!(counters.len(), 2);
+        assert_eq!(counters[0].name, "geo-block");
+        assert_eq!(counters[0].chain.as_deref(), Some("input"));
+        assert_eq!(counters[0].packets, 1200);
+        assert_eq!(counters[1].name, "ssh_limited");
+        assert_eq!(counters[1].chain, None);
+        assert_eq!(counters[1].bytes, 2520);
+    }
+
+    #[test]
+    fn test_parse_proc_net_tcp() {
+        let content = "  sl  local_address rem_address   st tx_queue rx_queue\n\
+            0: 00000000:0016 00000000:0000 0A 00000000:00000000\n\
+            1: 0100007F:0CEA 00000000:0000 0A 00000000:00000000\n\
+            2: 0100A8C0:0016 0200A8C0:D2F0 01 00000000:00000000\n";
+        let sockets = parse_proc_net_tcp(content);
+        assert_eq!(sockets.len(), 2);
+        assert_eq!(sockets[0].port, 22);
+        assert_eq!(sockets[1].address, "127.0.0.1".parse::<IpAddr>().unwrap());
+    }
+}
@@ -0,0 +1,195 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use serde_json::Value;
+
+use crate::domain::{Hypervisor, VirtualMachine, VmState};
+use crate::ports::VmSource;
+
+/// Lists VMs with the hypervisor's own CLI: `virsh` (needs the libvirt socket,
+/// e.g. `/var/run/libvirt` mounted) or `pvesh` on a Proxmox node
+pub struct CommandVmSource {
+    hypervisor: Hypervisor,
+    libvirt_uri: String,
+}
+
+impl CommandVmSource {
+    pub fn new(hypervisor: Hypervisor) -> Self {
+        Self {
+            hypervisor,
+            libvirt_uri: "qemu:///system".to_string(),
+        }
+    }
+
+    pub fn with_libvirt_uri(mut self, uri: impl Into<String>) -> Self {
+        self.libvirt_uri = uri.into();
+        self
+    }
+
+    async fn run(
+        program: &str,
+        args: &[&str],
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let output = tokio::process::Command::new(program)
+            .args(args)
+            .output()
+            .await
+            .map_err(|e| format!("{}: {}", program, e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "{} failed: {}",
+                program,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )
+            .into());
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+#[async_trait]
+impl VmSource for CommandVmSource {
+    async fn list_vms(
+        &self,
+    ) -> Result<Vec<VirtualMachine>, Box<dyn std::error::Error + Send + Sync>> {
+        match self.hypervisor {
+            Hypervisor::Libvirt => {
+                let output = Self::run(
+                    "virsh",
+                    &[
+                        "-c",
+                        &self.libvirt_uri,
+                        "domstats",
+                        "--raw",
+                        "--state",
+                        "--cpu-total",
+                        "--balloon",
+                        "--vcpu",
+                    ],
+                )
+                .await?;
+                Ok(parse_domstats(&output))
+            }
+            Hypervisor::Proxmox => {
+                let output = Self::run(
+                    "pvesh",
+                    &["get", "/nodes/localhost/qemu", "--output-format", "json"],
+                )
+                .await?;
+                Ok(parse_pvesh_qemu(&serde_json::from_str(&output)?))
+            }
+        }
+    }
+}
+
+/// Parse `virsh domstats --raw`: a `Domain: 'name'` line followed by
+/// indented `key=value` pairs, memory in KiB
+fn parse_domstats(output: &str) -> Vec<VirtualMachine> {
+    let mut domains: Vec<(String, HashMap<&str, &str>)> = Vec::new();
+    for line in output.lines() {
+        let line = line.trim();
+        if let Some(name) = line.strip_prefix("Domain:") {
+            let name = name.trim().trim_matches(|c| c == '\'' || c == '"');
+            domains.push((name.to_string(), HashMap::new()));
+        } else if let (Some((_, stats)), Some((key, value))) =
+            (domains.last_mut(), line.split_once('='))
+        {
+            stats.insert(key, value);
+        }
+    }
+
+    domains
+        .into_iter()
+        .map(|(name, stats)| {
+            let number = |key: &str| stats.get(key).and_then(|v| v.parse::<u64>().ok());
+            // virDomainState: 1 running, 3 paused, 4 shutting down, 5 shut off
+            let state = match number("state.state") {
+                Some(1) | Some(4) => VmState::Running,
+                Some(3) => VmState::Paused,
+                Some(5) => VmState::Stopped,
+                _ => VmState::Other,
+            };
+            // The guest's own view needs its balloon driver; RSS is the host's
+            let memory_used = match (number("balloon.available"), number("balloon.unused")) {
+                (Some(available), Some(unused)) => Some(available.saturating_sub(unused)),
+                _ => number("balloon.rss"),
+            };
+            VirtualMachine {
+                name,
+                id: None,
+                state,
+                vcpus: number("vcpu.current")
+                    .or(number("vcpu.maximum"))
+                    .unwrap_or(0) as u32,
+                memory_max_bytes: number("balloon.maximum").unwrap_or(0) * 1024,
+                memory_used_bytes: memory_used
+                    .filter(|_| state == VmState::Running)
+                    .map(|kib| kib * 1024),
+                cpu_percent: None,
+                cpu_time_ns: number("cpu.time"),
+            }
+        })
+        .collect()
+}
+
+/// Parse `pvesh get /nodes/localhost/qemu`; `cpu` is already a fraction of the VM's vCPUs
+fn parse_pvesh_qemu(json: &Value) -> Vec<VirtualMachine> {
+    json.as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|vm| {
+            let id = vm["vmid"]
+                .as_u64()
+                .map(|id| id.to_string())
+                .or_else(|| vm["vmid"].as_str().map(String::from))?;
+            let state = match vm["qmpstatus"].as_str().or(vm["status"].as_str()) {
+                Some("running") => VmState::Running,
+                Some("paused") | Some("suspended") => VmState::Paused,
+                Some("stopped") => VmState::Stopped,
+                _ => VmState::Other,
+            };
+            let running = state == VmState::Running;
+            Some(VirtualMachine {
+                name: vm["name"].as_str().unwrap_or(&id).to_string(),
+                id: Some(id),
+                state,
+                vcpus: vm["cpus"].as_u64().unwrap_or(0) as u32,
+                memory_max_bytes: vm["maxmem"].as_u64().unwrap_or(0),
+                memory_used_bytes: vm["mem"].as_u64().filter(|_| running),
+                cpu_percent: vm["cpu"].as_f64().filter(|_| running).map(|f| f * 100.0),
+                cpu_time_ns: None,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_domstats_and_pvesh() {
+        let output = "Domain: 'media'\n  state.state=1\n  state.reason=1\n  cpu.time=51234567890\n  balloon.current=4194304\n  balloon.maximum=4194304\n  balloon.available=4030000\n  balloon.unused=1030000\n  balloon.rss=4300000\n  vcpu.current=2\n  vcpu.maximum=4\n\nDomain: 'win11'\n  state.state=5\n  balloon.maximum=8388608\n  balloon.rss=0\n  vcpu.current=4\n";
+        let vms = parse_domstats(output);
+        assert_eq!(vms.len(), 2);
+        assert_eq!(vms[0].name, "media");
+        assert_eq!(vms[0].state, VmState::Running);
+        assert_eq!(vms[0].vcpus, 2);
+        assert_eq!(vms[0].memory_max_bytes, 4194304 * 1024);
+        assert_eq!(vms[0].memory_used_bytes, Some(3000000 * 1024));
+        assert_eq!(vms[0].cpu_time_ns, Some(51234567890));
+        assert_eq!(vms[1].state, VmState::Stopped);
+        assert_eq!(vms[1].memory_used_bytes, None);
+
+        let json = serde_json::json!([
+            {"vmid": 100, "name": "opnsense", "status": "running", "cpus": 2,
+             "maxmem": 2147483648u64, "mem": 1073741824u64, "cpu": 0.125},
+            {"vmid": 101, "status": "stopped", "cpus": 1, "maxmem": 1073741824u64, "mem": 0, "cpu": 0}
+        ]);
+        let vms = parse_pvesh_qemu(&json);
+        assert_eq!(vms[0].id.as_deref(), Some("100"));
+        assert_eq!(vms[0].cpu_percent, Some(12.5));
+        assert_eq!(vms[1].name, "101");
+        assert_eq!(vms[1].cpu_percent, None);
+    }
+}
@@ -0,0 +1,397 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Instant;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use crate::domain::{
+    Container, ContainerId, ContainerMount, ContainerPids, ContainerRuntime, ContainerState,
+    CpuMetrics, IoMetrics, MemoryMetrics, NetworkMetrics,
+};
+use crate::ports::{ContainerSource, ContainerStats};
+
+/// Prefix of the IDs given to LXC containers, keeping them apart from Docker's
+const ID_PREFIX: &str = "lxc-";
+
+/// Finds LXC/LXD containers and Proxmox CTs by scanning the cgroup v2 tree
+/// (`lxc.payload.<name>` or `lxc/<vmid>`); stopped Proxmox CTs come from
+/// `/etc/pve/lxc/<vmid>.conf`
+pub struct LxcCgroupSource {
+    proc_path: PathBuf,
+    cgroup_path: PathBuf,
+    etc_path: PathBuf,
+    /// Cumulative CPU usage (µs) and when it was read, per container
+    previous_cpu: Mutex<HashMap<String, (u64, Instant)>>,
+}
+
+impl LxcCgroupSource {
+    pub fn new(proc_path: PathBuf, sys_path: PathBuf, etc_path: PathBuf) -> Self {
+        Self {
+            proc_path,
+            cgroup_path: sys_path.join("fs/cgroup"),
+            etc_path,
+            previous_cpu: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Running containers: name and cgroup directory
+    fn running(&self) -> Vec<(String, PathBuf)> {
+        let mut found = Vec::new();
+        if let Ok(entries) = fs::read_dir(&self.cgroup_path) {
+            for entry in entries.flatten() {
+                let file_name = entry.file_name().to_string_lossy().into_owned();
+                if let Some(name) = file_name.strip_prefix("lxc.payload.") {
+                    found.push((name.to_string(), entry.path()));
+                }
+            }
+        }
+        if let Ok(entries) = fs::read_dir(self.cgroup_path.join("lxc")) {
+            for entry in entries.flatten() {
+                if entry.path().is_dir() {
+                    let name = entry.file_name().to_string_lossy().into_owned();
+                    found.push((name, entry.path()));
+                }
+            }
+        }
+        found
+    }
+
+    /// Proxmox CT IDs with a config, running or not
+    fn configured(&self) -> Vec<String> {
+        fs::read_dir(self.etc_path.join("pve/lxc"))
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter_map(|e| {
+                e.file_name()
+                    .to_string_lossy()
+                    .strip_suffix(".conf")
+                    .map(String::from)
+            })
+            .collect()
+    }
+
+    /// `hostname` and `ostype` from a Proxmox CT config
+    fn pve_config(&self, vmid: &str) -> (Option<String>, Option<String>) {
+        let content = fs::read_to_string(self.etc_path.join(format!("pve/lxc/{}.conf", vmid)))
+            .unwrap_or_default();
+        // Snapshots follow in `[name]` sections
+        let current = content.split("\n[").next().unwrap_or_default();
+        let value = |key: &str| {
+            current.lines().find_map(|l| {
+                l.strip_prefix(key)
+                    .and_then(|rest| rest.strip_prefix(':'))
+                    .map(|v| v.trim().to_string())
+            })
+        };
+        (value("hostname"), value("ostype"))
+    }
+
+    fn cgroup_of(&self, name: &str) -> Option<PathBuf> {
+        [
+            self.cgroup_path.join(format!("lxc.payload.{}", name)),
+            self.cgroup_path.join("lxc").join(name),
+        ]
+        .into_iter()
+        .find(|p| p.is_dir())
+    }
+
+    fn stats(&self, name: &str, cgroup: &Path) -> ContainerStats {
+        let read = |file: &str| fs::read_to_string(cgroup.join(file)).unwrap_or_default();
+
+        let cpu_usage_usec = keyed_value(&read("cpu.stat"), "usage_usec");
+        let cpu_percent = cpu_usage_usec.map_or(0.0, |usage| self.cpu_percent(name, usage));
+
+        let memory_used = read("memory.current").trim().parse().unwrap_or(0);
+        let memory_limit = read("memory.max")
+            .trim()
+            .parse()
+            .ok()
+            .or_else(|| self.host_memory_total())
+            .unwrap_or(memory_used);
+
+        let memory_stat = read("memory.stat");
+        let mut memory = MemoryMetrics::new(
+            memory_used,
+            memory_limit,
+            memory_limit.saturating_sub(memory_used),
+        );
+        if let Some(rss) = keyed_value(&memory_stat, "anon") {
+            memory = memory.with_rss(rss);
+        }
+        if let Some(cache) = keyed_value(&memory_stat, "file") {
+            memory = memory.with_cache(cache);
+        }
+        if let Ok(swap) = read("memory.swap.current").trim().parse() {
+            memory = memory.with_swap(swap);
+        }
+
+        let (read_bytes, write_bytes) = parse_io_stat(&read("io.stat"));
+        let network = self
+            .init_pid(cgroup)
+            .and_then(|pid| {
+                fs::read_to_string(self.proc_path.join(pid.to_string()).join("net/dev")).ok()
+            })
+            .map(|dev| parse_net_dev(&dev))
+            .unwrap_or_else(NetworkMetrics::zero);
+
+        ContainerStats {
+            cpu: CpuMetrics::new(cpu_percent, 0.0, 0.0),
+            memory,
+            network,
+            block_io: IoMetrics::new(read_bytes, write_bytes),
+            pids: read("pids.current")
+                .trim()
+                .parse()
+                .ok()
+                .map(|current| ContainerPids::new(current, read("pids.max").trim().parse().ok())),
+        }
+    }
+
+    /// Share of one CPU since the previous read, like Docker's figure
+    fn cpu_percent(&self, name: &str, usage_usec: u64) -> f64 {
+        let now = Instant::now();
+        let previous = self
+            .previous_cpu
+            .lock()
+            .unwrap()
+            .insert(name.to_string(), (usage_usec, now));
+        match previous {
+            Some((last, at)) if usage_usec >= last => {
+                let elapsed = now.duration_since(at).as_micros() as f64;
+                if elapsed > 0.0 {
+                    (usage_usec - last) as f64 / elapsed * 100.0
+                } else {
+                    0.0
+                }
+            }
+            _ => 0.0,
+        }
+    }
+
+    fn host_memory_total(&self) -> Option<u64> {
+        let meminfo = fs::read_to_string(self.proc_path.join("meminfo")).ok()?;
+        keyed_value(&meminfo.replace(':', ""), "MemTotal").map(|kib| kib * 1024)
+    }
+
+    /// A process in the container's network namespace; Proxmox keeps them in
+    /// a nested `ns` cgroup
+    fn init_pid(&self, cgroup: &Path) -> Option<u32> {
+        let first = |dir: &Path| {
+            fs::read_to_string(dir.join("cgroup.procs"))
+                .ok()?
+                .lines()
+                .next()?
+                .trim()
+                .parse()
+                .ok()
+        };
+        first(cgroup)
+            .or_else(|| first(&cgroup.join("ns")))
+            .or_else(|| {
+                fs::read_dir(cgroup.join("ns"))
+                    .ok()?
+                    .flatten()
+                    .find_map(|e| first(&e.path()))
+            })
+    }
+
+    fn container(&self, name: &str, cgroup: Option<&Path>) -> Container {
+        let (hostname, ostype) = self.pve_config(name);
+        let state = match cgroup {
+            Some(dir)
+                if fs::read_to_string(dir.join("cgroup.freeze")).is_ok_and(|f| f.trim() == "1") =>
+            {
+                ContainerState::Paused
+            }
+            Some(_) => ContainerState::Running,
+            None => ContainerState::Stopped,
+        };
+        let created_at = cgroup
+            .and_then(|dir| fs::metadata(dir).ok()?.modified().ok())
+            .map(DateTime::<Utc>::from)
+            .unwrap_or_else(Utc::now);
+
+        let container = Container::new(
+            ContainerId::new(format!("{}{}", ID_PREFIX, name)),
+            hostname.unwrap_or_else(|| name.to_string()),
+            ostype.unwrap_or_else(|| "lxc".to_string()),
+            state,
+            created_at,
+        )
+        .with_runtime(ContainerRuntime::Lxc);
+
+        match cgroup {
+            Some(dir) if state.is_running() => {
+                let stats = self.stats(name, dir);
+                container
+                    .with_metrics(stats.cpu, stats.memory, stats.network, stats.block_io)
+                    .with_pids(stats.pids)
+            }
+            _ => container,
+        }
+    }
+}
+
+#[async_trait]
+impl ContainerSource for LxcCgroupSource {
+    async fn list_containers(
+        &self,
+    ) -> Result<Vec<Container>, Box<dyn std::error::Error + Send + Sync>> {
+        let running = self.running();
+        let mut containers: Vec<Container> = running
+            .iter()
+            .map(|(name, dir)| self.container(name, Some(dir)))
+            .collect();
+        for vmid in self.configured() {
+            if !running.iter().any(|(name, _)| *name == vmid) {
+                containers.push(self.container(&vmid, None));
+            }
+        }
+        Ok(containers)
+    }
+
+    async fn get_container_stats(
+        &self,
+        id: &ContainerId,
+    ) -> Result<ContainerStats, Box<dyn std::error::Error + Send + Sync>> {
+        let name = id.as_str().strip_prefix(ID_PREFIX).unwrap_or(id.as_str());
+        let cgroup = self
+            .cgroup_of(name)
+            .ok_or_else(|| format!("LXC container {} is not running", name))?;
+        Ok(self.stats(name, &cgroup))
+    }
+
+    async fn list_mounts(
+        &self,
+        _id: &ContainerId,
+    ) -> Result<Option<Vec<ContainerMount>>, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(None)
+    }
+}
+
+/// Value of a `key value` line, as in `cpu.stat` and `memory.stat`
+fn keyed_value(content: &str, key: &str) -> Option<u64> {
+    content.lines().find_map(|line| {
+        let mut parts = line.split_whitespace();
+        (parts.next() == Some(key))
+            .then(|| parts.next()?.parse().ok())
+            .flatten()
+    })
+}
+
+/// Sum `rbytes`/`wbytes` over the devices of an `io.stat`
+fn parse_io_stat(content: &str) -> (u64, u64) {
+    let mut totals = (0, 0);
+    for field in content.split_whitespace() {
+        if let Some((key, value)) = field.split_once('=') {
+            let value: u64 = value.parse().unwrap_or(0);
+            match key {
+                "rbytes" => totals.0 += value,
+                "wbytes" => totals.1 += value,
+                _ => {}
+            }
+        }
+    }
+    totals
+}
+
+/// Receive/transmit counters over the non-loopback interfaces of `/proc/<pid>/net/dev`
+fn parse_net_dev(content: &str) -> NetworkMetrics {
+    let mut totals = NetworkMetrics::zero();
+    for line in content.lines().skip(2) {
+        let Some((interface, counters)) = line.split_once(':') else {
+            continue;
+        };
+        if interface.trim() == "lo" {
+            continue;
+        }
+        let fields: Vec<u64> = counters
+            .split_whitespace()
+            .map(|f| f.parse().unwrap_or(0))
+            .collect();
+        // bytes packets errs drop fifo frame compressed multicast, then transmit:
+        // bytes packets errs drop fifo colls carrier compressed
+        if fields.len() >= 14 {
+            totals.rx_bytes += fields[0];
+            totals.rx_errors += fields[2];
+            totals.rx_dropped += fields[3];
+            totals.tx_bytes += fields[8];
+            totals.tx_errors += fields[10];
+            totals.tx_dropped += fields[11];
+            totals.collisions += fields[13];
+        }
+    }
+    totals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_lists_running_and_stopped_cts() {
+        let root = std::env::temp_dir().join(format!("nanomon-lxc-{}", std::process::id()));
+        let cgroup = root.join("sys/fs/cgroup/lxc/101");
+        fs::create_dir_all(cgroup.join("ns")).unwrap();
+        fs::create_dir_all(root.join("etc/pve/lxc")).unwrap();
+        fs::create_dir_all(root.join("proc/4242/net")).unwrap();
+
+        fs::write(cgroup.join("cpu.stat"), "usage_usec 5000\nuser_usec 3000\n").unwrap();
+        fs::write(cgroup.join("memory.current"), "104857600\n").unwrap();
+        fs::write(cgroup.join("memory.max"), "536870912\n").unwrap();
+        fs::write(
+            cgroup.join("memory.stat"),
+            "anon 62914560\nfile 41943040\nkernel 1048576\n",
+        )
+        .unwrap();
+        fs::write(cgroup.join("memory.swap.current"), "4096\n").unwrap();
+        fs::write(cgroup.join("pids.current"), "12\n").unwrap();
+        fs::write(cgroup.join("pids.max"), "max\n").unwrap();
+        fs::write(
+            cgroup.join("io.stat"),
+            "8:0 rbytes=1000 wbytes=200 rios=1 wios=1\n8:16 rbytes=24 wbytes=0\n",
+        )
+        .unwrap();
+        fs::write(cgroup.join("ns/cgroup.procs"), "4242\n").unwrap();
+        fs::write(
+            root.join("proc/4242/net/dev"),
+            "Inter-|   Receive\n face |bytes packets errs\n    lo: 50 1 0 0 0 0 0 0 50 1 0 0 0 0 0 0\n  eth0: 900 9 1 4 0 0 0 0 300 3 2 0 0 0 0 0\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("etc/pve/lxc/101.conf"),
+            "hostname: pihole\nostype: debian\n\n[before-upgrade]\nhostname: old\n",
+        )
+        .unwrap();
+        fs::write(root.join("etc/pve/lxc/102.conf"), "hostname: unifi\n").unwrap();
+
+        let source = LxcCgroupSource::new(root.join("proc"), root.join("sys"), root.join("etc"));
+        let mut containers = source.list_containers().await.unwrap();
+        containers.sort_by(|a, b| a.id.as_str().cmp(b.id.as_str()));
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(containers.len(), 2);
+        let pihole = &containers[0];
+        assert_eq!(pihole.id.as_str(), "lxc-101");
+        assert_eq!(pihole.name, "pihole");
+        assert_eq!(pihole.image, "debian");
+        assert_eq!(pihole.runtime, ContainerRuntime::Lxc);
+        assert_eq!(pihole.state, ContainerState::Running);
+        assert_eq!(pihole.memory.total_bytes, 536870912);
+        assert_eq!(pihole.memory.rss_bytes, Some(62914560));
+        assert_eq!(pihole.memory.cached_bytes, Some(41943040));
+        assert_eq!(pihole.memory.swap_used_bytes, Some(4096));
+        assert_eq!(pihole.block_io.read_bytes, 1024);
+        assert_eq!(pihole.network.rx_bytes, 900);
+        assert_eq!(pihole.network.tx_errors, 2);
+        assert_eq!(pihole.network.rx_dropped, 4);
+        assert_eq!(pihole.pids.unwrap().limit, None);
+
+        assert_eq!(containers[1].name, "unifi");
+        assert_eq!(containers[1].state, ContainerState::Stopped);
+    }
+}
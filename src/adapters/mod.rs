@@ -1,11 +1,51 @@
+pub mod authlog;
+pub mod backup;
+pub mod combined;
 pub mod docker;
+pub mod du;
+pub mod firewall;
+pub mod hypervisor;
+pub mod lxc;
+pub mod notify;
+pub mod oidc;
+pub mod ping;
+pub mod plugin;
+pub mod power;
 pub mod procfs;
+pub mod push;
+pub mod registry;
+pub mod resilience;
+pub mod script;
+pub mod speedtest;
 pub mod store;
 pub mod systemd;
+pub mod textfile;
+pub mod vuln;
 pub mod webhook;
 
+pub use authlog::AuthLogReader;
+pub use backup::FsBackupSource;
+pub use combined::CombinedContainerSource;
 pub use docker::DockerAdapter;
+pub use du::{FsDirectoryScanner, LogReclaimSource};
+pub use firewall::NetfilterFirewall;
+pub use hypervisor::CommandVmSource;
+pub use lxc::LxcCgroupSource;
+pub use notify::ChannelSink;
+pub use oidc::OidcClient;
+pub use ping::PingCommandProber;
+pub use power::WolSshPowerControl;
 pub use procfs::{ProcfsAdapter, ProcfsConfig};
-pub use store::MemoryStore;
+pub use push::HttpSnapshotPublisher;
+pub use registry::CollectorRegistry;
+pub use resilience::{
+    CircuitBreaker, CircuitBreakerContainerSource, RetryingContainerSource, TimeoutSystemSource,
+};
+pub use script::ScriptRunner;
+pub use speedtest::CommandSpeedtestRunner;
+#[cfg(feature = "zstd")]
+pub use store::ZstdCodec;
+pub use store::{ArchiveStore, FileInventoryStore, JsonCodec, MemoryStore};
 pub use systemd::SystemctlAdapter;
+pub use vuln::{CommandVulnerabilitySource, ReportDirVulnerabilitySource, ScannerTool};
 pub use webhook::WebhookSink;
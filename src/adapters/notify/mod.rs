@@ -0,0 +1,228 @@
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+use crate::domain::{AlertEvent, Event};
+use crate::ports::AlertSink;
+
+type NotifyResult = Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+const MAILTO_SCHEME: &str = "mailto:";
+const TELEGRAM_SCHEME: &str = "telegram:";
+
+/// How long sendmail or the Telegram API may take
+const SEND_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// One line per alert
+fn alert_line(event: &AlertEvent) -> String {
+    let mut line = format!(
+        "[{}] {} on {}: {} is {:.1} ({} {})",
+        event.severity.as_str(),
+        event.rule_name,
+        event.hostname,
+        event.metric,
+        event.current_value,
+        event.condition.to_lowercase(),
+        event.threshold
+    );
+    if let Some(step) = event.escalation {
+        line.push_str(&format!(", unacknowledged (escalation {})", step));
+    }
+    if let Some(id) = event.incident_id {
+        line.push_str(&format!(", alert #{}", id));
+    }
+    line
+}
+
+/// Subject and body of a message about `events`
+fn alert_message(events: &[AlertEvent]) -> (String, String) {
+    let lines: Vec<String> = events.iter().map(alert_line).collect();
+    match events {
+        [event] => (
+            format!("[NanoMon] {} on {}", event.rule_name, event.hostname),
+            lines.join("\n"),
+        ),
+        _ => (
+            format!(
+                "[NanoMon] {} alerts on {}",
+                events.len(),
+                events.first().map_or("", |e| e.hostname.as_str())
+            ),
+            format!(
+                "{}\n\n{}",
+                AlertEvent::digest_summary(events),
+                lines.join("\n")
+            ),
+        ),
+    }
+}
+
+/// Sends notifications addressed to `mailto:<address>` through sendmail and
+/// to `telegram:<chat id>` through the Telegram Bot API, and everything else
+/// to the wrapped sink
+pub struct ChannelSink {
+    inner: Arc<dyn AlertSink>,
+    sendmail: PathBuf,
+    telegram_token: Option<String>,
+    #[cfg(feature = "webhook")]
+    client: reqwest::Client,
+}
+
+impl ChannelSink {
+    pub fn new(inner: Arc<dyn AlertSink>) -> Self {
+        Self {
+            inner,
+            sendmail: PathBuf::from("/usr/sbin/sendmail"),
+            telegram_token: None,
+            #[cfg(feature = "webhook")]
+            client: reqwest::Client::builder()
+                .timeout(SEND_TIMEOUT)
+                .build()
+                .expect("Failed to build HTTP client"),
+        }
+    }
+
+    /// The sendmail-compatible program `mailto:` messages are piped to
+    pub fn with_sendmail(mut self, sendmail: PathBuf) -> Self {
+        self.sendmail = sendmail;
+        self
+    }
+
+    pub fn with_telegram_token(mut self, token: Option<String>) -> Self {
+        self.telegram_token = token;
+        self
+    }
+
+    /// Sends through a channel of its own, `None` when `url` is not one
+    async fn send(&self, url: &str, subject: &str, body: &str) -> Option<NotifyResult> {
+        if let Some(address) = url.strip_prefix(MAILTO_SCHEME) {
+            return Some(self.mail(address, subject, body).await);
+        }
+        if let Some(chat_id) = url.strip_prefix(TELEGRAM_SCHEME) {
+            return Some(
+                self.telegram(chat_id, &format!("{}\n{}", subject, body))
+                    .await,
+            );
+        }
+        None
+    }
+
+    async fn mail(&self, address: &str, subject: &str, body: &str) -> NotifyResult {
+        if address.is_empty() || address.contains(['\r', '\n', ',']) {
+            return Err(format!("invalid mail address '{}'", address.escape_debug()).into());
+        }
+        let mut child = Command::new(&self.sendmail)
+            .args(["-t", "-i"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| format!("cannot run {}: {}", self.sendmail.display(), e))?;
+
+        let message = format!(
+            "To: {}\r\nSubject: {}\r\nContent-Type: text/plain; charset=utf-8\r\n\r\n{}\r\n",
+            address,
+            subject.replace(['\r', '\n'], " "),
+            body
+        );
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(message.as_bytes()).await?;
+        }
+        let status = tokio::time::timeout(SEND_TIMEOUT, child.wait())
+            .await
+            .map_err(|_| format!("{} timed out", self.sendmail.display()))??;
+        if !status.success() {
+            return Err(format!("{} exited with {}", self.sendmail.display(), status).into());
+        }
+        tracing::info!("Notification mailed to {}", address);
+        Ok(())
+    }
+
+    #[cfg(feature = "webhook")]
+    async fn telegram(&self, chat_id: &str, text: &str) -> NotifyResult {
+        let token = self
+            .telegram_token
+            .as_deref()
+            .ok_or("NANOMON_TELEGRAM_BOT_TOKEN is not set")?;
+        // Errors carry the URL, which holds the token
+        let response = self
+            .client
+            .post(format!("https://api.telegram.org/bot{}/sendMessage", token))
+            .json(&serde_json::json!({ "chat_id": chat_id, "text": text }))
+            .send()
+            .await
+            .map_err(|e| e.without_url())?;
+        if !response.status().is_success() {
+            return Err(format!("Telegram answered {}", response.status()).into());
+        }
+        Ok(())
+    }
+
+    #[cfg(not(feature = "webhook"))]
+    async fn telegram(&self, _chat_id: &str, _text: &str) -> NotifyResult {
+        Err("built without the webhook feature".into())
+    }
+}
+
+#[async_trait]
+impl AlertSink for ChannelSink {
+    async fn send_alert(&self, url: &str, event: &AlertEvent) -> NotifyResult {
+        self.send_alerts(url, std::slice::from_ref(event)).await
+    }
+
+    async fn send_alerts(&self, url: &str, events: &[AlertEvent]) -> NotifyResult {
+        let (subject, body) = alert_message(events);
+        match self.send(url, &subject, &body).await {
+            Some(result) => result,
+            None => match events {
+                [event] => self.inner.send_alert(url, event).await,
+                _ => self.inner.send_alerts(url, events).await,
+            },
+        }
+    }
+
+    async fn send_event(&self, url: &str, event: &Event, secret: Option<&str>) -> NotifyResult {
+        match self.send(url, "[NanoMon] Event", &event.message).await {
+            Some(result) => result,
+            None => self.inner.send_event(url, event, secret).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::Severity;
+
+    #[test]
+    fn test_grouped_alerts_read_as_one_message() {
+        let event = |rule: &str, value: f64| AlertEvent {
+            rule_name: rule.to_string(),
+            severity: Severity::Critical,
+            incident_id: None,
+            escalation: None,
+            metric: "DiskUsage".to_string(),
+            current_value: value,
+            threshold: 90.0,
+            condition: "Above".to_string(),
+            hostname: "nas".to_string(),
+            timestamp: String::new(),
+        };
+
+        let (subject, body) = alert_message(&[event("Disk full", 97.26)]);
+        assert_eq!(subject, "[NanoMon] Disk full on nas");
+        assert_eq!(
+            body,
+            "[critical] Disk full on nas: DiskUsage is 97.3 (above 90)"
+        );
+
+        let (subject, body) = alert_message(&[event("Disk full", 97.0), event("Pool", 91.0)]);
+        assert_eq!(subject, "[NanoMon] 2 alerts on nas");
+        assert_eq!(body.lines().count(), 4);
+    }
+}
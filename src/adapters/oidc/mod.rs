@@ -0,0 +1,269 @@
+#[cfg(feature = "oidc")]
+use std::time::Duration;
+
+use async_trait::async_trait;
+#[cfg(feature = "oidc")]
+use serde::Deserialize;
+#[cfg(feature = "oidc")]
+use tokio::sync::OnceCell;
+
+use crate::ports::IdentityProvider;
+
+/// Scopes asked for, enough for a user name
+#[cfg(feature = "oidc")]
+const SCOPES: &str = "openid profile email";
+
+/// Endpoints from the issuer's `.well-known/openid-configuration`
+#[cfg(feature = "oidc")]
+#[derive(Debug, Deserialize)]
+struct Discovery {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+}
+
+#[cfg(feature = "oidc")]
+#[derive(Deserialize)]
+struct TokenResponse {
+    id_token: String,
+}
+
+#[cfg(feature = "oidc")]
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Audience {
+    One(String),
+    Many(Vec<String>),
+}
+
+/// The ID token claims NanoMon looks at
+#[cfg(feature = "oidc")]
+#[derive(Deserialize)]
+struct Claims {
+    iss: String,
+    aud: Audience,
+    exp: i64,
+    #[serde(default)]
+    nonce: Option<String>,
+    sub: String,
+    #[serde(default)]
+    preferred_username: Option<String>,
+    #[serde(default)]
+    email: Option<String>,
+}
+
+#[cfg(feature = "oidc")]
+impl Claims {
+    /// The payload of a compact JWT
+    fn decode(id_token: &str) -> Result<Self, String> {
+        use base64::Engine;
+
+        let payload = id_token.split('.').nth(1).ok_or("ID token is not a JWT")?;
+        let json = base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .decode(payload.trim_end_matches('='))
+            .map_err(|e| format!("ID token payload: {}", e))?;
+        serde_json::from_slice(&json).map_err(|e| format!("ID token claims: {}", e))
+    }
+
+    /// The user name, once the token is checked to come from `issuer` for
+    /// `client_id`, to be unexpired at `now` and to belong to this login
+    fn user(self, issuer: &str, client_id: &str, nonce: &str, now: i64) -> Result<String, String> {
+        if self.iss != issuer {
+            return Err(format!(
+                "ID token issued by '{}', not '{}'",
+                self.iss, issuer
+            ));
+        }
+        let for_us = match &self.aud {
+            Audience::One(aud) => aud == client_id,
+            Audience::Many(auds) => auds.iter().any(|a| a == client_id),
+        };
+        if !for_us {
+            return Err("ID token is for another client".to_string());
+        }
+        if self.exp <= now {
+            return Err("ID token has expired".to_string());
+        }
+        if self.nonce.as_deref() != Some(nonce) {
+            return Err("ID token does not belong to this login".to_string());
+        }
+        Ok(self.preferred_username.or(self.email).unwrap_or(self.sub))
+    }
+}
+
+/// OpenID Connect authorization code flow against an issuer such as
+/// Authelia, Keycloak or authentik
+pub struct OidcClient {
+    #[cfg(feature = "oidc")]
+    client: reqwest::Client,
+    issuer: String,
+    #[cfg_attr(not(feature = "oidc"), allow(dead_code))]
+    client_id: String,
+    #[cfg_attr(not(feature = "oidc"), allow(dead_code))]
+    client_secret: String,
+    #[cfg_attr(not(feature = "oidc"), allow(dead_code))]
+    redirect_url: String,
+    /// Fetched on the first login, so a provider that is down at startup
+    /// only delays logins
+    #[cfg(feature = "oidc")]
+    discovery: OnceCell<Discovery>,
+}
+
+impl OidcClient {
+    pub fn new(
+        issuer: String,
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Self {
+        Self {
+            #[cfg(feature = "oidc")]
+            client: reqwest::Client::builder()
+                .timeout(Duration::from_secs(10))
+                .build()
+                .expect("Failed to build HTTP client"),
+            issuer: issuer.trim_end_matches('/').to_string(),
+            client_id,
+            client_secret,
+            redirect_url,
+            #[cfg(feature = "oidc")]
+            discovery: OnceCell::new(),
+        }
+    }
+
+    #[cfg(feature = "oidc")]
+    async fn discovery(&self) -> Result<&Discovery, Box<dyn std::error::Error + Send + Sync>> {
+        self.discovery
+            .get_or_try_init(|| async {
+                let url = format!("{}/.well-known/openid-configuration", self.issuer);
+                let response = self.client.get(&url).send().await?;
+                if !response.status().is_success() {
+                    return Err(format!("{} returned status {}", url, response.status()).into());
+                }
+                Ok(response.json().await?)
+            })
+            .await
+    }
+}
+
+#[async_trait]
+impl IdentityProvider for OidcClient {
+    #[cfg(feature = "oidc")]
+    async fn authorization_url(
+        &self,
+        state: &str,
+        nonce: &str,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let discovery = self.discovery().await?;
+        let url = reqwest::Url::parse_with_params(
+            &discovery.authorization_endpoint,
+            [
+                ("response_type", "code"),
+                ("client_id", &self.client_id),
+                ("redirect_uri", &self.redirect_url),
+                ("scope", SCOPES),
+                ("state", state),
+                ("nonce", nonce),
+            ],
+        )?;
+        Ok(url.into())
+    }
+
+    #[cfg(feature = "oidc")]
+    async fn exchange(
+        &self,
+        code: &str,
+        nonce: &str,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let discovery = self.discovery().await?;
+        let response = self
+            .client
+            .post(&discovery.token_endpoint)
+            .basic_auth(&self.client_id, Some(&self.client_secret))
+            .form(&[
+                ("grant_type", "authorization_code"),
+                ("code", code),
+                ("redirect_uri", &self.redirect_url),
+            ])
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(format!("token endpoint returned status {}", response.status()).into());
+        }
+        let TokenResponse { id_token } = response.json().await?;
+        // Received straight from the token endpoint rather than through the
+        // browser, so its signature need not be checked (OIDC Core 3.1.3.7)
+        let now = chrono::Utc::now().timestamp();
+        Ok(Claims::decode(&id_token)?.user(&discovery.issuer, &self.client_id, nonce, now)?)
+    }
+
+    #[cfg(not(feature = "oidc"))]
+    async fn authorization_url(
+        &self,
+        _state: &str,
+        _nonce: &str,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        Err(format!(
+            "built without the oidc feature, cannot log in with {}",
+            self.issuer
+        )
+        .into())
+    }
+
+    #[cfg(not(feature = "oidc"))]
+    async fn exchange(
+        &self,
+        _code: &str,
+        _nonce: &str,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        Err("built without the oidc feature".into())
+    }
+}
+
+#[cfg(all(test, feature = "oidc"))]
+mod tests {
+    use super::*;
+    use base64::Engine;
+
+    #[test]
+    fn test_id_token_claims_are_checked() {
+        let token = |claims: serde_json::Value| {
+            let payload = base64::engine::general_purpose::URL_SAFE_NO_PAD
+                .encode(serde_json::to_vec(&claims).unwrap());
+            format!("eyJhbGciOiJSUzI1NiJ9.{}.c2ln", payload)
+        };
+        let claims = serde_json::json!({
+            "iss": "https://auth.example.com",
+            "aud": ["nanomon", "other"],
+            "exp": 2000,
+            "nonce": "n0nce",
+            "sub": "3f2a",
+            "preferred_username": "alice",
+        });
+        let user = |claims: &serde_json::Value, nonce: &str, now: i64| {
+            Claims::decode(&token(claims.clone())).unwrap().user(
+                "https://auth.example.com",
+                "nanomon",
+                nonce,
+                now,
+            )
+        };
+
+        assert_eq!(user(&claims, "n0nce", 1000).unwrap(), "alice");
+        assert!(user(&claims, "n0nce", 2000)
+            .unwrap_err()
+            .contains("expired"));
+        assert!(user(&claims, "replayed", 1000).is_err());
+
+        let mut other_client = claims.clone();
+        other_client["aud"] = "grafana".into();
+        assert!(user(&other_client, "n0nce", 1000).is_err());
+
+        let mut anonymous = claims.clone();
+        anonymous
+            .as_object_mut()
+            .unwrap()
+            .remove("preferred_username");
+        assert_eq!(user(&anonymous, "n0nce", 1000).unwrap(), "3f2a");
+    }
+}
@@ -0,0 +1,124 @@
+use async_trait::async_trait;
+use chrono::Utc;
+
+use crate::domain::PingSample;
+use crate::ports::PingProber;
+
+/// Prober that shells out to `ping` (iputils).
+/// Raw ICMP sockets need CAP_NET_RAW; `ping` already handles that.
+pub struct PingCommandProber;
+
+impl Default for PingCommandProber {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PingCommandProber {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[async_trait]
+impl PingProber for PingCommandProber {
+    async fn probe(
+        &self,
+        host: &str,
+        count: u32,
+    ) -> Result<PingSample, Box<dyn std::error::Error + Send + Sync>> {
+        let output = tokio::process::Command::new("ping")
+            .args([
+                "-n",
+                "-q",
+                "-i",
+                "0.2",
+                "-W",
+                "2",
+                "-c",
+                &count.to_string(),
+                host,
+            ])
+            .output()
+            .await?;
+
+        // ping exits non-zero on packet loss, the summary is still on stdout
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        match parse_ping_summary(&stdout) {
+            Some(sample) => Ok(sample),
+            None if output.status.code() == Some(2) => Ok(PingSample::unreachable(count)),
+            None => Err(format!(
+                "unexpected ping output: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )
+            .into()),
+        }
+    }
+}
+
+/// Parse the summary lines of iputils/busybox `ping -q`
+fn parse_ping_summary(output: &str) -> Option<PingSample> {
+    let mut sample: Option<PingSample> = None;
+
+    for line in output.lines() {
+        if line.contains("packets transmitted") {
+            let mut numbers = line
+                .split(',')
+                .filter_map(|part| part.split_whitespace().next()?.parse::<u32>().ok());
+            let sent = numbers.next()?;
+            let received = numbers.next()?;
+            sample = Some(PingSample {
+                timestamp: Utc::now(),
+                sent,
+                received,
+                min_ms: None,
+                avg_ms: None,
+                max_ms: None,
+            });
+        } else if line.starts_with("rtt") || line.starts_with("round-trip") {
+            // rtt min/avg/max/mdev = 9.812/10.021/10.330/0.214 ms
+            let values: Vec<f64> = line
+                .split('=')
+                .nth(1)?
+                .trim()
+                .trim_end_matches("ms")
+                .split('/')
+                .filter_map(|v| v.trim().parse().ok())
+                .collect();
+            if let (Some(s), [min, avg, max, ..]) = (sample.as_mut(), values.as_slice()) {
+                s.min_ms = Some(*min);
+                s.avg_ms = Some(*avg);
+                s.max_ms = Some(*max);
+            }
+        }
+    }
+
+    sample
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ping_summary() {
+        let output = "PING 1.1.1.1 (1.1.1.1) 56(84) bytes of data.\n\n\
+            --- 1.1.1.1 ping statistics ---\n\
+            5 packets transmitted, 4 received, 20% packet loss, time 803ms\n\
+            rtt min/avg/max/mdev = 9.812/10.021/10.330/0.214 ms\n";
+        let sample = parse_ping_summary(output).unwrap();
+        assert_eq!(sample.sent, 5);
+        assert_eq!(sample.received, 4);
+        assert_eq!(sample.avg_ms, Some(10.021));
+        assert_eq!(sample.loss_percent(), 20.0);
+    }
+
+    #[test]
+    fn test_parse_ping_summary_total_loss() {
+        let output = "--- 10.0.0.99 ping statistics ---\n\
+            3 packets transmitted, 0 received, 100% packet loss, time 2040ms\n";
+        let sample = parse_ping_summary(output).unwrap();
+        assert_eq!(sample.received, 0);
+        assert!(sample.avg_ms.is_none());
+    }
+}
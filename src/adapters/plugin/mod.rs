@@ -0,0 +1,289 @@
+//! Out-of-process plugins speaking JSON-RPC 2.0 over stdin/stdout, one message per line.
+//!
+//! NanoMon starts the plugin on first use and keeps it running. Methods it calls:
+//! - `initialize` `{protocol_version, name}`: must answer `{"protocol_version": 1}`
+//! - `collect` `{collector}`: answers metrics in the script collector format
+//! - `notify` `{alert}` or `{event}`: answers anything, errors are logged
+//!
+//! A plugin that fails, times out or exits is restarted on the next call.
+//! Lines on stdout that are not responses are ignored; stderr goes to NanoMon's.
+
+use std::collections::HashMap;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+use tokio::sync::Mutex;
+
+use crate::domain::{
+    AlertEvent, CustomMetric, Event, PluginConfig, PLUGIN_PROTOCOL_VERSION, PLUGIN_URL_SCHEME,
+};
+use crate::ports::{AlertSink, MetricCollector};
+
+use super::script::parse_metrics;
+
+type PluginResult<T> = Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+/// A running plugin; dropping it kills the process
+struct Connection {
+    _child: Child,
+    stdin: ChildStdin,
+    stdout: Lines<BufReader<ChildStdout>>,
+}
+
+/// One configured plugin, started lazily and shared by its collectors and notifications
+pub struct PluginProcess {
+    config: PluginConfig,
+    connection: Mutex<Option<Connection>>,
+    next_id: AtomicU64,
+}
+
+impl PluginProcess {
+    pub fn new(config: PluginConfig) -> Self {
+        Self {
+            config,
+            connection: Mutex::new(None),
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    /// Call `method`, starting the plugin first if needed. Calls are serialized.
+    pub async fn call(&self, method: &str, params: Value) -> PluginResult<Value> {
+        let timeout = Duration::from_secs(self.config.timeout_seconds);
+        let mut connection = self.connection.lock().await;
+        let result = tokio::time::timeout(timeout, self.call_on(&mut connection, method, params))
+            .await
+            .unwrap_or_else(|_| Err(format!("timed out after {:?}", timeout).into()));
+
+        if result.is_err() {
+            // Restart from a clean state next time rather than read a stale reply
+            *connection = None;
+        }
+        result.map_err(|e| format!("plugin {}: {}", self.config.name, e).into())
+    }
+
+    async fn call_on(
+        &self,
+        connection: &mut Option<Connection>,
+        method: &str,
+        params: Value,
+    ) -> PluginResult<Value> {
+        let connection = match connection {
+            Some(connection) => connection,
+            None => connection.insert(self.start().await?),
+        };
+        self.exchange(connection, method, params).await
+    }
+
+    async fn start(&self) -> PluginResult<Connection> {
+        let (program, args) = self
+            .config
+            .command
+            .split_first()
+            .ok_or("plugin command is empty")?;
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .kill_on_drop(true)
+            .spawn()?;
+        let mut connection = Connection {
+            stdin: child.stdin.take().ok_or("no stdin")?,
+            stdout: BufReader::new(child.stdout.take().ok_or("no stdout")?).lines(),
+            _child: child,
+        };
+
+        let reply = self
+            .exchange(
+                &mut connection,
+                "initialize",
+                json!({ "protocol_version": PLUGIN_PROTOCOL_VERSION, "name": self.config.name }),
+            )
+            .await?;
+        match reply["protocol_version"].as_u64() {
+            Some(PLUGIN_PROTOCOL_VERSION) => {
+                tracing::info!("Plugin {} started", self.config.name);
+                Ok(connection)
+            }
+            other => Err(format!(
+                "speaks protocol version {:?}, expected {}",
+                other, PLUGIN_PROTOCOL_VERSION
+            )
+            .into()),
+        }
+    }
+
+    async fn exchange(
+        &self,
+        connection: &mut Connection,
+        method: &str,
+        params: Value,
+    ) -> PluginResult<Value> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let request = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+        let mut line = serde_json::to_vec(&request)?;
+        line.push(b'\n');
+        connection.stdin.write_all(&line).await?;
+        connection.stdin.flush().await?;
+
+        loop {
+            let line = connection.stdout.next_line().await?.ok_or("exited")?;
+            let Ok(response) = serde_json::from_str::<Value>(&line) else {
+                continue;
+            };
+            if response["id"].as_u64() != Some(id) {
+                continue;
+            }
+            if let Some(error) = response.get("error") {
+                let message = error["message"].as_str().unwrap_or("unknown error");
+                return Err(format!("{} failed: {}", method, message).into());
+            }
+            return Ok(response.get("result").cloned().unwrap_or(Value::Null));
+        }
+    }
+}
+
+/// A `[[collectors]]` entry of type `plugin`
+pub struct PluginCollector {
+    name: String,
+    plugin: Arc<PluginProcess>,
+}
+
+impl PluginCollector {
+    pub fn new(name: String, plugin: Arc<PluginProcess>) -> Self {
+        Self { name, plugin }
+    }
+}
+
+#[async_trait]
+impl MetricCollector for PluginCollector {
+    async fn collect(&self) -> PluginResult<Vec<CustomMetric>> {
+        let reply = self
+            .plugin
+            .call("collect", json!({ "collector": self.name }))
+            .await?;
+        parse_metrics(&self.name, reply)
+    }
+}
+
+/// Sends notifications addressed to `plugin://<name>` to that plugin and
+/// everything else to the wrapped sink
+pub struct PluginAlertSink {
+    inner: Arc<dyn AlertSink>,
+    plugins: HashMap<String, Arc<PluginProcess>>,
+}
+
+impl PluginAlertSink {
+    pub fn new(inner: Arc<dyn AlertSink>, plugins: HashMap<String, Arc<PluginProcess>>) -> Self {
+        Self { inner, plugins }
+    }
+
+    fn plugin(&self, url: &str) -> Option<PluginResult<&PluginProcess>> {
+        let name = url.strip_prefix(PLUGIN_URL_SCHEME)?;
+        Some(
+            self.plugins
+                .get(name)
+                .map(Arc::as_ref)
+                .ok_or_else(|| format!("no plugin named {}", name).into()),
+        )
+    }
+}
+
+#[async_trait]
+impl AlertSink for PluginAlertSink {
+    async fn send_alert(&self, url: &str, event: &AlertEvent) -> PluginResult<()> {
+        match self.plugin(url) {
+            Some(plugin) => plugin?
+                .call("notify", json!({ "alert": event }))
+                .await
+                .map(|_| ()),
+            None => self.inner.send_alert(url, event).await,
+        }
+    }
+
+    async fn send_alerts(&self, url: &str, events: &[AlertEvent]) -> PluginResult<()> {
+        match self.plugin(url) {
+            Some(plugin) => {
+                let plugin = plugin?;
+                for event in events {
+                    plugin.call("notify", json!({ "alert": event })).await?;
+                }
+                Ok(())
+            }
+            None => self.inner.send_alerts(url, events).await,
+        }
+    }
+
+    async fn send_event(&self, url: &str, event: &Event, secret: Option<&str>) -> PluginResult<()> {
+        match self.plugin(url) {
+            Some(plugin) => plugin?
+                .call("notify", json!({ "event": event }))
+                .await
+                .map(|_| ()),
+            None => self.inner.send_event(url, event, secret).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A minimal plugin in shell: requests are serialized with sorted keys, so
+    /// the id directly follows the opening brace
+    const ECHO_PLUGIN: &str = r#"
+        while read -r line; do
+            id=$(echo "$line" | sed 's/^{"id":\([0-9]*\).*/\1/')
+            case "$line" in
+                *'"initialize"'*) echo "{\"jsonrpc\":\"2.0\",\"id\":$id,\"result\":{\"protocol_version\":1}}" ;;
+                *'"collect"'*) echo "not json"; echo "{\"jsonrpc\":\"2.0\",\"id\":$id,\"result\":[{\"name\":\"pool_free\",\"value\":42}]}" ;;
+                *) echo "{\"jsonrpc\":\"2.0\",\"id\":$id,\"error\":{\"code\":-32601,\"message\":\"no such method\"}}" ;;
+            esac
+        done
+    "#;
+
+    fn plugin(script: &str) -> Arc<PluginProcess> {
+        Arc::new(PluginProcess::new(PluginConfig {
+            name: "zfs".to_string(),
+            command: vec!["sh".to_string(), "-c".to_string(), script.to_string()],
+            timeout_seconds: 5,
+        }))
+    }
+
+    #[tokio::test]
+    async fn test_collect_and_errors() {
+        let plugin = plugin(ECHO_PLUGIN);
+        let metrics = PluginCollector::new("zfs".to_string(), plugin.clone())
+            .collect()
+            .await
+            .unwrap();
+        assert_eq!(
+            (metrics[0].name.as_str(), metrics[0].value),
+            ("pool_free", 42.0)
+        );
+
+        let error = plugin.call("frobnicate", Value::Null).await.unwrap_err();
+        assert!(error.to_string().contains("no such method"));
+        // Restarted after the error
+        let metrics = PluginCollector::new("zfs".to_string(), plugin)
+            .collect()
+            .await
+            .unwrap();
+        assert_eq!(metrics.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_other_protocol_version() {
+        let plugin = plugin(
+            r#"read -r line; echo "{\"jsonrpc\":\"2.0\",\"id\":1,\"result\":{\"protocol_version\":2}}"; sleep 5"#,
+        );
+        let error = plugin.call("collect", Value::Null).await.unwrap_err();
+        assert!(error.to_string().contains("protocol version"));
+    }
+}
@@ -0,0 +1,135 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use tokio::net::UdpSocket;
+use tokio::process::Command;
+
+use crate::domain::{magic_packet, parse_mac, PowerTarget};
+use crate::ports::PowerControl;
+
+/// Longest a shutdown may take, reachability check included
+const SSH_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// What ssh prints when it never got a session; exit 255 with one of these
+/// is a failure, not the host dropping the connection as it goes down
+const CONNECTION_ERRORS: &[&str] = &[
+    "Connection refused",
+    "Connection timed out",
+    "No route to host",
+    "Network is unreachable",
+    "Could not resolve hostname",
+    "Permission denied",
+    "Host key verification failed",
+];
+
+fn connection_failed(stderr: &str) -> bool {
+    CONNECTION_ERRORS.iter().any(|error| stderr.contains(error))
+}
+
+/// `ssh` running `command` on `destination`, non-interactive; a host that
+/// stops answering ends the session after about 10s
+fn ssh(destination: &str, command: &str) -> Command {
+    let mut ssh = Command::new("ssh");
+    ssh.args([
+        "-o",
+        "BatchMode=yes",
+        "-o",
+        "ConnectTimeout=10",
+        "-o",
+        "ServerAliveInterval=5",
+        "-o",
+        "ServerAliveCountMax=2",
+        destination,
+        command,
+    ])
+    .kill_on_drop(true);
+    ssh
+}
+
+/// Wakes machines with a UDP magic packet and shuts them down over `ssh`.
+/// Shelling out keeps an SSH library out of the binary.
+pub struct WolSshPowerControl;
+
+impl Default for WolSshPowerControl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WolSshPowerControl {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[async_trait]
+impl PowerControl for WolSshPowerControl {
+    async fn wake(
+        &self,
+        target: &PowerTarget,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mac = target.mac.as_deref().ok_or("no MAC address configured")?;
+        let mac = parse_mac(mac).ok_or_else(|| format!("invalid MAC address '{}'", mac))?;
+
+        let socket = UdpSocket::bind("0.0.0.0:0").await?;
+        socket.set_broadcast(true)?;
+        socket
+            .send_to(&magic_packet(mac), (target.broadcast.as_str(), 9))
+            .await?;
+
+        Ok(())
+    }
+
+    async fn shutdown(
+        &self,
+        target: &PowerTarget,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let destination = target
+            .ssh
+            .as_deref()
+            .ok_or("no SSH destination configured")?;
+
+        tokio::time::timeout(SSH_TIMEOUT, async {
+            // Only a host known to accept the session may answer the
+            // shutdown with a dropped connection
+            let check = ssh(destination, "true").output().await?;
+            if !check.status.success() {
+                return Err(format!(
+                    "ssh to {} failed: {}",
+                    destination,
+                    String::from_utf8_lossy(&check.stderr).trim()
+                )
+                .into());
+            }
+
+            let output = ssh(destination, &target.shutdown_command).output().await?;
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            // The connection usually drops while the host goes down (exit 255)
+            let dropped = output.status.code() == Some(255) && !connection_failed(&stderr);
+            if !output.status.success() && !dropped {
+                return Err(format!("ssh exited with {}: {}", output.status, stderr.trim()).into());
+            }
+            Ok(())
+        })
+        .await
+        .map_err(|_| format!("ssh to {} timed out after {:?}", destination, SSH_TIMEOUT))?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_connection_failures_are_not_drops() {
+        assert!(connection_failed(
+            "ssh: connect to host nas port 22: Connection refused"
+        ));
+        assert!(connection_failed(
+            "root@nas: Permission denied (publickey)."
+        ));
+        assert!(!connection_failed(
+            "Connection to nas closed by remote host."
+        ));
+    }
+}
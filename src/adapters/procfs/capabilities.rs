@@ -0,0 +1,66 @@
+use std::fs;
+
+use async_trait::async_trait;
+
+use crate::domain::Capability;
+use crate::ports::CapabilityProbe;
+
+use super::ProcfsAdapter;
+
+/// Fewer visible PIDs than this means NanoMon only sees its own PID namespace
+const MIN_HOST_PIDS: usize = 10;
+
+#[async_trait]
+impl CapabilityProbe for ProcfsAdapter {
+    async fn probe(&self) -> Vec<(&'static str, Capability)> {
+        let proc = &self.config.proc_path;
+        let sys = &self.config.sys_path;
+
+        let host_metrics = if ["stat", "meminfo", "loadavg"]
+            .iter()
+            .all(|f| fs::metadata(proc.join(f)).is_ok())
+        {
+            Capability::available()
+        } else {
+            Capability::unavailable(format!("{} is not a readable procfs", proc.display()))
+        };
+
+        let pids: Vec<String> = fs::read_dir(proc)
+            .map(|dir| {
+                dir.flatten()
+                    .map(|e| e.file_name().to_string_lossy().into_owned())
+                    .filter(|name| name.parse::<u32>().is_ok())
+                    .collect()
+            })
+            .unwrap_or_default();
+        // hidepid= leaves other users' /proc/<pid> entries listed but unreadable
+        let readable = pids
+            .iter()
+            .filter(|pid| fs::metadata(proc.join(pid).join("stat")).is_ok())
+            .count();
+        let processes = if pids.len() < MIN_HOST_PIDS {
+            Capability::unavailable("host PID namespace not shared (`pid: host`)")
+        } else if readable * 2 < pids.len() {
+            Capability::unavailable(format!(
+                "only {} of {} processes readable (procfs mounted with hidepid?)",
+                readable,
+                pids.len()
+            ))
+        } else {
+            Capability::available()
+        };
+
+        let temperatures = if sys.join("class/thermal").is_dir() || sys.join("class/hwmon").is_dir()
+        {
+            Capability::available()
+        } else {
+            Capability::unavailable("no thermal zones or hwmon sensors exposed")
+        };
+
+        vec![
+            ("host_metrics", host_metrics),
+            ("processes", processes),
+            ("temperatures", temperatures),
+        ]
+    }
+}
@@ -0,0 +1,62 @@
+use std::fs;
+use std::io;
+
+use async_trait::async_trait;
+
+use crate::ports::ProcessControl;
+
+use super::ProcfsConfig;
+
+/// Renices processes with `setpriority(2)`
+pub struct ProcfsProcessControl {
+    config: ProcfsConfig,
+}
+
+impl ProcfsProcessControl {
+    pub fn new(config: ProcfsConfig) -> Self {
+        Self { config }
+    }
+
+    /// PIDs in the process list only name the same processes for `setpriority`
+    /// when the procfs NanoMon reads belongs to its own PID namespace, in which
+    /// case its `self` link resolves to NanoMon's PID
+    fn shares_pid_namespace(&self) -> bool {
+        fs::read_link(self.config.proc_path.join("self"))
+            .ok()
+            .and_then(|target| target.to_str()?.parse::<u32>().ok())
+            == Some(std::process::id())
+    }
+}
+
+#[async_trait]
+impl ProcessControl for ProcfsProcessControl {
+    async fn renice(
+        &self,
+        pid: u32,
+        nice: i64,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        if !self.shares_pid_namespace() {
+            return Err(format!(
+                "{} belongs to another PID namespace (run NanoMon with pid: host)",
+                self.config.proc_path.display()
+            )
+            .into());
+        }
+
+        // SAFETY: setpriority takes plain integers and touches no memory of ours
+        let result = unsafe {
+            libc::setpriority(libc::PRIO_PROCESS, pid as libc::id_t, nice as libc::c_int)
+        };
+        if result == 0 {
+            return Ok(());
+        }
+
+        let error = io::Error::last_os_error();
+        if error.raw_os_error() == Some(libc::ESRCH) {
+            return Err(
+                io::Error::new(io::ErrorKind::NotFound, format!("no process {}", pid)).into(),
+            );
+        }
+        Err(error.into())
+    }
+}
@@ -0,0 +1,99 @@
+//! Golden-file tests that run the procfs adapters against captured host trees
+//! under `tests/fixtures/procfs/<scenario>/{proc,sys,etc}`.
+//!
+//! Each scenario's parsed output is compared with its `expected.json`. After an
+//! intended parser change, regenerate them with
+//! `NANOMON_UPDATE_FIXTURES=1 cargo test fixtures` and review the diff.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde_json::{json, Value};
+
+use crate::ports::{ProcessSource, SystemSource};
+
+use super::{ProcfsAdapter, ProcfsConfig};
+
+fn fixture_root(scenario: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/procfs")
+        .join(scenario)
+}
+
+/// Everything the adapters derive from the tree; disks are left out because
+/// they come from `statvfs` on the real mount points
+async fn collect(root: &Path) -> Value {
+    let adapter = ProcfsAdapter::new(
+        ProcfsConfig::new(root.join("proc"), root.join("sys")).with_etc_path(root.join("etc")),
+    );
+    let system = adapter.system_source();
+    let processes = adapter.process_source();
+
+    let host = system.get_host_info().await.unwrap();
+    json!({
+        "host": {
+            "hostname": host.hostname,
+            "uptime_seconds": host.uptime_seconds,
+        },
+        "cpu": system.get_cpu_metrics().await.unwrap(),
+        "memory": system.get_memory_metrics().await.unwrap(),
+        "load": system.get_load_average().await.unwrap(),
+        "network": system.list_network_interfaces().await.unwrap(),
+        "default_route": system.default_route_interface().await.unwrap(),
+        "thermal_throttle_count": system.thermal_throttle_count().await.unwrap(),
+        "connections_by_port": system
+            .connections_by_port()
+            .await
+            .unwrap()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|((protocol, port), count)| (format!("{}/{}", protocol, port), count))
+            .collect::<BTreeMap<_, _>>(),
+        "temperatures": system.get_temperatures().await.unwrap(),
+        "kernel_limits": system.get_kernel_limits().await.unwrap(),
+        "memory_topology": system.get_memory_topology().await.unwrap(),
+        "processes": processes.list_processes().await.unwrap(),
+    })
+}
+
+async fn check(scenario: &str) {
+    let root = fixture_root(scenario);
+    // Compared as text: parsing floats back is not exact without serde_json's
+    // `float_roundtrip` feature
+    let mut actual = serde_json::to_string_pretty(&collect(&root).await).unwrap();
+    actual.push('\n');
+    let expected_path = root.join("expected.json");
+
+    if std::env::var_os("NANOMON_UPDATE_FIXTURES").is_some() {
+        std::fs::write(&expected_path, actual).unwrap();
+        return;
+    }
+
+    let expected = std::fs::read_to_string(&expected_path)
+        .unwrap_or_else(|e| panic!("{}: {}", expected_path.display(), e));
+    assert_eq!(
+        actual, expected,
+        "{} no longer matches; if intended, regenerate with NANOMON_UPDATE_FIXTURES=1",
+        scenario
+    );
+}
+
+#[tokio::test]
+async fn test_x86_nas() {
+    check("x86-nas").await;
+}
+
+#[tokio::test]
+async fn test_container() {
+    check("container").await;
+}
+
+#[tokio::test]
+async fn test_raspberry_pi() {
+    check("raspberry-pi").await;
+}
+
+#[tokio::test]
+async fn test_legacy_kernel() {
+    check("legacy-kernel").await;
+}
@@ -0,0 +1,43 @@
+use std::fs;
+
+use crate::domain::MemoryFootprint;
+use crate::ports::FootprintSource;
+
+use super::ProcfsAdapter;
+
+impl FootprintSource for ProcfsAdapter {
+    fn memory(&self) -> Result<MemoryFootprint, Box<dyn std::error::Error + Send + Sync>> {
+        // Always our own procfs: the configured proc_path may be the host's
+        let status = fs::read_to_string("/proc/self/status")?;
+        parse_self_status(&status).ok_or_else(|| "VmRSS missing from /proc/self/status".into())
+    }
+}
+
+/// `VmRSS` and `VmHWM` (in kB) from /proc/<pid>/status
+fn parse_self_status(content: &str) -> Option<MemoryFootprint> {
+    let field = |name: &str| {
+        content
+            .lines()
+            .find_map(|line| line.strip_prefix(name))
+            .and_then(|rest| rest.split_whitespace().next())
+            .and_then(|kb| kb.parse::<u64>().ok())
+            .map(|kb| kb * 1024)
+    };
+    let rss = field("VmRSS:")?;
+    Some(MemoryFootprint::new(rss, field("VmHWM:").unwrap_or(rss)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_self_status() {
+        let status =
+            "Name:\tnanomon\nVmPeak:\t  120000 kB\nVmHWM:\t   18432 kB\nVmRSS:\t   12288 kB\n";
+        let footprint = parse_self_status(status).unwrap();
+        assert_eq!(footprint.rss_bytes, 12288 * 1024);
+        assert_eq!(footprint.peak_rss_bytes, 18432 * 1024);
+        assert!(parse_self_status("Name:\tkthreadd\n").is_none());
+    }
+}
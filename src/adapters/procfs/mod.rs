@@ -1,9 +1,16 @@
-mod parser;
+mod capabilities;
+mod control;
+#[cfg(test)]
+mod fixtures;
+mod footprint;
+pub mod parser;
 mod process;
+mod reader;
 mod system;
 
 use std::path::PathBuf;
 
+pub use control::ProcfsProcessControl;
 pub use process::ProcfsProcessSource;
 pub use system::ProcfsSystemSource;
 
@@ -12,6 +19,8 @@ pub use system::ProcfsSystemSource;
 pub struct ProcfsConfig {
     pub proc_path: PathBuf,
     pub sys_path: PathBuf,
+    /// Source of `hostname` and `passwd`
+    pub etc_path: PathBuf,
 }
 
 impl ProcfsConfig {
@@ -19,14 +28,17 @@ impl ProcfsConfig {
         Self {
             proc_path: proc_path.into(),
             sys_path: sys_path.into(),
+            etc_path: PathBuf::from("/etc"),
         }
     }
 
+    pub fn with_etc_path(mut self, etc_path: impl Into<PathBuf>) -> Self {
+        self.etc_path = etc_path.into();
+        self
+    }
+
     pub fn host() -> Self {
-        Self {
-            proc_path: PathBuf::from("/proc"),
-            sys_path: PathBuf::from("/sys"),
-        }
+        Self::new("/proc", "/sys")
     }
 }
 
@@ -59,4 +71,8 @@ impl ProcfsAdapter {
     pub fn process_source(&self) -> ProcfsProcessSource {
         ProcfsProcessSource::new(self.config.clone())
     }
+
+    pub fn process_control(&self) -> ProcfsProcessControl {
+        ProcfsProcessControl::new(self.config.clone())
+    }
 }
@@ -1,9 +1,12 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
+use std::net::{Ipv4Addr, Ipv6Addr};
 use std::path::Path;
 
 use thiserror::Error;
 
+use crate::domain::{NetworkMetrics, QueueStats, WirelessLink};
+
 #[derive(Debug, Error)]
 pub enum ParseError {
     #[error("IO error: {0}")]
@@ -67,18 +70,23 @@ pub struct CpuStat {
 
 impl CpuStat {
     pub fn total(&self) -> u64 {
-        self.user
-            + self.nice
-            + self.system
-            + self.idle
-            + self.iowait
-            + self.irq
-            + self.softirq
-            + self.steal
+        [
+            self.nice,
+            self.system,
+            self.idle,
+            self.iowait,
+            self.irq,
+            self.softirq,
+            self.steal,
+        ]
+        .iter()
+        .fold(self.user, |sum, v| sum.saturating_add(*v))
     }
 
     pub fn busy(&self) -> u64 {
-        self.total() - self.idle - self.iowait
+        self.total()
+            .saturating_sub(self.idle)
+            .saturating_sub(self.iowait)
     }
 }
 
@@ -126,7 +134,8 @@ pub fn parse_cpu_stat(content: &str) -> ParseResult<CpuStat> {
     })
 }
 
-/// Parse /proc/meminfo into a map
+/// Parse /proc/meminfo into a map. Sizes in kB are converted to bytes;
+/// unitless counts (`HugePages_Total`) are kept as they are.
 pub fn parse_meminfo(content: &str) -> ParseResult<HashMap<String, u64>> {
     let mut map = HashMap::new();
 
@@ -137,16 +146,34 @@ pub fn parse_meminfo(content: &str) -> ParseResult<HashMap<String, u64>> {
         }
 
         let key = parts[0].trim().to_string();
-        let value_str = parts[1].trim().trim_end_matches(" kB");
+        let value_str = parts[1].trim();
+        let (value_str, unit) = match value_str.strip_suffix(" kB") {
+            Some(kilobytes) => (kilobytes, 1024),
+            None => (value_str, 1),
+        };
 
         if let Ok(value) = value_str.parse::<u64>() {
-            map.insert(key, value * 1024); // Convert kB to bytes
+            map.insert(key, value.saturating_mul(unit));
         }
     }
 
     Ok(map)
 }
 
+/// Parse /sys/devices/system/node/node{N}/meminfo, whose lines carry a
+/// `Node N ` prefix but are otherwise formatted like /proc/meminfo
+pub fn parse_node_meminfo(content: &str) -> ParseResult<HashMap<String, u64>> {
+    let stripped: Vec<&str> = content
+        .lines()
+        .map(|line| {
+            line.strip_prefix("Node ")
+                .and_then(|rest| rest.split_once(' '))
+                .map_or(line, |(_, rest)| rest)
+        })
+        .collect();
+    parse_meminfo(&stripped.join("\n"))
+}
+
 /// Parse /proc/mounts
 #[derive(Debug, Clone)]
 pub struct MountInfo {
@@ -165,8 +192,8 @@ pub fn parse_mounts(content: &str) -> ParseResult<Vec<MountInfo>> {
         }
 
         mounts.push(MountInfo {
-            device: parts[0].to_string(),
-            mount_point: parts[1].to_string(),
+            device: unescape_mount_field(parts[0]),
+            mount_point: unescape_mount_field(parts[1]),
             filesystem: parts[2].to_string(),
         });
     }
@@ -174,33 +201,73 @@ pub fn parse_mounts(content: &str) -> ParseResult<Vec<MountInfo>> {
     Ok(mounts)
 }
 
-/// Parse network statistics from /sys/class/net/{interface}/statistics
-pub fn parse_net_stats(stats_dir: &Path) -> ParseResult<(u64, u64, u64, u64)> {
-    let rx_bytes = fs::read_to_string(stats_dir.join("rx_bytes"))?
-        .trim()
-        .parse::<u64>()
-        .map_err(|e| ParseError::Parse(format!("rx_bytes: {}", e)))?;
-
-    let tx_bytes = fs::read_to_string(stats_dir.join("tx_bytes"))?
-        .trim()
-        .parse::<u64>()
-        .map_err(|e| ParseError::Parse(format!("tx_bytes: {}", e)))?;
-
-    let rx_errors = fs::read_to_string(stats_dir.join("rx_errors"))?
-        .trim()
-        .parse::<u64>()
-        .map_err(|e| ParseError::Parse(format!("rx_errors: {}", e)))?;
+/// The kernel writes space, tab, newline and backslash in mount fields as
+/// three-digit octal escapes (`/mnt/my\040disk`)
+fn unescape_mount_field(field: &str) -> String {
+    let bytes = field.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escape = bytes
+            .get(i + 1..i + 4)
+            .filter(|digits| bytes[i] == b'\\' && digits.iter().all(|d| (b'0'..=b'7').contains(d)));
+        match escape {
+            Some(digits) => {
+                let value = digits.iter().fold(0u16, |v, d| v * 8 + (d - b'0') as u16);
+                out.push(value as u8);
+                i += 4;
+            }
+            None => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
 
-    let tx_errors = fs::read_to_string(stats_dir.join("tx_errors"))?
-        .trim()
-        .parse::<u64>()
-        .map_err(|e| ParseError::Parse(format!("tx_errors: {}", e)))?;
+/// Parse network statistics from /sys/class/net/{interface}/statistics;
+/// drop and collision counters count as zero where a driver lacks them
+pub fn parse_net_stats(stats_dir: &Path) -> ParseResult<NetworkMetrics> {
+    let counter = |name: &str| -> ParseResult<u64> {
+        fs::read_to_string(stats_dir.join(name))?
+            .trim()
+            .parse::<u64>()
+            .map_err(|e| ParseError::Parse(format!("{}: {}", name, e)))
+    };
+    let optional = |name: &str| counter(name).unwrap_or(0);
+
+    Ok(NetworkMetrics::new(
+        counter("rx_bytes")?,
+        counter("tx_bytes")?,
+        counter("rx_errors")?,
+        counter("tx_errors")?,
+    )
+    .with_drops(optional("rx_dropped"), optional("tx_dropped"))
+    .with_collisions(optional("collisions")))
+}
 
-    Ok((rx_bytes, tx_bytes, rx_errors, tx_errors))
+/// Fields used from /proc/{pid}/stat
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcStat {
+    pub pid: u32,
+    pub ppid: u32,
+    pub state: char,
+    pub flags: u32,
+    pub utime: u64,
+    pub stime: u64,
+    /// Kernel view: `nice + 20` for normal tasks, `-1 - rt_priority` for real-time ones
+    pub priority: i64,
+    pub nice: i64,
+    /// Clock ticks after boot at which the process started
+    pub starttime: u64,
+    pub rss: u64,
+    /// `SCHED_*` constant, absent on kernels older than 2.5.19
+    pub policy: Option<u32>,
 }
 
 /// Parse /proc/{pid}/stat
-pub fn parse_proc_stat(content: &str) -> ParseResult<(u32, u32, char, u64, u64, u64)> {
+pub fn parse_proc_stat(content: &str) -> ParseResult<ProcStat> {
     // Format: pid (comm) state ppid ... utime stime ...
     // Need to handle comm with spaces and parentheses
 
@@ -209,13 +276,20 @@ pub fn parse_proc_stat(content: &str) -> ParseResult<(u32, u32, char, u64, u64,
         .ok_or_else(|| ParseError::Parse("No ( found".to_string()))?;
     let end = content
         .rfind(')')
+        .filter(|end| *end > start)
         .ok_or_else(|| ParseError::Parse("No ) found".to_string()))?;
 
     let pid_str = content[..start].trim();
     let after_comm = &content[end + 1..];
 
-    let parts: Vec<&str> = after_comm.split_whitespace().collect();
-    if parts.len() < 13 {
+    // Fields up to `policy`, kept on the stack: this runs for every PID on every scan
+    let mut parts = [""; 39];
+    let mut count = 0;
+    for (slot, part) in parts.iter_mut().zip(after_comm.split_whitespace()) {
+        *slot = part;
+        count += 1;
+    }
+    if count < 22 {
         return Err(ParseError::Parse("Incomplete proc stat".to_string()));
     }
 
@@ -226,32 +300,285 @@ pub fn parse_proc_stat(content: &str) -> ParseResult<(u32, u32, char, u64, u64,
     let ppid: u32 = parts[1]
         .parse()
         .map_err(|e| ParseError::Parse(format!("ppid: {}", e)))?;
+    let flags: u32 = parts[6]
+        .parse()
+        .map_err(|e| ParseError::Parse(format!("flags: {}", e)))?;
     let utime: u64 = parts[11]
         .parse()
         .map_err(|e| ParseError::Parse(format!("utime: {}", e)))?;
     let stime: u64 = parts[12]
         .parse()
         .map_err(|e| ParseError::Parse(format!("stime: {}", e)))?;
+    let priority: i64 = parts[15]
+        .parse()
+        .map_err(|e| ParseError::Parse(format!("priority: {}", e)))?;
+    let nice: i64 = parts[16]
+        .parse()
+        .map_err(|e| ParseError::Parse(format!("nice: {}", e)))?;
+    let starttime: u64 = parts[19]
+        .parse()
+        .map_err(|e| ParseError::Parse(format!("starttime: {}", e)))?;
     let rss: u64 = parts[21]
         .parse()
         .map_err(|e| ParseError::Parse(format!("rss: {}", e)))?;
+    let policy = parts[..count].get(38).and_then(|p| p.parse().ok());
+
+    Ok(ProcStat {
+        pid,
+        ppid,
+        state,
+        flags,
+        utime,
+        stime,
+        priority,
+        nice,
+        starttime,
+        rss,
+        policy,
+    })
+}
 
-    Ok((pid, ppid, state, utime, stime, rss))
+/// Boot time in seconds since the epoch from the `btime` line of /proc/stat
+pub fn parse_btime(content: &str) -> ParseResult<i64> {
+    content
+        .lines()
+        .find_map(|line| line.strip_prefix("btime "))
+        .ok_or_else(|| ParseError::MissingField("btime".to_string()))?
+        .trim()
+        .parse()
+        .map_err(|e| ParseError::Parse(format!("btime: {}", e)))
 }
 
 /// Parse /proc/{pid}/status for UID
 pub fn parse_proc_status_uid(content: &str) -> ParseResult<u32> {
     for line in content.lines() {
-        if line.starts_with("Uid:") {
+        if let Some(real) = line
+            .strip_prefix("Uid:")
+            .and_then(|ids| ids.split_whitespace().next())
+        {
+            return real
+                .parse()
+                .map_err(|e| ParseError::Parse(format!("uid: {}", e)));
+        }
+    }
+    Err(ParseError::MissingField("Uid".to_string()))
+}
+
+/// Parse /proc/sys/fs/file-nr: `allocated unused max`, returns (in use, max)
+pub fn parse_file_nr(content: &str) -> ParseResult<(u64, u64)> {
+    let values: Vec<u64> = content
+        .split_whitespace()
+        .map(|v| v.parse::<u64>())
+        .collect::<Result<_, _>>()
+        .map_err(|e| ParseError::Parse(format!("Invalid file-nr: {}", e)))?;
+    match values.as_slice() {
+        [allocated, unused, max] => Ok((allocated.saturating_sub(*unused), *max)),
+        _ => Err(ParseError::Parse("Invalid file-nr format".to_string())),
+    }
+}
+
+/// Number of scheduling entities (threads) from the 4th field of /proc/loadavg (`2/456`)
+pub fn parse_loadavg_threads(content: &str) -> ParseResult<u64> {
+    content
+        .split_whitespace()
+        .nth(3)
+        .and_then(|f| f.split_once('/'))
+        .and_then(|(_, total)| total.parse().ok())
+        .ok_or_else(|| ParseError::Parse("Invalid loadavg thread count".to_string()))
+}
+
+/// Parse /proc/net/route and return the interface of the default route
+pub fn parse_default_route(content: &str) -> Option<String> {
+    content
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
             let parts: Vec<&str> = line.split_whitespace().collect();
-            if parts.len() >= 2 {
-                return parts[1]
-                    .parse()
-                    .map_err(|e| ParseError::Parse(format!("uid: {}", e)));
+            // Iface Destination Gateway Flags RefCnt Use Metric ...
+            if parts.len() < 7 || parts[1] != "00000000" {
+                return None;
+            }
+            Some((parts[6].parse::<u32>().unwrap_or(u32::MAX), parts[0]))
+        })
+        .min_by_key(|(metric, _)| *metric)
+        .map(|(_, iface)| iface.to_string())
+}
+
+/// Addresses per interface, in CIDR notation: IPv4 from the `LOCAL` entries
+/// of `/proc/net/fib_trie`, matched to an interface and prefix through the
+/// most specific route of `/proc/net/route`; IPv6 from `/proc/net/if_inet6`
+pub fn parse_interface_addresses(
+    fib_trie: &str,
+    route: &str,
+    if_inet6: &str,
+) -> HashMap<String, Vec<String>> {
+    let routes: Vec<(&str, u32, u32)> = route
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            // Iface Destination Gateway Flags RefCnt Use Metric Mask ...
+            if parts.len() < 8 {
+                return None;
+            }
+            // Little-endian hex
+            let destination = u32::from_str_radix(parts[1], 16).ok()?.swap_bytes();
+            let mask = u32::from_str_radix(parts[7], 16).ok()?.swap_bytes();
+            (mask != 0).then_some((parts[0], destination, mask))
+        })
+        .collect();
+
+    let mut local = Vec::new();
+    let mut last_leaf = None;
+    for line in fib_trie.lines() {
+        let line = line.trim_start_matches([' ', '|', '+', '-']).trim();
+        if let Ok(ip) = line.parse::<Ipv4Addr>() {
+            last_leaf = Some(ip);
+        } else if line.starts_with("/32 host LOCAL") {
+            if let Some(ip) = last_leaf.filter(|ip| !ip.is_loopback() && !local.contains(ip)) {
+                local.push(ip);
             }
         }
     }
-    Err(ParseError::MissingField("Uid".to_string()))
+
+    let mut addresses: HashMap<String, Vec<String>> = HashMap::new();
+    for ip in local {
+        let bits = u32::from(ip);
+        if let Some((iface, _, mask)) = routes
+            .iter()
+            .filter(|(_, destination, mask)| bits & mask == *destination)
+            .max_by_key(|(_, _, mask)| mask.count_ones())
+        {
+            addresses
+                .entry(iface.to_string())
+                .or_default()
+                .push(format!("{}/{}", ip, mask.count_ones()));
+        }
+    }
+
+    for line in if_inet6.lines() {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        // address ifindex prefix_len scope flags name, numbers in hex
+        if parts.len() < 6 {
+            continue;
+        }
+        let (Ok(bits), Ok(prefix)) = (
+            u128::from_str_radix(parts[0], 16),
+            u8::from_str_radix(parts[2], 16),
+        ) else {
+            continue;
+        };
+        let ip = Ipv6Addr::from(bits);
+        if !ip.is_loopback() {
+            addresses
+                .entry(parts[5].to_string())
+                .or_default()
+                .push(format!("{}/{}", ip, prefix));
+        }
+    }
+
+    addresses
+}
+
+/// Count /proc/net/nf_conntrack entries by protocol and the destination port
+/// of the original direction; TCP only counts established connections
+pub fn parse_conntrack_ports(content: &str) -> HashMap<(String, u16), u64> {
+    let mut counts = HashMap::new();
+    for line in content.lines() {
+        // ipv4 2 tcp 6 431999 ESTABLISHED src=... dst=... sport=... dport=...
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        let Some(&protocol) = parts.get(2) else {
+            continue;
+        };
+        if protocol == "tcp" && parts.get(5) != Some(&"ESTABLISHED") {
+            continue;
+        }
+        let Some(port) = parts
+            .iter()
+            .find_map(|p| p.strip_prefix("dport="))
+            .and_then(|p| p.parse::<u16>().ok())
+        else {
+            continue;
+        };
+        *counts.entry((protocol.to_string(), port)).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Per-queue packets, bytes and drops from `ethtool -S`, whose counter names
+/// depend on the driver: `rx_queue_0_packets`, `rx-0.packets` or `rx0_packets`
+pub fn parse_ethtool_queues(output: &str) -> Vec<QueueStats> {
+    let mut queues: BTreeMap<(&str, u32), QueueStats> = BTreeMap::new();
+    for line in output.lines() {
+        let Some((key, value)) = line.trim().split_once(':') else {
+            continue;
+        };
+        let Ok(value) = value.trim().parse::<u64>() else {
+            continue;
+        };
+        let Some((direction, rest)) = ["rx", "tx"]
+            .into_iter()
+            .find_map(|d| key.strip_prefix(d).map(|rest| (d, rest)))
+        else {
+            continue;
+        };
+        let rest = rest
+            .strip_prefix("_queue_")
+            .or_else(|| rest.strip_prefix('-'))
+            .unwrap_or(rest);
+        let Some((index, counter)) = rest.split_once(['_', '.']) else {
+            continue;
+        };
+        let Ok(index) = index.parse::<u32>() else {
+            continue;
+        };
+        let queue = queues
+            .entry((direction, index))
+            .or_insert_with(|| QueueStats {
+                queue: format!("{}-{}", direction, index),
+                ..Default::default()
+            });
+        match counter {
+            "packets" => queue.packets = value,
+            "bytes" => queue.bytes = value,
+            "drops" | "dropped" | "drop" => queue.drops = value,
+            _ => {}
+        }
+    }
+    queues.into_values().collect()
+}
+
+/// Parse /proc/net/wireless into the link of each wireless interface.
+/// cfg80211 drivers report quality out of 70 and levels in dBm, older ones
+/// levels as unsigned bytes; a noise of -256 means unknown
+pub fn parse_wireless(content: &str) -> HashMap<String, WirelessLink> {
+    let level = |field: &str| {
+        let value = field.trim_end_matches('.').parse::<i32>().ok()?;
+        let dbm = if value > 0 { value - 256 } else { value };
+        (-255..0).contains(&dbm).then_some(dbm)
+    };
+    content
+        .lines()
+        .skip(2)
+        .filter_map(|line| {
+            let (name, rest) = line.split_once(':')?;
+            // status link level noise ...
+            let parts: Vec<&str> = rest.split_whitespace().collect();
+            if parts.len() < 4 {
+                return None;
+            }
+            let quality = parts[1].trim_end_matches('.').parse::<f64>().ok();
+            Some((
+                name.trim().to_string(),
+                WirelessLink {
+                    ssid: None,
+                    quality_percent: quality.map(|q| (q / 70.0 * 100.0).min(100.0)),
+                    signal_dbm: level(parts[2]),
+                    noise_dbm: level(parts[3]),
+                },
+            ))
+        })
+        .collect()
 }
 
 #[cfg(test)]
@@ -283,4 +610,177 @@ mod tests {
         assert_eq!(stat.idle, 10000);
         assert_eq!(stat.iowait, 200);
     }
+
+    #[test]
+    fn test_parse_kernel_limits() {
+        assert_eq!(
+            parse_file_nr("9344\t0\t9223372036854775807\n").unwrap().0,
+            9344
+        );
+        assert_eq!(parse_file_nr("1024 24 4096").unwrap(), (1000, 4096));
+        assert_eq!(
+            parse_loadavg_threads("0.52 0.78 1.21 2/456 12345\n").unwrap(),
+            456
+        );
+    }
+
+    #[test]
+    fn test_parse_default_route() {
+        let content = "Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\n\
+            wwan0\t00000000\t0100A8C0\t0003\t0\t0\t700\t00000000\n\
+            eth0\t00000000\t0101A8C0\t0003\t0\t0\t100\t00000000\n\
+            eth0\t0001A8C0\t00000000\t0001\t0\t0\t100\t00FFFFFF\n";
+        assert_eq!(parse_default_route(content).as_deref(), Some("eth0"));
+    }
+
+    #[test]
+    fn test_parse_ethtool_queues() {
+        let output = "NIC statistics:\n     rx_packets: 1500\n     tx_queue_0_packets: 700\n     tx_queue_0_bytes: 64000\n     rx_queue_0_packets: 1000\n     rx_queue_0_drops: 12\n     rx_queue_1_packets: 500\n     rx1_bytes: 42000\n";
+        let queues = parse_ethtool_queues(output);
+        let names: Vec<_> = queues.iter().map(|q| q.queue.as_str()).collect();
+        assert_eq!(names, vec!["rx-0", "rx-1", "tx-0"]);
+        assert_eq!(queues[0].drops, 12);
+        assert_eq!(queues[1].bytes, 42000);
+        assert_eq!(queues[2].bytes, 64000);
+    }
+
+    #[test]
+    fn test_parse_wireless() {
+        let content = "Inter-| sta-|   Quality        |   Discarded packets               | Missed | WE\n face | tus | link level noise |  nwid  crypt   frag  retry   misc | beacon | 22\n wlan0: 0000   49.  -61.  -256        0      0      0      0     12        0\n  wlp2: 0000   35   200   0        0      0      0      0      0        0\n";
+        let links = parse_wireless(content);
+        let wlan0 = &links["wlan0"];
+        assert_eq!(wlan0.quality_percent, Some(70.0));
+        assert_eq!(wlan0.signal_dbm, Some(-61));
+        assert_eq!(wlan0.noise_dbm, None);
+        assert_eq!(links["wlp2"].signal_dbm, Some(-56));
+    }
+
+    #[test]
+    fn test_parse_node_meminfo() {
+        let content = "Node 1 MemTotal:        8151920 kB\nNode 1 HugePages_Total:   256\n";
+        let map = parse_node_meminfo(content).unwrap();
+        assert_eq!(map["MemTotal"], 8151920 * 1024);
+        assert_eq!(map["HugePages_Total"], 256);
+    }
+
+    #[test]
+    fn test_parse_mounts_unescapes_fields() {
+        let mounts = parse_mounts("/dev/sdb1 /mnt/my\\040disk ext4 rw 0 0\n").unwrap();
+        assert_eq!(mounts[0].mount_point, "/mnt/my disk");
+    }
+
+    /// These parsers read kernel-formatted text that processes partly control
+    /// (comm names, mount points), so no input may make them panic
+    mod properties {
+        use super::*;
+        use proptest::prelude::*;
+
+        fn proc_stat_line(stat: &ProcStat, comm: &str) -> String {
+            format!(
+                "{} ({}) {} {} {} {} 0 -1 {} 0 0 0 0 {} {} 0 0 {} {} 1 0 {} 1000 {} \
+                 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 {} 0\n",
+                stat.pid,
+                comm,
+                stat.state,
+                stat.ppid,
+                stat.pid,
+                stat.pid,
+                stat.flags,
+                stat.utime,
+                stat.stime,
+                stat.priority,
+                stat.nice,
+                stat.starttime,
+                stat.rss,
+                stat.policy.unwrap_or_default()
+            )
+        }
+
+        proptest! {
+            #[test]
+            fn proc_stat_round_trips_any_comm(
+                comm in "[^\\x00]{0,16}",
+                (pid, ppid, flags) in any::<(u32, u32, u32)>(),
+                (utime, stime, starttime, rss) in any::<(u64, u64, u64, u64)>(),
+                nice in -20i64..=19,
+                policy in 0u32..=6,
+            ) {
+                let stat = ProcStat {
+                    pid,
+                    ppid,
+                    state: 'S',
+                    flags,
+                    utime,
+                    stime,
+                    priority: nice + 20,
+                    nice,
+                    starttime,
+                    rss,
+                    policy: Some(policy),
+                };
+                prop_assert_eq!(parse_proc_stat(&proc_stat_line(&stat, &comm)).unwrap(), stat);
+            }
+
+            #[test]
+            fn proc_stat_rejects_truncated_files(len in 0usize..60, comm in "[ ()a-z]{0,16}") {
+                let stat = ProcStat {
+                    pid: 1,
+                    ppid: 0,
+                    state: 'R',
+                    flags: u32::MAX,
+                    utime: u64::MAX,
+                    stime: u64::MAX,
+                    priority: -100,
+                    nice: 0,
+                    starttime: u64::MAX,
+                    rss: u64::MAX,
+                    policy: Some(1),
+                };
+                let line = proc_stat_line(&stat, &comm);
+                let end = line.char_indices().map(|(i, _)| i).take_while(|i| *i <= len).last().unwrap_or(0);
+                prop_assert!(parse_proc_stat(&line[..end]).is_err());
+            }
+
+            #[test]
+            fn proc_stat_never_panics(content in "\\PC*") {
+                let _ = parse_proc_stat(&content);
+            }
+
+            #[test]
+            fn cpu_stat_round_trips_huge_values(values in prop::array::uniform8(any::<u64>())) {
+                let line = format!(
+                    "cpu  {}\ncpu0 1 2 3 4\n",
+                    values.map(|v| v.to_string()).join(" ")
+                );
+                let stat = parse_cpu_stat(&line).unwrap();
+                prop_assert_eq!(stat.user, values[0]);
+                prop_assert_eq!(stat.steal, values[7]);
+                prop_assert!(stat.busy() <= stat.total());
+            }
+
+            #[test]
+            fn cpu_stat_never_panics(content in "(cpu  )?[0-9 \n]{0,80}") {
+                let _ = parse_cpu_stat(&content);
+            }
+
+            #[test]
+            fn meminfo_saturates_huge_values(key in "[A-Za-z_()]{1,20}", value: u64) {
+                let map = parse_meminfo(&format!("{}:  {} kB\nHugePages_Total: {}\n", key, value, value)).unwrap();
+                prop_assert_eq!(map[&key], value.saturating_mul(1024));
+                prop_assert_eq!(map["HugePages_Total"], value);
+            }
+
+            #[test]
+            fn meminfo_never_panics(content in "\\PC*") {
+                let _ = parse_meminfo(&content);
+            }
+
+            #[test]
+            fn mounts_never_panic(content in "([\\\\0-9a-z/ ]{0,30}\n){0,5}") {
+                for mount in parse_mounts(&content).unwrap() {
+                    prop_assert!(!mount.filesystem.is_empty());
+                }
+            }
+        }
+    }
 }
@@ -1,21 +1,78 @@
+use std::collections::{HashMap, HashSet};
 use std::fs;
+use std::io;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Mutex, OnceLock};
 
 use async_trait::async_trait;
+use chrono::DateTime;
+use nix::errno::Errno;
 
-use crate::domain::{Process, ProcessState};
+use crate::domain::{
+    CommandSanitizer, Process, ProcessFilter, ProcessOrder, ProcessSampling, ProcessScanStats,
+    ProcessState, SchedulingPolicy,
+};
 use crate::ports::ProcessSource;
 
 use super::parser;
+use super::reader::ProcReader;
 use super::ProcfsConfig;
 
+/// Processes read in full by every scan, by CPU, memory and nice each, so top
+/// lists stay exact however the rest is sampled
+const ALWAYS_READ: usize = 50;
+
+/// Per-scan values shared by every process
+struct ScanContext {
+    uptime: u64,
+    total_memory: u64,
+    /// UID to name, from /etc/passwd
+    users: HashMap<u32, String>,
+}
+
 /// Process source implementation using procfs
 pub struct ProcfsProcessSource {
     config: ProcfsConfig,
+    sanitizer: CommandSanitizer,
+    last_scan: Mutex<Option<ProcessScanStats>>,
+    /// Unexpected read failures are logged at warn level once, then at debug
+    failure_reported: AtomicBool,
+    /// `btime` from /proc/stat, fixed until the next reboot
+    boot_time: OnceLock<Option<i64>>,
+    sampling: ProcessSampling,
+    /// Sampling applies once a host runs more processes than this
+    sampling_above: usize,
+    /// Shard whose details the next scan re-reads
+    next_shard: AtomicU32,
+    /// Start time and last full read of each PID, for shard sampling
+    details: Mutex<HashMap<u32, (u64, Process)>>,
 }
 
 impl ProcfsProcessSource {
     pub fn new(config: ProcfsConfig) -> Self {
-        Self { config }
+        Self {
+            config,
+            sanitizer: CommandSanitizer::default(),
+            last_scan: Mutex::new(None),
+            failure_reported: AtomicBool::new(false),
+            boot_time: OnceLock::new(),
+            sampling: ProcessSampling::Full,
+            sampling_above: usize::MAX,
+            next_shard: AtomicU32::new(0),
+            details: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn with_sanitizer(mut self, sanitizer: CommandSanitizer) -> Self {
+        self.sanitizer = sanitizer;
+        self
+    }
+
+    /// Sample scans as `sampling` says on hosts running more than `above` processes
+    pub fn with_sampling(mut self, sampling: ProcessSampling, above: usize) -> Self {
+        self.sampling = sampling;
+        self.sampling_above = above;
+        self
     }
 
     fn list_pids(&self) -> Result<Vec<u32>, Box<dyn std::error::Error + Send + Sync>> {
@@ -31,111 +88,292 @@ impl ProcfsProcessSource {
             }
         }
 
+        pids.sort_unstable();
         Ok(pids)
     }
 
-    fn read_process(&self, pid: u32) -> Result<Process, Box<dyn std::error::Error + Send + Sync>> {
-        let pid_path = self.config.proc_path.join(pid.to_string());
-
-        // Read /proc/{pid}/stat
-        let stat_content = fs::read_to_string(pid_path.join("stat"))?;
-        let (_pid, ppid, state_char, utime, stime, rss) = parser::parse_proc_stat(&stat_content)?;
-
-        // Read /proc/{pid}/status for UID
-        let status_content = fs::read_to_string(pid_path.join("status"))?;
-        let uid = parser::parse_proc_status_uid(&status_content)?;
+    fn boot_time(&self) -> Option<i64> {
+        *self.boot_time.get_or_init(|| {
+            fs::read_to_string(self.config.proc_path.join("stat"))
+                .ok()
+                .and_then(|content| parser::parse_btime(&content).ok())
+        })
+    }
 
-        // Get username from UID (simple approach)
-        let user = self
-            .get_username_from_uid(uid)
-            .unwrap_or_else(|| uid.to_string());
+    /// Uptime, memory size and user names, read once per scan
+    fn scan_context(&self) -> Result<ScanContext, Box<dyn std::error::Error + Send + Sync>> {
+        let uptime_content = fs::read_to_string(self.config.proc_path.join("uptime"))?;
+        let meminfo_content = fs::read_to_string(self.config.proc_path.join("meminfo"))?;
+        let meminfo = parser::parse_meminfo(&meminfo_content)?;
+        // Simple implementation: read /etc/passwd
+        let passwd = fs::read_to_string(self.config.etc_path.join("passwd")).unwrap_or_default();
+        Ok(ScanContext {
+            uptime: parser::parse_uptime(&uptime_content)?,
+            total_memory: *meminfo.get("MemTotal").unwrap_or(&1),
+            users: parse_passwd(&passwd),
+        })
+    }
 
-        // Read command from /proc/{pid}/cmdline
-        let cmdline_content = fs::read_to_string(pid_path.join("cmdline")).unwrap_or_default();
-        let command = if cmdline_content.is_empty() {
-            // Kernel thread, use comm
-            fs::read_to_string(pid_path.join("comm"))
-                .unwrap_or_else(|_| format!("[pid:{}]", pid))
-                .trim()
-                .to_string()
-        } else {
-            // Replace null bytes with spaces and take first arg
-            cmdline_content.replace('\0', " ").trim().to_string()
-        };
+    fn read_stat(
+        &self,
+        reader: &mut ProcReader,
+        pid: u32,
+    ) -> Result<parser::ProcStat, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(parser::parse_proc_stat(reader.read_pid_str(pid, "stat")?)?)
+    }
 
-        // Read system uptime and calculate CPU usage (simplified, needs delta)
-        let uptime_content = fs::read_to_string(self.config.proc_path.join("uptime"))?;
-        let uptime = parser::parse_uptime(&uptime_content)?;
+    /// What /proc/{pid}/stat alone tells: metrics and scheduling, no user or command
+    fn stat_process(&self, stat: &parser::ProcStat, context: &ScanContext) -> Process {
+        // CPU usage averaged over the process lifetime (simplified, needs delta)
         let hertz = 100; // Typical USER_HZ value
-        let total_time = utime + stime;
-        let seconds = uptime - (total_time / hertz);
+        let total_time = stat.utime + stat.stime;
+        let seconds = context.uptime - (total_time / hertz);
         let cpu_percent = if seconds > 0 {
             (total_time as f64 / hertz as f64 / seconds as f64) * 100.0
         } else {
             0.0
         };
 
+        // Start time is in clock ticks after boot
+        let started_after_boot = stat.starttime / hertz;
+        let age_seconds = context.uptime.saturating_sub(started_after_boot);
+        let started_at = self
+            .boot_time()
+            .and_then(|boot| DateTime::from_timestamp(boot + started_after_boot as i64, 0));
+
         // Memory usage (RSS in pages, typically 4096 bytes)
         let page_size = 4096;
-        let memory_bytes = rss * page_size;
+        let memory_bytes = stat.rss * page_size;
+        let memory_percent = (memory_bytes as f64 / context.total_memory as f64) * 100.0;
 
-        // Get total memory for percentage
-        let meminfo_content = fs::read_to_string(self.config.proc_path.join("meminfo"))?;
-        let meminfo = parser::parse_meminfo(&meminfo_content)?;
-        let total_memory = *meminfo.get("MemTotal").unwrap_or(&1);
-        let memory_percent = (memory_bytes as f64 / total_memory as f64) * 100.0;
+        Process::new(
+            stat.pid,
+            stat.ppid,
+            String::new(),
+            String::new(),
+            ProcessState::from_char(stat.state),
+        )
+        .with_metrics(cpu_percent, memory_percent, memory_bytes)
+        .with_kernel_thread(stat.flags & PF_KTHREAD != 0)
+        .with_start(started_at, age_seconds)
+        .with_scheduling(
+            stat.priority,
+            stat.nice,
+            stat.policy
+                .map_or(SchedulingPolicy::Normal, SchedulingPolicy::from_raw),
+        )
+    }
+
+    /// Fill in user, command and container from the other per-PID files
+    fn read_details(
+        &self,
+        reader: &mut ProcReader,
+        context: &ScanContext,
+        process: Process,
+        flags: u32,
+    ) -> Result<Process, Box<dyn std::error::Error + Send + Sync>> {
+        let pid = process.pid;
+
+        // Only stat is required; other files may be unreadable when NanoMon is not
+        // root or procfs is mounted with hidepid, so degrade instead of dropping the process
+        let mut partial = false;
+
+        // Read /proc/{pid}/status for UID
+        let uid = reader
+            .read_pid_str(pid, "status")
+            .ok()
+            .and_then(|content| parser::parse_proc_status_uid(content).ok());
+
+        // Get username from UID (simple approach)
+        let user = match uid {
+            Some(uid) => context
+                .users
+                .get(&uid)
+                .cloned()
+                .unwrap_or_else(|| uid.to_string()),
+            None => {
+                partial = true;
+                String::new()
+            }
+        };
+
+        // Read command from /proc/{pid}/cmdline
+        let cmdline = reader.read_pid(pid, "cmdline");
+        let kernel_thread = is_kernel_thread(pid, process.ppid, flags, cmdline.as_deref().ok());
+        let command = match cmdline {
+            Ok(bytes) if !bytes.is_empty() => {
+                // Secrets and oversized arguments never leave the adapter
+                let cmdline_content = String::from_utf8_lossy(bytes);
+                let args: Vec<&str> = cmdline_content
+                    .split('\0')
+                    .filter(|a| !a.is_empty())
+                    .collect();
+                Some(self.sanitizer.sanitize(&args))
+            }
+            Ok(_) => None,
+            Err(_) => {
+                partial = true;
+                None
+            }
+        };
+        let command = command.unwrap_or_else(|| {
+            // Kernel thread (or unreadable cmdline), use comm
+            reader
+                .read_pid_str(pid, "comm")
+                .map(|comm| comm.trim().to_string())
+                .unwrap_or_else(|_| format!("[pid:{}]", pid))
+        });
 
         // Check if process is in a container by examining cgroup
-        let container_id = self.get_container_id_from_cgroup(pid)?;
+        let container_id = reader
+            .read_pid_str(pid, "cgroup")
+            .ok()
+            .and_then(container_id_from_cgroup);
 
-        Ok(Process::new(
-            pid,
-            ppid,
+        Ok(Process {
             user,
             command,
-            ProcessState::from_char(state_char),
-        )
-        .with_metrics(cpu_percent, memory_percent, memory_bytes)
-        .with_container(container_id))
+            ..process
+        }
+        .with_container(container_id)
+        .with_partial(partial)
+        .with_kernel_thread(kernel_thread))
     }
 
-    fn get_username_from_uid(&self, uid: u32) -> Option<String> {
-        // Simple implementation: read /etc/passwd
-        let passwd = fs::read_to_string("/etc/passwd").ok()?;
-        for line in passwd.lines() {
-            let parts: Vec<&str> = line.split(':').collect();
-            if parts.len() >= 3 {
-                if let Ok(line_uid) = parts[2].parse::<u32>() {
-                    if line_uid == uid {
-                        return Some(parts[0].to_string());
+    /// Read every PID, sampling as configured on large hosts; the top `keep`
+    /// processes matching `filter` by CPU, memory and nice are always read in full
+    fn scan(
+        &self,
+        keep: usize,
+        filter: &ProcessFilter,
+    ) -> Result<Vec<Process>, Box<dyn std::error::Error + Send + Sync>> {
+        let pids = self.list_pids()?;
+        let context = self.scan_context()?;
+        let sampling = if pids.len() > self.sampling_above {
+            self.sampling
+        } else {
+            ProcessSampling::Full
+        };
+        let mut stats = ProcessScanStats::default();
+
+        // Cheap pass over stat files, enough to rank processes
+        let mut reader = ProcReader::new(&self.config.proc_path);
+        let mut candidates = Vec::with_capacity(pids.len());
+        for pid in pids {
+            match self.read_stat(&mut reader, pid) {
+                Ok(stat) => {
+                    let process = self.stat_process(&stat, &context);
+                    candidates.push((stat, process));
+                }
+                Err(e) => self.record_failure(&mut stats, pid, e.as_ref()),
+            }
+        }
+
+        let top = if sampling == ProcessSampling::Full {
+            HashSet::new()
+        } else {
+            top_pids(&candidates, keep, filter)
+        };
+        let shard = self.next_shard.fetch_add(1, Ordering::Relaxed);
+        let mut cache = self.details.lock().unwrap();
+        let mut details = HashMap::new();
+        let mut processes = Vec::with_capacity(candidates.len());
+
+        for (stat, process) in candidates {
+            let pid = stat.pid;
+            if !top.contains(&pid) {
+                match sampling {
+                    ProcessSampling::Floor {
+                        cpu_percent,
+                        memory_percent,
+                    } if process.cpu_percent < cpu_percent
+                        && process.memory_percent < memory_percent =>
+                    {
+                        stats.skipped += 1;
+                        continue;
                     }
+                    ProcessSampling::Shards { count } if pid % count != shard % count => {
+                        // A reused PID has another start time and is read afresh
+                        if let Some((_, cached)) = cache
+                            .remove(&pid)
+                            .filter(|(starttime, _)| *starttime == stat.starttime)
+                        {
+                            let process = Process {
+                                user: cached.user.clone(),
+                                command: cached.command.clone(),
+                                container_id: cached.container_id.clone(),
+                                partial: cached.partial,
+                                kernel_thread: cached.kernel_thread,
+                                ..process
+                            };
+                            details.insert(pid, (stat.starttime, cached));
+                            stats.cached += 1;
+                            processes.push(process);
+                            continue;
+                        }
+                    }
+                    _ => {}
                 }
             }
+
+            match self.read_details(&mut reader, &context, process, stat.flags) {
+                Ok(process) => {
+                    stats.read += 1;
+                    if matches!(sampling, ProcessSampling::Shards { .. }) {
+                        details.insert(pid, (stat.starttime, process.clone()));
+                    }
+                    processes.push(process);
+                }
+                Err(e) => self.record_failure(&mut stats, pid, e.as_ref()),
+            }
         }
-        None
+        // Exited PIDs drop out of the cache
+        *cache = details;
+
+        tracing::debug!(
+            "Process scan: {} read, {} cached, {} skipped, {} vanished, {} failed",
+            stats.read,
+            stats.cached,
+            stats.skipped,
+            stats.vanished,
+            stats.failed
+        );
+        *self.last_scan.lock().unwrap() = Some(stats);
+        Ok(processes)
     }
 
-    fn get_container_id_from_cgroup(
+    /// Top `n` in `order` of a scan that reads at least those in full
+    fn top(
         &self,
+        n: usize,
+        filter: &ProcessFilter,
+        order: ProcessOrder,
+    ) -> Result<Vec<Process>, Box<dyn std::error::Error + Send + Sync>> {
+        let processes = self.scan(n.max(ALWAYS_READ), filter)?;
+        Ok(order
+            .top(&processes, n, filter)
+            .into_iter()
+            .cloned()
+            .collect())
+    }
+
+    fn record_failure(
+        &self,
+        stats: &mut ProcessScanStats,
         pid: u32,
-    ) -> Result<Option<crate::domain::ContainerId>, Box<dyn std::error::Error + Send + Sync>> {
-        let cgroup_path = self.config.proc_path.join(format!("{}/cgroup", pid));
-        let content = fs::read_to_string(cgroup_path).unwrap_or_default();
-
-        // Look for docker container ID in cgroup path
-        // Format: 0::/docker/{container_id}
-        for line in content.lines() {
-            if line.contains("/docker/") {
-                if let Some(id) = line.split("/docker/").nth(1) {
-                    let container_id = id.trim_end_matches(".scope").to_string();
-                    if !container_id.is_empty() {
-                        return Ok(Some(container_id.into()));
-                    }
-                }
+        error: &(dyn std::error::Error + Send + Sync + 'static),
+    ) {
+        if has_exited(error) {
+            stats.vanished += 1;
+            tracing::trace!("pid {} exited while being read", pid);
+        } else {
+            stats.failed += 1;
+            if self.failure_reported.swap(true, Ordering::Relaxed) {
+                tracing::debug!("Failed to read pid {}: {}", pid, error);
+            } else {
+                tracing::warn!("Failed to read pid {}: {}", pid, error);
             }
         }
-
-        Ok(None)
     }
 }
 
@@ -144,35 +382,225 @@ impl ProcessSource for ProcfsProcessSource {
     async fn list_processes(
         &self,
     ) -> Result<Vec<Process>, Box<dyn std::error::Error + Send + Sync>> {
-        let pids = self.list_pids()?;
-        let mut processes = Vec::new();
-
-        for pid in pids {
-            if let Ok(process) = self.read_process(pid) {
-                processes.push(process);
-            }
-        }
-
-        Ok(processes)
+        self.scan(ALWAYS_READ, &ProcessFilter::default())
     }
 
     async fn get_top_by_cpu(
         &self,
         n: usize,
+        filter: &ProcessFilter,
     ) -> Result<Vec<Process>, Box<dyn std::error::Error + Send + Sync>> {
-        let mut processes = self.list_processes().await?;
-        processes.sort_by(|a, b| b.cpu_percent.partial_cmp(&a.cpu_percent).unwrap());
-        processes.truncate(n);
-        Ok(processes)
+        self.top(n, filter, ProcessOrder::Cpu)
     }
 
     async fn get_top_by_memory(
         &self,
         n: usize,
+        filter: &ProcessFilter,
     ) -> Result<Vec<Process>, Box<dyn std::error::Error + Send + Sync>> {
-        let mut processes = self.list_processes().await?;
-        processes.sort_by(|a, b| b.memory_bytes.cmp(&a.memory_bytes));
-        processes.truncate(n);
-        Ok(processes)
+        self.top(n, filter, ProcessOrder::Memory)
+    }
+
+    async fn get_top_by_nice(
+        &self,
+        n: usize,
+        filter: &ProcessFilter,
+    ) -> Result<Vec<Process>, Box<dyn std::error::Error + Send + Sync>> {
+        self.top(n, filter, ProcessOrder::Nice)
+    }
+
+    fn last_scan(&self) -> Option<ProcessScanStats> {
+        *self.last_scan.lock().unwrap()
+    }
+}
+
+/// `PF_KTHREAD` in the flags field of /proc/{pid}/stat
+const PF_KTHREAD: u32 = 0x0020_0000;
+
+/// Kernel threads carry `PF_KTHREAD`; kthreadd and its children with an empty
+/// (but readable) cmdline are caught as well in case the flag layout ever moves
+fn is_kernel_thread(pid: u32, ppid: u32, flags: u32, cmdline: Option<&[u8]>) -> bool {
+    flags & PF_KTHREAD != 0 || (cmdline.is_some_and(|c| c.is_empty()) && (pid == 2 || ppid == 2))
+}
+
+/// UID to user name from the contents of /etc/passwd
+fn parse_passwd(content: &str) -> HashMap<u32, String> {
+    let mut users = HashMap::new();
+    for line in content.lines() {
+        let parts: Vec<&str> = line.split(':').collect();
+        if parts.len() >= 3 {
+            if let Ok(uid) = parts[2].parse::<u32>() {
+                // First entry wins, as with getpwuid
+                users.entry(uid).or_insert_with(|| parts[0].to_string());
+            }
+        }
+    }
+    users
+}
+
+/// Docker container of a process, from the contents of /proc/{pid}/cgroup
+fn container_id_from_cgroup(content: &str) -> Option<crate::domain::ContainerId> {
+    // Look for docker container ID in cgroup path
+    // cgroupfs driver:  0::/docker/{container_id}
+    // systemd driver:   0::/system.slice/docker-{container_id}.scope
+    for line in content.lines() {
+        let id = line.split("/docker/").nth(1).or_else(|| {
+            line.split("/docker-")
+                .nth(1)
+                .and_then(|s| s.strip_suffix(".scope"))
+        });
+        if let Some(id) = id {
+            let container_id = id.trim_end_matches(".scope").to_string();
+            if !container_id.is_empty() {
+                return Some(container_id.into());
+            }
+        }
+    }
+    None
+}
+
+/// PIDs among the first `n` matching `filter` by CPU, by memory and by nice
+fn top_pids(
+    candidates: &[(parser::ProcStat, Process)],
+    n: usize,
+    filter: &ProcessFilter,
+) -> HashSet<u32> {
+    [ProcessOrder::Cpu, ProcessOrder::Memory, ProcessOrder::Nice]
+        .iter()
+        .flat_map(|order| order.top(candidates.iter().map(|(_, p)| p), n, filter))
+        .map(|p| p.pid)
+        .collect()
+}
+
+/// ENOENT/ESRCH: the process exited between readdir and the read, not a bug
+fn has_exited(error: &(dyn std::error::Error + Send + Sync + 'static)) -> bool {
+    error.downcast_ref::<io::Error>().is_some_and(|e| {
+        e.kind() == io::ErrorKind::NotFound || e.raw_os_error() == Some(Errno::ESRCH as i32)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Root can read anything, so an unprivileged UID is simulated with a procfs
+    /// fixture where the files it would be denied are absent
+    #[tokio::test]
+    async fn test_unreadable_status_degrades_to_partial() {
+        let proc = std::env::temp_dir().join(format!("nanomon-proc-{}", std::process::id()));
+        let pid = proc.join("4242");
+        fs::create_dir_all(&pid).unwrap();
+        fs::write(proc.join("uptime"), "1000.00 900.00\n").unwrap();
+        fs::write(proc.join("meminfo"), "MemTotal: 1024 kB\n").unwrap();
+        fs::write(
+            pid.join("stat"),
+            "4242 (backup) S 1 4242 4242 0 -1 0 0 0 0 0 50 25 0 0 20 0 1 0 100 0 64\n",
+        )
+        .unwrap();
+        fs::write(pid.join("comm"), "backup\n").unwrap();
+
+        let source = ProcfsProcessSource::new(ProcfsConfig::new(&proc, "/sys"));
+        let processes = source.list_processes().await.unwrap();
+        fs::remove_dir_all(&proc).unwrap();
+
+        assert_eq!(processes.len(), 1);
+        assert!(processes[0].partial);
+        assert!(processes[0].user.is_empty());
+        assert_eq!(processes[0].command, "backup");
+        assert_eq!(processes[0].memory_bytes, 64 * 4096);
+    }
+
+    #[test]
+    fn test_is_kernel_thread() {
+        assert!(is_kernel_thread(57, 2, PF_KTHREAD | 0x40, Some(b"")));
+        assert!(is_kernel_thread(57, 2, 0, Some(b"")));
+        // Zombies and hidepid also leave cmdline empty or unreadable
+        assert!(!is_kernel_thread(4242, 1, 0x40, Some(b"")));
+        assert!(!is_kernel_thread(57, 2, 0, None));
+        assert!(!is_kernel_thread(812, 1, 0x40, Some(b"/usr/bin/dockerd\0")));
+    }
+
+    #[tokio::test]
+    async fn test_vanished_pids_are_told_apart_from_failures() {
+        let proc = std::env::temp_dir().join(format!("nanomon-race-{}", std::process::id()));
+        fs::create_dir_all(proc.join("100")).unwrap();
+        fs::create_dir_all(proc.join("200")).unwrap();
+        // Exited after readdir: the directory listing is stale, its files are gone
+        fs::create_dir_all(proc.join("300")).unwrap();
+        fs::write(proc.join("uptime"), "1000.00 900.00\n").unwrap();
+        fs::write(proc.join("meminfo"), "MemTotal: 1024 kB\n").unwrap();
+        fs::write(
+            proc.join("100/stat"),
+            "100 (ok) S 1 100 100 0 -1 0 0 0 0 0 50 25 0 0 20 0 1 0 100 0 64\n",
+        )
+        .unwrap();
+        fs::write(proc.join("200/stat"), "garbage\n").unwrap();
+
+        let source = ProcfsProcessSource::new(ProcfsConfig::new(&proc, "/sys"));
+        let processes = source.list_processes().await.unwrap();
+        fs::remove_dir_all(&proc).unwrap();
+
+        assert_eq!(processes.len(), 1);
+        assert_eq!(
+            source.last_scan(),
+            Some(ProcessScanStats {
+                read: 1,
+                vanished: 1,
+                failed: 1,
+                ..Default::default()
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sampling_keeps_top_processes_exact() {
+        let proc = std::env::temp_dir().join(format!("nanomon-sample-{}", std::process::id()));
+        let write = |pid: u32, name: &str, utime: u64, rss: u64| {
+            let dir = proc.join(pid.to_string());
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(
+                dir.join("stat"),
+                format!(
+                    "{pid} ({name}) S 1 {pid} {pid} 0 -1 0 0 0 0 0 {utime} 0 0 0 20 0 1 0 100 0 {rss}\n"
+                ),
+            )
+            .unwrap();
+            fs::write(dir.join("cmdline"), format!("/usr/bin/{name}\0")).unwrap();
+        };
+        fs::create_dir_all(&proc).unwrap();
+        fs::write(proc.join("uptime"), "1000.00 900.00\n").unwrap();
+        fs::write(proc.join("meminfo"), "MemTotal: 1024 kB\n").unwrap();
+        write(10, "busy", 50000, 1);
+        write(20, "big", 0, 200);
+        write(30, "idle", 0, 1);
+        write(41, "idle", 0, 1);
+
+        let floor = ProcfsProcessSource::new(ProcfsConfig::new(&proc, "/sys"))
+            .with_sampling("floor:10,10".parse().unwrap(), 2);
+        let mut pids: Vec<u32> = floor
+            .scan(1, &ProcessFilter::default())
+            .unwrap()
+            .iter()
+            .map(|p| p.pid)
+            .collect();
+        pids.sort_unstable();
+        assert_eq!(pids, [10, 20]);
+        assert_eq!(floor.last_scan().unwrap().skipped, 2);
+
+        let shards = ProcfsProcessSource::new(ProcfsConfig::new(&proc, "/sys"))
+            .with_sampling("shards:2".parse().unwrap(), 2);
+        shards.scan(1, &ProcessFilter::default()).unwrap();
+        // A renamed process in the skipped shard shows its old command until re-read
+        write(30, "renamed", 0, 1);
+        let processes = shards.scan(1, &ProcessFilter::default()).unwrap();
+        fs::remove_dir_all(&proc).unwrap();
+
+        assert_eq!(processes.len(), 4);
+        assert_eq!(shards.last_scan().unwrap().cached, 1);
+        assert_eq!(processes[0].command, "/usr/bin/busy");
+        assert!(processes
+            .iter()
+            .any(|p| p.command == "/usr/bin/idle" && p.pid == 30));
+        assert!("shards:1".parse::<ProcessSampling>().is_err());
     }
 }
@@ -0,0 +1,79 @@
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+/// Most per-PID files fit; larger ones grow the buffer once and keep it
+const INITIAL_CAPACITY: usize = 4096;
+
+/// Reads procfs files into buffers reused from one file to the next, so a
+/// process scan does not allocate a `String` and a `PathBuf` per file read
+pub struct ProcReader {
+    root: PathBuf,
+    path: PathBuf,
+    pid: String,
+    buf: Vec<u8>,
+}
+
+impl ProcReader {
+    pub fn new(root: &Path) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            path: PathBuf::new(),
+            pid: String::new(),
+            buf: Vec::with_capacity(INITIAL_CAPACITY),
+        }
+    }
+
+    /// Contents of `<root>/<pid>/<name>`, valid until the next read
+    pub fn read_pid(&mut self, pid: u32, name: &str) -> io::Result<&[u8]> {
+        self.pid.clear();
+        let _ = write!(self.pid, "{}", pid);
+        self.path.clear();
+        self.path.push(&self.root);
+        self.path.push(&self.pid);
+        self.path.push(name);
+        read_into(&self.path, &mut self.buf)?;
+        Ok(&self.buf)
+    }
+
+    /// `read_pid` for text files; invalid UTF-8 is an `InvalidData` error
+    pub fn read_pid_str(&mut self, pid: u32, name: &str) -> io::Result<&str> {
+        let bytes = self.read_pid(pid, name)?;
+        std::str::from_utf8(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+fn read_into(path: &Path, buf: &mut Vec<u8>) -> io::Result<()> {
+    buf.clear();
+    File::open(path)?.read_to_end(buf)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buffer_is_reused() {
+        let root = std::env::temp_dir().join(format!("nanomon-reader-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("7")).unwrap();
+        std::fs::write(root.join("7/comm"), "sshd\n").unwrap();
+        std::fs::write(root.join("7/cmdline"), b"\xff\0").unwrap();
+
+        let mut reader = ProcReader::new(&root);
+        assert_eq!(reader.read_pid_str(7, "comm").unwrap(), "sshd\n");
+        let capacity = reader.buf.capacity();
+        assert_eq!(reader.read_pid(7, "cmdline").unwrap(), b"\xff\0");
+        assert_eq!(reader.buf.capacity(), capacity);
+        assert_eq!(
+            reader.read_pid_str(7, "cmdline").unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+        assert_eq!(
+            reader.read_pid(8, "stat").unwrap_err().kind(),
+            io::ErrorKind::NotFound
+        );
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
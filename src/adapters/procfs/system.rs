@@ -1,17 +1,46 @@
+use std::collections::HashMap;
 use std::fs;
 use std::sync::Mutex;
 
 use async_trait::async_trait;
 
 use crate::domain::{
-    CpuMetrics, Disk, LoadAverage, MemoryMetrics, NetworkInterface, NetworkMetrics, Temperature,
-    TemperatureSource,
+    CpuMetrics, Disk, HugePages, KernelLimit, KernelLimits, LoadAverage, MemoryMetrics,
+    MemoryTopology, NetworkInterface, NumaNode, QueueStats, Temperature, TemperatureSource,
 };
 use crate::ports::{HostInfo, SystemSource};
 
 use super::parser::{self, CpuStat};
 use super::ProcfsConfig;
 
+/// SSID the interface is associated with, from `iw` since procfs has no
+/// trace of it; `None` when `iw` is missing
+async fn wireless_ssid(interface: &str) -> Option<String> {
+    let output = tokio::process::Command::new("iw")
+        .args(["dev", interface, "link"])
+        .output()
+        .await
+        .ok()?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("SSID: "))
+        .map(String::from)
+}
+
+/// Per-queue counters from `ethtool -S`; empty when `ethtool` is missing
+async fn nic_queues(interface: &str) -> Vec<QueueStats> {
+    match tokio::process::Command::new("ethtool")
+        .args(["-S", interface])
+        .output()
+        .await
+    {
+        Ok(output) if output.status.success() => {
+            parser::parse_ethtool_queues(&String::from_utf8_lossy(&output.stdout))
+        }
+        _ => Vec::new(),
+    }
+}
+
 /// System source implementation using procfs
 pub struct ProcfsSystemSource {
     config: ProcfsConfig,
@@ -26,12 +55,8 @@ impl ProcfsSystemSource {
         }
     }
 
-    fn read_file(&self, path: &str) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-        Ok(fs::read_to_string(path)?)
-    }
-
     fn get_hostname(&self) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-        let content = self.read_file("/etc/hostname")?;
+        let content = fs::read_to_string(self.config.etc_path.join("hostname"))?;
         Ok(content.trim().to_string())
     }
 
@@ -105,9 +130,13 @@ impl SystemSource for ProcfsSystemSource {
         let meminfo = parser::parse_meminfo(&meminfo_content)?;
 
         let total = *meminfo.get("MemTotal").unwrap_or(&0);
-        let available = *meminfo.get("MemAvailable").unwrap_or(&0);
         let cached = *meminfo.get("Cached").unwrap_or(&0);
         let buffers = *meminfo.get("Buffers").unwrap_or(&0);
+        // MemAvailable appeared in Linux 3.14; estimate it like `free` did before
+        let available = meminfo
+            .get("MemAvailable")
+            .copied()
+            .unwrap_or_else(|| meminfo.get("MemFree").copied().unwrap_or(0) + cached + buffers);
         let swap_total = *meminfo.get("SwapTotal").unwrap_or(&0);
         let swap_free = *meminfo.get("SwapFree").unwrap_or(&0);
 
@@ -164,9 +193,9 @@ impl SystemSource for ProcfsSystemSource {
             // Try to get disk stats using statvfs
             if let Ok(stat) = nix::sys::statvfs::statvfs(mount.mount_point.as_str()) {
                 let block_size = stat.block_size();
-                let total_bytes = stat.blocks() as u64 * block_size;
-                let available_bytes = stat.blocks_available() as u64 * block_size;
-                let free_bytes = stat.blocks_free() as u64 * block_size;
+                let total_bytes = stat.blocks() * block_size;
+                let available_bytes = stat.blocks_available() * block_size;
+                let free_bytes = stat.blocks_free() * block_size;
                 let used_bytes = total_bytes.saturating_sub(free_bytes);
 
                 // Skip disks with zero capacity (virtual filesystems)
@@ -193,11 +222,20 @@ impl SystemSource for ProcfsSystemSource {
     ) -> Result<Vec<NetworkInterface>, Box<dyn std::error::Error + Send + Sync>> {
         let net_class_path = self.config.sys_path.join("class/net");
         let mut interfaces = Vec::new();
+        let proc_net = |file: &str| {
+            fs::read_to_string(self.config.proc_path.join("net").join(file)).unwrap_or_default()
+        };
+        let mut addresses = parser::parse_interface_addresses(
+            &proc_net("fib_trie"),
+            &proc_net("route"),
+            &proc_net("if_inet6"),
+        );
+        let mut wireless = parser::parse_wireless(&proc_net("wireless"));
 
-        let entries = fs::read_dir(&net_class_path)?;
+        let mut entries = fs::read_dir(&net_class_path)?.collect::<Result<Vec<_>, _>>()?;
+        entries.sort_by_key(|e| e.file_name());
 
         for entry in entries {
-            let entry = entry?;
             let interface_name = entry.file_name().to_string_lossy().to_string();
 
             // Skip loopback
@@ -212,14 +250,37 @@ impl SystemSource for ProcfsSystemSource {
                 .map(|s| s.trim() == "up")
                 .unwrap_or(false);
 
-            if let Ok((rx_bytes, tx_bytes, rx_errors, tx_errors)) =
-                parser::parse_net_stats(&stats_dir)
-            {
-                interfaces.push(NetworkInterface::new(
-                    interface_name,
-                    is_up,
-                    NetworkMetrics::new(rx_bytes, tx_bytes, rx_errors, tx_errors),
-                ));
+            if let Ok(metrics) = parser::parse_net_stats(&stats_dir) {
+                let read = |file: &str| {
+                    fs::read_to_string(entry.path().join(file))
+                        .map(|s| s.trim().to_string())
+                        .ok()
+                };
+                // `speed` is -1 or unreadable (EINVAL) without a negotiated link
+                let speed = read("speed")
+                    .and_then(|s| s.parse::<i64>().ok())
+                    .filter(|&s| s > 0)
+                    .map(|s| s as u64);
+                let mac = read("address").filter(|m| !m.is_empty() && m != "00:00:00:00:00:00");
+                let assigned = addresses.remove(&interface_name).unwrap_or_default();
+                let mut link = wireless.remove(&interface_name);
+                if let Some(link) = link.as_mut().filter(|_| is_up) {
+                    link.ssid = wireless_ssid(&interface_name).await;
+                }
+                // Only hardware NICs have queues worth reporting
+                let queues = if is_up && entry.path().join("device").exists() {
+                    nic_queues(&interface_name).await
+                } else {
+                    Vec::new()
+                };
+
+                interfaces.push(
+                    NetworkInterface::new(interface_name, is_up, metrics)
+                        .with_link(speed, read("mtu").and_then(|m| m.parse().ok()), mac)
+                        .with_addresses(assigned)
+                        .with_wireless(link)
+                        .with_queues(queues),
+                );
             }
         }
 
@@ -234,8 +295,9 @@ impl SystemSource for ProcfsSystemSource {
         // Try hwmon sensors first (more reliable on NAS)
         let hwmon_path = self.config.sys_path.join("class/hwmon");
         if let Ok(entries) = fs::read_dir(&hwmon_path) {
-            for entry in entries.flatten() {
-                let hwmon_dir = entry.path();
+            let mut dirs: Vec<_> = entries.flatten().map(|e| e.path()).collect();
+            dirs.sort();
+            for hwmon_dir in dirs {
                 let device_name = fs::read_to_string(hwmon_dir.join("name"))
                     .map(|s| s.trim().to_string())
                     .unwrap_or_default();
@@ -300,8 +362,9 @@ impl SystemSource for ProcfsSystemSource {
         if temps.is_empty() {
             let thermal_path = self.config.sys_path.join("class/thermal");
             if let Ok(entries) = fs::read_dir(&thermal_path) {
-                for entry in entries.flatten() {
-                    let zone_dir = entry.path();
+                let mut zones: Vec<_> = entries.flatten().map(|e| e.path()).collect();
+                zones.sort();
+                for zone_dir in zones {
                     let name = zone_dir
                         .file_name()
                         .unwrap_or_default()
@@ -344,6 +407,140 @@ impl SystemSource for ProcfsSystemSource {
         tracing::debug!("Found {} temperature sensors", temps.len());
         Ok(temps)
     }
+
+    async fn get_kernel_limits(
+        &self,
+    ) -> Result<KernelLimits, Box<dyn std::error::Error + Send + Sync>> {
+        let read_u64 = |path: &str| -> Option<u64> {
+            fs::read_to_string(self.config.proc_path.join(path))
+                .ok()?
+                .trim()
+                .parse()
+                .ok()
+        };
+
+        let file_handles = fs::read_to_string(self.config.proc_path.join("sys/fs/file-nr"))
+            .ok()
+            .and_then(|c| parser::parse_file_nr(&c).ok())
+            .map(|(used, max)| KernelLimit::new(used, max));
+
+        let threads = fs::read_to_string(self.config.proc_path.join("loadavg"))
+            .ok()
+            .and_then(|c| parser::parse_loadavg_threads(&c).ok());
+        let pids = threads
+            .zip(read_u64("sys/kernel/pid_max"))
+            .map(|(used, max)| KernelLimit::new(used, max));
+
+        // Only present when the nf_conntrack module is loaded
+        let conntrack = read_u64("sys/net/netfilter/nf_conntrack_count")
+            .zip(read_u64("sys/net/netfilter/nf_conntrack_max"))
+            .map(|(used, max)| KernelLimit::new(used, max));
+
+        Ok(KernelLimits {
+            file_handles,
+            pids,
+            conntrack,
+            entropy_bits: read_u64("sys/kernel/random/entropy_avail"),
+        })
+    }
+
+    async fn get_memory_topology(
+        &self,
+    ) -> Result<MemoryTopology, Box<dyn std::error::Error + Send + Sync>> {
+        let meminfo =
+            parser::parse_meminfo(&fs::read_to_string(self.config.proc_path.join("meminfo"))?)?;
+        let page_size = meminfo.get("Hugepagesize").copied().unwrap_or(0);
+        let hugepages = hugepages_from_meminfo(&meminfo, page_size);
+
+        let mut numa_nodes = Vec::new();
+        // Absent on kernels built without CONFIG_NUMA
+        let node_root = self.config.sys_path.join("devices/system/node");
+        if let Ok(entries) = fs::read_dir(&node_root) {
+            for entry in entries.flatten() {
+                let name = entry.file_name();
+                let Some(node) = name
+                    .to_str()
+                    .and_then(|n| n.strip_prefix("node"))
+                    .and_then(|n| n.parse::<u32>().ok())
+                else {
+                    continue;
+                };
+                let Ok(content) = fs::read_to_string(entry.path().join("meminfo")) else {
+                    continue;
+                };
+                let meminfo = parser::parse_node_meminfo(&content)?;
+                let total = meminfo.get("MemTotal").copied().unwrap_or(0);
+                let free = meminfo.get("MemFree").copied().unwrap_or(0);
+                numa_nodes.push(NumaNode {
+                    node,
+                    total_bytes: total,
+                    free_bytes: free,
+                    used_bytes: total.saturating_sub(free),
+                    hugepages: hugepages_from_meminfo(&meminfo, page_size).map(|h| HugePages {
+                        reserved: None,
+                        ..h
+                    }),
+                });
+            }
+        }
+        numa_nodes.sort_by_key(|n| n.node);
+
+        Ok(MemoryTopology {
+            hugepages,
+            numa_nodes,
+        })
+    }
+
+    async fn connections_by_port(
+        &self,
+    ) -> Result<Option<HashMap<(String, u16), u64>>, Box<dyn std::error::Error + Send + Sync>> {
+        // Needs nf_conntrack loaded with its procfs interface
+        Ok(
+            fs::read_to_string(self.config.proc_path.join("net/nf_conntrack"))
+                .ok()
+                .map(|content| parser::parse_conntrack_ports(&content)),
+        )
+    }
+
+    async fn default_route_interface(
+        &self,
+    ) -> Result<Option<String>, Box<dyn std::error::Error + Send + Sync>> {
+        let route_path = self.config.proc_path.join("net/route");
+        let content = fs::read_to_string(&route_path)?;
+        Ok(parser::parse_default_route(&content))
+    }
+
+    async fn thermal_throttle_count(
+        &self,
+    ) -> Result<Option<u64>, Box<dyn std::error::Error + Send + Sync>> {
+        // Only x86 CPUs with a thermal monitor have these counters
+        let Ok(entries) = fs::read_dir(self.config.sys_path.join("devices/system/cpu")) else {
+            return Ok(None);
+        };
+        let mut total = None;
+        for entry in entries.flatten() {
+            let Ok(content) =
+                fs::read_to_string(entry.path().join("thermal_throttle/core_throttle_count"))
+            else {
+                continue;
+            };
+            if let Ok(count) = content.trim().parse::<u64>() {
+                *total.get_or_insert(0) += count;
+            }
+        }
+        Ok(total)
+    }
+}
+
+/// Hugepage counters of a (node) meminfo, absent without hugetlbfs support
+fn hugepages_from_meminfo(meminfo: &HashMap<String, u64>, page_size: u64) -> Option<HugePages> {
+    Some(HugePages {
+        total: *meminfo.get("HugePages_Total")?,
+        free: meminfo.get("HugePages_Free").copied().unwrap_or(0),
+        reserved: meminfo.get("HugePages_Rsvd").copied(),
+        surplus: meminfo.get("HugePages_Surp").copied().unwrap_or(0),
+        page_size_bytes: page_size,
+    })
 }
 
 // Need nix for statvfs
@@ -0,0 +1,62 @@
+#[cfg(feature = "webhook")]
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use crate::domain::Host;
+use crate::ports::SnapshotPublisher;
+
+/// Pushes snapshots as JSON to an aggregator's `/api/v1/ingest`, so agents
+/// behind NAT need no inbound connection
+pub struct HttpSnapshotPublisher {
+    #[cfg(feature = "webhook")]
+    client: reqwest::Client,
+    url: String,
+    #[cfg_attr(not(feature = "webhook"), allow(dead_code))]
+    token: Option<String>,
+}
+
+impl HttpSnapshotPublisher {
+    pub fn new(url: String, token: Option<String>) -> Self {
+        Self {
+            #[cfg(feature = "webhook")]
+            client: reqwest::Client::builder()
+                .timeout(Duration::from_secs(10))
+                .build()
+                .expect("Failed to build HTTP client"),
+            url,
+            token,
+        }
+    }
+}
+
+#[async_trait]
+impl SnapshotPublisher for HttpSnapshotPublisher {
+    #[cfg(feature = "webhook")]
+    async fn publish(
+        &self,
+        snapshot: &Host,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut request = self.client.post(&self.url).json(snapshot);
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+        let response = request.send().await?;
+        if !response.status().is_success() {
+            return Err(format!("aggregator returned status {}", response.status()).into());
+        }
+        Ok(())
+    }
+
+    #[cfg(not(feature = "webhook"))]
+    async fn publish(
+        &self,
+        _snapshot: &Host,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        Err(format!(
+            "built without the webhook feature, cannot push to {}",
+            self.url
+        )
+        .into())
+    }
+}
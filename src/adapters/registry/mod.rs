@@ -0,0 +1,58 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::domain::{CollectorConfig, CollectorKind, PluginConfig};
+use crate::ports::{AlertSink, MetricCollector};
+
+use super::plugin::{PluginAlertSink, PluginCollector, PluginProcess};
+use super::script::ScriptCollector;
+use super::textfile::TextfileCollector;
+
+/// Builds the collector behind each `[[collectors]]` entry by type, and owns
+/// the `[[plugins]]` processes shared by collectors and notifications
+pub struct CollectorRegistry {
+    plugins: HashMap<String, Arc<PluginProcess>>,
+}
+
+impl CollectorRegistry {
+    pub fn new(plugins: Vec<PluginConfig>) -> Self {
+        Self {
+            plugins: plugins
+                .into_iter()
+                .map(|p| (p.name.clone(), Arc::new(PluginProcess::new(p))))
+                .collect(),
+        }
+    }
+
+    pub fn build(&self, config: &CollectorConfig) -> Result<Arc<dyn MetricCollector>, String> {
+        match &config.kind {
+            CollectorKind::Script { command } => Ok(Arc::new(ScriptCollector::new(
+                config.name.clone(),
+                command.clone(),
+                Duration::from_secs(config.timeout_seconds),
+            ))),
+            CollectorKind::Plugin { plugin } => {
+                let process = self
+                    .plugins
+                    .get(plugin)
+                    .ok_or_else(|| format!("no [[plugins]] entry named {}", plugin))?;
+                Ok(Arc::new(PluginCollector::new(
+                    config.name.clone(),
+                    process.clone(),
+                )))
+            }
+            CollectorKind::Textfile { directory } => {
+                Ok(Arc::new(TextfileCollector::new(directory.clone())))
+            }
+        }
+    }
+
+    /// Wrap a sink so notifications to `plugin://<name>` reach that plugin
+    pub fn notifier(&self, inner: Arc<dyn AlertSink>) -> Arc<dyn AlertSink> {
+        if self.plugins.is_empty() {
+            return inner;
+        }
+        Arc::new(PluginAlertSink::new(inner, self.plugins.clone()))
+    }
+}
@@ -0,0 +1,140 @@
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+
+use crate::domain::{Container, ContainerId, ContainerMount};
+use crate::ports::{ContainerSource, ContainerStats};
+
+#[derive(Debug, Default)]
+struct BreakerState {
+    consecutive_failures: u32,
+    open_until: Option<Instant>,
+}
+
+/// Stops calling a failing dependency for a cooldown period after
+/// `threshold` consecutive failures, then lets calls through again
+pub struct CircuitBreaker {
+    name: &'static str,
+    threshold: u32,
+    cooldown: Duration,
+    state: Mutex<BreakerState>,
+}
+
+impl CircuitBreaker {
+    pub fn new(name: &'static str, threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            name,
+            threshold: threshold.max(1),
+            cooldown,
+            state: Mutex::new(BreakerState::default()),
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.state
+            .lock()
+            .unwrap()
+            .open_until
+            .is_some_and(|until| Instant::now() < until)
+    }
+
+    /// Runs `call` unless the circuit is open
+    pub async fn call<R>(
+        &self,
+        call: impl Future<Output = Result<R, Box<dyn std::error::Error + Send + Sync>>>,
+    ) -> Result<R, Box<dyn std::error::Error + Send + Sync>> {
+        if self.is_open() {
+            return Err(format!("{} circuit open after repeated failures", self.name).into());
+        }
+        let result = call.await;
+        self.record(result.is_ok());
+        result
+    }
+
+    fn record(&self, success: bool) {
+        let mut state = self.state.lock().unwrap();
+        if success {
+            if state.open_until.take().is_some() {
+                tracing::info!("{} recovered, circuit closed", self.name);
+            }
+            state.consecutive_failures = 0;
+            return;
+        }
+
+        state.consecutive_failures += 1;
+        // After the cooldown a single failed trial call reopens the circuit
+        if state.consecutive_failures >= self.threshold {
+            if state.open_until.is_none() {
+                tracing::warn!(
+                    "{} failed {} times in a row, pausing calls for {:?}",
+                    self.name,
+                    state.consecutive_failures,
+                    self.cooldown
+                );
+            }
+            state.open_until = Some(Instant::now() + self.cooldown);
+        }
+    }
+}
+
+/// Container source guarded by a circuit breaker, so an unresponsive
+/// Docker daemon fails fast instead of stalling every collection
+pub struct CircuitBreakerContainerSource<T: ?Sized> {
+    inner: Arc<T>,
+    breaker: CircuitBreaker,
+}
+
+impl<T: ContainerSource + ?Sized> CircuitBreakerContainerSource<T> {
+    pub fn new(inner: Arc<T>, breaker: CircuitBreaker) -> Self {
+        Self { inner, breaker }
+    }
+}
+
+#[async_trait]
+impl<T: ContainerSource + ?Sized> ContainerSource for CircuitBreakerContainerSource<T> {
+    async fn list_containers(
+        &self,
+    ) -> Result<Vec<Container>, Box<dyn std::error::Error + Send + Sync>> {
+        self.breaker.call(self.inner.list_containers()).await
+    }
+
+    async fn get_container_stats(
+        &self,
+        id: &ContainerId,
+    ) -> Result<ContainerStats, Box<dyn std::error::Error + Send + Sync>> {
+        self.breaker.call(self.inner.get_container_stats(id)).await
+    }
+
+    async fn list_mounts(
+        &self,
+        id: &ContainerId,
+    ) -> Result<Option<Vec<ContainerMount>>, Box<dyn std::error::Error + Send + Sync>> {
+        self.breaker.call(self.inner.list_mounts(id)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_opens_after_threshold_and_recovers() {
+        let breaker = CircuitBreaker::new("docker", 2, Duration::from_millis(20));
+        let fail = || async { Err::<(), Box<dyn std::error::Error + Send + Sync>>("down".into()) };
+
+        assert!(breaker.call(fail()).await.is_err());
+        assert!(!breaker.is_open());
+        assert!(breaker.call(fail()).await.is_err());
+        assert!(breaker.is_open());
+
+        // Rejected without running the call
+        let result = breaker.call(async { Ok(()) }).await;
+        assert!(result.unwrap_err().to_string().contains("circuit open"));
+
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert!(breaker.call(async { Ok(()) }).await.is_ok());
+        assert!(!breaker.is_open());
+    }
+}
@@ -0,0 +1,7 @@
+mod circuit_breaker;
+mod retry;
+mod timeout;
+
+pub use circuit_breaker::{CircuitBreaker, CircuitBreakerContainerSource};
+pub use retry::RetryingContainerSource;
+pub use timeout::TimeoutSystemSource;
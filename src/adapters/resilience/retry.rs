@@ -0,0 +1,140 @@
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use crate::domain::{Container, ContainerId, ContainerMount};
+use crate::ports::{ContainerSource, ContainerStats};
+
+/// Retries failed calls to a container source with exponential backoff
+pub struct RetryingContainerSource<T: ?Sized> {
+    inner: Arc<T>,
+    retries: u32,
+    backoff: Duration,
+}
+
+impl<T: ContainerSource + ?Sized> RetryingContainerSource<T> {
+    /// `retries` extra attempts, the first one after `backoff`, doubling each time
+    pub fn new(inner: Arc<T>, retries: u32, backoff: Duration) -> Self {
+        Self {
+            inner,
+            retries,
+            backoff,
+        }
+    }
+
+    async fn retry<R, F, Fut>(
+        &self,
+        operation: &str,
+        call: F,
+    ) -> Result<R, Box<dyn std::error::Error + Send + Sync>>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<R, Box<dyn std::error::Error + Send + Sync>>>,
+    {
+        let mut delay = self.backoff;
+        let mut attempt = 0;
+        loop {
+            match call().await {
+                Err(e) if attempt < self.retries => {
+                    attempt += 1;
+                    tracing::debug!(
+                        "{} failed ({}), retry {}/{} in {:?}",
+                        operation,
+                        e,
+                        attempt,
+                        self.retries,
+                        delay
+                    );
+                    tokio::time::sleep(delay).await;
+                    delay *= 2;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl<T: ContainerSource + ?Sized> ContainerSource for RetryingContainerSource<T> {
+    async fn list_containers(
+        &self,
+    ) -> Result<Vec<Container>, Box<dyn std::error::Error + Send + Sync>> {
+        self.retry("list_containers", || self.inner.list_containers())
+            .await
+    }
+
+    async fn get_container_stats(
+        &self,
+        id: &ContainerId,
+    ) -> Result<ContainerStats, Box<dyn std::error::Error + Send + Sync>> {
+        self.retry("get_container_stats", || self.inner.get_container_stats(id))
+            .await
+    }
+
+    async fn list_mounts(
+        &self,
+        id: &ContainerId,
+    ) -> Result<Option<Vec<ContainerMount>>, Box<dyn std::error::Error + Send + Sync>> {
+        self.retry("list_mounts", || self.inner.list_mounts(id))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    /// Fails the first `failures` calls to `list_containers`
+    struct Flaky {
+        failures: u32,
+        calls: AtomicU32,
+    }
+
+    #[async_trait]
+    impl ContainerSource for Flaky {
+        async fn list_containers(
+            &self,
+        ) -> Result<Vec<Container>, Box<dyn std::error::Error + Send + Sync>> {
+            if self.calls.fetch_add(1, Ordering::SeqCst) < self.failures {
+                Err("daemon busy".into())
+            } else {
+                Ok(Vec::new())
+            }
+        }
+
+        async fn get_container_stats(
+            &self,
+            _id: &ContainerId,
+        ) -> Result<ContainerStats, Box<dyn std::error::Error + Send + Sync>> {
+            Err("unused".into())
+        }
+
+        async fn list_mounts(
+            &self,
+            _id: &ContainerId,
+        ) -> Result<Option<Vec<ContainerMount>>, Box<dyn std::error::Error + Send + Sync>> {
+            Ok(None)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retries_until_success_or_exhausted() {
+        let flaky = Arc::new(Flaky {
+            failures: 2,
+            calls: AtomicU32::new(0),
+        });
+        let source = RetryingContainerSource::new(flaky.clone(), 2, Duration::from_millis(1));
+        assert!(source.list_containers().await.is_ok());
+        assert_eq!(flaky.calls.load(Ordering::SeqCst), 3);
+
+        let flaky = Arc::new(Flaky {
+            failures: 3,
+            calls: AtomicU32::new(0),
+        });
+        let source = RetryingContainerSource::new(flaky, 2, Duration::from_millis(1));
+        assert!(source.list_containers().await.is_err());
+    }
+}
@@ -0,0 +1,125 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use crate::domain::{
+    CpuMetrics, Disk, KernelLimits, LoadAverage, MemoryMetrics, MemoryTopology, NetworkInterface,
+    Temperature,
+};
+use crate::ports::{HostInfo, SystemSource};
+
+/// Bounds every call to a system source, e.g. `statvfs` on a hung NFS mount
+pub struct TimeoutSystemSource<T: ?Sized> {
+    inner: Arc<T>,
+    timeout: Duration,
+}
+
+impl<T: SystemSource + ?Sized> TimeoutSystemSource<T> {
+    pub fn new(inner: Arc<T>, timeout: Duration) -> Self {
+        Self { inner, timeout }
+    }
+
+    async fn bounded<R>(
+        &self,
+        operation: &str,
+        call: impl Future<Output = Result<R, Box<dyn std::error::Error + Send + Sync>>>,
+    ) -> Result<R, Box<dyn std::error::Error + Send + Sync>> {
+        tokio::time::timeout(self.timeout, call)
+            .await
+            .map_err(|_| format!("{} timed out after {:?}", operation, self.timeout))?
+    }
+}
+
+#[async_trait]
+impl<T: SystemSource + ?Sized> SystemSource for TimeoutSystemSource<T> {
+    async fn get_host_info(&self) -> Result<HostInfo, Box<dyn std::error::Error + Send + Sync>> {
+        self.bounded("get_host_info", self.inner.get_host_info())
+            .await
+    }
+
+    async fn get_cpu_metrics(
+        &self,
+    ) -> Result<CpuMetrics, Box<dyn std::error::Error + Send + Sync>> {
+        self.bounded("get_cpu_metrics", self.inner.get_cpu_metrics())
+            .await
+    }
+
+    async fn get_memory_metrics(
+        &self,
+    ) -> Result<MemoryMetrics, Box<dyn std::error::Error + Send + Sync>> {
+        self.bounded("get_memory_metrics", self.inner.get_memory_metrics())
+            .await
+    }
+
+    async fn get_load_average(
+        &self,
+    ) -> Result<LoadAverage, Box<dyn std::error::Error + Send + Sync>> {
+        self.bounded("get_load_average", self.inner.get_load_average())
+            .await
+    }
+
+    async fn list_disks(&self) -> Result<Vec<Disk>, Box<dyn std::error::Error + Send + Sync>> {
+        self.bounded("list_disks", self.inner.list_disks()).await
+    }
+
+    async fn list_network_interfaces(
+        &self,
+    ) -> Result<Vec<NetworkInterface>, Box<dyn std::error::Error + Send + Sync>> {
+        self.bounded(
+            "list_network_interfaces",
+            self.inner.list_network_interfaces(),
+        )
+        .await
+    }
+
+    async fn get_temperatures(
+        &self,
+    ) -> Result<Vec<Temperature>, Box<dyn std::error::Error + Send + Sync>> {
+        self.bounded("get_temperatures", self.inner.get_temperatures())
+            .await
+    }
+
+    async fn get_kernel_limits(
+        &self,
+    ) -> Result<KernelLimits, Box<dyn std::error::Error + Send + Sync>> {
+        self.bounded("get_kernel_limits", self.inner.get_kernel_limits())
+            .await
+    }
+
+    async fn get_memory_topology(
+        &self,
+    ) -> Result<MemoryTopology, Box<dyn std::error::Error + Send + Sync>> {
+        self.bounded("get_memory_topology", self.inner.get_memory_topology())
+            .await
+    }
+
+    async fn connections_by_port(
+        &self,
+    ) -> Result<Option<HashMap<(String, u16), u64>>, Box<dyn std::error::Error + Send + Sync>> {
+        self.bounded("connections_by_port", self.inner.connections_by_port())
+            .await
+    }
+
+    async fn default_route_interface(
+        &self,
+    ) -> Result<Option<String>, Box<dyn std::error::Error + Send + Sync>> {
+        self.bounded(
+            "default_route_interface",
+            self.inner.default_route_interface(),
+        )
+        .await
+    }
+
+    async fn thermal_throttle_count(
+        &self,
+    ) -> Result<Option<u64>, Box<dyn std::error::Error + Send + Sync>> {
+        self.bounded(
+            "thermal_throttle_count",
+            self.inner.thermal_throttle_count(),
+        )
+        .await
+    }
+}
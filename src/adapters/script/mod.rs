@@ -0,0 +1,209 @@
+use std::collections::BTreeMap;
+use std::process::Stdio;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use serde::Deserialize;
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use crate::domain::CustomMetric;
+use crate::ports::{CommandRunner, MetricCollector};
+
+/// Bytes kept of a command's stdout and of its stderr; the rest is read and
+/// dropped so the command does not block on a full pipe
+const OUTPUT_LIMIT: u64 = 1024 * 1024;
+
+/// One metric as printed by a script
+#[derive(Debug, Deserialize)]
+struct ScriptMetric {
+    name: String,
+    value: f64,
+    #[serde(default)]
+    unit: Option<String>,
+    #[serde(default)]
+    labels: BTreeMap<String, String>,
+}
+
+/// A script prints either a single metric object or an array of them
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum ScriptOutput {
+    One(ScriptMetric),
+    Many(Vec<ScriptMetric>),
+}
+
+/// Runs a user-supplied command and reads its metrics as JSON from stdout
+pub struct ScriptCollector {
+    name: String,
+    command: Vec<String>,
+    timeout: Duration,
+}
+
+impl ScriptCollector {
+    pub fn new(name: String, command: Vec<String>, timeout: Duration) -> Self {
+        Self {
+            name,
+            command,
+            timeout,
+        }
+    }
+}
+
+#[async_trait]
+impl MetricCollector for ScriptCollector {
+    async fn collect(&self) -> Result<Vec<CustomMetric>, Box<dyn std::error::Error + Send + Sync>> {
+        let stdout = run_command(&self.command, &[], self.timeout).await?;
+        parse_output(&self.name, &stdout)
+    }
+}
+
+/// Runs configured commands as child processes of NanoMon
+#[derive(Default)]
+pub struct ScriptRunner;
+
+#[async_trait]
+impl CommandRunner for ScriptRunner {
+    async fn run(
+        &self,
+        command: &[String],
+        env: &[(String, String)],
+        timeout: Duration,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        run_command(command, env, timeout).await.map(|_| ())
+    }
+}
+
+/// Runs `command` to completion, killing it once `timeout` passes, and
+/// returns its stdout (cut at `OUTPUT_LIMIT`); fails unless it exits with 0
+async fn run_command(
+    command: &[String],
+    env: &[(String, String)],
+    timeout: Duration,
+) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
+    let (program, args) = command.split_first().ok_or("command is empty")?;
+    let mut child = tokio::process::Command::new(program)
+        .args(args)
+        .envs(env.iter().map(|(k, v)| (k, v)))
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()?;
+    let (stdout, stderr) = (child.stdout.take(), child.stderr.take());
+    let finished =
+        async { tokio::try_join!(read_capped(stdout), read_capped(stderr), child.wait()) };
+    let (stdout, stderr, status) = tokio::time::timeout(timeout, finished)
+        .await
+        .map_err(|_| format!("{} timed out after {:?}", program, timeout))??;
+
+    if !status.success() {
+        // The last line usually says what went wrong
+        let stderr = String::from_utf8_lossy(&stderr);
+        let last_line = stderr.trim().lines().last().unwrap_or_default();
+        return Err(format!("{} exited with {}: {}", program, status, last_line).into());
+    }
+    Ok(stdout)
+}
+
+/// The first `OUTPUT_LIMIT` bytes of a pipe, read to its end
+async fn read_capped(pipe: Option<impl AsyncRead + Unpin>) -> std::io::Result<Vec<u8>> {
+    let mut kept = Vec::new();
+    if let Some(mut pipe) = pipe {
+        (&mut pipe)
+            .take(OUTPUT_LIMIT)
+            .read_to_end(&mut kept)
+            .await?;
+        tokio::io::copy(&mut pipe, &mut tokio::io::sink()).await?;
+    }
+    Ok(kept)
+}
+
+/// Parse and validate a script's stdout
+fn parse_output(
+    collector: &str,
+    stdout: &[u8],
+) -> Result<Vec<CustomMetric>, Box<dyn std::error::Error + Send + Sync>> {
+    parse_metrics(collector, serde_json::from_slice(stdout)?)
+}
+
+/// Validate metrics in the script output format, shared with plugins
+pub(crate) fn parse_metrics(
+    collector: &str,
+    output: serde_json::Value,
+) -> Result<Vec<CustomMetric>, Box<dyn std::error::Error + Send + Sync>> {
+    let metrics = match serde_json::from_value(output)? {
+        ScriptOutput::One(metric) => vec![metric],
+        ScriptOutput::Many(metrics) => metrics,
+    };
+    let collected_at = Utc::now();
+
+    metrics
+        .into_iter()
+        .map(|m| {
+            if m.name.is_empty() {
+                return Err("metric without a name".into());
+            }
+            if !m.value.is_finite() {
+                return Err(format!("metric {} is not a finite number", m.name).into());
+            }
+            Ok(CustomMetric {
+                collector: collector.to_string(),
+                name: m.name,
+                value: m.value,
+                unit: m.unit,
+                labels: m.labels,
+                collected_at,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_output() {
+        let one = parse_output("ups", br#"{"name": "battery", "value": 97, "unit": "%"}"#).unwrap();
+        assert_eq!(one[0].value, 97.0);
+        assert_eq!(one[0].collector, "ups");
+
+        let many = parse_output(
+            "ups",
+            br#"[{"name": "load", "value": 0.4, "labels": {"outlet": "1"}}, {"name": "load", "value": 0.1}]"#,
+        )
+        .unwrap();
+        assert_eq!(many.len(), 2);
+        assert_eq!(many[0].labels["outlet"], "1");
+
+        assert!(parse_output("ups", br#"{"name": "", "value": 1}"#).is_err());
+        assert!(parse_output("ups", b"battery=97").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_run_command_caps_output() {
+        let sh = |script: &str| vec!["sh".to_string(), "-c".to_string(), script.to_string()];
+        let timeout = Duration::from_secs(5);
+
+        let stdout = run_command(&sh("head -c 3000000 /dev/zero"), &[], timeout)
+            .await
+            .unwrap();
+        assert_eq!(stdout.len() as u64, OUTPUT_LIMIT);
+
+        let env = [("REASON".to_string(), "disk full".to_string())];
+        let err = run_command(
+            &sh("echo starting; echo \"$REASON\" >&2; exit 3"),
+            &env,
+            timeout,
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().ends_with(": disk full"), "{}", err);
+
+        let err = run_command(&sh("sleep 5"), &[], Duration::from_millis(100))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("timed out"));
+    }
+}
@@ -0,0 +1,119 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use serde_json::Value;
+
+use crate::domain::{SpeedtestResult, SpeedtestTool};
+use crate::ports::SpeedtestRunner;
+
+/// Runs an installed speedtest binary and parses its JSON output
+pub struct CommandSpeedtestRunner {
+    tool: SpeedtestTool,
+}
+
+impl CommandSpeedtestRunner {
+    pub fn new(tool: SpeedtestTool) -> Self {
+        Self { tool }
+    }
+
+    fn command(&self) -> (&'static str, &'static [&'static str]) {
+        match self.tool {
+            SpeedtestTool::SpeedtestCli => ("speedtest-cli", &["--json"]),
+            SpeedtestTool::Ookla => (
+                "speedtest",
+                &["-f", "json", "--accept-license", "--accept-gdpr"],
+            ),
+            SpeedtestTool::Librespeed => ("librespeed-cli", &["--json"]),
+        }
+    }
+}
+
+#[async_trait]
+impl SpeedtestRunner for CommandSpeedtestRunner {
+    async fn run(&self) -> Result<SpeedtestResult, Box<dyn std::error::Error + Send + Sync>> {
+        let (program, args) = self.command();
+        let output = tokio::process::Command::new(program)
+            .args(args)
+            .output()
+            .await?;
+
+        if !output.status.success() {
+            return Err(format!(
+                "{} failed: {}",
+                program,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )
+            .into());
+        }
+
+        let json: Value = serde_json::from_slice(&output.stdout)?;
+        parse_result(self.tool, &json)
+            .ok_or_else(|| format!("unexpected {} output", program).into())
+    }
+}
+
+/// Normalize the JSON of each tool to Mbit/s and milliseconds
+fn parse_result(tool: SpeedtestTool, json: &Value) -> Option<SpeedtestResult> {
+    let (download_mbps, upload_mbps, ping_ms, server) = match tool {
+        // bits per second
+        SpeedtestTool::SpeedtestCli => (
+            json["download"].as_f64()? / 1e6,
+            json["upload"].as_f64()? / 1e6,
+            json["ping"].as_f64()?,
+            json["server"]["sponsor"].as_str(),
+        ),
+        // bytes per second
+        SpeedtestTool::Ookla => (
+            json["download"]["bandwidth"].as_f64()? * 8.0 / 1e6,
+            json["upload"]["bandwidth"].as_f64()? * 8.0 / 1e6,
+            json["ping"]["latency"].as_f64()?,
+            json["server"]["name"].as_str(),
+        ),
+        // already Mbit/s, wrapped in an array
+        SpeedtestTool::Librespeed => {
+            let entry = json.get(0).unwrap_or(json);
+            (
+                entry["download"].as_f64()?,
+                entry["upload"].as_f64()?,
+                entry["ping"].as_f64()?,
+                entry["server"]["name"].as_str(),
+            )
+        }
+    };
+
+    Some(SpeedtestResult {
+        timestamp: Utc::now(),
+        download_mbps,
+        upload_mbps,
+        ping_ms,
+        server: server.map(str::to_string),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ookla() {
+        let json: Value = serde_json::from_str(
+            r#"{"ping":{"latency":12.5},"download":{"bandwidth":12500000},
+                "upload":{"bandwidth":2500000},"server":{"name":"Paris"}}"#,
+        )
+        .unwrap();
+        let result = parse_result(SpeedtestTool::Ookla, &json).unwrap();
+        assert_eq!(result.download_mbps, 100.0);
+        assert_eq!(result.upload_mbps, 20.0);
+        assert_eq!(result.ping_ms, 12.5);
+        assert_eq!(result.server.as_deref(), Some("Paris"));
+    }
+
+    #[test]
+    fn test_parse_speedtest_cli() {
+        let json: Value =
+            serde_json::from_str(r#"{"download":94000000.0,"upload":18000000.0,"ping":9.1}"#)
+                .unwrap();
+        let result = parse_result(SpeedtestTool::SpeedtestCli, &json).unwrap();
+        assert_eq!(result.download_mbps, 94.0);
+        assert!(result.server.is_none());
+    }
+}
@@ -0,0 +1,233 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufReader, Read, Write};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::domain::Host;
+use crate::ports::{MetricStore, SnapshotCodec};
+
+use super::codec::codec_for_tag;
+
+/// Covers everything the inner store still holds
+const ALL_HISTORY: Duration = Duration::from_secs(100 * 365 * 24 * 3600);
+
+/// Persists every snapshot of an inner store to an append-only archive file,
+/// so history survives restarts. Each record is `[codec tag: u8][length: u32 LE][payload]`.
+/// The file is compacted to the inner store's contents once it holds twice as many records.
+pub struct ArchiveStore {
+    inner: Arc<dyn MetricStore>,
+    codec: Box<dyn SnapshotCodec>,
+    path: PathBuf,
+    max_records: usize,
+    writer: Mutex<ArchiveWriter>,
+}
+
+#[derive(Default)]
+struct ArchiveWriter {
+    file: Option<File>,
+    records: usize,
+}
+
+impl ArchiveStore {
+    pub fn new(
+        inner: Arc<dyn MetricStore>,
+        codec: Box<dyn SnapshotCodec>,
+        path: impl Into<PathBuf>,
+        max_records: usize,
+    ) -> Self {
+        Self {
+            inner,
+            codec,
+            path: path.into(),
+            max_records: max_records.max(1),
+            writer: Mutex::new(ArchiveWriter::default()),
+        }
+    }
+
+    /// Load archived snapshots into the inner store; returns how many were restored
+    pub fn restore(&self) -> usize {
+        let records = match File::open(&self.path) {
+            Ok(file) => read_records(BufReader::new(file)),
+            Err(_) => Vec::new(),
+        };
+        let total = records.len();
+        let skip = total.saturating_sub(self.max_records);
+        let mut restored = 0;
+        let mut unreadable = 0;
+        for (tag, payload) in records.into_iter().skip(skip) {
+            match codec_for_tag(tag).map(|codec| codec.decode(&payload)) {
+                Some(Ok(snapshot)) => {
+                    self.inner.store(snapshot);
+                    restored += 1;
+                }
+                Some(Err(e)) => {
+                    tracing::warn!("Skipping unreadable archived snapshot: {}", e);
+                    unreadable += 1;
+                }
+                None => {
+                    tracing::warn!("Skipping archived snapshot with unknown codec {}", tag);
+                    unreadable += 1;
+                }
+            }
+        }
+        if unreadable > 0 {
+            self.keep_unreadable(unreadable);
+        }
+        self.writer.lock().unwrap().records = total;
+        restored
+    }
+
+    /// Copy the archive aside before compaction rewrites it without the
+    /// records this version could not read, e.g. after a downgrade
+    fn keep_unreadable(&self, unreadable: usize) {
+        let copy = self.path.with_extension("unreadable");
+        match fs::copy(&self.path, &copy) {
+            Ok(_) => tracing::warn!(
+                "{} archived snapshot(s) could not be read; the archive was copied to {:?}",
+                unreadable,
+                copy
+            ),
+            Err(e) => tracing::warn!("Failed to copy archive to {:?}: {}", copy, e),
+        }
+    }
+
+    fn append(&self, writer: &mut ArchiveWriter, record: &[u8]) -> std::io::Result<()> {
+        if writer.file.is_none() {
+            writer.file = Some(
+                OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&self.path)?,
+            );
+        }
+        if let Some(file) = writer.file.as_mut() {
+            file.write_all(record)?;
+        }
+        writer.records += 1;
+        Ok(())
+    }
+
+    /// Rewrite the archive with only what the inner store retains
+    fn compact(
+        &self,
+        writer: &mut ArchiveWriter,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let snapshots = self.inner.get_history(ALL_HISTORY);
+        let tmp = self.path.with_extension("tmp");
+        let mut buffer = Vec::new();
+        for snapshot in &snapshots {
+            buffer.extend(frame(self.codec.tag(), &self.codec.encode(snapshot)?));
+        }
+        fs::write(&tmp, buffer)?;
+        writer.file = None;
+        fs::rename(&tmp, &self.path)?;
+        writer.records = snapshots.len();
+        Ok(())
+    }
+}
+
+impl MetricStore for ArchiveStore {
+    fn store(&self, snapshot: Host) {
+        let encoded = self.codec.encode(&snapshot);
+        self.inner.store(snapshot);
+
+        let mut writer = self.writer.lock().unwrap();
+        let result = match encoded {
+            Ok(payload) => self
+                .append(&mut writer, &frame(self.codec.tag(), &payload))
+                .map_err(Into::into),
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            tracing::warn!("Failed to archive snapshot to {:?}: {}", self.path, e);
+            return;
+        }
+        if writer.records > 2 * self.max_records {
+            if let Err(e) = self.compact(&mut writer) {
+                tracing::warn!("Failed to compact archive {:?}: {}", self.path, e);
+            }
+        }
+    }
+
+    fn get_latest(&self) -> Option<Arc<Host>> {
+        self.inner.get_latest()
+    }
+
+    fn get_history(&self, duration: Duration) -> Vec<Arc<Host>> {
+        self.inner.get_history(duration)
+    }
+
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+}
+
+fn frame(tag: u8, payload: &[u8]) -> Vec<u8> {
+    let mut record = Vec::with_capacity(payload.len() + 5);
+    record.push(tag);
+    record.extend((payload.len() as u32).to_le_bytes());
+    record.extend(payload);
+    record
+}
+
+/// Read complete records, ignoring a trailing partial one left by a crash
+fn read_records(mut reader: impl Read) -> Vec<(u8, Vec<u8>)> {
+    let mut records = Vec::new();
+    let mut header = [0u8; 5];
+    while reader.read_exact(&mut header).is_ok() {
+        let len = u32::from_le_bytes([header[1], header[2], header[3], header[4]]) as usize;
+        let mut payload = vec![0u8; len];
+        if reader.read_exact(&mut payload).is_err() {
+            break;
+        }
+        records.push((header[0], payload));
+    }
+    records
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::store::{JsonCodec, MemoryStore};
+
+    #[test]
+    fn test_archive_restores_and_compacts() {
+        let path = std::env::temp_dir().join(format!("nanomon-archive-{}", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let store = ArchiveStore::new(Arc::new(MemoryStore::new(2)), Box::new(JsonCodec), &path, 2);
+        for seq in 1..=5 {
+            store.store(Host::new("nas".to_string()).with_sequence(seq));
+        }
+
+        let reopened =
+            ArchiveStore::new(Arc::new(MemoryStore::new(2)), Box::new(JsonCodec), &path, 2);
+        assert_eq!(reopened.restore(), 2);
+        assert_eq!(reopened.get_latest().unwrap().sequence, Some(5));
+        // Compacted after the 5th record: only what the inner store retained
+        assert_eq!(read_records(File::open(&path).unwrap()).len(), 2);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_unreadable_records_are_kept_aside() {
+        let path = std::env::temp_dir().join(format!("nanomon-newer-{}", std::process::id()));
+        let newer = br#"{"schema":99,"hostname":"nas"}"#;
+        let mut archive = frame(0, newer);
+        archive.extend(frame(
+            0,
+            &JsonCodec.encode(&Host::new("nas".to_string())).unwrap(),
+        ));
+        fs::write(&path, &archive).unwrap();
+
+        let store = ArchiveStore::new(Arc::new(MemoryStore::new(2)), Box::new(JsonCodec), &path, 2);
+        assert_eq!(store.restore(), 1);
+        let copy = path.with_extension("unreadable");
+        assert_eq!(fs::read(&copy).unwrap(), archive);
+
+        fs::remove_file(&path).unwrap();
+        fs::remove_file(&copy).unwrap();
+    }
+}
@@ -0,0 +1,97 @@
+use crate::domain::Host;
+use crate::ports::SnapshotCodec;
+
+use super::schema;
+
+/// Plain JSON, readable with standard tools
+pub struct JsonCodec;
+
+impl SnapshotCodec for JsonCodec {
+    fn tag(&self) -> u8 {
+        0
+    }
+
+    fn encode(&self, snapshot: &Host) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
+        schema::to_json(snapshot)
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<Host, Box<dyn std::error::Error + Send + Sync>> {
+        schema::from_json(bytes)
+    }
+}
+
+/// zstd-compressed JSON. Full snapshots are dominated by repetitive process and
+/// container field names, which compress roughly tenfold.
+#[cfg(feature = "zstd")]
+pub struct ZstdCodec {
+    level: i32,
+}
+
+#[cfg(feature = "zstd")]
+impl ZstdCodec {
+    /// `level` from 1 (fastest) to 19 (smallest); 3 is zstd's default
+    pub fn new(level: i32) -> Self {
+        Self {
+            level: level.clamp(1, 19),
+        }
+    }
+}
+
+#[cfg(feature = "zstd")]
+impl SnapshotCodec for ZstdCodec {
+    fn tag(&self) -> u8 {
+        1
+    }
+
+    fn encode(&self, snapshot: &Host) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(zstd::encode_all(
+            schema::to_json(snapshot)?.as_slice(),
+            self.level,
+        )?)
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<Host, Box<dyn std::error::Error + Send + Sync>> {
+        schema::from_json(&zstd::decode_all(bytes)?)
+    }
+}
+
+/// Codec able to read records written with `tag`
+pub fn codec_for_tag(tag: u8) -> Option<Box<dyn SnapshotCodec>> {
+    match tag {
+        0 => Some(Box::new(JsonCodec)),
+        #[cfg(feature = "zstd")]
+        1 => Some(Box::new(ZstdCodec::new(3))),
+        _ => None,
+    }
+}
+
+#[cfg(all(test, feature = "zstd"))]
+mod tests {
+    use super::*;
+    use crate::domain::{Process, ProcessState};
+
+    #[test]
+    fn test_zstd_round_trip_and_ratio() {
+        let processes = (0..200)
+            .map(|pid| {
+                Process::new(
+                    pid,
+                    1,
+                    "root".to_string(),
+                    format!("/usr/bin/worker --id {}", pid),
+                    ProcessState::Sleeping,
+                )
+                .with_metrics(0.1 * pid as f64, 0.2, 4096 * pid as u64)
+            })
+            .collect();
+        let host = Host::new("nas".to_string()).with_processes(processes);
+
+        let json = JsonCodec.encode(&host).unwrap();
+        let zstd = ZstdCodec::new(3).encode(&host).unwrap();
+        assert!(zstd.len() * 5 < json.len());
+
+        let decoded = codec_for_tag(1).unwrap().decode(&zstd).unwrap();
+        assert_eq!(decoded.processes.len(), 200);
+        assert_eq!(decoded.processes[199].command, "/usr/bin/worker --id 199");
+    }
+}
@@ -0,0 +1,37 @@
+use std::fs;
+use std::path::PathBuf;
+
+use crate::domain::Inventory;
+use crate::ports::InventoryStore;
+
+/// Stores the inventory as a small JSON file
+pub struct FileInventoryStore {
+    path: PathBuf,
+}
+
+impl FileInventoryStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl InventoryStore for FileInventoryStore {
+    fn load(&self) -> Option<Inventory> {
+        let content = fs::read_to_string(&self.path).ok()?;
+        match serde_json::from_str(&content) {
+            Ok(inventory) => Some(inventory),
+            Err(e) => {
+                tracing::warn!("Ignoring unreadable state file {:?}: {}", self.path, e);
+                None
+            }
+        }
+    }
+
+    fn save(&self, inventory: &Inventory) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        // Write to a temp file and rename so a crash never leaves a truncated file
+        let tmp = self.path.with_extension("tmp");
+        fs::write(&tmp, serde_json::to_vec(inventory)?)?;
+        fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+}
@@ -1,16 +1,59 @@
-use std::collections::VecDeque;
-use std::sync::{Arc, RwLock};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex, RwLock};
 use std::time::Duration;
 
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 
 use crate::domain::Host;
 use crate::ports::MetricStore;
 
+/// A per-resource series within stored snapshots
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Resource {
+    /// By `Container::identity`
+    Container(String),
+    Mount(String),
+    Interface(String),
+}
+
+fn resources(host: &Host) -> impl Iterator<Item = Resource> + '_ {
+    let containers = host
+        .containers
+        .iter()
+        .map(|c| Resource::Container(c.identity()));
+    let mounts = host
+        .disks
+        .iter()
+        .map(|d| Resource::Mount(d.mount_point.clone()));
+    let interfaces = host
+        .network_interfaces
+        .iter()
+        .map(|i| Resource::Interface(i.name.clone()));
+    containers.chain(mounts).chain(interfaces)
+}
+
+/// The snapshot without the series of `gone` resources, `None` if it has none of them
+fn without(host: &Host, gone: &HashSet<Resource>) -> Option<Host> {
+    if !resources(host).any(|r| gone.contains(&r)) {
+        return None;
+    }
+    let mut host = host.clone();
+    host.containers
+        .retain(|c| !gone.contains(&Resource::Container(c.identity())));
+    host.disks
+        .retain(|d| !gone.contains(&Resource::Mount(d.mount_point.clone())));
+    host.network_interfaces
+        .retain(|i| !gone.contains(&Resource::Interface(i.name.clone())));
+    Some(host)
+}
+
 /// In-memory ring buffer store for host snapshots using Arc for shared ownership
 pub struct MemoryStore {
     snapshots: RwLock<VecDeque<Arc<Host>>>,
     max_size: usize,
+    /// Containers, mounts and interfaces unseen for this long are dropped from history
+    forget_after: Option<chrono::Duration>,
+    last_seen: Mutex<HashMap<Resource, DateTime<Utc>>>,
 }
 
 impl MemoryStore {
@@ -18,14 +61,54 @@ impl MemoryStore {
         Self {
             snapshots: RwLock::new(VecDeque::with_capacity(max_size)),
             max_size,
+            forget_after: None,
+            last_seen: Mutex::new(HashMap::new()),
         }
     }
+
+    /// Prune the history of containers, mounts and interfaces once they have
+    /// not been seen for `forget_after`, so churned resources do not pile up
+    pub fn with_forget_after(mut self, forget_after: Duration) -> Self {
+        self.forget_after = chrono::Duration::from_std(forget_after).ok();
+        self
+    }
+
+    /// Records what `snapshot` shows and returns what was last seen more than
+    /// `forget_after` before it, which is forgotten
+    fn expire(&self, snapshot: &Host) -> HashSet<Resource> {
+        let Some(forget_after) = self.forget_after else {
+            return HashSet::new();
+        };
+        let mut last_seen = self.last_seen.lock().unwrap();
+        for resource in resources(snapshot) {
+            last_seen.insert(resource, snapshot.timestamp);
+        }
+        let cutoff = snapshot.timestamp - forget_after;
+        let mut gone = HashSet::new();
+        last_seen.retain(|resource, seen| {
+            let keep = *seen >= cutoff;
+            if !keep {
+                gone.insert(resource.clone());
+            }
+            keep
+        });
+        gone
+    }
 }
 
 impl MetricStore for MemoryStore {
     fn store(&self, snapshot: Host) {
+        let gone = self.expire(&snapshot);
         let mut snapshots = self.snapshots.write().unwrap();
 
+        if !gone.is_empty() {
+            for stored in snapshots.iter_mut() {
+                if let Some(pruned) = without(stored, &gone) {
+                    *stored = Arc::new(pruned);
+                }
+            }
+        }
+
         if snapshots.len() >= self.max_size {
             snapshots.pop_front();
         }
@@ -53,3 +136,46 @@ impl MetricStore for MemoryStore {
         self.snapshots.read().unwrap().len()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{Container, ContainerId, ContainerState};
+
+    #[test]
+    fn test_forgets_resources_unseen_for_a_while() {
+        let store = MemoryStore::new(100).with_forget_after(Duration::from_secs(60));
+        let start = Utc::now() - chrono::Duration::minutes(10);
+        let snapshot = |minute: i64, containers: &[&str]| {
+            let mut host = Host::new("nas".to_string());
+            host.timestamp = start + chrono::Duration::minutes(minute);
+            host.containers = containers
+                .iter()
+                .map(|name| {
+                    Container::new(
+                        ContainerId::new(*name),
+                        name.to_string(),
+                        "image".to_string(),
+                        ContainerState::Running,
+                        host.timestamp,
+                    )
+                })
+                .collect();
+            host
+        };
+
+        store.store(snapshot(0, &["web", "ci-1"]));
+        store.store(snapshot(1, &["web"]));
+        let history = store.get_history(Duration::from_secs(3600));
+        assert_eq!(history[0].containers.len(), 2);
+
+        // Two minutes after ci-1 was last seen its series is dropped everywhere
+        store.store(snapshot(2, &["web"]));
+        let history = store.get_history(Duration::from_secs(3600));
+        assert_eq!(history.len(), 3);
+        assert!(history
+            .iter()
+            .all(|s| s.containers.iter().all(|c| c.name == "web")));
+        assert_eq!(store.last_seen.lock().unwrap().len(), 1);
+    }
+}
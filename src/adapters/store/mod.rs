@@ -1,3 +1,12 @@
+mod archive;
+mod codec;
+mod inventory_file;
 mod memory;
+mod schema;
 
+pub use archive::ArchiveStore;
+pub use codec::JsonCodec;
+#[cfg(feature = "zstd")]
+pub use codec::ZstdCodec;
+pub use inventory_file::FileInventoryStore;
 pub use memory::MemoryStore;
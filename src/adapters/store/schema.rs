@@ -0,0 +1,114 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::domain::Host;
+
+/// Version of the snapshot layout written to archives. Fields added with a
+/// serde default need no bump; renames, moves and changed meanings do, along
+/// with a step in `MIGRATIONS`.
+pub const SNAPSHOT_SCHEMA: u32 = 1;
+
+/// Upgrades a record in place; entry `i` turns version `i + 1` into `i + 2`
+type Migration = fn(&mut Value) -> Result<(), String>;
+
+/// Steps from each older schema to the next. Records written before versioning
+/// carry no `schema` field and are version 1.
+const MIGRATIONS: &[Migration] = &[];
+
+#[derive(Serialize)]
+struct Versioned<'a> {
+    schema: u32,
+    #[serde(flatten)]
+    host: &'a Host,
+}
+
+#[derive(Deserialize)]
+struct Version {
+    #[serde(default = "first_schema")]
+    schema: u32,
+}
+
+fn first_schema() -> u32 {
+    1
+}
+
+/// JSON of a snapshot tagged with the current schema
+pub fn to_json(snapshot: &Host) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
+    Ok(serde_json::to_vec(&Versioned {
+        schema: SNAPSHOT_SCHEMA,
+        host: snapshot,
+    })?)
+}
+
+/// A snapshot of any known schema, migrated to the current one. Records from a
+/// newer build are refused rather than read with fields silently dropped.
+pub fn from_json(bytes: &[u8]) -> Result<Host, Box<dyn std::error::Error + Send + Sync>> {
+    let Version { schema } = serde_json::from_slice(bytes)?;
+    if schema == SNAPSHOT_SCHEMA {
+        return Ok(serde_json::from_slice(bytes)?);
+    }
+    let mut value: Value = serde_json::from_slice(bytes)?;
+    migrate(&mut value, schema, MIGRATIONS)?;
+    Ok(serde_json::from_value(value)?)
+}
+
+fn migrate(value: &mut Value, from: u32, migrations: &[Migration]) -> Result<(), String> {
+    let current = migrations.len() as u32 + 1;
+    if from == 0 || from > current {
+        return Err(format!(
+            "snapshot schema {} is not readable by this version (schema {})",
+            from, current
+        ));
+    }
+    for (step, migration) in migrations.iter().enumerate().skip(from as usize - 1) {
+        migration(value).map_err(|e| format!("migrating schema {}: {}", step + 1, e))?;
+    }
+    if let Some(object) = value.as_object_mut() {
+        object.insert("schema".to_string(), current.into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_old_records_are_migrated_and_newer_refused() {
+        let host = Host::new("nas".to_string()).with_sequence(3);
+        let current = to_json(&host).unwrap();
+        assert_eq!(from_json(&current).unwrap().sequence, Some(3));
+
+        // Written before snapshots were versioned
+        let unversioned = serde_json::to_vec(&host).unwrap();
+        assert_eq!(from_json(&unversioned).unwrap().hostname, "nas");
+
+        let newer =
+            serde_json::to_vec(&serde_json::json!({ "schema": SNAPSHOT_SCHEMA + 1 })).unwrap();
+        assert!(from_json(&newer)
+            .unwrap_err()
+            .to_string()
+            .contains("not readable"));
+
+        // A rename from schema 1 to 2, then a new field defaulted by 2 to 3
+        let rename: Migration = |v| {
+            let object = v.as_object_mut().ok_or("not an object")?;
+            let name = object.remove("host").ok_or("no host")?;
+            object.insert("hostname".to_string(), name);
+            Ok(())
+        };
+        let add: Migration = |v| {
+            v["uptime_seconds"] = 0.into();
+            Ok(())
+        };
+        let mut value = serde_json::json!({ "host": "nas" });
+        migrate(&mut value, 1, &[rename, add]).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({ "hostname": "nas", "uptime_seconds": 0, "schema": 3 })
+        );
+        let mut value = serde_json::json!({ "hostname": "nas", "schema": 2 });
+        migrate(&mut value, 2, &[rename, add]).unwrap();
+        assert_eq!(value["uptime_seconds"], 0);
+    }
+}
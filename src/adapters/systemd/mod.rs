@@ -10,6 +10,12 @@ pub struct SystemctlAdapter {
     available: bool,
 }
 
+impl Default for SystemctlAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl SystemctlAdapter {
     pub fn new() -> Self {
         let available = std::process::Command::new("systemctl")
@@ -0,0 +1,167 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+use chrono::Utc;
+
+use crate::domain::CustomMetric;
+use crate::ports::MetricCollector;
+
+use super::script::parse_metrics;
+
+/// Reads metric snippets that other tools drop into a directory, node_exporter
+/// style: `*.json` in the script collector format and `*.prom` in the Prometheus
+/// text format. Each file's stem becomes the metrics' collector. Writers should
+/// write to a temporary name and rename, so half-written files are never read.
+pub struct TextfileCollector {
+    directory: PathBuf,
+}
+
+impl TextfileCollector {
+    pub fn new(directory: PathBuf) -> Self {
+        Self { directory }
+    }
+}
+
+#[async_trait]
+impl MetricCollector for TextfileCollector {
+    async fn collect(&self) -> Result<Vec<CustomMetric>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut entries = tokio::fs::read_dir(&self.directory).await?;
+        let mut files = Vec::new();
+        while let Some(entry) = entries.next_entry().await? {
+            files.push(entry.path());
+        }
+        files.sort();
+
+        let mut metrics = Vec::new();
+        for path in files {
+            let (Some(stem), Some(extension)) = (
+                path.file_stem().and_then(|s| s.to_str()),
+                path.extension().and_then(|e| e.to_str()),
+            ) else {
+                continue;
+            };
+            let parsed = match extension {
+                "json" => match tokio::fs::read(&path).await {
+                    Ok(content) => serde_json::from_slice(&content)
+                        .map_err(Into::into)
+                        .and_then(|value| parse_metrics(stem, value)),
+                    Err(e) => Err(e.into()),
+                },
+                "prom" => match tokio::fs::read_to_string(&path).await {
+                    Ok(content) => parse_prometheus(stem, &content).map_err(Into::into),
+                    Err(e) => Err(e.into()),
+                },
+                _ => continue,
+            };
+            // One broken file must not hide the others
+            match parsed {
+                Ok(file_metrics) => metrics.extend(file_metrics),
+                Err(e) => tracing::warn!("Skipping textfile {}: {}", path.display(), e),
+            }
+        }
+        Ok(metrics)
+    }
+}
+
+/// Parse the Prometheus text exposition format; comments, `HELP`/`TYPE` lines
+/// and sample timestamps are ignored
+fn parse_prometheus(collector: &str, content: &str) -> Result<Vec<CustomMetric>, String> {
+    let collected_at = Utc::now();
+    let mut metrics = Vec::new();
+
+    for (number, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let error = |reason: &str| format!("line {}: {}", number + 1, reason);
+
+        let name_end = line
+            .find(|c: char| c == '{' || c.is_whitespace())
+            .ok_or_else(|| error("missing value"))?;
+        let name = &line[..name_end];
+        let mut rest = &line[name_end..];
+
+        let mut labels = BTreeMap::new();
+        if let Some(inner) = rest.strip_prefix('{') {
+            let (parsed, after) = parse_labels(inner).ok_or_else(|| error("malformed labels"))?;
+            labels = parsed;
+            rest = after;
+        }
+
+        let value = rest
+            .split_whitespace()
+            .next()
+            .ok_or_else(|| error("missing value"))?;
+        let value: f64 = value.parse().map_err(|_| error("invalid value"))?;
+        // NaN and infinities are valid Prometheus values but cannot be stored or compared
+        if name.is_empty() || !value.is_finite() {
+            continue;
+        }
+
+        metrics.push(CustomMetric {
+            collector: collector.to_string(),
+            name: name.to_string(),
+            value,
+            unit: None,
+            labels,
+            collected_at,
+        });
+    }
+    Ok(metrics)
+}
+
+/// Parse `key="value",...}` and return the labels and what follows the brace
+fn parse_labels(input: &str) -> Option<(BTreeMap<String, String>, &str)> {
+    let mut labels = BTreeMap::new();
+    let mut rest = input.trim_start();
+
+    loop {
+        if let Some(after) = rest.strip_prefix('}') {
+            return Some((labels, after));
+        }
+        let (key, after_key) = rest.split_once('=')?;
+        let mut chars = after_key.strip_prefix('"')?.char_indices();
+        let mut value = String::new();
+        let end = loop {
+            match chars.next()? {
+                (i, '"') => break i,
+                (_, '\\') => match chars.next()?.1 {
+                    'n' => value.push('\n'),
+                    other => value.push(other),
+                },
+                (_, c) => value.push(c),
+            }
+        };
+        labels.insert(key.trim().to_string(), value);
+        rest = after_key[end + 2..].trim_start();
+        rest = rest.strip_prefix(',').unwrap_or(rest).trim_start();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_prometheus() {
+        let content = r#"
+# HELP backup_last_success_timestamp Last successful run
+# TYPE backup_last_success_timestamp gauge
+backup_last_success_timestamp{job="photos",path="/mnt/a \"b\""} 1767787200 1767787200000
+backup_size_bytes 4.2e9
+backup_errors NaN
+"#;
+        let metrics = parse_prometheus("backup", content).unwrap();
+        assert_eq!(metrics.len(), 2);
+        assert_eq!(metrics[0].labels["job"], "photos");
+        assert_eq!(metrics[0].labels["path"], "/mnt/a \"b\"");
+        assert_eq!(metrics[0].value, 1767787200.0);
+        assert_eq!(metrics[1].value, 4.2e9);
+        assert_eq!(metrics[1].collector, "backup");
+
+        assert!(parse_prometheus("backup", "up{job=\"x\" 1").is_err());
+        assert!(parse_prometheus("backup", "up one").is_err());
+    }
+}
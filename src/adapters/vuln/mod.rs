@@ -0,0 +1,190 @@
+use std::fs;
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+
+use crate::domain::{ImageScan, VulnerabilityCounts};
+use crate::ports::VulnerabilitySource;
+
+/// Supported external scanners
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScannerTool {
+    Trivy,
+    Grype,
+}
+
+impl ScannerTool {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "trivy" => Some(Self::Trivy),
+            "grype" => Some(Self::Grype),
+            _ => None,
+        }
+    }
+}
+
+/// Invokes trivy/grype for each image
+pub struct CommandVulnerabilitySource {
+    tool: ScannerTool,
+}
+
+impl CommandVulnerabilitySource {
+    pub fn new(tool: ScannerTool) -> Self {
+        Self { tool }
+    }
+}
+
+#[async_trait]
+impl VulnerabilitySource for CommandVulnerabilitySource {
+    async fn report(
+        &self,
+        image: &str,
+    ) -> Result<Option<ImageScan>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut command = match self.tool {
+            ScannerTool::Trivy => {
+                let mut c = tokio::process::Command::new("trivy");
+                c.args(["image", "--quiet", "--format", "json", image]);
+                c
+            }
+            ScannerTool::Grype => {
+                let mut c = tokio::process::Command::new("grype");
+                c.args(["--quiet", "--output", "json", image]);
+                c
+            }
+        };
+        let output = command.output().await?;
+
+        if !output.status.success() {
+            return Err(format!(
+                "scan of {} failed: {}",
+                image,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )
+            .into());
+        }
+
+        let json: Value = serde_json::from_slice(&output.stdout)?;
+        Ok(parse_report(&json, Utc::now()).map(|mut scan| {
+            scan.image = image.to_string();
+            scan
+        }))
+    }
+}
+
+/// Ingests trivy/grype JSON reports written to a directory by an external job
+/// (e.g. `trivy image -f json -o /reports/nginx.json nginx:1.25`)
+pub struct ReportDirVulnerabilitySource {
+    dir: PathBuf,
+}
+
+impl ReportDirVulnerabilitySource {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+}
+
+#[async_trait]
+impl VulnerabilitySource for ReportDirVulnerabilitySource {
+    async fn report(
+        &self,
+        image: &str,
+    ) -> Result<Option<ImageScan>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut newest: Option<ImageScan> = None;
+
+        for entry in fs::read_dir(&self.dir)?.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            let Ok(modified) = entry.metadata().and_then(|m| m.modified()) else {
+                continue;
+            };
+            let Some(json) = fs::read(&path)
+                .ok()
+                .and_then(|bytes| serde_json::from_slice::<Value>(&bytes).ok())
+            else {
+                continue;
+            };
+            let Some(scan) = parse_report(&json, DateTime::<Utc>::from(modified)) else {
+                continue;
+            };
+            if scan.image != image {
+                continue;
+            }
+            if newest
+                .as_ref()
+                .is_none_or(|n| scan.scanned_at > n.scanned_at)
+            {
+                newest = Some(scan);
+            }
+        }
+
+        Ok(newest)
+    }
+}
+
+/// Parse a trivy or grype JSON report
+fn parse_report(json: &Value, scanned_at: DateTime<Utc>) -> Option<ImageScan> {
+    let mut counts = VulnerabilityCounts::default();
+
+    if let Some(results) = json["Results"].as_array() {
+        // trivy: Results[].Vulnerabilities[].Severity
+        for vuln in results
+            .iter()
+            .filter_map(|r| r["Vulnerabilities"].as_array())
+            .flatten()
+        {
+            counts.add(vuln["Severity"].as_str().unwrap_or_default());
+        }
+        Some(ImageScan {
+            image: json["ArtifactName"].as_str()?.to_string(),
+            scanner: "trivy".to_string(),
+            scanned_at,
+            counts,
+        })
+    } else if let Some(matches) = json["matches"].as_array() {
+        // grype: matches[].vulnerability.severity
+        for m in matches {
+            counts.add(m["vulnerability"]["severity"].as_str().unwrap_or_default());
+        }
+        Some(ImageScan {
+            image: json["source"]["target"]["userInput"].as_str()?.to_string(),
+            scanner: "grype".to_string(),
+            scanned_at,
+            counts,
+        })
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_trivy_and_grype_reports() {
+        let trivy: Value = serde_json::from_str(
+            r#"{"ArtifactName":"nginx:1.25","Results":[
+                {"Vulnerabilities":[{"Severity":"CRITICAL"},{"Severity":"HIGH"},{"Severity":"HIGH"}]},
+                {"Target":"app"}]}"#,
+        )
+        .unwrap();
+        let scan = parse_report(&trivy, Utc::now()).unwrap();
+        assert_eq!(scan.image, "nginx:1.25");
+        assert_eq!(scan.counts.critical, 1);
+        assert_eq!(scan.counts.high, 2);
+
+        let grype: Value = serde_json::from_str(
+            r#"{"source":{"target":{"userInput":"redis:7"}},
+                "matches":[{"vulnerability":{"severity":"High"}},{"vulnerability":{"severity":"Negligible"}}]}"#,
+        )
+        .unwrap();
+        let scan = parse_report(&grype, Utc::now()).unwrap();
+        assert_eq!(scan.scanner, "grype");
+        assert_eq!(scan.counts.high, 1);
+        assert_eq!(scan.counts.low, 1);
+    }
+}
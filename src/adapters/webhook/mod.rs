@@ -1,23 +1,114 @@
+#[cfg(feature = "webhook")]
+use std::fmt::Write;
+#[cfg(feature = "webhook")]
 use std::time::Duration;
 
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use serde::Serialize;
 
-use crate::domain::AlertEvent;
+use crate::domain::{format_local, AlertEvent, Event};
 use crate::ports::AlertSink;
 
+/// Header carrying the signature of signed payloads
+#[cfg(feature = "webhook")]
+const SIGNATURE_HEADER: &str = "X-NanoMon-Signature";
+
+/// `sha256=` and the hex HMAC-SHA256 of `body` keyed with `secret`, as
+/// GitHub signs its webhooks
+#[cfg(feature = "webhook")]
+fn sign(secret: &str, body: &[u8]) -> String {
+    use hmac::{Hmac, Mac};
+
+    let mut mac = Hmac::<sha2::Sha256>::new_from_slice(secret.as_bytes())
+        .expect("HMAC takes keys of any length");
+    mac.update(body);
+    mac.finalize()
+        .into_bytes()
+        .iter()
+        .fold(String::from("sha256="), |mut signature, b| {
+            let _ = write!(signature, "{:02x}", b);
+            signature
+        })
+}
+
 /// Sends alert events as JSON via HTTP POST to a webhook URL
 pub struct WebhookSink {
+    #[cfg(feature = "webhook")]
     client: reqwest::Client,
+    timezone: Tz,
+}
+
+impl Default for WebhookSink {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl WebhookSink {
     pub fn new() -> Self {
-        let client = reqwest::Client::builder()
-            .timeout(Duration::from_secs(5))
-            .build()
-            .expect("Failed to build HTTP client");
+        Self {
+            #[cfg(feature = "webhook")]
+            client: reqwest::Client::builder()
+                .timeout(Duration::from_secs(5))
+                .build()
+                .expect("Failed to build HTTP client"),
+            timezone: Tz::UTC,
+        }
+    }
+
+    /// Timezone used for the `local_time` field of payloads
+    pub fn with_timezone(mut self, timezone: Tz) -> Self {
+        self.timezone = timezone;
+        self
+    }
+
+    /// Serialize a payload, adding `local_time` next to its UTC timestamp
+    fn localized<T: Serialize>(
+        &self,
+        payload: &T,
+        timestamp: DateTime<Utc>,
+    ) -> Result<serde_json::Value, serde_json::Error> {
+        let mut value = serde_json::to_value(payload)?;
+        if let Some(obj) = value.as_object_mut() {
+            obj.insert(
+                "local_time".to_string(),
+                format_local(timestamp, self.timezone).into(),
+            );
+        }
+        Ok(value)
+    }
+
+    /// POST a JSON payload, signed when `secret` is given, returning the
+    /// response status
+    #[cfg(feature = "webhook")]
+    async fn post(
+        &self,
+        url: &str,
+        payload: &serde_json::Value,
+        secret: Option<&str>,
+    ) -> Result<u16, Box<dyn std::error::Error + Send + Sync>> {
+        let body = serde_json::to_vec(payload)?;
+        let mut request = self
+            .client
+            .post(url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json");
+        if let Some(secret) = secret {
+            request = request.header(SIGNATURE_HEADER, sign(secret, &body));
+        }
+        let response = request.body(body).send().await?;
+        Ok(response.status().as_u16())
+    }
 
-        Self { client }
+    #[cfg(not(feature = "webhook"))]
+    async fn post(
+        &self,
+        _url: &str,
+        _payload: &serde_json::Value,
+        _secret: Option<&str>,
+    ) -> Result<u16, Box<dyn std::error::Error + Send + Sync>> {
+        Err("built without the webhook feature".into())
     }
 }
 
@@ -28,12 +119,16 @@ impl AlertSink for WebhookSink {
         url: &str,
         event: &AlertEvent,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let response = self.client.post(url).json(event).send().await?;
+        let payload = match DateTime::parse_from_rfc3339(&event.timestamp) {
+            Ok(ts) => self.localized(event, ts.with_timezone(&Utc))?,
+            Err(_) => serde_json::to_value(event)?,
+        };
+        let status = self.post(url, &payload, None).await?;
 
-        if !response.status().is_success() {
+        if !(200..300).contains(&status) {
             tracing::warn!(
                 "Webhook returned status {} for alert '{}'",
-                response.status(),
+                status,
                 event.rule_name
             );
         } else {
@@ -42,4 +137,68 @@ impl AlertSink for WebhookSink {
 
         Ok(())
     }
+
+    async fn send_alerts(
+        &self,
+        url: &str,
+        events: &[AlertEvent],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let alerts = events
+            .iter()
+            .map(
+                |event| match DateTime::parse_from_rfc3339(&event.timestamp) {
+                    Ok(ts) => self.localized(event, ts.with_timezone(&Utc)),
+                    Err(_) => serde_json::to_value(event),
+                },
+            )
+            .collect::<Result<Vec<_>, _>>()?;
+        let payload = serde_json::json!({
+            "count": alerts.len(),
+            "summary": AlertEvent::digest_summary(events),
+            "alerts": alerts,
+        });
+        let status = self.post(url, &payload, None).await?;
+
+        if !(200..300).contains(&status) {
+            tracing::warn!(
+                "Webhook returned status {} for {} grouped alerts",
+                status,
+                events.len()
+            );
+        } else {
+            tracing::info!("{} grouped alerts sent to webhook", events.len());
+        }
+
+        Ok(())
+    }
+
+    async fn send_event(
+        &self,
+        url: &str,
+        event: &Event,
+        secret: Option<&str>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let payload = self.localized(event, event.timestamp)?;
+        let status = self.post(url, &payload, secret).await?;
+
+        if !(200..300).contains(&status) {
+            tracing::warn!("Webhook returned status {} for event", status);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "webhook"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signature_is_hmac_sha256() {
+        // RFC 4231, test case 2
+        assert_eq!(
+            sign("Jefe", b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
 }
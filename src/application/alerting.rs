@@ -1,16 +1,62 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, RwLock};
 
 use chrono::{DateTime, Utc};
 
-use crate::domain::{AlertEvent, AlertMetric, AlertRule, Host};
+use crate::domain::{
+    AlertEvent, AlertIncident, AlertRule, AlertStats, DomainEvent, Host, NotificationRoute,
+};
 use crate::ports::AlertSink;
 
+use super::notify::{Delivery, NotificationRouter};
+use super::{BurnInService, EventBus, ReactionService};
+
+/// Resolved incidents kept for statistics
+const MAX_RESOLVED_INCIDENTS: usize = 1000;
+
+/// Noisiest resources listed in statistics
+const NOISIEST_RESOURCES: usize = 10;
+
 /// Evaluates alert rules against host snapshots and fires webhooks
 pub struct AlertEvaluator {
     rules: Vec<AlertRule>,
     last_fired: RwLock<HashMap<String, DateTime<Utc>>>,
     sink: Arc<dyn AlertSink>,
+    router: Arc<NotificationRouter>,
+    /// Send the alerts of one evaluation bound for the same destination as
+    /// one message
+    digest: bool,
+    /// Incidents of the rules currently firing, by rule name
+    open: RwLock<HashMap<String, AlertIncident>>,
+    /// Oldest first
+    resolved: RwLock<VecDeque<AlertIncident>>,
+    next_incident_id: AtomicU64,
+    reactions: Option<Arc<ReactionService>>,
+    burn_in: Option<Arc<BurnInService>>,
+    bus: Option<Arc<EventBus>>,
+}
+
+/// Notification of `rule` firing at `value`
+fn alert_event(
+    rule: &AlertRule,
+    incident_id: u64,
+    value: f64,
+    hostname: &str,
+    now: DateTime<Utc>,
+) -> AlertEvent {
+    AlertEvent {
+        rule_name: rule.name.clone(),
+        severity: rule.severity,
+        incident_id: Some(incident_id),
+        escalation: None,
+        metric: format!("{:?}", rule.metric),
+        current_value: value,
+        threshold: rule.threshold,
+        condition: format!("{:?}", rule.condition),
+        hostname: hostname.to_string(),
+        timestamp: now.to_rfc3339(),
+    }
 }
 
 impl AlertEvaluator {
@@ -18,19 +64,106 @@ impl AlertEvaluator {
         Self {
             rules,
             last_fired: RwLock::new(HashMap::new()),
+            router: Arc::new(NotificationRouter::new(Vec::new(), sink.clone())),
             sink,
+            digest: true,
+            open: RwLock::new(HashMap::new()),
+            resolved: RwLock::new(VecDeque::new()),
+            next_incident_id: AtomicU64::new(1),
+            reactions: None,
+            burn_in: None,
+            bus: None,
         }
     }
 
+    /// Publish incidents on the bus as they open and resolve
+    pub fn with_bus(mut self, bus: Arc<EventBus>) -> Self {
+        self.bus = Some(bus);
+        self
+    }
+
+    /// Record alerts as expected instead of notifying them while a burn-in
+    /// runs
+    pub fn with_burn_in(mut self, burn_in: Arc<BurnInService>) -> Self {
+        self.burn_in = Some(burn_in);
+        self
+    }
+
+    /// Run the `[[reactions]]` commands of rules as they fire and resolve
+    pub fn with_reactions(mut self, reactions: Arc<ReactionService>) -> Self {
+        self.reactions = Some(reactions);
+        self
+    }
+
+    /// Send alerts where the first matching route says instead of to their
+    /// rule's webhook
+    pub fn with_routes(mut self, routes: Vec<NotificationRoute>) -> Self {
+        self.router = Arc::new(NotificationRouter::new(routes, self.sink.clone()));
+        self
+    }
+
+    /// Send one message per alert even when several fire at once
+    pub fn with_digest(mut self, digest: bool) -> Self {
+        self.digest = digest;
+        self
+    }
+
     /// Evaluate all rules against the current snapshot
     pub async fn evaluate(&self, snapshot: &Host) {
+        let mut due = Vec::new();
         for rule in &self.rules {
-            let current_value = match self.extract_metric(rule, snapshot) {
+            let current_value = match rule.metric.value(snapshot) {
                 Some(v) => v,
                 None => continue,
             };
 
             if !rule.condition.evaluate(current_value, rule.threshold) {
+                self.resolve(rule, current_value, &snapshot.hostname);
+                continue;
+            }
+            let expected = self
+                .burn_in
+                .as_ref()
+                .is_some_and(|burn_in| burn_in.expect(rule, current_value));
+            let mut opened = false;
+            let (incident_id, acknowledged, new_incident) = {
+                let mut open = self.open.write().unwrap();
+                let incident = open
+                    .entry(rule.name.clone())
+                    .and_modify(|incident| {
+                        if rule.condition.evaluate(current_value, incident.peak_value) {
+                            incident.peak_value = current_value;
+                        }
+                    })
+                    .or_insert_with(|| {
+                        opened = true;
+                        let id = self.next_incident_id.fetch_add(1, Ordering::Relaxed);
+                        AlertIncident::new(id, rule, Utc::now(), current_value)
+                    });
+                incident.expected |= expected;
+                (
+                    incident.id,
+                    incident.acknowledged_at.is_some(),
+                    opened.then(|| incident.clone()),
+                )
+            };
+            if let (Some(bus), Some(incident)) = (&self.bus, new_incident) {
+                bus.publish(DomainEvent::Alert(incident));
+            }
+            if expected {
+                continue;
+            }
+            let event = alert_event(
+                rule,
+                incident_id,
+                current_value,
+                &snapshot.hostname,
+                Utc::now(),
+            );
+            if let Some(reactions) = &self.reactions {
+                reactions.firing(rule, &event);
+            }
+            if acknowledged {
                 continue;
             }
 
@@ -45,49 +178,252 @@ impl AlertEvaluator {
                 }
             }
 
-            let event = AlertEvent {
-                rule_name: rule.name.clone(),
-                metric: format!("{:?}", rule.metric),
-                current_value,
-                threshold: rule.threshold,
-                condition: format!("{:?}", rule.condition),
-                hostname: snapshot.hostname.clone(),
-                timestamp: Utc::now().to_rfc3339(),
-            };
+            due.push((rule, event));
+        }
 
-            if let Err(e) = self.sink.send_alert(&rule.webhook_url, &event).await {
-                tracing::error!("Failed to send alert '{}': {}", rule.name, e);
-            } else {
-                let mut last_fired = self.last_fired.write().unwrap();
-                last_fired.insert(rule.name.clone(), Utc::now());
+        self.escalate(&snapshot.hostname).await;
+
+        if self.digest {
+            let rules: Vec<&AlertRule> = due.iter().map(|(rule, _)| *rule).collect();
+            let results = self.router.dispatch_digest(due).await;
+            for (rule, result) in rules.into_iter().zip(results) {
+                self.delivered(rule, result);
+            }
+        } else {
+            for (rule, event) in due {
+                let result = self.router.dispatch(rule, event).await;
+                self.delivered(rule, result.map_err(|e| e.to_string()));
             }
         }
     }
 
-    fn extract_metric(&self, rule: &AlertRule, snapshot: &Host) -> Option<f64> {
-        match &rule.metric {
-            AlertMetric::CpuUsage => Some(snapshot.cpu.usage_percent),
-            AlertMetric::MemoryUsage => {
-                if snapshot.memory.total_bytes == 0 {
-                    return None;
+    /// Notifies the escalation steps that came due for unacknowledged
+    /// incidents
+    async fn escalate(&self, hostname: &str) {
+        let now = Utc::now();
+        let mut due = Vec::new();
+        for incident in self.open.write().unwrap().values_mut() {
+            if incident.acknowledged_at.is_some() || incident.expected {
+                continue;
+            }
+            let Some(rule) = self.rules.iter().find(|r| r.name == incident.rule) else {
+                continue;
+            };
+            let firing_minutes = (now - incident.started_at).num_minutes().max(0) as u64;
+            while let Some(step) = rule
+                .escalation
+                .get(incident.escalations)
+                .filter(|step| step.after_minutes <= firing_minutes)
+            {
+                incident.escalations += 1;
+                let event = AlertEvent {
+                    escalation: Some(incident.escalations),
+                    ..alert_event(rule, incident.id, incident.peak_value, hostname, now)
+                };
+                due.push((step.notify.clone(), event));
+            }
+        }
+
+        for (urls, event) in due {
+            tracing::warn!(
+                "Alert '{}' unacknowledged, escalating (step {})",
+                event.rule_name,
+                event.escalation.unwrap_or_default()
+            );
+            for url in urls {
+                if let Err(e) = self.sink.send_alert(&url, &event).await {
+                    tracing::error!("Failed to escalate alert '{}': {}", event.rule_name, e);
                 }
-                Some(snapshot.memory.used_bytes as f64 / snapshot.memory.total_bytes as f64 * 100.0)
             }
-            AlertMetric::DiskUsage { mount_point } => snapshot
-                .disks
-                .iter()
-                .find(|d| d.mount_point == *mount_point)
-                .map(|d| d.usage_percent()),
-            AlertMetric::LoadAverage1m => Some(snapshot.load_average.one),
-            AlertMetric::Temperature { label } => snapshot
-                .temperatures
-                .iter()
-                .find(|t| t.label == *label)
-                .map(|t| t.current_celsius),
         }
     }
 
+    /// Incidents still firing, oldest first
+    pub fn open_incidents(&self) -> Vec<AlertIncident> {
+        let mut open: Vec<AlertIncident> = self.open.read().unwrap().values().cloned().collect();
+        open.sort_by_key(|i| i.started_at);
+        open
+    }
+
+    /// Acknowledges open incident `id`, stopping its repeat notifications
+    /// and escalation; `None` when no open incident has that ID. The first
+    /// acknowledgment is kept.
+    pub fn acknowledge(&self, id: u64, by: Option<String>) -> Option<AlertIncident> {
+        let mut open = self.open.write().unwrap();
+        let incident = open.values_mut().find(|i| i.id == id)?;
+        if incident.acknowledged_at.is_none() {
+            tracing::info!(
+                "Alert '{}' acknowledged by {}",
+                incident.rule,
+                by.as_deref().unwrap_or("an admin")
+            );
+            incident.acknowledged_at = Some(Utc::now());
+            incident.acknowledged_by = by;
+        }
+        Some(incident.clone())
+    }
+
+    /// Starts the cooldown of `rule` once its alert is handled
+    fn delivered(&self, rule: &AlertRule, result: Result<Delivery, String>) {
+        let delivery = match result {
+            Ok(delivery) => delivery,
+            Err(e) => {
+                tracing::error!("Failed to send alert '{}': {}", rule.name, e);
+                return;
+            }
+        };
+        if delivery == Delivery::Throttled {
+            return;
+        }
+        let mut last_fired = self.last_fired.write().unwrap();
+        last_fired.insert(rule.name.clone(), Utc::now());
+        if delivery != Delivery::Dropped {
+            if let Some(incident) = self.open.write().unwrap().get_mut(&rule.name) {
+                incident.notifications += 1;
+            }
+        }
+    }
+
+    fn resolve(&self, rule: &AlertRule, value: f64, hostname: &str) {
+        let Some(mut incident) = self.open.write().unwrap().remove(&rule.name) else {
+            return;
+        };
+        incident.resolved_at = Some(Utc::now());
+        if let Some(bus) = &self.bus {
+            bus.publish(DomainEvent::Alert(incident.clone()));
+        }
+        if let Some(reactions) = self.reactions.as_ref().filter(|_| !incident.expected) {
+            let event = alert_event(rule, incident.id, value, hostname, Utc::now());
+            reactions.resolved(rule, &event);
+        }
+        let mut resolved = self.resolved.write().unwrap();
+        if resolved.len() >= MAX_RESOLVED_INCIDENTS {
+            resolved.pop_front();
+        }
+        resolved.push_back(incident);
+    }
+
+    /// Alert frequency, time to resolve and noisiest resources over the last `window`
+    pub fn stats(&self, window: std::time::Duration) -> AlertStats {
+        let since = Utc::now()
+            - chrono::Duration::from_std(window).unwrap_or_else(|_| chrono::Duration::zero());
+        let mut incidents: Vec<AlertIncident> =
+            self.resolved.read().unwrap().iter().cloned().collect();
+        incidents.extend(self.open.read().unwrap().values().cloned());
+        AlertStats::build(&incidents, since, NOISIEST_RESOURCES)
+    }
+
     pub fn has_rules(&self) -> bool {
         !self.rules.is_empty()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{AlertCondition, AlertMetric, EscalationStep, Event, Severity};
+    use async_trait::async_trait;
+    use std::sync::Mutex;
+
+    /// Records `(url, escalation)` per alert
+    #[derive(Default)]
+    struct RecordingSink {
+        sent: Mutex<Vec<(String, Option<usize>)>>,
+    }
+
+    #[async_trait]
+    impl AlertSink for RecordingSink {
+        async fn send_alert(
+            &self,
+            url: &str,
+            event: &AlertEvent,
+        ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+            let sent = (url.to_string(), event.escalation);
+            self.sent.lock().unwrap().push(sent);
+            Ok(())
+        }
+
+        async fn send_event(
+            &self,
+            _url: &str,
+            _event: &Event,
+            _secret: Option<&str>,
+        ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_unacknowledged_alerts_escalate() {
+        let step = |after_minutes: u64, url: &str| EscalationStep {
+            after_minutes,
+            notify: vec![url.to_string()],
+        };
+        let rule = AlertRule {
+            name: "cpu".to_string(),
+            metric: AlertMetric::CpuUsage,
+            condition: AlertCondition::Above,
+            threshold: 90.0,
+            webhook_url: "http://alex".to_string(),
+            cooldown_seconds: 0,
+            severity: Severity::Critical,
+            escalation: vec![step(0, "telegram:sam"), step(30, "mailto:all@home")],
+        };
+        let sink = Arc::new(RecordingSink::default());
+        let alerts = AlertEvaluator::new(vec![rule], sink.clone());
+        let mut host = Host::new("nas".to_string());
+        host.cpu.usage_percent = 95.0;
+
+        alerts.evaluate(&host).await;
+        assert_eq!(
+            *sink.sent.lock().unwrap(),
+            [
+                ("telegram:sam".to_string(), Some(1)),
+                ("http://alex".to_string(), None),
+            ]
+        );
+
+        let id = alerts.open_incidents()[0].id;
+        assert!(alerts.acknowledge(id + 1, None).is_none());
+        let acked = alerts.acknowledge(id, Some("alex".to_string())).unwrap();
+        assert_eq!(acked.acknowledged_by.as_deref(), Some("alex"));
+
+        // Acknowledged: no repeat even without a cooldown
+        alerts.evaluate(&host).await;
+        assert_eq!(sink.sent.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_incidents_are_published_on_the_bus() {
+        let rule = AlertRule {
+            name: "cpu".to_string(),
+            metric: AlertMetric::CpuUsage,
+            condition: AlertCondition::Above,
+            threshold: 90.0,
+            webhook_url: "http://alex".to_string(),
+            cooldown_seconds: 0,
+            severity: Severity::Warning,
+            escalation: Vec::new(),
+        };
+        let bus = Arc::new(EventBus::default());
+        let mut incidents = bus.subscribe();
+        let alerts = AlertEvaluator::new(vec![rule], Arc::new(RecordingSink::default()))
+            .with_bus(bus.clone());
+        let mut host = Host::new("nas".to_string());
+
+        // Opened once however long it fires, then resolved
+        for cpu in [95.0, 97.0, 20.0] {
+            host.cpu.usage_percent = cpu;
+            alerts.evaluate(&host).await;
+        }
+        let mut published = Vec::new();
+        while let Ok(DomainEvent::Alert(incident)) = incidents.try_recv() {
+            published.push(incident);
+        }
+        assert_eq!(published.len(), 2);
+        assert!(published[0].resolved_at.is_none());
+        assert_eq!(published[1].id, published[0].id);
+        assert_eq!(published[1].peak_value, 97.0);
+        assert!(published[1].resolved_at.is_some());
+    }
+}
@@ -0,0 +1,174 @@
+use std::collections::{HashMap, VecDeque};
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+
+use crate::domain::{ApiAuthSummary, ApiLockout, Event, EventKind, OffenderCount};
+
+use super::EventLog;
+
+/// Offenders listed in the summary
+const TOP_OFFENDERS: usize = 10;
+
+#[derive(Default)]
+struct Attempts {
+    /// Failures within the lockout window, oldest first
+    failures: VecDeque<DateTime<Utc>>,
+    locked_until: Option<DateTime<Utc>>,
+}
+
+/// Counts rejected API tokens per client address and locks an address out
+/// for a while once it has too many
+pub struct AuthGuard {
+    max_failures: usize,
+    lockout: chrono::Duration,
+    clients: Mutex<HashMap<IpAddr, Attempts>>,
+    failures_total: AtomicU64,
+    lockouts_total: AtomicU64,
+    events: Arc<EventLog>,
+}
+
+impl AuthGuard {
+    /// `max_failures` within `lockout` lock an address out for `lockout`;
+    /// 0 only counts failures
+    pub fn new(max_failures: usize, lockout: Duration, events: Arc<EventLog>) -> Self {
+        Self {
+            max_failures,
+            lockout: chrono::Duration::from_std(lockout).unwrap_or_default(),
+            clients: Mutex::new(HashMap::new()),
+            failures_total: AtomicU64::new(0),
+            lockouts_total: AtomicU64::new(0),
+            events,
+        }
+    }
+
+    /// Time left before `ip` may try again, `None` unless it is locked out
+    pub fn locked(&self, ip: IpAddr) -> Option<Duration> {
+        let now = Utc::now();
+        let clients = self.clients.lock().unwrap();
+        let until = clients.get(&ip)?.locked_until.filter(|u| *u > now)?;
+        (until - now).to_std().ok()
+    }
+
+    /// Records a rejected token from `ip` on `route`; returns whether that
+    /// locked it out
+    pub fn record_failure(&self, ip: IpAddr, route: &str) -> bool {
+        self.record_failure_at(ip, route, Utc::now())
+    }
+
+    fn record_failure_at(&self, ip: IpAddr, route: &str, now: DateTime<Utc>) -> bool {
+        self.failures_total.fetch_add(1, Ordering::Relaxed);
+        tracing::warn!("Rejected API token from {} on {}", ip, route);
+
+        let cutoff = now - self.lockout;
+        let mut clients = self.clients.lock().unwrap();
+        clients.retain(|_, a| {
+            a.failures.back().is_some_and(|f| *f >= cutoff)
+                || a.locked_until.is_some_and(|u| u > now)
+        });
+        let attempts = clients.entry(ip).or_default();
+        while attempts.failures.front().is_some_and(|f| *f < cutoff) {
+            attempts.failures.pop_front();
+        }
+        attempts.failures.push_back(now);
+
+        let locked = attempts.locked_until.is_some_and(|u| u > now);
+        if self.max_failures == 0 || locked || attempts.failures.len() < self.max_failures {
+            return false;
+        }
+        attempts.locked_until = Some(now + self.lockout);
+        let failures = attempts.failures.len();
+        drop(clients);
+
+        self.lockouts_total.fetch_add(1, Ordering::Relaxed);
+        self.events.record(
+            Event::new(
+                EventKind::ApiLockout,
+                format!(
+                    "Locked {} out of the API for {} minutes after {} rejected tokens (last on {})",
+                    ip,
+                    self.lockout.num_minutes(),
+                    failures,
+                    route
+                ),
+            )
+            .with_resource(ip.to_string()),
+        );
+        true
+    }
+
+    /// Failure and lockout counters with the current offenders
+    pub fn summary(&self) -> ApiAuthSummary {
+        let now = Utc::now();
+        let cutoff = now - self.lockout;
+        let clients = self.clients.lock().unwrap();
+
+        let mut top_offenders: Vec<OffenderCount> = clients
+            .iter()
+            .map(|(ip, a)| OffenderCount {
+                ip: ip.to_string(),
+                failures: a.failures.iter().filter(|f| **f >= cutoff).count(),
+            })
+            .filter(|o| o.failures > 0)
+            .collect();
+        top_offenders.sort_by(|a, b| b.failures.cmp(&a.failures).then(a.ip.cmp(&b.ip)));
+        top_offenders.truncate(TOP_OFFENDERS);
+
+        let mut locked_out: Vec<ApiLockout> = clients
+            .iter()
+            .filter_map(|(ip, a)| {
+                Some(ApiLockout {
+                    ip: ip.to_string(),
+                    failures: a.failures.len(),
+                    until: a.locked_until.filter(|u| *u > now)?,
+                })
+            })
+            .collect();
+        locked_out.sort_by_key(|l| std::cmp::Reverse(l.until));
+
+        ApiAuthSummary {
+            max_failures: self.max_failures,
+            lockout_seconds: self.lockout.num_seconds().max(0) as u64,
+            failures_total: self.failures_total.load(Ordering::Relaxed),
+            lockouts_total: self.lockouts_total.load(Ordering::Relaxed),
+            top_offenders,
+            locked_out,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repeated_failures_lock_an_address_out() {
+        let guard = AuthGuard::new(3, Duration::from_secs(600), Arc::new(EventLog::new(10)));
+        let (attacker, typo) = ("203.0.113.9".parse().unwrap(), "192.0.2.1".parse().unwrap());
+        let start = Utc::now() - chrono::Duration::minutes(30);
+        let at = |minutes: i64| start + chrono::Duration::minutes(minutes);
+
+        // Failures further apart than the window never add up
+        assert!(!guard.record_failure_at(typo, "/api/v1/containers", at(0)));
+        assert!(!guard.record_failure_at(typo, "/api/v1/containers", at(11)));
+        assert!(!guard.record_failure_at(typo, "/api/v1/containers", at(22)));
+
+        assert!(!guard.record_failure_at(attacker, "/api/v1/admin/backup", at(25)));
+        assert!(!guard.record_failure_at(attacker, "/api/v1/admin/backup", at(26)));
+        assert!(guard.record_failure_at(attacker, "/api/v1/admin/backup", at(27)));
+        // Already locked out: counted, but not locked again
+        assert!(!guard.record_failure_at(attacker, "/api/v1/admin/backup", at(28)));
+
+        assert!(guard.locked(attacker).is_some());
+        assert!(guard.locked(typo).is_none());
+
+        let summary = guard.summary();
+        assert_eq!((summary.failures_total, summary.lockouts_total), (7, 1));
+        assert_eq!(summary.locked_out.len(), 1);
+        assert_eq!(summary.top_offenders[0].ip, "203.0.113.9");
+        assert_eq!(summary.top_offenders[0].failures, 4);
+    }
+}
@@ -0,0 +1,131 @@
+use std::collections::HashSet;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+
+use crate::domain::{AlertEvent, BackupJob, BackupStatus, Event, EventKind, Severity};
+use crate::ports::{AlertSink, BackupSource};
+
+use super::EventLog;
+
+/// Backups change at most a few times a day, no need to rescan on every poll
+const REFRESH_INTERVAL: Duration = Duration::from_secs(300);
+
+/// Verifies backup jobs and alerts when one goes stale
+pub struct BackupMonitor {
+    jobs: Vec<BackupJob>,
+    source: Arc<dyn BackupSource>,
+    statuses: RwLock<Vec<BackupStatus>>,
+    last_refresh: RwLock<Option<Instant>>,
+    alerted: RwLock<HashSet<String>>,
+    sink: Arc<dyn AlertSink>,
+    events: Arc<EventLog>,
+}
+
+impl BackupMonitor {
+    pub fn new(
+        jobs: Vec<BackupJob>,
+        source: Arc<dyn BackupSource>,
+        sink: Arc<dyn AlertSink>,
+        events: Arc<EventLog>,
+    ) -> Self {
+        Self {
+            jobs,
+            source,
+            statuses: RwLock::new(Vec::new()),
+            last_refresh: RwLock::new(None),
+            alerted: RwLock::new(HashSet::new()),
+            sink,
+            events,
+        }
+    }
+
+    /// Latest verification result for every job
+    pub fn statuses(&self) -> Vec<BackupStatus> {
+        self.statuses.read().unwrap().clone()
+    }
+
+    /// Re-inspect backup jobs if the refresh interval elapsed, alerting on stale ones
+    pub async fn refresh(&self, hostname: &str) {
+        let due = self
+            .last_refresh
+            .read()
+            .unwrap()
+            .is_none_or(|t| t.elapsed() >= REFRESH_INTERVAL);
+        if !due {
+            return;
+        }
+        *self.last_refresh.write().unwrap() = Some(Instant::now());
+
+        let now = Utc::now();
+        let mut statuses = Vec::with_capacity(self.jobs.len());
+        for job in &self.jobs {
+            let latest = match self.source.latest_artifact(job).await {
+                Ok(latest) => latest,
+                Err(e) => {
+                    tracing::warn!("Failed to inspect backup '{}': {}", job.name, e);
+                    None
+                }
+            };
+            let status = BackupStatus::evaluate(job, latest, now);
+            self.notify(job, &status, hostname).await;
+            statuses.push(status);
+        }
+
+        *self.statuses.write().unwrap() = statuses;
+    }
+
+    async fn notify(&self, job: &BackupJob, status: &BackupStatus, hostname: &str) {
+        if !status.stale {
+            if self.alerted.write().unwrap().remove(&job.name) {
+                self.events.record(
+                    Event::new(
+                        EventKind::BackupRecovered,
+                        format!("Backup '{}' is fresh again", job.name),
+                    )
+                    .with_resource(job.name.clone()),
+                );
+            }
+            return;
+        }
+
+        if !self.alerted.write().unwrap().insert(job.name.clone()) {
+            return;
+        }
+
+        let problem = status.problem.as_deref().unwrap_or("stale");
+        self.events.record(
+            Event::new(
+                EventKind::BackupStale,
+                format!("Backup '{}': {}", job.name, problem),
+            )
+            .with_resource(job.name.clone()),
+        );
+
+        let Some(url) = job.webhook_url.as_deref() else {
+            return;
+        };
+
+        let event = AlertEvent {
+            rule_name: format!("backup:{}", job.name),
+            severity: Severity::Critical,
+            incident_id: None,
+            escalation: None,
+            metric: "BackupAge".to_string(),
+            current_value: status.age_seconds.map(|a| a as f64).unwrap_or(-1.0),
+            threshold: (job.max_age_hours * 3600) as f64,
+            condition: "Above".to_string(),
+            hostname: hostname.to_string(),
+            timestamp: Utc::now().to_rfc3339(),
+        };
+
+        if let Err(e) = self.sink.send_alert(url, &event).await {
+            tracing::error!("Failed to send backup alert '{}': {}", job.name, e);
+        }
+    }
+
+    pub fn has_jobs(&self) -> bool {
+        !self.jobs.is_empty()
+    }
+}
@@ -0,0 +1,187 @@
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::domain::{CollectionCost, Collector, CollectorCost};
+
+/// A stretched collector still runs at least every this many polls
+const MAX_STRIDE: u32 = 8;
+
+/// Weight of the latest measurement in a collector's running cost
+const COST_WEIGHT: f64 = 0.3;
+
+/// A stretch is undone only if the cost then stays under this share of the
+/// budget, so intervals do not flap around the limit
+const RELAX_SHARE: f64 = 0.7;
+
+#[derive(Debug, Clone, Copy)]
+struct CollectorState {
+    /// Smoothed seconds per run
+    cost: f64,
+    /// Collected every `stride` polls
+    stride: u32,
+    /// Polls left until the next collection
+    countdown: u32,
+}
+
+impl Default for CollectorState {
+    fn default() -> Self {
+        Self {
+            cost: 0.0,
+            stride: 1,
+            countdown: 1,
+        }
+    }
+}
+
+impl CollectorState {
+    fn amortized(&self) -> f64 {
+        self.cost / self.stride as f64
+    }
+}
+
+/// Measures what each collector costs per poll and stretches the interval of
+/// the most expensive ones (process scan, container stats) while collecting
+/// takes more than its share of the poll interval
+pub struct CollectionBudget {
+    budget: Duration,
+    collectors: Mutex<BTreeMap<Collector, CollectorState>>,
+}
+
+impl CollectionBudget {
+    /// `fraction` of `poll_interval` may be spent collecting
+    pub fn new(poll_interval: Duration, fraction: f64) -> Self {
+        Self {
+            budget: poll_interval.mul_f64(fraction.clamp(0.0, 1.0)),
+            collectors: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Collectors to skip on this poll, their data carried over; call once per poll
+    pub fn skipped(&self) -> Vec<Collector> {
+        let mut collectors = self.collectors.lock().unwrap();
+        let mut skipped = Vec::new();
+        for (collector, state) in collectors.iter_mut() {
+            if state.countdown > 1 {
+                state.countdown -= 1;
+                skipped.push(*collector);
+            } else {
+                state.countdown = state.stride;
+            }
+        }
+        skipped
+    }
+
+    /// Time one collector took on this poll
+    pub fn record(&self, collector: Collector, elapsed: Duration) {
+        let mut collectors = self.collectors.lock().unwrap();
+        let state = collectors.entry(collector).or_default();
+        state.cost = if state.cost == 0.0 {
+            elapsed.as_secs_f64()
+        } else {
+            state.cost * (1.0 - COST_WEIGHT) + elapsed.as_secs_f64() * COST_WEIGHT
+        };
+    }
+
+    /// After a poll: stretch the collector weighing most on the budget while
+    /// over it, or undo the cheapest stretch once there is room again
+    pub fn adjust(&self) {
+        let budget = self.budget.as_secs_f64();
+        let mut collectors = self.collectors.lock().unwrap();
+        let amortized: f64 = collectors.values().map(CollectorState::amortized).sum();
+
+        if amortized > budget {
+            let heaviest = collectors
+                .iter_mut()
+                .filter(|(_, s)| s.stride < MAX_STRIDE)
+                .max_by(|(_, a), (_, b)| a.amortized().total_cmp(&b.amortized()));
+            if let Some((collector, state)) = heaviest {
+                state.stride *= 2;
+                state.countdown = state.stride;
+                tracing::info!(
+                    "Collection takes {:.0} ms per poll, over its {:.0} ms budget: collecting {} every {} polls",
+                    amortized * 1000.0,
+                    budget * 1000.0,
+                    collector.name(),
+                    state.stride
+                );
+            }
+            return;
+        }
+
+        let cheapest = collectors
+            .iter_mut()
+            .filter(|(_, s)| s.stride > 1)
+            .min_by(|(_, a), (_, b)| a.cost.total_cmp(&b.cost));
+        if let Some((collector, state)) = cheapest {
+            let relaxed = amortized - state.amortized() + state.cost / (state.stride / 2) as f64;
+            if relaxed <= budget * RELAX_SHARE {
+                state.stride /= 2;
+                state.countdown = state.countdown.min(state.stride);
+                tracing::info!(
+                    "Collection back within budget: collecting {} every {} poll(s)",
+                    collector.name(),
+                    state.stride
+                );
+            }
+        }
+    }
+
+    pub fn status(&self) -> CollectionCost {
+        let collectors = self.collectors.lock().unwrap();
+        CollectionCost {
+            budget_ms: self.budget.as_secs_f64() * 1000.0,
+            amortized_ms: collectors
+                .values()
+                .map(CollectorState::amortized)
+                .sum::<f64>()
+                * 1000.0,
+            collectors: collectors
+                .iter()
+                .map(|(collector, state)| CollectorCost {
+                    collector: *collector,
+                    cost_ms: state.cost * 1000.0,
+                    every_polls: state.stride,
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn poll(budget: &CollectionBudget, processes_ms: u64) -> Vec<Collector> {
+        let skipped = budget.skipped();
+        if !skipped.contains(&Collector::Processes) {
+            budget.record(Collector::Processes, Duration::from_millis(processes_ms));
+        }
+        if !skipped.contains(&Collector::Disks) {
+            budget.record(Collector::Disks, Duration::from_millis(10));
+        }
+        budget.adjust();
+        skipped
+    }
+
+    #[test]
+    fn test_stretches_expensive_collector_and_relaxes() {
+        // 1s poll, half of it for collecting
+        let budget = CollectionBudget::new(Duration::from_secs(1), 0.5);
+
+        // A 900 ms process scan needs a stride of 2 to fit in 500 ms
+        poll(&budget, 900);
+        let status = budget.status();
+        assert_eq!(status.collectors[0].collector, Collector::Disks);
+        assert_eq!(status.collectors[0].every_polls, 1);
+        assert_eq!(status.collectors[1].every_polls, 2);
+        assert!(poll(&budget, 900).contains(&Collector::Processes));
+        assert!(!poll(&budget, 900).contains(&Collector::Processes));
+
+        // Cheaper scans bring it back to every poll
+        for _ in 0..20 {
+            poll(&budget, 50);
+        }
+        assert_eq!(budget.status().collectors[1].every_polls, 1);
+    }
+}
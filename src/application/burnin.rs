@@ -0,0 +1,175 @@
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use chrono::Utc;
+use thiserror::Error;
+
+use crate::domain::{AlertRule, BurnInReport, Event, EventKind, Host};
+
+use super::EventLog;
+
+/// Longest burn-in, long enough for a weekend of stress-ng
+pub const MAX_BURN_IN: Duration = Duration::from_secs(72 * 3600);
+
+#[derive(Debug, Error)]
+pub enum BurnInError {
+    #[error("A burn-in is already running until {0}")]
+    Running(chrono::DateTime<Utc>),
+
+    #[error("Duration must be between 1s and {}h", MAX_BURN_IN.as_secs() / 3600)]
+    InvalidDuration,
+}
+
+/// Stress-test mode: while it runs, alerts are recorded as expected instead
+/// of notified, and a report of the hardware's peaks is kept
+pub struct BurnInService {
+    /// The running burn-in, or the last one until the next starts
+    report: RwLock<Option<BurnInReport>>,
+    events: Arc<EventLog>,
+}
+
+impl BurnInService {
+    pub fn new(events: Arc<EventLog>) -> Self {
+        Self {
+            report: RwLock::new(None),
+            events,
+        }
+    }
+
+    pub fn start(
+        &self,
+        duration: Duration,
+        reason: Option<String>,
+    ) -> Result<BurnInReport, BurnInError> {
+        if duration.is_zero() || duration > MAX_BURN_IN {
+            return Err(BurnInError::InvalidDuration);
+        }
+        if let Some(running) = self.running() {
+            return Err(BurnInError::Running(running.until));
+        }
+
+        let now = Utc::now();
+        let until = now + chrono::Duration::from_std(duration).unwrap_or_default();
+        let report = BurnInReport::new(now, until, reason);
+        let message = format!(
+            "Burn-in started until {}, alerts are expected{}",
+            self.events.describe_time(until),
+            report
+                .reason
+                .as_deref()
+                .map(|r| format!(": {}", r))
+                .unwrap_or_default()
+        );
+        self.events.record(Event::new(EventKind::BurnIn, message));
+        *self.report.write().unwrap() = Some(report.clone());
+        Ok(report)
+    }
+
+    /// Ends the running burn-in now, returning its report
+    pub fn end(&self) -> Option<BurnInReport> {
+        self.finish(Utc::now())
+    }
+
+    /// The running burn-in, after closing it if its time is up
+    pub fn running(&self) -> Option<BurnInReport> {
+        let until = self
+            .report
+            .read()
+            .unwrap()
+            .as_ref()
+            .filter(|r| r.is_running())?
+            .until;
+        if until <= Utc::now() {
+            self.finish(until);
+            return None;
+        }
+        self.report.read().unwrap().clone()
+    }
+
+    /// The running burn-in, or else the last one
+    pub fn report(&self) -> Option<BurnInReport> {
+        self.running();
+        self.report.read().unwrap().clone()
+    }
+
+    /// Adds a snapshot to the running burn-in's report
+    pub fn observe(&self, host: &Host, throttle_count: Option<u64>) {
+        if let Some(report) = self.report.write().unwrap().as_mut() {
+            if report.is_running() && host.timestamp <= report.until {
+                report.observe(host, throttle_count);
+            }
+        }
+    }
+
+    /// Records `rule` firing at `value` when a burn-in is running; `true`
+    /// means the alert is expected and must not be notified
+    pub fn expect(&self, rule: &AlertRule, value: f64) -> bool {
+        if self.running().is_none() {
+            return false;
+        }
+        match self.report.write().unwrap().as_mut() {
+            Some(report) if report.is_running() => {
+                report.expect_alert(rule, value, Utc::now());
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn finish(&self, at: chrono::DateTime<Utc>) -> Option<BurnInReport> {
+        let report = {
+            let mut report = self.report.write().unwrap();
+            let report = report.as_mut().filter(|r| r.is_running())?;
+            report.ended_at = Some(at);
+            report.clone()
+        };
+        self.events.record(Event::new(
+            EventKind::BurnIn,
+            format!("Burn-in ended: {}", report.summary()),
+        ));
+        Some(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{AlertCondition, AlertMetric, Severity};
+
+    #[test]
+    fn test_alerts_are_expected_only_while_running() {
+        let events = Arc::new(EventLog::new(10));
+        let burn_in = BurnInService::new(events.clone());
+        let rule = AlertRule {
+            name: "cpu".to_string(),
+            metric: AlertMetric::CpuUsage,
+            condition: AlertCondition::Above,
+            threshold: 90.0,
+            webhook_url: String::new(),
+            cooldown_seconds: 0,
+            severity: Severity::Warning,
+            escalation: Vec::new(),
+        };
+
+        assert!(!burn_in.expect(&rule, 95.0));
+        assert!(matches!(
+            burn_in.start(Duration::ZERO, None),
+            Err(BurnInError::InvalidDuration)
+        ));
+        burn_in
+            .start(Duration::from_secs(3600), Some("new NAS".to_string()))
+            .unwrap();
+        assert!(matches!(
+            burn_in.start(Duration::from_secs(60), None),
+            Err(BurnInError::Running(_))
+        ));
+        assert!(burn_in.expect(&rule, 95.0));
+
+        let report = burn_in.end().unwrap();
+        assert_eq!(report.expected_alerts.len(), 1);
+        assert!(!burn_in.expect(&rule, 95.0));
+        assert!(burn_in.report().is_some_and(|r| !r.is_running()));
+        assert!(burn_in.end().is_none());
+        assert_eq!(events.recent(10).len(), 2);
+    }
+}
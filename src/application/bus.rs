@@ -0,0 +1,70 @@
+use tokio::sync::broadcast;
+
+use crate::domain::DomainEvent;
+
+/// Events buffered per subscriber; one that falls further behind skips the
+/// oldest ones
+pub const BUS_CAPACITY: usize = 64;
+
+/// Carries snapshots, log entries and alert incidents from the subsystems
+/// producing them to any number of subscribers, without either side knowing
+/// the other
+pub struct EventBus {
+    sender: broadcast::Sender<DomainEvent>,
+}
+
+impl EventBus {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            sender: broadcast::channel(capacity.max(1)).0,
+        }
+    }
+
+    /// Hands `event` to every current subscriber; never blocks, and is a
+    /// no-op without subscribers
+    pub fn publish(&self, event: DomainEvent) {
+        let _ = self.sender.send(event);
+    }
+
+    /// Receive every event published from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<DomainEvent> {
+        self.sender.subscribe()
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new(BUS_CAPACITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{Event, EventKind};
+    use tokio::sync::broadcast::error::TryRecvError;
+
+    fn recorded(message: &str) -> DomainEvent {
+        DomainEvent::Recorded(Event::new(EventKind::Maintenance, message.to_string()))
+    }
+
+    #[test]
+    fn test_subscribers_see_events_published_after_subscribing() {
+        let bus = EventBus::new(2);
+        bus.publish(recorded("before anyone listens"));
+
+        let mut receiver = bus.subscribe();
+        for message in ["one", "two", "three"] {
+            bus.publish(recorded(message));
+        }
+        // The slow subscriber lost the oldest event, not the newest
+        assert!(matches!(receiver.try_recv(), Err(TryRecvError::Lagged(1))));
+        for expected in ["two", "three"] {
+            match receiver.try_recv() {
+                Ok(DomainEvent::Recorded(event)) => assert_eq!(event.message, expected),
+                other => panic!("unexpected {:?}", other),
+            }
+        }
+        assert!(matches!(receiver.try_recv(), Err(TryRecvError::Empty)));
+    }
+}
@@ -0,0 +1,80 @@
+use std::sync::{Arc, RwLock};
+
+use crate::domain::{Capabilities, Capability};
+use crate::ports::CapabilityProbe;
+
+/// Without these NanoMon has nothing useful to show
+#[cfg(feature = "docker")]
+const CORE_CAPABILITIES: &[&str] = &["host_metrics", "containers"];
+#[cfg(not(feature = "docker"))]
+const CORE_CAPABILITIES: &[&str] = &["host_metrics"];
+
+/// Tracks which collectors can work with the current permissions, so the UI
+/// can hide panels instead of showing zeros
+pub struct CapabilityMonitor {
+    probes: Vec<Arc<dyn CapabilityProbe>>,
+    fixed: Vec<(&'static str, Capability)>,
+    current: RwLock<Capabilities>,
+}
+
+impl CapabilityMonitor {
+    pub fn new(probes: Vec<Arc<dyn CapabilityProbe>>) -> Self {
+        Self {
+            probes,
+            fixed: Vec::new(),
+            current: RwLock::new(Capabilities::new()),
+        }
+    }
+
+    /// Capability decided once at startup (e.g. by configuration)
+    pub fn with_capability(mut self, name: &'static str, capability: Capability) -> Self {
+        self.fixed.push((name, capability));
+        self
+    }
+
+    /// Re-run all probes
+    pub async fn refresh(&self) -> Capabilities {
+        let mut capabilities: Capabilities = self.fixed.iter().cloned().collect();
+        for probe in &self.probes {
+            capabilities.extend(probe.probe().await);
+        }
+        *self.current.write().unwrap() = capabilities.clone();
+        capabilities
+    }
+
+    /// Result of the last refresh
+    pub fn current(&self) -> Capabilities {
+        self.current.read().unwrap().clone()
+    }
+
+    pub fn is_ready(capabilities: &Capabilities) -> bool {
+        CORE_CAPABILITIES
+            .iter()
+            .all(|name| capabilities.get(name).is_some_and(|c| c.available))
+    }
+}
+
+#[cfg(all(test, feature = "docker"))]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+
+    struct NoDocker;
+
+    #[async_trait]
+    impl CapabilityProbe for NoDocker {
+        async fn probe(&self) -> Vec<(&'static str, Capability)> {
+            vec![("containers", Capability::unavailable("socket missing"))]
+        }
+    }
+
+    #[tokio::test]
+    async fn test_not_ready_without_core_collector() {
+        let monitor = CapabilityMonitor::new(vec![Arc::new(NoDocker)])
+            .with_capability("host_metrics", Capability::available());
+
+        let capabilities = monitor.refresh().await;
+        assert!(!CapabilityMonitor::is_ready(&capabilities));
+        assert_eq!(monitor.current().len(), 2);
+    }
+}
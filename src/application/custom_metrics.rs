@@ -0,0 +1,116 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use crate::domain::{CollectorConfig, CustomMetric};
+use crate::ports::MetricCollector;
+
+/// Runs user-defined collectors, each on its own interval, and keeps their
+/// latest values for the next snapshot
+pub struct CustomMetricsService {
+    collectors: Vec<(CollectorConfig, Arc<dyn MetricCollector>)>,
+    latest: RwLock<BTreeMap<String, Vec<CustomMetric>>>,
+}
+
+impl CustomMetricsService {
+    pub fn new(collectors: Vec<(CollectorConfig, Arc<dyn MetricCollector>)>) -> Self {
+        Self {
+            collectors,
+            latest: RwLock::new(BTreeMap::new()),
+        }
+    }
+
+    pub fn has_collectors(&self) -> bool {
+        !self.collectors.is_empty()
+    }
+
+    /// Run one collector and replace its values. On failure they are dropped
+    /// rather than kept, so alerts never fire on stale data.
+    async fn refresh(&self, config: &CollectorConfig, collector: &dyn MetricCollector) {
+        let metrics = match collector.collect().await {
+            Ok(metrics) => metrics,
+            Err(e) => {
+                tracing::warn!("Collector {} failed: {}", config.name, e);
+                Vec::new()
+            }
+        };
+        self.latest
+            .write()
+            .unwrap()
+            .insert(config.name.clone(), metrics);
+    }
+
+    /// Run every collector forever at its own interval
+    pub async fn run(self: Arc<Self>) {
+        let loops = self.collectors.iter().map(|(config, collector)| {
+            let service = self.clone();
+            async move {
+                let mut ticker =
+                    tokio::time::interval(Duration::from_secs(config.interval_seconds.max(1)));
+                loop {
+                    ticker.tick().await;
+                    service.refresh(config, collector.as_ref()).await;
+                }
+            }
+        });
+        futures::future::join_all(loops).await;
+    }
+
+    /// Latest values of all collectors, by collector name
+    pub fn latest(&self) -> Vec<CustomMetric> {
+        self.latest
+            .read()
+            .unwrap()
+            .values()
+            .flatten()
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use chrono::Utc;
+
+    use crate::domain::CollectorKind;
+
+    struct Fixed(Option<f64>);
+
+    #[async_trait]
+    impl MetricCollector for Fixed {
+        async fn collect(
+            &self,
+        ) -> Result<Vec<CustomMetric>, Box<dyn std::error::Error + Send + Sync>> {
+            let value = self.0.ok_or("sensor unplugged")?;
+            Ok(vec![CustomMetric {
+                collector: "ups".to_string(),
+                name: "battery".to_string(),
+                value,
+                unit: None,
+                labels: BTreeMap::new(),
+                collected_at: Utc::now(),
+            }])
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failed_collection_drops_values() {
+        let config = CollectorConfig {
+            name: "ups".to_string(),
+            kind: CollectorKind::Script {
+                command: vec!["true".to_string()],
+            },
+            interval_seconds: 60,
+            timeout_seconds: 10,
+        };
+        let service = CustomMetricsService::new(Vec::new());
+
+        service.refresh(&config, &Fixed(Some(97.0))).await;
+        assert_eq!(service.latest()[0].value, 97.0);
+
+        service.refresh(&config, &Fixed(None)).await;
+        assert!(service.latest().is_empty());
+    }
+}
@@ -0,0 +1,118 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, RwLock};
+
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use tokio::sync::broadcast::{self, error::RecvError};
+
+use crate::domain::{format_utc_and_local, DomainEvent, Event, EventSubscription};
+use crate::ports::AlertSink;
+
+use super::EventBus;
+
+/// Bounded in-memory log of notable events, newest last.
+/// Optionally publishes recorded events on the event bus.
+pub struct EventLog {
+    events: RwLock<VecDeque<Event>>,
+    max_size: usize,
+    bus: Option<Arc<EventBus>>,
+    timezone: Tz,
+}
+
+impl EventLog {
+    pub fn new(max_size: usize) -> Self {
+        Self {
+            events: RwLock::new(VecDeque::with_capacity(max_size.min(64))),
+            max_size,
+            bus: None,
+            timezone: Tz::UTC,
+        }
+    }
+
+    /// Timezone used for times in human-readable messages
+    pub fn with_timezone(mut self, timezone: Tz) -> Self {
+        self.timezone = timezone;
+        self
+    }
+
+    /// Render a time for an event message, in UTC and the display timezone
+    pub fn describe_time(&self, ts: DateTime<Utc>) -> String {
+        format_utc_and_local(ts, self.timezone)
+    }
+
+    /// Publish each recorded event as `DomainEvent::Recorded`
+    pub fn with_bus(mut self, bus: Arc<EventBus>) -> Self {
+        self.bus = Some(bus);
+        self
+    }
+
+    /// Record an event, evicting the oldest one when full
+    pub fn record(&self, event: Event) {
+        tracing::info!("Event: {}", event.message);
+
+        if let Some(bus) = &self.bus {
+            bus.publish(DomainEvent::Recorded(event.clone()));
+        }
+
+        let mut events = self.events.write().unwrap();
+        if events.len() >= self.max_size {
+            events.pop_front();
+        }
+        events.push_back(event);
+    }
+
+    /// Get the most recent events, newest first
+    pub fn recent(&self, limit: usize) -> Vec<Event> {
+        self.events
+            .read()
+            .unwrap()
+            .iter()
+            .rev()
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+}
+
+/// Pushes the entries of the events log to the webhooks subscribed to their
+/// kind, as the bus delivers them
+pub struct EventPusher {
+    sink: Arc<dyn AlertSink>,
+    subscriptions: Vec<EventSubscription>,
+}
+
+impl EventPusher {
+    pub fn new(sink: Arc<dyn AlertSink>, subscriptions: Vec<EventSubscription>) -> Self {
+        Self {
+            sink,
+            subscriptions,
+        }
+    }
+
+    /// Pushes events until the bus closes; each push runs on its own task so
+    /// a slow webhook never holds up the others
+    pub async fn run(self, mut events: broadcast::Receiver<DomainEvent>) {
+        loop {
+            let event = match events.recv().await {
+                Ok(DomainEvent::Recorded(event)) => event,
+                Ok(_) => continue,
+                Err(RecvError::Lagged(skipped)) => {
+                    tracing::warn!("Event webhooks fell behind, {} events not pushed", skipped);
+                    continue;
+                }
+                Err(RecvError::Closed) => return,
+            };
+            for subscription in self.subscriptions.iter().filter(|s| s.matches(&event)) {
+                let sink = self.sink.clone();
+                let subscription = subscription.clone();
+                let pushed = event.clone();
+                tokio::spawn(async move {
+                    let secret = subscription.secret.as_deref();
+                    if let Err(e) = sink.send_event(&subscription.url, &pushed, secret).await {
+                        tracing::error!("Failed to push event: {}", e);
+                    }
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,194 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+
+use crate::domain::{FirewallSummary, RuleCounter, RuleCounterSeries};
+use crate::ports::FirewallSource;
+
+/// Rulesets rarely change; avoid spawning nft on every request
+const CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// Summarizes the host firewall on demand and, opted in, samples rule counters
+pub struct FirewallMonitor {
+    source: Arc<dyn FirewallSource>,
+    cache: RwLock<Option<(Instant, Option<FirewallSummary>)>>,
+    counter_samples: RwLock<VecDeque<(DateTime<Utc>, Vec<RuleCounter>)>>,
+    /// Zero when counters are not sampled
+    max_counter_samples: usize,
+}
+
+impl FirewallMonitor {
+    pub fn new(source: Arc<dyn FirewallSource>) -> Self {
+        Self {
+            source,
+            cache: RwLock::new(None),
+            counter_samples: RwLock::new(VecDeque::new()),
+            max_counter_samples: 0,
+        }
+    }
+
+    /// Keep this many rule counter samples; `run_counters` takes them
+    pub fn with_counter_history(mut self, samples: usize) -> Self {
+        self.max_counter_samples = samples;
+        self
+    }
+
+    pub fn counters_enabled(&self) -> bool {
+        self.max_counter_samples > 0
+    }
+
+    pub async fn run_counters(self: Arc<Self>, interval: Duration) {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            match self.source.rule_counters().await {
+                Ok(Some(counters)) => self.record_counters(Utc::now(), counters),
+                Ok(None) => {}
+                Err(e) => tracing::debug!("Failed to read firewall counters: {}", e),
+            }
+        }
+    }
+
+    fn record_counters(&self, at: DateTime<Utc>, counters: Vec<RuleCounter>) {
+        let mut samples = self.counter_samples.write().unwrap();
+        samples.push_back((at, counters));
+        while samples.len() > self.max_counter_samples {
+            samples.pop_front();
+        }
+    }
+
+    /// Latest totals of every counter with its hit rates between consecutive
+    /// samples; a counter reset (ruleset reload) yields no rate for that step
+    pub fn counter_series(&self) -> Vec<RuleCounterSeries> {
+        let samples = self.counter_samples.read().unwrap();
+        let Some((_, latest)) = samples.back() else {
+            return Vec::new();
+        };
+
+        let mut series: BTreeMap<_, RuleCounterSeries> = latest
+            .iter()
+            .map(|counter| {
+                (
+                    counter.key(),
+                    RuleCounterSeries {
+                        counter: counter.clone(),
+                        timestamps: Vec::new(),
+                        packets_per_second: Vec::new(),
+                        bytes_per_second: Vec::new(),
+                    },
+                )
+            })
+            .collect();
+
+        for ((before_at, before), (at, after)) in samples.iter().zip(samples.iter().skip(1)) {
+            let seconds = (*at - *before_at).num_milliseconds() as f64 / 1000.0;
+            if seconds <= 0.0 {
+                continue;
+            }
+            for counter in after {
+                let (Some(entry), Some(previous)) = (
+                    series.get_mut(&counter.key()),
+                    before.iter().find(|c| c.key() == counter.key()),
+                ) else {
+                    continue;
+                };
+                if counter.packets < previous.packets || counter.bytes < previous.bytes {
+                    continue;
+                }
+                entry.timestamps.push(*at);
+                entry
+                    .packets_per_second
+                    .push((counter.packets - previous.packets) as f64 / seconds);
+                entry
+                    .bytes_per_second
+                    .push((counter.bytes - previous.bytes) as f64 / seconds);
+            }
+        }
+
+        series.into_values().collect()
+    }
+
+    /// Current summary, `None` if neither nftables nor iptables could be read
+    pub async fn summary(
+        &self,
+    ) -> Result<Option<FirewallSummary>, Box<dyn std::error::Error + Send + Sync>> {
+        if let Some((at, summary)) = self.cache.read().unwrap().as_ref() {
+            if at.elapsed() < CACHE_TTL {
+                return Ok(summary.clone());
+            }
+        }
+
+        let summary = match self.source.ruleset().await? {
+            Some(ruleset) => {
+                let sockets = self.source.listening_sockets().await?;
+                Some(FirewallSummary::build(ruleset, &sockets))
+            }
+            None => None,
+        };
+
+        *self.cache.write().unwrap() = Some((Instant::now(), summary.clone()));
+        Ok(summary)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+
+    use crate::domain::{FirewallRuleset, ListeningSocket};
+
+    struct NoFirewall;
+
+    #[async_trait]
+    impl FirewallSource for NoFirewall {
+        async fn ruleset(
+            &self,
+        ) -> Result<Option<FirewallRuleset>, Box<dyn std::error::Error + Send + Sync>> {
+            Ok(None)
+        }
+
+        async fn rule_counters(
+            &self,
+        ) -> Result<Option<Vec<RuleCounter>>, Box<dyn std::error::Error + Send + Sync>> {
+            Ok(None)
+        }
+
+        async fn listening_sockets(
+            &self,
+        ) -> Result<Vec<ListeningSocket>, Box<dyn std::error::Error + Send + Sync>> {
+            Ok(Vec::new())
+        }
+    }
+
+    fn counter(name: &str, packets: u64) -> RuleCounter {
+        RuleCounter {
+            table: "inet filter".to_string(),
+            chain: Some("input".to_string()),
+            name: name.to_string(),
+            packets,
+            bytes: packets * 60,
+        }
+    }
+
+    #[test]
+    fn test_counter_rates_skip_resets() {
+        let monitor = FirewallMonitor::new(Arc::new(NoFirewall)).with_counter_history(3);
+        let start = Utc::now();
+        let at = |secs| start + chrono::Duration::seconds(secs);
+        monitor.record_counters(at(0), vec![counter("old", 1)]);
+        monitor.record_counters(at(10), vec![counter("geo-block", 100)]);
+        monitor.record_counters(at(20), vec![counter("geo-block", 300)]);
+        // Reload resets the counter
+        monitor.record_counters(at(30), vec![counter("geo-block", 5)]);
+
+        let series = monitor.counter_series();
+        assert_eq!(series.len(), 1);
+        assert_eq!(series[0].counter.packets, 5);
+        assert_eq!(series[0].packets_per_second, vec![20.0]);
+        assert_eq!(series[0].bytes_per_second, vec![1200.0]);
+        assert_eq!(series[0].timestamps, vec![at(20)]);
+    }
+}
@@ -0,0 +1,46 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use crate::domain::MemoryFootprint;
+use crate::ports::FootprintSource;
+
+/// Keeps NanoMon honest about its own size: reports resident memory and
+/// warns once each time it crosses the configured budget
+pub struct FootprintMonitor {
+    source: Arc<dyn FootprintSource>,
+    budget_bytes: Option<u64>,
+    over_budget: AtomicBool,
+}
+
+impl FootprintMonitor {
+    pub fn new(source: Arc<dyn FootprintSource>, budget_bytes: Option<u64>) -> Self {
+        Self {
+            source,
+            budget_bytes,
+            over_budget: AtomicBool::new(false),
+        }
+    }
+
+    /// Current footprint, `None` if it cannot be measured on this platform
+    pub fn check(&self) -> Option<MemoryFootprint> {
+        let footprint = self.source.memory().ok()?.with_budget(self.budget_bytes);
+
+        let was_over = self
+            .over_budget
+            .swap(footprint.over_budget, Ordering::Relaxed);
+        let mb = |bytes: u64| bytes / (1024 * 1024);
+        match (was_over, footprint.over_budget) {
+            (false, true) => tracing::warn!(
+                "NanoMon uses {} MB of memory, above its {} MB budget",
+                mb(footprint.rss_bytes),
+                mb(self.budget_bytes.unwrap_or_default())
+            ),
+            (true, false) => tracing::info!(
+                "NanoMon memory back within budget ({} MB)",
+                mb(footprint.rss_bytes)
+            ),
+            _ => {}
+        }
+        Some(footprint)
+    }
+}
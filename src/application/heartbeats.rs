@@ -0,0 +1,143 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, RwLock};
+
+use chrono::{DateTime, Utc};
+
+use crate::domain::{
+    AlertEvent, Event, EventKind, HeartbeatCheck, HeartbeatState, HeartbeatStatus, Severity,
+};
+use crate::ports::AlertSink;
+
+use super::EventLog;
+
+/// Tracks heartbeat pings from scheduled jobs and alerts when one goes missing
+pub struct HeartbeatMonitor {
+    checks: Vec<HeartbeatCheck>,
+    started_at: DateTime<Utc>,
+    last_ping: RwLock<HashMap<String, DateTime<Utc>>>,
+    alerted: RwLock<HashSet<String>>,
+    sink: Arc<dyn AlertSink>,
+    events: Arc<EventLog>,
+}
+
+impl HeartbeatMonitor {
+    pub fn new(
+        checks: Vec<HeartbeatCheck>,
+        sink: Arc<dyn AlertSink>,
+        events: Arc<EventLog>,
+    ) -> Self {
+        Self {
+            checks,
+            started_at: Utc::now(),
+            last_ping: RwLock::new(HashMap::new()),
+            alerted: RwLock::new(HashSet::new()),
+            sink,
+            events,
+        }
+    }
+
+    /// Record a ping carrying `token`. Returns false if no check with this
+    /// name is configured or it does not accept the token.
+    pub fn ping(&self, name: &str, token: Option<&str>, admin_token: Option<&str>) -> bool {
+        if !self
+            .checks
+            .iter()
+            .any(|c| c.name == name && c.accepts(token, admin_token))
+        {
+            return false;
+        }
+
+        self.last_ping
+            .write()
+            .unwrap()
+            .insert(name.to_string(), Utc::now());
+
+        if self.alerted.write().unwrap().remove(name) {
+            self.events.record(
+                Event::new(
+                    EventKind::HeartbeatRecovered,
+                    format!("Heartbeat '{}' is back", name),
+                )
+                .with_resource(name),
+            );
+        }
+
+        true
+    }
+
+    /// Current status of every configured check
+    pub fn statuses(&self) -> Vec<HeartbeatStatus> {
+        let now = Utc::now();
+        let last_ping = self.last_ping.read().unwrap();
+
+        self.checks
+            .iter()
+            .map(|check| {
+                let last = last_ping.get(&check.name).copied();
+                HeartbeatStatus {
+                    name: check.name.clone(),
+                    interval_seconds: check.interval_seconds,
+                    grace_seconds: check.grace_seconds,
+                    state: check.state_at(last, self.started_at, now),
+                    last_ping: last,
+                }
+            })
+            .collect()
+    }
+
+    /// Alert once for every check that just went down
+    pub async fn check(&self, hostname: &str) {
+        let newly_down: Vec<HeartbeatStatus> = {
+            let mut alerted = self.alerted.write().unwrap();
+            self.statuses()
+                .into_iter()
+                .filter(|s| s.state == HeartbeatState::Down && alerted.insert(s.name.clone()))
+                .collect()
+        };
+
+        for status in newly_down {
+            let since = status
+                .last_ping
+                .map(|t| format!("last ping {}", self.events.describe_time(t)))
+                .unwrap_or_else(|| "never pinged".to_string());
+            self.events.record(
+                Event::new(
+                    EventKind::HeartbeatMissed,
+                    format!("Heartbeat '{}' missed ({})", status.name, since),
+                )
+                .with_resource(status.name.clone()),
+            );
+
+            let Some(url) = self
+                .checks
+                .iter()
+                .find(|c| c.name == status.name)
+                .and_then(|c| c.webhook_url.as_deref())
+            else {
+                continue;
+            };
+
+            let reference = status.last_ping.unwrap_or(self.started_at);
+            let event = AlertEvent {
+                rule_name: format!("heartbeat:{}", status.name),
+                severity: Severity::Critical,
+                incident_id: None,
+                escalation: None,
+                metric: "Heartbeat".to_string(),
+                current_value: Utc::now().signed_duration_since(reference).num_seconds() as f64,
+                threshold: (status.interval_seconds + status.grace_seconds) as f64,
+                condition: "Above".to_string(),
+                hostname: hostname.to_string(),
+                timestamp: Utc::now().to_rfc3339(),
+            };
+
+            if let Err(e) = self.sink.send_alert(url, &event).await {
+                tracing::error!("Failed to send heartbeat alert '{}': {}", status.name, e);
+            }
+        }
+    }
+
+    pub fn has_checks(&self) -> bool {
+        !self.checks.is_empty()
+    }
+}
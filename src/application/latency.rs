@@ -0,0 +1,89 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use crate::domain::{PingSample, PingTarget};
+use crate::ports::PingProber;
+
+/// Echo requests sent per target on each round
+const PINGS_PER_ROUND: u32 = 5;
+
+/// Continuously pings configured targets and keeps a latency/loss history
+pub struct LatencyMonitor {
+    targets: Vec<PingTarget>,
+    prober: Arc<dyn PingProber>,
+    history: RwLock<HashMap<String, VecDeque<PingSample>>>,
+    max_samples: usize,
+}
+
+impl LatencyMonitor {
+    pub fn new(targets: Vec<PingTarget>, prober: Arc<dyn PingProber>, max_samples: usize) -> Self {
+        Self {
+            targets,
+            prober,
+            history: RwLock::new(HashMap::new()),
+            max_samples,
+        }
+    }
+
+    pub fn targets(&self) -> &[PingTarget] {
+        &self.targets
+    }
+
+    /// Probe all targets concurrently and append the results to their history
+    pub async fn probe_all(&self) {
+        let rounds = self.targets.iter().map(|target| async move {
+            let sample = match self.prober.probe(&target.host, PINGS_PER_ROUND).await {
+                Ok(sample) => sample,
+                Err(e) => {
+                    tracing::warn!("Ping to {} ({}) failed: {}", target.name, target.host, e);
+                    PingSample::unreachable(PINGS_PER_ROUND)
+                }
+            };
+            (target.name.clone(), sample)
+        });
+        let results = futures::future::join_all(rounds).await;
+
+        let mut history = self.history.write().unwrap();
+        for (name, sample) in results {
+            let samples = history.entry(name).or_default();
+            if samples.len() >= self.max_samples {
+                samples.pop_front();
+            }
+            samples.push_back(sample);
+        }
+    }
+
+    /// Probe forever at the given interval
+    pub async fn run(self: Arc<Self>, interval: Duration) {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            self.probe_all().await;
+        }
+    }
+
+    /// Most recent sample per target
+    pub fn latest(&self) -> Vec<(PingTarget, Option<PingSample>)> {
+        let history = self.history.read().unwrap();
+        self.targets
+            .iter()
+            .map(|t| {
+                let latest = history.get(&t.name).and_then(|s| s.back().cloned());
+                (t.clone(), latest)
+            })
+            .collect()
+    }
+
+    /// Full history for a target, `None` if the target is not configured
+    pub fn history(&self, name: &str) -> Option<Vec<PingSample>> {
+        self.targets.iter().find(|t| t.name == name)?;
+        let history = self.history.read().unwrap();
+        Some(
+            history
+                .get(name)
+                .map(|s| s.iter().cloned().collect())
+                .unwrap_or_default(),
+        )
+    }
+}
@@ -0,0 +1,227 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use thiserror::Error;
+
+use crate::ports::IdentityProvider;
+
+/// How long a started login may take at the provider
+const LOGIN_TIMEOUT: Duration = Duration::from_secs(600);
+
+#[derive(Debug, Error)]
+pub enum LoginError {
+    #[error("Login expired or was started elsewhere, please try again")]
+    UnknownState,
+
+    #[error("User '{0}' is not allowed to use this dashboard")]
+    NotAllowed(String),
+
+    #[error("{0}")]
+    Failed(String),
+}
+
+struct PendingLogin {
+    nonce: String,
+    return_to: String,
+    started: Instant,
+}
+
+struct Session {
+    user: String,
+    expires: Instant,
+}
+
+/// Dashboard logins through a single sign-on provider and the sessions they
+/// open, kept in memory so a restart logs everyone out
+pub struct LoginService {
+    provider: Arc<dyn IdentityProvider>,
+    allowed_users: Vec<String>,
+    session_ttl: Duration,
+    pending: Mutex<HashMap<String, PendingLogin>>,
+    sessions: Mutex<HashMap<String, Session>>,
+}
+
+impl LoginService {
+    pub fn new(provider: Arc<dyn IdentityProvider>, session_ttl: Duration) -> Self {
+        Self {
+            provider,
+            allowed_users: Vec::new(),
+            session_ttl,
+            pending: Mutex::new(HashMap::new()),
+            sessions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Only let these users in; anyone the provider vouches for when empty
+    pub fn with_allowed_users(mut self, users: Vec<String>) -> Self {
+        self.allowed_users = users;
+        self
+    }
+
+    pub fn session_ttl(&self) -> Duration {
+        self.session_ttl
+    }
+
+    /// Starts a login that lands on `return_to` once done; returns the
+    /// provider URL to send the browser to
+    pub async fn start(&self, return_to: String) -> Result<String, LoginError> {
+        let (state, nonce) = (random_id()?, random_id()?);
+        let url = self
+            .provider
+            .authorization_url(&state, &nonce)
+            .await
+            .map_err(|e| LoginError::Failed(e.to_string()))?;
+
+        let mut pending = self.pending.lock().unwrap();
+        pending.retain(|_, p| p.started.elapsed() < LOGIN_TIMEOUT);
+        pending.insert(
+            state,
+            PendingLogin {
+                nonce,
+                return_to,
+                started: Instant::now(),
+            },
+        );
+        Ok(url)
+    }
+
+    /// Completes the login `state` names with the provider's `code`; returns
+    /// the new session ID, its user and where to go next
+    pub async fn finish(
+        &self,
+        state: &str,
+        code: &str,
+    ) -> Result<(String, String, String), LoginError> {
+        let login = self
+            .pending
+            .lock()
+            .unwrap()
+            .remove(state)
+            .filter(|p| p.started.elapsed() < LOGIN_TIMEOUT)
+            .ok_or(LoginError::UnknownState)?;
+        let user = self
+            .provider
+            .exchange(code, &login.nonce)
+            .await
+            .map_err(|e| LoginError::Failed(e.to_string()))?;
+        if !self.allowed_users.is_empty() && !self.allowed_users.contains(&user) {
+            return Err(LoginError::NotAllowed(user));
+        }
+
+        let id = random_id()?;
+        let mut sessions = self.sessions.lock().unwrap();
+        sessions.retain(|_, s| s.expires > Instant::now());
+        sessions.insert(
+            id.clone(),
+            Session {
+                user: user.clone(),
+                expires: Instant::now() + self.session_ttl,
+            },
+        );
+        Ok((id, user, login.return_to))
+    }
+
+    /// The user logged in with session `id`, if it has not expired
+    pub fn user(&self, id: &str) -> Option<String> {
+        self.sessions
+            .lock()
+            .unwrap()
+            .get(id)
+            .filter(|s| s.expires > Instant::now())
+            .map(|s| s.user.clone())
+    }
+
+    pub fn logout(&self, id: &str) {
+        self.sessions.lock().unwrap().remove(id);
+    }
+}
+
+/// 256 random bits, hex encoded
+fn random_id() -> Result<String, LoginError> {
+    let mut bytes = [0u8; 32];
+    getrandom::fill(&mut bytes).map_err(|e| LoginError::Failed(e.to_string()))?;
+    Ok(bytes.iter().fold(String::with_capacity(64), |mut id, b| {
+        let _ = write!(id, "{:02x}", b);
+        id
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+
+    /// Issues identities for the code `"<user>:<nonce>"`
+    struct FakeProvider;
+
+    #[async_trait]
+    impl IdentityProvider for FakeProvider {
+        async fn authorization_url(
+            &self,
+            state: &str,
+            nonce: &str,
+        ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+            Ok(format!(
+                "https://sso/authorize?state={}&nonce={}",
+                state, nonce
+            ))
+        }
+
+        async fn exchange(
+            &self,
+            code: &str,
+            nonce: &str,
+        ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+            match code.split_once(':') {
+                Some((user, n)) if n == nonce => Ok(user.to_string()),
+                _ => Err("nonce mismatch".into()),
+            }
+        }
+    }
+
+    fn params(url: &str) -> (String, String) {
+        let query = url.split_once('?').unwrap().1;
+        let value = |key: &str| {
+            query
+                .split('&')
+                .find_map(|p| p.strip_prefix(key))
+                .unwrap()
+                .to_string()
+        };
+        (value("state="), value("nonce="))
+    }
+
+    #[tokio::test]
+    async fn test_login_opens_a_session_once() {
+        let logins = LoginService::new(Arc::new(FakeProvider), Duration::from_secs(3600))
+            .with_allowed_users(vec!["alice".to_string()]);
+
+        let (state, nonce) = params(&logins.start("/containers".to_string()).await.unwrap());
+        let (id, user, return_to) = logins
+            .finish(&state, &format!("alice:{}", nonce))
+            .await
+            .unwrap();
+        assert_eq!(
+            (user.as_str(), return_to.as_str()),
+            ("alice", "/containers")
+        );
+        assert_eq!(logins.user(&id).as_deref(), Some("alice"));
+
+        // A state is good for one callback only
+        assert!(matches!(
+            logins.finish(&state, &format!("alice:{}", nonce)).await,
+            Err(LoginError::UnknownState)
+        ));
+
+        let (state, nonce) = params(&logins.start("/".to_string()).await.unwrap());
+        assert!(matches!(
+            logins.finish(&state, &format!("mallory:{}", nonce)).await,
+            Err(LoginError::NotAllowed(_))
+        ));
+
+        logins.logout(&id);
+        assert_eq!(logins.user(&id), None);
+    }
+}
@@ -0,0 +1,174 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use chrono::Utc;
+use thiserror::Error;
+
+use crate::domain::{Collector, Event, EventKind, MaintenanceWindow};
+
+use super::EventLog;
+
+/// Longest pause, so a forgotten window cannot hide a collector for good
+pub const MAX_MAINTENANCE: Duration = Duration::from_secs(24 * 3600);
+
+#[derive(Debug, Error)]
+pub enum MaintenanceError {
+    #[error("No collectors to pause")]
+    NoCollectors,
+
+    #[error("Duration must be between 1s and {}h", MAX_MAINTENANCE.as_secs() / 3600)]
+    InvalidDuration,
+}
+
+/// Pauses collectors for a while; their data is served stale from the last
+/// snapshot until the window ends or is cancelled
+pub struct MaintenanceService {
+    windows: RwLock<BTreeMap<Collector, MaintenanceWindow>>,
+    events: Arc<EventLog>,
+}
+
+impl MaintenanceService {
+    pub fn new(events: Arc<EventLog>) -> Self {
+        Self {
+            windows: RwLock::new(BTreeMap::new()),
+            events,
+        }
+    }
+
+    /// Pause `collectors` for `duration`, extending or shortening any window
+    /// already open for them
+    pub fn start(
+        &self,
+        collectors: &[Collector],
+        duration: Duration,
+        reason: Option<String>,
+    ) -> Result<Vec<MaintenanceWindow>, MaintenanceError> {
+        if collectors.is_empty() {
+            return Err(MaintenanceError::NoCollectors);
+        }
+        if duration.is_zero() || duration > MAX_MAINTENANCE {
+            return Err(MaintenanceError::InvalidDuration);
+        }
+
+        let now = Utc::now();
+        let until = now + chrono::Duration::from_std(duration).unwrap_or_default();
+        let mut windows = self.windows.write().unwrap();
+        for &collector in collectors {
+            windows.insert(
+                collector,
+                MaintenanceWindow {
+                    collector,
+                    started_at: now,
+                    until,
+                    reason: reason.clone(),
+                },
+            );
+            let message = format!(
+                "Collection of {} paused until {}{}",
+                collector.name(),
+                self.events.describe_time(until),
+                reason
+                    .as_deref()
+                    .map(|r| format!(": {}", r))
+                    .unwrap_or_default()
+            );
+            self.events.record(
+                Event::new(EventKind::Maintenance, message).with_resource(collector.name()),
+            );
+        }
+        Ok(collectors.iter().map(|c| windows[c].clone()).collect())
+    }
+
+    /// Resume every paused collector now; returns how many were paused
+    pub fn end(&self) -> usize {
+        let ended = std::mem::take(&mut *self.windows.write().unwrap());
+        for collector in ended.keys() {
+            self.record_resumed(*collector);
+        }
+        ended.len()
+    }
+
+    /// Open windows; expired ones are closed on the way
+    pub fn active(&self) -> Vec<MaintenanceWindow> {
+        let now = Utc::now();
+        let expired = {
+            let windows = self.windows.read().unwrap();
+            windows.values().any(|w| w.until <= now)
+        };
+        if expired {
+            let mut windows = self.windows.write().unwrap();
+            windows.retain(|collector, window| {
+                if window.until > now {
+                    return true;
+                }
+                self.record_resumed(*collector);
+                false
+            });
+        }
+        self.windows.read().unwrap().values().cloned().collect()
+    }
+
+    fn record_resumed(&self, collector: Collector) {
+        self.events.record(
+            Event::new(
+                EventKind::Maintenance,
+                format!("Collection of {} resumed", collector.name()),
+            )
+            .with_resource(collector.name()),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_windows_expire_and_validate() {
+        let events = Arc::new(EventLog::new(10));
+        let maintenance = MaintenanceService::new(events.clone());
+        let paused = |collector| {
+            maintenance
+                .active()
+                .iter()
+                .any(|w| w.collector == collector)
+        };
+
+        assert!(maintenance
+            .start(
+                &[Collector::Containers],
+                Duration::from_secs(48 * 3600),
+                None
+            )
+            .is_err());
+        assert!(maintenance
+            .start(&[], Duration::from_secs(60), None)
+            .is_err());
+
+        maintenance
+            .start(
+                &[Collector::Containers, Collector::Disks],
+                Duration::from_secs(600),
+                Some("engine upgrade".to_string()),
+            )
+            .unwrap();
+        assert!(paused(Collector::Disks));
+        assert!(!paused(Collector::Processes));
+
+        // Expired windows resume on their own
+        maintenance
+            .windows
+            .write()
+            .unwrap()
+            .get_mut(&Collector::Disks)
+            .unwrap()
+            .until = Utc::now();
+        assert!(!paused(Collector::Disks));
+        assert_eq!(maintenance.active().len(), 1);
+        assert_eq!(events.recent(1)[0].message, "Collection of disks resumed");
+
+        assert_eq!(maintenance.end(), 1);
+        assert!(maintenance.active().is_empty());
+    }
+}
@@ -1,5 +1,61 @@
 pub mod alerting;
+mod auth_guard;
+mod backups;
+mod budget;
+mod burnin;
+mod bus;
+mod capabilities;
+mod custom_metrics;
+mod events;
+mod firewall;
+mod footprint;
+mod heartbeats;
+mod latency;
+mod login;
+mod maintenance;
 mod monitoring;
+mod notify;
+mod power;
+mod process_control;
+mod reactions;
+mod reboot;
+mod reclaim;
+mod remote_hosts;
+mod security;
+mod speedtest;
+mod ssh;
+mod supervisor;
+mod topology;
+mod vms;
+mod volumes;
 
 pub use alerting::AlertEvaluator;
-pub use monitoring::MonitoringService;
+pub use auth_guard::AuthGuard;
+pub use backups::BackupMonitor;
+pub use budget::CollectionBudget;
+pub use burnin::{BurnInError, BurnInService, MAX_BURN_IN};
+pub use bus::{EventBus, BUS_CAPACITY};
+pub use capabilities::CapabilityMonitor;
+pub use custom_metrics::CustomMetricsService;
+pub use events::{EventLog, EventPusher};
+pub use firewall::FirewallMonitor;
+pub use footprint::FootprintMonitor;
+pub use heartbeats::HeartbeatMonitor;
+pub use latency::LatencyMonitor;
+pub use login::{LoginError, LoginService};
+pub use maintenance::{MaintenanceError, MaintenanceService};
+pub use monitoring::{MonitoringService, SnapshotObserver};
+pub use notify::{Delivery, NotificationRouter};
+pub use power::{PowerError, PowerService};
+pub use process_control::{ProcessControlError, ProcessControlService};
+pub use reactions::ReactionService;
+pub use reboot::RebootDetector;
+pub use reclaim::ReclaimService;
+pub use remote_hosts::{IngestError, RemoteHostService, RemoteHostSummary};
+pub use security::SecurityMonitor;
+pub use speedtest::SpeedtestMonitor;
+pub use ssh::SshMonitor;
+pub use supervisor::ContainerSupervisor;
+pub use topology::TopologyService;
+pub use vms::VmMonitor;
+pub use volumes::VolumeUsageService;
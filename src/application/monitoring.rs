@@ -1,17 +1,72 @@
-use std::sync::Arc;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
 
-use chrono::Utc;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use tokio::sync::broadcast;
+use tracing::Instrument;
 
-use crate::domain::{Container, Host, Process, Stack, SystemdService};
+use crate::domain::{
+    idle_containers, rate_per_second, resolve_containers, top_talkers, unresolved_processes,
+    Annotation, CollectionCost, CollectionEpoch, Collector, Container, DiskRate, DomainEvent,
+    Event, EventKind, Host, IdleContainer, MemoryTopology, MemoryTrend, Process, ProcessFilter,
+    ProcessScanStats, QuotaRecommendation, SpikeAnalysis, Stack, SystemdService, Talker,
+    UsageRates, DISK_WARN_PERCENT, KERNEL_LIMIT_WARN_PERCENT,
+};
 use crate::ports::{ContainerSource, MetricStore, ProcessSource, ServiceSource, SystemSource};
 
+use super::{CollectionBudget, CustomMetricsService, EventBus, EventLog, MaintenanceService};
+
+/// How far back memory trends look for leak detection
+const TREND_WINDOW: Duration = Duration::from_secs(6 * 3600);
+
+/// How far back host memory rates look; short enough to follow a change in workload
+const MEMORY_RATE_WINDOW: Duration = Duration::from_secs(10 * 60);
+
+/// How far back disk rates look; disks fill slowly and in bursts
+const DISK_RATE_WINDOW: Duration = Duration::from_secs(3600);
+
+/// How often snapshots are collected unless `with_poll_interval` says otherwise
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Polls a stored snapshot may fall behind before reads collect their own
+const STALE_AFTER_POLLS: u32 = 2;
+
+/// Points of a custom metric over time, keyed by label set
+pub type LabeledSeries = BTreeMap<BTreeMap<String, String>, Vec<(DateTime<Utc>, f64)>>;
+
+/// Subsystems reacting to each polled snapshot before it is stored, such as
+/// alert evaluation
+#[async_trait]
+pub trait SnapshotObserver: Send + Sync {
+    async fn observe(&self, snapshot: &Host);
+}
+
 /// Main application service for monitoring
 pub struct MonitoringService {
     system_source: Arc<dyn SystemSource>,
     container_source: Arc<dyn ContainerSource>,
     process_source: Arc<dyn ProcessSource>,
     service_source: Option<Arc<dyn ServiceSource>>,
+    custom_metrics: Option<Arc<CustomMetricsService>>,
+    maintenance: Option<Arc<MaintenanceService>>,
+    budget: Option<CollectionBudget>,
+    poll_interval: Duration,
     metric_store: Arc<dyn MetricStore>,
+    events: Arc<EventLog>,
+    flagged_leaks: RwLock<HashSet<String>>,
+    flagged_limits: RwLock<HashSet<&'static str>>,
+    flagged_pids: RwLock<HashSet<String>>,
+    flagged_disks: RwLock<HashSet<String>>,
+    /// Whether each container was running at the previous poll, `None`
+    /// before the first
+    container_states: RwLock<Option<HashMap<String, bool>>>,
+    sequence: AtomicU64,
+    /// Annotations waiting for the next stored snapshot
+    pending_annotations: Mutex<Vec<Annotation>>,
+    bus: Arc<EventBus>,
 }
 
 impl MonitoringService {
@@ -20,16 +75,42 @@ impl MonitoringService {
         container_source: Arc<dyn ContainerSource>,
         process_source: Arc<dyn ProcessSource>,
         metric_store: Arc<dyn MetricStore>,
+        events: Arc<EventLog>,
     ) -> Self {
+        // Continue numbering from restored history
+        let last_sequence = metric_store
+            .get_latest()
+            .and_then(|h| h.sequence)
+            .unwrap_or(0);
         Self {
             system_source,
             container_source,
             process_source,
             service_source: None,
+            custom_metrics: None,
+            maintenance: None,
+            budget: None,
+            poll_interval: DEFAULT_POLL_INTERVAL,
             metric_store,
+            events,
+            flagged_leaks: RwLock::new(HashSet::new()),
+            flagged_limits: RwLock::new(HashSet::new()),
+            flagged_pids: RwLock::new(HashSet::new()),
+            flagged_disks: RwLock::new(HashSet::new()),
+            container_states: RwLock::new(None),
+            sequence: AtomicU64::new(last_sequence),
+            pending_annotations: Mutex::new(Vec::new()),
+            bus: Arc::new(EventBus::default()),
         }
     }
 
+    /// Publish stored snapshots on a bus shared with other subsystems
+    /// rather than one of its own
+    pub fn with_bus(mut self, bus: Arc<EventBus>) -> Self {
+        self.bus = bus;
+        self
+    }
+
     pub fn with_service_source(mut self, source: Arc<dyn ServiceSource>) -> Self {
         if source.is_available() {
             self.service_source = Some(source);
@@ -37,19 +118,162 @@ impl MonitoringService {
         self
     }
 
+    pub fn with_custom_metrics(mut self, custom_metrics: Arc<CustomMetricsService>) -> Self {
+        if custom_metrics.has_collectors() {
+            self.custom_metrics = Some(custom_metrics);
+        }
+        self
+    }
+
+    pub fn with_maintenance(mut self, maintenance: Arc<MaintenanceService>) -> Self {
+        self.maintenance = Some(maintenance);
+        self
+    }
+
+    /// Stretch expensive collectors when collecting takes more than its budget
+    pub fn with_budget(mut self, budget: CollectionBudget) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// What collecting costs against its budget, `None` when unlimited
+    pub fn collection_cost(&self) -> Option<CollectionCost> {
+        self.budget.as_ref().map(|b| b.status())
+    }
+
+    /// Collectors currently paused for maintenance
+    fn paused(&self) -> Vec<Collector> {
+        self.maintenance
+            .as_ref()
+            .map(|m| m.active().into_iter().map(|w| w.collector).collect())
+            .unwrap_or_default()
+    }
+
     /// Collect a complete host snapshot with all metrics
     pub async fn collect_all(&self) -> Result<Host, Box<dyn std::error::Error + Send + Sync>> {
+        self.collect(false).await
+    }
+
+    /// With `budgeted`, collector costs count against the collection budget and
+    /// collectors it stretched are skipped on this poll
+    #[tracing::instrument(skip(self))]
+    async fn collect(
+        &self,
+        budgeted: bool,
+    ) -> Result<Host, Box<dyn std::error::Error + Send + Sync>> {
+        // Paused and skipped collectors are not queried; their last data is carried over
+        let mut stale = self.paused();
+        let previous = self.metric_store.get_latest();
+        let budget = self.budget.as_ref().filter(|_| budgeted);
+        if let (Some(budget), Some(_)) = (budget, &previous) {
+            stale.extend(budget.skipped());
+            stale.sort();
+            stale.dedup();
+        }
+        let carried = |collector| stale.contains(&collector).then_some(previous.as_deref());
+        // Containers and processes reference each other, so record when each was read
+        let mut epoch = CollectionEpoch::new(Utc::now());
+        let epoch_started = Instant::now();
+        let read_at = || Some(epoch_started.elapsed().as_millis() as u64);
+        let timed = |collector, started: Instant| {
+            if let Some(budget) = budget {
+                budget.record(collector, started.elapsed());
+            }
+        };
+        let disks = async {
+            match carried(Collector::Disks) {
+                Some(previous) => Ok(previous.map(|p| p.disks.clone()).unwrap_or_default()),
+                None => {
+                    let started = Instant::now();
+                    let disks = self.system_source.list_disks().await;
+                    timed(Collector::Disks, started);
+                    disks
+                }
+            }
+        }
+        .instrument(tracing::info_span!("collector", collector = "disks"));
+        let interfaces = async {
+            match carried(Collector::Network) {
+                Some(previous) => Ok(previous
+                    .map(|p| p.network_interfaces.clone())
+                    .unwrap_or_default()),
+                None => {
+                    let started = Instant::now();
+                    let interfaces = self.system_source.list_network_interfaces().await;
+                    timed(Collector::Network, started);
+                    interfaces
+                }
+            }
+        }
+        .instrument(tracing::info_span!("collector", collector = "network"));
+        let containers = async {
+            match carried(Collector::Containers) {
+                Some(previous) => Ok((
+                    previous.map(|p| p.containers.clone()).unwrap_or_default(),
+                    None,
+                )),
+                None => {
+                    let started = Instant::now();
+                    let containers = self.container_source.list_containers().await;
+                    timed(Collector::Containers, started);
+                    containers.map(|containers| (containers, read_at()))
+                }
+            }
+        }
+        .instrument(tracing::info_span!("collector", collector = "containers"));
+        let processes = async {
+            match carried(Collector::Processes) {
+                Some(previous) => Ok((
+                    previous.map(|p| p.processes.clone()).unwrap_or_default(),
+                    None,
+                )),
+                None => {
+                    let started = Instant::now();
+                    let processes = self.process_source.list_processes().await;
+                    timed(Collector::Processes, started);
+                    processes.map(|processes| (processes, read_at()))
+                }
+            }
+        }
+        .instrument(tracing::info_span!("collector", collector = "processes"));
+
         // Collect all metrics in parallel
         let (host_info, cpu, memory, load_avg, disks, interfaces, containers, processes) = tokio::try_join!(
             self.system_source.get_host_info(),
             self.system_source.get_cpu_metrics(),
             self.system_source.get_memory_metrics(),
             self.system_source.get_load_average(),
-            self.system_source.list_disks(),
-            self.system_source.list_network_interfaces(),
-            self.container_source.list_containers(),
-            self.process_source.list_processes(),
+            disks,
+            interfaces,
+            containers,
+            processes,
         )?;
+        let ((mut containers, containers_ms), (mut processes, processes_ms)) =
+            (containers, processes);
+        epoch.containers_ms = containers_ms;
+        epoch.processes_ms = processes_ms;
+
+        // A container started after the list was read has processes pointing
+        // at it already: read the list again once, while both reads are fresh
+        if containers_ms.is_some()
+            && processes_ms.is_some()
+            && unresolved_processes(&processes, &containers) > 0
+        {
+            match self.container_source.list_containers().await {
+                Ok(relisted) => {
+                    containers = relisted;
+                    epoch.containers_ms = read_at();
+                    epoch.retries += 1;
+                }
+                Err(e) => tracing::debug!("Container list retry failed: {}", e),
+            }
+        }
+        epoch.unresolved = resolve_containers(&mut processes, &containers);
 
         // Temperatures are optional - don't fail the whole collection
         let temperatures = self
@@ -58,6 +282,12 @@ impl MonitoringService {
             .await
             .unwrap_or_default();
 
+        let kernel_limits = self
+            .system_source
+            .get_kernel_limits()
+            .await
+            .unwrap_or_default();
+
         let host = Host::new(host_info.hostname)
             .with_metrics(host_info.uptime_seconds, load_avg, cpu, memory)
             .with_network_interfaces(interfaces)
@@ -65,14 +295,68 @@ impl MonitoringService {
             .with_containers(containers)
             .with_processes(processes)
             .with_temperatures(temperatures)
+            .with_kernel_limits(kernel_limits)
+            .with_custom_metrics(
+                self.custom_metrics
+                    .as_ref()
+                    .map(|c| c.latest())
+                    .unwrap_or_default(),
+            )
+            .with_stale(stale)
+            .with_epoch(epoch)
             .with_timestamp(Utc::now());
 
+        if let Some(budget) = budget {
+            budget.adjust();
+        }
         Ok(host)
     }
 
-    /// Store a snapshot in the metric store
-    pub fn store_snapshot(&self, snapshot: Host) {
+    /// Collect the next numbered snapshot for the store
+    pub async fn collect_next(&self) -> Result<Host, Box<dyn std::error::Error + Send + Sync>> {
+        // Taken before collecting so a failed attempt shows up as a gap in history
+        let sequence = self.sequence.fetch_add(1, Ordering::Relaxed) + 1;
+        Ok(self.collect(true).await?.with_sequence(sequence))
+    }
+
+    /// Store a snapshot in the metric store and publish it on the bus
+    pub fn store_snapshot(&self, mut snapshot: Host) {
+        snapshot
+            .annotations
+            .append(&mut self.pending_annotations.lock().unwrap());
         self.metric_store.store(snapshot);
+        if let Some(latest) = self.metric_store.get_latest() {
+            self.bus.publish(DomainEvent::Snapshot(latest));
+        }
+    }
+
+    /// Poll forever: collect a snapshot every interval, hand it to
+    /// `observer`, store it, then look for trends in the stored history
+    pub async fn run(self: Arc<Self>, observer: impl SnapshotObserver) {
+        let mut interval = tokio::time::interval(self.poll_interval);
+        loop {
+            interval.tick().await;
+            match self.collect_next().await {
+                Ok(snapshot) => {
+                    observer.observe(&snapshot).await;
+                    self.store_snapshot(snapshot);
+                    self.detect_memory_leaks();
+                    self.detect_kernel_limit_pressure();
+                    self.detect_container_pids_pressure();
+                    self.detect_disk_pressure();
+                    self.detect_container_changes();
+                }
+                Err(e) => {
+                    tracing::error!("Failed to collect metrics: {}", e);
+                }
+            }
+        }
+    }
+
+    /// Receive each newly stored snapshot, along with whatever else is
+    /// published on the bus
+    pub fn subscribe(&self) -> broadcast::Receiver<DomainEvent> {
+        self.bus.subscribe()
     }
 
     /// Get history from the metric store
@@ -80,61 +364,437 @@ impl MonitoringService {
         self.metric_store.get_history(duration)
     }
 
+    /// Attach a note to the next stored snapshot, so it shares the history's retention
+    pub fn annotate(&self, annotation: Annotation) {
+        self.pending_annotations.lock().unwrap().push(annotation);
+    }
+
+    /// Annotations timestamped within `duration`, oldest first, including ones
+    /// not stored yet
+    pub fn get_annotations(&self, duration: Duration) -> Vec<Annotation> {
+        let since = Utc::now() - chrono::Duration::from_std(duration).unwrap_or_default();
+        let mut annotations: Vec<Annotation> = self
+            .metric_store
+            .get_history(duration)
+            .iter()
+            .flat_map(|s| s.annotations.iter().cloned())
+            .chain(self.pending_annotations.lock().unwrap().iter().cloned())
+            .filter(|a| a.timestamp >= since)
+            .collect();
+        annotations.sort_by_key(|a| a.timestamp);
+        annotations
+    }
+
     /// Get the latest stored snapshot
     pub fn get_latest_snapshot(&self) -> Option<Arc<Host>> {
         self.metric_store.get_latest()
     }
 
-    /// Get all containers
+    /// The snapshot of the latest poll, so reads don't scrape procfs and the
+    /// container runtime again. Collected on the spot before the first poll,
+    /// or when polling fell behind (a stuck collector, restored history).
+    pub async fn snapshot(&self) -> Result<Arc<Host>, Box<dyn std::error::Error + Send + Sync>> {
+        match self.metric_store.get_latest() {
+            Some(host) if self.is_fresh(&host, Utc::now()) => Ok(host),
+            _ => Ok(Arc::new(self.collect_all().await?)),
+        }
+    }
+
+    /// Whether `host` is at most `STALE_AFTER_POLLS` poll intervals old
+    fn is_fresh(&self, host: &Host, now: DateTime<Utc>) -> bool {
+        let max_age = chrono::Duration::from_std(self.poll_interval * STALE_AFTER_POLLS)
+            .unwrap_or(chrono::Duration::MAX);
+        now.signed_duration_since(host.timestamp) <= max_age
+    }
+
+    /// Containers of the latest poll, annotated with their memory trend when
+    /// history allows
     pub async fn get_containers(
         &self,
     ) -> Result<Vec<Container>, Box<dyn std::error::Error + Send + Sync>> {
-        self.container_source.list_containers().await
+        let containers = self.snapshot().await?.containers.clone();
+        let mut trends = self.get_memory_trends();
+
+        Ok(containers
+            .into_iter()
+            .map(|c| {
+                let trend = trends.remove(&c.identity());
+                c.with_memory_trend(trend)
+            })
+            .collect())
     }
 
-    /// Get containers grouped by stack
-    pub async fn get_stacks(&self) -> Result<Vec<Stack>, Box<dyn std::error::Error + Send + Sync>> {
-        let containers = self.get_containers().await?;
-        let mut stacks_map = std::collections::HashMap::new();
+    /// Aggregates of one stack in each stored snapshot within `duration`, oldest first.
+    /// Snapshots where none of its containers existed are left out.
+    pub fn get_stack_history(&self, name: &str, duration: Duration) -> Vec<(DateTime<Utc>, Stack)> {
+        self.metric_store
+            .get_history(duration)
+            .iter()
+            .filter_map(|s| Stack::in_snapshot(name, &s.containers).map(|st| (s.timestamp, st)))
+            .collect()
+    }
 
-        for container in containers {
-            if let Some(stack_name) = &container.stack {
-                stacks_map
-                    .entry(stack_name.clone())
-                    .or_insert_with(Vec::new)
-                    .push(container);
+    /// Stored values of a custom metric within `duration`, one series per label set
+    pub fn get_custom_metric_history(&self, name: &str, duration: Duration) -> LabeledSeries {
+        let mut series: BTreeMap<_, Vec<_>> = BTreeMap::new();
+        for snapshot in self.metric_store.get_history(duration) {
+            for metric in snapshot.custom_metrics.iter().filter(|m| m.name == name) {
+                series
+                    .entry(metric.labels.clone())
+                    .or_default()
+                    .push((metric.collected_at, metric.value));
             }
         }
+        // A collector slower than the poll interval shows up in several snapshots
+        for points in series.values_mut() {
+            points.dedup_by_key(|(t, _)| *t);
+        }
+        series
+    }
 
-        let mut stacks: Vec<Stack> = stacks_map
+    /// Heaviest consumers of any kind over the last `window` of stored history
+    pub fn get_top_talkers(&self, window: Duration, limit: usize, redact: bool) -> Vec<Talker> {
+        top_talkers(&self.metric_store.get_history(window), limit, redact)
+    }
+
+    /// Running containers that stayed near idle over the last `window` of
+    /// stored history
+    pub fn get_idle_containers(&self, window: Duration) -> Vec<IdleContainer> {
+        idle_containers(&self.metric_store.get_history(window))
+    }
+
+    /// What drove the host between `from` and `to`, from stored history
+    /// reaching back as far again before `from`
+    pub fn analyze_spike(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        limit: usize,
+        redact: bool,
+    ) -> Option<SpikeAnalysis> {
+        let reach = (Utc::now() - (from - (to - from)))
+            .to_std()
+            .unwrap_or_default();
+        SpikeAnalysis::new(
+            &self.metric_store.get_history(reach),
+            from,
+            to,
+            limit,
+            redact,
+        )
+    }
+
+    /// Limits suggested for the container `identity` from the last `window`
+    /// of stored history, `None` while there is too little of it
+    pub fn get_quota_recommendation(
+        &self,
+        identity: &str,
+        window: Duration,
+    ) -> Option<QuotaRecommendation> {
+        QuotaRecommendation::from_history(identity, &self.metric_store.get_history(window))
+    }
+
+    /// Compute per-container memory trends from stored history, keyed by
+    /// `Container::identity` so a recreated container keeps its samples
+    pub fn get_memory_trends(&self) -> HashMap<String, MemoryTrend> {
+        let history = self.metric_store.get_history(TREND_WINDOW);
+        let mut samples: HashMap<String, Vec<(DateTime<Utc>, u64)>> = HashMap::new();
+        let mut limits: HashMap<String, u64> = HashMap::new();
+
+        for snapshot in &history {
+            for container in snapshot.containers.iter().filter(|c| c.state.is_running()) {
+                let identity = container.identity();
+                samples
+                    .entry(identity.clone())
+                    .or_default()
+                    .push((snapshot.timestamp, container.memory.used_bytes));
+                limits.insert(identity, container.memory.total_bytes);
+            }
+        }
+
+        samples
             .into_iter()
-            .map(|(name, containers)| Stack::from_containers(name, &containers))
+            .filter_map(|(id, points)| {
+                let limit = limits.get(&id).copied().unwrap_or(0);
+                MemoryTrend::from_samples(&points, limit).map(|t| (id, t))
+            })
+            .collect()
+    }
+
+    /// Memory and per-disk rates of change from stored history
+    pub fn get_usage_rates(&self) -> UsageRates {
+        let memory: Vec<(DateTime<Utc>, u64)> = self
+            .metric_store
+            .get_history(MEMORY_RATE_WINDOW)
+            .iter()
+            .map(|s| (s.timestamp, s.memory.used_bytes))
             .collect();
 
-        // Sort stacks by CPU usage (descending) to highlight top consumers
-        stacks.sort_by(|a, b| {
-            b.cpu_percent
-                .partial_cmp(&a.cpu_percent)
-                .unwrap_or(std::cmp::Ordering::Equal)
+        let mut disks: BTreeMap<String, Vec<(DateTime<Utc>, u64)>> = BTreeMap::new();
+        for snapshot in self.metric_store.get_history(DISK_RATE_WINDOW) {
+            for disk in &snapshot.disks {
+                disks
+                    .entry(disk.mount_point.clone())
+                    .or_default()
+                    .push((snapshot.timestamp, disk.used_bytes));
+            }
+        }
+
+        UsageRates {
+            memory_used_bytes_per_minute: rate_per_second(&memory).map(|r| r * 60.0),
+            disks: disks
+                .into_iter()
+                .filter_map(|(mount_point, points)| {
+                    rate_per_second(&points).map(|r| DiskRate {
+                        mount_point,
+                        used_bytes_per_hour: r * 3600.0,
+                    })
+                })
+                .collect(),
+        }
+    }
+
+    /// Record an event for containers that newly look like they are leaking memory.
+    /// Runs against the latest stored snapshot.
+    pub fn detect_memory_leaks(&self) {
+        let Some(snapshot) = self.metric_store.get_latest() else {
+            return;
+        };
+        let trends = self.get_memory_trends();
+        let mut flagged = self.flagged_leaks.write().unwrap();
+
+        for container in &snapshot.containers {
+            let identity = container.identity();
+            let leaking = trends.get(&identity).filter(|t| t.likely_leak);
+            match leaking {
+                Some(trend) if flagged.insert(identity.clone()) => {
+                    self.events.record(
+                        Event::new(
+                            EventKind::MemoryLeak,
+                            format!("{}: {}", container.name, trend.summary()),
+                        )
+                        .with_resource(container.name.clone()),
+                    );
+                }
+                Some(_) => {}
+                None => {
+                    flagged.remove(&identity);
+                }
+            }
+        }
+
+        flagged.retain(|identity| {
+            snapshot
+                .containers
+                .iter()
+                .any(|c| &c.identity() == identity)
         });
+    }
+
+    /// Record an event when a kernel limit newly crosses the warning threshold.
+    /// Runs against the latest stored snapshot.
+    pub fn detect_kernel_limit_pressure(&self) {
+        let Some(snapshot) = self.metric_store.get_latest() else {
+            return;
+        };
+        let mut flagged = self.flagged_limits.write().unwrap();
+
+        for (name, limit) in snapshot.kernel_limits.iter() {
+            if limit.usage_percent < KERNEL_LIMIT_WARN_PERCENT {
+                flagged.remove(name);
+            } else if flagged.insert(name) {
+                self.events.record(
+                    Event::new(
+                        EventKind::KernelLimit,
+                        format!(
+                            "{} at {:.0}% ({} / {})",
+                            name, limit.usage_percent, limit.used, limit.max
+                        ),
+                    )
+                    .with_resource(name),
+                );
+            }
+        }
+    }
+
+    /// Record an event when a container newly nears its pids limit.
+    /// Runs against the latest stored snapshot.
+    pub fn detect_container_pids_pressure(&self) {
+        let Some(snapshot) = self.metric_store.get_latest() else {
+            return;
+        };
+        let mut flagged = self.flagged_pids.write().unwrap();
+
+        for container in &snapshot.containers {
+            let identity = container.identity();
+            let pressure = container.pids.and_then(|pids| {
+                let percent = pids.usage_percent?;
+                Some((pids, percent)).filter(|_| percent >= KERNEL_LIMIT_WARN_PERCENT)
+            });
+            match pressure {
+                Some((pids, percent)) if flagged.insert(identity.clone()) => {
+                    self.events.record(
+                        Event::new(
+                            EventKind::KernelLimit,
+                            format!(
+                                "{}: pids at {:.0}% ({} / {})",
+                                container.name,
+                                percent,
+                                pids.current,
+                                pids.limit.unwrap_or_default()
+                            ),
+                        )
+                        .with_resource(container.name.clone()),
+                    );
+                }
+                Some(_) => {}
+                None => {
+                    flagged.remove(&identity);
+                }
+            }
+        }
+
+        flagged.retain(|identity| {
+            snapshot
+                .containers
+                .iter()
+                .any(|c| &c.identity() == identity)
+        });
+    }
 
-        Ok(stacks)
+    /// Record an event when a filesystem newly fills past the warning
+    /// threshold. Runs against the latest stored snapshot.
+    pub fn detect_disk_pressure(&self) {
+        let Some(snapshot) = self.metric_store.get_latest() else {
+            return;
+        };
+        let mut flagged = self.flagged_disks.write().unwrap();
+
+        for disk in &snapshot.disks {
+            let percent = disk.usage_percent();
+            if percent < DISK_WARN_PERCENT {
+                flagged.remove(&disk.mount_point);
+            } else if flagged.insert(disk.mount_point.clone()) {
+                self.events.record(
+                    Event::new(
+                        EventKind::DiskThreshold,
+                        format!(
+                            "{} at {:.0}% ({} bytes free)",
+                            disk.mount_point, percent, disk.available_bytes
+                        ),
+                    )
+                    .with_resource(disk.mount_point.clone()),
+                );
+            }
+        }
+    }
+
+    /// Record an event when a container started, stopped or went away since
+    /// the previous poll. Runs against the latest stored snapshot.
+    pub fn detect_container_changes(&self) {
+        let Some(snapshot) = self.metric_store.get_latest() else {
+            return;
+        };
+        let current: HashMap<String, bool> = snapshot
+            .containers
+            .iter()
+            .map(|c| (c.identity(), c.state.is_running()))
+            .collect();
+        let Some(previous) = self.container_states.write().unwrap().replace(current) else {
+            return;
+        };
+
+        for container in &snapshot.containers {
+            let identity = container.identity();
+            let running = container.state.is_running();
+            let was_running = previous.get(&identity).copied();
+            if running && was_running != Some(true) {
+                self.events.record(
+                    Event::new(
+                        EventKind::ContainerStarted,
+                        format!("Container {} started", container.name),
+                    )
+                    .with_resource(identity),
+                );
+            } else if !running && was_running == Some(true) {
+                self.events.record(
+                    Event::new(
+                        EventKind::ContainerStopped,
+                        format!(
+                            "Container {} is {}",
+                            container.name,
+                            format!("{:?}", container.state).to_lowercase()
+                        ),
+                    )
+                    .with_resource(identity),
+                );
+            }
+        }
+        for (identity, was_running) in previous {
+            let gone = !snapshot.containers.iter().any(|c| c.identity() == identity);
+            if was_running && gone {
+                self.events.record(
+                    Event::new(
+                        EventKind::ContainerStopped,
+                        format!("Container {} went away", identity),
+                    )
+                    .with_resource(identity),
+                );
+            }
+        }
+    }
+
+    /// Get the most recent events, newest first
+    pub fn get_events(&self, limit: usize) -> Vec<Event> {
+        self.events.recent(limit)
     }
 
     /// Get top N processes sorted by CPU
     pub async fn get_top_processes_by_cpu(
         &self,
         n: usize,
+        filter: &ProcessFilter,
     ) -> Result<Vec<Process>, Box<dyn std::error::Error + Send + Sync>> {
-        self.process_source.get_top_by_cpu(n).await
+        self.process_source.get_top_by_cpu(n, filter).await
     }
 
     /// Get top N processes sorted by memory
     pub async fn get_top_processes_by_memory(
         &self,
         n: usize,
+        filter: &ProcessFilter,
+    ) -> Result<Vec<Process>, Box<dyn std::error::Error + Send + Sync>> {
+        self.process_source.get_top_by_memory(n, filter).await
+    }
+
+    /// Get top N processes from lowest to highest nice
+    pub async fn get_top_processes_by_nice(
+        &self,
+        n: usize,
+        filter: &ProcessFilter,
     ) -> Result<Vec<Process>, Box<dyn std::error::Error + Send + Sync>> {
-        self.process_source.get_top_by_memory(n).await
+        self.process_source.get_top_by_nice(n, filter).await
+    }
+
+    /// Hugepages and per-NUMA-node memory; too detailed for every snapshot
+    pub async fn get_memory_topology(
+        &self,
+    ) -> Result<MemoryTopology, Box<dyn std::error::Error + Send + Sync>> {
+        self.system_source.get_memory_topology().await
+    }
+
+    /// Tracked connections by protocol and host port
+    pub async fn get_connections_by_port(
+        &self,
+    ) -> Result<Option<HashMap<(String, u16), u64>>, Box<dyn std::error::Error + Send + Sync>> {
+        self.system_source.connections_by_port().await
+    }
+
+    /// Thermal throttling events of the CPUs since boot, where they count them
+    pub async fn get_thermal_throttle_count(
+        &self,
+    ) -> Result<Option<u64>, Box<dyn std::error::Error + Send + Sync>> {
+        self.system_source.thermal_throttle_count().await
     }
 
     /// Get all processes
@@ -155,8 +815,168 @@ impl MonitoringService {
         }
     }
 
+    /// Outcome of the latest process listing
+    pub fn process_scan_stats(&self) -> Option<ProcessScanStats> {
+        self.process_source.last_scan()
+    }
+
     /// Check if systemd monitoring is available
     pub fn has_services(&self) -> bool {
         self.service_source.is_some()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::MemoryStore;
+    use crate::domain::{
+        ContainerId, ContainerMount, ContainerState, CpuMetrics, Disk, LoadAverage, MemoryMetrics,
+        NetworkInterface,
+    };
+    use crate::ports::{ContainerStats, HostInfo};
+    use std::sync::atomic::AtomicUsize;
+
+    /// A host with one container, `fresh`, counting its collections
+    #[derive(Default)]
+    struct Sources {
+        collections: AtomicUsize,
+    }
+
+    type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+    #[async_trait]
+    impl SystemSource for Sources {
+        async fn get_host_info(&self) -> Result<HostInfo> {
+            self.collections.fetch_add(1, Ordering::Relaxed);
+            Ok(HostInfo {
+                hostname: "nas".to_string(),
+                uptime_seconds: 60,
+            })
+        }
+        async fn get_cpu_metrics(&self) -> Result<CpuMetrics> {
+            Ok(CpuMetrics::new(0.0, 0.0, 0.0))
+        }
+        async fn get_memory_metrics(&self) -> Result<MemoryMetrics> {
+            Ok(MemoryMetrics::new(0, 0, 0))
+        }
+        async fn get_load_average(&self) -> Result<LoadAverage> {
+            Ok(LoadAverage::new(0.0, 0.0, 0.0))
+        }
+        async fn list_disks(&self) -> Result<Vec<Disk>> {
+            Ok(Vec::new())
+        }
+        async fn list_network_interfaces(&self) -> Result<Vec<NetworkInterface>> {
+            Ok(Vec::new())
+        }
+    }
+
+    #[async_trait]
+    impl ContainerSource for Sources {
+        async fn list_containers(&self) -> Result<Vec<Container>> {
+            Ok(vec![container("fresh")])
+        }
+        async fn get_container_stats(&self, _id: &ContainerId) -> Result<ContainerStats> {
+            Err("no stats".into())
+        }
+        async fn list_mounts(&self, _id: &ContainerId) -> Result<Option<Vec<ContainerMount>>> {
+            Ok(None)
+        }
+    }
+
+    #[async_trait]
+    impl ProcessSource for Sources {
+        async fn list_processes(&self) -> Result<Vec<Process>> {
+            Ok(Vec::new())
+        }
+        async fn get_top_by_cpu(&self, _n: usize, _f: &ProcessFilter) -> Result<Vec<Process>> {
+            Ok(Vec::new())
+        }
+        async fn get_top_by_memory(&self, _n: usize, _f: &ProcessFilter) -> Result<Vec<Process>> {
+            Ok(Vec::new())
+        }
+        async fn get_top_by_nice(&self, _n: usize, _f: &ProcessFilter) -> Result<Vec<Process>> {
+            Ok(Vec::new())
+        }
+    }
+
+    fn container(name: &str) -> Container {
+        Container::new(
+            ContainerId::new(name),
+            name.to_string(),
+            "image".to_string(),
+            ContainerState::Running,
+            Utc::now(),
+        )
+    }
+
+    fn service(sources: &Arc<Sources>, store: &Arc<MemoryStore>) -> MonitoringService {
+        MonitoringService::new(
+            sources.clone(),
+            sources.clone(),
+            sources.clone(),
+            store.clone(),
+            Arc::new(EventLog::new(10)),
+        )
+        .with_poll_interval(Duration::from_secs(10))
+    }
+
+    #[tokio::test]
+    async fn test_reads_collect_once_the_stored_snapshot_is_stale() {
+        let (sources, store) = (Arc::new(Sources::default()), Arc::new(MemoryStore::new(10)));
+        let service = service(&sources, &store);
+        let stored = |seconds_ago| {
+            Host::new("nas".to_string())
+                .with_containers(vec![container("stored")])
+                .with_timestamp(Utc::now() - chrono::Duration::seconds(seconds_ago))
+        };
+        let names = |containers: Vec<Container>| -> Vec<String> {
+            containers.into_iter().map(|c| c.name).collect()
+        };
+
+        // Within two poll intervals: served as stored
+        store.store(stored(15));
+        assert_eq!(names(service.get_containers().await.unwrap()), ["stored"]);
+        assert_eq!(
+            service.snapshot().await.unwrap().containers[0].name,
+            "stored"
+        );
+        assert_eq!(sources.collections.load(Ordering::Relaxed), 0);
+
+        // The poll loop stalled: both paths collect
+        store.store(stored(25));
+        assert_eq!(names(service.get_containers().await.unwrap()), ["fresh"]);
+        assert_eq!(
+            service.snapshot().await.unwrap().containers[0].name,
+            "fresh"
+        );
+        assert_eq!(sources.collections.load(Ordering::Relaxed), 2);
+    }
+
+    struct Seen(Arc<AtomicUsize>);
+
+    #[async_trait]
+    impl SnapshotObserver for Seen {
+        async fn observe(&self, _snapshot: &Host) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_observes_and_stores_each_poll() {
+        let (sources, store) = (Arc::new(Sources::default()), Arc::new(MemoryStore::new(10)));
+        let service =
+            Arc::new(service(&sources, &store).with_poll_interval(Duration::from_millis(10)));
+        let seen = Arc::new(AtomicUsize::new(0));
+
+        let poll = tokio::spawn(service.clone().run(Seen(seen.clone())));
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        poll.abort();
+
+        let latest = store.get_latest().unwrap();
+        assert_eq!(latest.containers[0].name, "fresh");
+        let polls = latest.sequence.unwrap() as usize;
+        assert!(polls >= 2);
+        assert!(seen.load(Ordering::Relaxed) >= polls);
+    }
+}
@@ -0,0 +1,357 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::domain::{AlertEvent, AlertRule, NotificationRoute};
+use crate::ports::AlertSink;
+
+/// What became of an alert handed to the router
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Delivery {
+    Sent,
+    /// Waiting to go out with the other alerts of its group
+    Queued,
+    /// The route sent a message too recently; try again later
+    Throttled,
+    /// Matched a route without destinations, or nowhere to send it
+    Dropped,
+}
+
+/// Where an alert is sent
+#[derive(Debug, PartialEq, Eq)]
+enum Target {
+    /// The destinations of a `[[routes]]` entry
+    Route(usize),
+    /// A rule's own webhook
+    Webhook(String),
+}
+
+enum Plan {
+    Done(Delivery),
+    Send(Target),
+}
+
+#[derive(Default)]
+struct RouteState {
+    last_sent: Option<Instant>,
+    /// Alerts of the group being collected
+    pending: Vec<AlertEvent>,
+}
+
+/// Sends each alert where the first matching `[[routes]]` entry says,
+/// throttling and grouping per route
+pub struct NotificationRouter {
+    routes: Vec<NotificationRoute>,
+    sink: Arc<dyn AlertSink>,
+    /// One per route
+    state: Mutex<Vec<RouteState>>,
+}
+
+impl NotificationRouter {
+    pub fn new(routes: Vec<NotificationRoute>, sink: Arc<dyn AlertSink>) -> Self {
+        let state = routes.iter().map(|_| RouteState::default()).collect();
+        Self {
+            routes,
+            sink,
+            state: Mutex::new(state),
+        }
+    }
+
+    /// Routes `event` of `rule`; a rule no route matches goes to its own
+    /// webhook
+    pub async fn dispatch(
+        self: &Arc<Self>,
+        rule: &AlertRule,
+        event: AlertEvent,
+    ) -> Result<Delivery, Box<dyn std::error::Error + Send + Sync>> {
+        match self.plan(rule, event.clone()) {
+            Plan::Done(delivery) => Ok(delivery),
+            Plan::Send(target) => {
+                self.send(&target, std::slice::from_ref(&event)).await?;
+                Ok(Delivery::Sent)
+            }
+        }
+    }
+
+    /// Routes the alerts due in one evaluation, sending those bound for the
+    /// same destination as a single digest. Results are in the order given.
+    pub async fn dispatch_digest(
+        self: &Arc<Self>,
+        alerts: Vec<(&AlertRule, AlertEvent)>,
+    ) -> Vec<Result<Delivery, String>> {
+        let mut results = Vec::with_capacity(alerts.len());
+        // Destination, then the alerts for it with their index in `results`
+        let mut digests: Vec<(Target, Vec<(usize, AlertEvent)>)> = Vec::new();
+        for (i, (rule, event)) in alerts.into_iter().enumerate() {
+            match self.plan(rule, event.clone()) {
+                Plan::Done(delivery) => results.push(Ok(delivery)),
+                Plan::Send(target) => {
+                    results.push(Ok(Delivery::Sent));
+                    match digests.iter_mut().find(|(t, _)| *t == target) {
+                        Some((_, events)) => events.push((i, event)),
+                        None => digests.push((target, vec![(i, event)])),
+                    }
+                }
+            }
+        }
+
+        for (target, members) in digests {
+            let events: Vec<AlertEvent> = members.iter().map(|(_, e)| e.clone()).collect();
+            if let Err(e) = self.send(&target, &events).await {
+                for (i, _) in &members {
+                    results[*i] = Err(e.to_string());
+                }
+            }
+        }
+        results
+    }
+
+    /// Where `event` goes; queues it when its route groups alerts
+    fn plan(self: &Arc<Self>, rule: &AlertRule, event: AlertEvent) -> Plan {
+        let Some(index) = self.routes.iter().position(|r| r.matches(rule)) else {
+            if rule.webhook_url.is_empty() {
+                return Plan::Done(Delivery::Dropped);
+            }
+            return Plan::Send(Target::Webhook(rule.webhook_url.clone()));
+        };
+        let route = &self.routes[index];
+        if route.notify.is_empty() {
+            return Plan::Done(Delivery::Dropped);
+        }
+
+        let mut state = self.state.lock().unwrap();
+        let state = &mut state[index];
+        if !state.pending.is_empty() {
+            // A rule firing again before the group goes out only updates it
+            match state
+                .pending
+                .iter_mut()
+                .find(|e| e.rule_name == event.rule_name)
+            {
+                Some(pending) => *pending = event,
+                None => state.pending.push(event),
+            }
+            return Plan::Done(Delivery::Queued);
+        }
+        let throttle = Duration::from_secs(route.throttle_seconds);
+        if state.last_sent.is_some_and(|at| at.elapsed() < throttle) {
+            return Plan::Done(Delivery::Throttled);
+        }
+        if route.group_seconds > 0 {
+            state.pending.push(event);
+            let router = self.clone();
+            let wait = Duration::from_secs(route.group_seconds);
+            tokio::spawn(async move {
+                tokio::time::sleep(wait).await;
+                router.flush(index).await;
+            });
+            return Plan::Done(Delivery::Queued);
+        }
+        state.last_sent = Some(Instant::now());
+        Plan::Send(Target::Route(index))
+    }
+
+    /// Sends the group collected for route `index`
+    async fn flush(&self, index: usize) {
+        let events = {
+            let mut state = self.state.lock().unwrap();
+            state[index].last_sent = Some(Instant::now());
+            std::mem::take(&mut state[index].pending)
+        };
+        if let Err(e) = self.send(&Target::Route(index), &events).await {
+            tracing::error!("Failed to send {} grouped alerts: {}", events.len(), e);
+        }
+    }
+
+    /// Sends to every destination of `target`; fails only when none got it
+    async fn send(
+        &self,
+        target: &Target,
+        events: &[AlertEvent],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let urls = match target {
+            Target::Route(index) => self.routes[*index].notify.as_slice(),
+            Target::Webhook(url) => std::slice::from_ref(url),
+        };
+        let mut delivered = false;
+        let mut last_error = None;
+        for url in urls {
+            let result = match events {
+                [event] => self.sink.send_alert(url, event).await,
+                _ => self.sink.send_alerts(url, events).await,
+            };
+            match result {
+                Ok(()) => delivered = true,
+                Err(e) => {
+                    tracing::warn!("Failed to notify a route destination: {}", e);
+                    last_error = Some(e);
+                }
+            }
+        }
+        match last_error {
+            Some(e) if !delivered => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{AlertCondition, AlertMetric, Event, Severity};
+    use async_trait::async_trait;
+
+    /// Records `(url, rules)` per message
+    #[derive(Default)]
+    struct RecordingSink {
+        sent: Mutex<Vec<(String, Vec<String>)>>,
+    }
+
+    #[async_trait]
+    impl AlertSink for RecordingSink {
+        async fn send_alert(
+            &self,
+            url: &str,
+            event: &AlertEvent,
+        ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+            self.send_alerts(url, std::slice::from_ref(event)).await
+        }
+
+        async fn send_alerts(
+            &self,
+            url: &str,
+            events: &[AlertEvent],
+        ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+            let rules = events.iter().map(|e| e.rule_name.clone()).collect();
+            self.sent.lock().unwrap().push((url.to_string(), rules));
+            Ok(())
+        }
+
+        async fn send_event(
+            &self,
+            _url: &str,
+            _event: &Event,
+            _secret: Option<&str>,
+        ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+            Ok(())
+        }
+    }
+
+    fn rule(name: &str, severity: Severity) -> AlertRule {
+        AlertRule {
+            name: name.to_string(),
+            metric: AlertMetric::CpuUsage,
+            condition: AlertCondition::Above,
+            threshold: 90.0,
+            webhook_url: format!("http://hooks/{}", name),
+            cooldown_seconds: 300,
+            severity,
+            escalation: Vec::new(),
+        }
+    }
+
+    fn event(rule: &AlertRule) -> AlertEvent {
+        AlertEvent {
+            rule_name: rule.name.clone(),
+            severity: rule.severity,
+            incident_id: None,
+            escalation: None,
+            metric: "CpuUsage".to_string(),
+            current_value: 95.0,
+            threshold: rule.threshold,
+            condition: "Above".to_string(),
+            hostname: "nas".to_string(),
+            timestamp: String::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_routes_throttle_group_and_drop() {
+        let sink = Arc::new(RecordingSink::default());
+        let routes = vec![
+            NotificationRoute {
+                severities: vec![Severity::Info],
+                ..Default::default()
+            },
+            NotificationRoute {
+                rules: vec!["cpu".to_string()],
+                notify: vec!["telegram:42".to_string()],
+                throttle_seconds: 600,
+                ..Default::default()
+            },
+            NotificationRoute {
+                severities: vec![Severity::Critical],
+                notify: vec!["mailto:ops@example.com".to_string()],
+                group_seconds: 1,
+                ..Default::default()
+            },
+        ];
+        let router = Arc::new(NotificationRouter::new(routes, sink.clone()));
+        let send = |rule: AlertRule| {
+            let router = router.clone();
+            async move { router.dispatch(&rule, event(&rule)).await.unwrap() }
+        };
+
+        assert_eq!(send(rule("noise", Severity::Info)).await, Delivery::Dropped);
+        assert_eq!(send(rule("cpu", Severity::Warning)).await, Delivery::Sent);
+        assert_eq!(
+            send(rule("cpu", Severity::Warning)).await,
+            Delivery::Throttled
+        );
+        assert_eq!(
+            send(rule("disk", Severity::Critical)).await,
+            Delivery::Queued
+        );
+        assert_eq!(
+            send(rule("ups", Severity::Critical)).await,
+            Delivery::Queued
+        );
+        assert_eq!(send(rule("temp", Severity::Warning)).await, Delivery::Sent);
+
+        tokio::time::sleep(Duration::from_millis(1200)).await;
+        let sent = sink.sent.lock().unwrap().clone();
+        let expected = [
+            ("telegram:42", vec!["cpu"]),
+            ("http://hooks/temp", vec!["temp"]),
+            ("mailto:ops@example.com", vec!["disk", "ups"]),
+        ]
+        .map(|(url, rules)| {
+            let rules = rules.into_iter().map(str::to_string).collect();
+            (url.to_string(), rules)
+        });
+        assert_eq!(sent, expected);
+    }
+
+    #[tokio::test]
+    async fn test_digest_sends_one_message_per_destination() {
+        let sink = Arc::new(RecordingSink::default());
+        let routes = vec![NotificationRoute {
+            rules: vec!["nextcloud".to_string(), "plex".to_string()],
+            notify: vec!["telegram:42".to_string()],
+            ..Default::default()
+        }];
+        let router = Arc::new(NotificationRouter::new(routes, sink.clone()));
+        let mut load = rule("load", Severity::Warning);
+        load.webhook_url = "http://hooks/cpu".to_string();
+        let rules = [
+            rule("cpu", Severity::Warning),
+            rule("nextcloud", Severity::Critical),
+            load,
+            rule("plex", Severity::Warning),
+        ];
+
+        let results = router
+            .dispatch_digest(rules.iter().map(|r| (r, event(r))).collect())
+            .await;
+        assert_eq!(results, vec![Ok(Delivery::Sent); 4]);
+        let sent = sink.sent.lock().unwrap().clone();
+        let expected = [
+            ("http://hooks/cpu", vec!["cpu", "load"]),
+            ("telegram:42", vec!["nextcloud", "plex"]),
+        ]
+        .map(|(url, rules)| {
+            let rules = rules.into_iter().map(str::to_string).collect();
+            (url.to_string(), rules)
+        });
+        assert_eq!(sent, expected);
+    }
+}
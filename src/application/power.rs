@@ -0,0 +1,68 @@
+use std::sync::Arc;
+
+use thiserror::Error;
+
+use crate::domain::{Event, EventKind, PowerAction, PowerTarget};
+use crate::ports::PowerControl;
+
+use super::EventLog;
+
+#[derive(Debug, Error)]
+pub enum PowerError {
+    #[error("Host '{0}' is not configured")]
+    UnknownHost(String),
+
+    #[error("{0}")]
+    Failed(String),
+}
+
+/// Runs power actions on configured hosts and audits them in the events log
+pub struct PowerService {
+    targets: Vec<PowerTarget>,
+    control: Arc<dyn PowerControl>,
+    events: Arc<EventLog>,
+}
+
+impl PowerService {
+    pub fn new(
+        targets: Vec<PowerTarget>,
+        control: Arc<dyn PowerControl>,
+        events: Arc<EventLog>,
+    ) -> Self {
+        Self {
+            targets,
+            control,
+            events,
+        }
+    }
+
+    pub fn targets(&self) -> &[PowerTarget] {
+        &self.targets
+    }
+
+    pub async fn run(&self, name: &str, action: PowerAction) -> Result<(), PowerError> {
+        let target = self
+            .targets
+            .iter()
+            .find(|t| t.name == name)
+            .ok_or_else(|| PowerError::UnknownHost(name.to_string()))?;
+
+        let result = match action {
+            PowerAction::Wake => self.control.wake(target).await,
+            PowerAction::Shutdown => self.control.shutdown(target).await,
+        };
+
+        let verb = match action {
+            PowerAction::Wake => "Wake-on-LAN",
+            PowerAction::Shutdown => "Shutdown",
+        };
+        let message = match &result {
+            Ok(()) => format!("{} sent to host '{}'", verb, name),
+            Err(e) => format!("{} of host '{}' failed: {}", verb, name, e),
+        };
+        self.events
+            .record(Event::new(EventKind::PowerAction, message).with_resource(name));
+
+        result.map_err(|e| PowerError::Failed(e.to_string()))
+    }
+}
@@ -0,0 +1,117 @@
+use std::io;
+use std::sync::Arc;
+
+use thiserror::Error;
+
+use crate::domain::{Event, EventKind, NICE_RANGE};
+use crate::ports::ProcessControl;
+
+use super::EventLog;
+
+#[derive(Debug, Error)]
+pub enum ProcessControlError {
+    #[error("Nice must be between {} and {}", NICE_RANGE.start(), NICE_RANGE.end())]
+    InvalidNice,
+
+    #[error("Process {0} not found")]
+    NotFound(u32),
+
+    #[error("{0}")]
+    PermissionDenied(String),
+
+    #[error("{0}")]
+    Failed(String),
+}
+
+/// Changes the scheduling of local processes and audits it in the events log
+pub struct ProcessControlService {
+    control: Arc<dyn ProcessControl>,
+    events: Arc<EventLog>,
+}
+
+impl ProcessControlService {
+    pub fn new(control: Arc<dyn ProcessControl>, events: Arc<EventLog>) -> Self {
+        Self { control, events }
+    }
+
+    /// Lowering nice below the current value needs `CAP_SYS_NICE`
+    pub async fn renice(&self, pid: u32, nice: i64) -> Result<(), ProcessControlError> {
+        if !NICE_RANGE.contains(&nice) {
+            return Err(ProcessControlError::InvalidNice);
+        }
+        // setpriority treats PID 0 as the caller, i.e. NanoMon itself
+        if pid == 0 {
+            return Err(ProcessControlError::NotFound(pid));
+        }
+
+        let result = self.control.renice(pid, nice).await;
+        let message = match &result {
+            Ok(()) => format!("Process {} reniced to {}", pid, nice),
+            Err(e) => format!("Renicing process {} to {} failed: {}", pid, nice, e),
+        };
+        self.events
+            .record(Event::new(EventKind::ProcessAction, message).with_resource(pid.to_string()));
+
+        result.map_err(
+            |e| match e.downcast_ref::<io::Error>().map(io::Error::kind) {
+                Some(io::ErrorKind::NotFound) => ProcessControlError::NotFound(pid),
+                Some(io::ErrorKind::PermissionDenied) => {
+                    ProcessControlError::PermissionDenied(format!(
+                        "Not permitted to renice process {} (raising priority needs CAP_SYS_NICE)",
+                        pid
+                    ))
+                }
+                _ => ProcessControlError::Failed(e.to_string()),
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+
+    /// Fails like `setpriority` does for a missing process or a lowered nice
+    struct Kernel;
+
+    #[async_trait]
+    impl ProcessControl for Kernel {
+        async fn renice(
+            &self,
+            pid: u32,
+            nice: i64,
+        ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+            match (pid, nice) {
+                (404, _) => Err(io::Error::new(io::ErrorKind::NotFound, "no process").into()),
+                (_, n) if n < 0 => Err(io::Error::from_raw_os_error(1).into()),
+                _ => Ok(()),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_renice_errors_and_audit() {
+        let events = Arc::new(EventLog::new(10));
+        let service = ProcessControlService::new(Arc::new(Kernel), events.clone());
+
+        assert!(service.renice(42, 10).await.is_ok());
+        assert!(matches!(
+            service.renice(42, 20).await,
+            Err(ProcessControlError::InvalidNice)
+        ));
+        assert!(matches!(
+            service.renice(404, 10).await,
+            Err(ProcessControlError::NotFound(404))
+        ));
+        assert!(matches!(
+            service.renice(42, -5).await,
+            Err(ProcessControlError::PermissionDenied(_))
+        ));
+
+        // Rejected requests never reach the kernel and are not audited
+        let recent = events.recent(10);
+        assert_eq!(recent.len(), 3);
+        assert!(recent.iter().all(|e| e.kind == EventKind::ProcessAction));
+    }
+}
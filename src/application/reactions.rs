@@ -0,0 +1,198 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::domain::{AlertEvent, AlertRule, Event, EventKind, Reaction, ReactionTrigger};
+use crate::ports::CommandRunner;
+
+use super::EventLog;
+
+/// Runs `[[reactions]]` commands as alerts fire and resolve, and audits each
+/// run in the events log
+pub struct ReactionService {
+    reactions: Vec<Reaction>,
+    runner: Arc<dyn CommandRunner>,
+    events: Arc<EventLog>,
+    /// Consecutive firing evaluations, by rule name
+    streaks: Mutex<HashMap<String, u32>>,
+    /// Reactions whose command has not finished yet
+    running: Mutex<HashSet<String>>,
+}
+
+impl ReactionService {
+    pub fn new(
+        reactions: Vec<Reaction>,
+        runner: Arc<dyn CommandRunner>,
+        events: Arc<EventLog>,
+    ) -> Self {
+        Self {
+            reactions,
+            runner,
+            events,
+            streaks: Mutex::new(HashMap::new()),
+            running: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// `rule` fired in an evaluation; runs the reactions whose `after` it
+    /// just reached
+    pub fn firing(self: &Arc<Self>, rule: &AlertRule, event: &AlertEvent) {
+        let streak = {
+            let mut streaks = self.streaks.lock().unwrap();
+            let streak = streaks.entry(rule.name.clone()).or_default();
+            *streak += 1;
+            *streak
+        };
+        self.trigger(ReactionTrigger::Fire, rule, event, |r| r.after == streak);
+    }
+
+    /// The alert of `rule` resolved
+    pub fn resolved(self: &Arc<Self>, rule: &AlertRule, event: &AlertEvent) {
+        self.streaks.lock().unwrap().remove(&rule.name);
+        self.trigger(ReactionTrigger::Resolve, rule, event, |_| true);
+    }
+
+    fn trigger(
+        self: &Arc<Self>,
+        on: ReactionTrigger,
+        rule: &AlertRule,
+        event: &AlertEvent,
+        due: impl Fn(&Reaction) -> bool,
+    ) {
+        for reaction in &self.reactions {
+            if reaction.rule != rule.name || reaction.on != on || !due(reaction) {
+                continue;
+            }
+            if !self.running.lock().unwrap().insert(reaction.name.clone()) {
+                tracing::warn!("Reaction '{}' is still running, skipped", reaction.name);
+                continue;
+            }
+            let service = self.clone();
+            let reaction = reaction.clone();
+            let env = reaction.environment(rule, event);
+            tokio::spawn(async move {
+                service.run(&reaction, &env).await;
+            });
+        }
+    }
+
+    async fn run(&self, reaction: &Reaction, env: &[(String, String)]) {
+        tracing::info!(
+            "Running reaction '{}' for '{}'",
+            reaction.name,
+            reaction.rule
+        );
+        let timeout = Duration::from_secs(reaction.timeout_seconds);
+        let result = self.runner.run(&reaction.command, env, timeout).await;
+        self.running.lock().unwrap().remove(&reaction.name);
+
+        let message = match &result {
+            Ok(()) => format!(
+                "Reaction '{}' to alert '{}' ran: {}",
+                reaction.name,
+                reaction.rule,
+                reaction.command.join(" ")
+            ),
+            Err(e) => format!(
+                "Reaction '{}' to alert '{}' failed: {}",
+                reaction.name, reaction.rule, e
+            ),
+        };
+        self.events
+            .record(Event::new(EventKind::Reaction, message).with_resource(&reaction.name));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{AlertCondition, AlertMetric, Severity};
+    use async_trait::async_trait;
+
+    /// Records the command and `NANOMON_ALERT_STATE` of each run
+    #[derive(Default)]
+    struct RecordingRunner {
+        runs: Mutex<Vec<(String, String)>>,
+    }
+
+    #[async_trait]
+    impl CommandRunner for RecordingRunner {
+        async fn run(
+            &self,
+            command: &[String],
+            env: &[(String, String)],
+            _timeout: Duration,
+        ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+            let state = env
+                .iter()
+                .find(|(name, _)| name == "NANOMON_ALERT_STATE")
+                .map(|(_, value)| value.clone())
+                .unwrap_or_default();
+            self.runs.lock().unwrap().push((command.join(" "), state));
+            Ok(())
+        }
+    }
+
+    fn reaction(name: &str, on: ReactionTrigger, after: u32) -> Reaction {
+        Reaction {
+            name: name.to_string(),
+            rule: "nextcloud".to_string(),
+            on,
+            after,
+            command: vec!["echo".to_string(), name.to_string()],
+            timeout_seconds: 5,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reactions_wait_for_their_streak() {
+        let runner = Arc::new(RecordingRunner::default());
+        let events = Arc::new(EventLog::new(10));
+        let service = Arc::new(ReactionService::new(
+            vec![
+                reaction("restart", ReactionTrigger::Fire, 3),
+                reaction("recovered", ReactionTrigger::Resolve, 1),
+            ],
+            runner.clone(),
+            events.clone(),
+        ));
+        let rule = AlertRule {
+            name: "nextcloud".to_string(),
+            metric: AlertMetric::ContainerPids {
+                container: "nextcloud".to_string(),
+            },
+            condition: AlertCondition::Above,
+            threshold: 90.0,
+            webhook_url: String::new(),
+            cooldown_seconds: 300,
+            severity: Severity::Critical,
+            escalation: Vec::new(),
+        };
+        let event = AlertEvent {
+            rule_name: rule.name.clone(),
+            severity: rule.severity,
+            incident_id: Some(1),
+            escalation: None,
+            metric: "ContainerPids".to_string(),
+            current_value: 95.0,
+            threshold: rule.threshold,
+            condition: "Above".to_string(),
+            hostname: "nas".to_string(),
+            timestamp: String::new(),
+        };
+
+        for _ in 0..4 {
+            service.firing(&rule, &event);
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        service.resolved(&rule, &event);
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        let runs = runner.runs.lock().unwrap().clone();
+        let expected = [("echo restart", "firing"), ("echo recovered", "resolved")]
+            .map(|(command, state)| (command.to_string(), state.to_string()));
+        assert_eq!(runs, expected);
+        assert_eq!(events.recent(10).len(), 2);
+        assert!(events.recent(1)[0].message.contains("'recovered'"));
+    }
+}
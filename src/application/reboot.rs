@@ -0,0 +1,95 @@
+use std::sync::{Arc, RwLock};
+
+use chrono::Duration;
+
+use crate::domain::{
+    Event, EventKind, Host, Inventory, RebootReport, ServiceState, SystemdService,
+};
+use crate::ports::InventoryStore;
+
+use super::EventLog;
+
+/// Detects host reboots (uptime reset) and reports what did not come back up
+pub struct RebootDetector {
+    store: Option<Arc<dyn InventoryStore>>,
+    events: Arc<EventLog>,
+    /// Uptime to wait for before comparing, so slow containers can start
+    grace_seconds: u64,
+    last: RwLock<Option<Inventory>>,
+    /// Pre-reboot inventory waiting for the grace period to elapse
+    pending: RwLock<Option<Inventory>>,
+}
+
+impl RebootDetector {
+    pub fn new(
+        store: Option<Arc<dyn InventoryStore>>,
+        events: Arc<EventLog>,
+        grace_seconds: u64,
+    ) -> Self {
+        let last = store.as_ref().and_then(|s| s.load());
+        Self {
+            store,
+            events,
+            grace_seconds,
+            last: RwLock::new(last),
+            pending: RwLock::new(None),
+        }
+    }
+
+    /// Compare the snapshot against the last known inventory
+    pub fn observe(&self, snapshot: &Host, services: &[SystemdService]) {
+        let boot_time = snapshot.timestamp - Duration::seconds(snapshot.uptime_seconds as i64);
+        let current = Inventory::new(
+            boot_time,
+            snapshot
+                .containers
+                .iter()
+                .filter(|c| c.state.is_running())
+                .map(|c| c.name.clone())
+                .collect(),
+            services
+                .iter()
+                .filter(|s| s.state == ServiceState::Active)
+                .map(|s| s.name.clone())
+                .collect(),
+        );
+
+        let mut last = self.last.write().unwrap();
+        let mut pending = self.pending.write().unwrap();
+
+        if let Some(previous) = last.as_ref() {
+            if previous.is_other_boot(boot_time) {
+                self.events.record(Event::new(
+                    EventKind::Reboot,
+                    format!(
+                        "Host {} rebooted (up since {})",
+                        snapshot.hostname,
+                        self.events.describe_time(boot_time)
+                    ),
+                ));
+                *pending = Some(previous.clone());
+            }
+        }
+
+        if snapshot.uptime_seconds >= self.grace_seconds {
+            if let Some(before) = pending.take() {
+                let report = RebootReport::compare(&before, &current);
+                self.events.record(
+                    Event::new(EventKind::RebootReport, report.summary())
+                        .with_reboot_report(report),
+                );
+            }
+        }
+
+        // Keep the pre-reboot inventory on disk until the report is out,
+        // so a NanoMon restart during the grace period does not lose it
+        if pending.is_none() && last.as_ref() != Some(&current) {
+            if let Some(store) = &self.store {
+                if let Err(e) = store.save(&current) {
+                    tracing::warn!("Failed to save inventory: {}", e);
+                }
+            }
+        }
+        *last = Some(current);
+    }
+}
@@ -0,0 +1,81 @@
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use crate::domain::DiskSuggestions;
+use crate::ports::ReclaimSource;
+
+/// Gathers advice on freeing disk space from every source. Looking walks
+/// directories and asks Docker for sizes, so results are cached.
+pub struct ReclaimService {
+    sources: Vec<(&'static str, Arc<dyn ReclaimSource>)>,
+    cache_ttl: Duration,
+    cache: RwLock<Option<(Instant, DiskSuggestions)>>,
+}
+
+impl ReclaimService {
+    pub fn new(sources: Vec<(&'static str, Arc<dyn ReclaimSource>)>, cache_ttl: Duration) -> Self {
+        Self {
+            sources,
+            cache_ttl,
+            cache: RwLock::new(None),
+        }
+    }
+
+    /// Suggestions of every source, biggest savings first; a source that
+    /// fails is listed in `errors`
+    pub async fn suggestions(&self) -> DiskSuggestions {
+        if let Some((at, suggestions)) = self.cache.read().unwrap().as_ref() {
+            if at.elapsed() < self.cache_ttl {
+                return suggestions.clone();
+            }
+        }
+
+        let mut suggestions = Vec::new();
+        let mut errors = Vec::new();
+        for (name, source) in &self.sources {
+            match source.suggestions().await {
+                Ok(found) => suggestions.extend(found),
+                Err(e) => errors.push(format!("{}: {}", name, e)),
+            }
+        }
+        let suggestions = DiskSuggestions::new(suggestions, errors);
+        *self.cache.write().unwrap() = Some((Instant::now(), suggestions.clone()));
+        suggestions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::ReclaimSuggestion;
+    use async_trait::async_trait;
+
+    struct Fixed(Result<u64, &'static str>);
+
+    #[async_trait]
+    impl ReclaimSource for Fixed {
+        async fn suggestions(
+            &self,
+        ) -> Result<Vec<ReclaimSuggestion>, Box<dyn std::error::Error + Send + Sync>> {
+            let bytes = self.0?;
+            Ok(vec![ReclaimSuggestion::log_file(
+                "/var/log/syslog.1",
+                bytes,
+            )])
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failing_sources_are_reported() {
+        let service = ReclaimService::new(
+            vec![
+                ("logs", Arc::new(Fixed(Ok(1024)))),
+                ("docker", Arc::new(Fixed(Err("permission denied")))),
+            ],
+            Duration::from_secs(60),
+        );
+        let suggestions = service.suggestions().await;
+        assert_eq!(suggestions.total_savings_bytes, 1024);
+        assert_eq!(suggestions.errors, ["docker: permission denied"]);
+    }
+}
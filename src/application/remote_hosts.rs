@@ -0,0 +1,196 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, RwLock};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::domain::Host;
+use crate::ports::MetricStore;
+
+/// Hosts accepted before new names are refused, bounding aggregator memory
+pub const MAX_REMOTE_HOSTS: usize = 64;
+
+/// Default clock difference with an agent above which a warning is logged
+pub const DEFAULT_SKEW_WARN_SECONDS: u64 = 30;
+
+#[derive(Debug, Error)]
+pub enum IngestError {
+    #[error("Snapshot has no hostname")]
+    MissingHostname,
+
+    #[error("Already tracking {0} remote hosts")]
+    TooManyHosts(usize),
+}
+
+/// A pushing agent and when it last reported
+#[derive(Debug, Clone, Serialize)]
+pub struct RemoteHostSummary {
+    pub hostname: String,
+    /// When the aggregator received the last push
+    pub last_seen: DateTime<Utc>,
+    pub snapshots: usize,
+    /// The agent's clock minus the aggregator's at the last push, positive
+    /// when the agent runs ahead
+    pub skew_seconds: i64,
+}
+
+type StoreFactory = Box<dyn Fn() -> Arc<dyn MetricStore> + Send + Sync>;
+
+/// Snapshots pushed by agents, kept in one metric store per hostname.
+/// Snapshots are stored under the aggregator's receive time, so a skewed
+/// agent clock can't move `last_seen` or the history cutoffs.
+pub struct RemoteHostService {
+    stores: RwLock<BTreeMap<String, Arc<dyn MetricStore>>>,
+    /// Last measured skew per host, in seconds
+    skews: RwLock<HashMap<String, i64>>,
+    new_store: StoreFactory,
+    max_hosts: usize,
+    skew_warn_seconds: u64,
+}
+
+impl RemoteHostService {
+    /// `new_store` creates the history for a host on its first push
+    pub fn new(new_store: impl Fn() -> Arc<dyn MetricStore> + Send + Sync + 'static) -> Self {
+        Self {
+            stores: RwLock::new(BTreeMap::new()),
+            skews: RwLock::new(HashMap::new()),
+            new_store: Box::new(new_store),
+            max_hosts: MAX_REMOTE_HOSTS,
+            skew_warn_seconds: DEFAULT_SKEW_WARN_SECONDS,
+        }
+    }
+
+    /// Warn when an agent's clock is off by more than `seconds`
+    pub fn with_skew_warning(mut self, seconds: u64) -> Self {
+        self.skew_warn_seconds = seconds;
+        self
+    }
+
+    pub fn ingest(&self, snapshot: Host) -> Result<(), IngestError> {
+        self.ingest_at(snapshot, Utc::now())
+    }
+
+    /// `ingest` of a snapshot received at `received_at`
+    pub(crate) fn ingest_at(
+        &self,
+        snapshot: Host,
+        received_at: DateTime<Utc>,
+    ) -> Result<(), IngestError> {
+        let hostname = snapshot.hostname.trim();
+        if hostname.is_empty() {
+            return Err(IngestError::MissingHostname);
+        }
+
+        let existing = self.stores.read().unwrap().get(hostname).cloned();
+        let store = match existing {
+            Some(store) => store,
+            None => {
+                let mut stores = self.stores.write().unwrap();
+                if !stores.contains_key(hostname) && stores.len() >= self.max_hosts {
+                    return Err(IngestError::TooManyHosts(self.max_hosts));
+                }
+                stores
+                    .entry(hostname.to_string())
+                    .or_insert_with(|| {
+                        tracing::info!("Remote host '{}' started pushing snapshots", hostname);
+                        (self.new_store)()
+                    })
+                    .clone()
+            }
+        };
+        self.record_skew(hostname, (snapshot.timestamp - received_at).num_seconds());
+        store.store(snapshot.with_timestamp(received_at));
+        Ok(())
+    }
+
+    /// Logs when a host's skew crosses the warning threshold either way
+    fn record_skew(&self, hostname: &str, skew: i64) {
+        let skewed = |skew: i64| skew.unsigned_abs() > self.skew_warn_seconds;
+        let previous = self
+            .skews
+            .write()
+            .unwrap()
+            .insert(hostname.to_string(), skew);
+        match (previous.is_some_and(skewed), skewed(skew)) {
+            (false, true) => tracing::warn!(
+                "Clock of remote host '{}' is off by {}s; its snapshots are stored under receive time",
+                hostname,
+                skew
+            ),
+            (true, false) => tracing::info!(
+                "Clock of remote host '{}' is back within {}s",
+                hostname,
+                self.skew_warn_seconds
+            ),
+            _ => {}
+        }
+    }
+
+    /// Every host that has pushed, ordered by name
+    pub fn hosts(&self) -> Vec<RemoteHostSummary> {
+        let skews = self.skews.read().unwrap();
+        self.stores
+            .read()
+            .unwrap()
+            .iter()
+            .filter_map(|(hostname, store)| {
+                let latest = store.get_latest()?;
+                Some(RemoteHostSummary {
+                    hostname: hostname.clone(),
+                    last_seen: latest.timestamp,
+                    snapshots: store.len(),
+                    skew_seconds: skews.get(hostname).copied().unwrap_or_default(),
+                })
+            })
+            .collect()
+    }
+
+    pub fn latest(&self, hostname: &str) -> Option<Arc<Host>> {
+        self.stores.read().unwrap().get(hostname)?.get_latest()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::MemoryStore;
+
+    #[test]
+    fn test_stores_per_host_and_caps_hosts() {
+        let mut service = RemoteHostService::new(|| Arc::new(MemoryStore::new(10)));
+        service.max_hosts = 2;
+
+        for name in ["nas", "pi", "nas"] {
+            service.ingest(Host::new(name.to_string())).unwrap();
+        }
+        let hosts = service.hosts();
+        assert_eq!(hosts.len(), 2);
+        assert_eq!(hosts[0].hostname, "nas");
+        assert_eq!(hosts[0].snapshots, 2);
+        assert!(service.latest("pi").is_some());
+
+        assert!(matches!(
+            service.ingest(Host::new("vps".to_string())),
+            Err(IngestError::TooManyHosts(2))
+        ));
+        assert!(matches!(
+            service.ingest(Host::new(" ".to_string())),
+            Err(IngestError::MissingHostname)
+        ));
+    }
+
+    #[test]
+    fn test_snapshots_are_stored_under_receive_time() {
+        let service = RemoteHostService::new(|| Arc::new(MemoryStore::new(10)));
+        let ahead = Utc::now() + chrono::Duration::hours(2);
+        service
+            .ingest(Host::new("pi".to_string()).with_timestamp(ahead))
+            .unwrap();
+
+        let host = &service.hosts()[0];
+        assert!((7195..=7200).contains(&host.skew_seconds));
+        assert!(host.last_seen <= Utc::now());
+        assert!(service.latest("pi").unwrap().timestamp < ahead);
+    }
+}
@@ -0,0 +1,104 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use chrono::Utc;
+
+use crate::domain::{Container, ContainerSecurity, ImageScan};
+use crate::ports::VulnerabilitySource;
+
+/// Aggregates vulnerability reports for the images of running containers.
+/// Scans run in the background; the API only reads cached results.
+pub struct SecurityMonitor {
+    source: Option<Arc<dyn VulnerabilitySource>>,
+    /// (container name, image) of running containers from the last poll
+    running: RwLock<Vec<(String, String)>>,
+    scans: RwLock<HashMap<String, ImageScan>>,
+    max_age: chrono::Duration,
+}
+
+impl SecurityMonitor {
+    pub fn new(source: Option<Arc<dyn VulnerabilitySource>>, max_age: Duration) -> Self {
+        Self {
+            source,
+            running: RwLock::new(Vec::new()),
+            scans: RwLock::new(HashMap::new()),
+            max_age: chrono::Duration::from_std(max_age).unwrap_or(chrono::Duration::hours(6)),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.source.is_some()
+    }
+
+    /// Remember which images are currently running (cheap, called every poll)
+    pub fn observe(&self, containers: &[Container]) {
+        *self.running.write().unwrap() = containers
+            .iter()
+            .filter(|c| c.state.is_running())
+            .map(|c| (c.name.clone(), c.image.clone()))
+            .collect();
+    }
+
+    /// Fetch reports for running images whose report is missing or stale
+    pub async fn refresh(&self) {
+        let Some(source) = &self.source else {
+            return;
+        };
+
+        let mut images: Vec<String> = self
+            .running
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(_, image)| image.clone())
+            .collect();
+        images.sort();
+        images.dedup();
+
+        let now = Utc::now();
+        for image in images {
+            let fresh = self
+                .scans
+                .read()
+                .unwrap()
+                .get(&image)
+                .is_some_and(|s| now - s.scanned_at < self.max_age);
+            if fresh {
+                continue;
+            }
+
+            match source.report(&image).await {
+                Ok(Some(scan)) => {
+                    self.scans.write().unwrap().insert(image, scan);
+                }
+                Ok(None) => tracing::debug!("No vulnerability report for {}", image),
+                Err(e) => tracing::warn!("Vulnerability report for {} failed: {}", image, e),
+            }
+        }
+    }
+
+    /// Refresh forever at the given interval
+    pub async fn run(self: Arc<Self>, interval: Duration) {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            self.refresh().await;
+        }
+    }
+
+    /// Latest report per running container
+    pub fn summary(&self) -> Vec<ContainerSecurity> {
+        let scans = self.scans.read().unwrap();
+        self.running
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(container, image)| ContainerSecurity {
+                container: container.clone(),
+                image: image.clone(),
+                scan: scans.get(image).cloned(),
+            })
+            .collect()
+    }
+}
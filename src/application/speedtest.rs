@@ -0,0 +1,115 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use crate::domain::SpeedtestResult;
+use crate::ports::{SpeedtestRunner, SystemSource};
+
+/// A bandwidth test saturates the link, never run more often than hourly
+pub const MIN_SPEEDTEST_INTERVAL: u64 = 3600;
+
+/// Runs scheduled bandwidth tests and keeps their history
+pub struct SpeedtestMonitor {
+    runner: Arc<dyn SpeedtestRunner>,
+    system: Arc<dyn SystemSource>,
+    interval_seconds: u64,
+    /// Tests are skipped while the default route uses one of these interfaces
+    metered_interfaces: Vec<String>,
+    history: RwLock<VecDeque<SpeedtestResult>>,
+    last_skip: RwLock<Option<String>>,
+    max_results: usize,
+}
+
+impl SpeedtestMonitor {
+    pub fn new(
+        runner: Arc<dyn SpeedtestRunner>,
+        system: Arc<dyn SystemSource>,
+        interval_seconds: u64,
+        max_results: usize,
+    ) -> Self {
+        Self {
+            runner,
+            system,
+            interval_seconds: interval_seconds.max(MIN_SPEEDTEST_INTERVAL),
+            metered_interfaces: Vec::new(),
+            history: RwLock::new(VecDeque::new()),
+            last_skip: RwLock::new(None),
+            max_results,
+        }
+    }
+
+    pub fn with_metered_interfaces(mut self, interfaces: Vec<String>) -> Self {
+        self.metered_interfaces = interfaces;
+        self
+    }
+
+    pub fn interval_seconds(&self) -> u64 {
+        self.interval_seconds
+    }
+
+    /// Name of the metered interface currently carrying the default route
+    async fn metered_route(&self) -> Option<String> {
+        if self.metered_interfaces.is_empty() {
+            return None;
+        }
+        match self.system.default_route_interface().await {
+            Ok(Some(iface)) if self.metered_interfaces.contains(&iface) => Some(iface),
+            Ok(_) => None,
+            Err(e) => {
+                tracing::warn!("Cannot determine default route: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Run one test unless the link is metered
+    pub async fn run_once(&self) {
+        if let Some(iface) = self.metered_route().await {
+            tracing::info!("Skipping speedtest: default route is metered ({})", iface);
+            *self.last_skip.write().unwrap() = Some(format!("metered link ({})", iface));
+            return;
+        }
+
+        match self.runner.run().await {
+            Ok(result) => {
+                tracing::info!(
+                    "Speedtest: {:.1} Mbit/s down, {:.1} Mbit/s up, {:.1} ms",
+                    result.download_mbps,
+                    result.upload_mbps,
+                    result.ping_ms
+                );
+                *self.last_skip.write().unwrap() = None;
+                let mut history = self.history.write().unwrap();
+                if history.len() >= self.max_results {
+                    history.pop_front();
+                }
+                history.push_back(result);
+            }
+            Err(e) => {
+                tracing::warn!("Speedtest failed: {}", e);
+                *self.last_skip.write().unwrap() = Some(format!("failed: {}", e));
+            }
+        }
+    }
+
+    /// Test forever at the configured interval.
+    /// The first test waits a full interval so restarts cannot trigger bursts.
+    pub async fn run(self: Arc<Self>) {
+        let period = Duration::from_secs(self.interval_seconds);
+        let mut ticker = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+        loop {
+            ticker.tick().await;
+            self.run_once().await;
+        }
+    }
+
+    /// All recorded results, oldest first
+    pub fn history(&self) -> Vec<SpeedtestResult> {
+        self.history.read().unwrap().iter().cloned().collect()
+    }
+
+    /// Why the last scheduled test produced no result, if it did not
+    pub fn last_skip(&self) -> Option<String> {
+        self.last_skip.read().unwrap().clone()
+    }
+}
@@ -0,0 +1,146 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, RwLock};
+
+use chrono::{Duration, Utc};
+
+use crate::domain::{
+    AlertEvent, AuthFailure, Event, EventKind, OffenderCount, Severity, SshSummary, SshWatch,
+};
+use crate::ports::{AlertSink, AuthLogSource};
+
+use super::EventLog;
+
+/// Failures older than this are dropped
+const RETENTION_HOURS: i64 = 24;
+
+/// Offenders listed in the summary
+const TOP_OFFENDERS: usize = 10;
+
+/// Tracks failed SSH logins and alerts when they spike
+pub struct SshMonitor {
+    watch: SshWatch,
+    source: Arc<dyn AuthLogSource>,
+    failures: RwLock<VecDeque<AuthFailure>>,
+    banned: RwLock<Option<Vec<String>>>,
+    spiking: RwLock<bool>,
+    sink: Arc<dyn AlertSink>,
+    events: Arc<EventLog>,
+}
+
+impl SshMonitor {
+    pub fn new(
+        watch: SshWatch,
+        source: Arc<dyn AuthLogSource>,
+        sink: Arc<dyn AlertSink>,
+        events: Arc<EventLog>,
+    ) -> Self {
+        Self {
+            watch,
+            source,
+            failures: RwLock::new(VecDeque::new()),
+            banned: RwLock::new(None),
+            spiking: RwLock::new(false),
+            sink,
+            events,
+        }
+    }
+
+    /// Read new log entries, refresh the ban list and alert on spikes
+    pub async fn refresh(&self, hostname: &str) {
+        match self.source.read_failures().await {
+            Ok(new) => {
+                let cutoff = Utc::now() - Duration::hours(RETENTION_HOURS);
+                let mut failures = self.failures.write().unwrap();
+                failures.extend(new);
+                while failures.front().is_some_and(|f| f.timestamp < cutoff) {
+                    failures.pop_front();
+                }
+            }
+            Err(e) => tracing::warn!("Failed to read SSH auth log: {}", e),
+        }
+
+        match self.source.banned_ips().await {
+            Ok(banned) => *self.banned.write().unwrap() = banned,
+            Err(e) => tracing::warn!("Failed to query fail2ban: {}", e),
+        }
+
+        let recent = self.count_recent(&self.failures.read().unwrap());
+        let spike = recent >= self.watch.spike_threshold;
+        let was_spiking = std::mem::replace(&mut *self.spiking.write().unwrap(), spike);
+        if spike && !was_spiking {
+            self.notify(recent, hostname).await;
+        }
+    }
+
+    fn count_recent(&self, failures: &VecDeque<AuthFailure>) -> usize {
+        let since = Utc::now() - Duration::minutes(self.watch.spike_window_minutes);
+        failures.iter().filter(|f| f.timestamp >= since).count()
+    }
+
+    async fn notify(&self, recent: usize, hostname: &str) {
+        let top = self
+            .summary()
+            .top_offenders
+            .first()
+            .map(|o| format!(", top offender {} ({})", o.ip, o.failures))
+            .unwrap_or_default();
+        self.events.record(
+            Event::new(
+                EventKind::SshBruteForce,
+                format!(
+                    "{} failed SSH logins in the last {} minutes{}",
+                    recent, self.watch.spike_window_minutes, top
+                ),
+            )
+            .with_resource("sshd"),
+        );
+
+        let Some(url) = self.watch.webhook_url.as_deref() else {
+            return;
+        };
+
+        let event = AlertEvent {
+            rule_name: "ssh:brute-force".to_string(),
+            severity: Severity::Warning,
+            incident_id: None,
+            escalation: None,
+            metric: "SshFailedLogins".to_string(),
+            current_value: recent as f64,
+            threshold: self.watch.spike_threshold as f64,
+            condition: "Above".to_string(),
+            hostname: hostname.to_string(),
+            timestamp: Utc::now().to_rfc3339(),
+        };
+
+        if let Err(e) = self.sink.send_alert(url, &event).await {
+            tracing::error!("Failed to send SSH alert: {}", e);
+        }
+    }
+
+    /// Failed logins, top offenders and banned addresses
+    pub fn summary(&self) -> SshSummary {
+        let failures = self.failures.read().unwrap();
+        let mut per_ip: HashMap<&str, usize> = HashMap::new();
+        for failure in failures.iter() {
+            *per_ip.entry(&failure.ip).or_default() += 1;
+        }
+        let mut top_offenders: Vec<OffenderCount> = per_ip
+            .into_iter()
+            .map(|(ip, failures)| OffenderCount {
+                ip: ip.to_string(),
+                failures,
+            })
+            .collect();
+        top_offenders.sort_by(|a, b| b.failures.cmp(&a.failures).then(a.ip.cmp(&b.ip)));
+        top_offenders.truncate(TOP_OFFENDERS);
+
+        SshSummary {
+            window_hours: RETENTION_HOURS,
+            failed_logins: failures.len(),
+            recent_failures: self.count_recent(&failures),
+            spike: *self.spiking.read().unwrap(),
+            top_offenders,
+            banned_ips: self.banned.read().unwrap().clone(),
+        }
+    }
+}
@@ -0,0 +1,274 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+
+use crate::domain::{
+    container_failure, AlertEvent, Container, ContainerId, Event, EventKind, RestartPolicy,
+    Severity,
+};
+use crate::ports::{AlertSink, ContainerControl};
+
+use super::EventLog;
+
+/// What the supervisor knows of one container
+#[derive(Default)]
+struct Supervised {
+    /// Since when it has been failing, while it is
+    failing_since: Option<DateTime<Utc>>,
+    /// Since when it has been well, while it is
+    well_since: Option<DateTime<Utc>>,
+    attempts: u32,
+    last_attempt: Option<DateTime<Utc>>,
+    /// A restart is in progress
+    restarting: bool,
+    gave_up: bool,
+}
+
+/// A restart to make, or the news that there will be no more
+struct Action {
+    policy: usize,
+    identity: String,
+    id: ContainerId,
+    name: String,
+    reason: &'static str,
+    /// `None` once attempts ran out
+    attempt: Option<u32>,
+}
+
+/// Restarts containers that stay unhealthy or crash-loop, as `[[supervise]]`
+/// policies allow, with bounded attempts and growing waits between them
+pub struct ContainerSupervisor {
+    policies: Vec<RestartPolicy>,
+    control: Arc<dyn ContainerControl>,
+    sink: Arc<dyn AlertSink>,
+    events: Arc<EventLog>,
+    /// By container identity
+    state: Mutex<HashMap<String, Supervised>>,
+}
+
+impl ContainerSupervisor {
+    pub fn new(
+        policies: Vec<RestartPolicy>,
+        control: Arc<dyn ContainerControl>,
+        sink: Arc<dyn AlertSink>,
+        events: Arc<EventLog>,
+    ) -> Self {
+        Self {
+            policies,
+            control,
+            sink,
+            events,
+            state: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.policies.is_empty()
+    }
+
+    /// Looks at the containers of a new snapshot and restarts, in the
+    /// background, those whose policy says it is time
+    pub fn check(self: &Arc<Self>, hostname: &str, containers: &[Container]) {
+        for action in self.plan(containers, Utc::now()) {
+            let supervisor = self.clone();
+            let hostname = hostname.to_string();
+            tokio::spawn(async move {
+                supervisor.act(&hostname, action).await;
+            });
+        }
+    }
+
+    fn plan(&self, containers: &[Container], now: DateTime<Utc>) -> Vec<Action> {
+        let mut state = self.state.lock().unwrap();
+        state.retain(|identity, _| containers.iter().any(|c| &c.identity() == identity));
+        let mut actions = Vec::new();
+
+        for container in containers {
+            let Some(index) = self.policies.iter().position(|p| p.matches(container)) else {
+                continue;
+            };
+            let policy = &self.policies[index];
+            let identity = container.identity();
+            let entry = state.entry(identity.clone()).or_default();
+            let seconds_since = |t: DateTime<Utc>| (now - t).num_seconds().max(0) as u64;
+
+            let Some(reason) = container_failure(container) else {
+                entry.failing_since = None;
+                let well_since = *entry.well_since.get_or_insert(now);
+                if entry.attempts > 0 && seconds_since(well_since) >= policy.reset_after_seconds {
+                    entry.attempts = 0;
+                    entry.gave_up = false;
+                }
+                continue;
+            };
+            entry.well_since = None;
+            let failing_since = *entry.failing_since.get_or_insert(now);
+            if entry.restarting || seconds_since(failing_since) < policy.grace_seconds {
+                continue;
+            }
+            let mut action = Action {
+                policy: index,
+                identity,
+                id: container.id.clone(),
+                name: container.name.clone(),
+                reason,
+                attempt: None,
+            };
+            if entry.attempts >= policy.max_attempts {
+                if !entry.gave_up {
+                    entry.gave_up = true;
+                    actions.push(action);
+                }
+                continue;
+            }
+            let backoff = policy.backoff(entry.attempts).as_secs();
+            if entry
+                .last_attempt
+                .is_some_and(|at| seconds_since(at) < backoff)
+            {
+                continue;
+            }
+            entry.attempts += 1;
+            entry.last_attempt = Some(now);
+            entry.restarting = true;
+            action.attempt = Some(entry.attempts);
+            actions.push(action);
+        }
+        actions
+    }
+
+    async fn act(&self, hostname: &str, action: Action) {
+        let policy = &self.policies[action.policy];
+        let (message, severity) = match action.attempt {
+            Some(attempt) => {
+                let result = self.control.restart(&action.id).await;
+                if let Some(entry) = self.state.lock().unwrap().get_mut(&action.identity) {
+                    entry.restarting = false;
+                }
+                let outcome = match result {
+                    Ok(()) => "restarted".to_string(),
+                    Err(e) => format!("restart failed: {}", e),
+                };
+                (
+                    format!(
+                        "Container {} {}, {} (attempt {}/{})",
+                        action.name, action.reason, outcome, attempt, policy.max_attempts
+                    ),
+                    Severity::Warning,
+                )
+            }
+            None => (
+                format!(
+                    "Container {} still {} after {} restarts, giving up until it recovers",
+                    action.name, action.reason, policy.max_attempts
+                ),
+                Severity::Critical,
+            ),
+        };
+        self.events.record(
+            Event::new(EventKind::ContainerRestart, message).with_resource(action.name.clone()),
+        );
+
+        let event = AlertEvent {
+            rule_name: format!("restart:{}", action.name),
+            severity,
+            incident_id: None,
+            escalation: None,
+            metric: "ContainerRestart".to_string(),
+            current_value: action.attempt.unwrap_or(policy.max_attempts) as f64,
+            threshold: policy.max_attempts as f64,
+            condition: "Above".to_string(),
+            hostname: hostname.to_string(),
+            timestamp: Utc::now().to_rfc3339(),
+        };
+        for url in &policy.notify {
+            if let Err(e) = self.sink.send_alert(url, &event).await {
+                tracing::error!("Failed to notify restart of {}: {}", action.name, e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{ContainerHealth, ContainerState};
+    use async_trait::async_trait;
+
+    struct NoControl;
+
+    #[async_trait]
+    impl ContainerControl for NoControl {
+        async fn restart(
+            &self,
+            _id: &ContainerId,
+        ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+            Ok(())
+        }
+    }
+
+    struct NoSink;
+
+    #[async_trait]
+    impl AlertSink for NoSink {
+        async fn send_alert(
+            &self,
+            _url: &str,
+            _event: &AlertEvent,
+        ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+            Ok(())
+        }
+
+        async fn send_event(
+            &self,
+            _url: &str,
+            _event: &Event,
+            _secret: Option<&str>,
+        ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_restarts_are_bounded_and_backed_off() {
+        let policy = RestartPolicy {
+            containers: vec!["nextcloud".to_string()],
+            grace_seconds: 60,
+            max_attempts: 2,
+            backoff_seconds: 300,
+            reset_after_seconds: 3600,
+            notify: Vec::new(),
+        };
+        let supervisor = ContainerSupervisor::new(
+            vec![policy],
+            Arc::new(NoControl),
+            Arc::new(NoSink),
+            Arc::new(EventLog::new(10)),
+        );
+        let unhealthy = [Container::new(
+            ContainerId::new("abc".to_string()),
+            "nextcloud".to_string(),
+            "nextcloud:28".to_string(),
+            ContainerState::Running,
+            Utc::now(),
+        )
+        .with_health(Some(ContainerHealth::Unhealthy))];
+        let start = Utc::now();
+        // Attempts made, or `None` for giving up, at `seconds` after start
+        let plan = |seconds: i64| {
+            let actions = supervisor.plan(&unhealthy, start + chrono::Duration::seconds(seconds));
+            for entry in supervisor.state.lock().unwrap().values_mut() {
+                entry.restarting = false;
+            }
+            actions.iter().map(|a| a.attempt).collect::<Vec<_>>()
+        };
+
+        assert_eq!(plan(0), []);
+        assert_eq!(plan(60), [Some(1)]);
+        assert_eq!(plan(120), []);
+        assert_eq!(plan(360), [Some(2)]);
+        assert_eq!(plan(2000), [None]);
+        assert_eq!(plan(3000), []);
+    }
+}
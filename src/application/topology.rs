@@ -0,0 +1,218 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, RwLock};
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::domain::{
+    Event, EventKind, HostState, Topology, TopologyGroup, TopologyHost, UNGROUPED,
+};
+
+use super::{EventLog, RemoteHostService};
+
+/// Groups the local host and pushing agents by room or rack, and reports
+/// agents that stop pushing unless a down parent (their hypervisor) explains it
+pub struct TopologyService {
+    topology: Topology,
+    remote_hosts: Arc<RemoteHostService>,
+    stale_after: Duration,
+    /// Hosts currently down, mapped to whether an event was recorded
+    down: RwLock<HashMap<String, bool>>,
+    events: Arc<EventLog>,
+}
+
+impl TopologyService {
+    pub fn new(
+        topology: Topology,
+        remote_hosts: Arc<RemoteHostService>,
+        stale_after_seconds: u64,
+        events: Arc<EventLog>,
+    ) -> Self {
+        Self {
+            topology,
+            remote_hosts,
+            stale_after: Duration::seconds(stale_after_seconds as i64),
+            down: RwLock::new(HashMap::new()),
+            events,
+        }
+    }
+
+    /// State and last report of every known host; `local` is always up
+    fn states(
+        &self,
+        local: Option<&str>,
+        now: DateTime<Utc>,
+    ) -> BTreeMap<String, (HostState, Option<DateTime<Utc>>)> {
+        let mut states: BTreeMap<_, _> = self
+            .topology
+            .hosts()
+            .map(|host| (host.to_string(), (HostState::Unknown, None)))
+            .collect();
+        for remote in self.remote_hosts.hosts() {
+            let state = if now.signed_duration_since(remote.last_seen) > self.stale_after {
+                HostState::Down
+            } else {
+                HostState::Up
+            };
+            states.insert(remote.hostname, (state, Some(remote.last_seen)));
+        }
+        if let Some(local) = local {
+            states.insert(local.to_string(), (HostState::Up, Some(now)));
+        }
+        states
+    }
+
+    /// Hosts by group, groups and hosts ordered by name
+    pub fn groups(&self, local: Option<&str>) -> Vec<TopologyGroup> {
+        let states = self.states(local, Utc::now());
+        let is_down = |host: &str| {
+            states
+                .get(host)
+                .is_some_and(|(state, _)| *state == HostState::Down)
+        };
+
+        let mut groups: BTreeMap<&str, Vec<TopologyHost>> = BTreeMap::new();
+        for (name, (state, last_seen)) in &states {
+            let suppressed_by = (*state == HostState::Down)
+                .then(|| self.topology.down_ancestor(name, is_down))
+                .flatten()
+                .map(String::from);
+            groups
+                .entry(self.topology.group(name))
+                .or_default()
+                .push(TopologyHost {
+                    name: name.clone(),
+                    state: *state,
+                    last_seen: *last_seen,
+                    parent: self.topology.parent(name).map(String::from),
+                    children: self.topology.children(name),
+                    suppressed_by,
+                });
+        }
+
+        // Placed hosts first, the catch-all group last
+        let ungrouped = groups.remove(UNGROUPED);
+        groups
+            .into_iter()
+            .map(|(name, hosts)| TopologyGroup {
+                name: name.to_string(),
+                hosts,
+            })
+            .chain(ungrouped.map(|hosts| TopologyGroup {
+                name: UNGROUPED.to_string(),
+                hosts,
+            }))
+            .collect()
+    }
+
+    /// Record events for hosts that stopped or resumed reporting; a host whose
+    /// ancestor is down is only reported if it stays down once that one is back
+    pub fn check(&self, local: &str) {
+        let states = self.states(Some(local), Utc::now());
+        let is_down = |host: &str| {
+            states
+                .get(host)
+                .is_some_and(|(state, _)| *state == HostState::Down)
+        };
+
+        let mut down = self.down.write().unwrap();
+        down.retain(|host, alerted| {
+            if is_down(host) {
+                return true;
+            }
+            if *alerted {
+                self.events.record(
+                    Event::new(
+                        EventKind::HostRecovered,
+                        format!("Host '{}' is reporting again", host),
+                    )
+                    .with_resource(host.clone()),
+                );
+            }
+            false
+        });
+
+        for (host, (state, last_seen)) in &states {
+            if *state != HostState::Down || down.get(host) == Some(&true) {
+                continue;
+            }
+            let suppressed_by = self.topology.down_ancestor(host, is_down);
+            if let Some(parent) = suppressed_by {
+                if !down.contains_key(host) {
+                    tracing::info!(
+                        "Host '{}' stopped reporting while its parent '{}' is down, not alerting",
+                        host,
+                        parent
+                    );
+                }
+            } else {
+                let since = last_seen
+                    .map(|t| format!("last report {}", self.events.describe_time(t)))
+                    .unwrap_or_default();
+                self.events.record(
+                    Event::new(
+                        EventKind::HostDown,
+                        format!("Host '{}' stopped reporting ({})", host, since),
+                    )
+                    .with_resource(host.clone()),
+                );
+            }
+            down.insert(host.clone(), suppressed_by.is_none());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::MemoryStore;
+    use crate::domain::{Host, TopologyEntry};
+
+    fn push(remote: &RemoteHostService, name: &str, minutes_ago: i64) {
+        let received_at = Utc::now() - Duration::minutes(minutes_ago);
+        let host = Host::new(name.to_string()).with_timestamp(received_at);
+        remote.ingest_at(host, received_at).unwrap();
+    }
+
+    #[test]
+    fn test_vm_alerts_suppressed_while_hypervisor_down() {
+        let remote = Arc::new(RemoteHostService::new(|| Arc::new(MemoryStore::new(10))));
+        let topology = Topology::new(vec![
+            TopologyEntry {
+                host: "pve".to_string(),
+                group: Some("rack1".to_string()),
+                parent: None,
+            },
+            TopologyEntry {
+                host: "vm".to_string(),
+                group: Some("rack1".to_string()),
+                parent: Some("pve".to_string()),
+            },
+        ])
+        .unwrap();
+        let events = Arc::new(EventLog::new(10));
+        let service = TopologyService::new(topology, remote.clone(), 180, events.clone());
+
+        push(&remote, "pve", 10);
+        push(&remote, "vm", 10);
+        service.check("aggregator");
+        let recorded = events.recent(10);
+        assert_eq!(recorded.len(), 1);
+        assert_eq!(recorded[0].resource.as_deref(), Some("pve"));
+
+        let groups = service.groups(Some("aggregator"));
+        assert_eq!(groups[0].name, "rack1");
+        assert_eq!(groups[0].hosts[1].suppressed_by.as_deref(), Some("pve"));
+        assert_eq!(groups[1].name, UNGROUPED);
+
+        // The hypervisor is back but the VM is not: now it is reported
+        push(&remote, "pve", 0);
+        service.check("aggregator");
+        let kinds: Vec<_> = events
+            .recent(10)
+            .iter()
+            .map(|e| (e.kind, e.resource.clone().unwrap()))
+            .collect();
+        assert!(kinds.contains(&(EventKind::HostRecovered, "pve".to_string())));
+        assert!(kinds.contains(&(EventKind::HostDown, "vm".to_string())));
+    }
+}
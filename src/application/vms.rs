@@ -0,0 +1,60 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::domain::VirtualMachine;
+use crate::ports::VmSource;
+
+/// Shorter gaps between calls give too noisy a CPU figure
+const MIN_SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Lists the VMs of a hypervisor host, deriving CPU usage from the cumulative
+/// CPU time between two calls when the source only reports that
+pub struct VmMonitor {
+    source: Arc<dyn VmSource>,
+    previous: Mutex<HashMap<String, (u64, Instant)>>,
+}
+
+impl VmMonitor {
+    pub fn new(source: Arc<dyn VmSource>) -> Self {
+        Self {
+            source,
+            previous: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub async fn list(
+        &self,
+    ) -> Result<Vec<VirtualMachine>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut vms = self.source.list_vms().await?;
+        let now = Instant::now();
+        let mut previous = self.previous.lock().unwrap();
+        let mut current = HashMap::new();
+
+        for vm in &mut vms {
+            let Some(cpu_time) = vm.cpu_time_ns else {
+                continue;
+            };
+            let sample = match previous.get(&vm.name) {
+                Some(&(last_time, last_at)) if now - last_at < MIN_SAMPLE_INTERVAL => {
+                    (last_time, last_at)
+                }
+                Some(&(last_time, last_at)) => {
+                    let elapsed = (now - last_at).as_nanos() as f64;
+                    if vm.cpu_percent.is_none() && vm.vcpus > 0 && cpu_time >= last_time {
+                        let used = (cpu_time - last_time) as f64;
+                        vm.cpu_percent =
+                            Some((used / elapsed / vm.vcpus as f64 * 100.0).min(100.0));
+                    }
+                    (cpu_time, now)
+                }
+                None => (cpu_time, now),
+            };
+            current.insert(vm.name.clone(), sample);
+        }
+
+        *previous = current;
+        vms.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(vms)
+    }
+}
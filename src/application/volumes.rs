@@ -0,0 +1,86 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use crate::domain::{ContainerId, DirectoryUsage, VolumeUsage};
+use crate::ports::{ContainerSource, DirectoryScanner};
+
+/// Reports per-mount disk usage of containers.
+/// Walking large volumes is expensive, so results are cached per host path.
+pub struct VolumeUsageService {
+    containers: Arc<dyn ContainerSource>,
+    scanner: Arc<dyn DirectoryScanner>,
+    cache: RwLock<HashMap<String, (Instant, DirectoryUsage)>>,
+    cache_ttl: Duration,
+    max_entries: u64,
+}
+
+impl VolumeUsageService {
+    pub fn new(
+        containers: Arc<dyn ContainerSource>,
+        scanner: Arc<dyn DirectoryScanner>,
+        cache_ttl: Duration,
+        max_entries: u64,
+    ) -> Self {
+        Self {
+            containers,
+            scanner,
+            cache: RwLock::new(HashMap::new()),
+            cache_ttl,
+            max_entries,
+        }
+    }
+
+    fn cached(&self, path: &str) -> Option<DirectoryUsage> {
+        let cache = self.cache.read().unwrap();
+        cache
+            .get(path)
+            .filter(|(at, _)| at.elapsed() < self.cache_ttl)
+            .map(|(_, usage)| usage.clone())
+    }
+
+    /// Usage of every mount of a container, `None` if the container does not exist
+    pub async fn container_volumes(
+        &self,
+        id: &ContainerId,
+    ) -> Result<Option<Vec<VolumeUsage>>, Box<dyn std::error::Error + Send + Sync>> {
+        let Some(mounts) = self.containers.list_mounts(id).await? else {
+            return Ok(None);
+        };
+
+        let mut volumes = Vec::with_capacity(mounts.len());
+        for mount in mounts {
+            if !mount.is_measurable() {
+                volumes.push(VolumeUsage {
+                    mount,
+                    usage: None,
+                    error: None,
+                });
+                continue;
+            }
+
+            let (usage, error) = match self.cached(&mount.source) {
+                Some(usage) => (Some(usage), None),
+                None => match self.scanner.usage(&mount.source, self.max_entries).await {
+                    Ok(usage) => {
+                        self.cache
+                            .write()
+                            .unwrap()
+                            .insert(mount.source.clone(), (Instant::now(), usage.clone()));
+                        (Some(usage), None)
+                    }
+                    Err(e) => (None, Some(e.to_string())),
+                },
+            };
+            volumes.push(VolumeUsage {
+                mount,
+                usage,
+                error,
+            });
+        }
+
+        // Biggest consumers first
+        volumes.sort_by_key(|v| std::cmp::Reverse(v.usage.as_ref().map_or(0, |u| u.used_bytes)));
+        Ok(Some(volumes))
+    }
+}
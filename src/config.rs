@@ -1,21 +1,181 @@
 use std::env;
+use std::net::IpAddr;
 use std::path::PathBuf;
 
+use chrono_tz::Tz;
+
+use crate::domain::{
+    parse_timezone, AlertRule, BackupJob, CollectorConfig, EventSubscription, HeartbeatCheck,
+    Hypervisor, NotificationRoute, PingTarget, PluginConfig, PowerTarget, ProcessSampling,
+    Reaction, RestartPolicy, SpeedtestTool, SshWatch, TopologyEntry, Viewer,
+    DEFAULT_REDACT_PATTERNS,
+};
+use crate::interface::http::DEFAULT_ACCESS_LOG_EXCLUDE;
+
+/// Alert rules and the other lists read from `NANOMON_ALERT_CONFIG`
+#[derive(Default, serde::Deserialize)]
+pub struct AlertConfig {
+    #[serde(default)]
+    pub rules: Vec<AlertRule>,
+    #[serde(default)]
+    pub heartbeats: Vec<HeartbeatCheck>,
+    #[serde(default)]
+    pub backups: Vec<BackupJob>,
+    #[serde(default)]
+    pub hosts: Vec<PowerTarget>,
+    #[serde(default)]
+    pub ping: Vec<PingTarget>,
+    #[serde(default)]
+    pub collectors: Vec<CollectorConfig>,
+    #[serde(default)]
+    pub plugins: Vec<PluginConfig>,
+    #[serde(default)]
+    pub topology: Vec<TopologyEntry>,
+    pub ssh: Option<SshWatch>,
+    #[serde(default)]
+    pub viewers: Vec<Viewer>,
+    #[serde(default)]
+    pub routes: Vec<NotificationRoute>,
+    #[serde(default)]
+    pub event_webhooks: Vec<EventSubscription>,
+    #[serde(default)]
+    pub reactions: Vec<Reaction>,
+    #[serde(default)]
+    pub supervise: Vec<RestartPolicy>,
+}
+
 /// Application configuration
 #[derive(Debug, Clone)]
 pub struct Config {
     pub port: u16,
+    /// Path prefix when served behind a reverse proxy (`/nanomon`), empty at the root
+    pub base_path: String,
     pub poll_interval: u64,
     pub history_size: usize,
+    /// Seconds before the history of a container, mount or interface that
+    /// disappeared is dropped, 0 to keep it while its snapshots are kept
+    pub history_forget_after: u64,
+    pub event_log_size: usize,
     #[allow(dead_code)]
     pub process_limit: usize,
     #[allow(dead_code)]
     pub docker_socket: String,
     pub proc_path: PathBuf,
     pub sys_path: PathBuf,
+    /// Directory holding the host's `hostname` and `passwd`
+    pub etc_path: PathBuf,
     pub log_level: String,
+    /// `text` or `json`
+    pub log_format: String,
     pub enable_systemd: bool,
     pub alert_config_path: Option<PathBuf>,
+    pub event_webhook_url: Option<String>,
+    /// Alerts of one evaluation bound for the same destination go out as one
+    /// digest
+    pub alert_digest: bool,
+    /// Bot that `telegram:<chat id>` notifications are sent as
+    pub telegram_bot_token: Option<String>,
+    /// Program `mailto:` notifications are piped to
+    pub sendmail_path: PathBuf,
+    pub state_file: Option<PathBuf>,
+    pub reboot_grace_seconds: u64,
+    pub admin_token: Option<String>,
+    /// Bearer token agents present to `POST /api/v1/ingest`; ingest is disabled when unset
+    pub ingest_token: Option<String>,
+    /// Aggregator ingest URL this instance pushes its snapshots to
+    pub push_url: Option<String>,
+    pub push_token: Option<String>,
+    /// Seconds without a push before an agent counts as down
+    pub remote_stale_seconds: u64,
+    /// Clock difference with an agent above which a warning is logged
+    pub remote_skew_seconds: u64,
+    pub ping_interval: u64,
+    pub speedtest_tool: Option<String>,
+    pub speedtest_interval: u64,
+    pub metered_interfaces: Vec<String>,
+    pub host_root: Option<PathBuf>,
+    pub volume_cache_ttl: u64,
+    pub volume_scan_limit: u64,
+    pub vuln_scanner: Option<String>,
+    pub vuln_reports_dir: Option<PathBuf>,
+    pub vuln_scan_interval: u64,
+    pub enable_firewall: bool,
+    /// Sample nftables rule counters for `/api/security/firewall/counters`
+    pub firewall_counters: bool,
+    /// List LXC/LXD containers and Proxmox CTs next to Docker's
+    pub enable_lxc: bool,
+    /// UDP address of the SNMP agent, disabled when unset
+    pub snmp_bind: Option<String>,
+    #[cfg_attr(not(feature = "snmp"), allow(dead_code))]
+    pub snmp_community: String,
+    /// Subtree the SNMP agent serves its objects under; the default is in
+    /// NET-SNMP's experimental "playpen", for lack of an enterprise number
+    #[cfg_attr(not(feature = "snmp"), allow(dead_code))]
+    pub snmp_oid: String,
+    /// OTLP/HTTP collector traces are sent to, disabled when unset
+    pub otlp_endpoint: Option<String>,
+    /// `libvirt` or `proxmox` to list VMs; unset on hosts without a hypervisor
+    pub hypervisor: Option<String>,
+    pub libvirt_uri: String,
+    /// Display timezone for human-readable output; API timestamps stay UTC
+    pub timezone: Tz,
+    /// Decimals kept for floats in JSON responses, `None` for full precision
+    pub json_precision: Option<u32>,
+    /// Argument names whose values are redacted from process commands
+    pub cmdline_redact: Vec<String>,
+    /// Maximum process command length in characters, 0 for no limit
+    pub cmdline_max_len: usize,
+    /// How process scans are sampled on hosts with many processes
+    pub process_sampling: ProcessSampling,
+    /// Process count above which scans are sampled
+    pub process_sampling_above: usize,
+    /// Omit identifying details (process owners and arguments, host paths, IPs) from the API
+    pub privacy_mode: bool,
+    /// Snapshot archive file; history is kept in memory only when unset
+    pub archive_path: Option<PathBuf>,
+    /// Directory of `*.json`/`*.prom` metric snippets read on every poll
+    pub textfile_dir: Option<PathBuf>,
+    /// `zstd` or `none`
+    pub archive_compression: String,
+    pub archive_compression_level: i32,
+    /// Apply Landlock and seccomp restrictions at startup
+    pub sandbox: bool,
+    /// Extra attempts for failed Docker API calls
+    pub docker_retries: u32,
+    /// Delay before the first Docker retry, doubled for each further one
+    pub docker_retry_backoff_ms: u64,
+    /// Consecutive Docker failures before calls are paused
+    pub docker_breaker_threshold: u32,
+    /// How long Docker calls stay paused once the breaker opens
+    pub docker_breaker_cooldown: u64,
+    /// Upper bound in seconds on each host metrics read (hung NFS mounts)
+    pub system_timeout: u64,
+    /// Resident memory NanoMon should stay under, in MB; 0 disables the check
+    pub memory_budget_mb: u64,
+    /// Percent of the poll interval collecting may take before expensive
+    /// collectors are stretched, 0 for no limit
+    pub collection_budget_percent: u64,
+    /// Log one line per HTTP request
+    pub access_log: bool,
+    /// Path prefixes left out of the access log
+    pub access_log_exclude: Vec<String>,
+    /// How long rendered API responses are reused, 0 to render every request
+    pub response_cache_ms: u64,
+    /// OpenID Connect issuer the dashboard logs in with; no login when unset
+    pub oidc_issuer: Option<String>,
+    pub oidc_client_id: Option<String>,
+    pub oidc_client_secret: Option<String>,
+    /// Public URL of `<base>/auth/callback`, registered with the provider
+    pub oidc_redirect_url: Option<String>,
+    /// Users allowed in, anyone the provider knows when empty
+    pub oidc_allowed_users: Vec<String>,
+    pub oidc_session_hours: u64,
+    /// Rejected tokens from one address before it is locked out, 0 to never lock out
+    pub auth_max_failures: usize,
+    /// Seconds failures are counted over and a lockout lasts
+    pub auth_lockout: u64,
+    /// Reverse proxies trusted to name the client in `X-Forwarded-For`
+    pub trusted_proxies: Vec<IpAddr>,
 }
 
 impl Config {
@@ -25,6 +185,9 @@ impl Config {
                 .ok()
                 .and_then(|s| s.parse().ok())
                 .unwrap_or(3000),
+            base_path: env::var("NANOMON_BASE_PATH")
+                .map(|s| normalize_base_path(&s))
+                .unwrap_or_default(),
             poll_interval: env::var("NANOMON_POLL_INTERVAL")
                 .ok()
                 .and_then(|s| s.parse().ok())
@@ -33,6 +196,14 @@ impl Config {
                 .ok()
                 .and_then(|s| s.parse().ok())
                 .unwrap_or(360),
+            history_forget_after: env::var("NANOMON_HISTORY_FORGET_AFTER")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(21600),
+            event_log_size: env::var("NANOMON_EVENT_LOG_SIZE")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(200),
             process_limit: env::var("NANOMON_PROCESS_LIMIT")
                 .ok()
                 .and_then(|s| s.parse().ok())
@@ -45,11 +216,255 @@ impl Config {
             sys_path: env::var("NANOMON_SYS_PATH")
                 .map(PathBuf::from)
                 .unwrap_or_else(|_| PathBuf::from("/sys")),
+            etc_path: env::var("NANOMON_ETC_PATH")
+                .map(PathBuf::from)
+                .unwrap_or_else(|_| PathBuf::from("/etc")),
             log_level: env::var("NANOMON_LOG_LEVEL").unwrap_or_else(|_| "info".to_string()),
+            log_format: env::var("NANOMON_LOG_FORMAT").unwrap_or_else(|_| "text".to_string()),
             enable_systemd: env::var("NANOMON_ENABLE_SYSTEMD")
                 .map(|s| s == "true" || s == "1")
                 .unwrap_or(false),
             alert_config_path: env::var("NANOMON_ALERT_CONFIG").ok().map(PathBuf::from),
+            event_webhook_url: env::var("NANOMON_EVENT_WEBHOOK_URL").ok(),
+            alert_digest: env::var("NANOMON_ALERT_DIGEST")
+                .map(|s| s == "true" || s == "1")
+                .unwrap_or(true),
+            telegram_bot_token: env::var("NANOMON_TELEGRAM_BOT_TOKEN")
+                .ok()
+                .filter(|s| !s.is_empty()),
+            sendmail_path: env::var("NANOMON_SENDMAIL")
+                .map(PathBuf::from)
+                .unwrap_or_else(|_| PathBuf::from("/usr/sbin/sendmail")),
+            state_file: env::var("NANOMON_STATE_FILE").ok().map(PathBuf::from),
+            reboot_grace_seconds: env::var("NANOMON_REBOOT_GRACE")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(300),
+            admin_token: env::var("NANOMON_ADMIN_TOKEN")
+                .ok()
+                .filter(|s| !s.is_empty()),
+            ingest_token: env::var("NANOMON_INGEST_TOKEN")
+                .ok()
+                .filter(|s| !s.is_empty()),
+            push_url: env::var("NANOMON_PUSH_URL").ok().filter(|s| !s.is_empty()),
+            push_token: env::var("NANOMON_PUSH_TOKEN")
+                .ok()
+                .filter(|s| !s.is_empty()),
+            remote_stale_seconds: env::var("NANOMON_REMOTE_STALE_SECONDS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(180),
+            remote_skew_seconds: env::var("NANOMON_REMOTE_SKEW_SECONDS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(30),
+            ping_interval: env::var("NANOMON_PING_INTERVAL")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(30),
+            speedtest_tool: env::var("NANOMON_SPEEDTEST_TOOL")
+                .ok()
+                .filter(|s| !s.is_empty()),
+            speedtest_interval: env::var("NANOMON_SPEEDTEST_INTERVAL")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(86400),
+            metered_interfaces: env::var("NANOMON_METERED_INTERFACES")
+                .map(|s| {
+                    s.split(',')
+                        .map(|i| i.trim().to_string())
+                        .filter(|i| !i.is_empty())
+                        .collect()
+                })
+                .unwrap_or_default(),
+            host_root: env::var("NANOMON_HOST_ROOT").ok().map(PathBuf::from),
+            volume_cache_ttl: env::var("NANOMON_VOLUME_CACHE_TTL")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(600),
+            volume_scan_limit: env::var("NANOMON_VOLUME_SCAN_LIMIT")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(200_000),
+            vuln_scanner: env::var("NANOMON_VULN_SCANNER")
+                .ok()
+                .filter(|s| !s.is_empty()),
+            vuln_reports_dir: env::var("NANOMON_VULN_REPORTS_DIR").ok().map(PathBuf::from),
+            vuln_scan_interval: env::var("NANOMON_VULN_SCAN_INTERVAL")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(21600),
+            enable_firewall: env::var("NANOMON_ENABLE_FIREWALL")
+                .map(|s| s == "true" || s == "1")
+                .unwrap_or(false),
+            firewall_counters: env::var("NANOMON_FIREWALL_COUNTERS")
+                .map(|s| s == "true" || s == "1")
+                .unwrap_or(false),
+            enable_lxc: env::var("NANOMON_ENABLE_LXC")
+                .map(|s| s == "true" || s == "1")
+                .unwrap_or(false),
+            snmp_bind: env::var("NANOMON_SNMP_BIND").ok().filter(|s| !s.is_empty()),
+            snmp_community: env::var("NANOMON_SNMP_COMMUNITY")
+                .unwrap_or_else(|_| "public".to_string()),
+            snmp_oid: env::var("NANOMON_SNMP_OID")
+                .unwrap_or_else(|_| "1.3.6.1.4.1.8072.9999.9999.1".to_string()),
+            otlp_endpoint: env::var("NANOMON_OTLP_ENDPOINT")
+                .ok()
+                .filter(|s| !s.is_empty()),
+            hypervisor: env::var("NANOMON_HYPERVISOR")
+                .ok()
+                .filter(|s| !s.is_empty()),
+            libvirt_uri: env::var("NANOMON_LIBVIRT_URI")
+                .unwrap_or_else(|_| "qemu:///system".to_string()),
+            timezone: env::var("NANOMON_TIMEZONE")
+                .or_else(|_| env::var("TZ"))
+                .ok()
+                .and_then(|s| parse_timezone(&s))
+                .unwrap_or(Tz::UTC),
+            json_precision: match env::var("NANOMON_JSON_PRECISION") {
+                Ok(s) if s == "off" => None,
+                Ok(s) => Some(s.parse().unwrap_or(2).min(10)),
+                Err(_) => Some(2),
+            },
+            cmdline_redact: env::var("NANOMON_CMDLINE_REDACT")
+                .map(|s| {
+                    s.split(',')
+                        .map(|p| p.trim().to_string())
+                        .filter(|p| !p.is_empty())
+                        .collect()
+                })
+                .unwrap_or_else(|_| {
+                    DEFAULT_REDACT_PATTERNS
+                        .iter()
+                        .map(|p| p.to_string())
+                        .collect()
+                }),
+            cmdline_max_len: env::var("NANOMON_CMDLINE_MAX_LEN")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(512),
+            process_sampling: env::var("NANOMON_PROCESS_SAMPLING")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or_default(),
+            process_sampling_above: env::var("NANOMON_PROCESS_SAMPLING_ABOVE")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(5000),
+            privacy_mode: env::var("NANOMON_PRIVACY_MODE")
+                .map(|s| s == "true" || s == "1")
+                .unwrap_or(false),
+            archive_path: env::var("NANOMON_ARCHIVE_PATH").ok().map(PathBuf::from),
+            textfile_dir: env::var("NANOMON_TEXTFILE_DIR").ok().map(PathBuf::from),
+            archive_compression: env::var("NANOMON_ARCHIVE_COMPRESSION")
+                .unwrap_or_else(|_| "zstd".to_string()),
+            archive_compression_level: env::var("NANOMON_ARCHIVE_COMPRESSION_LEVEL")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(3),
+            sandbox: env::var("NANOMON_SANDBOX")
+                .map(|s| s == "true" || s == "1")
+                .unwrap_or(false),
+            docker_retries: env::var("NANOMON_DOCKER_RETRIES")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(2),
+            docker_retry_backoff_ms: env::var("NANOMON_DOCKER_RETRY_BACKOFF_MS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(200),
+            docker_breaker_threshold: env::var("NANOMON_DOCKER_BREAKER_THRESHOLD")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(5),
+            docker_breaker_cooldown: env::var("NANOMON_DOCKER_BREAKER_COOLDOWN")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(30),
+            system_timeout: env::var("NANOMON_SYSTEM_TIMEOUT")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(5),
+            memory_budget_mb: env::var("NANOMON_MEMORY_BUDGET_MB")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(64),
+            collection_budget_percent: env::var("NANOMON_COLLECTION_BUDGET")
+                .ok()
+                .and_then(|s| s.trim_end_matches('%').parse().ok())
+                .unwrap_or(50)
+                .min(100),
+            response_cache_ms: env::var("NANOMON_RESPONSE_CACHE_MS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(1000),
+            oidc_issuer: env::var("NANOMON_OIDC_ISSUER")
+                .ok()
+                .filter(|s| !s.is_empty()),
+            oidc_client_id: env::var("NANOMON_OIDC_CLIENT_ID")
+                .ok()
+                .filter(|s| !s.is_empty()),
+            oidc_client_secret: env::var("NANOMON_OIDC_CLIENT_SECRET")
+                .ok()
+                .filter(|s| !s.is_empty()),
+            oidc_redirect_url: env::var("NANOMON_OIDC_REDIRECT_URL")
+                .ok()
+                .filter(|s| !s.is_empty()),
+            oidc_allowed_users: env::var("NANOMON_OIDC_ALLOWED_USERS")
+                .map(|s| {
+                    s.split(',')
+                        .map(|u| u.trim().to_string())
+                        .filter(|u| !u.is_empty())
+                        .collect()
+                })
+                .unwrap_or_default(),
+            oidc_session_hours: env::var("NANOMON_OIDC_SESSION_HOURS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .filter(|h| *h > 0)
+                .unwrap_or(12),
+            auth_max_failures: env::var("NANOMON_AUTH_MAX_FAILURES")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(10),
+            auth_lockout: env::var("NANOMON_AUTH_LOCKOUT")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(900),
+            trusted_proxies: env::var("NANOMON_TRUSTED_PROXIES")
+                .map(|s| s.split(',').filter_map(|p| p.trim().parse().ok()).collect())
+                .unwrap_or_default(),
+            access_log: env::var("NANOMON_ACCESS_LOG")
+                .map(|s| s == "true" || s == "1")
+                .unwrap_or(true),
+            access_log_exclude: env::var("NANOMON_ACCESS_LOG_EXCLUDE")
+                .map(|s| {
+                    s.split(',')
+                        .map(|p| p.trim().to_string())
+                        .filter(|p| !p.is_empty())
+                        .collect()
+                })
+                .unwrap_or_else(|_| {
+                    DEFAULT_ACCESS_LOG_EXCLUDE
+                        .iter()
+                        .map(|p| p.to_string())
+                        .collect()
+                }),
+        }
+    }
+
+    /// Copy safe to log: credentials and webhook URLs (which embed tokens) are masked
+    pub fn redacted(&self) -> Self {
+        let mask = |s: &Option<String>| s.as_ref().map(|_| "***".to_string());
+        Self {
+            event_webhook_url: mask(&self.event_webhook_url),
+            telegram_bot_token: mask(&self.telegram_bot_token),
+            admin_token: mask(&self.admin_token),
+            ingest_token: mask(&self.ingest_token),
+            push_token: mask(&self.push_token),
+            oidc_client_secret: mask(&self.oidc_client_secret),
+            snmp_community: "***".to_string(),
+            ..self.clone()
         }
     }
 }
@@ -59,3 +474,223 @@ impl Default for Config {
         Self::from_env()
     }
 }
+
+/// `nanomon/`, `/nanomon/` and `/nanomon` all become `/nanomon`; `/` becomes empty
+fn normalize_base_path(path: &str) -> String {
+    let trimmed = path.trim().trim_matches('/');
+    if trimmed.is_empty() {
+        String::new()
+    } else {
+        format!("/{}", trimmed)
+    }
+}
+
+/// What a variable's value must look like, for `--check-config`
+#[derive(Clone, Copy)]
+pub enum EnvValue {
+    /// Anything, such as paths, URLs and tokens
+    Text,
+    Unsigned,
+    Port,
+    /// `true`/`1`; anything else reads as false
+    Flag,
+    OneOf(&'static [&'static str]),
+    /// Checked by the function; the text names the format
+    Check(&'static str, fn(&str) -> Result<(), String>),
+}
+
+/// A variable `from_env` reads
+pub struct EnvVar {
+    pub name: &'static str,
+    pub value: EnvValue,
+    /// Comma-separated for lists, `None` when unset means off
+    pub default: Option<&'static str>,
+    pub default_list: Option<&'static [&'static str]>,
+    /// Masked wherever the value is shown
+    pub secret: bool,
+}
+
+impl EnvVar {
+    const fn new(name: &'static str, value: EnvValue) -> Self {
+        Self {
+            name,
+            value,
+            default: None,
+            default_list: None,
+            secret: false,
+        }
+    }
+
+    const fn with_default(mut self, default: &'static str) -> Self {
+        self.default = Some(default);
+        self
+    }
+
+    const fn with_default_list(mut self, default: &'static [&'static str]) -> Self {
+        self.default_list = Some(default);
+        self
+    }
+
+    const fn secret(mut self) -> Self {
+        self.secret = true;
+        self
+    }
+}
+
+/// Every variable `from_env` reads
+pub const ENV_VARS: &[EnvVar] = &[
+    EnvVar::new("NANOMON_PORT", EnvValue::Port).with_default("3000"),
+    EnvVar::new("NANOMON_BASE_PATH", EnvValue::Text),
+    EnvVar::new("NANOMON_POLL_INTERVAL", EnvValue::Unsigned).with_default("10"),
+    EnvVar::new("NANOMON_HISTORY_SIZE", EnvValue::Unsigned).with_default("360"),
+    EnvVar::new("NANOMON_HISTORY_FORGET_AFTER", EnvValue::Unsigned).with_default("21600"),
+    EnvVar::new("NANOMON_EVENT_LOG_SIZE", EnvValue::Unsigned).with_default("200"),
+    EnvVar::new("NANOMON_PROCESS_LIMIT", EnvValue::Unsigned).with_default("20"),
+    EnvVar::new("DOCKER_HOST", EnvValue::Text).with_default("unix:///var/run/docker.sock"),
+    EnvVar::new("NANOMON_PROC_PATH", EnvValue::Text).with_default("/proc"),
+    EnvVar::new("NANOMON_SYS_PATH", EnvValue::Text).with_default("/sys"),
+    EnvVar::new("NANOMON_ETC_PATH", EnvValue::Text).with_default("/etc"),
+    EnvVar::new(
+        "NANOMON_LOG_LEVEL",
+        EnvValue::OneOf(&["trace", "debug", "info", "warn", "error"]),
+    )
+    .with_default("info"),
+    EnvVar::new("NANOMON_LOG_FORMAT", EnvValue::OneOf(&["text", "json"])).with_default("text"),
+    EnvVar::new("NANOMON_ENABLE_SYSTEMD", EnvValue::Flag).with_default("false"),
+    EnvVar::new("NANOMON_ALERT_CONFIG", EnvValue::Text),
+    EnvVar::new("NANOMON_EVENT_WEBHOOK_URL", EnvValue::Text).secret(),
+    EnvVar::new("NANOMON_ALERT_DIGEST", EnvValue::Flag).with_default("true"),
+    EnvVar::new("NANOMON_TELEGRAM_BOT_TOKEN", EnvValue::Text).secret(),
+    EnvVar::new("NANOMON_SENDMAIL", EnvValue::Text).with_default("/usr/sbin/sendmail"),
+    EnvVar::new("NANOMON_STATE_FILE", EnvValue::Text),
+    EnvVar::new("NANOMON_REBOOT_GRACE", EnvValue::Unsigned).with_default("300"),
+    EnvVar::new("NANOMON_ADMIN_TOKEN", EnvValue::Text).secret(),
+    EnvVar::new("NANOMON_INGEST_TOKEN", EnvValue::Text).secret(),
+    EnvVar::new("NANOMON_PUSH_URL", EnvValue::Text),
+    EnvVar::new("NANOMON_PUSH_TOKEN", EnvValue::Text).secret(),
+    EnvVar::new("NANOMON_REMOTE_STALE_SECONDS", EnvValue::Unsigned).with_default("180"),
+    EnvVar::new("NANOMON_REMOTE_SKEW_SECONDS", EnvValue::Unsigned).with_default("30"),
+    EnvVar::new("NANOMON_PING_INTERVAL", EnvValue::Unsigned).with_default("30"),
+    EnvVar::new(
+        "NANOMON_SPEEDTEST_TOOL",
+        EnvValue::Check("speedtest tool", |s| {
+            SpeedtestTool::parse(s)
+                .map(|_| ())
+                .ok_or_else(|| "expected ookla, speedtest-cli or librespeed".to_string())
+        }),
+    ),
+    EnvVar::new("NANOMON_SPEEDTEST_INTERVAL", EnvValue::Unsigned).with_default("86400"),
+    EnvVar::new("NANOMON_METERED_INTERFACES", EnvValue::Text),
+    EnvVar::new("NANOMON_HOST_ROOT", EnvValue::Text),
+    EnvVar::new("NANOMON_VOLUME_CACHE_TTL", EnvValue::Unsigned).with_default("600"),
+    EnvVar::new("NANOMON_VOLUME_SCAN_LIMIT", EnvValue::Unsigned).with_default("200000"),
+    EnvVar::new("NANOMON_VULN_SCANNER", EnvValue::OneOf(&["trivy", "grype"])),
+    EnvVar::new("NANOMON_VULN_REPORTS_DIR", EnvValue::Text),
+    EnvVar::new("NANOMON_VULN_SCAN_INTERVAL", EnvValue::Unsigned).with_default("21600"),
+    EnvVar::new("NANOMON_ENABLE_FIREWALL", EnvValue::Flag).with_default("false"),
+    EnvVar::new("NANOMON_FIREWALL_COUNTERS", EnvValue::Flag).with_default("false"),
+    EnvVar::new("NANOMON_ENABLE_LXC", EnvValue::Flag).with_default("false"),
+    EnvVar::new("NANOMON_SNMP_BIND", EnvValue::Text),
+    EnvVar::new("NANOMON_SNMP_COMMUNITY", EnvValue::Text)
+        .with_default("public")
+        .secret(),
+    EnvVar::new("NANOMON_SNMP_OID", EnvValue::Text).with_default("1.3.6.1.4.1.8072.9999.9999.1"),
+    EnvVar::new("NANOMON_OTLP_ENDPOINT", EnvValue::Text),
+    EnvVar::new(
+        "NANOMON_HYPERVISOR",
+        EnvValue::Check("hypervisor", |s| {
+            Hypervisor::parse(s)
+                .map(|_| ())
+                .ok_or_else(|| "expected libvirt or proxmox".to_string())
+        }),
+    ),
+    EnvVar::new("NANOMON_LIBVIRT_URI", EnvValue::Text).with_default("qemu:///system"),
+    EnvVar::new(
+        "NANOMON_TIMEZONE",
+        EnvValue::Check("IANA timezone", |s| {
+            parse_timezone(s)
+                .map(|_| ())
+                .ok_or_else(|| "not an IANA timezone such as Europe/Paris".to_string())
+        }),
+    )
+    .with_default("UTC"),
+    EnvVar::new(
+        "NANOMON_JSON_PRECISION",
+        EnvValue::Check("decimals or off", |s| match s.parse::<u32>() {
+            _ if s == "off" => Ok(()),
+            Ok(n) if n > 10 => Err("capped at 10 decimals".to_string()),
+            Ok(_) => Ok(()),
+            Err(_) => Err("expected a number of decimals or off, 2 are kept".to_string()),
+        }),
+    )
+    .with_default("2"),
+    EnvVar::new("NANOMON_CMDLINE_REDACT", EnvValue::Text)
+        .with_default_list(DEFAULT_REDACT_PATTERNS),
+    EnvVar::new("NANOMON_CMDLINE_MAX_LEN", EnvValue::Unsigned).with_default("512"),
+    EnvVar::new(
+        "NANOMON_PROCESS_SAMPLING",
+        EnvValue::Check("sampling mode", |s| {
+            s.parse::<ProcessSampling>().map(|_| ())
+        }),
+    )
+    .with_default("full"),
+    EnvVar::new("NANOMON_PROCESS_SAMPLING_ABOVE", EnvValue::Unsigned).with_default("5000"),
+    EnvVar::new("NANOMON_PRIVACY_MODE", EnvValue::Flag).with_default("false"),
+    EnvVar::new("NANOMON_ARCHIVE_PATH", EnvValue::Text),
+    EnvVar::new("NANOMON_TEXTFILE_DIR", EnvValue::Text),
+    EnvVar::new(
+        "NANOMON_ARCHIVE_COMPRESSION",
+        EnvValue::OneOf(&["zstd", "none"]),
+    )
+    .with_default("zstd"),
+    EnvVar::new(
+        "NANOMON_ARCHIVE_COMPRESSION_LEVEL",
+        EnvValue::Check("1 to 19", |s| match s.parse::<i32>() {
+            Ok(1..=19) => Ok(()),
+            _ => Err("expected a level from 1 to 19".to_string()),
+        }),
+    )
+    .with_default("3"),
+    EnvVar::new("NANOMON_SANDBOX", EnvValue::Flag).with_default("false"),
+    EnvVar::new("NANOMON_DOCKER_RETRIES", EnvValue::Unsigned).with_default("2"),
+    EnvVar::new("NANOMON_DOCKER_RETRY_BACKOFF_MS", EnvValue::Unsigned).with_default("200"),
+    EnvVar::new("NANOMON_DOCKER_BREAKER_THRESHOLD", EnvValue::Unsigned).with_default("5"),
+    EnvVar::new("NANOMON_DOCKER_BREAKER_COOLDOWN", EnvValue::Unsigned).with_default("30"),
+    EnvVar::new("NANOMON_SYSTEM_TIMEOUT", EnvValue::Unsigned).with_default("5"),
+    EnvVar::new("NANOMON_MEMORY_BUDGET_MB", EnvValue::Unsigned).with_default("64"),
+    EnvVar::new(
+        "NANOMON_COLLECTION_BUDGET",
+        EnvValue::Check("percent", |s| {
+            match s.trim_end_matches('%').parse::<u64>() {
+                Ok(0..=100) => Ok(()),
+                _ => Err("expected a percentage from 0 to 100".to_string()),
+            }
+        }),
+    )
+    .with_default("50"),
+    EnvVar::new("NANOMON_RESPONSE_CACHE_MS", EnvValue::Unsigned).with_default("1000"),
+    EnvVar::new("NANOMON_OIDC_ISSUER", EnvValue::Text),
+    EnvVar::new("NANOMON_OIDC_CLIENT_ID", EnvValue::Text),
+    EnvVar::new("NANOMON_OIDC_CLIENT_SECRET", EnvValue::Text).secret(),
+    EnvVar::new("NANOMON_OIDC_REDIRECT_URL", EnvValue::Text),
+    EnvVar::new("NANOMON_OIDC_ALLOWED_USERS", EnvValue::Text),
+    EnvVar::new("NANOMON_OIDC_SESSION_HOURS", EnvValue::Unsigned).with_default("12"),
+    EnvVar::new("NANOMON_AUTH_MAX_FAILURES", EnvValue::Unsigned).with_default("10"),
+    EnvVar::new("NANOMON_AUTH_LOCKOUT", EnvValue::Unsigned).with_default("900"),
+    EnvVar::new(
+        "NANOMON_TRUSTED_PROXIES",
+        EnvValue::Check("IP addresses", |s| {
+            match s
+                .split(',')
+                .map(str::trim)
+                .find(|p| !p.is_empty() && p.parse::<IpAddr>().is_err())
+            {
+                Some(bad) => Err(format!("'{}' is not an IP address", bad)),
+                None => Ok(()),
+            }
+        }),
+    ),
+    EnvVar::new("NANOMON_ACCESS_LOG", EnvValue::Flag).with_default("true"),
+    EnvVar::new("NANOMON_ACCESS_LOG_EXCLUDE", EnvValue::Text)
+        .with_default_list(DEFAULT_ACCESS_LOG_EXCLUDE),
+];
@@ -1,5 +1,13 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use super::{Host, Severity};
+
+/// Resource of host-wide metrics in alert statistics
+pub const HOST_RESOURCE: &str = "host";
+
 /// A rule defining when an alert should fire
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AlertRule {
@@ -7,12 +15,28 @@ pub struct AlertRule {
     pub metric: AlertMetric,
     pub condition: AlertCondition,
     pub threshold: f64,
+    /// Used when no `[[routes]]` entry matches the rule
+    #[serde(default)]
     pub webhook_url: String,
     #[serde(default = "default_cooldown")]
     pub cooldown_seconds: u64,
+    #[serde(default)]
+    pub severity: Severity,
+    /// Who else to notify while an alert stays unacknowledged
+    #[serde(default)]
+    pub escalation: Vec<EscalationStep>,
 }
 
-fn default_cooldown() -> u64 {
+/// Destinations notified once an alert has gone unacknowledged for a while
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EscalationStep {
+    /// Minutes after the alert started firing
+    pub after_minutes: u64,
+    /// Webhook URLs, `plugin://`, `mailto:` or `telegram:` destinations
+    pub notify: Vec<String>,
+}
+
+pub fn default_cooldown() -> u64 {
     300 // 5 minutes
 }
 
@@ -22,9 +46,91 @@ fn default_cooldown() -> u64 {
 pub enum AlertMetric {
     CpuUsage,
     MemoryUsage,
-    DiskUsage { mount_point: String },
+    DiskUsage {
+        mount_point: String,
+    },
+    #[serde(alias = "load_average_1m")]
     LoadAverage1m,
-    Temperature { label: String },
+    Temperature {
+        label: String,
+    },
+    /// Usage percent of `file_handles`, `pids` or `conntrack`
+    KernelLimit {
+        name: String,
+    },
+    /// Usage percent of a container's pids limit
+    ContainerPids {
+        container: String,
+    },
+    /// Value of a user-defined collector's metric; with `labels`, the first
+    /// metric carrying all of them
+    Custom {
+        name: String,
+        #[serde(default)]
+        labels: BTreeMap<String, String>,
+    },
+}
+
+impl AlertMetric {
+    /// What the metric is about: a mount point, sensor, container...
+    pub fn resource(&self) -> String {
+        match self {
+            Self::CpuUsage | Self::MemoryUsage | Self::LoadAverage1m => HOST_RESOURCE.to_string(),
+            Self::DiskUsage { mount_point } => mount_point.clone(),
+            Self::Temperature { label } => label.clone(),
+            Self::KernelLimit { name } | Self::Custom { name, .. } => name.clone(),
+            Self::ContainerPids { container } => container.clone(),
+        }
+    }
+
+    /// The metric's name in the alert config, such as `disk_usage`
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::CpuUsage => "cpu_usage",
+            Self::MemoryUsage => "memory_usage",
+            Self::DiskUsage { .. } => "disk_usage",
+            Self::LoadAverage1m => "load_average1m",
+            Self::Temperature { .. } => "temperature",
+            Self::KernelLimit { .. } => "kernel_limit",
+            Self::ContainerPids { .. } => "container_pids",
+            Self::Custom { .. } => "custom",
+        }
+    }
+
+    /// The metric's value in `snapshot`, `None` when it does not have it
+    pub fn value(&self, snapshot: &Host) -> Option<f64> {
+        match self {
+            Self::CpuUsage => Some(snapshot.cpu.usage_percent),
+            Self::MemoryUsage => {
+                if snapshot.memory.total_bytes == 0 {
+                    return None;
+                }
+                Some(snapshot.memory.used_bytes as f64 / snapshot.memory.total_bytes as f64 * 100.0)
+            }
+            Self::DiskUsage { mount_point } => snapshot
+                .disks
+                .iter()
+                .find(|d| d.mount_point == *mount_point)
+                .map(|d| d.usage_percent()),
+            Self::LoadAverage1m => Some(snapshot.load_average.one),
+            Self::Temperature { label } => snapshot
+                .temperatures
+                .iter()
+                .find(|t| t.label == *label)
+                .map(|t| t.current_celsius),
+            Self::KernelLimit { name } => snapshot.kernel_limits.get(name).map(|l| l.usage_percent),
+            Self::ContainerPids { container } => snapshot
+                .containers
+                .iter()
+                .find(|c| c.name == *container)
+                .and_then(|c| c.pids?.usage_percent),
+            Self::Custom { name, labels } => snapshot
+                .custom_metrics
+                .iter()
+                .find(|m| m.matches(name, labels))
+                .map(|m| m.value),
+        }
+    }
 }
 
 /// Comparison condition
@@ -48,6 +154,13 @@ impl AlertCondition {
 #[derive(Debug, Clone, Serialize)]
 pub struct AlertEvent {
     pub rule_name: String,
+    pub severity: Severity,
+    /// Incident to acknowledge at `POST /api/alerts/{id}/ack`, for rule alerts
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub incident_id: Option<u64>,
+    /// Escalation step this notification is for, from 1
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub escalation: Option<usize>,
     pub metric: String,
     pub current_value: f64,
     pub threshold: f64,
@@ -55,3 +168,349 @@ pub struct AlertEvent {
     pub hostname: String,
     pub timestamp: String,
 }
+
+impl AlertEvent {
+    /// One line summing up alerts sent together, most severe first:
+    /// `3 alerts on nas (1 critical, 2 warning): Disk, High CPU, High Load`
+    pub fn digest_summary(events: &[AlertEvent]) -> String {
+        let mut sorted: Vec<&AlertEvent> = events.iter().collect();
+        sorted.sort_by_key(|e| std::cmp::Reverse(e.severity));
+        let mut counts: Vec<(Severity, usize)> = Vec::new();
+        for event in &sorted {
+            match counts.last_mut() {
+                Some((severity, count)) if *severity == event.severity => *count += 1,
+                _ => counts.push((event.severity, 1)),
+            }
+        }
+        let counts: Vec<String> = counts
+            .iter()
+            .map(|(severity, count)| format!("{} {}", count, severity.as_str()))
+            .collect();
+        let rules: Vec<&str> = sorted.iter().map(|e| e.rule_name.as_str()).collect();
+        format!(
+            "{} alerts on {} ({}): {}",
+            events.len(),
+            events.first().map_or("", |e| e.hostname.as_str()),
+            counts.join(", "),
+            rules.join(", ")
+        )
+    }
+}
+
+/// A stretch of time during which a rule's condition held
+#[derive(Debug, Clone, Serialize)]
+pub struct AlertIncident {
+    /// Unique since startup
+    pub id: u64,
+    pub rule: String,
+    pub resource: String,
+    pub started_at: DateTime<Utc>,
+    /// `None` while the condition still holds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolved_at: Option<DateTime<Utc>>,
+    /// Value furthest past the threshold
+    pub peak_value: f64,
+    /// Webhooks sent; the cooldown keeps this low for long incidents
+    pub notifications: u32,
+    /// Acknowledging stops repeat notifications and escalation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub acknowledged_by: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub acknowledged_at: Option<DateTime<Utc>>,
+    /// Escalation steps notified so far
+    pub escalations: usize,
+    /// Fired during a burn-in: recorded, never notified
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub expected: bool,
+}
+
+impl AlertIncident {
+    /// An incident of `rule` that started at `started_at` with `value`
+    pub fn new(id: u64, rule: &AlertRule, started_at: DateTime<Utc>, value: f64) -> Self {
+        Self {
+            id,
+            rule: rule.name.clone(),
+            resource: rule.metric.resource(),
+            started_at,
+            resolved_at: None,
+            peak_value: value,
+            notifications: 0,
+            acknowledged_by: None,
+            acknowledged_at: None,
+            escalations: 0,
+            expected: false,
+        }
+    }
+}
+
+/// When a rule would have fired over past snapshots, to tune its threshold
+/// without waiting for a real incident
+#[derive(Debug, Clone, Serialize)]
+pub struct AlertDryRun {
+    /// Snapshots replayed, and how many of them had the metric
+    pub samples: usize,
+    pub evaluated: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_value: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_value: Option<f64>,
+    /// Value in the latest snapshot having the metric
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current_value: Option<f64>,
+    /// Whether the rule would be firing now
+    pub firing: bool,
+    /// Webhooks the rule would have sent, given its cooldown
+    pub notifications: u32,
+    /// Oldest first
+    pub incidents: Vec<AlertIncident>,
+}
+
+impl AlertDryRun {
+    /// Replays `rule` over `snapshots` (oldest first) the way the evaluator
+    /// would have, timed by the snapshots
+    pub fn run<'a>(rule: &AlertRule, snapshots: impl IntoIterator<Item = &'a Host>) -> Self {
+        let mut run = Self {
+            samples: 0,
+            evaluated: 0,
+            min_value: None,
+            max_value: None,
+            current_value: None,
+            firing: false,
+            notifications: 0,
+            incidents: Vec::new(),
+        };
+        let mut open: Option<AlertIncident> = None;
+        let mut last_fired: Option<DateTime<Utc>> = None;
+        for snapshot in snapshots {
+            run.samples += 1;
+            let Some(value) = rule.metric.value(snapshot) else {
+                continue;
+            };
+            run.evaluated += 1;
+            run.min_value = Some(run.min_value.map_or(value, |v| v.min(value)));
+            run.max_value = Some(run.max_value.map_or(value, |v| v.max(value)));
+            run.current_value = Some(value);
+
+            let at = snapshot.timestamp;
+            if !rule.condition.evaluate(value, rule.threshold) {
+                if let Some(mut incident) = open.take() {
+                    incident.resolved_at = Some(at);
+                    run.incidents.push(incident);
+                }
+                continue;
+            }
+            let id = run.incidents.len() as u64 + 1;
+            let incident = open.get_or_insert_with(|| AlertIncident::new(id, rule, at, value));
+            if rule.condition.evaluate(value, incident.peak_value) {
+                incident.peak_value = value;
+            }
+            let cooled_down = last_fired
+                .is_none_or(|last| (at - last).num_seconds() >= rule.cooldown_seconds as i64);
+            if cooled_down {
+                last_fired = Some(at);
+                incident.notifications += 1;
+                run.notifications += 1;
+            }
+        }
+        run.firing = open.is_some();
+        run.incidents.extend(open);
+        run
+    }
+}
+
+/// How often one rule fired over the window
+#[derive(Debug, Clone, Serialize)]
+pub struct RuleStats {
+    pub rule: String,
+    pub incidents: usize,
+    pub notifications: u32,
+    /// Still firing at the end of the window
+    pub open: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mean_time_to_resolve_seconds: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ResourceStats {
+    pub resource: String,
+    pub incidents: usize,
+}
+
+/// Alert frequency per rule and noisiest resources, to tune thresholds
+#[derive(Debug, Clone, Serialize)]
+pub struct AlertStats {
+    pub window_seconds: u64,
+    pub incidents: usize,
+    /// Most incidents first
+    pub rules: Vec<RuleStats>,
+    pub noisiest_resources: Vec<ResourceStats>,
+}
+
+impl AlertStats {
+    /// Statistics over the incidents active at some point since `since`
+    pub fn build(incidents: &[AlertIncident], since: DateTime<Utc>, resource_limit: usize) -> Self {
+        let in_window: Vec<&AlertIncident> = incidents
+            .iter()
+            .filter(|i| i.resolved_at.is_none_or(|at| at >= since))
+            .collect();
+
+        let mut by_rule: BTreeMap<&str, Vec<&AlertIncident>> = BTreeMap::new();
+        let mut by_resource: HashMap<&str, usize> = HashMap::new();
+        for incident in &in_window {
+            by_rule.entry(&incident.rule).or_default().push(incident);
+            *by_resource.entry(&incident.resource).or_default() += 1;
+        }
+
+        let mut rules: Vec<RuleStats> = by_rule
+            .into_iter()
+            .map(|(rule, incidents)| {
+                let resolve_times: Vec<f64> = incidents
+                    .iter()
+                    .filter_map(|i| i.resolved_at.map(|at| at - i.started_at))
+                    .map(|d| d.num_milliseconds() as f64 / 1000.0)
+                    .collect();
+                RuleStats {
+                    rule: rule.to_string(),
+                    incidents: incidents.len(),
+                    notifications: incidents.iter().map(|i| i.notifications).sum(),
+                    open: incidents.iter().any(|i| i.resolved_at.is_none()),
+                    mean_time_to_resolve_seconds: (!resolve_times.is_empty())
+                        .then(|| resolve_times.iter().sum::<f64>() / resolve_times.len() as f64),
+                }
+            })
+            .collect();
+        rules.sort_by_key(|r| std::cmp::Reverse(r.incidents));
+
+        let mut noisiest_resources: Vec<ResourceStats> = by_resource
+            .into_iter()
+            .map(|(resource, incidents)| ResourceStats {
+                resource: resource.to_string(),
+                incidents,
+            })
+            .collect();
+        noisiest_resources.sort_by(|a, b| {
+            b.incidents
+                .cmp(&a.incidents)
+                .then_with(|| a.resource.cmp(&b.resource))
+        });
+        noisiest_resources.truncate(resource_limit);
+
+        Self {
+            window_seconds: (Utc::now() - since).num_seconds().max(0) as u64,
+            incidents: in_window.len(),
+            rules,
+            noisiest_resources,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn incident(
+        rule: &str,
+        resource: &str,
+        minutes_ago: i64,
+        lasted: Option<i64>,
+    ) -> AlertIncident {
+        let started_at = Utc::now() - Duration::minutes(minutes_ago);
+        AlertIncident {
+            id: 1,
+            rule: rule.to_string(),
+            resource: resource.to_string(),
+            started_at,
+            resolved_at: lasted.map(|m| started_at + Duration::minutes(m)),
+            peak_value: 95.0,
+            notifications: 1,
+            acknowledged_by: None,
+            acknowledged_at: None,
+            escalations: 0,
+            expected: false,
+        }
+    }
+
+    #[test]
+    fn test_stats_per_rule_and_resource() {
+        let incidents = [
+            incident("disk", "/data", 300, Some(10)),
+            incident("disk", "/data", 50, Some(4)),
+            incident("disk", "/data", 30, Some(2)),
+            incident("cpu", HOST_RESOURCE, 10, None),
+        ];
+        let stats = AlertStats::build(&incidents, Utc::now() - Duration::hours(1), 10);
+
+        assert_eq!(stats.incidents, 3);
+        assert_eq!(stats.rules[0].rule, "disk");
+        assert_eq!(stats.rules[0].incidents, 2);
+        assert_eq!(stats.rules[0].mean_time_to_resolve_seconds, Some(180.0));
+        assert!(!stats.rules[0].open);
+        assert!(stats.rules[1].open);
+        assert_eq!(stats.rules[1].mean_time_to_resolve_seconds, None);
+        assert_eq!(stats.noisiest_resources[0].resource, "/data");
+    }
+
+    #[test]
+    fn test_digest_summary_counts_by_severity() {
+        let event = |rule: &str, severity: Severity| AlertEvent {
+            rule_name: rule.to_string(),
+            severity,
+            incident_id: None,
+            escalation: None,
+            metric: "CpuUsage".to_string(),
+            current_value: 95.0,
+            threshold: 90.0,
+            condition: "Above".to_string(),
+            hostname: "nas".to_string(),
+            timestamp: String::new(),
+        };
+        let events = [
+            event("High CPU", Severity::Warning),
+            event("Disk", Severity::Critical),
+            event("High Load", Severity::Warning),
+        ];
+        assert_eq!(
+            AlertEvent::digest_summary(&events),
+            "3 alerts on nas (1 critical, 2 warning): Disk, High CPU, High Load"
+        );
+    }
+
+    #[test]
+    fn test_dry_run_replays_cooldown_and_incidents() {
+        let start = Utc::now() - Duration::hours(1);
+        let cpu = [50.0, 95.0, 97.0, 99.0, 96.0, 40.0, 92.0, 93.0];
+        let history: Vec<Host> = cpu
+            .iter()
+            .enumerate()
+            .map(|(i, usage)| {
+                let mut host = Host::new("nas".to_string())
+                    .with_timestamp(start + Duration::minutes(i as i64 * 2));
+                host.cpu.usage_percent = *usage;
+                host
+            })
+            .collect();
+        let rule = AlertRule {
+            name: "cpu".to_string(),
+            metric: AlertMetric::CpuUsage,
+            condition: AlertCondition::Above,
+            threshold: 90.0,
+            webhook_url: String::new(),
+            cooldown_seconds: 300,
+            severity: Severity::Warning,
+            escalation: Vec::new(),
+        };
+
+        let run = AlertDryRun::run(&rule, &history);
+        assert_eq!((run.samples, run.evaluated), (8, 8));
+        assert_eq!((run.min_value, run.max_value), (Some(40.0), Some(99.0)));
+        assert!(run.firing);
+        assert_eq!(run.incidents.len(), 2);
+        assert_eq!(run.incidents[0].peak_value, 99.0);
+        assert_eq!(run.incidents[0].resolved_at, Some(history[5].timestamp));
+        // Fired at 2 and 8 minutes; the second incident starts within the cooldown
+        assert_eq!(run.incidents[0].notifications, 2);
+        assert_eq!(run.incidents[1].notifications, 1);
+        assert_eq!(run.notifications, 3);
+    }
+}
@@ -0,0 +1,69 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Longest accepted annotation note, in characters
+pub const MAX_NOTE_LENGTH: usize = 500;
+
+/// A note pinned to a point in time ("upgraded nextcloud", "swapped PSU"),
+/// shown as a marker on the history charts
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Annotation {
+    pub timestamp: DateTime<Utc>,
+    pub note: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+impl Annotation {
+    /// Fails on an empty or overly long note
+    pub fn new(note: &str, timestamp: DateTime<Utc>) -> Result<Self, String> {
+        let note = note.trim();
+        if note.is_empty() {
+            return Err("Annotation note is empty".to_string());
+        }
+        if note.chars().count() > MAX_NOTE_LENGTH {
+            return Err(format!(
+                "Annotation note is longer than {} characters",
+                MAX_NOTE_LENGTH
+            ));
+        }
+        Ok(Self {
+            timestamp,
+            note: note.to_string(),
+            tags: Vec::new(),
+        })
+    }
+
+    /// Lowercased, deduplicated tags, blank ones dropped
+    pub fn with_tags(mut self, tags: &[String]) -> Self {
+        let mut tags: Vec<String> = tags
+            .iter()
+            .map(|t| t.trim().to_lowercase())
+            .filter(|t| !t.is_empty())
+            .collect();
+        tags.sort();
+        tags.dedup();
+        self.tags = tags;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_annotation_validation_and_tags() {
+        let now = Utc::now();
+        assert!(Annotation::new("   ", now).is_err());
+        assert!(Annotation::new(&"x".repeat(MAX_NOTE_LENGTH + 1), now).is_err());
+
+        let annotation = Annotation::new(" swapped PSU ", now).unwrap().with_tags(&[
+            "Hardware".to_string(),
+            " ".to_string(),
+            "hardware".to_string(),
+        ]);
+        assert_eq!(annotation.note, "swapped PSU");
+        assert_eq!(annotation.tags, vec!["hardware"]);
+    }
+}
@@ -0,0 +1,35 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use super::OffenderCount;
+
+/// An address refused by the API for a while after too many rejected tokens
+#[derive(Debug, Clone, Serialize)]
+pub struct ApiLockout {
+    pub ip: String,
+    pub failures: usize,
+    pub until: DateTime<Utc>,
+}
+
+/// Rejected API credentials since startup and the lockouts they caused
+#[derive(Debug, Clone, Serialize)]
+pub struct ApiAuthSummary {
+    /// Rejected tokens from one address within `lockout_seconds` before it
+    /// is locked out, 0 when lockouts are disabled
+    pub max_failures: usize,
+    pub lockout_seconds: u64,
+    pub failures_total: u64,
+    pub lockouts_total: u64,
+    /// Addresses with recent failures, most first
+    pub top_offenders: Vec<OffenderCount>,
+    pub locked_out: Vec<ApiLockout>,
+}
+
+impl ApiAuthSummary {
+    /// Keep the counters, drop the addresses
+    pub fn redacted(mut self) -> Self {
+        self.top_offenders.clear();
+        self.locked_out.clear();
+        self
+    }
+}
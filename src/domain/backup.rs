@@ -0,0 +1,132 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// How a backup job leaves traces on disk
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BackupKind {
+    /// Plain archive files matching a glob (e.g. `/backups/db-*.sql.gz`)
+    Files,
+    /// A restic repository directory
+    Restic,
+    /// A borg repository directory
+    Borg,
+}
+
+/// A backup job to verify
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupJob {
+    pub name: String,
+    pub kind: BackupKind,
+    pub path: String,
+    pub max_age_hours: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_size_bytes: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhook_url: Option<String>,
+}
+
+/// Most recent artifact found for a backup job
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupArtifact {
+    pub path: String,
+    pub modified: DateTime<Utc>,
+    pub size_bytes: u64,
+}
+
+/// Verification result for a backup job
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupStatus {
+    pub name: String,
+    pub kind: BackupKind,
+    pub max_age_hours: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_success: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub age_seconds: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub artifact: Option<BackupArtifact>,
+    pub stale: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub problem: Option<String>,
+}
+
+impl BackupStatus {
+    /// Judge the job from its newest artifact
+    pub fn evaluate(job: &BackupJob, latest: Option<BackupArtifact>, now: DateTime<Utc>) -> Self {
+        let mut status = Self {
+            name: job.name.clone(),
+            kind: job.kind,
+            max_age_hours: job.max_age_hours,
+            last_success: None,
+            age_seconds: None,
+            artifact: None,
+            stale: true,
+            problem: None,
+        };
+
+        let Some(artifact) = latest else {
+            status.problem = Some("no backup found".to_string());
+            return status;
+        };
+
+        let age = now
+            .signed_duration_since(artifact.modified)
+            .num_seconds()
+            .max(0) as u64;
+        let too_small = job
+            .min_size_bytes
+            .is_some_and(|min| artifact.size_bytes < min);
+
+        status.problem = if too_small {
+            Some(format!(
+                "latest backup is only {} bytes",
+                artifact.size_bytes
+            ))
+        } else if age > job.max_age_hours * 3600 {
+            Some(format!("latest backup is {}h old", age / 3600))
+        } else {
+            None
+        };
+        status.stale = status.problem.is_some();
+        if !too_small {
+            status.last_success = Some(artifact.modified);
+        }
+        status.age_seconds = Some(age);
+        status.artifact = Some(artifact);
+        status
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job() -> BackupJob {
+        BackupJob {
+            name: "db".to_string(),
+            kind: BackupKind::Files,
+            path: "/backups/db-*.gz".to_string(),
+            max_age_hours: 24,
+            min_size_bytes: Some(1000),
+            webhook_url: None,
+        }
+    }
+
+    fn artifact(hours_ago: i64, size_bytes: u64) -> BackupArtifact {
+        BackupArtifact {
+            path: "/backups/db-1.gz".to_string(),
+            modified: Utc::now() - chrono::Duration::hours(hours_ago),
+            size_bytes,
+        }
+    }
+
+    #[test]
+    fn test_backup_evaluation() {
+        let now = Utc::now();
+        assert!(!BackupStatus::evaluate(&job(), Some(artifact(2, 5000)), now).stale);
+        assert!(BackupStatus::evaluate(&job(), Some(artifact(30, 5000)), now).stale);
+        assert!(BackupStatus::evaluate(&job(), Some(artifact(2, 10)), now).stale);
+        assert!(BackupStatus::evaluate(&job(), None, now).stale);
+    }
+}
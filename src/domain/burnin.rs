@@ -0,0 +1,239 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use super::{AlertRule, Host};
+
+/// Host CPU usage counted as sustained load
+pub const SUSTAINED_CPU_PERCENT: f64 = 90.0;
+
+/// Hottest reading of one sensor during a burn-in
+#[derive(Debug, Clone, Serialize)]
+pub struct SensorPeak {
+    pub label: String,
+    pub max_celsius: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub critical_celsius: Option<f64>,
+}
+
+/// A rule that fired during a burn-in, recorded instead of notified
+#[derive(Debug, Clone, Serialize)]
+pub struct ExpectedAlert {
+    pub rule: String,
+    pub first_at: DateTime<Utc>,
+    pub last_at: DateTime<Utc>,
+    /// Value furthest past the threshold
+    pub peak_value: f64,
+    /// Evaluations it fired on
+    pub evaluations: u32,
+}
+
+/// What a stress test did to the hardware, built up poll by poll while the
+/// burn-in runs
+#[derive(Debug, Clone, Serialize)]
+pub struct BurnInReport {
+    pub started_at: DateTime<Utc>,
+    pub until: DateTime<Utc>,
+    /// `None` while running
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ended_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    pub samples: u32,
+    pub max_cpu_percent: f64,
+    pub mean_cpu_percent: f64,
+    pub max_load_one: f64,
+    /// Time spent at `SUSTAINED_CPU_PERCENT` or more
+    pub sustained_load_seconds: u64,
+    /// Longest unbroken stretch at that load
+    pub longest_sustained_seconds: u64,
+    /// Hottest first
+    pub temperatures: Vec<SensorPeak>,
+    /// Thermal throttling events the CPUs counted, absent where they don't
+    /// report them
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub throttle_events: Option<u64>,
+    pub expected_alerts: Vec<ExpectedAlert>,
+    #[serde(skip)]
+    cpu_sum: f64,
+    #[serde(skip)]
+    last_sample: Option<(DateTime<Utc>, f64)>,
+    #[serde(skip)]
+    streak_seconds: u64,
+    /// Throttle counter when the burn-in started
+    #[serde(skip)]
+    throttle_baseline: Option<u64>,
+}
+
+impl BurnInReport {
+    pub fn new(started_at: DateTime<Utc>, until: DateTime<Utc>, reason: Option<String>) -> Self {
+        Self {
+            started_at,
+            until,
+            ended_at: None,
+            reason,
+            samples: 0,
+            max_cpu_percent: 0.0,
+            mean_cpu_percent: 0.0,
+            max_load_one: 0.0,
+            sustained_load_seconds: 0,
+            longest_sustained_seconds: 0,
+            temperatures: Vec::new(),
+            throttle_events: None,
+            expected_alerts: Vec::new(),
+            cpu_sum: 0.0,
+            last_sample: None,
+            streak_seconds: 0,
+            throttle_baseline: None,
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.ended_at.is_none()
+    }
+
+    /// Takes in a snapshot and the CPUs' cumulative throttle counter
+    pub fn observe(&mut self, host: &Host, throttle_count: Option<u64>) {
+        let cpu = host.cpu.usage_percent;
+        self.samples += 1;
+        self.cpu_sum += cpu;
+        self.mean_cpu_percent = self.cpu_sum / self.samples as f64;
+        self.max_cpu_percent = self.max_cpu_percent.max(cpu);
+        self.max_load_one = self.max_load_one.max(host.load_average.one);
+
+        match self.last_sample {
+            Some((at, previous))
+                if previous >= SUSTAINED_CPU_PERCENT && cpu >= SUSTAINED_CPU_PERCENT =>
+            {
+                let seconds = (host.timestamp - at).num_seconds().max(0) as u64;
+                self.sustained_load_seconds += seconds;
+                self.streak_seconds += seconds;
+                self.longest_sustained_seconds =
+                    self.longest_sustained_seconds.max(self.streak_seconds);
+            }
+            _ => self.streak_seconds = 0,
+        }
+        self.last_sample = Some((host.timestamp, cpu));
+
+        for temperature in &host.temperatures {
+            match self
+                .temperatures
+                .iter_mut()
+                .find(|peak| peak.label == temperature.label)
+            {
+                Some(peak) => peak.max_celsius = peak.max_celsius.max(temperature.current_celsius),
+                None => self.temperatures.push(SensorPeak {
+                    label: temperature.label.clone(),
+                    max_celsius: temperature.current_celsius,
+                    critical_celsius: temperature.critical_celsius,
+                }),
+            }
+        }
+        self.temperatures
+            .sort_by(|a, b| b.max_celsius.total_cmp(&a.max_celsius));
+
+        if let Some(count) = throttle_count {
+            let baseline = *self.throttle_baseline.get_or_insert(count);
+            self.throttle_events = Some(count.saturating_sub(baseline));
+        }
+    }
+
+    /// Records `rule` firing at `value`
+    pub fn expect_alert(&mut self, rule: &AlertRule, value: f64, at: DateTime<Utc>) {
+        match self
+            .expected_alerts
+            .iter_mut()
+            .find(|a| a.rule == rule.name)
+        {
+            Some(alert) => {
+                alert.last_at = at;
+                alert.evaluations += 1;
+                if rule.condition.evaluate(value, alert.peak_value) {
+                    alert.peak_value = value;
+                }
+            }
+            None => self.expected_alerts.push(ExpectedAlert {
+                rule: rule.name.clone(),
+                first_at: at,
+                last_at: at,
+                peak_value: value,
+                evaluations: 1,
+            }),
+        }
+    }
+
+    /// One line for the events log
+    pub fn summary(&self) -> String {
+        let mut summary = format!(
+            "max CPU {:.0}% ({}s sustained), max load {:.2}",
+            self.max_cpu_percent, self.sustained_load_seconds, self.max_load_one
+        );
+        if let Some(hottest) = self.temperatures.first() {
+            summary += &format!(
+                ", hottest {} at {:.0}°C",
+                hottest.label, hottest.max_celsius
+            );
+        }
+        if let Some(events) = self.throttle_events {
+            summary += &format!(", {} throttle events", events);
+        }
+        summary += &format!(", {} alerts expected", self.expected_alerts.len());
+        summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{
+        AlertCondition, AlertMetric, CpuMetrics, Severity, Temperature, TemperatureSource,
+    };
+    use chrono::Duration;
+
+    #[test]
+    fn test_report_tracks_sustained_load_and_peaks() {
+        let start = Utc::now();
+        let mut report = BurnInReport::new(start, start + Duration::hours(1), None);
+        for (minute, cpu, celsius, throttles) in [
+            (0, 20.0, 45.0, 100),
+            (1, 95.0, 70.0, 100),
+            (2, 100.0, 88.0, 104),
+            (3, 98.0, 91.0, 110),
+            (4, 30.0, 60.0, 110),
+            (5, 96.0, 75.0, 110),
+        ] {
+            let mut host =
+                Host::new("test".to_string()).with_timestamp(start + Duration::minutes(minute));
+            host.cpu = CpuMetrics::new(cpu, cpu, 0.0);
+            host.temperatures = vec![Temperature::new(
+                "Package id 0".to_string(),
+                TemperatureSource::Cpu,
+                celsius,
+            )];
+            report.observe(&host, Some(throttles));
+        }
+        assert_eq!(report.samples, 6);
+        assert_eq!(report.max_cpu_percent, 100.0);
+        assert_eq!(report.sustained_load_seconds, 120);
+        assert_eq!(report.longest_sustained_seconds, 120);
+        assert_eq!(report.temperatures[0].max_celsius, 91.0);
+        assert_eq!(report.throttle_events, Some(10));
+
+        let rule = AlertRule {
+            name: "cpu-temp".to_string(),
+            metric: AlertMetric::CpuUsage,
+            condition: AlertCondition::Above,
+            threshold: 85.0,
+            webhook_url: String::new(),
+            cooldown_seconds: 0,
+            severity: Severity::Warning,
+            escalation: Vec::new(),
+        };
+        report.expect_alert(&rule, 88.0, start);
+        report.expect_alert(&rule, 91.0, start + Duration::minutes(1));
+        assert_eq!(report.expected_alerts[0].evaluations, 2);
+        assert_eq!(report.expected_alerts[0].peak_value, 91.0);
+        assert!(report
+            .summary()
+            .ends_with("10 throttle events, 1 alerts expected"));
+    }
+}
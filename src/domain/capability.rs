@@ -0,0 +1,31 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+/// Whether a collector can work with the current mounts and permissions
+#[derive(Debug, Clone, Serialize)]
+pub struct Capability {
+    pub available: bool,
+    /// Why the collector is degraded or unavailable
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl Capability {
+    pub fn available() -> Self {
+        Self {
+            available: true,
+            reason: None,
+        }
+    }
+
+    pub fn unavailable(reason: impl Into<String>) -> Self {
+        Self {
+            available: false,
+            reason: Some(reason.into()),
+        }
+    }
+}
+
+/// Capabilities by collector name (`host_metrics`, `containers`, ...)
+pub type Capabilities = BTreeMap<&'static str, Capability>;
@@ -0,0 +1,117 @@
+use std::collections::HashSet;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{Container, Process};
+
+/// When the container list and the process scan of a snapshot were read,
+/// which processes' `container_id` must agree with
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CollectionEpoch {
+    /// Start of the collection both reads are measured from
+    pub started_at: DateTime<Utc>,
+    /// Milliseconds after `started_at` the container list was read, absent
+    /// when it was carried over
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub containers_ms: Option<u64>,
+    /// Same for the process scan
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub processes_ms: Option<u64>,
+    /// Times the container list was read again to resolve processes
+    #[serde(default)]
+    pub retries: u32,
+    /// Processes left pointing at a container missing from the list
+    #[serde(default)]
+    pub unresolved: usize,
+}
+
+impl CollectionEpoch {
+    pub fn new(started_at: DateTime<Utc>) -> Self {
+        Self {
+            started_at,
+            containers_ms: None,
+            processes_ms: None,
+            retries: 0,
+            unresolved: 0,
+        }
+    }
+
+    /// How far apart the two reads were, when both were fresh
+    pub fn skew_ms(&self) -> Option<u64> {
+        Some(self.containers_ms?.abs_diff(self.processes_ms?))
+    }
+}
+
+/// Processes whose container is missing from `containers`, e.g. one started
+/// between the two reads
+pub fn unresolved_processes(processes: &[Process], containers: &[Container]) -> usize {
+    let listed: HashSet<_> = containers.iter().map(|c| &c.id).collect();
+    processes
+        .iter()
+        .filter(|p| {
+            p.container_id
+                .as_ref()
+                .is_some_and(|id| !listed.contains(id))
+        })
+        .count()
+}
+
+/// Marks the processes whose container is missing from `containers`,
+/// returning how many there are
+pub fn resolve_containers(processes: &mut [Process], containers: &[Container]) -> usize {
+    let listed: HashSet<_> = containers.iter().map(|c| &c.id).collect();
+    let mut unresolved = 0;
+    for process in processes.iter_mut() {
+        process.container_unresolved = process
+            .container_id
+            .as_ref()
+            .is_some_and(|id| !listed.contains(id));
+        unresolved += usize::from(process.container_unresolved);
+    }
+    unresolved
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{ContainerId, ContainerState, ProcessState};
+
+    #[test]
+    fn test_processes_of_unlisted_containers_are_marked() {
+        let container = Container::new(
+            ContainerId::new("abc"),
+            "web".to_string(),
+            "nginx".to_string(),
+            ContainerState::Running,
+            Utc::now(),
+        );
+        let process = |pid, container: Option<&str>| {
+            Process::new(
+                pid,
+                1,
+                "root".to_string(),
+                "nginx".to_string(),
+                ProcessState::Running,
+            )
+            .with_container(container.map(ContainerId::new))
+        };
+        let mut processes = vec![
+            process(10, Some("abc")),
+            process(11, Some("def")),
+            process(12, None),
+        ];
+
+        let containers = [container];
+        assert_eq!(unresolved_processes(&processes, &containers), 1);
+        assert_eq!(resolve_containers(&mut processes, &containers), 1);
+        let marked: Vec<_> = processes.iter().map(|p| p.container_unresolved).collect();
+        assert_eq!(marked, [false, true, false]);
+
+        let mut epoch = CollectionEpoch::new(Utc::now());
+        epoch.processes_ms = Some(40);
+        assert_eq!(epoch.skew_ms(), None);
+        epoch.containers_ms = Some(15);
+        assert_eq!(epoch.skew_ms(), Some(25));
+    }
+}
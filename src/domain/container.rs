@@ -1,8 +1,11 @@
+use std::collections::HashMap;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 use super::{
-    CpuMetrics, IoMetrics, MemoryMetrics, MonitoredResource, NetworkMetrics, ResourceType,
+    CpuMetrics, IoMetrics, MemoryMetrics, MemoryTrend, MonitoredResource, NetworkMetrics,
+    ResourceType,
 };
 
 /// Unique identifier for a container
@@ -49,20 +52,81 @@ impl ContainerState {
     }
 }
 
+/// Result of a container's health check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ContainerHealth {
+    Starting,
+    Healthy,
+    Unhealthy,
+}
+
+impl ContainerHealth {
+    /// From Docker's status line, such as `Up 2 hours (unhealthy)`; `None`
+    /// for containers without a health check
+    pub fn from_status(status: &str) -> Option<Self> {
+        if status.contains("(unhealthy)") {
+            Some(Self::Unhealthy)
+        } else if status.contains("(healthy)") {
+            Some(Self::Healthy)
+        } else if status.contains("(health: starting)") {
+            Some(Self::Starting)
+        } else {
+            None
+        }
+    }
+}
+
+/// Engine a container runs under
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ContainerRuntime {
+    #[default]
+    Docker,
+    /// LXC/LXD system containers, including Proxmox CTs
+    Lxc,
+}
+
 /// Container entity
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Container {
     pub id: ContainerId,
     pub name: String,
     pub image: String,
+    #[serde(default)]
+    pub runtime: ContainerRuntime,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub stack: Option<String>, // com.docker.compose.project label
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub service: Option<String>, // com.docker.compose.service label
     pub state: ContainerState,
+    /// Absent for containers without a health check
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub health: Option<ContainerHealth>,
     pub created_at: DateTime<Utc>,
     pub cpu: CpuMetrics,
     pub memory: MemoryMetrics,
     pub network: NetworkMetrics,
     pub block_io: IoMetrics,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory_trend: Option<MemoryTrend>,
+    /// Absent for stopped containers and daemons without the pids controller
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pids: Option<ContainerPids>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ports: Vec<PublishedPort>,
+}
+
+/// A container port published on the host
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PublishedPort {
+    pub host_port: u16,
+    pub container_port: u16,
+    /// `tcp`, `udp` or `sctp`
+    pub protocol: String,
+    /// Tracked connections to `host_port`, only filled in the container detail
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub connections: Option<u64>,
 }
 
 impl Container {
@@ -77,21 +141,59 @@ impl Container {
             id,
             name,
             image,
+            runtime: ContainerRuntime::Docker,
             stack: None,
+            service: None,
             state,
+            health: None,
             created_at,
             cpu: CpuMetrics::new(0.0, 0.0, 0.0),
             memory: MemoryMetrics::new(0, 0, 0),
             network: NetworkMetrics::zero(),
             block_io: IoMetrics::zero(),
+            memory_trend: None,
+            pids: None,
+            ports: Vec::new(),
         }
     }
 
+    pub fn with_runtime(mut self, runtime: ContainerRuntime) -> Self {
+        self.runtime = runtime;
+        self
+    }
+
     pub fn with_stack(mut self, stack: Option<String>) -> Self {
         self.stack = stack;
         self
     }
 
+    pub fn with_service(mut self, service: Option<String>) -> Self {
+        self.service = service;
+        self
+    }
+
+    /// Memory the container can't give back: without the page cache, which
+    /// the kernel reclaims before a limit is hit
+    pub fn working_set_bytes(&self) -> u64 {
+        match self.memory.rss_bytes {
+            Some(rss) => rss,
+            None => self
+                .memory
+                .used_bytes
+                .saturating_sub(self.memory.cached_bytes.unwrap_or(0)),
+        }
+    }
+
+    /// Key joining a container across snapshots: its compose project, service
+    /// and name, or just the name outside compose. Unlike the ID it survives
+    /// `docker compose up -d` recreating the container.
+    pub fn identity(&self) -> String {
+        match (&self.stack, &self.service) {
+            (Some(stack), Some(service)) => format!("{}/{}/{}", stack, service, self.name),
+            _ => self.name.clone(),
+        }
+    }
+
     pub fn with_metrics(
         mut self,
         cpu: CpuMetrics,
@@ -105,6 +207,48 @@ impl Container {
         self.block_io = block_io;
         self
     }
+
+    pub fn with_memory_trend(mut self, trend: Option<MemoryTrend>) -> Self {
+        self.memory_trend = trend;
+        self
+    }
+
+    pub fn with_pids(mut self, pids: Option<ContainerPids>) -> Self {
+        self.pids = pids;
+        self
+    }
+
+    pub fn with_ports(mut self, ports: Vec<PublishedPort>) -> Self {
+        self.ports = ports;
+        self
+    }
+
+    pub fn with_health(mut self, health: Option<ContainerHealth>) -> Self {
+        self.health = health;
+        self
+    }
+}
+
+/// Tasks in a container against its `pids_limit`, the ceiling a fork bomb hits
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ContainerPids {
+    pub current: u64,
+    /// `None` when the container may create tasks up to the host's pid_max
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub usage_percent: Option<f64>,
+}
+
+impl ContainerPids {
+    pub fn new(current: u64, limit: Option<u64>) -> Self {
+        let limit = limit.filter(|l| *l > 0 && *l != u64::MAX);
+        Self {
+            current,
+            limit,
+            usage_percent: limit.map(|l| current as f64 / l as f64 * 100.0),
+        }
+    }
 }
 
 impl MonitoredResource for Container {
@@ -154,4 +298,108 @@ impl Stack {
             memory_bytes,
         }
     }
+
+    /// Stacks of `containers`, busiest first; containers outside a stack are left out
+    pub fn group(containers: &[Container]) -> Vec<Self> {
+        let mut members: HashMap<&str, Vec<Container>> = HashMap::new();
+        for container in containers {
+            if let Some(stack) = &container.stack {
+                members.entry(stack).or_default().push(container.clone());
+            }
+        }
+
+        let mut stacks: Vec<Self> = members
+            .into_iter()
+            .map(|(name, containers)| Self::from_containers(name.to_string(), &containers))
+            .collect();
+        stacks.sort_by(|a, b| b.cpu_percent.total_cmp(&a.cpu_percent));
+        stacks
+    }
+
+    /// The stack as it was in one snapshot's containers, `None` if it had none then
+    pub fn in_snapshot(name: &str, containers: &[Container]) -> Option<Self> {
+        let members: Vec<Container> = containers
+            .iter()
+            .filter(|c| c.stack.as_deref() == Some(name))
+            .cloned()
+            .collect();
+        (!members.is_empty()).then(|| Self::from_containers(name.to_string(), &members))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identity_survives_recreation() {
+        let container = |id: &str| {
+            Container::new(
+                ContainerId::new(id),
+                "blog-web-1".to_string(),
+                "nginx".to_string(),
+                ContainerState::Running,
+                Utc::now(),
+            )
+            .with_stack(Some("blog".to_string()))
+        };
+        assert_eq!(container("a1").identity(), "blog-web-1");
+
+        let (before, after) = (
+            container("a1").with_service(Some("web".to_string())),
+            container("b2").with_service(Some("web".to_string())),
+        );
+        assert_eq!(before.identity(), "blog/web/blog-web-1");
+        assert_eq!(before.identity(), after.identity());
+    }
+
+    #[test]
+    fn test_stack_in_snapshot() {
+        let container = |name: &str, stack: Option<&str>, cpu: f64| {
+            Container::new(
+                ContainerId::new(name),
+                name.to_string(),
+                "image".to_string(),
+                ContainerState::Running,
+                Utc::now(),
+            )
+            .with_stack(stack.map(str::to_string))
+            .with_metrics(
+                CpuMetrics::new(cpu, 0.0, 0.0),
+                MemoryMetrics::new(0, 0, 0),
+                NetworkMetrics::zero(),
+                IoMetrics::zero(),
+            )
+        };
+        let containers = [
+            container("web", Some("blog"), 1.5),
+            container("db", Some("blog"), 2.0),
+            container("cache", None, 9.0),
+        ];
+
+        let stack = Stack::in_snapshot("blog", &containers).unwrap();
+        assert_eq!((stack.containers_total, stack.cpu_percent), (2, 3.5));
+        assert!(Stack::in_snapshot("wiki", &containers).is_none());
+
+        let mut with_wiki = containers.to_vec();
+        with_wiki.push(container("wiki", Some("wiki"), 4.0));
+        let stacks = Stack::group(&with_wiki);
+        assert_eq!(stacks.len(), 2);
+        assert_eq!(
+            (stacks[0].name.as_str(), stacks[1].cpu_percent),
+            ("wiki", 3.5)
+        );
+    }
+
+    #[test]
+    fn test_container_pids_usage() {
+        let pids = ContainerPids::new(950, Some(1000));
+        assert_eq!(pids.usage_percent, Some(95.0));
+
+        // Docker reports an unlimited container as 0 (cgroup v2) or u64::MAX (v1)
+        for unlimited in [None, Some(0), Some(u64::MAX)] {
+            let pids = ContainerPids::new(12, unlimited);
+            assert_eq!((pids.limit, pids.usage_percent), (None, None));
+        }
+    }
 }
@@ -0,0 +1,80 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A user-defined collector from the config file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollectorConfig {
+    pub name: String,
+    #[serde(flatten)]
+    pub kind: CollectorKind,
+    #[serde(default = "default_collector_interval")]
+    pub interval_seconds: u64,
+    #[serde(default = "default_collector_timeout")]
+    pub timeout_seconds: u64,
+}
+
+fn default_collector_interval() -> u64 {
+    60
+}
+
+fn default_collector_timeout() -> u64 {
+    10
+}
+
+/// How a collector gets its metrics, selected by `type` in the config
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CollectorKind {
+    /// Runs `command` (program then arguments, no shell) and reads JSON from its stdout
+    Script { command: Vec<String> },
+    /// Calls `collect` on a configured `[[plugins]]` entry
+    Plugin { plugin: String },
+    /// Reads the `*.json` and `*.prom` files other tools drop into `directory`
+    Textfile { directory: PathBuf },
+}
+
+/// A value reported by a user-defined collector
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CustomMetric {
+    pub collector: String,
+    pub name: String,
+    pub value: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub labels: BTreeMap<String, String>,
+    pub collected_at: DateTime<Utc>,
+}
+
+impl CustomMetric {
+    /// Same name and carrying every one of `labels` (extra labels are fine)
+    pub fn matches(&self, name: &str, labels: &BTreeMap<String, String>) -> bool {
+        self.name == name && labels.iter().all(|(k, v)| self.labels.get(k) == Some(v))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collector_config_from_toml() {
+        let config: CollectorConfig = toml::from_str(
+            r#"
+            name = "ups"
+            type = "script"
+            command = ["/usr/local/bin/ups-stats", "--json"]
+            interval_seconds = 30
+            "#,
+        )
+        .unwrap();
+        let CollectorKind::Script { command } = &config.kind else {
+            panic!("expected a script collector");
+        };
+        assert_eq!(command.len(), 2);
+        assert_eq!((config.interval_seconds, config.timeout_seconds), (30, 10));
+    }
+}
@@ -1,5 +1,8 @@
 use serde::{Deserialize, Serialize};
 
+/// Usage percentage at which a filesystem is reported in the events log
+pub const DISK_WARN_PERCENT: f64 = 90.0;
+
 /// Disk entity (mount point with usage information)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Disk {
@@ -30,7 +33,6 @@ impl Disk {
         }
     }
 
-    #[allow(dead_code)]
     pub fn usage_percent(&self) -> f64 {
         if self.total_bytes == 0 {
             return 0.0;
@@ -0,0 +1,113 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{AlertIncident, Host, RebootReport};
+
+/// Kind of event recorded in the events log
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    MemoryLeak,
+    Reboot,
+    RebootReport,
+    HeartbeatMissed,
+    HeartbeatRecovered,
+    BackupStale,
+    BackupRecovered,
+    PowerAction,
+    ProcessAction,
+    SshBruteForce,
+    /// An address locked out of the API after repeated bad tokens
+    ApiLockout,
+    KernelLimit,
+    HostDown,
+    HostRecovered,
+    /// A collector paused or resumed
+    Maintenance,
+    ContainerStarted,
+    /// A running container stopped, or went away
+    ContainerStopped,
+    /// A filesystem filled past the warning threshold
+    DiskThreshold,
+    /// A `[[reactions]]` command ran for an alert
+    Reaction,
+    /// The supervisor restarted a container, or gave up on one
+    ContainerRestart,
+    /// A burn-in started or ended
+    BurnIn,
+}
+
+/// A notable occurrence detected by NanoMon (leak suspicion, reboot, ...)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Event {
+    pub kind: EventKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resource: Option<String>,
+    pub message: String,
+    pub timestamp: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reboot_report: Option<RebootReport>,
+}
+
+impl Event {
+    pub fn new(kind: EventKind, message: String) -> Self {
+        Self {
+            kind,
+            resource: None,
+            message,
+            timestamp: Utc::now(),
+            reboot_report: None,
+        }
+    }
+
+    pub fn with_resource(mut self, resource: impl Into<String>) -> Self {
+        self.resource = Some(resource.into());
+        self
+    }
+
+    pub fn with_reboot_report(mut self, report: RebootReport) -> Self {
+        self.reboot_report = Some(report);
+        self
+    }
+}
+
+/// A webhook receiving events of the log as they are recorded, from
+/// `[[event_webhooks]]` in the alert config
+#[derive(Debug, Clone, Deserialize)]
+pub struct EventSubscription {
+    pub url: String,
+    /// Every kind when empty
+    #[serde(default)]
+    pub kinds: Vec<EventKind>,
+    /// Key for the `X-NanoMon-Signature` HMAC-SHA256 of each payload
+    #[serde(default)]
+    pub secret: Option<String>,
+}
+
+impl EventSubscription {
+    /// Every event to `url`, unsigned
+    pub fn all(url: String) -> Self {
+        Self {
+            url,
+            kinds: Vec::new(),
+            secret: None,
+        }
+    }
+
+    pub fn matches(&self, event: &Event) -> bool {
+        self.kinds.is_empty() || self.kinds.contains(&event.kind)
+    }
+}
+
+/// What one subsystem tells the others over the event bus
+#[derive(Debug, Clone)]
+pub enum DomainEvent {
+    /// A collected snapshot was stored
+    Snapshot(Arc<Host>),
+    /// An entry was added to the events log
+    Recorded(Event),
+    /// An alert incident opened, or resolved once `resolved_at` is set
+    Alert(AlertIncident),
+}
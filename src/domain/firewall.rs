@@ -0,0 +1,192 @@
+use std::collections::BTreeSet;
+use std::net::IpAddr;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Well-known administration ports worth flagging when reachable
+pub const MANAGEMENT_PORTS: &[(u16, &str)] = &[
+    (22, "ssh"),
+    (23, "telnet"),
+    (2375, "docker-api"),
+    (2376, "docker-api-tls"),
+    (3389, "rdp"),
+    (5000, "synology-dsm"),
+    (5001, "synology-dsm-tls"),
+    (5900, "vnc"),
+    (8006, "proxmox"),
+    (9000, "portainer"),
+    (9090, "cockpit"),
+    (9443, "portainer-tls"),
+    (10000, "webmin"),
+];
+
+/// A chain (nftables) or built-in/user chain (iptables)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FirewallChain {
+    pub table: String,
+    pub name: String,
+    /// Netfilter hook (`input`, `forward`, ...), `None` for regular chains
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hook: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub policy: Option<String>,
+    pub rules: usize,
+}
+
+/// Ruleset as read from nftables or iptables
+#[derive(Debug, Clone, Default)]
+pub struct FirewallRuleset {
+    pub backend: String,
+    pub chains: Vec<FirewallChain>,
+    /// Destination ports referenced by any input rule
+    pub input_ports: BTreeSet<u16>,
+}
+
+/// A TCP socket in LISTEN state
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ListeningSocket {
+    pub address: IpAddr,
+    pub port: u16,
+}
+
+/// A listening management port and whether it looks reachable
+#[derive(Debug, Clone, Serialize)]
+pub struct ManagementPort {
+    pub port: u16,
+    pub service: String,
+    pub addresses: Vec<String>,
+    /// Heuristic: open policy without a rule for the port, or closed policy with one
+    pub exposed: bool,
+}
+
+/// Packet/byte counter of a commented nftables rule or a named counter object
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RuleCounter {
+    pub table: String,
+    /// `None` for named counter objects, which any rule of the table can feed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chain: Option<String>,
+    /// Rule comment or counter object name
+    pub name: String,
+    pub packets: u64,
+    pub bytes: u64,
+}
+
+impl RuleCounter {
+    pub fn key(&self) -> (&str, Option<&str>, &str) {
+        (&self.table, self.chain.as_deref(), &self.name)
+    }
+}
+
+/// Current totals of a rule counter and its hit rate between samples
+#[derive(Debug, Clone, Serialize)]
+pub struct RuleCounterSeries {
+    #[serde(flatten)]
+    pub counter: RuleCounter,
+    pub timestamps: Vec<DateTime<Utc>>,
+    pub packets_per_second: Vec<f64>,
+    pub bytes_per_second: Vec<f64>,
+}
+
+/// "Is this box accidentally wide open" overview
+#[derive(Debug, Clone, Serialize)]
+pub struct FirewallSummary {
+    pub backend: String,
+    pub total_rules: usize,
+    /// `accept` or `drop`; `accept` when no input chain exists
+    pub input_policy: String,
+    /// No input filtering at all
+    pub wide_open: bool,
+    pub management_ports: Vec<ManagementPort>,
+    pub chains: Vec<FirewallChain>,
+}
+
+impl FirewallSummary {
+    pub fn build(ruleset: FirewallRuleset, sockets: &[ListeningSocket]) -> Self {
+        let input_chains: Vec<&FirewallChain> = ruleset
+            .chains
+            .iter()
+            .filter(|c| c.hook.as_deref() == Some("input"))
+            .collect();
+
+        // Traffic must pass every input chain, one dropping chain closes the door
+        let closed = input_chains
+            .iter()
+            .any(|c| c.policy.as_deref() == Some("drop"));
+        let input_rules: usize = input_chains.iter().map(|c| c.rules).sum();
+
+        let management_ports = MANAGEMENT_PORTS
+            .iter()
+            .filter_map(|&(port, service)| {
+                let mut addresses: Vec<String> = sockets
+                    .iter()
+                    .filter(|s| s.port == port && !s.address.is_loopback())
+                    .map(|s| s.address.to_string())
+                    .collect();
+                if addresses.is_empty() {
+                    return None;
+                }
+                addresses.sort();
+                addresses.dedup();
+                let referenced = ruleset.input_ports.contains(&port);
+                Some(ManagementPort {
+                    port,
+                    service: service.to_string(),
+                    addresses,
+                    exposed: closed == referenced,
+                })
+            })
+            .collect();
+
+        Self {
+            backend: ruleset.backend,
+            total_rules: ruleset.chains.iter().map(|c| c.rules).sum(),
+            input_policy: if closed { "drop" } else { "accept" }.to_string(),
+            wide_open: !closed && input_rules == 0,
+            management_ports,
+            chains: ruleset.chains,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn socket(address: &str, port: u16) -> ListeningSocket {
+        ListeningSocket {
+            address: address.parse().unwrap(),
+            port,
+        }
+    }
+
+    #[test]
+    fn test_build_flags_reachable_management_ports() {
+        let ruleset = FirewallRuleset {
+            backend: "nftables".to_string(),
+            chains: vec![FirewallChain {
+                table: "inet filter".to_string(),
+                name: "input".to_string(),
+                hook: Some("input".to_string()),
+                policy: Some("drop".to_string()),
+                rules: 3,
+            }],
+            input_ports: [22].into_iter().collect(),
+        };
+        let sockets = [
+            socket("0.0.0.0", 22),
+            socket("0.0.0.0", 9090),
+            socket("127.0.0.1", 2375),
+        ];
+
+        let summary = FirewallSummary::build(ruleset, &sockets);
+        assert_eq!(summary.input_policy, "drop");
+        assert!(!summary.wide_open);
+        assert_eq!(summary.management_ports.len(), 2);
+        // Explicitly allowed through the drop policy
+        assert!(summary.management_ports[0].exposed);
+        // Listening but dropped by policy
+        assert!(!summary.management_ports[1].exposed);
+    }
+}
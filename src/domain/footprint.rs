@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+
+use super::Collector;
+
+/// NanoMon's own resident memory, compared against its budget
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryFootprint {
+    pub rss_bytes: u64,
+    /// Highest RSS since start (VmHWM)
+    pub peak_rss_bytes: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub budget_bytes: Option<u64>,
+    pub over_budget: bool,
+}
+
+impl MemoryFootprint {
+    pub fn new(rss_bytes: u64, peak_rss_bytes: u64) -> Self {
+        Self {
+            rss_bytes,
+            peak_rss_bytes,
+            budget_bytes: None,
+            over_budget: false,
+        }
+    }
+
+    pub fn with_budget(mut self, budget_bytes: Option<u64>) -> Self {
+        self.budget_bytes = budget_bytes;
+        self.over_budget = budget_bytes.is_some_and(|b| self.rss_bytes > b);
+        self
+    }
+}
+
+/// Running cost of one collector and how often the collection budget lets it run
+#[derive(Debug, Clone, Serialize)]
+pub struct CollectorCost {
+    pub collector: Collector,
+    pub cost_ms: f64,
+    /// 1 when collected every poll
+    pub every_polls: u32,
+}
+
+/// Time NanoMon may spend collecting per poll, and what it spends
+#[derive(Debug, Clone, Serialize)]
+pub struct CollectionCost {
+    pub budget_ms: f64,
+    /// Sum of each collector's cost spread over the polls it runs on
+    pub amortized_ms: f64,
+    pub collectors: Vec<CollectorCost>,
+}
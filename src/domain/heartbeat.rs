@@ -0,0 +1,126 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A job expected to ping NanoMon at a regular interval (dead man's switch)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeartbeatCheck {
+    pub name: String,
+    pub interval_seconds: u64,
+    #[serde(default = "default_grace")]
+    pub grace_seconds: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhook_url: Option<String>,
+    /// Secret pings must carry, as a bearer token or `?token=`
+    #[serde(default, skip_serializing)]
+    pub token: Option<String>,
+}
+
+fn default_grace() -> u64 {
+    60
+}
+
+impl HeartbeatCheck {
+    /// Whether a ping carrying `token` counts: it must match the check's own
+    /// token or `admin_token`, so a check without one only takes the admin's
+    pub fn accepts(&self, token: Option<&str>, admin_token: Option<&str>) -> bool {
+        token.is_some() && (token == self.token.as_deref() || token == admin_token)
+    }
+
+    /// Evaluate the check given the last ping (or, if never pinged, when watching started)
+    pub fn state_at(
+        &self,
+        last_ping: Option<DateTime<Utc>>,
+        watching_since: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> HeartbeatState {
+        let reference = last_ping.unwrap_or(watching_since);
+        let elapsed = now.signed_duration_since(reference).num_seconds();
+        let deadline = (self.interval_seconds + self.grace_seconds) as i64;
+
+        if elapsed > deadline {
+            HeartbeatState::Down
+        } else if last_ping.is_none() {
+            HeartbeatState::New
+        } else if elapsed > self.interval_seconds as i64 {
+            HeartbeatState::Late
+        } else {
+            HeartbeatState::Up
+        }
+    }
+}
+
+/// State of a heartbeat check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HeartbeatState {
+    /// Never pinged, still within the first interval
+    New,
+    Up,
+    /// Past the interval but within the grace period
+    Late,
+    Down,
+}
+
+/// Current status of a heartbeat check as exposed by the API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeartbeatStatus {
+    pub name: String,
+    pub interval_seconds: u64,
+    pub grace_seconds: u64,
+    pub state: HeartbeatState,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_ping: Option<DateTime<Utc>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heartbeat_states() {
+        let check = HeartbeatCheck {
+            name: "backup".to_string(),
+            interval_seconds: 3600,
+            grace_seconds: 300,
+            webhook_url: None,
+            token: None,
+        };
+        let start = Utc::now();
+        let at = |secs: i64| start + chrono::Duration::seconds(secs);
+
+        assert_eq!(check.state_at(None, start, at(10)), HeartbeatState::New);
+        assert_eq!(check.state_at(None, start, at(4000)), HeartbeatState::Down);
+        assert_eq!(
+            check.state_at(Some(at(0)), start, at(100)),
+            HeartbeatState::Up
+        );
+        assert_eq!(
+            check.state_at(Some(at(0)), start, at(3700)),
+            HeartbeatState::Late
+        );
+        assert_eq!(
+            check.state_at(Some(at(0)), start, at(3901)),
+            HeartbeatState::Down
+        );
+    }
+
+    #[test]
+    fn test_pings_need_the_check_or_admin_token() {
+        let mut check = HeartbeatCheck {
+            name: "backup".to_string(),
+            interval_seconds: 3600,
+            grace_seconds: 300,
+            webhook_url: None,
+            token: None,
+        };
+        assert!(!check.accepts(None, None));
+        assert!(!check.accepts(Some("guess"), None));
+        assert!(check.accepts(Some("admin"), Some("admin")));
+
+        check.token = Some("s3cret".to_string());
+        assert!(!check.accepts(None, Some("admin")));
+        assert!(!check.accepts(Some("guess"), Some("admin")));
+        assert!(check.accepts(Some("s3cret"), Some("admin")));
+        assert!(check.accepts(Some("admin"), Some("admin")));
+    }
+}
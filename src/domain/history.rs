@@ -0,0 +1,137 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::Host;
+
+/// Run of missed collections between two stored snapshots
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SequenceGap {
+    /// Last sequence number stored before the gap
+    pub after_sequence: u64,
+    /// Number of collections that produced no snapshot
+    pub missing: u64,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+}
+
+impl SequenceGap {
+    /// Find holes in `(sequence, timestamp)` samples (oldest first)
+    pub fn find(samples: &[(u64, DateTime<Utc>)]) -> Vec<Self> {
+        samples
+            .windows(2)
+            .filter_map(|pair| {
+                let ((prev_seq, prev_ts), (seq, ts)) = (pair[0], pair[1]);
+                // A lower sequence means the collector restarted, not a gap
+                (seq > prev_seq + 1).then(|| Self {
+                    after_sequence: prev_seq,
+                    missing: seq - prev_seq - 1,
+                    from: prev_ts,
+                    to: ts,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Host CPU, memory and load of one chart point
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryPoint {
+    pub timestamp: DateTime<Utc>,
+    pub sequence: Option<u64>,
+    pub cpu_percent: f64,
+    pub memory_used_bytes: u64,
+    pub load_1: f64,
+    pub load_5: f64,
+    pub load_15: f64,
+}
+
+impl HistoryPoint {
+    pub fn from_host(host: &Host) -> Self {
+        Self {
+            timestamp: host.timestamp,
+            sequence: host.sequence,
+            cpu_percent: host.cpu.usage_percent,
+            memory_used_bytes: host.memory.used_bytes,
+            load_1: host.load_average.one,
+            load_5: host.load_average.five,
+            load_15: host.load_average.fifteen,
+        }
+    }
+
+    /// `history` (oldest first) averaged over buckets of `resolution`, aligned
+    /// on the epoch so points stay put between refreshes; each point has the
+    /// timestamp and sequence of its bucket's last snapshot
+    pub fn downsample(history: &[Arc<Host>], resolution: Duration) -> Vec<Self> {
+        let step = resolution.as_secs().max(1) as i64;
+        history
+            .chunk_by(|a, b| {
+                a.timestamp.timestamp().div_euclid(step) == b.timestamp.timestamp().div_euclid(step)
+            })
+            .map(|bucket| {
+                let count = bucket.len() as f64;
+                let mean =
+                    |value: fn(&Host) -> f64| bucket.iter().map(|h| value(h)).sum::<f64>() / count;
+                let last = &bucket[bucket.len() - 1];
+                Self {
+                    timestamp: last.timestamp,
+                    sequence: last.sequence,
+                    cpu_percent: mean(|h| h.cpu.usage_percent),
+                    memory_used_bytes: mean(|h| h.memory.used_bytes as f64) as u64,
+                    load_1: mean(|h| h.load_average.one),
+                    load_5: mean(|h| h.load_average.five),
+                    load_15: mean(|h| h.load_average.fifteen),
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::CpuMetrics;
+
+    #[test]
+    fn test_find_gaps() {
+        let t = |s: i64| DateTime::from_timestamp(s, 0).unwrap();
+        let samples = [(1, t(0)), (2, t(5)), (5, t(20)), (6, t(25)), (1, t(30))];
+
+        let gaps = SequenceGap::find(&samples);
+        assert_eq!(
+            gaps,
+            vec![SequenceGap {
+                after_sequence: 2,
+                missing: 2,
+                from: t(5),
+                to: t(20),
+            }]
+        );
+        assert!(SequenceGap::find(&samples[..2]).is_empty());
+    }
+
+    #[test]
+    fn test_downsample_averages_buckets() {
+        let t = |s: i64| DateTime::from_timestamp(s, 0).unwrap();
+        let history: Vec<Arc<Host>> = [(0, 10.0), (10, 20.0), (20, 30.0), (30, 50.0), (40, 70.0)]
+            .into_iter()
+            .enumerate()
+            .map(|(index, (seconds, cpu))| {
+                let mut host = Host::new("test".to_string()).with_timestamp(t(seconds));
+                host.cpu = CpuMetrics::new(cpu, 0.0, 0.0);
+                Arc::new(host.with_sequence(index as u64 + 1))
+            })
+            .collect();
+
+        let points = HistoryPoint::downsample(&history, Duration::from_secs(30));
+        assert_eq!(points.len(), 2);
+        assert_eq!((points[0].timestamp, points[0].cpu_percent), (t(20), 20.0));
+        assert_eq!((points[1].sequence, points[1].cpu_percent), (Some(5), 60.0));
+        assert_eq!(
+            HistoryPoint::downsample(&history, Duration::from_secs(5)).len(),
+            5
+        );
+    }
+}
@@ -2,8 +2,9 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 use super::{
-    Container, CpuMetrics, Disk, LoadAverage, MemoryMetrics, MonitoredResource, NetworkInterface,
-    Process, ResourceType, Temperature,
+    Annotation, CollectionEpoch, Collector, Container, CpuMetrics, CustomMetric, Disk,
+    KernelLimits, LoadAverage, MemoryMetrics, MonitoredResource, NetworkInterface, Process,
+    ResourceType, Temperature,
 };
 
 /// Host aggregate root
@@ -19,6 +20,25 @@ pub struct Host {
     pub containers: Vec<Container>,
     pub processes: Vec<Process>,
     pub temperatures: Vec<Temperature>,
+    #[serde(default)]
+    pub kernel_limits: KernelLimits,
+    /// Latest values of user-defined collectors
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub custom_metrics: Vec<CustomMetric>,
+    /// Poll number of stored snapshots, incremented on every attempt (failed ones leave a gap)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sequence: Option<u64>,
+    /// Collectors paused for maintenance or skipped by the collection budget, whose
+    /// data is carried over from an earlier snapshot
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stale: Vec<Collector>,
+    /// Notes recorded since the previous snapshot, kept with history
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub annotations: Vec<Annotation>,
+    /// When containers and processes were read, absent for pushed snapshots
+    /// of older agents
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub epoch: Option<CollectionEpoch>,
     pub timestamp: DateTime<Utc>,
 }
 
@@ -35,6 +55,12 @@ impl Host {
             containers: Vec::new(),
             processes: Vec::new(),
             temperatures: Vec::new(),
+            kernel_limits: KernelLimits::default(),
+            custom_metrics: Vec::new(),
+            sequence: None,
+            stale: Vec::new(),
+            annotations: Vec::new(),
+            epoch: None,
             timestamp: Utc::now(),
         }
     }
@@ -78,6 +104,31 @@ impl Host {
         self
     }
 
+    pub fn with_kernel_limits(mut self, kernel_limits: KernelLimits) -> Self {
+        self.kernel_limits = kernel_limits;
+        self
+    }
+
+    pub fn with_custom_metrics(mut self, custom_metrics: Vec<CustomMetric>) -> Self {
+        self.custom_metrics = custom_metrics;
+        self
+    }
+
+    pub fn with_stale(mut self, stale: Vec<Collector>) -> Self {
+        self.stale = stale;
+        self
+    }
+
+    pub fn with_epoch(mut self, epoch: CollectionEpoch) -> Self {
+        self.epoch = Some(epoch);
+        self
+    }
+
+    pub fn with_sequence(mut self, sequence: u64) -> Self {
+        self.sequence = Some(sequence);
+        self
+    }
+
     pub fn with_timestamp(mut self, timestamp: DateTime<Utc>) -> Self {
         self.timestamp = timestamp;
         self
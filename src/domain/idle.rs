@@ -0,0 +1,157 @@
+use std::sync::Arc;
+
+use serde::Serialize;
+
+use super::Host;
+
+/// Mean CPU usage below which a container counts as doing nothing
+pub const IDLE_CPU_PERCENT: f64 = 0.5;
+
+/// Network and block IO traffic allowed per day of an idle container, each
+const IDLE_BYTES_PER_DAY: f64 = 10.0 * 1024.0 * 1024.0;
+
+/// Containers seen for less than this are not judged
+const MIN_OBSERVED_SECONDS: i64 = 3600;
+
+/// A running container that barely did anything over the history looked at,
+/// and what it holds while doing so
+#[derive(Debug, Clone, Serialize)]
+pub struct IdleContainer {
+    pub name: String,
+    /// `Container::identity`, stable across recreations
+    pub identity: String,
+    /// Span of history the container was seen in
+    pub observed_seconds: u64,
+    pub mean_cpu_percent: f64,
+    pub peak_cpu_percent: f64,
+    /// Received plus sent over `observed_seconds`
+    pub network_bytes: u64,
+    /// Read plus written over `observed_seconds`
+    pub block_io_bytes: u64,
+    /// Memory it would give back if stopped
+    pub memory_bytes: u64,
+}
+
+/// Running containers of the latest snapshot whose CPU, network and IO stayed
+/// near zero across `history` (oldest first), most memory first
+pub fn idle_containers(history: &[Arc<Host>]) -> Vec<IdleContainer> {
+    let Some(latest) = history.last() else {
+        return Vec::new();
+    };
+    let mut idle = Vec::new();
+
+    for container in latest.containers.iter().filter(|c| c.state.is_running()) {
+        let identity = container.identity();
+        let samples: Vec<_> = history
+            .iter()
+            .filter_map(|host| {
+                host.containers
+                    .iter()
+                    .find(|c| c.state.is_running() && c.identity() == identity)
+                    .map(|c| (host.timestamp, c))
+            })
+            .collect();
+        let observed = (latest.timestamp - samples[0].0).num_seconds();
+        if observed < MIN_OBSERVED_SECONDS {
+            continue;
+        }
+
+        let (mut network_bytes, mut block_io_bytes) = (0, 0);
+        for pair in samples.windows(2) {
+            let (before, after) = (pair[0].1, pair[1].1);
+            network_bytes += counter_delta(
+                before.network.rx_bytes + before.network.tx_bytes,
+                after.network.rx_bytes + after.network.tx_bytes,
+            );
+            block_io_bytes += counter_delta(
+                before.block_io.read_bytes + before.block_io.write_bytes,
+                after.block_io.read_bytes + after.block_io.write_bytes,
+            );
+        }
+        let cpu = samples.iter().map(|(_, c)| c.cpu.usage_percent);
+        let mean_cpu_percent = cpu.clone().sum::<f64>() / samples.len() as f64;
+        let peak_cpu_percent = cpu.fold(0.0, f64::max);
+
+        let allowed_bytes = IDLE_BYTES_PER_DAY * observed as f64 / 86400.0;
+        if mean_cpu_percent < IDLE_CPU_PERCENT
+            && (network_bytes as f64) < allowed_bytes
+            && (block_io_bytes as f64) < allowed_bytes
+        {
+            idle.push(IdleContainer {
+                name: container.name.clone(),
+                identity,
+                observed_seconds: observed as u64,
+                mean_cpu_percent,
+                peak_cpu_percent,
+                network_bytes,
+                block_io_bytes,
+                memory_bytes: container.memory.used_bytes,
+            });
+        }
+    }
+    idle.sort_by_key(|c| std::cmp::Reverse(c.memory_bytes));
+    idle
+}
+
+/// Growth of a cumulative counter; one that went down was reset by a
+/// restart and counts from zero
+pub(crate) fn counter_delta(before: u64, after: u64) -> u64 {
+    if after >= before {
+        after - before
+    } else {
+        after
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{
+        Container, ContainerId, ContainerState, CpuMetrics, MemoryMetrics, NetworkMetrics,
+    };
+    use chrono::{Duration, Utc};
+
+    fn container(name: &str, cpu: f64, rx_bytes: u64) -> Container {
+        let mut container = Container::new(
+            ContainerId::new(name.to_string()),
+            name.to_string(),
+            "image".to_string(),
+            ContainerState::Running,
+            Utc::now(),
+        );
+        container.cpu = CpuMetrics::new(cpu, cpu, 0.0);
+        container.memory = MemoryMetrics::new(512 * 1024 * 1024, 0, 0);
+        container.network = NetworkMetrics::new(rx_bytes, 0, 0, 0);
+        container
+    }
+
+    #[test]
+    fn test_idle_containers_need_quiet_history() {
+        let start = Utc::now() - Duration::hours(2);
+        let history: Vec<Arc<Host>> = (0..3)
+            .map(|hour| {
+                let mut host =
+                    Host::new("test".to_string()).with_timestamp(start + Duration::hours(hour));
+                host.containers = vec![
+                    container("wiki", 0.1, 1000 * hour as u64),
+                    container("proxy", 0.2, 500_000_000 * hour as u64),
+                    container("worker", if hour == 1 { 40.0 } else { 0.0 }, 0),
+                ];
+                if hour == 2 {
+                    // Restarted: its counters start over
+                    host.containers[0].network.rx_bytes = 10;
+                    host.containers.push(container("new", 0.0, 0));
+                }
+                Arc::new(host)
+            })
+            .collect();
+
+        let idle = idle_containers(&history);
+        assert_eq!(idle.len(), 1);
+        assert_eq!(idle[0].name, "wiki");
+        assert_eq!(idle[0].observed_seconds, 7200);
+        assert_eq!(idle[0].network_bytes, 1010);
+        assert_eq!(idle[0].memory_bytes, 512 * 1024 * 1024);
+        assert!(idle_containers(&history[..1]).is_empty());
+    }
+}
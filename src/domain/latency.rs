@@ -0,0 +1,44 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A host to ping continuously (gateway, public resolver, ISP hop...)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PingTarget {
+    pub name: String,
+    pub host: String,
+}
+
+/// Result of one probe round against a target
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PingSample {
+    pub timestamp: DateTime<Utc>,
+    pub sent: u32,
+    pub received: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_ms: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub avg_ms: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_ms: Option<f64>,
+}
+
+impl PingSample {
+    /// A round where nothing could be sent (resolution failure, no route...)
+    pub fn unreachable(sent: u32) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            sent,
+            received: 0,
+            min_ms: None,
+            avg_ms: None,
+            max_ms: None,
+        }
+    }
+
+    pub fn loss_percent(&self) -> f64 {
+        if self.sent == 0 {
+            return 100.0;
+        }
+        (self.sent.saturating_sub(self.received)) as f64 / self.sent as f64 * 100.0
+    }
+}
@@ -0,0 +1,61 @@
+use serde::{Deserialize, Serialize};
+
+/// Usage above which a kernel limit is reported as under pressure
+pub const KERNEL_LIMIT_WARN_PERCENT: f64 = 80.0;
+
+/// Usage of a kernel table with a fixed maximum
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KernelLimit {
+    pub used: u64,
+    pub max: u64,
+    pub usage_percent: f64,
+}
+
+impl KernelLimit {
+    pub fn new(used: u64, max: u64) -> Self {
+        let usage_percent = if max == 0 {
+            0.0
+        } else {
+            used as f64 / max as f64 * 100.0
+        };
+        Self {
+            used,
+            max,
+            usage_percent,
+        }
+    }
+}
+
+/// Kernel limits whose exhaustion fails silently (EMFILE, fork failures, dropped NAT flows)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct KernelLimits {
+    /// Allocated file handles vs fs.file-max
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_handles: Option<KernelLimit>,
+    /// Threads vs kernel.pid_max
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pids: Option<KernelLimit>,
+    /// Tracked connections vs nf_conntrack_max (absent without netfilter conntrack)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conntrack: Option<KernelLimit>,
+    /// Informational: available entropy in bits
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entropy_bits: Option<u64>,
+}
+
+impl KernelLimits {
+    /// All present limits by name
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, &KernelLimit)> {
+        [
+            ("file_handles", &self.file_handles),
+            ("pids", &self.pids),
+            ("conntrack", &self.conntrack),
+        ]
+        .into_iter()
+        .filter_map(|(name, limit)| limit.as_ref().map(|l| (name, l)))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&KernelLimit> {
+        self.iter().find(|(n, _)| *n == name).map(|(_, l)| l)
+    }
+}
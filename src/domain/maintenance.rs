@@ -0,0 +1,35 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A collector that can be paused for maintenance
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Collector {
+    /// The container engine, e.g. during an upgrade
+    Containers,
+    /// Mounts and filesystem usage, e.g. during a RAID rebuild
+    Disks,
+    Network,
+    Processes,
+}
+
+impl Collector {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Containers => "containers",
+            Self::Disks => "disks",
+            Self::Network => "network",
+            Self::Processes => "processes",
+        }
+    }
+}
+
+/// A collector paused until `until`, after which it resumes on its own
+#[derive(Debug, Clone, Serialize)]
+pub struct MaintenanceWindow {
+    pub collector: Collector,
+    pub started_at: DateTime<Utc>,
+    pub until: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
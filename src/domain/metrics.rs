@@ -32,10 +32,14 @@ pub struct MemoryMetrics {
     pub used_bytes: u64,
     pub total_bytes: u64,
     pub available_bytes: u64,
+    /// Anonymous memory (heap, stacks), what the processes actually hold
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rss_bytes: Option<u64>,
+    /// Page cache, reclaimable under pressure; a container's `used_bytes` includes it
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub cached_bytes: Option<u64>, // host only
+    pub cached_bytes: Option<u64>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub swap_used_bytes: Option<u64>, // host only
+    pub swap_used_bytes: Option<u64>,
 }
 
 impl MemoryMetrics {
@@ -44,11 +48,17 @@ impl MemoryMetrics {
             used_bytes,
             total_bytes,
             available_bytes,
+            rss_bytes: None,
             cached_bytes: None,
             swap_used_bytes: None,
         }
     }
 
+    pub fn with_rss(mut self, rss_bytes: u64) -> Self {
+        self.rss_bytes = Some(rss_bytes);
+        self
+    }
+
     pub fn with_cache(mut self, cached_bytes: u64) -> Self {
         self.cached_bytes = Some(cached_bytes);
         self
@@ -59,7 +69,6 @@ impl MemoryMetrics {
         self
     }
 
-    #[allow(dead_code)]
     pub fn usage_percent(&self) -> f64 {
         if self.total_bytes == 0 {
             return 0.0;
@@ -98,6 +107,13 @@ pub struct NetworkMetrics {
     pub tx_bytes: u64,
     pub rx_errors: u64,
     pub tx_errors: u64,
+    /// Packets dropped without an error, e.g. on a full ring buffer
+    #[serde(default)]
+    pub rx_dropped: u64,
+    #[serde(default)]
+    pub tx_dropped: u64,
+    #[serde(default)]
+    pub collisions: u64,
 }
 
 impl NetworkMetrics {
@@ -107,16 +123,25 @@ impl NetworkMetrics {
             tx_bytes,
             rx_errors,
             tx_errors,
+            rx_dropped: 0,
+            tx_dropped: 0,
+            collisions: 0,
         }
     }
 
+    pub fn with_drops(mut self, rx_dropped: u64, tx_dropped: u64) -> Self {
+        self.rx_dropped = rx_dropped;
+        self.tx_dropped = tx_dropped;
+        self
+    }
+
+    pub fn with_collisions(mut self, collisions: u64) -> Self {
+        self.collisions = collisions;
+        self
+    }
+
     pub fn zero() -> Self {
-        Self {
-            rx_bytes: 0,
-            tx_bytes: 0,
-            rx_errors: 0,
-            tx_errors: 0,
-        }
+        Self::new(0, 0, 0, 0)
     }
 }
 
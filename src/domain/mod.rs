@@ -1,21 +1,109 @@
 pub mod alert;
+pub mod annotation;
+pub mod api_auth;
+pub mod backup;
+pub mod burnin;
+pub mod capability;
+pub mod consistency;
 pub mod container;
+pub mod custom;
 pub mod disk;
+pub mod event;
+pub mod firewall;
+pub mod footprint;
+pub mod heartbeat;
+pub mod history;
 pub mod host;
+pub mod idle;
+pub mod latency;
+pub mod limits;
+pub mod maintenance;
 pub mod metrics;
+pub mod nagios;
 pub mod network;
+pub mod notify;
+pub mod numa;
+pub mod plugin;
+pub mod power;
 pub mod process;
+pub mod quota;
+pub mod reaction;
+pub mod reboot;
+pub mod reclaim;
 pub mod resource;
+pub mod security;
 pub mod service;
+pub mod speedtest;
+pub mod spike;
+pub mod ssh;
+pub mod supervisor;
 pub mod temperature;
+pub mod time;
+pub mod top;
+pub mod topology;
+pub mod trend;
+pub mod viewer;
+pub mod vm;
+pub mod volume;
 
-pub use alert::{AlertEvent, AlertMetric, AlertRule};
-pub use container::{Container, ContainerId, ContainerState, Stack};
-pub use disk::Disk;
+pub use alert::{
+    default_cooldown, AlertCondition, AlertDryRun, AlertEvent, AlertIncident, AlertMetric,
+    AlertRule, AlertStats, EscalationStep,
+};
+pub use annotation::Annotation;
+pub use api_auth::{ApiAuthSummary, ApiLockout};
+pub use backup::{BackupArtifact, BackupJob, BackupKind, BackupStatus};
+pub use burnin::{BurnInReport, ExpectedAlert, SensorPeak, SUSTAINED_CPU_PERCENT};
+pub use capability::{Capabilities, Capability};
+pub use consistency::{resolve_containers, unresolved_processes, CollectionEpoch};
+pub use container::{
+    Container, ContainerHealth, ContainerId, ContainerPids, ContainerRuntime, ContainerState,
+    PublishedPort, Stack,
+};
+pub use custom::{CollectorConfig, CollectorKind, CustomMetric};
+pub use disk::{Disk, DISK_WARN_PERCENT};
+pub use event::{DomainEvent, Event, EventKind, EventSubscription};
+pub use firewall::{
+    FirewallChain, FirewallRuleset, FirewallSummary, ListeningSocket, RuleCounter,
+    RuleCounterSeries,
+};
+pub use footprint::{CollectionCost, CollectorCost, MemoryFootprint};
+pub use heartbeat::{HeartbeatCheck, HeartbeatState, HeartbeatStatus};
+pub use history::{HistoryPoint, SequenceGap};
 pub use host::Host;
+pub use idle::{idle_containers, IdleContainer};
+pub use latency::{PingSample, PingTarget};
+pub use limits::{KernelLimit, KernelLimits, KERNEL_LIMIT_WARN_PERCENT};
+pub use maintenance::{Collector, MaintenanceWindow};
 pub use metrics::{CpuMetrics, IoMetrics, LoadAverage, MemoryMetrics, NetworkMetrics};
-pub use network::NetworkInterface;
-pub use process::{Process, ProcessState};
+pub use nagios::{CheckResult, NagiosCheck};
+pub use network::{NetworkInterface, QueueStats, WirelessLink};
+pub use notify::{NotificationRoute, Severity};
+pub use numa::{HugePages, MemoryTopology, NumaNode};
+pub use plugin::{PluginConfig, PLUGIN_PROTOCOL_VERSION, PLUGIN_URL_SCHEME};
+pub use power::{magic_packet, parse_mac, PowerAction, PowerTarget};
+pub use process::{
+    CommandSanitizer, Process, ProcessFilter, ProcessOrder, ProcessSampling, ProcessScanStats,
+    ProcessState, SchedulingPolicy, DEFAULT_REDACT_PATTERNS, NICE_RANGE,
+};
+pub use quota::{
+    CpuRecommendation, MemoryRecommendation, QuotaRecommendation, QUOTA_MIN_OBSERVED_SECONDS,
+};
+pub use reaction::{Reaction, ReactionTrigger};
+pub use reboot::{Inventory, RebootReport};
+pub use reclaim::{DiskSuggestions, ReclaimKind, ReclaimSuggestion, LARGE_LOG_BYTES};
 pub use resource::{MonitoredResource, ResourceType};
+pub use security::{ContainerSecurity, ImageScan, VulnerabilityCounts};
 pub use service::{ServiceState, SystemdService};
+pub use speedtest::{SpeedtestResult, SpeedtestTool};
+pub use spike::{HostChange, SpikeAnalysis, SpikeBreakdown, SpikeContributor};
+pub use ssh::{AuthFailure, OffenderCount, SshSummary, SshWatch};
+pub use supervisor::{container_failure, RestartPolicy};
 pub use temperature::{Temperature, TemperatureSource};
+pub use time::{format_local, format_utc_and_local, parse_timezone, parse_window};
+pub use top::{top_talkers, Talker, TalkerKind};
+pub use topology::{HostState, Topology, TopologyEntry, TopologyGroup, TopologyHost, UNGROUPED};
+pub use trend::{rate_per_second, DiskRate, MemoryTrend, UsageRates};
+pub use viewer::Viewer;
+pub use vm::{Hypervisor, VirtualMachine, VmState};
+pub use volume::{ContainerMount, DirectoryUsage, MountKind, VolumeUsage};
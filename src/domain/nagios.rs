@@ -0,0 +1,316 @@
+use std::fmt::Write;
+use std::str::FromStr;
+
+use super::{Container, ContainerState, Disk, Host, MemoryMetrics};
+
+/// Nagios plugin state, ordered by severity; the exit code is the plugin's
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CheckState {
+    Ok,
+    Warning,
+    Critical,
+    Unknown,
+}
+
+impl CheckState {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::Ok => 0,
+            Self::Warning => 1,
+            Self::Critical => 2,
+            Self::Unknown => 3,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Ok => "OK",
+            Self::Warning => "WARNING",
+            Self::Critical => "CRITICAL",
+            Self::Unknown => "UNKNOWN",
+        }
+    }
+}
+
+/// Percentages at which a check turns WARNING and CRITICAL
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Thresholds {
+    pub warn: f64,
+    pub crit: f64,
+}
+
+impl Thresholds {
+    pub fn state(&self, value: f64) -> CheckState {
+        if value >= self.crit {
+            CheckState::Critical
+        } else if value >= self.warn {
+            CheckState::Warning
+        } else {
+            CheckState::Ok
+        }
+    }
+}
+
+/// One `label=value[unit];warn;crit;min;max` performance data item
+#[derive(Debug, Clone)]
+pub struct PerfData {
+    pub label: String,
+    pub value: f64,
+    pub unit: &'static str,
+    pub thresholds: Option<Thresholds>,
+    pub max: Option<f64>,
+}
+
+impl PerfData {
+    fn new(label: impl Into<String>, value: f64, unit: &'static str) -> Self {
+        Self {
+            label: label.into(),
+            value,
+            unit,
+            thresholds: None,
+            max: None,
+        }
+    }
+
+    fn with_thresholds(mut self, thresholds: Thresholds, max: f64) -> Self {
+        self.thresholds = Some(thresholds);
+        self.max = Some(max);
+        self
+    }
+}
+
+/// Checks NanoMon answers in Nagios plugin format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NagiosCheck {
+    Disk,
+    Memory,
+    Containers,
+}
+
+impl NagiosCheck {
+    /// Service name leading the plugin output
+    pub fn service(&self) -> &'static str {
+        match self {
+            Self::Disk => "DISK",
+            Self::Memory => "MEMORY",
+            Self::Containers => "CONTAINERS",
+        }
+    }
+
+    /// Used when the caller gives no thresholds
+    fn default_thresholds(&self) -> Thresholds {
+        match self {
+            Self::Disk => Thresholds {
+                warn: 80.0,
+                crit: 90.0,
+            },
+            Self::Memory | Self::Containers => Thresholds {
+                warn: 90.0,
+                crit: 95.0,
+            },
+        }
+    }
+
+    /// Run the check against a snapshot, with the default for any threshold not
+    /// given; containers take no thresholds
+    pub fn evaluate(&self, host: &Host, warn: Option<f64>, crit: Option<f64>) -> CheckResult {
+        let defaults = self.default_thresholds();
+        let thresholds = Thresholds {
+            warn: warn.unwrap_or(defaults.warn),
+            crit: crit.unwrap_or(defaults.crit),
+        };
+        match self {
+            Self::Disk => check_disks(&host.disks, thresholds),
+            Self::Memory => check_memory(&host.memory, thresholds),
+            Self::Containers => check_containers(&host.containers),
+        }
+    }
+}
+
+impl FromStr for NagiosCheck {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "disk" => Ok(Self::Disk),
+            "memory" => Ok(Self::Memory),
+            "containers" => Ok(Self::Containers),
+            other => Err(format!(
+                "Unknown check '{}', expected disk, memory or containers",
+                other
+            )),
+        }
+    }
+}
+
+/// Result of one check, rendered as plugin output
+#[derive(Debug, Clone)]
+pub struct CheckResult {
+    pub service: &'static str,
+    pub state: CheckState,
+    pub summary: String,
+    pub perfdata: Vec<PerfData>,
+}
+
+impl CheckResult {
+    pub fn unknown(service: &'static str, reason: impl Into<String>) -> Self {
+        Self {
+            service,
+            state: CheckState::Unknown,
+            summary: reason.into(),
+            perfdata: Vec::new(),
+        }
+    }
+
+    /// `SERVICE STATE - summary | perfdata`
+    pub fn render(&self) -> String {
+        let mut output = format!("{} {} - {}", self.service, self.state.label(), self.summary);
+        if !self.perfdata.is_empty() {
+            output.push_str(" |");
+        }
+        for perf in &self.perfdata {
+            let value = (perf.value * 10.0).round() / 10.0;
+            let _ = write!(output, " '{}'={}{}", perf.label, value, perf.unit);
+            if let Some(t) = perf.thresholds {
+                let _ = write!(output, ";{};{};0", t.warn, t.crit);
+                if let Some(max) = perf.max {
+                    let _ = write!(output, ";{}", max);
+                }
+            }
+        }
+        output
+    }
+}
+
+/// Worst filesystem usage against `thresholds`
+fn check_disks(disks: &[Disk], thresholds: Thresholds) -> CheckResult {
+    if disks.is_empty() {
+        return CheckResult::unknown("DISK", "no filesystems found");
+    }
+    let mut state = CheckState::Ok;
+    let mut over = Vec::new();
+    let mut perfdata = Vec::new();
+    for disk in disks {
+        let used = disk.usage_percent();
+        let disk_state = thresholds.state(used);
+        if disk_state != CheckState::Ok {
+            over.push(format!("{} {:.1}%", disk.mount_point, used));
+        }
+        state = state.max(disk_state);
+        perfdata.push(
+            PerfData::new(disk.mount_point.clone(), used, "%").with_thresholds(thresholds, 100.0),
+        );
+    }
+    let summary = if over.is_empty() {
+        format!(
+            "{} filesystems below {}% used",
+            disks.len(),
+            thresholds.warn
+        )
+    } else {
+        format!("{} used", over.join(", "))
+    };
+    CheckResult {
+        service: "DISK",
+        state,
+        summary,
+        perfdata,
+    }
+}
+
+/// Host memory usage against `thresholds`
+fn check_memory(memory: &MemoryMetrics, thresholds: Thresholds) -> CheckResult {
+    if memory.total_bytes == 0 {
+        return CheckResult::unknown("MEMORY", "memory size unknown");
+    }
+    let used = memory.usage_percent();
+    CheckResult {
+        service: "MEMORY",
+        state: thresholds.state(used),
+        summary: format!(
+            "{:.1}% used ({} of {} MiB)",
+            used,
+            memory.used_bytes / (1024 * 1024),
+            memory.total_bytes / (1024 * 1024)
+        ),
+        perfdata: vec![
+            PerfData::new("used", used, "%").with_thresholds(thresholds, 100.0),
+            PerfData::new("used_bytes", memory.used_bytes as f64, "B"),
+        ],
+    }
+}
+
+/// Dead or restarting containers are critical, paused ones a warning; stopped
+/// ones are counted but not alerted on since one-off jobs exit normally
+fn check_containers(containers: &[Container]) -> CheckResult {
+    let mut state = CheckState::Ok;
+    let mut problems = Vec::new();
+    for container in containers {
+        let container_state = match container.state {
+            ContainerState::Dead | ContainerState::Restarting => CheckState::Critical,
+            ContainerState::Paused => CheckState::Warning,
+            _ => continue,
+        };
+        state = state.max(container_state);
+        let state_name = format!("{:?}", container.state).to_lowercase();
+        problems.push(format!("{} {}", container.name, state_name));
+    }
+    let running = containers.iter().filter(|c| c.state.is_running()).count();
+    let summary = if problems.is_empty() {
+        format!("{} of {} containers running", running, containers.len())
+    } else {
+        problems.join(", ")
+    };
+    CheckResult {
+        service: "CONTAINERS",
+        state,
+        summary,
+        perfdata: vec![
+            PerfData::new("running", running as f64, ""),
+            PerfData::new("total", containers.len() as f64, ""),
+        ],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disk_check_output() {
+        let disks = vec![
+            Disk::new(
+                "/dev/sda1".to_string(),
+                "/".to_string(),
+                "ext4".to_string(),
+                100,
+                50,
+                50,
+            ),
+            Disk::new(
+                "/dev/md0".to_string(),
+                "/mnt/data".to_string(),
+                "ext4".to_string(),
+                100,
+                85,
+                15,
+            ),
+        ];
+        let mut host = Host::new("nas".to_string());
+        host.disks = disks;
+        let result = NagiosCheck::Disk.evaluate(&host, None, None);
+        assert_eq!(result.state, CheckState::Warning);
+        assert_eq!(result.state.exit_code(), 1);
+        assert_eq!(
+            result.render(),
+            "DISK WARNING - /mnt/data 85.0% used | '/'=50%;80;90;0;100 '/mnt/data'=85%;80;90;0;100"
+        );
+
+        host.memory = MemoryMetrics::new(960, 1000, 40);
+        let result = NagiosCheck::Memory.evaluate(&host, None, None);
+        assert_eq!(result.state, CheckState::Critical);
+        let result = NagiosCheck::Memory.evaluate(&host, Some(97.0), Some(99.0));
+        assert_eq!(result.state, CheckState::Ok);
+        assert!("nope".parse::<NagiosCheck>().is_err());
+    }
+}
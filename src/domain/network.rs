@@ -8,6 +8,45 @@ pub struct NetworkInterface {
     pub name: String,
     pub is_up: bool,
     pub metrics: NetworkMetrics,
+    /// Negotiated link speed, absent for virtual interfaces and down links
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speed_mbps: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mtu: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mac: Option<String>,
+    /// Assigned addresses in CIDR notation, IPv4 first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub addresses: Vec<String>,
+    /// Only for wireless interfaces
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wireless: Option<WirelessLink>,
+    /// Per-queue counters, for drivers that report them
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub queues: Vec<QueueStats>,
+}
+
+/// Counters of one hardware queue, named like `rx-0`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct QueueStats {
+    pub queue: String,
+    pub packets: u64,
+    pub bytes: u64,
+    pub drops: u64,
+}
+
+/// Association of a wireless interface with its access point
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WirelessLink {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ssid: Option<String>,
+    /// Link quality scaled to 0-100
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quality_percent: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signal_dbm: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub noise_dbm: Option<i32>,
 }
 
 impl NetworkInterface {
@@ -16,6 +55,49 @@ impl NetworkInterface {
             name,
             is_up,
             metrics,
+            speed_mbps: None,
+            mtu: None,
+            mac: None,
+            addresses: Vec::new(),
+            wireless: None,
+            queues: Vec::new(),
+        }
+    }
+
+    pub fn with_link(
+        mut self,
+        speed_mbps: Option<u64>,
+        mtu: Option<u32>,
+        mac: Option<String>,
+    ) -> Self {
+        self.speed_mbps = speed_mbps;
+        self.mtu = mtu;
+        self.mac = mac;
+        self
+    }
+
+    pub fn with_addresses(mut self, addresses: Vec<String>) -> Self {
+        self.addresses = addresses;
+        self
+    }
+
+    pub fn with_wireless(mut self, wireless: Option<WirelessLink>) -> Self {
+        self.wireless = wireless;
+        self
+    }
+
+    pub fn with_queues(mut self, queues: Vec<QueueStats>) -> Self {
+        self.queues = queues;
+        self
+    }
+
+    /// Strip the MAC, addresses and SSID, which identify the host on its network
+    pub fn redacted(mut self) -> Self {
+        self.mac = None;
+        self.addresses.clear();
+        if let Some(wireless) = &mut self.wireless {
+            wireless.ssid = None;
         }
+        self
     }
 }
@@ -0,0 +1,113 @@
+use serde::{Deserialize, Serialize};
+
+use super::AlertRule;
+
+/// How urgent an alert is, for routing
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Info,
+    #[default]
+    Warning,
+    Critical,
+}
+
+impl Severity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Info => "info",
+            Self::Warning => "warning",
+            Self::Critical => "critical",
+        }
+    }
+}
+
+/// Where the alerts it matches are sent, from `[[routes]]` in the alert
+/// config. Every condition set must hold; the first matching route wins and
+/// rules no route matches use their own `webhook_url`.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct NotificationRoute {
+    /// Rule names; any rule when empty
+    #[serde(default)]
+    pub rules: Vec<String>,
+    /// Any severity when empty
+    #[serde(default)]
+    pub severities: Vec<Severity>,
+    /// Metric kinds such as `disk_usage`; any metric when empty
+    #[serde(default)]
+    pub metrics: Vec<String>,
+    /// Mount point, container, sensor...: exact, or a prefix ending in `*`
+    #[serde(default)]
+    pub resource: Option<String>,
+    /// Webhook URLs, `plugin://<name>`, `mailto:<address>` or
+    /// `telegram:<chat id>`; matching alerts are dropped when empty
+    #[serde(default)]
+    pub notify: Vec<String>,
+    /// At most one message per this many seconds; alerts in between wait
+    /// for the next evaluation
+    #[serde(default)]
+    pub throttle_seconds: u64,
+    /// Alerts arriving within this many seconds of the first are sent as
+    /// one message
+    #[serde(default)]
+    pub group_seconds: u64,
+}
+
+impl NotificationRoute {
+    pub fn matches(&self, rule: &AlertRule) -> bool {
+        let resource = rule.metric.resource();
+        (self.rules.is_empty() || self.rules.contains(&rule.name))
+            && (self.severities.is_empty() || self.severities.contains(&rule.severity))
+            && (self.metrics.is_empty() || self.metrics.iter().any(|m| m == rule.metric.kind()))
+            && self
+                .resource
+                .as_deref()
+                .is_none_or(|pattern| match pattern.strip_suffix('*') {
+                    Some(prefix) => resource.starts_with(prefix),
+                    None => resource == pattern,
+                })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{AlertCondition, AlertMetric};
+
+    fn rule(name: &str, metric: AlertMetric, severity: Severity) -> AlertRule {
+        AlertRule {
+            name: name.to_string(),
+            metric,
+            condition: AlertCondition::Above,
+            threshold: 90.0,
+            webhook_url: String::new(),
+            cooldown_seconds: 300,
+            severity,
+            escalation: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_route_conditions_all_hold() {
+        let disks = NotificationRoute {
+            metrics: vec!["disk_usage".to_string()],
+            resource: Some("/volume*".to_string()),
+            ..Default::default()
+        };
+        let disk = |mount_point: &str| AlertMetric::DiskUsage {
+            mount_point: mount_point.to_string(),
+        };
+        assert!(disks.matches(&rule("data", disk("/volume1"), Severity::Warning)));
+        assert!(!disks.matches(&rule("root", disk("/"), Severity::Warning)));
+        assert!(!disks.matches(&rule("cpu", AlertMetric::CpuUsage, Severity::Warning)));
+
+        let info = NotificationRoute {
+            severities: vec![Severity::Info],
+            ..Default::default()
+        };
+        assert!(info.matches(&rule("cpu", AlertMetric::CpuUsage, Severity::Info)));
+        assert!(!info.matches(&rule("cpu", AlertMetric::CpuUsage, Severity::Critical)));
+    }
+}
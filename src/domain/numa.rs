@@ -0,0 +1,35 @@
+use serde::{Deserialize, Serialize};
+
+/// Hugepage pool of the default size, host-wide or for one NUMA node
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct HugePages {
+    pub total: u64,
+    pub free: u64,
+    /// Promised to a mapping but not faulted in yet (host-wide only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reserved: Option<u64>,
+    /// Allocated beyond `total` through overcommit
+    pub surplus: u64,
+    pub page_size_bytes: u64,
+}
+
+/// Memory of one NUMA node
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NumaNode {
+    pub node: u32,
+    pub total_bytes: u64,
+    pub free_bytes: u64,
+    pub used_bytes: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hugepages: Option<HugePages>,
+}
+
+/// Memory layout detail for bigger hosts: a node can run out and reclaim or swap
+/// while the host as a whole still looks comfortable
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MemoryTopology {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hugepages: Option<HugePages>,
+    /// Empty on kernels without NUMA support; a single node on most small machines
+    pub numa_nodes: Vec<NumaNode>,
+}
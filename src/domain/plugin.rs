@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+
+/// Version of the plugin protocol spoken over stdin/stdout; bumped on breaking changes
+pub const PLUGIN_PROTOCOL_VERSION: u64 = 1;
+
+/// URL scheme that routes a rule's or the events log's notifications to a plugin
+pub const PLUGIN_URL_SCHEME: &str = "plugin://";
+
+/// A third-party plugin: a long-running program speaking JSON-RPC 2.0, one
+/// message per line, that can act as a collector, a notifier or both
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginConfig {
+    pub name: String,
+    /// Program then arguments, no shell
+    pub command: Vec<String>,
+    /// Limit for starting the plugin and for each call
+    #[serde(default = "default_plugin_timeout")]
+    pub timeout_seconds: u64,
+}
+
+fn default_plugin_timeout() -> u64 {
+    10
+}
@@ -0,0 +1,74 @@
+use serde::{Deserialize, Serialize};
+
+/// A machine whose power NanoMon can control
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PowerTarget {
+    pub name: String,
+    /// MAC address for Wake-on-LAN, e.g. `aa:bb:cc:dd:ee:ff`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mac: Option<String>,
+    #[serde(default = "default_broadcast")]
+    pub broadcast: String,
+    /// SSH destination used for shutdown, e.g. `admin@192.168.1.20`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ssh: Option<String>,
+    #[serde(default = "default_shutdown_command")]
+    pub shutdown_command: String,
+}
+
+fn default_broadcast() -> String {
+    "255.255.255.255".to_string()
+}
+
+fn default_shutdown_command() -> String {
+    "sudo shutdown -h now".to_string()
+}
+
+/// Power action requested on a target
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PowerAction {
+    Wake,
+    Shutdown,
+}
+
+/// Parse a MAC address written with `:` or `-` separators
+pub fn parse_mac(mac: &str) -> Option<[u8; 6]> {
+    let parts: Vec<&str> = mac.split([':', '-']).collect();
+    if parts.len() != 6 {
+        return None;
+    }
+
+    let mut bytes = [0u8; 6];
+    for (byte, part) in bytes.iter_mut().zip(parts) {
+        *byte = u8::from_str_radix(part, 16).ok()?;
+    }
+    Some(bytes)
+}
+
+/// Build a Wake-on-LAN magic packet: 6 x 0xFF followed by the MAC 16 times
+pub fn magic_packet(mac: [u8; 6]) -> Vec<u8> {
+    let mut packet = vec![0xFF; 6];
+    for _ in 0..16 {
+        packet.extend_from_slice(&mac);
+    }
+    packet
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_magic_packet() {
+        let mac = parse_mac("AA:bb:cc:dd:ee:01").unwrap();
+        assert_eq!(mac, [0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0x01]);
+        assert_eq!(parse_mac("aa-bb-cc-dd-ee-01"), Some(mac));
+        assert!(parse_mac("aa:bb:cc").is_none());
+
+        let packet = magic_packet(mac);
+        assert_eq!(packet.len(), 102);
+        assert_eq!(&packet[..6], &[0xFF; 6]);
+        assert_eq!(&packet[96..], &mac);
+    }
+}
@@ -0,0 +1,168 @@
+use std::sync::Arc;
+
+use serde::Serialize;
+
+use super::Host;
+
+/// Room added on top of p95 memory usage
+const MEMORY_HEADROOM: f64 = 1.25;
+
+/// Room added on top of p95 CPU usage; CPU limits throttle rather than kill,
+/// so they get more
+const CPU_HEADROOM: f64 = 1.5;
+
+/// Memory limits are rounded up to this
+const MEMORY_STEP_BYTES: u64 = 64 * 1024 * 1024;
+
+/// History a container must have been seen in to get recommendations
+pub const QUOTA_MIN_OBSERVED_SECONDS: i64 = 3600;
+
+/// Suggested `mem_limit`
+#[derive(Debug, Clone, Serialize)]
+pub struct MemoryRecommendation {
+    pub p95_bytes: u64,
+    pub peak_bytes: u64,
+    /// The limit set now, absent when the container has none
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current_limit_bytes: Option<u64>,
+    /// p95 plus headroom, never below the peak
+    pub recommended_bytes: u64,
+    /// Line for the Compose service, e.g. `mem_limit: 512m`
+    pub compose: String,
+}
+
+/// Suggested `cpus`
+#[derive(Debug, Clone, Serialize)]
+pub struct CpuRecommendation {
+    /// Percent of one core
+    pub p95_percent: f64,
+    pub peak_percent: f64,
+    /// p95 plus headroom, in cores
+    pub recommended_cpus: f64,
+    /// Line for the Compose service, e.g. `cpus: "0.5"`
+    pub compose: String,
+}
+
+/// Limits suggested for one container from its stored history
+#[derive(Debug, Clone, Serialize)]
+pub struct QuotaRecommendation {
+    pub container: String,
+    pub observed_seconds: u64,
+    pub samples: usize,
+    pub memory: MemoryRecommendation,
+    pub cpu: CpuRecommendation,
+}
+
+impl QuotaRecommendation {
+    /// Recommendations for the running container `identity` over `history`
+    /// (oldest first), `None` until it was seen for
+    /// `QUOTA_MIN_OBSERVED_SECONDS`
+    pub fn from_history(identity: &str, history: &[Arc<Host>]) -> Option<Self> {
+        let samples: Vec<_> = history
+            .iter()
+            .filter_map(|host| {
+                host.containers
+                    .iter()
+                    .find(|c| c.state.is_running() && c.identity() == identity)
+                    .map(|c| (host, c))
+            })
+            .collect();
+        let (first, latest) = (samples.first()?, samples.last()?);
+        let observed = (latest.0.timestamp - first.0.timestamp).num_seconds();
+        if observed < QUOTA_MIN_OBSERVED_SECONDS {
+            return None;
+        }
+
+        let mut memory: Vec<f64> = samples
+            .iter()
+            .map(|(_, c)| c.working_set_bytes() as f64)
+            .collect();
+        let mut cpu: Vec<f64> = samples.iter().map(|(_, c)| c.cpu.usage_percent).collect();
+        let (memory_p95, memory_peak) = p95_and_peak(&mut memory);
+        let (cpu_p95, cpu_peak) = p95_and_peak(&mut cpu);
+
+        let recommended_bytes = ((memory_p95 * MEMORY_HEADROOM).max(memory_peak) as u64)
+            .div_ceil(MEMORY_STEP_BYTES)
+            .max(1)
+            * MEMORY_STEP_BYTES;
+        // Tenths of a core (10% each), at least one
+        let recommended_cpus = ((cpu_p95 * CPU_HEADROOM / 10.0).ceil() / 10.0).max(0.1);
+        // Docker reports the host's memory as the limit of unlimited containers
+        let limit = latest.1.memory.total_bytes;
+        let current_limit_bytes =
+            (limit > 0 && limit < latest.0.memory.total_bytes).then_some(limit);
+
+        Some(Self {
+            container: latest.1.name.clone(),
+            observed_seconds: observed as u64,
+            samples: samples.len(),
+            memory: MemoryRecommendation {
+                p95_bytes: memory_p95 as u64,
+                peak_bytes: memory_peak as u64,
+                current_limit_bytes,
+                recommended_bytes,
+                compose: format!("mem_limit: {}m", recommended_bytes / (1024 * 1024)),
+            },
+            cpu: CpuRecommendation {
+                p95_percent: cpu_p95,
+                peak_percent: cpu_peak,
+                recommended_cpus,
+                compose: format!("cpus: \"{:.1}\"", recommended_cpus),
+            },
+        })
+    }
+}
+
+/// Nearest-rank 95th percentile and maximum of non-empty `values`
+fn p95_and_peak(values: &mut [f64]) -> (f64, f64) {
+    values.sort_by(f64::total_cmp);
+    let rank = (values.len() as f64 * 0.95).ceil() as usize;
+    (values[rank.max(1) - 1], values[values.len() - 1])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{Container, ContainerId, ContainerState, CpuMetrics, MemoryMetrics};
+    use chrono::{Duration, Utc};
+
+    const MIB: u64 = 1024 * 1024;
+
+    #[test]
+    fn test_recommendations_add_headroom_to_p95() {
+        let start = Utc::now() - Duration::hours(2);
+        let history: Vec<Arc<Host>> = (0..100)
+            .map(|minute| {
+                let mut host =
+                    Host::new("test".to_string()).with_timestamp(start + Duration::minutes(minute));
+                host.memory = MemoryMetrics::new(0, 8192 * MIB, 0);
+                let mut container = Container::new(
+                    ContainerId::new("abc".to_string()),
+                    "nextcloud".to_string(),
+                    "nextcloud:28".to_string(),
+                    ContainerState::Running,
+                    start,
+                );
+                // One spike above an otherwise steady 400 MiB and 20% CPU
+                let spike = minute == 50;
+                let rss = if spike { 700 * MIB } else { 400 * MIB };
+                container.memory = MemoryMetrics::new(0, 8192 * MIB, 0).with_rss(rss);
+                container.cpu = CpuMetrics::new(if spike { 180.0 } else { 20.0 }, 0.0, 0.0);
+                host.containers = vec![container];
+                Arc::new(host)
+            })
+            .collect();
+
+        let recommendation = QuotaRecommendation::from_history("nextcloud", &history).unwrap();
+        assert_eq!(recommendation.samples, 100);
+        assert_eq!(recommendation.memory.p95_bytes, 400 * MIB);
+        assert_eq!(recommendation.memory.peak_bytes, 700 * MIB);
+        assert_eq!(recommendation.memory.current_limit_bytes, None);
+        // The peak wins over 500 MiB of p95 plus headroom, rounded up to 64 MiB
+        assert_eq!(recommendation.memory.compose, "mem_limit: 704m");
+        assert_eq!(recommendation.cpu.compose, "cpus: \"0.3\"");
+
+        assert!(QuotaRecommendation::from_history("nextcloud", &history[..30]).is_none());
+        assert!(QuotaRecommendation::from_history("other", &history).is_none());
+    }
+}
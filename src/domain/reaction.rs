@@ -0,0 +1,70 @@
+use serde::Deserialize;
+
+use super::{AlertEvent, AlertRule};
+
+/// When a reaction runs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReactionTrigger {
+    #[default]
+    Fire,
+    Resolve,
+}
+
+/// A local command run when a rule's alert fires or resolves, from
+/// `[[reactions]]` in the alert config
+#[derive(Debug, Clone, Deserialize)]
+pub struct Reaction {
+    pub name: String,
+    /// Name of the rule whose alert triggers the command
+    pub rule: String,
+    #[serde(default)]
+    pub on: ReactionTrigger,
+    /// Consecutive evaluations the rule must fire before the command runs,
+    /// once per alert
+    #[serde(default = "default_after")]
+    pub after: u32,
+    /// Program and arguments, run without a shell
+    pub command: Vec<String>,
+    #[serde(default = "default_timeout")]
+    pub timeout_seconds: u64,
+}
+
+fn default_after() -> u32 {
+    1
+}
+
+fn default_timeout() -> u64 {
+    30
+}
+
+impl Reaction {
+    /// Variables describing the alert, added to the command's environment
+    pub fn environment(&self, rule: &AlertRule, event: &AlertEvent) -> Vec<(String, String)> {
+        let state = match self.on {
+            ReactionTrigger::Fire => "firing",
+            ReactionTrigger::Resolve => "resolved",
+        };
+        [
+            ("NANOMON_REACTION", self.name.clone()),
+            ("NANOMON_ALERT_RULE", rule.name.clone()),
+            ("NANOMON_ALERT_STATE", state.to_string()),
+            ("NANOMON_ALERT_SEVERITY", rule.severity.as_str().to_string()),
+            ("NANOMON_ALERT_METRIC", rule.metric.kind().to_string()),
+            ("NANOMON_ALERT_RESOURCE", rule.metric.resource()),
+            ("NANOMON_ALERT_VALUE", event.current_value.to_string()),
+            ("NANOMON_ALERT_THRESHOLD", rule.threshold.to_string()),
+            (
+                "NANOMON_ALERT_ID",
+                event
+                    .incident_id
+                    .map(|id| id.to_string())
+                    .unwrap_or_default(),
+            ),
+            ("NANOMON_HOSTNAME", event.hostname.clone()),
+        ]
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value))
+        .collect()
+    }
+}
@@ -0,0 +1,118 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// What was up and running during a given boot of the host
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Inventory {
+    pub boot_time: DateTime<Utc>,
+    pub running_containers: Vec<String>,
+    pub active_services: Vec<String>,
+}
+
+impl Inventory {
+    pub fn new(
+        boot_time: DateTime<Utc>,
+        mut running_containers: Vec<String>,
+        mut active_services: Vec<String>,
+    ) -> Self {
+        running_containers.sort();
+        running_containers.dedup();
+        active_services.sort();
+        active_services.dedup();
+        Self {
+            boot_time,
+            running_containers,
+            active_services,
+        }
+    }
+
+    /// Whether this inventory belongs to a different boot than `boot_time`.
+    /// Boot time is derived from uptime, so allow a minute of jitter.
+    pub fn is_other_boot(&self, boot_time: DateTime<Utc>) -> bool {
+        (boot_time - self.boot_time).num_seconds().abs() > 60
+    }
+}
+
+/// Comparison of what was running before a reboot against what came back
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RebootReport {
+    pub previous_boot: DateTime<Utc>,
+    pub current_boot: DateTime<Utc>,
+    pub missing_containers: Vec<String>,
+    pub missing_services: Vec<String>,
+    pub new_containers: Vec<String>,
+    pub new_services: Vec<String>,
+}
+
+impl RebootReport {
+    pub fn compare(before: &Inventory, after: &Inventory) -> Self {
+        let missing = |a: &[String], b: &[String]| -> Vec<String> {
+            a.iter().filter(|x| !b.contains(x)).cloned().collect()
+        };
+
+        Self {
+            previous_boot: before.boot_time,
+            current_boot: after.boot_time,
+            missing_containers: missing(&before.running_containers, &after.running_containers),
+            missing_services: missing(&before.active_services, &after.active_services),
+            new_containers: missing(&after.running_containers, &before.running_containers),
+            new_services: missing(&after.active_services, &before.active_services),
+        }
+    }
+
+    /// True when everything that was running before the reboot came back
+    pub fn is_clean(&self) -> bool {
+        self.missing_containers.is_empty() && self.missing_services.is_empty()
+    }
+
+    pub fn summary(&self) -> String {
+        if self.is_clean() {
+            return "Post-reboot check: everything came back up".to_string();
+        }
+
+        let mut parts = Vec::new();
+        if !self.missing_containers.is_empty() {
+            parts.push(format!(
+                "containers not running: {}",
+                self.missing_containers.join(", ")
+            ));
+        }
+        if !self.missing_services.is_empty() {
+            parts.push(format!(
+                "services not active: {}",
+                self.missing_services.join(", ")
+            ));
+        }
+        format!("Post-reboot check: {}", parts.join("; "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_report_lists_what_did_not_come_back() {
+        let before = Inventory::new(
+            Utc::now() - chrono::Duration::days(3),
+            names(&["plex", "nextcloud", "db"]),
+            names(&["sshd", "smbd"]),
+        );
+        let after = Inventory::new(
+            Utc::now(),
+            names(&["db", "plex", "watchtower"]),
+            names(&["sshd", "smbd"]),
+        );
+
+        assert!(before.is_other_boot(after.boot_time));
+        let report = RebootReport::compare(&before, &after);
+        assert_eq!(report.missing_containers, names(&["nextcloud"]));
+        assert!(report.missing_services.is_empty());
+        assert_eq!(report.new_containers, names(&["watchtower"]));
+        assert!(!report.is_clean());
+    }
+}
@@ -0,0 +1,140 @@
+use serde::Serialize;
+
+/// Journal size `journalctl --vacuum-size` is suggested to keep
+pub const JOURNAL_KEEP_BYTES: u64 = 500 * 1024 * 1024;
+
+/// Log files from this size up are worth a look
+pub const LARGE_LOG_BYTES: u64 = 100 * 1024 * 1024;
+
+/// What could be freed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReclaimKind {
+    /// An untagged image no tag points to anymore
+    DanglingImage,
+    /// The writable layer of a stopped container
+    StoppedContainer,
+    Journal,
+    LogFile,
+}
+
+/// Disk space that could be freed, and how
+#[derive(Debug, Clone, Serialize)]
+pub struct ReclaimSuggestion {
+    pub kind: ReclaimKind,
+    /// Image ID, container name or host path
+    pub target: String,
+    /// Estimated bytes freed by following the advice
+    pub savings_bytes: u64,
+    pub advice: String,
+}
+
+impl ReclaimSuggestion {
+    /// Vacuuming a journal of `used_bytes` down to `JOURNAL_KEEP_BYTES`,
+    /// `None` when it is smaller
+    pub fn journal(path: &str, used_bytes: u64) -> Option<Self> {
+        let savings_bytes = used_bytes.checked_sub(JOURNAL_KEEP_BYTES)?;
+        Some(Self {
+            kind: ReclaimKind::Journal,
+            target: path.to_string(),
+            savings_bytes,
+            advice: format!(
+                "journalctl --vacuum-size={}M, or set SystemMaxUse in journald.conf",
+                JOURNAL_KEEP_BYTES / (1024 * 1024)
+            ),
+        })
+    }
+
+    /// A log file of `bytes`: rotated copies can go, live logs need rotating
+    pub fn log_file(path: &str, bytes: u64) -> Self {
+        let advice = if is_rotated_log(path) {
+            "Delete this rotated log, or keep fewer in its logrotate rule".to_string()
+        } else {
+            format!(
+                "truncate -s 0 {}, and rotate it more often with logrotate",
+                path
+            )
+        };
+        Self {
+            kind: ReclaimKind::LogFile,
+            target: path.to_string(),
+            savings_bytes: bytes,
+            advice,
+        }
+    }
+}
+
+/// Whether `path` is an old log kept by rotation: `syslog.1`,
+/// `messages-20240101`, `app.log.2.gz`...
+pub fn is_rotated_log(path: &str) -> bool {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    if [".gz", ".xz", ".zst", ".bz2", ".old"]
+        .iter()
+        .any(|ext| name.ends_with(ext))
+    {
+        return true;
+    }
+    name.rsplit_once(['.', '-'])
+        .is_some_and(|(_, suffix)| !suffix.is_empty() && suffix.bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// Everything found, biggest savings first
+#[derive(Debug, Clone, Serialize)]
+pub struct DiskSuggestions {
+    pub total_savings_bytes: u64,
+    pub suggestions: Vec<ReclaimSuggestion>,
+    /// Sources that could not be looked at
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<String>,
+}
+
+impl DiskSuggestions {
+    pub fn new(mut suggestions: Vec<ReclaimSuggestion>, errors: Vec<String>) -> Self {
+        suggestions.sort_by_key(|s| std::cmp::Reverse(s.savings_bytes));
+        Self {
+            total_savings_bytes: suggestions.iter().map(|s| s.savings_bytes).sum(),
+            suggestions,
+            errors,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_suggestions_rank_by_savings() {
+        for rotated in [
+            "syslog.1",
+            "messages-20240101",
+            "app.log.2.gz",
+            "dpkg.log.old",
+        ] {
+            assert!(
+                is_rotated_log(&format!("/var/log/{}", rotated)),
+                "{}",
+                rotated
+            );
+        }
+        for live in ["syslog", "php8.2-fpm.log", "kern.log"] {
+            assert!(!is_rotated_log(&format!("/var/log/{}", live)), "{}", live);
+        }
+
+        const MIB: u64 = 1024 * 1024;
+        assert!(ReclaimSuggestion::journal("/var/log/journal", 400 * MIB).is_none());
+        let journal = ReclaimSuggestion::journal("/var/log/journal", 3000 * MIB).unwrap();
+        assert_eq!(journal.savings_bytes, 2500 * MIB);
+
+        let suggestions = DiskSuggestions::new(
+            vec![
+                ReclaimSuggestion::log_file("/var/log/syslog", 200 * MIB),
+                journal,
+            ],
+            Vec::new(),
+        );
+        assert_eq!(suggestions.suggestions[0].kind, ReclaimKind::Journal);
+        assert_eq!(suggestions.total_savings_bytes, 2700 * MIB);
+        assert!(suggestions.suggestions[1].advice.starts_with("truncate"));
+    }
+}
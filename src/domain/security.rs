@@ -0,0 +1,52 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Vulnerability counts by severity
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VulnerabilityCounts {
+    pub critical: u32,
+    pub high: u32,
+    pub medium: u32,
+    pub low: u32,
+    pub unknown: u32,
+}
+
+impl VulnerabilityCounts {
+    /// Count one finding; severity names from trivy (`CRITICAL`) and grype (`Critical`)
+    pub fn add(&mut self, severity: &str) {
+        match severity.to_ascii_lowercase().as_str() {
+            "critical" => self.critical += 1,
+            "high" => self.high += 1,
+            "medium" => self.medium += 1,
+            "low" | "negligible" => self.low += 1,
+            _ => self.unknown += 1,
+        }
+    }
+
+    pub fn merge(&mut self, other: &Self) {
+        self.critical += other.critical;
+        self.high += other.high;
+        self.medium += other.medium;
+        self.low += other.low;
+        self.unknown += other.unknown;
+    }
+}
+
+/// Result of a vulnerability scan for one image
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageScan {
+    pub image: String,
+    /// Tool that produced the report (`trivy`, `grype`)
+    pub scanner: String,
+    pub scanned_at: DateTime<Utc>,
+    pub counts: VulnerabilityCounts,
+}
+
+/// Security summary of a running container
+#[derive(Debug, Clone, Serialize)]
+pub struct ContainerSecurity {
+    pub container: String,
+    pub image: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scan: Option<ImageScan>,
+}
@@ -0,0 +1,36 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// External bandwidth test tool
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SpeedtestTool {
+    /// Python `speedtest-cli --json`
+    SpeedtestCli,
+    /// Official Ookla `speedtest -f json`
+    Ookla,
+    /// `librespeed-cli --json`
+    Librespeed,
+}
+
+impl SpeedtestTool {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "speedtest-cli" | "speedtestcli" => Some(Self::SpeedtestCli),
+            "ookla" | "speedtest" => Some(Self::Ookla),
+            "librespeed" | "librespeed-cli" => Some(Self::Librespeed),
+            _ => None,
+        }
+    }
+}
+
+/// Result of one bandwidth test
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpeedtestResult {
+    pub timestamp: DateTime<Utc>,
+    pub download_mbps: f64,
+    pub upload_mbps: f64,
+    pub ping_ms: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub server: Option<String>,
+}
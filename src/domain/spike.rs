@@ -0,0 +1,338 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use super::idle::counter_delta;
+use super::top::TalkerKind;
+use super::Host;
+
+/// A host-wide figure before and during the spike
+#[derive(Debug, Clone, Serialize)]
+pub struct HostChange {
+    pub baseline: f64,
+    pub during: f64,
+    /// Highest sample during the spike
+    pub peak: f64,
+}
+
+/// A container, or a process outside containers, that used more during the
+/// spike than before it
+#[derive(Debug, Clone, Serialize)]
+pub struct SpikeContributor {
+    pub kind: TalkerKind,
+    pub name: String,
+    pub baseline: f64,
+    pub during: f64,
+    pub increase: f64,
+    /// Share of the host's increase, where both are measured alike
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub share_percent: Option<f64>,
+}
+
+/// One resource's change, biggest contributors first
+#[derive(Debug, Clone, Serialize)]
+pub struct SpikeBreakdown {
+    /// Unit of every figure
+    pub unit: &'static str,
+    /// Absent when the host doesn't measure the resource as a whole
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub host: Option<HostChange>,
+    pub contributors: Vec<SpikeContributor>,
+}
+
+/// What drove the host's CPU, memory and block IO between `from` and `to`,
+/// measured against the same length of history just before
+#[derive(Debug, Clone, Serialize)]
+pub struct SpikeAnalysis {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub samples: usize,
+    /// Samples before `from` compared against; none when history starts later
+    pub baseline_samples: usize,
+    /// Host CPU in percent of all cores; containers and processes in percent
+    /// of one core
+    pub cpu: SpikeBreakdown,
+    pub memory: SpikeBreakdown,
+    /// Block IO of containers
+    pub io: SpikeBreakdown,
+}
+
+/// Figures of one snapshot, given the one before it for counters
+type Sample = (Option<f64>, Vec<((TalkerKind, String), f64)>);
+
+impl SpikeAnalysis {
+    /// Analysis of `history` (oldest first, reaching back before `from` for
+    /// the baseline) keeping `limit` contributors per resource, `None` without
+    /// samples between `from` and `to`. Processes are named by program only
+    /// when `redact` (privacy mode).
+    pub fn new(
+        history: &[Arc<Host>],
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        limit: usize,
+        redact: bool,
+    ) -> Option<Self> {
+        let baseline_from = from - (to - from);
+        let during = |host: &Host| host.timestamp >= from && host.timestamp <= to;
+        let baseline = |host: &Host| host.timestamp >= baseline_from && host.timestamp < from;
+        let samples = history.iter().filter(|h| during(h)).count();
+        if samples == 0 {
+            return None;
+        }
+        let baseline_samples = history.iter().filter(|h| baseline(h)).count();
+
+        let breakdown = |unit, shares: bool, sample: &dyn Fn(Option<&Host>, &Host) -> Sample| {
+            let mut host_sums = (0.0, 0.0);
+            let mut peak: Option<f64> = None;
+            let mut sums: HashMap<(TalkerKind, String), (f64, f64)> = HashMap::new();
+            for (index, host) in history.iter().enumerate() {
+                let in_spike = during(host);
+                if !in_spike && !baseline(host) {
+                    continue;
+                }
+                let previous = index.checked_sub(1).map(|i| &*history[i]);
+                let (host_value, values) = sample(previous, host);
+                if let Some(value) = host_value {
+                    if in_spike {
+                        host_sums.1 += value;
+                        peak = Some(peak.map_or(value, |p| p.max(value)));
+                    } else {
+                        host_sums.0 += value;
+                    }
+                }
+                for (key, value) in values {
+                    let entry = sums.entry(key).or_default();
+                    if in_spike {
+                        entry.1 += value;
+                    } else {
+                        entry.0 += value;
+                    }
+                }
+            }
+
+            // Missing from a sample counts as using nothing then
+            let mean = |sum: f64, count: usize| if count == 0 { 0.0 } else { sum / count as f64 };
+            let host = peak.map(|peak| HostChange {
+                baseline: mean(host_sums.0, baseline_samples),
+                during: mean(host_sums.1, samples),
+                peak,
+            });
+            let host_increase = host
+                .as_ref()
+                .map(|h| h.during - h.baseline)
+                .filter(|increase| shares && *increase > 0.0);
+
+            let mut contributors: Vec<SpikeContributor> = sums
+                .into_iter()
+                .map(|((kind, name), (before, during))| {
+                    let baseline = mean(before, baseline_samples);
+                    let during = mean(during, samples);
+                    let increase = during - baseline;
+                    SpikeContributor {
+                        kind,
+                        name,
+                        baseline,
+                        during,
+                        increase,
+                        share_percent: host_increase.map(|total| increase / total * 100.0),
+                    }
+                })
+                .filter(|c| c.increase > 0.0)
+                .collect();
+            contributors.sort_by(|a, b| b.increase.total_cmp(&a.increase));
+            contributors.truncate(limit);
+            SpikeBreakdown {
+                unit,
+                host,
+                contributors,
+            }
+        };
+
+        let cpu = breakdown("percent", false, &|_, host| {
+            let mut values: Vec<_> = host
+                .containers
+                .iter()
+                .map(|c| ((TalkerKind::Container, c.name.clone()), c.cpu.usage_percent))
+                .collect();
+            values.extend(
+                host.processes
+                    .iter()
+                    .filter(|p| p.container_id.is_none())
+                    .map(|p| ((TalkerKind::Process, p.label(redact)), p.cpu_percent)),
+            );
+            (Some(host.cpu.usage_percent), values)
+        });
+        let memory = breakdown("bytes", true, &|_, host| {
+            let mut values: Vec<_> = host
+                .containers
+                .iter()
+                .map(|c| {
+                    let key = (TalkerKind::Container, c.name.clone());
+                    (key, c.working_set_bytes() as f64)
+                })
+                .collect();
+            values.extend(
+                host.processes
+                    .iter()
+                    .filter(|p| p.container_id.is_none())
+                    .map(|p| {
+                        (
+                            (TalkerKind::Process, p.label(redact)),
+                            p.memory_bytes as f64,
+                        )
+                    }),
+            );
+            (Some(host.memory.used_bytes as f64), values)
+        });
+        let io = breakdown("bytes_per_second", false, &|previous, host| {
+            let Some(previous) = previous else {
+                return (None, Vec::new());
+            };
+            let seconds = (host.timestamp - previous.timestamp).num_milliseconds() as f64 / 1000.0;
+            if seconds <= 0.0 {
+                return (None, Vec::new());
+            }
+            let values = host
+                .containers
+                .iter()
+                .filter_map(|c| {
+                    let before = previous
+                        .containers
+                        .iter()
+                        .find(|b| b.identity() == c.identity())?;
+                    let bytes = counter_delta(
+                        before.block_io.read_bytes + before.block_io.write_bytes,
+                        c.block_io.read_bytes + c.block_io.write_bytes,
+                    );
+                    Some((
+                        (TalkerKind::Container, c.name.clone()),
+                        bytes as f64 / seconds,
+                    ))
+                })
+                .collect();
+            (None, values)
+        });
+
+        Some(Self {
+            from,
+            to,
+            samples,
+            baseline_samples,
+            cpu,
+            memory,
+            io,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{
+        Container, ContainerId, ContainerState, CpuMetrics, IoMetrics, MemoryMetrics, Process,
+        ProcessState,
+    };
+    use chrono::Duration;
+
+    #[test]
+    fn test_spike_is_blamed_on_what_grew() {
+        let start = Utc::now() - Duration::hours(1);
+        let history: Vec<Arc<Host>> = (0..20)
+            .map(|minute| {
+                let spike = minute >= 10;
+                let mut host =
+                    Host::new("test".to_string()).with_timestamp(start + Duration::minutes(minute));
+                host.cpu = CpuMetrics::new(if spike { 80.0 } else { 10.0 }, 0.0, 0.0);
+                host.memory = MemoryMetrics::new(if spike { 3000 } else { 1000 }, 8000, 0);
+                host.containers = ["backup", "web"]
+                    .into_iter()
+                    .map(|name| {
+                        let busy = spike && name == "backup";
+                        let mut container = Container::new(
+                            ContainerId::new(name.to_string()),
+                            name.to_string(),
+                            "image".to_string(),
+                            ContainerState::Running,
+                            start,
+                        );
+                        container.cpu = CpuMetrics::new(if busy { 150.0 } else { 5.0 }, 0.0, 0.0);
+                        container.memory =
+                            MemoryMetrics::new(0, 0, 0).with_rss(if busy { 1500 } else { 500 });
+                        // 1 MB/s while busy, a trickle otherwise
+                        let written = if name == "backup" && spike {
+                            540 + (minute as u64 - 9) * 60_000_000
+                        } else {
+                            minute as u64 * 60
+                        };
+                        container.block_io = IoMetrics::new(0, written);
+                        container
+                    })
+                    .collect();
+                Arc::new(host)
+            })
+            .collect();
+
+        let analysis = SpikeAnalysis::new(
+            &history,
+            start + Duration::minutes(10),
+            start + Duration::minutes(20),
+            5,
+            false,
+        )
+        .unwrap();
+        assert_eq!((analysis.samples, analysis.baseline_samples), (10, 10));
+
+        let host_cpu = analysis.cpu.host.as_ref().unwrap();
+        assert_eq!((host_cpu.baseline, host_cpu.during), (10.0, 80.0));
+        assert_eq!(analysis.cpu.contributors.len(), 1);
+        assert_eq!(analysis.cpu.contributors[0].name, "backup");
+        assert_eq!(analysis.cpu.contributors[0].increase, 145.0);
+
+        let memory = &analysis.memory.contributors[0];
+        assert_eq!((memory.name.as_str(), memory.increase), ("backup", 1000.0));
+        assert_eq!(memory.share_percent, Some(50.0));
+
+        assert!(analysis.io.host.is_none());
+        assert_eq!(analysis.io.contributors[0].name, "backup");
+        assert_eq!(analysis.io.contributors[0].during, 1_000_000.0);
+
+        let later = Utc::now() + Duration::hours(1);
+        assert!(
+            SpikeAnalysis::new(&history, later, later + Duration::minutes(5), 5, false).is_none()
+        );
+    }
+
+    #[test]
+    fn test_privacy_mode_names_processes_by_program() {
+        let start = Utc::now() - Duration::hours(1);
+        let history: Vec<Arc<Host>> = (0..4)
+            .map(|minute| {
+                let process = Process::new(
+                    42,
+                    1,
+                    "root".to_string(),
+                    "python3 sync.py --token s3cret".to_string(),
+                    ProcessState::Running,
+                )
+                .with_metrics(if minute >= 2 { 90.0 } else { 1.0 }, 0.0, 0);
+                Arc::new(
+                    Host::new("test".to_string())
+                        .with_processes(vec![process])
+                        .with_timestamp(start + Duration::minutes(minute)),
+                )
+            })
+            .collect();
+        let analyze = |redact| {
+            let (from, to) = (start + Duration::minutes(2), start + Duration::minutes(4));
+            SpikeAnalysis::new(&history, from, to, 5, redact).unwrap()
+        };
+
+        assert_eq!(
+            analyze(false).cpu.contributors[0].name,
+            "python3 sync.py --token s3cret (42)"
+        );
+        assert_eq!(analyze(true).cpu.contributors[0].name, "python3 (42)");
+    }
+}
@@ -0,0 +1,69 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// SSH login monitoring settings (`[ssh]` in the alert config)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SshWatch {
+    /// Path to the auth log, or `journald`
+    #[serde(default = "default_log")]
+    pub log: String,
+    /// Also report IPs currently banned by fail2ban's `sshd` jail
+    #[serde(default)]
+    pub fail2ban: bool,
+    /// Failed logins within `spike_window_minutes` that count as an attack
+    #[serde(default = "default_spike_threshold")]
+    pub spike_threshold: usize,
+    #[serde(default = "default_spike_window")]
+    pub spike_window_minutes: i64,
+    pub webhook_url: Option<String>,
+}
+
+fn default_log() -> String {
+    "/var/log/auth.log".to_string()
+}
+
+fn default_spike_threshold() -> usize {
+    20
+}
+
+fn default_spike_window() -> i64 {
+    10
+}
+
+/// One failed SSH authentication
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuthFailure {
+    pub timestamp: DateTime<Utc>,
+    pub ip: String,
+    pub user: String,
+}
+
+/// Failed login count for one source address
+#[derive(Debug, Clone, Serialize)]
+pub struct OffenderCount {
+    pub ip: String,
+    pub failures: usize,
+}
+
+/// Failed SSH logins over the retention window
+#[derive(Debug, Clone, Serialize)]
+pub struct SshSummary {
+    pub window_hours: i64,
+    pub failed_logins: usize,
+    /// Failures within the spike window
+    pub recent_failures: usize,
+    pub spike: bool,
+    pub top_offenders: Vec<OffenderCount>,
+    /// `None` when fail2ban is not queried or unavailable
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub banned_ips: Option<Vec<String>>,
+}
+
+impl SshSummary {
+    /// Keep the counters, drop the addresses
+    pub fn redacted(mut self) -> Self {
+        self.top_offenders.clear();
+        self.banned_ips = None;
+        self
+    }
+}
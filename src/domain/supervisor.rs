@@ -0,0 +1,120 @@
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use super::{Container, ContainerHealth, ContainerRuntime, ContainerState};
+
+/// Restarts the Docker containers it matches when they stay unhealthy or
+/// crash-loop, from `[[supervise]]` in the alert config
+#[derive(Debug, Clone, Deserialize)]
+pub struct RestartPolicy {
+    /// Container names or `stack/service/name` identities: exact, or a
+    /// prefix ending in `*`
+    pub containers: Vec<String>,
+    /// How long a container must keep failing before it is restarted
+    #[serde(default = "default_grace")]
+    pub grace_seconds: u64,
+    /// Restarts before giving up, until the container recovers
+    #[serde(default = "default_max_attempts")]
+    pub max_attempts: u32,
+    /// Wait after the first restart before the next, doubled after each one
+    #[serde(default = "default_backoff")]
+    pub backoff_seconds: u64,
+    /// How long a container must stay well for its attempts to start over
+    #[serde(default = "default_reset_after")]
+    pub reset_after_seconds: u64,
+    /// Webhook URLs, `plugin://`, `mailto:` or `telegram:` destinations told
+    /// about every restart
+    #[serde(default)]
+    pub notify: Vec<String>,
+}
+
+fn default_grace() -> u64 {
+    60
+}
+
+fn default_max_attempts() -> u32 {
+    3
+}
+
+fn default_backoff() -> u64 {
+    60
+}
+
+fn default_reset_after() -> u64 {
+    3600
+}
+
+impl RestartPolicy {
+    pub fn matches(&self, container: &Container) -> bool {
+        if container.runtime != ContainerRuntime::Docker {
+            return false;
+        }
+        let identity = container.identity();
+        self.containers
+            .iter()
+            .any(|pattern| match pattern.strip_suffix('*') {
+                Some(prefix) => container.name.starts_with(prefix) || identity.starts_with(prefix),
+                None => container.name == *pattern || identity == *pattern,
+            })
+    }
+
+    /// Wait after restart number `attempts` before another one
+    pub fn backoff(&self, attempts: u32) -> Duration {
+        let factor = 1u64 << attempts.saturating_sub(1).min(16);
+        Duration::from_secs(self.backoff_seconds.saturating_mul(factor))
+    }
+}
+
+/// Why `container` needs a restart, `None` while it is well or deliberately
+/// stopped
+pub fn container_failure(container: &Container) -> Option<&'static str> {
+    match (container.state, container.health) {
+        (ContainerState::Restarting, _) => Some("crash-looping"),
+        (ContainerState::Dead, _) => Some("dead"),
+        (ContainerState::Running, Some(ContainerHealth::Unhealthy)) => Some("unhealthy"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::ContainerId;
+    use chrono::Utc;
+
+    #[test]
+    fn test_policy_matches_and_backs_off() {
+        let policy: RestartPolicy = toml::from_str(
+            r#"
+containers = ["nextcloud", "media/*"]
+backoff_seconds = 30
+"#,
+        )
+        .unwrap();
+        let container = |name: &str, stack: Option<&str>| {
+            Container::new(
+                ContainerId::new(name.to_string()),
+                name.to_string(),
+                "image".to_string(),
+                ContainerState::Running,
+                Utc::now(),
+            )
+            .with_stack(stack.map(str::to_string))
+            .with_service(Some("app".to_string()))
+        };
+
+        assert!(policy.matches(&container("nextcloud", None)));
+        assert!(policy.matches(&container("plex", Some("media"))));
+        assert!(!policy.matches(&container("nextcloud-db", None)));
+        assert_eq!(policy.max_attempts, 3);
+        assert_eq!(
+            [1, 2, 3].map(|n| policy.backoff(n).as_secs()),
+            [30, 60, 120]
+        );
+
+        let unhealthy = container("nextcloud", None).with_health(Some(ContainerHealth::Unhealthy));
+        assert_eq!(container_failure(&unhealthy), Some("unhealthy"));
+        assert_eq!(container_failure(&container("plex", None)), None);
+    }
+}
@@ -0,0 +1,71 @@
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+
+/// Parse an IANA timezone name (`Europe/Paris`, `UTC`)
+pub fn parse_timezone(name: &str) -> Option<Tz> {
+    name.trim().parse().ok()
+}
+
+/// Parse a window such as `90s`, `15m`, `6h` or `1d`; a bare number is seconds
+pub fn parse_window(window: &str) -> Option<std::time::Duration> {
+    let window = window.trim();
+    let (number, unit) = match window.find(|c: char| !c.is_ascii_digit()) {
+        Some(split) => window.split_at(split),
+        None => (window, "s"),
+    };
+    let multiplier = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        "d" => 86400,
+        _ => return None,
+    };
+    let seconds = number.parse::<u64>().ok()?.checked_mul(multiplier)?;
+    Some(std::time::Duration::from_secs(seconds))
+}
+
+/// Human-readable wall-clock time in the display timezone
+pub fn format_local(ts: DateTime<Utc>, tz: Tz) -> String {
+    ts.with_timezone(&tz)
+        .format("%Y-%m-%d %H:%M:%S %Z")
+        .to_string()
+}
+
+/// UTC for machines plus local time for humans, as used in messages
+pub fn format_utc_and_local(ts: DateTime<Utc>, tz: Tz) -> String {
+    let utc = ts.to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+    if tz == Tz::UTC {
+        utc
+    } else {
+        format!("{} ({})", utc, format_local(ts, tz))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_window() {
+        assert_eq!(parse_window("15m").unwrap().as_secs(), 900);
+        assert_eq!(parse_window("2h").unwrap().as_secs(), 7200);
+        assert_eq!(parse_window("45").unwrap().as_secs(), 45);
+        assert!(parse_window("15 minutes").is_none());
+        assert!(parse_window("m").is_none());
+    }
+
+    #[test]
+    fn test_format_utc_and_local() {
+        let ts = DateTime::parse_from_rfc3339("2026-01-07T13:32:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let paris = parse_timezone("Europe/Paris").unwrap();
+
+        assert_eq!(
+            format_utc_and_local(ts, paris),
+            "2026-01-07T13:32:00Z (2026-01-07 14:32:00 CET)"
+        );
+        assert_eq!(format_utc_and_local(ts, Tz::UTC), "2026-01-07T13:32:00Z");
+        assert!(parse_timezone("Mars/Olympus").is_none());
+    }
+}
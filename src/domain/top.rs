@@ -0,0 +1,216 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use super::{rate_per_second, Host};
+
+/// Throughput counted as a fully busy interface; link speeds are not known here
+const INTERFACE_CAPACITY_BYTES_PER_SECOND: f64 = 125_000_000.0;
+
+/// Horizon over which disk fill rates are projected
+const DISK_HORIZON_SECONDS: f64 = 24.0 * 3600.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TalkerKind {
+    Process,
+    Container,
+    Disk,
+    Interface,
+}
+
+/// One heavy consumer in the triage list.
+/// `score` is a percentage of the relevant capacity, so kinds can be ranked together:
+/// a process's CPU share of one core, a container's memory growth against its limit,
+/// the share of a disk's free space filled within a day at the current rate, and
+/// an interface's throughput against 1 Gbit/s.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Talker {
+    pub kind: TalkerKind,
+    pub name: String,
+    pub value: f64,
+    /// Unit of `value`
+    pub unit: String,
+    pub score: f64,
+}
+
+impl Talker {
+    fn new(kind: TalkerKind, name: String, value: f64, unit: &str, score: f64) -> Self {
+        Self {
+            kind,
+            name,
+            value,
+            unit: unit.to_string(),
+            score,
+        }
+    }
+}
+
+/// Heaviest consumers across processes, containers, disks and interfaces over
+/// `history` (oldest first), highest score first; idle ones are left out.
+/// Processes are named by program only when `redact` (privacy mode).
+pub fn top_talkers(history: &[Arc<Host>], limit: usize, redact: bool) -> Vec<Talker> {
+    let (Some(first), Some(latest)) = (history.first(), history.last()) else {
+        return Vec::new();
+    };
+    let span = latest
+        .timestamp
+        .signed_duration_since(first.timestamp)
+        .num_milliseconds() as f64
+        / 1000.0;
+    let mut talkers = Vec::new();
+
+    for process in &latest.processes {
+        talkers.push(Talker::new(
+            TalkerKind::Process,
+            process.label(redact),
+            process.cpu_percent,
+            "cpu_percent",
+            process.cpu_percent,
+        ));
+    }
+
+    for container in latest.containers.iter().filter(|c| c.state.is_running()) {
+        let identity = container.identity();
+        let Some(before) = first.containers.iter().find(|c| c.identity() == identity) else {
+            continue;
+        };
+        let growth = container
+            .memory
+            .used_bytes
+            .saturating_sub(before.memory.used_bytes);
+        let limit_bytes = match container.memory.total_bytes {
+            0 => latest.memory.total_bytes,
+            limit => limit,
+        };
+        if limit_bytes > 0 {
+            talkers.push(Talker::new(
+                TalkerKind::Container,
+                container.name.clone(),
+                growth as f64,
+                "bytes_grown",
+                growth as f64 / limit_bytes as f64 * 100.0,
+            ));
+        }
+    }
+
+    let mut disks: BTreeMap<&str, Vec<_>> = BTreeMap::new();
+    for snapshot in history {
+        for disk in &snapshot.disks {
+            disks
+                .entry(disk.mount_point.as_str())
+                .or_default()
+                .push((snapshot.timestamp, disk.used_bytes));
+        }
+    }
+    for disk in &latest.disks {
+        let Some(rate) = disks
+            .get(disk.mount_point.as_str())
+            .and_then(|points| rate_per_second(points))
+        else {
+            continue;
+        };
+        if disk.available_bytes > 0 {
+            talkers.push(Talker::new(
+                TalkerKind::Disk,
+                disk.mount_point.clone(),
+                rate * 3600.0,
+                "bytes_per_hour",
+                rate * DISK_HORIZON_SECONDS / disk.available_bytes as f64 * 100.0,
+            ));
+        }
+    }
+
+    if span > 0.0 {
+        for interface in &latest.network_interfaces {
+            let Some(before) = first
+                .network_interfaces
+                .iter()
+                .find(|i| i.name == interface.name)
+            else {
+                continue;
+            };
+            // Counters start over when an interface is recreated
+            let bytes = (interface.metrics.rx_bytes + interface.metrics.tx_bytes)
+                .saturating_sub(before.metrics.rx_bytes + before.metrics.tx_bytes);
+            let throughput = bytes as f64 / span;
+            talkers.push(Talker::new(
+                TalkerKind::Interface,
+                interface.name.clone(),
+                throughput,
+                "bytes_per_second",
+                throughput / INTERFACE_CAPACITY_BYTES_PER_SECOND * 100.0,
+            ));
+        }
+    }
+
+    talkers.retain(|t| t.score > 0.0);
+    talkers.sort_by(|a, b| b.score.total_cmp(&a.score));
+    talkers.truncate(limit);
+    talkers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{Disk, NetworkInterface, NetworkMetrics, Process, ProcessState};
+    use chrono::{Duration, Utc};
+
+    fn snapshot(minutes: i64, disk_used: u64, rx_bytes: u64) -> Arc<Host> {
+        Arc::new(
+            Host::new("nas".to_string())
+                .with_disks(vec![Disk::new(
+                    "/dev/sda1".to_string(),
+                    "/srv".to_string(),
+                    "ext4".to_string(),
+                    1000 * 1024 * 1024,
+                    disk_used,
+                    1000 * 1024 * 1024 - disk_used,
+                )])
+                .with_network_interfaces(vec![NetworkInterface::new(
+                    "eth0".to_string(),
+                    true,
+                    NetworkMetrics::new(rx_bytes, 0, 0, 0),
+                )])
+                .with_timestamp(Utc::now() + Duration::minutes(minutes)),
+        )
+    }
+
+    #[test]
+    fn test_ranks_across_kinds() {
+        // The disk fills 10 MB per minute out of ~900 MB free: full well within a day.
+        // eth0 moves 1 MB/s, under 1% of a gigabit link.
+        let mb = 1024 * 1024;
+        let history = [snapshot(0, 100 * mb, 0), snapshot(10, 200 * mb, 600 * mb)];
+
+        let talkers = top_talkers(&history, 10, false);
+        assert_eq!(talkers.len(), 2);
+        assert_eq!(talkers[0].kind, TalkerKind::Disk);
+        assert_eq!(talkers[1].kind, TalkerKind::Interface);
+        assert!((talkers[1].value - mb as f64).abs() < 1.0);
+
+        assert_eq!(top_talkers(&history, 1, false).len(), 1);
+        assert!(top_talkers(&[], 10, false).is_empty());
+    }
+
+    #[test]
+    fn test_privacy_mode_names_processes_by_program() {
+        let process = Process::new(
+            42,
+            1,
+            "root".to_string(),
+            "/usr/bin/backup --password hunter2".to_string(),
+            ProcessState::Running,
+        )
+        .with_metrics(80.0, 1.0, 1024);
+        let history = [Arc::new(
+            Host::new("nas".to_string()).with_processes(vec![process]),
+        )];
+
+        let talkers = top_talkers(&history, 10, false);
+        assert_eq!(talkers[0].name, "/usr/bin/backup --password hunter2 (42)");
+        let talkers = top_talkers(&history, 10, true);
+        assert_eq!(talkers[0].name, "backup (42)");
+    }
+}
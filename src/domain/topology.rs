@@ -0,0 +1,159 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Group name for hosts placed in no `group`
+pub const UNGROUPED: &str = "ungrouped";
+
+/// Where a host sits: a room or rack `group`, and the `parent` host it runs on
+/// (a VM on its hypervisor)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TopologyEntry {
+    pub host: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+}
+
+/// Placement of every configured host, keyed by hostname
+#[derive(Debug, Clone, Default)]
+pub struct Topology {
+    entries: BTreeMap<String, TopologyEntry>,
+}
+
+impl Topology {
+    /// Fails on duplicate hosts and parent cycles
+    pub fn new(entries: Vec<TopologyEntry>) -> Result<Self, String> {
+        let mut by_host = BTreeMap::new();
+        for entry in entries {
+            if entry.parent.as_deref() == Some(entry.host.as_str()) {
+                return Err(format!("Host '{}' is its own parent", entry.host));
+            }
+            if let Some(previous) = by_host.insert(entry.host.clone(), entry) {
+                return Err(format!("Host '{}' is placed twice", previous.host));
+            }
+        }
+
+        let topology = Self { entries: by_host };
+        for host in topology.entries.keys() {
+            let mut current = host.as_str();
+            for _ in 0..=topology.entries.len() {
+                match topology.parent(current) {
+                    Some(parent) if parent == host => {
+                        return Err(format!("Host '{}' is its own ancestor", host))
+                    }
+                    Some(parent) => current = parent,
+                    None => break,
+                }
+            }
+        }
+        Ok(topology)
+    }
+
+    pub fn hosts(&self) -> impl Iterator<Item = &str> {
+        self.entries.keys().map(String::as_str)
+    }
+
+    pub fn group(&self, host: &str) -> &str {
+        self.entries
+            .get(host)
+            .and_then(|e| e.group.as_deref())
+            .unwrap_or(UNGROUPED)
+    }
+
+    pub fn parent(&self, host: &str) -> Option<&str> {
+        self.entries.get(host)?.parent.as_deref()
+    }
+
+    pub fn children(&self, host: &str) -> Vec<String> {
+        self.entries
+            .values()
+            .filter(|e| e.parent.as_deref() == Some(host))
+            .map(|e| e.host.clone())
+            .collect()
+    }
+
+    /// Closest ancestor that `is_down`, which explains this host being unreachable
+    pub fn down_ancestor(&self, host: &str, is_down: impl Fn(&str) -> bool) -> Option<&str> {
+        let mut current = self.parent(host);
+        while let Some(parent) = current {
+            if is_down(parent) {
+                return Some(parent);
+            }
+            current = self.parent(parent);
+        }
+        None
+    }
+}
+
+/// Reachability of a host in the topology
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HostState {
+    Up,
+    /// Stopped reporting
+    Down,
+    /// Configured but never reported
+    Unknown,
+}
+
+/// A host as shown in a topology group
+#[derive(Debug, Clone, Serialize)]
+pub struct TopologyHost {
+    pub name: String,
+    pub state: HostState,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_seen: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<String>,
+    /// Down ancestor that explains this host's outage; its alerts are suppressed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suppressed_by: Option<String>,
+}
+
+/// Hosts of one room or rack
+#[derive(Debug, Clone, Serialize)]
+pub struct TopologyGroup {
+    pub name: String,
+    pub hosts: Vec<TopologyHost>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(host: &str, parent: Option<&str>) -> TopologyEntry {
+        TopologyEntry {
+            host: host.to_string(),
+            group: Some("rack1".to_string()),
+            parent: parent.map(String::from),
+        }
+    }
+
+    #[test]
+    fn test_cascade_and_cycles() {
+        let topology = Topology::new(vec![
+            entry("pve", None),
+            entry("vm-media", Some("pve")),
+            entry("docker-in-vm", Some("vm-media")),
+        ])
+        .unwrap();
+
+        assert_eq!(topology.children("pve"), vec!["vm-media"]);
+        assert_eq!(topology.group("pve"), "rack1");
+        assert_eq!(topology.group("laptop"), UNGROUPED);
+        assert_eq!(
+            topology.down_ancestor("docker-in-vm", |h| h == "pve"),
+            Some("pve")
+        );
+        assert_eq!(topology.down_ancestor("pve", |_| true), None);
+
+        assert!(Topology::new(vec![entry("a", Some("b")), entry("b", Some("a"))]).is_err());
+        assert!(Topology::new(vec![entry("a", Some("a"))]).is_err());
+        assert!(Topology::new(vec![entry("a", None), entry("a", None)]).is_err());
+    }
+}
@@ -0,0 +1,171 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Minimum number of samples before a trend is computed
+const MIN_SAMPLES: usize = 10;
+
+/// Minimum time span covered by the samples (10 minutes)
+const MIN_SPAN_SECONDS: f64 = 600.0;
+
+/// Share of sample-to-sample deltas that must be non-negative to call growth monotonic
+const MONOTONIC_RATIO: f64 = 0.9;
+
+/// Minimum growth rate to be flagged as a likely leak (1 MiB/h)
+const LEAK_MIN_BYTES_PER_HOUR: f64 = 1024.0 * 1024.0;
+
+/// Minimum time span for a rate of change; shorter ones are mostly noise
+const MIN_RATE_SPAN_SECONDS: f64 = 60.0;
+
+/// Average change per second between the oldest and newest `(timestamp, value)` sample.
+/// Returns `None` when the samples span less than a minute.
+pub fn rate_per_second(samples: &[(DateTime<Utc>, u64)]) -> Option<f64> {
+    let (first, last) = (samples.first()?, samples.last()?);
+    let span = last.0.signed_duration_since(first.0).num_milliseconds() as f64 / 1000.0;
+    if span < MIN_RATE_SPAN_SECONDS {
+        return None;
+    }
+    Some((last.1 as f64 - first.1 as f64) / span)
+}
+
+/// Change in used space of one mount point
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiskRate {
+    pub mount_point: String,
+    pub used_bytes_per_hour: f64,
+}
+
+/// First derivatives of host usage, so a UI can show trend arrows without history
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UsageRates {
+    pub memory_used_bytes_per_minute: Option<f64>,
+    /// Only mount points with enough history
+    pub disks: Vec<DiskRate>,
+}
+
+/// Memory usage trend computed from historical samples
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryTrend {
+    pub growth_bytes_per_hour: f64,
+    pub samples: usize,
+    pub likely_leak: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hours_to_limit: Option<f64>,
+}
+
+impl MemoryTrend {
+    /// Fit a linear trend over `(timestamp, used_bytes)` samples (oldest first).
+    /// Returns `None` when there is not enough data to say anything useful.
+    pub fn from_samples(samples: &[(DateTime<Utc>, u64)], limit_bytes: u64) -> Option<Self> {
+        if samples.len() < MIN_SAMPLES {
+            return None;
+        }
+
+        let origin = samples[0].0;
+        let points: Vec<(f64, f64)> = samples
+            .iter()
+            .map(|(t, v)| {
+                let secs = t.signed_duration_since(origin).num_milliseconds() as f64 / 1000.0;
+                (secs, *v as f64)
+            })
+            .collect();
+
+        let span = points.last()?.0;
+        if span < MIN_SPAN_SECONDS {
+            return None;
+        }
+
+        // Least-squares slope in bytes per second
+        let n = points.len() as f64;
+        let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+        let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+        let covariance: f64 = points
+            .iter()
+            .map(|(x, y)| (x - mean_x) * (y - mean_y))
+            .sum();
+        let variance: f64 = points.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+        if variance == 0.0 {
+            return None;
+        }
+        let growth_bytes_per_hour = covariance / variance * 3600.0;
+
+        let non_decreasing = samples.windows(2).filter(|w| w[1].1 >= w[0].1).count();
+        let monotonic = non_decreasing as f64 / (samples.len() - 1) as f64 >= MONOTONIC_RATIO;
+
+        let likely_leak = monotonic && growth_bytes_per_hour >= LEAK_MIN_BYTES_PER_HOUR;
+
+        let current = samples.last()?.1;
+        let hours_to_limit = if likely_leak && limit_bytes > current {
+            Some((limit_bytes - current) as f64 / growth_bytes_per_hour)
+        } else {
+            None
+        };
+
+        Some(Self {
+            growth_bytes_per_hour,
+            samples: samples.len(),
+            likely_leak,
+            hours_to_limit,
+        })
+    }
+
+    /// Human-readable summary, e.g. "likely leak, +40MB/h, will hit limit in ~9h"
+    pub fn summary(&self) -> String {
+        let rate = format!("+{:.0}MB/h", self.growth_bytes_per_hour / (1024.0 * 1024.0));
+        match self.hours_to_limit {
+            Some(hours) => format!(
+                "likely leak, {}, will hit limit in ~{:.0}h",
+                rate,
+                hours.max(1.0)
+            ),
+            None => format!("likely leak, {}", rate),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MB: u64 = 1024 * 1024;
+
+    fn series(step_bytes: i64, count: usize) -> Vec<(DateTime<Utc>, u64)> {
+        let start = Utc::now();
+        (0..count)
+            .map(|i| {
+                let t = start + chrono::Duration::seconds(60 * i as i64);
+                let v = (100 * MB as i64 + step_bytes * i as i64) as u64;
+                (t, v)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_steady_growth_is_flagged() {
+        // +1 MB per minute = 60 MB/h
+        let trend = MemoryTrend::from_samples(&series(MB as i64, 30), 1024 * MB).unwrap();
+        assert!(trend.likely_leak);
+        assert!((trend.growth_bytes_per_hour / MB as f64 - 60.0).abs() < 0.1);
+        assert!(trend.hours_to_limit.unwrap() > 14.0);
+    }
+
+    #[test]
+    fn test_flat_usage_is_not_flagged() {
+        let trend = MemoryTrend::from_samples(&series(0, 30), 1024 * MB).unwrap();
+        assert!(!trend.likely_leak);
+        assert!(trend.hours_to_limit.is_none());
+    }
+
+    #[test]
+    fn test_rate_per_second() {
+        // +1 MB per minute, shrinking is negative
+        let rate = rate_per_second(&series(MB as i64, 3)).unwrap();
+        assert!((rate * 60.0 - MB as f64).abs() < 1.0);
+        assert!(rate_per_second(&series(-(MB as i64), 3)).unwrap() < 0.0);
+        assert!(rate_per_second(&series(MB as i64, 1)).is_none());
+    }
+
+    #[test]
+    fn test_too_few_samples() {
+        assert!(MemoryTrend::from_samples(&series(MB as i64, 5), 1024 * MB).is_none());
+    }
+}
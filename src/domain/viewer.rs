@@ -0,0 +1,100 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use serde::Deserialize;
+
+use super::{Container, ContainerId};
+
+/// A read-only API token from `[[viewers]]` in the alert config. Without
+/// `stacks` or `containers` it sees everything; with them, only those
+/// containers (by name or compose project) and their processes.
+#[derive(Clone, Deserialize)]
+pub struct Viewer {
+    pub name: String,
+    pub token: String,
+    #[serde(default)]
+    pub stacks: Vec<String>,
+    #[serde(default)]
+    pub containers: Vec<String>,
+}
+
+impl fmt::Debug for Viewer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Viewer")
+            .field("name", &self.name)
+            .field("stacks", &self.stacks)
+            .field("containers", &self.containers)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Viewer {
+    /// Limited to some containers rather than seeing the whole host
+    pub fn is_scoped(&self) -> bool {
+        !self.stacks.is_empty() || !self.containers.is_empty()
+    }
+
+    pub fn sees(&self, container: &Container) -> bool {
+        !self.is_scoped()
+            || self.containers.contains(&container.name)
+            || container
+                .stack
+                .as_ref()
+                .is_some_and(|stack| self.stacks.contains(stack))
+    }
+
+    pub fn sees_stack(&self, stack: &str) -> bool {
+        !self.is_scoped() || self.stacks.iter().any(|s| s == stack)
+    }
+
+    /// IDs of the visible ones among `containers`, to match their processes
+    pub fn visible_ids(&self, containers: &[Container]) -> HashSet<ContainerId> {
+        containers
+            .iter()
+            .filter(|c| self.sees(c))
+            .map(|c| c.id.clone())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::ContainerState;
+
+    #[test]
+    fn test_scoped_viewer_sees_its_stack_and_containers() {
+        let container = |name: &str, stack: Option<&str>| {
+            Container::new(
+                ContainerId::new(name),
+                name.to_string(),
+                "image".to_string(),
+                ContainerState::Running,
+                chrono::Utc::now(),
+            )
+            .with_stack(stack.map(str::to_string))
+        };
+        let friend: Viewer = toml::from_str(
+            r#"
+            name = "friend"
+            token = "s3cret"
+            stacks = ["media"]
+            containers = ["minecraft"]
+            "#,
+        )
+        .unwrap();
+        assert!(friend.is_scoped());
+        assert!(friend.sees(&container("jellyfin", Some("media"))));
+        assert!(friend.sees(&container("minecraft", None)));
+        assert!(!friend.sees(&container("db", Some("nextcloud"))));
+        assert!(!friend.sees_stack("nextcloud"));
+        assert!(!format!("{:?}", friend).contains("s3cret"));
+
+        let everyone = Viewer {
+            stacks: Vec::new(),
+            containers: Vec::new(),
+            ..friend
+        };
+        assert!(everyone.sees(&container("db", Some("nextcloud"))));
+    }
+}
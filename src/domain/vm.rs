@@ -0,0 +1,52 @@
+use serde::{Deserialize, Serialize};
+
+/// Virtualization stack VMs are listed from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Hypervisor {
+    /// `virsh domstats`, for KVM/QEMU hosts managed by libvirt
+    Libvirt,
+    /// `pvesh`, on a Proxmox VE node
+    Proxmox,
+}
+
+impl Hypervisor {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "libvirt" | "virsh" | "kvm" => Some(Self::Libvirt),
+            "proxmox" | "pve" => Some(Self::Proxmox),
+            _ => None,
+        }
+    }
+}
+
+/// Run state of a virtual machine
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VmState {
+    Running,
+    Paused,
+    Stopped,
+    Other,
+}
+
+/// A guest of the hypervisor, with its allocation and current usage
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VirtualMachine {
+    pub name: String,
+    /// Hypervisor identifier, e.g. the Proxmox VMID
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub state: VmState,
+    pub vcpus: u32,
+    pub memory_max_bytes: u64,
+    /// Memory in use, as reported by the balloon driver or the hypervisor
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory_used_bytes: Option<u64>,
+    /// Share of the allocated vCPUs, needs two samples for libvirt
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu_percent: Option<f64>,
+    /// Cumulative guest CPU time, used to derive `cpu_percent`
+    #[serde(skip)]
+    pub cpu_time_ns: Option<u64>,
+}
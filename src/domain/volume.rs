@@ -0,0 +1,67 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// How a container mount is backed on the host
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MountKind {
+    Volume,
+    Bind,
+    Tmpfs,
+    Other,
+}
+
+/// A container mount mapped to its host path
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContainerMount {
+    pub kind: MountKind,
+    /// Volume name, `None` for bind mounts
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Path on the host
+    pub source: String,
+    /// Path inside the container
+    pub destination: String,
+    pub read_only: bool,
+}
+
+impl ContainerMount {
+    /// Only mounts backed by a host directory can be measured
+    pub fn is_measurable(&self) -> bool {
+        matches!(self.kind, MountKind::Volume | MountKind::Bind) && !self.source.is_empty()
+    }
+}
+
+/// du-style usage of a directory tree
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DirectoryUsage {
+    /// Allocated bytes (st_blocks), like `du`
+    pub used_bytes: u64,
+    pub files: u64,
+    /// Scan stopped at the entry cap; `used_bytes` is a lower bound
+    pub truncated: bool,
+    /// Some directories were unreadable (unprivileged NanoMon); `used_bytes` is a lower bound
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub partial: bool,
+    pub measured_at: DateTime<Utc>,
+}
+
+/// Disk usage of one container mount
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VolumeUsage {
+    #[serde(flatten)]
+    pub mount: ContainerMount,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub usage: Option<DirectoryUsage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl VolumeUsage {
+    /// Hide the host path, which often reveals user and share names
+    pub fn redacted(mut self) -> Self {
+        self.mount.source.clear();
+        self.error = None;
+        self
+    }
+}
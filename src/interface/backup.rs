@@ -0,0 +1,200 @@
+//! Backups of NanoMon's own files (alert config, state file and snapshot
+//! archive with its annotations) as a tar.gz, and `nanomon restore` to put
+//! them back.
+
+use std::path::PathBuf;
+
+use crate::config::Config;
+
+const USAGE: &str = "usage: nanomon restore <backup.tar.gz>";
+
+/// Names inside a backup and the variable configuring where each is kept
+const FILES: [(&str, &str); 3] = [
+    ("alerts.toml", "NANOMON_ALERT_CONFIG"),
+    ("state.json", "NANOMON_STATE_FILE"),
+    ("history.archive", "NANOMON_ARCHIVE_PATH"),
+];
+
+/// The files worth keeping, each under a fixed name inside the backup so it
+/// can be restored to wherever the restoring instance is configured to keep it
+#[derive(Debug, Clone, Default)]
+pub struct DataFiles {
+    #[cfg_attr(not(feature = "backup"), allow(dead_code))]
+    files: Vec<(&'static str, PathBuf)>,
+}
+
+impl DataFiles {
+    pub fn from_config(config: &Config) -> Self {
+        let paths = [
+            &config.alert_config_path,
+            &config.state_file,
+            &config.archive_path,
+        ];
+        Self {
+            files: FILES
+                .iter()
+                .zip(paths)
+                .filter_map(|((name, _), path)| Some((*name, path.clone()?)))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(feature = "backup")]
+mod archive {
+    use std::ffi::OsString;
+    use std::fs::{self, File};
+    use std::io::{self, Read, Write};
+    use std::path::{Path, PathBuf};
+
+    use flate2::read::GzDecoder;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+
+    use super::DataFiles;
+
+    impl DataFiles {
+        /// Writes a tar.gz of the configured files that exist, copying each
+        /// as it goes rather than reading it whole; returns their names
+        pub fn write_backup(&self, out: impl Write) -> io::Result<Vec<&'static str>> {
+            let mut tar = tar::Builder::new(GzEncoder::new(out, Compression::default()));
+            let mut written = Vec::new();
+            for (name, path) in &self.files {
+                let file = match File::open(path) {
+                    Ok(file) => file,
+                    Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+                    Err(e) => return Err(e),
+                };
+                // Copy only the length stated now: the archive may grow while
+                // it is copied, and a trailing partial record is ignored when
+                // it is restored
+                let len = file.metadata()?.len();
+                let mut header = tar::Header::new_gnu();
+                header.set_size(len);
+                header.set_mode(0o600);
+                header.set_mtime(chrono::Utc::now().timestamp().max(0) as u64);
+                tar.append_data(&mut header, name, file.take(len))?;
+                written.push(*name);
+            }
+            tar.into_inner()?.finish()?.flush()?;
+            Ok(written)
+        }
+
+        /// Replaces the configured files with those in a backup. Returns the
+        /// restored paths and, for files in the backup with nowhere to go, the
+        /// variable to set.
+        pub fn restore(&self, input: impl Read) -> io::Result<(Vec<PathBuf>, Vec<&'static str>)> {
+            let mut tar = tar::Archive::new(GzDecoder::new(input));
+            let (mut restored, mut skipped) = (Vec::new(), Vec::new());
+            for entry in tar.entries()? {
+                let mut entry = entry?;
+                let name = entry.path()?.to_string_lossy().into_owned();
+                match self.files.iter().find(|(n, _)| *n == name) {
+                    Some((_, path)) => {
+                        replace(path, &mut entry)?;
+                        restored.push(path.clone());
+                    }
+                    None => {
+                        if let Some((_, var)) = super::FILES.iter().find(|(n, _)| *n == name) {
+                            skipped.push(*var);
+                        }
+                    }
+                }
+            }
+            Ok((restored, skipped))
+        }
+    }
+
+    /// Writes next to `path` and renames, so an interrupted restore leaves
+    /// the previous file intact
+    fn replace(path: &Path, content: &mut impl Read) -> io::Result<()> {
+        let mut tmp = OsString::from(path);
+        tmp.push(".restoring");
+        let tmp = PathBuf::from(tmp);
+        let mut file = File::create(&tmp)?;
+        io::copy(content, &mut file)?;
+        file.sync_all()?;
+        fs::rename(&tmp, path)
+    }
+}
+
+/// `nanomon restore <file>`: put the files of a backup where this
+/// configuration keeps them. Returns the process exit code.
+pub fn run(config: &Config, args: &[String]) -> i32 {
+    let [file] = args else {
+        eprintln!("{}", USAGE);
+        return 2;
+    };
+    #[cfg(not(feature = "backup"))]
+    {
+        let _ = (config, file);
+        eprintln!("Built without the backup feature");
+        1
+    }
+    #[cfg(feature = "backup")]
+    {
+        let input = match std::fs::File::open(file) {
+            Ok(input) => input,
+            Err(e) => {
+                eprintln!("Cannot open {}: {}", file, e);
+                return 1;
+            }
+        };
+        println!(
+            "Restoring {}; NanoMon should not be running meanwhile",
+            file
+        );
+        match DataFiles::from_config(config).restore(input) {
+            Ok((restored, skipped)) => {
+                for path in &restored {
+                    println!("restored {}", path.display());
+                }
+                for var in &skipped {
+                    println!("skipped a file: set {} to restore it", var);
+                }
+                i32::from(restored.is_empty())
+            }
+            Err(e) => {
+                eprintln!("Restore failed: {}", e);
+                1
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "backup"))]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_backup_round_trip() {
+        let dir = std::env::temp_dir().join(format!("nanomon-backup-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let (alerts, archive) = (dir.join("alerts.toml"), dir.join("history.bin"));
+        fs::write(&alerts, "[[rules]]").unwrap();
+        let files = DataFiles {
+            files: vec![
+                ("alerts.toml", alerts.clone()),
+                // Configured but not written yet
+                ("history.archive", archive.clone()),
+            ],
+        };
+
+        let mut backup = Vec::new();
+        assert_eq!(files.write_backup(&mut backup).unwrap(), ["alerts.toml"]);
+
+        fs::write(&alerts, "changed").unwrap();
+        let elsewhere = DataFiles {
+            files: vec![("history.archive", archive)],
+        };
+        let (restored, skipped) = elsewhere.restore(backup.as_slice()).unwrap();
+        assert!(restored.is_empty());
+        assert_eq!(skipped, ["NANOMON_ALERT_CONFIG"]);
+
+        let (restored, _) = files.restore(backup.as_slice()).unwrap();
+        assert_eq!(restored, vec![alerts.clone()]);
+        assert_eq!(fs::read_to_string(&alerts).unwrap(), "[[rules]]");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
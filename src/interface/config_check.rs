@@ -0,0 +1,557 @@
+//! `nanomon --check-config`: validates the `NANOMON_*` variables and the alert
+//! config file, reporting what NanoMon would otherwise ignore or replace by
+//! its default without a word.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use serde::Serialize;
+use toml_edit::{ImDocument, TableLike};
+
+use crate::config::{AlertConfig, Config, EnvValue, EnvVar, ENV_VARS};
+use crate::domain::Topology;
+
+/// Destinations a notification route can send to
+const NOTIFY_SCHEMES: &[&str] = &["http://", "https://", "plugin://", "mailto:", "telegram:"];
+
+/// Something wrong in the configuration, and where
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Problem {
+    /// A variable name, or `file:line:column`
+    pub location: String,
+    pub message: String,
+}
+
+impl Problem {
+    fn new(location: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            location: location.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.location, self.message)
+    }
+}
+
+/// Values that would be ignored and `NANOMON_*` names NanoMon does not read
+pub fn check_env(vars: impl IntoIterator<Item = (String, String)>) -> Vec<Problem> {
+    let mut problems = Vec::new();
+    for (name, value) in vars {
+        let Some(var) = ENV_VARS.iter().find(|v| v.name == name) else {
+            if name.starts_with("NANOMON_") {
+                let hint = closest(&name)
+                    .map(|known| format!(", did you mean {}?", known))
+                    .unwrap_or_default();
+                problems.push(Problem::new(&name, format!("unknown variable{}", hint)));
+            }
+            continue;
+        };
+        problems.extend(value_error(var, &value).map(|e| Problem::new(&name, e)));
+    }
+    problems.sort_by(|a, b| a.location.cmp(&b.location));
+    problems
+}
+
+/// Why `value` would not be used for `var`
+fn value_error(var: &EnvVar, value: &str) -> Option<String> {
+    match var.value {
+        EnvValue::Text => None,
+        EnvValue::Unsigned => value
+            .parse::<u64>()
+            .err()
+            .map(|_| format!("'{}' is not a whole number, the default is used", value)),
+        EnvValue::Port => value
+            .parse::<u16>()
+            .err()
+            .map(|_| format!("'{}' is not a port number, the default is used", value)),
+        EnvValue::Flag => (!["true", "1", "false", "0"].contains(&value))
+            .then(|| format!("'{}' reads as false, use true or false", value)),
+        EnvValue::OneOf(choices) => (!choices.contains(&value))
+            .then(|| format!("'{}' is not one of {}", value, choices.join(", "))),
+        EnvValue::Check(_, check) => check(value).err().map(|e| format!("'{}': {}", value, e)),
+    }
+}
+
+/// A supported variable as `/api/config/schema` lists it
+#[derive(Debug, Serialize)]
+pub struct SettingSchema {
+    pub name: &'static str,
+    /// `string`, `integer`, `port`, `boolean` or `enum`
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub choices: Option<&'static [&'static str]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<&'static str>,
+    pub default: Option<String>,
+    /// What NanoMon runs with: the variable when set and valid, else the
+    /// default; secrets are masked
+    pub value: Option<String>,
+    pub set: bool,
+    /// Why the variable's value is not used
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub secret: bool,
+}
+
+/// Every supported variable with its type, default and effective value
+/// given the environment `vars`
+pub fn schema(vars: impl IntoIterator<Item = (String, String)>) -> Vec<SettingSchema> {
+    let vars: HashMap<String, String> = vars.into_iter().collect();
+    ENV_VARS
+        .iter()
+        .map(|var| {
+            let (kind, choices, format) = match var.value {
+                EnvValue::Text => ("string", None, None),
+                EnvValue::Unsigned => ("integer", None, None),
+                EnvValue::Port => ("port", None, None),
+                EnvValue::Flag => ("boolean", None, None),
+                EnvValue::OneOf(choices) => ("enum", Some(choices), None),
+                EnvValue::Check(format, _) => ("string", None, Some(format)),
+            };
+            let default = var
+                .default
+                .map(str::to_string)
+                .or_else(|| var.default_list.map(|list| list.join(",")));
+            let set = vars.get(var.name);
+            let error = set.and_then(|value| value_error(var, value));
+            let value = match set {
+                Some(_) if var.secret => Some("***".to_string()),
+                Some(value) if error.is_none() => Some(value.clone()),
+                _ => default.clone(),
+            };
+            SettingSchema {
+                name: var.name,
+                kind,
+                choices,
+                format,
+                default,
+                value,
+                set: set.is_some(),
+                error,
+                secret: var.secret,
+            }
+        })
+        .collect()
+}
+
+/// The known variable `name` is most likely a typo of
+fn closest(name: &str) -> Option<&'static str> {
+    ENV_VARS
+        .iter()
+        .map(|var| (edit_distance(name, var.name), var.name))
+        .filter(|(distance, _)| *distance <= 3)
+        .min()
+        .map(|(_, known)| known)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = (diagonal + usize::from(ca != *cb))
+                .min(above + 1)
+                .min(row[j] + 1);
+            diagonal = above;
+        }
+    }
+    row[b.len()]
+}
+
+/// One step into a TOML document
+#[derive(Debug)]
+enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+fn segments(path: &serde_ignored::Path, out: &mut Vec<Segment>) {
+    use serde_ignored::Path;
+    match path {
+        Path::Root => {}
+        Path::Seq { parent, index } => {
+            segments(parent, out);
+            out.push(Segment::Index(*index));
+        }
+        Path::Map { parent, key } => {
+            segments(parent, out);
+            out.push(Segment::Key(key.clone()));
+        }
+        Path::Some { parent }
+        | Path::NewtypeStruct { parent }
+        | Path::NewtypeVariant { parent } => segments(parent, out),
+    }
+}
+
+/// `rules[2].metric`
+fn dotted(path: &[Segment]) -> String {
+    let mut dotted = String::new();
+    for segment in path {
+        match segment {
+            Segment::Key(key) if dotted.is_empty() => dotted.push_str(key),
+            Segment::Key(key) => {
+                dotted.push('.');
+                dotted.push_str(key);
+            }
+            Segment::Index(index) => dotted.push_str(&format!("[{}]", index)),
+        }
+    }
+    dotted
+}
+
+/// Byte offset of the deepest key along `path` found in the document
+fn locate(document: &ImDocument<&str>, path: &[Segment]) -> Option<usize> {
+    let mut table: &dyn TableLike = document.as_table();
+    let mut offset = None;
+    let mut segments = path.iter().peekable();
+    while let Some(segment) = segments.next() {
+        let Segment::Key(key) = segment else {
+            break;
+        };
+        let Some((key, item)) = table.get_key_value(key) else {
+            break;
+        };
+        offset = key.span().map(|span| span.start).or(offset);
+        let next = match segments.peek() {
+            Some(Segment::Index(index)) => {
+                segments.next();
+                match item.as_array_of_tables() {
+                    Some(tables) => tables.get(*index).map(|t| t as &dyn TableLike),
+                    None => item
+                        .as_array()
+                        .and_then(|a| a.get(*index))
+                        .and_then(|v| v.as_inline_table())
+                        .map(|t| t as &dyn TableLike),
+                }
+            }
+            _ => item.as_table_like(),
+        };
+        match next {
+            Some(next) => table = next,
+            None => break,
+        }
+    }
+    offset
+}
+
+/// `file:line:column` of a byte offset, 1-based
+fn position(file: &str, content: &str, offset: usize) -> String {
+    let before = &content[..offset.min(content.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.len() - before.rfind('\n').map_or(0, |i| i + 1) + 1;
+    format!("{}:{}:{}", file, line, column)
+}
+
+/// Parses the alert config `file`, listing type errors, unknown keys and
+/// settings the services would reject. `None` when it cannot be read at all.
+pub fn parse_alert_config(file: &str, content: &str) -> (Option<AlertConfig>, Vec<Problem>) {
+    let mut ignored = Vec::new();
+    let parsed: Result<AlertConfig, _> =
+        serde_ignored::deserialize(toml::Deserializer::new(content), |path| {
+            let mut path_segments = Vec::new();
+            segments(&path, &mut path_segments);
+            ignored.push(path_segments);
+        });
+    let config = match parsed {
+        Ok(config) => config,
+        Err(e) => {
+            let location = match e.span() {
+                Some(span) => position(file, content, span.start),
+                None => file.to_string(),
+            };
+            return (None, vec![Problem::new(location, e.message().trim())]);
+        }
+    };
+
+    let document = ImDocument::parse(content).ok();
+    let mut problems: Vec<Problem> = ignored
+        .iter()
+        .map(|path| {
+            let location = document
+                .as_ref()
+                .and_then(|d| locate(d, path))
+                .map_or_else(|| file.to_string(), |o| position(file, content, o));
+            Problem::new(location, format!("unknown key `{}`", dotted(path)))
+        })
+        .collect();
+
+    if let Err(e) = Topology::new(config.topology.clone()) {
+        problems.push(Problem::new(file, format!("[[topology]]: {}", e)));
+    }
+    for (i, route) in config.routes.iter().enumerate() {
+        for url in &route.notify {
+            if !NOTIFY_SCHEMES.iter().any(|s| url.starts_with(s)) {
+                problems.push(Problem::new(
+                    file,
+                    format!("routes[{}]: cannot send to '{}'", i, url),
+                ));
+            }
+        }
+    }
+    for (i, webhook) in config.event_webhooks.iter().enumerate() {
+        if !NOTIFY_SCHEMES.iter().any(|s| webhook.url.starts_with(s)) {
+            problems.push(Problem::new(
+                file,
+                format!("event_webhooks[{}]: cannot send to '{}'", i, webhook.url),
+            ));
+        } else if webhook.secret.is_some() && !webhook.url.starts_with("http") {
+            problems.push(Problem::new(
+                file,
+                format!(
+                    "event_webhooks[{}]: only http(s) payloads are signed, secret is ignored",
+                    i
+                ),
+            ));
+        }
+    }
+    for reaction in &config.reactions {
+        let location = format!("reaction '{}'", reaction.name);
+        if !config.rules.iter().any(|r| r.name == reaction.rule) {
+            problems.push(Problem::new(
+                file,
+                format!("{}: no rule is named '{}'", location, reaction.rule),
+            ));
+        }
+        if reaction.command.is_empty() {
+            problems.push(Problem::new(
+                file,
+                format!("{}: command is empty", location),
+            ));
+        }
+        if reaction.after == 0 || reaction.timeout_seconds == 0 {
+            problems.push(Problem::new(
+                file,
+                format!("{}: after and timeout_seconds must be at least 1", location),
+            ));
+        }
+    }
+    for (i, policy) in config.supervise.iter().enumerate() {
+        if policy.containers.is_empty() || policy.max_attempts == 0 {
+            problems.push(Problem::new(
+                file,
+                format!(
+                    "supervise[{}]: needs containers and max_attempts of 1 or more",
+                    i
+                ),
+            ));
+        }
+        for url in &policy.notify {
+            if !NOTIFY_SCHEMES.iter().any(|s| url.starts_with(s)) {
+                problems.push(Problem::new(
+                    file,
+                    format!("supervise[{}]: cannot send to '{}'", i, url),
+                ));
+            }
+        }
+    }
+    for rule in &config.rules {
+        for url in rule.escalation.iter().flat_map(|step| &step.notify) {
+            if !NOTIFY_SCHEMES.iter().any(|s| url.starts_with(s)) {
+                problems.push(Problem::new(
+                    file,
+                    format!("rule '{}': cannot escalate to '{}'", rule.name, url),
+                ));
+            }
+        }
+        if rule
+            .escalation
+            .windows(2)
+            .any(|w| w[0].after_minutes > w[1].after_minutes)
+        {
+            problems.push(Problem::new(
+                file,
+                format!(
+                    "rule '{}': escalation steps must be in order of after_minutes",
+                    rule.name
+                ),
+            ));
+        }
+        if rule.webhook_url.is_empty() && !config.routes.iter().any(|r| r.matches(rule)) {
+            problems.push(Problem::new(
+                file,
+                format!(
+                    "rule '{}' has no webhook_url and no [[routes]] entry matches it",
+                    rule.name
+                ),
+            ));
+        }
+    }
+    for (i, viewer) in config.viewers.iter().enumerate() {
+        if viewer.token.is_empty() {
+            problems.push(Problem::new(
+                file,
+                format!("viewer '{}' has an empty token", viewer.name),
+            ));
+        } else if config.viewers[..i].iter().any(|v| v.token == viewer.token) {
+            problems.push(Problem::new(
+                file,
+                format!("viewer '{}' reuses another viewer's token", viewer.name),
+            ));
+        }
+    }
+    (Some(config), problems)
+}
+
+/// `nanomon --check-config`: prints every problem found. Returns the process
+/// exit code (1 when there is any).
+pub fn run(config: &Config) -> i32 {
+    let mut problems = check_env(std::env::vars());
+    if let Some(path) = &config.alert_config_path {
+        let file = path.display().to_string();
+        match std::fs::read_to_string(path) {
+            Ok(content) => problems.extend(parse_alert_config(&file, &content).1),
+            Err(e) => problems.push(Problem::new(&file, format!("cannot read: {}", e))),
+        }
+    }
+
+    if problems.is_empty() {
+        println!("Configuration OK");
+        return 0;
+    }
+    for problem in &problems {
+        println!("{}", problem);
+    }
+    println!("{} problem(s) found", problems.len());
+    1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_env_typos_and_ignored_values_are_reported() {
+        let vars = [
+            ("NANOMON_POLL_INTERVALL", "5"),
+            ("NANOMON_HISTORY_SIZE", "1h"),
+            ("NANOMON_SANDBOX", "yes"),
+            ("NANOMON_JSON_PRECISION", "two"),
+            ("NANOMON_PORT", "8080"),
+            ("HOME", "/root"),
+        ]
+        .map(|(k, v)| (k.to_string(), v.to_string()));
+        let problems = check_env(vars);
+        let messages: Vec<String> = problems.iter().map(|p| p.to_string()).collect();
+        assert_eq!(
+            messages,
+            [
+                "NANOMON_HISTORY_SIZE: '1h' is not a whole number, the default is used",
+                "NANOMON_JSON_PRECISION: 'two': expected a number of decimals or off, 2 are kept",
+                "NANOMON_POLL_INTERVALL: unknown variable, did you mean NANOMON_POLL_INTERVAL?",
+                "NANOMON_SANDBOX: 'yes' reads as false, use true or false",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_schema_shows_effective_values() {
+        let vars = [
+            ("NANOMON_PORT", "8080"),
+            ("NANOMON_POLL_INTERVAL", "soon"),
+            ("NANOMON_ADMIN_TOKEN", "hunter2"),
+        ]
+        .map(|(k, v)| (k.to_string(), v.to_string()));
+        let settings = schema(vars);
+        let get = |name: &str| settings.iter().find(|s| s.name == name).unwrap();
+
+        let port = get("NANOMON_PORT");
+        assert_eq!((port.kind, port.default.as_deref()), ("port", Some("3000")));
+        assert_eq!(port.value.as_deref(), Some("8080"));
+        let poll = get("NANOMON_POLL_INTERVAL");
+        assert_eq!(poll.value.as_deref(), Some("10"));
+        assert!(poll.set && poll.error.is_some());
+        assert_eq!(get("NANOMON_ADMIN_TOKEN").value.as_deref(), Some("***"));
+        assert_eq!(get("NANOMON_ARCHIVE_PATH").value, None);
+        assert_eq!(
+            get("NANOMON_ACCESS_LOG_EXCLUDE").default,
+            Some(crate::interface::http::DEFAULT_ACCESS_LOG_EXCLUDE.join(","))
+        );
+    }
+
+    #[test]
+    fn test_every_variable_read_is_known() {
+        let source = include_str!("../config.rs");
+        for read in source.split("env::var(\"").skip(1) {
+            let name = &read[..read.find('"').unwrap()];
+            assert!(
+                name == "TZ" || ENV_VARS.iter().any(|v| v.name == name),
+                "{} missing from ENV_VARS",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_alert_config_problems_have_positions() {
+        let content = r#"
+[[rules]]
+name = "disk"
+metric = "cpu_usage"
+threshold = 90.0
+condition = "above"
+webhook_url = "http://hooks"
+treshold = 80.0
+
+[ssh]
+log = "journald"
+spike_treshold = 5
+"#;
+        let (config, problems) = parse_alert_config("alerts.toml", content);
+        assert!(config.is_some());
+        let messages: Vec<String> = problems.iter().map(|p| p.to_string()).collect();
+        assert_eq!(
+            messages,
+            [
+                "alerts.toml:8:1: unknown key `rules[0].treshold`",
+                "alerts.toml:12:1: unknown key `ssh.spike_treshold`",
+            ]
+        );
+
+        let content = r#"
+[[rules]]
+name = "cpu"
+metric = "cpu_usage"
+condition = "above"
+threshold = 90.0
+
+[[routes]]
+rules = ["disk"]
+notify = ["slack:#ops"]
+
+[[event_webhooks]]
+url = "telegram:42"
+kinds = ["container_stopped"]
+secret = "s3cret"
+
+[[reactions]]
+name = "restart"
+rule = "nextcloud"
+command = []
+"#;
+        let (_, problems) = parse_alert_config("alerts.toml", content);
+        let messages: Vec<String> = problems.iter().map(|p| p.to_string()).collect();
+        assert_eq!(
+            messages,
+            [
+                "alerts.toml: routes[0]: cannot send to 'slack:#ops'",
+                "alerts.toml: event_webhooks[0]: only http(s) payloads are signed, secret is ignored",
+                "alerts.toml: reaction 'restart': no rule is named 'nextcloud'",
+                "alerts.toml: reaction 'restart': command is empty",
+                "alerts.toml: rule 'cpu' has no webhook_url and no [[routes]] entry matches it",
+            ]
+        );
+
+        let (config, problems) =
+            parse_alert_config("alerts.toml", "[[viewers]]\nname = \"tv\"\ntoken = 42\n");
+        assert!(config.is_none());
+        assert!(problems[0].location.starts_with("alerts.toml:3:9"));
+    }
+}
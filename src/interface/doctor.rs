@@ -0,0 +1,339 @@
+use std::fs;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+
+use crate::adapters::DockerAdapter;
+use crate::config::Config;
+
+/// Outcome of one environment check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    Ok,
+    /// An optional feature will be missing or degraded
+    Warn,
+    /// Core monitoring cannot work
+    Fail,
+}
+
+#[derive(Debug)]
+pub struct Check {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    pub detail: String,
+}
+
+impl Check {
+    fn new(name: &'static str, status: CheckStatus, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            status,
+            detail: detail.into(),
+        }
+    }
+}
+
+/// `nanomon doctor`: check mounts, Docker access and optional tools, print a report.
+/// Returns the process exit code (1 when any check failed).
+pub async fn run(config: &Config) -> i32 {
+    let mut checks = check_paths(config);
+    checks.push(check_docker().await);
+    checks.extend(check_features(config));
+
+    let color = std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none();
+    println!("NanoMon v{} environment check\n", env!("CARGO_PKG_VERSION"));
+    for check in &checks {
+        println!("{}", render(check, color));
+    }
+
+    let failed = checks
+        .iter()
+        .filter(|c| c.status == CheckStatus::Fail)
+        .count();
+    let warned = checks
+        .iter()
+        .filter(|c| c.status == CheckStatus::Warn)
+        .count();
+    println!("\n{} failed, {} warnings", failed, warned);
+    i32::from(failed > 0)
+}
+
+fn render(check: &Check, color: bool) -> String {
+    let (symbol, code) = match check.status {
+        CheckStatus::Ok => ("OK  ", "32"),
+        CheckStatus::Warn => ("WARN", "33"),
+        CheckStatus::Fail => ("FAIL", "31"),
+    };
+    if color {
+        format!(
+            "\x1b[{}m[{}]\x1b[0m {:<14} {}",
+            code, symbol, check.name, check.detail
+        )
+    } else {
+        format!("[{}] {:<14} {}", symbol, check.name, check.detail)
+    }
+}
+
+/// procfs/sysfs readability and cgroup version
+fn check_paths(config: &Config) -> Vec<Check> {
+    let proc = &config.proc_path;
+    let sys = &config.sys_path;
+    let mut checks = Vec::new();
+
+    let missing: Vec<&str> = ["stat", "meminfo", "loadavg", "uptime"]
+        .into_iter()
+        .filter(|f| fs::read_to_string(proc.join(f)).is_err())
+        .collect();
+    checks.push(if missing.is_empty() {
+        Check::new(
+            "procfs",
+            CheckStatus::Ok,
+            format!("{} readable", proc.display()),
+        )
+    } else {
+        Check::new(
+            "procfs",
+            CheckStatus::Fail,
+            format!(
+                "cannot read {} under {} (mount the host /proc, e.g. /proc:/host/proc:ro)",
+                missing.join(", "),
+                proc.display()
+            ),
+        )
+    });
+
+    // Only a handful of PIDs means we are looking at our own PID namespace
+    let pids = fs::read_dir(proc)
+        .map(|dir| {
+            dir.flatten()
+                .filter(|e| e.file_name().to_string_lossy().parse::<u32>().is_ok())
+                .count()
+        })
+        .unwrap_or(0);
+    checks.push(if pids >= 10 {
+        Check::new(
+            "processes",
+            CheckStatus::Ok,
+            format!("{} PIDs visible", pids),
+        )
+    } else {
+        Check::new(
+            "processes",
+            CheckStatus::Warn,
+            format!(
+                "only {} PIDs visible; host processes need `pid: host`",
+                pids
+            ),
+        )
+    });
+
+    checks.push(if sys.join("class/net").is_dir() {
+        Check::new(
+            "sysfs",
+            CheckStatus::Ok,
+            format!("{} readable", sys.display()),
+        )
+    } else {
+        Check::new(
+            "sysfs",
+            CheckStatus::Fail,
+            format!(
+                "{}/class/net missing (mount the host /sys, e.g. /sys:/host/sys:ro)",
+                sys.display()
+            ),
+        )
+    });
+
+    checks.push(
+        if sys.join("class/thermal").is_dir() || sys.join("class/hwmon").is_dir() {
+            Check::new(
+                "temperatures",
+                CheckStatus::Ok,
+                "thermal zones or hwmon found",
+            )
+        } else {
+            Check::new(
+                "temperatures",
+                CheckStatus::Warn,
+                "no thermal zones or hwmon sensors exposed",
+            )
+        },
+    );
+
+    let cgroup = sys.join("fs/cgroup");
+    checks.push(if cgroup.join("cgroup.controllers").exists() {
+        Check::new("cgroup", CheckStatus::Ok, "cgroup v2 (unified)")
+    } else if cgroup.join("memory").is_dir() {
+        Check::new("cgroup", CheckStatus::Ok, "cgroup v1")
+    } else {
+        Check::new(
+            "cgroup",
+            CheckStatus::Warn,
+            format!("no cgroup hierarchy under {}", cgroup.display()),
+        )
+    });
+
+    checks
+}
+
+async fn check_docker() -> Check {
+    if !cfg!(feature = "docker") {
+        return Check::new(
+            "docker",
+            CheckStatus::Warn,
+            "built without the docker feature".to_string(),
+        );
+    }
+    let adapter = match DockerAdapter::new() {
+        Ok(adapter) => adapter,
+        Err(e) => {
+            return Check::new(
+                "docker",
+                CheckStatus::Fail,
+                format!("cannot connect: {}", e),
+            )
+        }
+    };
+    match adapter.version().await {
+        Ok((engine, api)) => Check::new(
+            "docker",
+            CheckStatus::Ok,
+            format!("engine {} (API {})", engine, api),
+        ),
+        Err(e) => Check::new(
+            "docker",
+            CheckStatus::Fail,
+            format!(
+                "socket not usable: {} (mount /var/run/docker.sock and check its group)",
+                e
+            ),
+        ),
+    }
+}
+
+/// Tools and files needed by enabled optional features
+fn check_features(config: &Config) -> Vec<Check> {
+    let mut checks = Vec::new();
+
+    let tool = |name: &'static str, binary: &str, why: &str| match find_in_path(binary) {
+        Some(path) => Check::new(name, CheckStatus::Ok, path.display().to_string()),
+        None => Check::new(
+            name,
+            CheckStatus::Warn,
+            format!("`{}` not found in PATH; {}", binary, why),
+        ),
+    };
+
+    if config.enable_systemd {
+        checks.push(tool("systemd", "systemctl", "service status is disabled"));
+    }
+    checks.push(tool(
+        "smart",
+        "smartctl",
+        "disk SMART health is unavailable",
+    ));
+    checks.push(tool(
+        "journald",
+        "journalctl",
+        "journald-based log sources are unavailable",
+    ));
+    if config.enable_firewall && config.firewall_counters {
+        checks.push(tool(
+            "firewall counters",
+            "nft",
+            "rule counters are unavailable (nftables only)",
+        ));
+    }
+    if config.enable_firewall && find_in_path("nft").is_none() {
+        checks.push(tool(
+            "firewall",
+            "iptables-save",
+            "firewall summary is unavailable",
+        ));
+    }
+    if let Some(scanner) = &config.vuln_scanner {
+        checks.push(tool("vuln-scanner", scanner, "image scans are skipped"));
+    }
+    if let Some(root) = &config.host_root {
+        checks.push(if root.is_dir() {
+            Check::new("host-root", CheckStatus::Ok, root.display().to_string())
+        } else {
+            Check::new(
+                "host-root",
+                CheckStatus::Warn,
+                format!("{} missing; volume sizes are unavailable", root.display()),
+            )
+        });
+    }
+
+    for (name, path) in [
+        ("state-file", &config.state_file),
+        ("archive", &config.archive_path),
+    ] {
+        if let Some(path) = path {
+            checks.push(check_writable_parent(name, path));
+        }
+    }
+
+    if let Some(path) = &config.alert_config_path {
+        let parsed = fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|c| {
+                toml::from_str::<crate::config::AlertConfig>(&c).map_err(|e| e.to_string())
+            });
+        checks.push(match parsed {
+            Ok(_) => Check::new("alert-config", CheckStatus::Ok, path.display().to_string()),
+            Err(e) => Check::new(
+                "alert-config",
+                CheckStatus::Fail,
+                format!("{}: {}", path.display(), e),
+            ),
+        });
+    }
+
+    checks
+}
+
+fn check_writable_parent(name: &'static str, path: &Path) -> Check {
+    let dir = path
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let probe = dir.join(".nanomon-doctor");
+    match fs::write(&probe, b"") {
+        Ok(()) => {
+            let _ = fs::remove_file(&probe);
+            Check::new(name, CheckStatus::Ok, format!("{} writable", dir.display()))
+        }
+        Err(e) => Check::new(
+            name,
+            CheckStatus::Warn,
+            format!("cannot write to {}: {}", dir.display(), e),
+        ),
+    }
+}
+
+fn find_in_path(binary: &str) -> Option<PathBuf> {
+    if binary.contains('/') {
+        return Some(PathBuf::from(binary)).filter(|p| p.is_file());
+    }
+    std::env::split_paths(&std::env::var_os("PATH")?)
+        .map(|dir| dir.join(binary))
+        .find(|p| p.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_procfs_fails() {
+        let mut config = Config::from_env();
+        config.proc_path = PathBuf::from("/nonexistent/proc");
+        config.sys_path = PathBuf::from("/nonexistent/sys");
+
+        let checks = check_paths(&config);
+        let procfs = checks.iter().find(|c| c.name == "procfs").unwrap();
+        assert_eq!(procfs.status, CheckStatus::Fail);
+        assert!(render(procfs, false).starts_with("[FAIL] procfs"));
+    }
+}
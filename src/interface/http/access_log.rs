@@ -0,0 +1,107 @@
+use std::sync::Arc;
+use std::time::Instant;
+
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::domain::DEFAULT_REDACT_PATTERNS;
+
+/// Paths left out of the access log by default: polled by the dashboard or long-lived
+pub const DEFAULT_ACCESS_LOG_EXCLUDE: &[&str] = &[
+    "/api/v1/dashboard",
+    "/api/v1/stream",
+    "/api/v1/health",
+    "/api/dashboard",
+    "/api/stream",
+    "/api/health",
+];
+
+/// Which requests reach the access log
+pub struct AccessLog {
+    base_path: String,
+    exclude: Vec<String>,
+}
+
+impl AccessLog {
+    pub fn new(base_path: String, exclude: Vec<String>) -> Self {
+        Self { base_path, exclude }
+    }
+
+    /// Excluded paths match exactly or as a parent segment, relative to the base path
+    fn is_excluded(&self, path: &str) -> bool {
+        let path = path.strip_prefix(&self.base_path).unwrap_or(path);
+        self.exclude.iter().any(|prefix| {
+            path.strip_prefix(prefix.as_str())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+        })
+    }
+}
+
+/// Replace the values of credential-like query parameters with `***`
+pub fn scrub_query(query: &str) -> String {
+    query
+        .split('&')
+        .map(|pair| match pair.split_once('=') {
+            Some((key, _)) if is_sensitive(key) => format!("{}=***", key),
+            _ => pair.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+fn is_sensitive(key: &str) -> bool {
+    let key = key.to_ascii_lowercase();
+    key == "key" || DEFAULT_REDACT_PATTERNS.iter().any(|p| key.contains(p))
+}
+
+/// Logs method, route (path and scrubbed query), status and latency as event fields;
+/// headers are never logged
+pub async fn log_request(
+    State(log): State<Arc<AccessLog>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let uri = request.uri();
+    if log.is_excluded(uri.path()) {
+        return next.run(request).await;
+    }
+
+    let method = request.method().clone();
+    let route = match uri.query() {
+        Some(query) => format!("{}?{}", uri.path(), scrub_query(query)),
+        None => uri.path().to_string(),
+    };
+    let started = Instant::now();
+    let response = next.run(request).await;
+
+    tracing::info!(
+        target: "nanomon::access",
+        method = %method,
+        route = %route,
+        status = response.status().as_u16(),
+        latency_ms = started.elapsed().as_millis() as u64,
+        "request"
+    );
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scrub_query_and_exclusions() {
+        assert_eq!(
+            scrub_query("duration=3600&token=abc&API_KEY=x&key=y"),
+            "duration=3600&token=***&API_KEY=***&key=***"
+        );
+
+        let log = AccessLog::new("/nanomon".to_string(), vec!["/api/v1/stream".to_string()]);
+        assert!(log.is_excluded("/nanomon/api/v1/stream"));
+        assert!(!log.is_excluded("/nanomon/api/v1/streams"));
+        assert!(!log.is_excluded("/nanomon/api/v1/host"));
+    }
+}
@@ -0,0 +1,214 @@
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use axum::{
+    body::{Body, Bytes},
+    extract::{MatchedPath, Request, State},
+    http::{header, HeaderMap, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use futures::StreamExt;
+
+use super::handlers::AppState;
+use super::visibility::api_route;
+use crate::domain::Viewer;
+
+/// Routes, under either API prefix, whose rendered bodies are shared between clients
+const CACHED_ROUTES: &[&str] = &[
+    "/dashboard",
+    "/host",
+    "/containers",
+    "/processes",
+    "/disks",
+    "/network",
+    "/history",
+    "/top",
+];
+
+/// Larger bodies are passed through without being kept
+const MAX_CACHED_BODY: usize = 4 * 1024 * 1024;
+
+/// Distinct URLs kept at once; expired ones are dropped first when full
+const MAX_ENTRIES: usize = 64;
+
+#[derive(Clone)]
+struct Entry {
+    /// Latest stored snapshot when the body was rendered
+    sequence: Option<u64>,
+    rendered_at: Instant,
+    etag: HeaderValue,
+    headers: HeaderMap,
+    body: Bytes,
+}
+
+impl Entry {
+    fn respond(&self, if_none_match: Option<&HeaderValue>) -> Response {
+        if if_none_match == Some(&self.etag) {
+            return (
+                StatusCode::NOT_MODIFIED,
+                [(header::ETAG, self.etag.clone())],
+            )
+                .into_response();
+        }
+        let mut response = Response::new(Body::from(self.body.clone()));
+        *response.headers_mut() = self.headers.clone();
+        response
+            .headers_mut()
+            .insert(header::ETAG, self.etag.clone());
+        response
+    }
+}
+
+/// Rendered JSON of the busiest read endpoints, keyed by URL (path and query)
+/// and reused while no new snapshot was stored and `ttl` has not passed, so
+/// clients polling together share one collection and one serialization
+pub struct ResponseCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<String, Entry>>,
+}
+
+impl ResponseCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn get(&self, key: &str, sequence: Option<u64>) -> Option<Entry> {
+        let entries = self.entries.lock().unwrap();
+        entries
+            .get(key)
+            .filter(|e| e.sequence == sequence && e.rendered_at.elapsed() < self.ttl)
+            .cloned()
+    }
+
+    fn insert(&self, key: String, entry: Entry) {
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= MAX_ENTRIES && !entries.contains_key(&key) {
+            entries.retain(|_, e| e.rendered_at.elapsed() < self.ttl);
+            if entries.len() >= MAX_ENTRIES {
+                entries.clear();
+            }
+        }
+        entries.insert(key, entry);
+    }
+}
+
+fn is_cached_route(base_path: &str, route: &str) -> bool {
+    api_route(base_path, route).is_some_and(|path| CACHED_ROUTES.contains(&path))
+}
+
+/// Serves cached bodies of `CACHED_ROUTES` with an `ETag`, answering a
+/// matching `If-None-Match` with 304
+pub async fn cache_responses(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let cache = match &state.response_cache {
+        Some(cache) if request.method() == Method::GET => cache.clone(),
+        _ => return next.run(request).await,
+    };
+    let cacheable = request
+        .extensions()
+        .get::<MatchedPath>()
+        .is_some_and(|route| is_cached_route(&state.base_path, route.as_str()));
+    if !cacheable {
+        return next.run(request).await;
+    }
+
+    // Scoped viewers get their own filtered copy
+    let key = match request.extensions().get::<Viewer>() {
+        Some(viewer) => format!("{}@{}", viewer.name, request.uri()),
+        None => request.uri().to_string(),
+    };
+    let if_none_match = request.headers().get(header::IF_NONE_MATCH).cloned();
+    let sequence = state
+        .monitoring_service
+        .get_latest_snapshot()
+        .and_then(|host| host.sequence);
+    if let Some(entry) = cache.get(&key, sequence) {
+        return entry.respond(if_none_match.as_ref());
+    }
+
+    let response = next.run(request).await;
+    if response.status() != StatusCode::OK {
+        return response;
+    }
+    let (parts, body) = response.into_parts();
+    let mut stream = body.into_data_stream();
+    let mut buf = Vec::new();
+    while let Some(chunk) = stream.next().await {
+        match chunk {
+            Ok(chunk) => buf.extend_from_slice(&chunk),
+            Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        }
+        if buf.len() > MAX_CACHED_BODY {
+            // Too big to keep: send what was read, then the rest as it comes
+            let head = futures::stream::once(async { Ok::<_, axum::Error>(Bytes::from(buf)) });
+            return Response::from_parts(parts, Body::from_stream(head.chain(stream)));
+        }
+    }
+
+    let mut hasher = DefaultHasher::new();
+    buf.hash(&mut hasher);
+    let etag = format!("\"{}-{:016x}\"", sequence.unwrap_or(0), hasher.finish());
+    let entry = Entry {
+        sequence,
+        rendered_at: Instant::now(),
+        etag: HeaderValue::from_str(&etag).expect("ETag is ASCII"),
+        headers: parts.headers,
+        body: Bytes::from(buf),
+    };
+    let response = entry.respond(if_none_match.as_ref());
+    cache.insert(key, entry);
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(sequence: Option<u64>, rendered_at: Instant) -> Entry {
+        Entry {
+            sequence,
+            rendered_at,
+            etag: HeaderValue::from_static("\"7-00\""),
+            headers: HeaderMap::new(),
+            body: Bytes::from_static(b"{}"),
+        }
+    }
+
+    #[test]
+    fn test_entries_expire_with_new_snapshot_or_ttl() {
+        let cache = ResponseCache::new(Duration::from_secs(1));
+        cache.insert(
+            "/api/v1/dashboard".to_string(),
+            entry(Some(7), Instant::now()),
+        );
+        assert!(cache.get("/api/v1/dashboard", Some(7)).is_some());
+        assert!(cache.get("/api/v1/dashboard", Some(8)).is_none());
+        assert!(cache.get("/api/v1/dashboard?top=5", Some(7)).is_none());
+
+        let old = Instant::now() - Duration::from_secs(2);
+        cache.insert("/api/v1/host".to_string(), entry(Some(7), old));
+        assert!(cache.get("/api/v1/host", Some(7)).is_none());
+
+        let cached = entry(Some(7), Instant::now());
+        let etag = cached.etag.clone();
+        assert_eq!(
+            cached.respond(Some(&etag)).status(),
+            StatusCode::NOT_MODIFIED
+        );
+        assert_eq!(cached.respond(None).headers()[header::ETAG], etag);
+
+        assert!(is_cached_route("", "/api/v1/dashboard"));
+        assert!(is_cached_route("/nanomon", "/nanomon/api/processes"));
+        assert!(!is_cached_route("", "/api/v1/processes/{pid}/nice"));
+        assert!(!is_cached_route("", "/api/v1/stream"));
+    }
+}
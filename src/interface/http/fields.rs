@@ -0,0 +1,93 @@
+use std::collections::BTreeMap;
+
+use serde_json::Value;
+
+/// Fields that survive any selection
+const ALWAYS_KEPT: &[&str] = &["api_version"];
+
+/// Sparse fieldset parsed from `?fields=host.cpu,containers.name`.
+/// Each dotted path selects a subtree; arrays are filtered element-wise.
+#[derive(Debug, Default, Clone)]
+pub struct FieldSelection {
+    children: BTreeMap<String, FieldSelection>,
+}
+
+impl FieldSelection {
+    pub fn parse(fields: &str) -> Self {
+        let mut root = Self::default();
+        for path in fields.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let mut node = &mut root;
+            for segment in path.split('.') {
+                node = node.children.entry(segment.to_string()).or_default();
+            }
+        }
+        root
+    }
+
+    /// Whether a top-level field is selected; used to skip expensive collection
+    pub fn includes(&self, field: &str) -> bool {
+        self.children.contains_key(field)
+    }
+
+    /// Remove everything from `value` that is not selected
+    pub fn prune(&self, value: &mut Value) {
+        // A leaf keeps its whole subtree
+        if self.children.is_empty() {
+            return;
+        }
+        match value {
+            Value::Object(map) => {
+                map.retain(|key, _| {
+                    self.children.contains_key(key) || ALWAYS_KEPT.contains(&key.as_str())
+                });
+                for (key, child) in map.iter_mut() {
+                    if let Some(selection) = self.children.get(key) {
+                        selection.prune(child);
+                    }
+                }
+            }
+            Value::Array(items) => {
+                for item in items {
+                    self.prune(item);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_prune_nested_and_arrays() {
+        let mut value = json!({
+            "api_version": "v1",
+            "host": { "cpu": { "usage_percent": 12.0 }, "memory": { "used_bytes": 1 }, "hostname": "nas" },
+            "containers": [
+                { "name": "db", "state": "running", "stats": { "cpu_percent": 1.0 } },
+                { "name": "web", "state": "exited", "stats": null }
+            ],
+            "processes": [{ "pid": 1 }]
+        });
+
+        let selection =
+            FieldSelection::parse("host.cpu,host.memory,containers.name,containers.state");
+        selection.prune(&mut value);
+
+        assert_eq!(
+            value,
+            json!({
+                "api_version": "v1",
+                "host": { "cpu": { "usage_percent": 12.0 }, "memory": { "used_bytes": 1 } },
+                "containers": [
+                    { "name": "db", "state": "running" },
+                    { "name": "web", "state": "exited" }
+                ]
+            })
+        );
+        assert!(!selection.includes("processes"));
+    }
+}
@@ -0,0 +1,251 @@
+//! Grafana JSON datasource (SimpleJSON / Infinity contract) over the history store
+
+use std::time::Duration;
+
+use axum::{extract::State, http::StatusCode, response::IntoResponse, response::Response, Json};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::domain::Host;
+
+use super::handlers::AppState;
+use super::precision::rounded_json;
+
+#[derive(Debug, Deserialize)]
+pub struct TimeRange {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+}
+
+/// Body of POST /grafana/search
+#[derive(Debug, Default, Deserialize)]
+pub struct SearchRequest {
+    #[serde(default)]
+    pub target: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct QueryTarget {
+    pub target: String,
+    #[serde(default)]
+    pub hide: bool,
+}
+
+/// Body of POST /grafana/query
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryRequest {
+    pub range: TimeRange,
+    pub targets: Vec<QueryTarget>,
+    #[serde(default)]
+    pub max_data_points: Option<usize>,
+}
+
+/// One series: `[value, unix milliseconds]` pairs
+#[derive(Debug, Serialize)]
+pub struct TimeSeries {
+    pub target: String,
+    pub datapoints: Vec<(f64, i64)>,
+}
+
+/// Body of POST /grafana/annotations; `annotation.query` filters by tag
+#[derive(Debug, Deserialize)]
+pub struct AnnotationQuery {
+    pub range: TimeRange,
+    pub annotation: Value,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GrafanaAnnotation {
+    pub annotation: Value,
+    pub time: i64,
+    pub title: String,
+    pub text: String,
+    pub tags: Vec<String>,
+}
+
+/// Metric names available in a snapshot, e.g. `container.nextcloud.cpu_percent`
+fn targets(host: &Host) -> Vec<String> {
+    let mut targets: Vec<String> = [
+        "host.cpu_percent",
+        "host.memory_used_bytes",
+        "host.memory_percent",
+        "host.load_1",
+        "host.load_5",
+        "host.load_15",
+    ]
+    .iter()
+    .map(|t| t.to_string())
+    .collect();
+    for container in &host.containers {
+        targets.push(format!("container.{}.cpu_percent", container.name));
+        targets.push(format!("container.{}.memory_bytes", container.name));
+    }
+    for disk in &host.disks {
+        targets.push(format!("disk.{}.used_percent", disk.mount_point));
+    }
+    targets
+}
+
+/// Value of a metric in a snapshot; container and disk names may contain dots
+fn value(host: &Host, target: &str) -> Option<f64> {
+    match target {
+        "host.cpu_percent" => return Some(host.cpu.usage_percent),
+        "host.memory_used_bytes" => return Some(host.memory.used_bytes as f64),
+        "host.memory_percent" => return Some(host.memory.usage_percent()),
+        "host.load_1" => return Some(host.load_average.one),
+        "host.load_5" => return Some(host.load_average.five),
+        "host.load_15" => return Some(host.load_average.fifteen),
+        _ => {}
+    }
+    if let Some(rest) = target.strip_prefix("container.") {
+        let (name, field) = rest.rsplit_once('.')?;
+        let container = host.containers.iter().find(|c| c.name == name)?;
+        return match field {
+            "cpu_percent" => Some(container.cpu.usage_percent),
+            "memory_bytes" => Some(container.memory.used_bytes as f64),
+            _ => None,
+        };
+    }
+    let mount = target
+        .strip_prefix("disk.")?
+        .strip_suffix(".used_percent")?;
+    let disk = host.disks.iter().find(|d| d.mount_point == mount)?;
+    Some(disk.usage_percent())
+}
+
+/// Stored snapshots within `range`, oldest first
+fn history_in(state: &AppState, range: &TimeRange) -> Vec<std::sync::Arc<Host>> {
+    let lookback = (Utc::now() - range.from).to_std().unwrap_or(Duration::ZERO);
+    let mut history = state.monitoring_service.get_history(lookback);
+    history.retain(|h| h.timestamp >= range.from && h.timestamp <= range.to);
+    history
+}
+
+/// Handler for GET /api/grafana: the datasource connection test
+pub async fn grafana_root_handler() -> Response {
+    (StatusCode::OK, "OK").into_response()
+}
+
+/// Handler for POST /api/grafana/search: metric names containing `target`
+pub async fn grafana_search_handler(
+    State(state): State<AppState>,
+    body: Option<Json<SearchRequest>>,
+) -> Response {
+    let filter = body.map(|Json(b)| b.target).unwrap_or_default();
+    let names: Vec<String> = state
+        .monitoring_service
+        .get_latest_snapshot()
+        .map(|h| targets(&h))
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|t| t.contains(&filter))
+        .collect();
+    (StatusCode::OK, Json(names)).into_response()
+}
+
+/// Handler for POST /api/grafana/query: one time series per target
+pub async fn grafana_query_handler(
+    State(state): State<AppState>,
+    Json(request): Json<QueryRequest>,
+) -> Response {
+    let history = history_in(&state, &request.range);
+    // Keep at most `maxDataPoints` evenly spaced samples
+    let step = match request.max_data_points {
+        Some(max) if max > 0 => history.len().div_ceil(max).max(1),
+        _ => 1,
+    };
+
+    let series: Vec<TimeSeries> = request
+        .targets
+        .iter()
+        .filter(|t| !t.hide)
+        .map(|t| TimeSeries {
+            target: t.target.clone(),
+            datapoints: history
+                .iter()
+                .step_by(step)
+                .filter_map(|h| Some((value(h, &t.target)?, h.timestamp.timestamp_millis())))
+                .collect(),
+        })
+        .collect();
+    rounded_json(&series, state.json_precision)
+}
+
+/// Handler for POST /api/grafana/annotations: NanoMon annotations within the range
+pub async fn grafana_annotations_handler(
+    State(state): State<AppState>,
+    Json(request): Json<AnnotationQuery>,
+) -> Response {
+    let tag = request.annotation["query"]
+        .as_str()
+        .map(str::trim)
+        .unwrap_or("")
+        .to_lowercase();
+    let lookback = (Utc::now() - request.range.from)
+        .to_std()
+        .unwrap_or(Duration::ZERO);
+
+    let annotations: Vec<GrafanaAnnotation> = state
+        .monitoring_service
+        .get_annotations(lookback)
+        .into_iter()
+        .filter(|a| a.timestamp <= request.range.to)
+        .filter(|a| tag.is_empty() || a.tags.contains(&tag))
+        .map(|a| GrafanaAnnotation {
+            annotation: request.annotation.clone(),
+            time: a.timestamp.timestamp_millis(),
+            title: a.note.clone(),
+            text: a.note,
+            tags: a.tags,
+        })
+        .collect();
+    (StatusCode::OK, Json(annotations)).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{
+        Container, ContainerId, ContainerState, CpuMetrics, Disk, IoMetrics, MemoryMetrics,
+        NetworkMetrics,
+    };
+
+    #[test]
+    fn test_targets_resolve_to_values() {
+        let mut host = Host::new("nas".to_string());
+        host.cpu = CpuMetrics::new(42.0, 0.0, 0.0);
+        host.containers.push(
+            Container::new(
+                ContainerId::new("abc"),
+                "app.v2".to_string(),
+                "app:latest".to_string(),
+                ContainerState::Running,
+                Utc::now(),
+            )
+            .with_metrics(
+                CpuMetrics::new(12.5, 0.0, 0.0),
+                MemoryMetrics::new(2048, 0, 0),
+                NetworkMetrics::zero(),
+                IoMetrics::zero(),
+            ),
+        );
+        host.disks.push(Disk::new(
+            "/dev/sda1".to_string(),
+            "/mnt/data".to_string(),
+            "ext4".to_string(),
+            100,
+            25,
+            75,
+        ));
+
+        for target in targets(&host) {
+            assert!(value(&host, &target).is_some(), "{}", target);
+        }
+        assert_eq!(value(&host, "host.cpu_percent"), Some(42.0));
+        assert_eq!(value(&host, "container.app.v2.cpu_percent"), Some(12.5));
+        assert_eq!(value(&host, "disk./mnt/data.used_percent"), Some(25.0));
+        assert_eq!(value(&host, "container.missing.cpu_percent"), None);
+    }
+}
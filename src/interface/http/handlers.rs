@@ -1,18 +1,56 @@
+use std::collections::BTreeMap;
+use std::convert::Infallible;
 use std::fmt::Write;
+use std::net::IpAddr;
 use std::sync::Arc;
 use std::time::Duration;
 
 use axum::{
+    body::Bytes,
     debug_handler,
-    extract::{Path, Query, State},
-    http::StatusCode,
-    response::{IntoResponse, Response},
+    extract::{Extension, Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{
+        sse::{Event as SseEvent, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
     Json,
 };
+use chrono_tz::Tz;
+use futures::Stream;
 use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::error::RecvError;
 
-use crate::application::MonitoringService;
-use crate::domain::{Container, Host, Process, Stack, SystemdService, Temperature};
+use crate::application::{
+    AlertEvaluator, AuthGuard, BackupMonitor, BurnInError, BurnInService, CapabilityMonitor,
+    CustomMetricsService, FirewallMonitor, FootprintMonitor, HeartbeatMonitor, IngestError,
+    LatencyMonitor, LoginService, MaintenanceError, MaintenanceService, MonitoringService,
+    PowerError, PowerService, ProcessControlError, ProcessControlService, ReclaimService,
+    RemoteHostService, RemoteHostSummary, SecurityMonitor, SpeedtestMonitor, SshMonitor,
+    TopologyService, VmMonitor, VolumeUsageService,
+};
+use crate::domain::{
+    default_cooldown, format_local, parse_timezone, parse_window, AlertCondition, AlertDryRun,
+    AlertIncident, AlertMetric, AlertRule, Annotation, ApiAuthSummary, BackupStatus, Capabilities,
+    CheckResult, CollectionCost, CollectionEpoch, Collector, Container, ContainerId,
+    ContainerSecurity, CustomMetric, DomainEvent, Event, FirewallSummary, HeartbeatStatus,
+    HistoryPoint, Host, IdleContainer, KernelLimits, MaintenanceWindow, MemoryFootprint,
+    MemoryTopology, NagiosCheck, NetworkInterface, PingSample, PowerAction, Process, ProcessFilter,
+    ProcessOrder, RuleCounterSeries, SequenceGap, Severity, SpeedtestResult, SshSummary, Stack,
+    SystemdService, Talker, Temperature, TopologyGroup, UsageRates, Viewer, VirtualMachine,
+    VolumeUsage, VulnerabilityCounts, QUOTA_MIN_OBSERVED_SECONDS,
+};
+use crate::interface::backup::DataFiles;
+use crate::interface::config_check::{self, SettingSchema};
+
+use super::access_log::AccessLog;
+use super::cache::ResponseCache;
+use super::fields::FieldSelection;
+use super::metrics::{HttpMetrics, RouteStats};
+use super::precision::{rounded_json, to_rounded_vec};
+#[cfg(feature = "backup")]
+use super::stream::streamed_body;
+use super::stream::streamed_json;
 
 /// Custom error type that implements IntoResponse
 #[derive(Debug)]
@@ -31,34 +69,211 @@ impl From<Box<dyn std::error::Error + Send + Sync>> for AppError {
     }
 }
 
+/// Version of the public JSON API served under /api/v1
+pub const API_VERSION: &str = "v1";
+
+/// Response envelope stamping every JSON body with the API version
+#[derive(Debug, Serialize)]
+pub struct Versioned<T> {
+    pub api_version: &'static str,
+    #[serde(flatten)]
+    pub body: T,
+}
+
+/// Apply privacy mode to a process listing
+fn visible_processes(state: &AppState, processes: Vec<Process>) -> Vec<Process> {
+    if state.privacy_mode {
+        processes.into_iter().map(Process::redacted).collect()
+    } else {
+        processes
+    }
+}
+
+fn visible_interfaces(state: &AppState, interfaces: &[NetworkInterface]) -> Vec<NetworkInterface> {
+    if state.privacy_mode {
+        interfaces
+            .iter()
+            .cloned()
+            .map(NetworkInterface::redacted)
+            .collect()
+    } else {
+        interfaces.to_vec()
+    }
+}
+
+fn versioned<T: Serialize>(state: &AppState, body: T) -> Response {
+    rounded_json(
+        &Versioned {
+            api_version: API_VERSION,
+            body,
+        },
+        state.json_precision,
+    )
+}
+
+/// `versioned`, streamed: for bodies that grow with the process count or history length
+fn versioned_stream<T: Serialize + Send + 'static>(state: &AppState, body: T) -> Response {
+    streamed_json(
+        Versioned {
+            api_version: API_VERSION,
+            body,
+        },
+        state.json_precision,
+    )
+}
+
 /// Shared application state
 #[derive(Clone)]
 pub struct AppState {
     pub monitoring_service: Arc<MonitoringService>,
+    pub heartbeats: Arc<HeartbeatMonitor>,
+    pub backups: Arc<BackupMonitor>,
+    pub power: Arc<PowerService>,
+    pub process_control: Arc<ProcessControlService>,
+    pub latency: Arc<LatencyMonitor>,
+    pub custom_metrics: Arc<CustomMetricsService>,
+    /// Scheduled bandwidth test, `None` when NANOMON_SPEEDTEST_TOOL is unset
+    pub speedtest: Option<Arc<SpeedtestMonitor>>,
+    pub volumes: Arc<VolumeUsageService>,
+    /// Advice on freeing disk space
+    pub reclaim: Arc<ReclaimService>,
+    pub security: Arc<SecurityMonitor>,
+    /// SSH login monitoring, `None` without an `[ssh]` config section
+    pub ssh: Option<Arc<SshMonitor>>,
+    /// Firewall summary, `None` unless NANOMON_ENABLE_FIREWALL is set
+    pub firewall: Option<Arc<FirewallMonitor>>,
+    /// Hypervisor guests, `None` unless NANOMON_HYPERVISOR is set
+    pub vms: Option<Arc<VmMonitor>>,
+    /// Display timezone for human-readable fields
+    pub timezone: Tz,
+    /// Decimals kept for floats in JSON output, `None` for full precision
+    pub json_precision: Option<u32>,
+    /// Hide process owners and arguments, host paths and client addresses
+    pub privacy_mode: bool,
+    pub capabilities: Arc<CapabilityMonitor>,
+    /// Path prefix all routes are mounted under, empty at the root
+    pub base_path: String,
+    pub access_log: Option<Arc<AccessLog>>,
+    /// Rendered bodies of the busiest endpoints, `None` when NANOMON_RESPONSE_CACHE_MS is 0
+    pub response_cache: Option<Arc<ResponseCache>>,
+    pub http_metrics: Arc<HttpMetrics>,
+    pub footprint: Arc<FootprintMonitor>,
+    /// Bearer token required for admin actions; admin actions are disabled when unset
+    pub admin_token: Option<String>,
+    /// Files included in `GET /api/admin/backup`
+    pub data_files: Arc<DataFiles>,
+    /// Read tokens from `[[viewers]]`; once any is set, API reads need a token
+    pub viewers: Arc<Vec<Viewer>>,
+    /// Dashboard login through OIDC, `None` unless NANOMON_OIDC_ISSUER is set
+    pub logins: Option<Arc<LoginService>>,
+    /// Mark the session cookie `Secure`, when the dashboard is served over HTTPS
+    pub secure_cookies: bool,
+    /// Rejected tokens per client address and the lockouts they cause
+    pub auth_guard: Arc<AuthGuard>,
+    /// Reverse proxies whose `X-Forwarded-For` names the client
+    pub trusted_proxies: Arc<Vec<IpAddr>>,
+    /// Snapshots pushed by agents
+    pub remote_hosts: Arc<RemoteHostService>,
+    /// Bearer token agents push with; ingest is disabled when unset
+    pub ingest_token: Option<String>,
+    pub topology: Arc<TopologyService>,
+    pub alerts: Arc<AlertEvaluator>,
+    pub maintenance: Arc<MaintenanceService>,
+    pub burn_in: Arc<BurnInService>,
+}
+
+impl AppState {
+    /// Returns an error response unless the request carries the admin token
+    fn require_admin(&self, headers: &HeaderMap) -> Option<Response> {
+        let Some(expected) = self.admin_token.as_deref() else {
+            return Some(
+                (
+                    StatusCode::FORBIDDEN,
+                    "Admin actions are disabled (set NANOMON_ADMIN_TOKEN)",
+                )
+                    .into_response(),
+            );
+        };
+
+        if bearer_token(headers) == Some(expected) {
+            None
+        } else {
+            Some((StatusCode::UNAUTHORIZED, "Invalid or missing admin token").into_response())
+        }
+    }
+
+    /// Returns an error response unless the request carries the ingest token
+    fn require_ingest(&self, headers: &HeaderMap) -> Option<Response> {
+        let Some(expected) = self.ingest_token.as_deref() else {
+            return Some(
+                (
+                    StatusCode::FORBIDDEN,
+                    "Ingest is disabled (set NANOMON_INGEST_TOKEN)",
+                )
+                    .into_response(),
+            );
+        };
+
+        if bearer_token(headers) == Some(expected) {
+            None
+        } else {
+            Some((StatusCode::UNAUTHORIZED, "Invalid or missing ingest token").into_response())
+        }
+    }
+}
+
+fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
 }
 
 /// Response for /api/host
 #[derive(Debug, Serialize)]
 pub struct HostResponse {
     pub timestamp: String,
+    /// Absent for on-demand collections, which are not stored
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sequence: Option<u64>,
     pub hostname: String,
     pub uptime_seconds: u64,
     pub load_average: serde_json::Value,
     pub cpu: serde_json::Value,
     pub memory: serde_json::Value,
     pub temperatures: Vec<Temperature>,
+    pub kernel_limits: KernelLimits,
+    /// Collectors paused for maintenance, their data carried over
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub stale: Vec<Collector>,
+    /// When containers and processes were read, and how many processes point
+    /// at a container missing from the list
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub epoch: Option<CollectionEpoch>,
+    /// Only with `?detail=numa`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub numa: Option<MemoryTopology>,
+    /// Only with `?detail=network`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interfaces: Option<Vec<NetworkInterface>>,
 }
 
 impl From<&Host> for HostResponse {
     fn from(host: &Host) -> Self {
         Self {
             timestamp: host.timestamp.to_rfc3339(),
+            sequence: host.sequence,
             hostname: host.hostname.clone(),
             uptime_seconds: host.uptime_seconds,
             load_average: serde_json::to_value(&host.load_average).unwrap(),
             cpu: serde_json::to_value(&host.cpu).unwrap(),
             memory: serde_json::to_value(&host.memory).unwrap(),
             temperatures: host.temperatures.clone(),
+            kernel_limits: host.kernel_limits.clone(),
+            stale: host.stale.clone(),
+            epoch: host.epoch.clone(),
+            numa: None,
+            interfaces: None,
         }
     }
 }
@@ -69,6 +284,9 @@ pub struct ContainersResponse {
     pub timestamp: String,
     pub containers: Vec<Container>,
     pub stacks: Vec<Stack>,
+    /// With `filter=idle`: why each listed container counts as idle
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub idle: Option<Vec<IdleContainer>>,
 }
 
 /// Response for /api/processes
@@ -99,20 +317,51 @@ pub struct DashboardResponse {
     pub containers: Vec<Container>,
     pub stacks: Vec<Stack>,
     pub processes: Vec<Process>,
+    /// One top list per order asked for with `top_by`
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub processes_by: BTreeMap<ProcessOrder, Vec<Process>>,
     pub disks: serde_json::Value,
     pub network: serde_json::Value,
+    /// Rates of change from stored history, for trend arrows
+    pub rates: UsageRates,
+    /// Guests of a hypervisor host
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub vms: Vec<VirtualMachine>,
 }
 
 /// Response for /api/history
 #[derive(Debug, Serialize)]
 pub struct HistoryResponse {
     pub timestamps: Vec<String>,
+    pub sequences: Vec<u64>,
+    /// Missed collections: no data there, as opposed to zero values
+    pub gaps: Vec<SequenceGap>,
     pub cpu: Vec<f64>,
     pub memory_used: Vec<u64>,
     pub memory_total: u64,
     pub load_1: Vec<f64>,
     pub load_5: Vec<f64>,
     pub load_15: Vec<f64>,
+    /// Notes within the same window, for chart markers
+    pub annotations: Vec<Annotation>,
+}
+
+/// Response for /api/stacks/:name/history
+#[derive(Debug, Serialize)]
+pub struct StackHistoryResponse {
+    pub name: String,
+    pub timestamps: Vec<String>,
+    pub cpu_percent: Vec<f64>,
+    pub memory_bytes: Vec<u64>,
+    pub containers_running: Vec<usize>,
+}
+
+/// Response for /api/top
+#[derive(Debug, Serialize)]
+pub struct TopResponse {
+    pub timestamp: String,
+    pub window_seconds: u64,
+    pub talkers: Vec<Talker>,
 }
 
 /// Response for /api/services
@@ -123,6 +372,163 @@ pub struct ServicesResponse {
     pub available: bool,
 }
 
+/// Response for /api/events
+#[derive(Debug, Serialize)]
+pub struct EventsResponse {
+    pub timestamp: String,
+    pub timezone: String,
+    pub events: Vec<EventView>,
+}
+
+/// Event with its time rendered for humans
+#[derive(Debug, Serialize)]
+pub struct EventView {
+    #[serde(flatten)]
+    pub event: Event,
+    pub local_time: String,
+}
+
+/// Response for /api/heartbeats
+#[derive(Debug, Serialize)]
+pub struct HeartbeatsResponse {
+    pub timestamp: String,
+    pub heartbeats: Vec<HeartbeatStatus>,
+}
+
+/// Response for /api/checks/backups
+#[derive(Debug, Serialize)]
+pub struct BackupsResponse {
+    pub timestamp: String,
+    pub backups: Vec<BackupStatus>,
+}
+
+/// Latest ping result for one target in /api/checks/ping
+#[derive(Debug, Serialize)]
+pub struct PingTargetStatus {
+    pub name: String,
+    pub host: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub loss_percent: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latest: Option<PingSample>,
+}
+
+/// Response for /api/checks/ping
+#[derive(Debug, Serialize)]
+pub struct PingResponse {
+    pub timestamp: String,
+    pub targets: Vec<PingTargetStatus>,
+}
+
+/// Response for /api/custom
+#[derive(Debug, Serialize)]
+pub struct CustomMetricsResponse {
+    pub timestamp: String,
+    pub metrics: Vec<CustomMetric>,
+}
+
+/// One label set of a custom metric over time
+#[derive(Debug, Serialize)]
+pub struct CustomSeries {
+    pub labels: BTreeMap<String, String>,
+    pub timestamps: Vec<String>,
+    pub values: Vec<f64>,
+}
+
+/// Response for /api/custom/:name/history
+#[derive(Debug, Serialize)]
+pub struct CustomHistoryResponse {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unit: Option<String>,
+    pub series: Vec<CustomSeries>,
+}
+
+/// Response for /api/containers/:name/volumes
+#[derive(Debug, Serialize)]
+pub struct VolumesResponse {
+    pub container: String,
+    pub volumes: Vec<VolumeUsage>,
+}
+
+/// Response for /api/containers/security
+#[derive(Debug, Serialize)]
+pub struct SecurityResponse {
+    pub enabled: bool,
+    pub totals: VulnerabilityCounts,
+    pub containers: Vec<ContainerSecurity>,
+}
+
+/// Response for /api/security/ssh
+#[derive(Debug, Serialize)]
+pub struct SshResponse {
+    pub enabled: bool,
+    #[serde(flatten)]
+    pub summary: Option<SshSummary>,
+}
+
+/// Response for /api/security/auth
+#[derive(Debug, Serialize)]
+pub struct ApiAuthResponse {
+    #[serde(flatten)]
+    pub summary: ApiAuthSummary,
+}
+
+/// Response for /api/config/schema
+#[derive(Debug, Serialize)]
+pub struct ConfigSchemaResponse {
+    pub settings: Vec<SettingSchema>,
+}
+
+/// Response for /api/security/firewall
+#[derive(Debug, Serialize)]
+pub struct FirewallResponse {
+    pub enabled: bool,
+    /// Whether nftables or iptables could be read
+    pub available: bool,
+    #[serde(flatten)]
+    pub summary: Option<FirewallSummary>,
+}
+
+/// Response for /api/security/firewall/counters
+#[derive(Debug, Serialize)]
+pub struct FirewallCountersResponse {
+    /// Both the firewall summary and counter sampling are opted in
+    pub enabled: bool,
+    pub counters: Vec<RuleCounterSeries>,
+}
+
+/// Response for /api/vms
+#[derive(Debug, Serialize)]
+pub struct VmsResponse {
+    pub enabled: bool,
+    pub vms: Vec<VirtualMachine>,
+}
+
+/// Response for /api/checks/speedtest
+#[derive(Debug, Serialize)]
+pub struct SpeedtestResponse {
+    pub enabled: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interval_seconds: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latest: Option<SpeedtestResult>,
+    /// Why the last scheduled run produced no result (metered link, failure)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub skipped: Option<String>,
+    pub history: Vec<SpeedtestResult>,
+}
+
+/// Response for /api/checks/ping/:target/history
+#[derive(Debug, Serialize)]
+pub struct PingHistoryResponse {
+    pub target: String,
+    pub samples: Vec<PingSample>,
+    pub loss_percent: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub avg_ms: Option<f64>,
+}
+
 /// Query params for /api/processes
 #[derive(Debug, Deserialize)]
 pub struct ProcessQuery {
@@ -130,246 +536,1817 @@ pub struct ProcessQuery {
     pub sort: String,
     #[serde(default = "default_limit")]
     pub limit: usize,
+    /// Kernel threads (`kworker/...`) are hidden unless asked for
+    #[serde(default)]
+    pub include_kernel: bool,
+    pub min_nice: Option<i64>,
+    pub max_nice: Option<i64>,
+}
+
+impl ProcessQuery {
+    fn filter(&self) -> ProcessFilter {
+        ProcessFilter {
+            include_kernel: self.include_kernel,
+            min_nice: self.min_nice,
+            max_nice: self.max_nice,
+        }
+    }
+}
+
+/// Body of POST /api/processes/:pid/nice
+#[derive(Debug, Deserialize)]
+pub struct ReniceRequest {
+    pub nice: i64,
+}
+
+/// Query params for /api/host
+#[derive(Debug, Deserialize)]
+pub struct HostQuery {
+    /// Extra detail to include: `numa` or `network`
+    pub detail: Option<String>,
+}
+
+/// Query params for /api/containers
+#[derive(Debug, Deserialize)]
+pub struct ContainersQuery {
+    /// `idle` keeps only containers that barely did anything over `window`
+    pub filter: Option<String>,
+    /// How much history `filter=idle` looks at, e.g. `7d`
+    #[serde(default = "default_usage_window")]
+    pub window: String,
+}
+
+/// Query params for /api/containers/:name/recommendations
+#[derive(Debug, Deserialize)]
+pub struct RecommendationQuery {
+    /// How much history usage is taken from, e.g. `7d`
+    #[serde(default = "default_usage_window")]
+    pub window: String,
+}
+
+/// Query params for /api/analyze/spike
+#[derive(Debug, Deserialize)]
+pub struct SpikeQuery {
+    /// RFC 3339 start of the spike
+    pub from: chrono::DateTime<chrono::Utc>,
+    /// RFC 3339 end of the spike, now when absent
+    pub to: Option<chrono::DateTime<chrono::Utc>>,
+    /// Contributors kept per resource
+    #[serde(default = "default_spike_limit")]
+    pub limit: usize,
+}
+
+/// Query params for /api/top
+#[derive(Debug, Deserialize)]
+pub struct TopQuery {
+    /// How much history to look at, e.g. `15m` or `1h`
+    #[serde(default = "default_top_window")]
+    pub window: String,
+    #[serde(default = "default_top_limit")]
+    pub limit: usize,
+}
+
+/// Body of POST /api/maintenance
+#[derive(Debug, Deserialize)]
+pub struct MaintenanceRequest {
+    pub collectors: Vec<Collector>,
+    /// e.g. `30m` or `2h`
+    pub duration: String,
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+/// Body of POST /api/burnin
+#[derive(Debug, Deserialize)]
+pub struct BurnInRequest {
+    /// e.g. `2h` or `3d`
+    pub duration: String,
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+/// Body of POST /api/annotations
+#[derive(Debug, Deserialize)]
+pub struct AnnotationRequest {
+    pub note: String,
+    /// Defaults to now
+    #[serde(default)]
+    pub timestamp: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// Response for /api/annotations
+#[derive(Debug, Serialize)]
+pub struct AnnotationsResponse {
+    pub annotations: Vec<Annotation>,
+}
+
+/// Response for /api/maintenance
+#[derive(Debug, Serialize)]
+pub struct MaintenanceResponse {
+    pub windows: Vec<MaintenanceWindow>,
+}
+
+/// Query params for /api/alerts/stats
+#[derive(Debug, Deserialize)]
+pub struct AlertStatsQuery {
+    /// How much history to look at, e.g. `24h` or `7d`
+    #[serde(default = "default_alert_stats_window")]
+    pub window: String,
+}
+
+fn default_alert_stats_window() -> String {
+    "24h".to_string()
+}
+
+/// Response for /api/alerts
+#[derive(Debug, Serialize)]
+pub struct AlertsResponse {
+    /// Oldest first
+    pub open: Vec<AlertIncident>,
+}
+
+/// Body for POST /api/alerts/{id}/ack, optional
+#[derive(Debug, Deserialize)]
+pub struct AckRequest {
+    /// Who is taking care of it
+    pub by: Option<String>,
+}
+
+/// Body for POST /api/alerts/test: a rule as in the alert config, without
+/// its webhook
+#[derive(Debug, Deserialize)]
+pub struct AlertTestRequest {
+    #[serde(default = "default_alert_test_name")]
+    pub name: String,
+    pub metric: AlertMetric,
+    pub condition: AlertCondition,
+    pub threshold: f64,
+    #[serde(default = "default_cooldown")]
+    pub cooldown_seconds: u64,
+    #[serde(default)]
+    pub severity: Severity,
+    /// How much history to replay, e.g. `6h`
+    #[serde(default = "default_alert_stats_window")]
+    pub window: String,
+}
+
+fn default_alert_test_name() -> String {
+    "dry-run".to_string()
+}
+
+/// Query params for /api/nagios/:check; unset thresholds use the check's defaults
+#[derive(Debug, Deserialize)]
+pub struct NagiosQuery {
+    #[serde(default)]
+    pub warn: Option<f64>,
+    #[serde(default)]
+    pub crit: Option<f64>,
+}
+
+/// Query params for /api/history
+#[derive(Debug, Deserialize)]
+pub struct HistoryQuery {
+    /// Duration in seconds (default: 3600 = 1 hour)
+    #[serde(default = "default_history_duration")]
+    pub duration: u64,
+    /// Same as `duration` as a window such as `1h`, taking precedence
+    pub window: Option<String>,
+    /// Average /api/history into points this far apart, e.g. `30s`
+    pub resolution: Option<String>,
+}
+
+impl HistoryQuery {
+    /// `window` or else `duration`, `None` when `window` doesn't parse
+    fn window(&self) -> Option<Duration> {
+        match &self.window {
+            Some(window) => parse_window(window),
+            None => Some(Duration::from_secs(self.duration)),
+        }
+    }
+}
+
+/// Query params for /api/events
+#[derive(Debug, Deserialize)]
+pub struct EventsQuery {
+    #[serde(default = "default_events_limit")]
+    pub limit: usize,
+    /// IANA timezone for `local_time`, overriding NANOMON_TIMEZONE
+    pub tz: Option<String>,
+}
+
+/// Query params for /api/dashboard
+#[derive(Debug, Deserialize)]
+pub struct DashboardQuery {
+    /// Sparse fieldset, e.g. `host.cpu,containers.name`
+    pub fields: Option<String>,
+    /// Processes per top list
+    #[serde(default = "default_limit")]
+    pub top: usize,
+    /// Orders of the top process lists, e.g. `cpu,memory`; the first also fills `processes`
+    pub top_by: Option<String>,
+}
+
+fn default_usage_window() -> String {
+    "7d".to_string()
+}
+
+fn default_spike_limit() -> usize {
+    5
+}
+
+fn default_top_window() -> String {
+    "15m".to_string()
+}
+
+fn default_top_limit() -> usize {
+    10
+}
+
+fn default_sort() -> String {
+    "cpu".to_string()
+}
+
+fn default_limit() -> usize {
+    20
+}
+
+fn default_history_duration() -> u64 {
+    3600
+}
+
+fn default_events_limit() -> usize {
+    50
+}
+
+/// Handler for GET /api/health
+pub async fn health_handler(State(state): State<AppState>) -> Response {
+    (
+        StatusCode::OK,
+        versioned(
+            &state,
+            serde_json::json!({
+                "status": "healthy",
+                "service": "nanomon",
+                "version": env!("CARGO_PKG_VERSION"),
+                "timezone": state.timezone.name(),
+                "capabilities": state.capabilities.current()
+            }),
+        ),
+    )
+        .into_response()
+}
+
+/// Response for /api/self
+#[derive(Debug, Serialize)]
+pub struct SelfResponse {
+    pub uptime_seconds: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory: Option<MemoryFootprint>,
+    /// Per-collector cost against the collection budget
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub collection: Option<CollectionCost>,
+    pub requests: u64,
+    pub routes: Vec<RouteStats>,
+}
+
+/// Handler for GET /api/self: load generated on NanoMon's own HTTP server
+pub async fn self_handler(State(state): State<AppState>) -> Response {
+    let routes = state.http_metrics.routes();
+    versioned(
+        &state,
+        SelfResponse {
+            uptime_seconds: state.http_metrics.uptime().as_secs(),
+            memory: state.footprint.check(),
+            collection: state.monitoring_service.collection_cost(),
+            requests: routes.iter().map(|r| r.requests).sum(),
+            routes,
+        },
+    )
+}
+
+/// Handler for GET /api/host
+#[debug_handler]
+pub async fn host_handler(
+    State(state): State<AppState>,
+    Query(params): Query<HostQuery>,
+) -> Response {
+    let detail = params.detail.as_deref();
+    if let Some(other) = detail.filter(|d| !matches!(*d, "numa" | "network")) {
+        return (
+            StatusCode::BAD_REQUEST,
+            format!("Unknown detail '{}', expected 'numa' or 'network'", other),
+        )
+            .into_response();
+    }
+
+    let host = match state.monitoring_service.snapshot().await {
+        Ok(host) => host,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    let mut response = HostResponse::from(&*host);
+    if detail == Some("network") {
+        response.interfaces = Some(visible_interfaces(&state, &host.network_interfaces));
+    }
+    if detail == Some("numa") {
+        match state.monitoring_service.get_memory_topology().await {
+            Ok(topology) => response.numa = Some(topology),
+            Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        }
+    }
+    (StatusCode::OK, versioned(&state, response)).into_response()
+}
+
+/// Response for /api/health/ready
+#[derive(Debug, Serialize)]
+pub struct ReadyResponse {
+    /// Core collectors (host metrics, containers) work
+    pub ready: bool,
+    pub capabilities: Capabilities,
+}
+
+/// Handler for GET /api/health/ready: re-probes collectors, 503 until the core ones work
+pub async fn ready_handler(State(state): State<AppState>) -> Response {
+    let capabilities = state.capabilities.refresh().await;
+    let ready = CapabilityMonitor::is_ready(&capabilities);
+    let status = if ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (
+        status,
+        versioned(
+            &state,
+            ReadyResponse {
+                ready,
+                capabilities,
+            },
+        ),
+    )
+        .into_response()
+}
+
+/// Handler for GET /api/stream (Server-Sent Events: one `snapshot` per poll,
+/// plus the `event`s and `alert` incidents published on the bus)
+pub async fn stream_handler(
+    State(state): State<AppState>,
+) -> Sse<impl Stream<Item = Result<SseEvent, Infallible>>> {
+    let updates = state.monitoring_service.subscribe();
+    let precision = state.json_precision;
+    let data = move |body: serde_json::Value| {
+        to_rounded_vec(&body, precision)
+            .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+            .unwrap_or_default()
+    };
+    let events = futures::stream::unfold(updates, move |mut updates| async move {
+        let event = match updates.recv().await {
+            Ok(DomainEvent::Snapshot(host)) => {
+                let body = serde_json::to_value(Versioned {
+                    api_version: API_VERSION,
+                    body: HostResponse::from(host.as_ref()),
+                })
+                .unwrap_or_default();
+                let event = SseEvent::default().event("snapshot").data(data(body));
+                match host.sequence {
+                    Some(sequence) => event.id(sequence.to_string()),
+                    None => event,
+                }
+            }
+            Ok(DomainEvent::Recorded(event)) => SseEvent::default()
+                .event("event")
+                .data(data(serde_json::to_value(event).unwrap_or_default())),
+            Ok(DomainEvent::Alert(incident)) => SseEvent::default()
+                .event("alert")
+                .data(data(serde_json::to_value(incident).unwrap_or_default())),
+            // Only this client fell behind; it skips the oldest snapshots instead of
+            // holding up the poller or buffering without bound
+            Err(RecvError::Lagged(skipped)) => SseEvent::default()
+                .event("lagged")
+                .data(serde_json::json!({ "skipped": skipped }).to_string()),
+            Err(RecvError::Closed) => return None,
+        };
+        Some((Ok(event), updates))
+    });
+
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
+
+/// Handler for GET /api/containers
+pub async fn containers_handler(
+    State(state): State<AppState>,
+    viewer: Option<Extension<Viewer>>,
+    Query(params): Query<ContainersQuery>,
+) -> Response {
+    let idle_window = match params.filter.as_deref() {
+        None => None,
+        Some("idle") => match parse_window(&params.window) {
+            Some(window) => Some(window),
+            None => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    format!("Invalid window '{}', expected e.g. 7d", params.window),
+                )
+                    .into_response()
+            }
+        },
+        Some(other) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                format!("Unknown filter '{}', expected idle", other),
+            )
+                .into_response()
+        }
+    };
+
+    let mut containers = match state.monitoring_service.get_containers().await {
+        Ok(c) => c,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    if let Some(Extension(viewer)) = viewer {
+        containers.retain(|c| viewer.sees(c));
+    }
+    let idle = idle_window.map(|window| {
+        let mut idle = state.monitoring_service.get_idle_containers(window);
+        idle.retain(|i| containers.iter().any(|c| c.identity() == i.identity));
+        containers.retain(|c| idle.iter().any(|i| i.identity == c.identity()));
+        idle
+    });
+
+    let stacks = Stack::group(&containers);
+
+    (
+        StatusCode::OK,
+        versioned(
+            &state,
+            ContainersResponse {
+                timestamp: chrono::Utc::now().to_rfc3339(),
+                containers,
+                stacks,
+                idle,
+            },
+        ),
+    )
+        .into_response()
+}
+
+/// Handler for GET /api/processes
+pub async fn processes_handler(
+    State(state): State<AppState>,
+    viewer: Option<Extension<Viewer>>,
+    Query(params): Query<ProcessQuery>,
+) -> Response {
+    let filter = params.filter();
+    if let Some(Extension(viewer)) = viewer {
+        return scoped_processes_response(&state, &viewer, &params, &filter);
+    }
+    let result = match params.sort.as_str() {
+        "memory" => {
+            state
+                .monitoring_service
+                .get_top_processes_by_memory(params.limit, &filter)
+                .await
+        }
+        "nice" => {
+            state
+                .monitoring_service
+                .get_top_processes_by_nice(params.limit, &filter)
+                .await
+        }
+        _ => {
+            state
+                .monitoring_service
+                .get_top_processes_by_cpu(params.limit, &filter)
+                .await
+        }
+    };
+
+    match result {
+        Ok(processes) => versioned_stream(
+            &state,
+            ProcessesResponse {
+                timestamp: chrono::Utc::now().to_rfc3339(),
+                processes: visible_processes(&state, processes),
+            },
+        ),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+/// Top processes of a scoped viewer's containers, from the latest snapshot
+fn scoped_processes_response(
+    state: &AppState,
+    viewer: &Viewer,
+    params: &ProcessQuery,
+    filter: &ProcessFilter,
+) -> Response {
+    let Some(host) = state.monitoring_service.get_latest_snapshot() else {
+        return (StatusCode::SERVICE_UNAVAILABLE, "No snapshot collected yet").into_response();
+    };
+    let visible = viewer.visible_ids(&host.containers);
+    let order = params.sort.parse().unwrap_or(ProcessOrder::Cpu);
+    let processes = order
+        .top(
+            host.processes.iter().filter(|p| {
+                p.container_id
+                    .as_ref()
+                    .is_some_and(|id| visible.contains(id))
+            }),
+            params.limit,
+            filter,
+        )
+        .into_iter()
+        .cloned()
+        .collect();
+    versioned_stream(
+        state,
+        ProcessesResponse {
+            timestamp: host.timestamp.to_rfc3339(),
+            processes: visible_processes(state, processes),
+        },
+    )
+}
+
+/// Handler for GET /api/disks
+#[debug_handler]
+pub async fn disks_handler(State(state): State<AppState>) -> Response {
+    match state.monitoring_service.snapshot().await {
+        Ok(host) => (
+            StatusCode::OK,
+            versioned(
+                &state,
+                DisksResponse {
+                    timestamp: host.timestamp.to_rfc3339(),
+                    disks: serde_json::to_value(&host.disks).unwrap(),
+                },
+            ),
+        )
+            .into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+/// Handler for GET /api/suggest/disk: what could be deleted to free space,
+/// biggest first. Only looks, never deletes.
+pub async fn disk_suggestions_handler(State(state): State<AppState>) -> Response {
+    (
+        StatusCode::OK,
+        versioned(&state, state.reclaim.suggestions().await),
+    )
+        .into_response()
+}
+
+/// Handler for GET /api/network
+#[debug_handler]
+pub async fn network_handler(State(state): State<AppState>) -> Response {
+    match state.monitoring_service.snapshot().await {
+        Ok(host) => (
+            StatusCode::OK,
+            versioned(
+                &state,
+                NetworkResponse {
+                    timestamp: host.timestamp.to_rfc3339(),
+                    interfaces: serde_json::to_value(visible_interfaces(
+                        &state,
+                        &host.network_interfaces,
+                    ))
+                    .unwrap(),
+                },
+            ),
+        )
+            .into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+/// Handler for GET /api/dashboard (aggregated endpoint)
+#[debug_handler]
+pub async fn dashboard_handler(
+    State(state): State<AppState>,
+    Query(query): Query<DashboardQuery>,
+) -> Response {
+    let selection = query.fields.as_deref().map(FieldSelection::parse);
+    // Skip sections the fieldset leaves out
+    let wanted = |field: &str| selection.as_ref().is_none_or(|s| s.includes(field));
+
+    // Stacks and top processes come from the latest poll, not a second
+    // container listing and process scan
+    let host = match state.monitoring_service.snapshot().await {
+        Ok(h) => h,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+
+    let stacks = if wanted("stacks") {
+        Stack::group(&host.containers)
+    } else {
+        Vec::new()
+    };
+
+    let orders: Vec<ProcessOrder> = match query.top_by.as_deref() {
+        Some(list) => match list.split(',').map(|o| o.trim().parse()).collect() {
+            Ok(orders) => orders,
+            Err(e) => return (StatusCode::BAD_REQUEST, e).into_response(),
+        },
+        None => vec![ProcessOrder::Cpu],
+    };
+    let mut processes = Vec::new();
+    let mut processes_by = BTreeMap::new();
+    if wanted("processes") || wanted("processes_by") {
+        for (i, order) in orders.iter().enumerate() {
+            let top = order.top(&host.processes, query.top, &ProcessFilter::default());
+            let list = visible_processes(&state, top.into_iter().cloned().collect());
+            if query.top_by.is_some() {
+                processes_by.insert(*order, list.clone());
+            }
+            if i == 0 {
+                processes = list;
+            }
+        }
+    }
+
+    let vms = match &state.vms {
+        Some(monitor) if wanted("vms") => monitor.list().await.unwrap_or_else(|e| {
+            tracing::debug!("Failed to list VMs: {}", e);
+            Vec::new()
+        }),
+        _ => Vec::new(),
+    };
+
+    let response = DashboardResponse {
+        host: HostResponse::from(&*host),
+        containers: host.containers.clone(),
+        stacks,
+        processes,
+        processes_by,
+        disks: serde_json::to_value(&host.disks).unwrap(),
+        network: serde_json::to_value(visible_interfaces(&state, &host.network_interfaces))
+            .unwrap(),
+        rates: if wanted("rates") {
+            state.monitoring_service.get_usage_rates()
+        } else {
+            UsageRates::default()
+        },
+        vms,
+    };
+
+    match selection {
+        Some(selection) => {
+            let mut value = serde_json::to_value(Versioned {
+                api_version: API_VERSION,
+                body: response,
+            })
+            .unwrap();
+            selection.prune(&mut value);
+            rounded_json(&value, state.json_precision)
+        }
+        None => versioned_stream(&state, response),
+    }
+}
+
+/// Handler for GET /api/history
+#[debug_handler]
+pub async fn history_handler(
+    State(state): State<AppState>,
+    Query(params): Query<HistoryQuery>,
+) -> Response {
+    let Some(window) = params.window() else {
+        return (StatusCode::BAD_REQUEST, "Invalid window, e.g. 1h").into_response();
+    };
+    let resolution = match params.resolution.as_deref() {
+        Some(resolution) => match parse_window(resolution).filter(|r| !r.is_zero()) {
+            Some(resolution) => Some(resolution),
+            None => {
+                return (StatusCode::BAD_REQUEST, "Invalid resolution, e.g. 30s").into_response()
+            }
+        },
+        None => None,
+    };
+    let history = state.monitoring_service.get_history(window);
+
+    let memory_total = history.last().map(|h| h.memory.total_bytes).unwrap_or(0);
+
+    // Gaps come from every stored snapshot, averaged or not
+    let samples: Vec<_> = history
+        .iter()
+        .filter_map(|h| Some((h.sequence?, h.timestamp)))
+        .collect();
+    let points: Vec<_> = match resolution {
+        Some(resolution) => HistoryPoint::downsample(&history, resolution),
+        None => history.iter().map(|h| HistoryPoint::from_host(h)).collect(),
+    };
+
+    let response = HistoryResponse {
+        timestamps: points.iter().map(|p| p.timestamp.to_rfc3339()).collect(),
+        sequences: points.iter().filter_map(|p| p.sequence).collect(),
+        gaps: SequenceGap::find(&samples),
+        cpu: points.iter().map(|p| p.cpu_percent).collect(),
+        memory_used: points.iter().map(|p| p.memory_used_bytes).collect(),
+        memory_total,
+        load_1: points.iter().map(|p| p.load_1).collect(),
+        load_5: points.iter().map(|p| p.load_5).collect(),
+        load_15: points.iter().map(|p| p.load_15).collect(),
+        annotations: state.monitoring_service.get_annotations(window),
+    };
+
+    versioned_stream(&state, response)
+}
+
+/// Handler for GET /api/stacks/:name/history
+pub async fn stack_history_handler(
+    State(state): State<AppState>,
+    viewer: Option<Extension<Viewer>>,
+    Path(name): Path<String>,
+    Query(params): Query<HistoryQuery>,
+) -> Response {
+    if viewer.is_some_and(|Extension(viewer)| !viewer.sees_stack(&name)) {
+        return (
+            StatusCode::NOT_FOUND,
+            format!("No history for stack '{}'", name),
+        )
+            .into_response();
+    }
+    let Some(window) = params.window() else {
+        return (StatusCode::BAD_REQUEST, "Invalid window, e.g. 1h").into_response();
+    };
+    let history = state.monitoring_service.get_stack_history(&name, window);
+
+    if history.is_empty() {
+        return (
+            StatusCode::NOT_FOUND,
+            format!("No history for stack '{}'", name),
+        )
+            .into_response();
+    }
+
+    let response = StackHistoryResponse {
+        name,
+        timestamps: history.iter().map(|(t, _)| t.to_rfc3339()).collect(),
+        cpu_percent: history.iter().map(|(_, s)| s.cpu_percent).collect(),
+        memory_bytes: history.iter().map(|(_, s)| s.memory_bytes).collect(),
+        containers_running: history.iter().map(|(_, s)| s.containers_running).collect(),
+    };
+    (StatusCode::OK, versioned(&state, response)).into_response()
+}
+
+/// Handler for GET /api/top: what to look at first, across resource kinds
+pub async fn top_handler(
+    State(state): State<AppState>,
+    Query(params): Query<TopQuery>,
+) -> Response {
+    let Some(window) = parse_window(&params.window) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            format!(
+                "Invalid window '{}', expected e.g. 15m or 1h",
+                params.window
+            ),
+        )
+            .into_response();
+    };
+
+    let talkers =
+        state
+            .monitoring_service
+            .get_top_talkers(window, params.limit, state.privacy_mode);
+    (
+        StatusCode::OK,
+        versioned(
+            &state,
+            TopResponse {
+                timestamp: chrono::Utc::now().to_rfc3339(),
+                window_seconds: window.as_secs(),
+                talkers,
+            },
+        ),
+    )
+        .into_response()
+}
+
+/// Handler for GET /api/analyze/spike
+pub async fn spike_handler(
+    State(state): State<AppState>,
+    Query(params): Query<SpikeQuery>,
+) -> Response {
+    let to = params.to.unwrap_or_else(chrono::Utc::now);
+    if params.from >= to {
+        return (StatusCode::BAD_REQUEST, "from must be before to").into_response();
+    }
+    match state
+        .monitoring_service
+        .analyze_spike(params.from, to, params.limit, state.privacy_mode)
+    {
+        Some(analysis) => (StatusCode::OK, versioned(&state, analysis)).into_response(),
+        None => (
+            StatusCode::NOT_FOUND,
+            format!(
+                "No history between {} and {}",
+                params.from.to_rfc3339(),
+                to.to_rfc3339()
+            ),
+        )
+            .into_response(),
+    }
+}
+
+/// Handler for GET /api/services
+#[debug_handler]
+pub async fn services_handler(State(state): State<AppState>) -> Response {
+    let available = state.monitoring_service.has_services();
+    let services = match state.monitoring_service.get_services().await {
+        Ok(s) => s,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+
+    (
+        StatusCode::OK,
+        versioned(
+            &state,
+            ServicesResponse {
+                timestamp: chrono::Utc::now().to_rfc3339(),
+                services,
+                available,
+            },
+        ),
+    )
+        .into_response()
+}
+
+/// Handler for GET /api/events
+pub async fn events_handler(
+    State(state): State<AppState>,
+    Query(params): Query<EventsQuery>,
+) -> Response {
+    let timezone = match params.tz.as_deref() {
+        Some(name) => match parse_timezone(name) {
+            Some(tz) => tz,
+            None => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    format!("Unknown timezone '{}'", name),
+                )
+                    .into_response()
+            }
+        },
+        None => state.timezone,
+    };
+
+    let events = state
+        .monitoring_service
+        .get_events(params.limit)
+        .into_iter()
+        .map(|event| EventView {
+            local_time: format_local(event.timestamp, timezone),
+            event,
+        })
+        .collect();
+
+    (
+        StatusCode::OK,
+        versioned(
+            &state,
+            EventsResponse {
+                timestamp: chrono::Utc::now().to_rfc3339(),
+                timezone: timezone.name().to_string(),
+                events,
+            },
+        ),
+    )
+        .into_response()
+}
+
+/// Handler for GET /api/heartbeats
+pub async fn heartbeats_handler(State(state): State<AppState>) -> Response {
+    (
+        StatusCode::OK,
+        versioned(
+            &state,
+            HeartbeatsResponse {
+                timestamp: chrono::Utc::now().to_rfc3339(),
+                heartbeats: state.heartbeats.statuses(),
+            },
+        ),
+    )
+        .into_response()
+}
+
+/// Handler for GET /api/checks/backups
+pub async fn backups_handler(State(state): State<AppState>) -> Response {
+    (
+        StatusCode::OK,
+        versioned(
+            &state,
+            BackupsResponse {
+                timestamp: chrono::Utc::now().to_rfc3339(),
+                backups: state.backups.statuses(),
+            },
+        ),
+    )
+        .into_response()
+}
+
+/// Handler for GET /api/checks/ping
+pub async fn ping_handler(State(state): State<AppState>) -> Response {
+    let targets = state
+        .latency
+        .latest()
+        .into_iter()
+        .map(|(target, latest)| PingTargetStatus {
+            name: target.name,
+            host: target.host,
+            loss_percent: latest.as_ref().map(|s| s.loss_percent()),
+            latest,
+        })
+        .collect();
+
+    (
+        StatusCode::OK,
+        versioned(
+            &state,
+            PingResponse {
+                timestamp: chrono::Utc::now().to_rfc3339(),
+                targets,
+            },
+        ),
+    )
+        .into_response()
+}
+
+/// Handler for GET /api/custom: latest values of user-defined collectors
+pub async fn custom_metrics_handler(State(state): State<AppState>) -> Response {
+    versioned(
+        &state,
+        CustomMetricsResponse {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            metrics: state.custom_metrics.latest(),
+        },
+    )
+    .into_response()
+}
+
+/// Handler for GET /api/custom/:name/history
+pub async fn custom_history_handler(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Query(params): Query<HistoryQuery>,
+) -> Response {
+    let Some(window) = params.window() else {
+        return (StatusCode::BAD_REQUEST, "Invalid window, e.g. 1h").into_response();
+    };
+    let history = state
+        .monitoring_service
+        .get_custom_metric_history(&name, window);
+    if history.is_empty() {
+        return (
+            StatusCode::NOT_FOUND,
+            format!("No history for custom metric '{}'", name),
+        )
+            .into_response();
+    }
+
+    let unit = state
+        .custom_metrics
+        .latest()
+        .into_iter()
+        .find(|m| m.name == name)
+        .and_then(|m| m.unit);
+    let series = history
+        .into_iter()
+        .map(|(labels, points)| CustomSeries {
+            labels,
+            timestamps: points.iter().map(|(t, _)| t.to_rfc3339()).collect(),
+            values: points.iter().map(|(_, v)| *v).collect(),
+        })
+        .collect();
+    versioned(&state, CustomHistoryResponse { name, unit, series }).into_response()
+}
+
+/// Handler for GET /api/checks/ping/:target/history
+pub async fn ping_history_handler(
+    State(state): State<AppState>,
+    Path(target): Path<String>,
+) -> Response {
+    let Some(samples) = state.latency.history(&target) else {
+        return (
+            StatusCode::NOT_FOUND,
+            format!("Ping target '{}' is not configured", target),
+        )
+            .into_response();
+    };
+
+    let sent: u32 = samples.iter().map(|s| s.sent).sum();
+    let received: u32 = samples.iter().map(|s| s.received).sum();
+    let loss_percent = if sent == 0 {
+        0.0
+    } else {
+        (sent - received) as f64 / sent as f64 * 100.0
+    };
+    let latencies: Vec<f64> = samples.iter().filter_map(|s| s.avg_ms).collect();
+    let avg_ms = if latencies.is_empty() {
+        None
+    } else {
+        Some(latencies.iter().sum::<f64>() / latencies.len() as f64)
+    };
+
+    (
+        StatusCode::OK,
+        versioned(
+            &state,
+            PingHistoryResponse {
+                target,
+                samples,
+                loss_percent,
+                avg_ms,
+            },
+        ),
+    )
+        .into_response()
+}
+
+/// Handler for GET /api/checks/speedtest
+pub async fn speedtest_handler(State(state): State<AppState>) -> Response {
+    let response = match &state.speedtest {
+        Some(monitor) => {
+            let history = monitor.history();
+            SpeedtestResponse {
+                enabled: true,
+                interval_seconds: Some(monitor.interval_seconds()),
+                latest: history.last().cloned(),
+                skipped: monitor.last_skip(),
+                history,
+            }
+        }
+        None => SpeedtestResponse {
+            enabled: false,
+            interval_seconds: None,
+            latest: None,
+            skipped: None,
+            history: Vec::new(),
+        },
+    };
+
+    (StatusCode::OK, versioned(&state, response)).into_response()
+}
+
+/// Handler for POST /api/hosts/:name/wake
+pub async fn host_wake_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(name): Path<String>,
+) -> Response {
+    power_action(state, headers, name, PowerAction::Wake).await
+}
+
+/// Handler for POST /api/hosts/:name/shutdown
+pub async fn host_shutdown_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(name): Path<String>,
+) -> Response {
+    power_action(state, headers, name, PowerAction::Shutdown).await
+}
+
+async fn power_action(
+    state: AppState,
+    headers: HeaderMap,
+    name: String,
+    action: PowerAction,
+) -> Response {
+    if let Some(denied) = state.require_admin(&headers) {
+        return denied;
+    }
+
+    match state.power.run(&name, action).await {
+        Ok(()) => StatusCode::ACCEPTED.into_response(),
+        Err(e @ PowerError::UnknownHost(_)) => {
+            (StatusCode::NOT_FOUND, e.to_string()).into_response()
+        }
+        Err(e) => (StatusCode::BAD_GATEWAY, e.to_string()).into_response(),
+    }
+}
+
+/// Handler for POST /api/processes/:pid/nice
+pub async fn process_renice_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(pid): Path<u32>,
+    Json(request): Json<ReniceRequest>,
+) -> Response {
+    if let Some(denied) = state.require_admin(&headers) {
+        return denied;
+    }
+
+    match state.process_control.renice(pid, request.nice).await {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(e @ ProcessControlError::InvalidNice) => {
+            (StatusCode::BAD_REQUEST, e.to_string()).into_response()
+        }
+        Err(e @ ProcessControlError::NotFound(_)) => {
+            (StatusCode::NOT_FOUND, e.to_string()).into_response()
+        }
+        Err(e @ ProcessControlError::PermissionDenied(_)) => {
+            (StatusCode::FORBIDDEN, e.to_string()).into_response()
+        }
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+/// Decode a pushed snapshot: MessagePack (with field names) when the content
+/// type says so, JSON otherwise
+fn decode_snapshot(headers: &HeaderMap, body: &[u8]) -> Result<Host, String> {
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+
+    if content_type.contains("msgpack") {
+        #[cfg(feature = "msgpack")]
+        return rmp_serde::from_slice(body).map_err(|e| e.to_string());
+        #[cfg(not(feature = "msgpack"))]
+        return Err("Built without the msgpack feature, push JSON instead".to_string());
+    }
+    serde_json::from_slice(body).map_err(|e| e.to_string())
+}
+
+/// Handler for POST /api/ingest: a snapshot pushed by an agent
+pub async fn ingest_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    if let Some(denied) = state.require_ingest(&headers) {
+        return denied;
+    }
+
+    let snapshot = match decode_snapshot(&headers, &body) {
+        Ok(snapshot) => snapshot,
+        Err(e) => {
+            return (StatusCode::BAD_REQUEST, format!("Invalid snapshot: {}", e)).into_response()
+        }
+    };
+
+    match state.remote_hosts.ingest(snapshot) {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(e @ IngestError::MissingHostname) => {
+            (StatusCode::BAD_REQUEST, e.to_string()).into_response()
+        }
+        Err(e @ IngestError::TooManyHosts(_)) => {
+            (StatusCode::INSUFFICIENT_STORAGE, e.to_string()).into_response()
+        }
+    }
+}
+
+/// Response for /api/hosts
+#[derive(Debug, Serialize)]
+pub struct RemoteHostsResponse {
+    pub hosts: Vec<RemoteHostSummary>,
+}
+
+/// Handler for GET /api/hosts: agents that pushed snapshots
+pub async fn remote_hosts_handler(State(state): State<AppState>) -> Response {
+    let response = RemoteHostsResponse {
+        hosts: state.remote_hosts.hosts(),
+    };
+    (StatusCode::OK, versioned(&state, response)).into_response()
+}
+
+/// Handler for GET /api/hosts/:name: latest snapshot pushed by an agent
+pub async fn remote_host_handler(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Response {
+    match state.remote_hosts.latest(&name) {
+        // Agents push their snapshots whole; privacy mode applies as to local ones
+        Some(snapshot) if state.privacy_mode => {
+            let mut host = Host::clone(&snapshot);
+            host.processes = visible_processes(&state, std::mem::take(&mut host.processes));
+            host.network_interfaces = visible_interfaces(&state, &host.network_interfaces);
+            (StatusCode::OK, versioned(&state, host)).into_response()
+        }
+        Some(snapshot) => (StatusCode::OK, versioned(&state, &*snapshot)).into_response(),
+        None => (
+            StatusCode::NOT_FOUND,
+            format!("Host '{}' has not pushed any snapshot", name),
+        )
+            .into_response(),
+    }
+}
+
+/// Response for /api/topology
+#[derive(Debug, Serialize)]
+pub struct TopologyResponse {
+    pub groups: Vec<TopologyGroup>,
+}
+
+/// Handler for GET /api/topology: hosts by room or rack, with VM parents
+pub async fn topology_handler(State(state): State<AppState>) -> Response {
+    let local = state.monitoring_service.get_latest_snapshot();
+    let response = TopologyResponse {
+        groups: state
+            .topology
+            .groups(local.as_ref().map(|h| h.hostname.as_str())),
+    };
+    (StatusCode::OK, versioned(&state, response)).into_response()
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HeartbeatPingQuery {
+    pub token: Option<String>,
+}
+
+/// Handler for POST /api/heartbeats/:name
+///
+/// Unknown checks and wrong tokens both answer 404, so pings cannot be used
+/// to discover check names.
+pub async fn heartbeat_ping_handler(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Query(query): Query<HeartbeatPingQuery>,
+    headers: HeaderMap,
+) -> Response {
+    let token = bearer_token(&headers).or(query.token.as_deref());
+    if state
+        .heartbeats
+        .ping(&name, token, state.admin_token.as_deref())
+    {
+        StatusCode::NO_CONTENT.into_response()
+    } else {
+        (
+            StatusCode::NOT_FOUND,
+            format!(
+                "Heartbeat '{}' is not configured or the token is wrong",
+                name
+            ),
+        )
+            .into_response()
+    }
+}
+
+/// Handler for GET /api/containers/:name
+#[debug_handler]
+pub async fn container_detail_handler(
+    State(state): State<AppState>,
+    viewer: Option<Extension<Viewer>>,
+    Path(name): Path<String>,
+) -> Response {
+    let containers = match state.monitoring_service.get_containers().await {
+        Ok(c) => c,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+
+    let visible = |c: &Container| viewer.as_ref().is_none_or(|Extension(v)| v.sees(c));
+    match containers
+        .into_iter()
+        .find(|c| c.name == name && visible(c))
+    {
+        Some(mut container) => {
+            if !container.ports.is_empty() {
+                match state.monitoring_service.get_connections_by_port().await {
+                    Ok(Some(connections)) => {
+                        for port in &mut container.ports {
+                            let key = (port.protocol.clone(), port.host_port);
+                            port.connections = Some(connections.get(&key).copied().unwrap_or(0));
+                        }
+                    }
+                    Ok(None) => {}
+                    Err(e) => tracing::debug!("Failed to count connections: {}", e),
+                }
+            }
+            (StatusCode::OK, versioned(&state, container)).into_response()
+        }
+        None => (
+            StatusCode::NOT_FOUND,
+            format!("Container '{}' not found", name),
+        )
+            .into_response(),
+    }
 }
 
-/// Query params for /api/history
-#[derive(Debug, Deserialize)]
-pub struct HistoryQuery {
-    /// Duration in seconds (default: 3600 = 1 hour)
-    #[serde(default = "default_history_duration")]
-    pub duration: u64,
-}
+/// Handler for GET /api/containers/security
+pub async fn container_security_handler(State(state): State<AppState>) -> Response {
+    let mut containers = state.security.summary();
+    // Worst offenders first
+    containers.sort_by_key(|c| {
+        std::cmp::Reverse(
+            c.scan
+                .as_ref()
+                .map_or((0, 0), |s| (s.counts.critical, s.counts.high)),
+        )
+    });
 
-fn default_sort() -> String {
-    "cpu".to_string()
-}
+    let mut totals = VulnerabilityCounts::default();
+    for scan in containers.iter().filter_map(|c| c.scan.as_ref()) {
+        totals.merge(&scan.counts);
+    }
 
-fn default_limit() -> usize {
-    20
+    (
+        StatusCode::OK,
+        versioned(
+            &state,
+            SecurityResponse {
+                enabled: state.security.is_enabled(),
+                totals,
+                containers,
+            },
+        ),
+    )
+        .into_response()
 }
 
-fn default_history_duration() -> u64 {
-    3600
+/// Handler for GET /api/security/ssh
+pub async fn ssh_handler(State(state): State<AppState>) -> Response {
+    (
+        StatusCode::OK,
+        versioned(
+            &state,
+            SshResponse {
+                enabled: state.ssh.is_some(),
+                summary: state.ssh.as_ref().map(|monitor| {
+                    let summary = monitor.summary();
+                    if state.privacy_mode {
+                        summary.redacted()
+                    } else {
+                        summary
+                    }
+                }),
+            },
+        ),
+    )
+        .into_response()
 }
 
-/// Handler for GET /api/health
-pub async fn health_handler() -> (StatusCode, Json<serde_json::Value>) {
+/// Handler for GET /api/security/auth
+pub async fn api_auth_handler(State(state): State<AppState>) -> Response {
+    let summary = state.auth_guard.summary();
+    let summary = if state.privacy_mode {
+        summary.redacted()
+    } else {
+        summary
+    };
     (
         StatusCode::OK,
-        Json(serde_json::json!({
-            "status": "healthy",
-            "service": "nanomon",
-            "version": env!("CARGO_PKG_VERSION")
-        })),
+        versioned(&state, ApiAuthResponse { summary }),
     )
+        .into_response()
 }
 
-/// Handler for GET /api/host
-#[debug_handler]
-pub async fn host_handler(State(state): State<AppState>) -> Response {
-    match state.monitoring_service.collect_all().await {
-        Ok(host) => (StatusCode::OK, Json(HostResponse::from(&host))).into_response(),
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
-    }
+/// Handler for GET /api/config/schema
+pub async fn config_schema_handler(State(state): State<AppState>) -> Response {
+    let settings = config_check::schema(std::env::vars());
+    (
+        StatusCode::OK,
+        versioned(&state, ConfigSchemaResponse { settings }),
+    )
+        .into_response()
 }
 
-/// Handler for GET /api/containers
-pub async fn containers_handler(State(state): State<AppState>) -> Response {
-    let containers = match state.monitoring_service.get_containers().await {
-        Ok(c) => c,
-        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+/// Handler for GET /api/security/firewall
+pub async fn firewall_handler(State(state): State<AppState>) -> Response {
+    let summary = match &state.firewall {
+        Some(monitor) => match monitor.summary().await {
+            Ok(summary) => summary,
+            Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        },
+        None => None,
     };
 
-    let stacks = match state.monitoring_service.get_stacks().await {
-        Ok(s) => s,
-        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
-    };
+    (
+        StatusCode::OK,
+        versioned(
+            &state,
+            FirewallResponse {
+                enabled: state.firewall.is_some(),
+                available: summary.is_some(),
+                summary,
+            },
+        ),
+    )
+        .into_response()
+}
 
+/// Handler for GET /api/maintenance
+pub async fn maintenance_handler(State(state): State<AppState>) -> Response {
     (
         StatusCode::OK,
-        Json(ContainersResponse {
-            timestamp: chrono::Utc::now().to_rfc3339(),
-            containers,
-            stacks,
-        }),
+        versioned(
+            &state,
+            MaintenanceResponse {
+                windows: state.maintenance.active(),
+            },
+        ),
     )
         .into_response()
 }
 
-/// Handler for GET /api/processes
-pub async fn processes_handler(
+/// Handler for POST /api/maintenance: pause collectors for a while
+pub async fn maintenance_start_handler(
     State(state): State<AppState>,
-    Query(params): Query<ProcessQuery>,
+    headers: HeaderMap,
+    Json(request): Json<MaintenanceRequest>,
 ) -> Response {
-    let result = match params.sort.as_str() {
-        "memory" => {
-            state
-                .monitoring_service
-                .get_top_processes_by_memory(params.limit)
-                .await
-        }
-        _ => {
-            state
-                .monitoring_service
-                .get_top_processes_by_cpu(params.limit)
-                .await
-        }
+    if let Some(denied) = state.require_admin(&headers) {
+        return denied;
+    }
+    let Some(duration) = parse_window(&request.duration) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            format!(
+                "Invalid duration '{}', expected e.g. 30m or 2h",
+                request.duration
+            ),
+        )
+            .into_response();
     };
 
-    match result {
-        Ok(processes) => (
+    match state
+        .maintenance
+        .start(&request.collectors, duration, request.reason)
+    {
+        Ok(windows) => (
             StatusCode::OK,
-            Json(ProcessesResponse {
-                timestamp: chrono::Utc::now().to_rfc3339(),
-                processes,
-            }),
+            versioned(&state, MaintenanceResponse { windows }),
         )
             .into_response(),
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        Err(e @ (MaintenanceError::NoCollectors | MaintenanceError::InvalidDuration)) => {
+            (StatusCode::BAD_REQUEST, e.to_string()).into_response()
+        }
     }
 }
 
-/// Handler for GET /api/disks
-#[debug_handler]
-pub async fn disks_handler(State(state): State<AppState>) -> Response {
-    match state.monitoring_service.collect_all().await {
-        Ok(host) => (
-            StatusCode::OK,
-            Json(DisksResponse {
-                timestamp: host.timestamp.to_rfc3339(),
-                disks: serde_json::to_value(&host.disks).unwrap(),
-            }),
+/// Handler for DELETE /api/maintenance: resume every paused collector
+pub async fn maintenance_end_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Response {
+    if let Some(denied) = state.require_admin(&headers) {
+        return denied;
+    }
+    state.maintenance.end();
+    StatusCode::NO_CONTENT.into_response()
+}
+
+/// Handler for GET /api/burnin: the running burn-in's report, or the last one
+pub async fn burn_in_handler(State(state): State<AppState>) -> Response {
+    match state.burn_in.report() {
+        Some(report) => (StatusCode::OK, versioned(&state, report)).into_response(),
+        None => (StatusCode::NOT_FOUND, "No burn-in has run yet").into_response(),
+    }
+}
+
+/// Handler for POST /api/burnin: record alerts as expected while stress
+/// testing
+pub async fn burn_in_start_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<BurnInRequest>,
+) -> Response {
+    if let Some(denied) = state.require_admin(&headers) {
+        return denied;
+    }
+    let Some(duration) = parse_window(&request.duration) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            format!(
+                "Invalid duration '{}', expected e.g. 2h or 3d",
+                request.duration
+            ),
         )
-            .into_response(),
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+            .into_response();
+    };
+
+    match state.burn_in.start(duration, request.reason) {
+        Ok(report) => (StatusCode::OK, versioned(&state, report)).into_response(),
+        Err(e @ BurnInError::InvalidDuration) => {
+            (StatusCode::BAD_REQUEST, e.to_string()).into_response()
+        }
+        Err(e @ BurnInError::Running(_)) => (StatusCode::CONFLICT, e.to_string()).into_response(),
     }
 }
 
-/// Handler for GET /api/network
-#[debug_handler]
-pub async fn network_handler(State(state): State<AppState>) -> Response {
-    match state.monitoring_service.collect_all().await {
-        Ok(host) => (
-            StatusCode::OK,
-            Json(NetworkResponse {
-                timestamp: host.timestamp.to_rfc3339(),
-                interfaces: serde_json::to_value(&host.network_interfaces).unwrap(),
-            }),
+/// Handler for DELETE /api/burnin: end the burn-in now and return its report
+pub async fn burn_in_end_handler(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    if let Some(denied) = state.require_admin(&headers) {
+        return denied;
+    }
+    match state.burn_in.end() {
+        Some(report) => (StatusCode::OK, versioned(&state, report)).into_response(),
+        None => (StatusCode::NOT_FOUND, "No burn-in is running").into_response(),
+    }
+}
+
+/// Handler for GET /api/admin/backup: tar.gz of the alert config, state file
+/// and snapshot archive
+pub async fn backup_handler(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    if let Some(denied) = state.require_admin(&headers) {
+        return denied;
+    }
+    #[cfg(not(feature = "backup"))]
+    return (
+        StatusCode::NOT_IMPLEMENTED,
+        "Built without the backup feature",
+    )
+        .into_response();
+
+    #[cfg(feature = "backup")]
+    {
+        let files = state.data_files.clone();
+        let disposition = format!(
+            "attachment; filename=\"nanomon-backup-{}.tar.gz\"",
+            chrono::Utc::now().format("%Y%m%d-%H%M%S")
+        );
+        (
+            [
+                (header::CONTENT_TYPE, "application/gzip".to_string()),
+                (header::CONTENT_DISPOSITION, disposition),
+            ],
+            streamed_body(move |out| files.write_backup(out).map(|_| ())),
         )
-            .into_response(),
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+            .into_response()
     }
 }
 
-/// Handler for GET /api/dashboard (aggregated endpoint)
-#[debug_handler]
-pub async fn dashboard_handler(State(state): State<AppState>) -> Response {
-    let host = match state.monitoring_service.collect_all().await {
-        Ok(h) => h,
-        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+/// Handler for GET /api/annotations
+pub async fn annotations_handler(
+    State(state): State<AppState>,
+    Query(params): Query<HistoryQuery>,
+) -> Response {
+    let Some(window) = params.window() else {
+        return (StatusCode::BAD_REQUEST, "Invalid window, e.g. 1h").into_response();
     };
+    (
+        StatusCode::OK,
+        versioned(
+            &state,
+            AnnotationsResponse {
+                annotations: state.monitoring_service.get_annotations(window),
+            },
+        ),
+    )
+        .into_response()
+}
 
-    let stacks = match state.monitoring_service.get_stacks().await {
-        Ok(s) => s,
-        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
-    };
+/// Handler for POST /api/annotations: pin a note to a point in time
+pub async fn annotation_create_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<AnnotationRequest>,
+) -> Response {
+    if let Some(denied) = state.require_admin(&headers) {
+        return denied;
+    }
+    let now = chrono::Utc::now();
+    let timestamp = request.timestamp.unwrap_or(now);
+    if timestamp > now {
+        return (
+            StatusCode::BAD_REQUEST,
+            "Annotation timestamp is in the future",
+        )
+            .into_response();
+    }
+    match Annotation::new(&request.note, timestamp) {
+        Ok(annotation) => {
+            let annotation = annotation.with_tags(&request.tags);
+            state.monitoring_service.annotate(annotation.clone());
+            (StatusCode::CREATED, versioned(&state, annotation)).into_response()
+        }
+        Err(e) => (StatusCode::BAD_REQUEST, e).into_response(),
+    }
+}
 
-    let processes = match state.monitoring_service.get_top_processes_by_cpu(20).await {
-        Ok(p) => p,
-        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+/// Handler for GET /api/nagios/:check: Nagios plugin output for check_http or Zabbix
+pub async fn nagios_handler(
+    State(state): State<AppState>,
+    Path(check): Path<String>,
+    Query(params): Query<NagiosQuery>,
+) -> Response {
+    let check = match check.parse::<NagiosCheck>() {
+        Ok(check) => check,
+        Err(e) => return (StatusCode::NOT_FOUND, e).into_response(),
+    };
+    let result = match state.monitoring_service.get_latest_snapshot() {
+        Some(host) => check.evaluate(&host, params.warn, params.crit),
+        None => CheckResult::unknown(check.service(), "no snapshot collected yet"),
     };
-
     (
         StatusCode::OK,
-        Json(DashboardResponse {
-            host: HostResponse::from(&host),
-            containers: host.containers.clone(),
-            stacks,
-            processes,
-            disks: serde_json::to_value(&host.disks).unwrap(),
-            network: serde_json::to_value(&host.network_interfaces).unwrap(),
-        }),
+        [(header::CONTENT_TYPE, "text/plain; charset=utf-8")],
+        result.render() + "\n",
     )
         .into_response()
 }
 
-/// Handler for GET /api/history
-#[debug_handler]
-pub async fn history_handler(
+/// Handler for GET /api/alerts/stats
+pub async fn alert_stats_handler(
     State(state): State<AppState>,
-    Query(params): Query<HistoryQuery>,
+    Query(params): Query<AlertStatsQuery>,
 ) -> Response {
-    let history = state
-        .monitoring_service
-        .get_history(Duration::from_secs(params.duration));
-
-    if history.is_empty() {
+    let Some(window) = parse_window(&params.window) else {
         return (
-            StatusCode::OK,
-            Json(HistoryResponse {
-                timestamps: Vec::new(),
-                cpu: Vec::new(),
-                memory_used: Vec::new(),
-                memory_total: 0,
-                load_1: Vec::new(),
-                load_5: Vec::new(),
-                load_15: Vec::new(),
-            }),
+            StatusCode::BAD_REQUEST,
+            format!(
+                "Invalid window '{}', expected e.g. 24h or 7d",
+                params.window
+            ),
         )
             .into_response();
-    }
+    };
+    (
+        StatusCode::OK,
+        versioned(&state, state.alerts.stats(window)),
+    )
+        .into_response()
+}
 
-    let memory_total = history.last().map(|h| h.memory.total_bytes).unwrap_or(0);
+/// Handler for GET /api/alerts: the incidents still firing, with their IDs
+/// to acknowledge
+pub async fn alerts_handler(State(state): State<AppState>) -> Response {
+    let open = state.alerts.open_incidents();
+    (StatusCode::OK, versioned(&state, AlertsResponse { open })).into_response()
+}
 
-    let response = HistoryResponse {
-        timestamps: history.iter().map(|h| h.timestamp.to_rfc3339()).collect(),
-        cpu: history.iter().map(|h| h.cpu.usage_percent).collect(),
-        memory_used: history.iter().map(|h| h.memory.used_bytes).collect(),
-        memory_total,
-        load_1: history.iter().map(|h| h.load_average.one).collect(),
-        load_5: history.iter().map(|h| h.load_average.five).collect(),
-        load_15: history.iter().map(|h| h.load_average.fifteen).collect(),
+/// Handler for POST /api/alerts/{id}/ack: stops an incident's repeat
+/// notifications and escalation
+pub async fn alert_ack_handler(
+    State(state): State<AppState>,
+    Path(id): Path<u64>,
+    headers: HeaderMap,
+    request: Option<Json<AckRequest>>,
+) -> Response {
+    if let Some(denied) = state.require_admin(&headers) {
+        return denied;
+    }
+    let by = request.and_then(|Json(r)| r.by).filter(|by| !by.is_empty());
+    match state.alerts.acknowledge(id, by) {
+        Some(incident) => (StatusCode::OK, versioned(&state, incident)).into_response(),
+        None => (StatusCode::NOT_FOUND, format!("No open alert {}", id)).into_response(),
+    }
+}
+
+/// Handler for POST /api/alerts/test: when a rule would have fired over the
+/// stored history, without sending anything
+pub async fn alert_test_handler(
+    State(state): State<AppState>,
+    Json(request): Json<AlertTestRequest>,
+) -> Response {
+    let Some(window) = parse_window(&request.window) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            format!(
+                "Invalid window '{}', expected e.g. 24h or 7d",
+                request.window
+            ),
+        )
+            .into_response();
+    };
+    let rule = AlertRule {
+        name: request.name,
+        metric: request.metric,
+        condition: request.condition,
+        threshold: request.threshold,
+        webhook_url: String::new(),
+        cooldown_seconds: request.cooldown_seconds,
+        severity: request.severity,
+        escalation: Vec::new(),
     };
+    let history = state.monitoring_service.get_history(window);
+    let run = AlertDryRun::run(&rule, history.iter().map(|s| s.as_ref()));
+    (StatusCode::OK, versioned(&state, run)).into_response()
+}
 
-    (StatusCode::OK, Json(response)).into_response()
+/// Handler for GET /api/security/firewall/counters
+pub async fn firewall_counters_handler(State(state): State<AppState>) -> Response {
+    let monitor = state.firewall.as_ref().filter(|m| m.counters_enabled());
+    (
+        StatusCode::OK,
+        versioned(
+            &state,
+            FirewallCountersResponse {
+                enabled: monitor.is_some(),
+                counters: monitor.map(|m| m.counter_series()).unwrap_or_default(),
+            },
+        ),
+    )
+        .into_response()
 }
 
-/// Handler for GET /api/services
-#[debug_handler]
-pub async fn services_handler(State(state): State<AppState>) -> Response {
-    let available = state.monitoring_service.has_services();
-    let services = match state.monitoring_service.get_services().await {
-        Ok(s) => s,
-        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+/// Handler for GET /api/vms
+pub async fn vms_handler(State(state): State<AppState>) -> Response {
+    let vms = match &state.vms {
+        Some(monitor) => match monitor.list().await {
+            Ok(vms) => vms,
+            Err(e) => return (StatusCode::BAD_GATEWAY, e.to_string()).into_response(),
+        },
+        None => Vec::new(),
     };
 
     (
         StatusCode::OK,
-        Json(ServicesResponse {
-            timestamp: chrono::Utc::now().to_rfc3339(),
-            services,
-            available,
-        }),
+        versioned(
+            &state,
+            VmsResponse {
+                enabled: state.vms.is_some(),
+                vms,
+            },
+        ),
     )
         .into_response()
 }
 
-/// Handler for GET /api/containers/:name
-#[debug_handler]
-pub async fn container_detail_handler(
+/// Handler for GET /api/containers/:name/volumes (name or ID)
+pub async fn container_volumes_handler(
     State(state): State<AppState>,
+    viewer: Option<Extension<Viewer>>,
     Path(name): Path<String>,
 ) -> Response {
-    let containers = match state.monitoring_service.get_containers().await {
-        Ok(c) => c,
-        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    if let Some(Extension(viewer)) = viewer {
+        let visible = state
+            .monitoring_service
+            .get_latest_snapshot()
+            .is_some_and(|host| {
+                host.containers
+                    .iter()
+                    .any(|c| (c.name == name || c.id.as_str() == name) && viewer.sees(c))
+            });
+        if !visible {
+            return (
+                StatusCode::NOT_FOUND,
+                format!("Container '{}' not found", name),
+            )
+                .into_response();
+        }
+    }
+    match state
+        .volumes
+        .container_volumes(&ContainerId::new(name.clone()))
+        .await
+    {
+        Ok(Some(volumes)) => (
+            StatusCode::OK,
+            versioned(
+                &state,
+                VolumesResponse {
+                    container: name,
+                    volumes: if state.privacy_mode {
+                        volumes.into_iter().map(VolumeUsage::redacted).collect()
+                    } else {
+                        volumes
+                    },
+                },
+            ),
+        )
+            .into_response(),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            format!("Container '{}' not found", name),
+        )
+            .into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+/// Handler for GET /api/containers/:name/recommendations (name or ID)
+pub async fn container_recommendations_handler(
+    State(state): State<AppState>,
+    viewer: Option<Extension<Viewer>>,
+    Path(name): Path<String>,
+    Query(params): Query<RecommendationQuery>,
+) -> Response {
+    let Some(window) = parse_window(&params.window) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            format!("Invalid window '{}', expected e.g. 7d", params.window),
+        )
+            .into_response();
+    };
+    let visible = |c: &Container| viewer.as_ref().is_none_or(|Extension(v)| v.sees(c));
+    let Some(identity) = state
+        .monitoring_service
+        .get_latest_snapshot()
+        .and_then(|host| {
+            host.containers
+                .iter()
+                .find(|c| (c.name == name || c.id.as_str() == name) && visible(c))
+                .map(Container::identity)
+        })
+    else {
+        return (
+            StatusCode::NOT_FOUND,
+            format!("Container '{}' not found", name),
+        )
+            .into_response();
     };
 
-    match containers.into_iter().find(|c| c.name == name) {
-        Some(container) => (StatusCode::OK, Json(container)).into_response(),
+    match state
+        .monitoring_service
+        .get_quota_recommendation(&identity, window)
+    {
+        Some(recommendation) => (StatusCode::OK, versioned(&state, recommendation)).into_response(),
         None => (
             StatusCode::NOT_FOUND,
-            format!("Container '{}' not found", name),
+            format!(
+                "Not enough history of container '{}' yet, it must have run for {}s",
+                name, QUOTA_MIN_OBSERVED_SECONDS
+            ),
         )
             .into_response(),
     }
@@ -379,12 +2356,9 @@ pub async fn container_detail_handler(
 #[debug_handler]
 pub async fn prometheus_handler(State(state): State<AppState>) -> Response {
     // Try latest snapshot from store first, fall back to live collection
-    let host = match state.monitoring_service.get_latest_snapshot() {
-        Some(h) => (*h).clone(),
-        None => match state.monitoring_service.collect_all().await {
-            Ok(h) => h,
-            Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
-        },
+    let host = match state.monitoring_service.snapshot().await {
+        Ok(h) => h,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
     };
 
     let mut output = String::with_capacity(4096);
@@ -521,6 +2495,26 @@ pub async fn prometheus_handler(State(state): State<AppState>) -> Response {
             container.memory.used_bytes as f64,
             &labels,
         );
+        if let Some(pids) = container.pids {
+            write_metric(
+                &mut output,
+                "nanomon_container_pids",
+                "gauge",
+                "Tasks running in the container",
+                pids.current as f64,
+                &labels,
+            );
+            if let Some(limit) = pids.limit {
+                write_metric(
+                    &mut output,
+                    "nanomon_container_pids_limit",
+                    "gauge",
+                    "Container pids limit",
+                    limit as f64,
+                    &labels,
+                );
+            }
+        }
     }
 
     // Temperatures
@@ -541,6 +2535,70 @@ pub async fn prometheus_handler(State(state): State<AppState>) -> Response {
         );
     }
 
+    // Kernel limits
+    for (name, limit) in host.kernel_limits.iter() {
+        let labels = [("limit", name)];
+        write_metric(
+            &mut output,
+            "nanomon_kernel_limit_used",
+            "gauge",
+            "Kernel table entries in use",
+            limit.used as f64,
+            &labels,
+        );
+        write_metric(
+            &mut output,
+            "nanomon_kernel_limit_max",
+            "gauge",
+            "Kernel table capacity",
+            limit.max as f64,
+            &labels,
+        );
+    }
+
+    // PIDs skipped by the latest process scan
+    if let Some(scan) = state.monitoring_service.process_scan_stats() {
+        for (outcome, count) in [
+            ("read", scan.read),
+            ("cached", scan.cached),
+            ("skipped", scan.skipped),
+            ("vanished", scan.vanished),
+            ("failed", scan.failed),
+        ] {
+            write_metric(
+                &mut output,
+                "nanomon_process_scan_pids",
+                "gauge",
+                "PIDs in the latest process scan by outcome",
+                count as f64,
+                &[("outcome", outcome)],
+            );
+        }
+    }
+
+    // NanoMon's own footprint and HTTP server
+    if let Some(memory) = state.footprint.check() {
+        write_metric(
+            &mut output,
+            "nanomon_process_resident_memory_bytes",
+            "gauge",
+            "Resident memory of NanoMon itself",
+            memory.rss_bytes as f64,
+            &[],
+        );
+        if let Some(budget) = memory.budget_bytes {
+            write_metric(
+                &mut output,
+                "nanomon_memory_budget_bytes",
+                "gauge",
+                "Configured memory budget of NanoMon",
+                budget as f64,
+                &[],
+            );
+        }
+    }
+    state.http_metrics.write_prometheus(&mut output);
+
     (
         StatusCode::OK,
         [(
@@ -0,0 +1,90 @@
+use std::net::{IpAddr, SocketAddr};
+
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::{header, HeaderMap, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use super::handlers::AppState;
+use super::visibility::request_token;
+
+/// The client's address: the peer, or when the peer is a trusted reverse
+/// proxy, the last address it appended to `X-Forwarded-For` that is not
+/// itself a trusted proxy
+fn client_ip(peer: IpAddr, headers: &HeaderMap, trusted_proxies: &[IpAddr]) -> IpAddr {
+    if !trusted_proxies.contains(&peer) {
+        return peer;
+    }
+    headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .filter_map(|ip| ip.trim().parse::<IpAddr>().ok())
+        .rev()
+        .find(|ip| !trusted_proxies.contains(ip))
+        .unwrap_or(peer)
+}
+
+/// Answers locked-out addresses with a 429, and counts each 401 answered to
+/// a request that presented a token towards locking its address out
+pub async fn guard_auth(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    let Some(ConnectInfo(peer)) = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .copied()
+    else {
+        return next.run(request).await;
+    };
+    let ip = client_ip(peer.ip(), request.headers(), &state.trusted_proxies);
+
+    if let Some(left) = state.auth_guard.locked(ip) {
+        return (
+            StatusCode::TOO_MANY_REQUESTS,
+            [(header::RETRY_AFTER, (left.as_secs() + 1).to_string())],
+            "Too many rejected tokens, try again later",
+        )
+            .into_response();
+    }
+
+    let presented_token = request_token(&request).is_some();
+    let path = request.uri().path().to_string();
+    let response = next.run(request).await;
+    if presented_token && response.status() == StatusCode::UNAUTHORIZED {
+        state.auth_guard.record_failure(ip, &path);
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_client_ip_behind_trusted_proxies() {
+        let ip = |s: &str| s.parse::<IpAddr>().unwrap();
+        let proxies = [ip("172.18.0.2"), ip("10.0.0.1")];
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "x-forwarded-for",
+            "6.6.6.6, 203.0.113.9, 10.0.0.1".parse().unwrap(),
+        );
+
+        // A spoofed leftmost entry is ignored
+        assert_eq!(
+            client_ip(ip("172.18.0.2"), &headers, &proxies),
+            ip("203.0.113.9")
+        );
+        // Untrusted peers cannot claim another address
+        assert_eq!(
+            client_ip(ip("192.0.2.1"), &headers, &proxies),
+            ip("192.0.2.1")
+        );
+        assert_eq!(
+            client_ip(ip("172.18.0.2"), &HeaderMap::new(), &proxies),
+            ip("172.18.0.2")
+        );
+    }
+}
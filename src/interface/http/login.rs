@@ -0,0 +1,198 @@
+//! Dashboard login through an OpenID Connect provider, kept in a session cookie
+
+use axum::{
+    extract::{Query, Request, State},
+    http::{header, HeaderMap, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Redirect, Response},
+};
+use serde::Deserialize;
+use tracing::{info, warn};
+
+use crate::application::LoginError;
+
+use super::handlers::AppState;
+use super::visibility::{api_route, is_open_path, request_token};
+
+const SESSION_COOKIE: &str = "nanomon_session";
+
+/// Marks a request authenticated by its session cookie
+#[derive(Debug, Clone, Copy)]
+pub struct LoggedIn;
+
+#[derive(Debug, Deserialize)]
+pub struct CallbackQuery {
+    code: Option<String>,
+    state: Option<String>,
+    error: Option<String>,
+    error_description: Option<String>,
+}
+
+fn session_id(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(';'))
+        .find_map(|c| c.trim().strip_prefix(SESSION_COOKIE)?.strip_prefix('='))
+}
+
+/// `Set-Cookie` value for session `id`, or clearing it when `None`
+fn session_cookie(state: &AppState, id: Option<&str>, max_age: u64) -> String {
+    let path = if state.base_path.is_empty() {
+        "/"
+    } else {
+        &state.base_path
+    };
+    format!(
+        "{}={}; Path={}; Max-Age={}; HttpOnly; SameSite=Lax{}",
+        SESSION_COOKIE,
+        id.unwrap_or(""),
+        path,
+        max_age,
+        if state.secure_cookies { "; Secure" } else { "" }
+    )
+}
+
+/// A local path to come back to after logging in; anything else could send
+/// the browser to another site
+fn local_path(state: &AppState, path: &str) -> String {
+    if path.starts_with('/') && !path.starts_with("//") && !path.starts_with("/\\") {
+        path.to_string()
+    } else {
+        format!("{}/", state.base_path)
+    }
+}
+
+async fn start_login(state: &AppState, return_to: String) -> Response {
+    let Some(logins) = &state.logins else {
+        return (StatusCode::NOT_FOUND, "Login is not configured").into_response();
+    };
+    match logins.start(return_to).await {
+        Ok(url) => Redirect::to(&url).into_response(),
+        Err(e) => {
+            warn!("Cannot start login: {}", e);
+            (StatusCode::BAD_GATEWAY, e.to_string()).into_response()
+        }
+    }
+}
+
+/// Handler for GET /auth/login
+pub async fn login_handler(State(state): State<AppState>) -> Response {
+    let home = format!("{}/", state.base_path);
+    start_login(&state, home).await
+}
+
+/// Handler for GET /auth/callback, where the provider sends the browser back
+pub async fn callback_handler(
+    State(state): State<AppState>,
+    Query(query): Query<CallbackQuery>,
+) -> Response {
+    let Some(logins) = &state.logins else {
+        return (StatusCode::NOT_FOUND, "Login is not configured").into_response();
+    };
+    if let Some(error) = query.error {
+        let reason = query.error_description.unwrap_or(error);
+        return (StatusCode::FORBIDDEN, format!("Login refused: {}", reason)).into_response();
+    }
+    let (Some(code), Some(login)) = (query.code, query.state) else {
+        return (StatusCode::BAD_REQUEST, "Missing code or state").into_response();
+    };
+
+    match logins.finish(&login, &code).await {
+        Ok((id, user, return_to)) => {
+            info!("User '{}' logged in to the dashboard", user);
+            let cookie = session_cookie(&state, Some(&id), logins.session_ttl().as_secs());
+            (
+                [(header::SET_COOKIE, cookie)],
+                Redirect::to(&local_path(&state, &return_to)),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            warn!("Dashboard login failed: {}", e);
+            let status = match e {
+                LoginError::UnknownState => StatusCode::BAD_REQUEST,
+                LoginError::NotAllowed(_) => StatusCode::FORBIDDEN,
+                LoginError::Failed(_) => StatusCode::BAD_GATEWAY,
+            };
+            (status, e.to_string()).into_response()
+        }
+    }
+}
+
+/// Handler for GET /auth/logout
+pub async fn logout_handler(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    if let (Some(logins), Some(id)) = (&state.logins, session_id(&headers)) {
+        logins.logout(id);
+    }
+    (
+        [(header::SET_COOKIE, session_cookie(&state, None, 0))],
+        "Logged out of NanoMon",
+    )
+        .into_response()
+}
+
+/// With login configured, every page and API call needs a session or a
+/// known token (admin, ingest or viewer); health checks and the endpoints
+/// checking a token of their own stay open. Pages start a login, API calls
+/// get a 401.
+pub async fn require_login(
+    State(state): State<AppState>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let Some(logins) = &state.logins else {
+        return next.run(request).await;
+    };
+    let full_path = request.uri().path();
+    let path = full_path
+        .strip_prefix(state.base_path.as_str())
+        .unwrap_or(full_path);
+    let api = api_route("", path);
+    if path.starts_with("/auth/") || api.is_some_and(is_open_path) {
+        return next.run(request).await;
+    }
+
+    if let Some(token) = request_token(&request) {
+        let known = [&state.admin_token, &state.ingest_token]
+            .into_iter()
+            .any(|t| t.as_deref() == Some(token))
+            || state.viewers.iter().any(|v| v.token == token);
+        if known {
+            return next.run(request).await;
+        }
+    }
+    if session_id(request.headers()).is_some_and(|id| logins.user(id).is_some()) {
+        request.extensions_mut().insert(LoggedIn);
+        return next.run(request).await;
+    }
+
+    if api.is_some() || path == "/metrics" || request.method() != Method::GET {
+        return (StatusCode::UNAUTHORIZED, "Login required").into_response();
+    }
+    let return_to = request
+        .uri()
+        .path_and_query()
+        .map(|p| p.as_str().to_string())
+        .unwrap_or_default();
+    start_login(&state, local_path(&state, &return_to)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_cookie_is_read_among_others() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::COOKIE,
+            "theme=dark; nanomon_session=abc123".parse().unwrap(),
+        );
+        assert_eq!(session_id(&headers), Some("abc123"));
+
+        headers.insert(header::COOKIE, "nanomon_sessionx=abc123".parse().unwrap());
+        assert_eq!(session_id(&headers), None);
+    }
+}
@@ -0,0 +1,214 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use axum::{
+    extract::{MatchedPath, Request, State},
+    middleware::Next,
+    response::Response,
+};
+use serde::Serialize;
+use tracing::Instrument;
+
+/// Label for requests that matched no API route (static files, 404s)
+const UNMATCHED_ROUTE: &str = "other";
+
+/// Counters for one method and route template
+#[derive(Debug, Clone, Default)]
+struct RouteCounters {
+    requests: u64,
+    client_errors: u64,
+    server_errors: u64,
+    latency: Duration,
+    max_latency: Duration,
+}
+
+/// Per-route statistics as served by /api/self
+#[derive(Debug, Serialize)]
+pub struct RouteStats {
+    pub method: String,
+    pub route: String,
+    pub requests: u64,
+    pub client_errors: u64,
+    pub server_errors: u64,
+    /// Share of requests answered with a 5xx status
+    pub error_rate: f64,
+    pub avg_latency_ms: f64,
+    pub max_latency_ms: f64,
+}
+
+/// Request counts, errors and latencies of NanoMon's own HTTP server.
+/// Routes are keyed by template (`/api/v1/containers/{name}`) to bound cardinality;
+/// for streams the latency covers the time to the first byte only.
+pub struct HttpMetrics {
+    started: Instant,
+    routes: Mutex<BTreeMap<(String, String), RouteCounters>>,
+}
+
+impl HttpMetrics {
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            routes: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    pub fn record(&self, method: &str, route: &str, status: u16, latency: Duration) {
+        let mut routes = self.routes.lock().unwrap();
+        let counters = routes
+            .entry((route.to_string(), method.to_string()))
+            .or_default();
+        counters.requests += 1;
+        match status {
+            400..=499 => counters.client_errors += 1,
+            500..=599 => counters.server_errors += 1,
+            _ => {}
+        }
+        counters.latency += latency;
+        counters.max_latency = counters.max_latency.max(latency);
+    }
+
+    pub fn uptime(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    /// Statistics for every route seen so far, ordered by route
+    pub fn routes(&self) -> Vec<RouteStats> {
+        self.routes
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|((route, method), c)| RouteStats {
+                method: method.clone(),
+                route: route.clone(),
+                requests: c.requests,
+                client_errors: c.client_errors,
+                server_errors: c.server_errors,
+                error_rate: c.server_errors as f64 / c.requests as f64,
+                avg_latency_ms: c.latency.as_secs_f64() * 1000.0 / c.requests as f64,
+                max_latency_ms: c.max_latency.as_secs_f64() * 1000.0,
+            })
+            .collect()
+    }
+
+    /// Append the counters in Prometheus text format
+    pub fn write_prometheus(&self, output: &mut String) {
+        let routes = self.routes.lock().unwrap();
+        let series = |name: &str, metric_type: &str, help: &str| {
+            format!(
+                "# HELP {} {}\n# TYPE {} {}\n",
+                name, help, name, metric_type
+            )
+        };
+
+        output.push_str(&series(
+            "nanomon_http_requests_total",
+            "counter",
+            "HTTP requests served by NanoMon",
+        ));
+        for ((route, method), c) in routes.iter() {
+            let _ = writeln!(
+                output,
+                "nanomon_http_requests_total{{method=\"{}\",route=\"{}\"}} {}",
+                method, route, c.requests
+            );
+        }
+
+        output.push_str(&series(
+            "nanomon_http_errors_total",
+            "counter",
+            "HTTP responses with a 4xx or 5xx status",
+        ));
+        for ((route, method), c) in routes.iter() {
+            for (class, count) in [("4xx", c.client_errors), ("5xx", c.server_errors)] {
+                let _ = writeln!(
+                    output,
+                    "nanomon_http_errors_total{{method=\"{}\",route=\"{}\",class=\"{}\"}} {}",
+                    method, route, class, count
+                );
+            }
+        }
+
+        output.push_str(&series(
+            "nanomon_http_request_duration_seconds",
+            "summary",
+            "Time to respond to HTTP requests",
+        ));
+        for ((route, method), c) in routes.iter() {
+            let labels = format!("method=\"{}\",route=\"{}\"", method, route);
+            let _ = writeln!(
+                output,
+                "nanomon_http_request_duration_seconds_sum{{{}}} {}",
+                labels,
+                c.latency.as_secs_f64()
+            );
+            let _ = writeln!(
+                output,
+                "nanomon_http_request_duration_seconds_count{{{}}} {}",
+                labels, c.requests
+            );
+        }
+    }
+}
+
+impl Default for HttpMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Records every request against its matched route template, inside a span
+/// exported with the `otel` feature
+pub async fn track_request(
+    State(metrics): State<Arc<HttpMetrics>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let method = request.method().to_string();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map_or(UNMATCHED_ROUTE.to_string(), |p| p.as_str().to_string());
+    let span = tracing::info_span!(
+        "http_request",
+        http.request.method = %method,
+        http.route = %route,
+        http.response.status_code = tracing::field::Empty,
+    );
+    let started = Instant::now();
+    let response = next.run(request).instrument(span.clone()).await;
+    span.record("http.response.status_code", response.status().as_u16());
+    metrics.record(
+        &method,
+        &route,
+        response.status().as_u16(),
+        started.elapsed(),
+    );
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_route_stats() {
+        let metrics = HttpMetrics::new();
+        metrics.record("GET", "/api/v1/host", 200, Duration::from_millis(10));
+        metrics.record("GET", "/api/v1/host", 500, Duration::from_millis(30));
+
+        let routes = metrics.routes();
+        assert_eq!(routes.len(), 1);
+        assert_eq!(routes[0].requests, 2);
+        assert_eq!(routes[0].error_rate, 0.5);
+        assert_eq!(routes[0].avg_latency_ms, 20.0);
+        assert_eq!(routes[0].max_latency_ms, 30.0);
+
+        let mut output = String::new();
+        metrics.write_prometheus(&mut output);
+        assert!(
+            output.contains("nanomon_http_requests_total{method=\"GET\",route=\"/api/v1/host\"} 2")
+        );
+    }
+}
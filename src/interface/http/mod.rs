@@ -1,4 +1,18 @@
+mod access_log;
+mod cache;
+mod fields;
+mod grafana;
 mod handlers;
+mod lockout;
+mod login;
+mod metrics;
+mod precision;
 mod routes;
+mod stream;
+mod visibility;
 
+pub use access_log::{AccessLog, DEFAULT_ACCESS_LOG_EXCLUDE};
+pub use cache::ResponseCache;
+pub use handlers::AppState;
+pub use metrics::HttpMetrics;
 pub use routes::create_router;
@@ -0,0 +1,74 @@
+use std::io::{self, Write};
+
+use axum::{
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+use serde_json::ser::{CompactFormatter, Formatter};
+
+/// Compact JSON with every float rounded to `decimals` places, kept whole
+/// when `None`; integers are written as they are
+pub struct RoundingFormatter {
+    pub decimals: Option<u32>,
+}
+
+impl Formatter for RoundingFormatter {
+    fn write_f64<W: ?Sized + Write>(&mut self, writer: &mut W, value: f64) -> io::Result<()> {
+        let value = match self.decimals {
+            Some(decimals) => {
+                let factor = 10f64.powi(decimals as i32);
+                (value * factor).round() / factor
+            }
+            None => value,
+        };
+        CompactFormatter.write_f64(writer, value)
+    }
+}
+
+/// `body` serialized with floats rounded to `precision` decimals
+pub fn to_rounded_vec<T: Serialize + ?Sized>(
+    body: &T,
+    precision: Option<u32>,
+) -> serde_json::Result<Vec<u8>> {
+    let mut buf = Vec::with_capacity(128);
+    let formatter = RoundingFormatter {
+        decimals: precision,
+    };
+    body.serialize(&mut serde_json::Serializer::with_formatter(
+        &mut buf, formatter,
+    ))?;
+    Ok(buf)
+}
+
+/// JSON response with floats rounded while serializing, so the configured
+/// precision costs no second pass over the body
+pub fn rounded_json<T: Serialize + ?Sized>(body: &T, precision: Option<u32>) -> Response {
+    match to_rounded_vec(body, precision) {
+        Ok(bytes) => ([(header::CONTENT_TYPE, "application/json")], bytes).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_floats_are_rounded_while_serializing() {
+        let value = serde_json::json!({
+            "usage_percent": 33.333333333333336,
+            "bytes": 1234567,
+            "nested": [{"load": 0.456}, 1.0]
+        });
+
+        assert_eq!(
+            to_rounded_vec(&value, Some(1)).unwrap(),
+            br#"{"bytes":1234567,"nested":[{"load":0.5},1.0],"usage_percent":33.3}"#
+        );
+        assert_eq!(
+            to_rounded_vec(&value, None).unwrap(),
+            serde_json::to_vec(&value).unwrap()
+        );
+    }
+}
@@ -1,36 +1,270 @@
-use std::sync::Arc;
-
-use axum::{routing::get, Router};
-use tower_http::{cors::CorsLayer, services::ServeDir};
+use axum::{
+    extract::{OriginalUri, Request},
+    http::{header, HeaderValue},
+    middleware::{self, Next},
+    response::{Redirect, Response},
+    routing::{get, post},
+    Router,
+};
+use tower_http::{
+    cors::CorsLayer,
+    services::{ServeDir, ServeFile},
+};
 
-use crate::application::MonitoringService;
+use super::access_log::log_request;
+use super::cache::cache_responses;
+use super::grafana::{
+    grafana_annotations_handler, grafana_query_handler, grafana_root_handler,
+    grafana_search_handler,
+};
+use super::lockout::guard_auth;
+use super::login::{callback_handler, login_handler, logout_handler, require_login};
+use super::metrics::track_request;
+use super::visibility::enforce_visibility;
 
 use super::handlers::{
-    container_detail_handler, containers_handler, dashboard_handler, disks_handler, health_handler,
-    history_handler, host_handler, network_handler, processes_handler, prometheus_handler,
-    services_handler, AppState,
+    alert_ack_handler, alert_stats_handler, alert_test_handler, alerts_handler,
+    annotation_create_handler, annotations_handler, api_auth_handler, backup_handler,
+    backups_handler, burn_in_end_handler, burn_in_handler, burn_in_start_handler,
+    config_schema_handler, container_detail_handler, container_recommendations_handler,
+    container_security_handler, container_volumes_handler, containers_handler,
+    custom_history_handler, custom_metrics_handler, dashboard_handler, disk_suggestions_handler,
+    disks_handler, events_handler, firewall_counters_handler, firewall_handler, health_handler,
+    heartbeat_ping_handler, heartbeats_handler, history_handler, host_handler,
+    host_shutdown_handler, host_wake_handler, ingest_handler, maintenance_end_handler,
+    maintenance_handler, maintenance_start_handler, nagios_handler, network_handler, ping_handler,
+    ping_history_handler, process_renice_handler, processes_handler, prometheus_handler,
+    ready_handler, remote_host_handler, remote_hosts_handler, self_handler, services_handler,
+    speedtest_handler, spike_handler, ssh_handler, stack_history_handler, stream_handler,
+    top_handler, topology_handler, vms_handler, AppState, API_VERSION,
 };
 
-pub fn create_router(monitoring_service: Arc<MonitoringService>) -> Router {
-    let state = AppState { monitoring_service };
-
-    Router::new()
-        // API routes
-        .route("/api/health", get(health_handler))
-        .route("/api/host", get(host_handler))
-        .route("/api/containers", get(containers_handler))
-        .route("/api/containers/{name}", get(container_detail_handler))
-        .route("/api/processes", get(processes_handler))
-        .route("/api/disks", get(disks_handler))
-        .route("/api/network", get(network_handler))
-        .route("/api/dashboard", get(dashboard_handler))
-        .route("/api/history", get(history_handler))
-        .route("/api/services", get(services_handler))
+pub fn create_router(state: AppState) -> Router {
+    let app = Router::new()
+        // Versioned API
+        .nest("/api/v1", api_routes())
+        // Unversioned paths kept as a deprecated alias of the current version
+        .nest(
+            "/api",
+            api_routes().layer(middleware::from_fn(deprecated_alias)),
+        )
         // Prometheus metrics
         .route("/metrics", get(prometheus_handler))
+        // Dashboard login (NANOMON_OIDC_ISSUER)
+        .route("/auth/login", get(login_handler))
+        .route("/auth/callback", get(callback_handler))
+        .route("/auth/logout", get(logout_handler))
         // Serve static files
         .nest_service("/static", ServeDir::new("src/interface/web/static"))
         .fallback_service(ServeDir::new("src/interface/web/static"))
-        .layer(CorsLayer::permissive())
-        .with_state(state)
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            cache_responses,
+        ))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            enforce_visibility,
+        ))
+        .layer(middleware::from_fn_with_state(
+            state.http_metrics.clone(),
+            track_request,
+        ));
+
+    // The dashboard uses relative URLs, which only resolve under `<base>/`
+    let app = if state.base_path.is_empty() {
+        app
+    } else {
+        let index = format!("{}/", state.base_path);
+        Router::new()
+            // `nest` does not match the trailing slash itself
+            .route_service(
+                &index,
+                ServeFile::new("src/interface/web/static/index.html"),
+            )
+            .route(
+                &state.base_path,
+                get(move || async move { Redirect::permanent(&index) }),
+            )
+            .nest(&state.base_path, app)
+    };
+
+    // Also covers the static files and the index served outside the nest
+    let app = app
+        .layer(middleware::from_fn_with_state(state.clone(), require_login))
+        // Outside the login check, so the 401s it answers count too
+        .layer(middleware::from_fn_with_state(state.clone(), guard_auth));
+
+    // Applied after nesting so excluded paths are matched against the full URI
+    let app = match state.access_log.clone() {
+        Some(log) => app.layer(middleware::from_fn_with_state(log, log_request)),
+        None => app,
+    };
+
+    app.layer(CorsLayer::permissive()).with_state(state)
+}
+
+/// JSON API routes, relative to the version prefix
+fn api_routes() -> Router<AppState> {
+    Router::new()
+        .route("/health", get(health_handler))
+        .route("/health/ready", get(ready_handler))
+        .route("/self", get(self_handler))
+        .route("/host", get(host_handler))
+        .route("/containers", get(containers_handler))
+        .route("/containers/security", get(container_security_handler))
+        .route("/containers/{name}", get(container_detail_handler))
+        .route("/containers/{name}/volumes", get(container_volumes_handler))
+        .route(
+            "/containers/{name}/recommendations",
+            get(container_recommendations_handler),
+        )
+        .route("/stacks/{name}/history", get(stack_history_handler))
+        .route("/processes", get(processes_handler))
+        .route("/processes/{pid}/nice", post(process_renice_handler))
+        .route("/vms", get(vms_handler))
+        .route("/disks", get(disks_handler))
+        .route("/suggest/disk", get(disk_suggestions_handler))
+        .route("/network", get(network_handler))
+        .route("/dashboard", get(dashboard_handler))
+        .route("/history", get(history_handler))
+        .route("/nagios/{check}", get(nagios_handler))
+        // Grafana JSON datasource
+        .route("/grafana", get(grafana_root_handler))
+        .route("/grafana/search", post(grafana_search_handler))
+        .route("/grafana/query", post(grafana_query_handler))
+        .route("/grafana/annotations", post(grafana_annotations_handler))
+        .route(
+            "/annotations",
+            get(annotations_handler).post(annotation_create_handler),
+        )
+        .route("/top", get(top_handler))
+        .route("/analyze/spike", get(spike_handler))
+        .route("/stream", get(stream_handler))
+        .route("/services", get(services_handler))
+        .route("/events", get(events_handler))
+        .route("/alerts", get(alerts_handler))
+        .route("/alerts/{id}/ack", post(alert_ack_handler))
+        .route("/alerts/stats", get(alert_stats_handler))
+        .route("/alerts/test", post(alert_test_handler))
+        .route(
+            "/burnin",
+            get(burn_in_handler)
+                .post(burn_in_start_handler)
+                .delete(burn_in_end_handler),
+        )
+        .route(
+            "/maintenance",
+            get(maintenance_handler)
+                .post(maintenance_start_handler)
+                .delete(maintenance_end_handler),
+        )
+        .route("/admin/backup", get(backup_handler))
+        .route("/heartbeats", get(heartbeats_handler))
+        .route("/heartbeats/{name}", post(heartbeat_ping_handler))
+        .route("/checks/backups", get(backups_handler))
+        .route("/checks/ping", get(ping_handler))
+        .route("/checks/ping/{target}/history", get(ping_history_handler))
+        .route("/custom", get(custom_metrics_handler))
+        .route("/custom/{name}/history", get(custom_history_handler))
+        .route("/checks/speedtest", get(speedtest_handler))
+        .route("/security/ssh", get(ssh_handler))
+        .route("/config/schema", get(config_schema_handler))
+        .route("/security/auth", get(api_auth_handler))
+        .route("/security/firewall", get(firewall_handler))
+        .route(
+            "/security/firewall/counters",
+            get(firewall_counters_handler),
+        )
+        .route("/ingest", post(ingest_handler))
+        .route("/topology", get(topology_handler))
+        .route("/hosts", get(remote_hosts_handler))
+        .route("/hosts/{name}", get(remote_host_handler))
+        .route("/hosts/{name}/wake", post(host_wake_handler))
+        .route("/hosts/{name}/shutdown", post(host_shutdown_handler))
+}
+
+/// Marks responses of a deprecated path (RFC 8594 style headers) and points
+/// clients at the same path under the current API version
+async fn deprecated_alias(request: Request, next: Next) -> Response {
+    // Inside the nested router the URI no longer carries the `/api` prefix,
+    // the original one still has it along with any base path
+    let path = request.uri().path();
+    let original = request
+        .extensions()
+        .get::<OriginalUri>()
+        .map_or(path, |uri| uri.path());
+    let prefix = original.strip_suffix(path).unwrap_or("/api");
+    let successor = format!(
+        "<{}/{}{}>; rel=\"successor-version\"",
+        prefix, API_VERSION, path
+    );
+    let mut response = next.run(request).await;
+
+    let headers = response.headers_mut();
+    headers.insert("deprecation", HeaderValue::from_static("true"));
+    if let Ok(link) = HeaderValue::from_str(&successor) {
+        headers.insert(header::LINK, link);
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use tower::ServiceExt;
+
+    #[test]
+    fn test_versioned_and_legacy_routes_coexist() {
+        // Router::nest panics on conflicting paths
+        let _: Router<AppState> = Router::new()
+            .nest("/api/v1", api_routes())
+            .nest("/api", api_routes());
+    }
+
+    #[tokio::test]
+    async fn test_deprecated_alias_headers() {
+        let app: Router = Router::new().nest(
+            "/api",
+            Router::new()
+                .route("/host", get(|| async { "ok" }))
+                .layer(middleware::from_fn(deprecated_alias)),
+        );
+        let response = app
+            .oneshot(Request::get("/api/host").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(response.headers()["deprecation"], "true");
+        assert_eq!(
+            response.headers()[header::LINK],
+            "</api/v1/host>; rel=\"successor-version\""
+        );
+    }
+
+    #[tokio::test]
+    async fn test_deprecated_alias_under_base_path() {
+        let app: Router = Router::new().nest(
+            "/nanomon",
+            Router::new().nest(
+                "/api",
+                Router::new()
+                    .route("/host", get(|| async { "ok" }))
+                    .layer(middleware::from_fn(deprecated_alias)),
+            ),
+        );
+        let response = app
+            .oneshot(
+                Request::get("/nanomon/api/host")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            response.headers()[header::LINK],
+            "</nanomon/api/v1/host>; rel=\"successor-version\""
+        );
+    }
 }
@@ -0,0 +1,117 @@
+use std::io::{self, Write};
+
+use axum::{
+    body::{Body, Bytes},
+    http::header,
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+use tokio::sync::mpsc;
+
+use super::precision::RoundingFormatter;
+
+/// Bytes serialized before a chunk is handed to the connection
+const CHUNK_SIZE: usize = 16 * 1024;
+
+/// Chunks waiting to be sent; serialization pauses while the client catches up
+const CHUNKS_IN_FLIGHT: usize = 4;
+
+/// JSON body serialized on a blocking thread and sent in chunks as it is
+/// written, so a response listing thousands of processes never sits in memory
+/// whole, as bytes or as a `serde_json::Value`. Floats are rounded to
+/// `precision` decimals on the way out.
+pub fn streamed_json<T>(body: T, precision: Option<u32>) -> Response
+where
+    T: Serialize + Send + 'static,
+{
+    let body = streamed_body(move |writer| {
+        let formatter = RoundingFormatter {
+            decimals: precision,
+        };
+        let mut serializer = serde_json::Serializer::with_formatter(writer, formatter);
+        body.serialize(&mut serializer).map_err(io::Error::other)
+    });
+    ([(header::CONTENT_TYPE, "application/json")], body).into_response()
+}
+
+/// Body produced by `write` on a blocking thread and sent in chunks as it is
+/// written. An error ends the body early, so the client sees a failed
+/// transfer rather than a truncated success.
+pub fn streamed_body<F>(write: F) -> Body
+where
+    F: FnOnce(&mut dyn Write) -> io::Result<()> + Send + 'static,
+{
+    let (tx, mut rx) = mpsc::channel::<io::Result<Bytes>>(CHUNKS_IN_FLIGHT);
+    tokio::task::spawn_blocking(move || {
+        let mut writer = ChunkWriter {
+            buf: Vec::with_capacity(CHUNK_SIZE),
+            tx,
+        };
+        // Also fails when the client went away and the channel closed
+        if let Err(e) = write(&mut writer).and_then(|()| writer.flush()) {
+            tracing::debug!("Streamed response aborted: {}", e);
+            let _ = writer.tx.blocking_send(Err(e));
+        }
+    });
+
+    Body::from_stream(futures::stream::poll_fn(move |cx| rx.poll_recv(cx)))
+}
+
+/// Collects serialized bytes and sends them once a chunk is full
+struct ChunkWriter {
+    buf: Vec<u8>,
+    tx: mpsc::Sender<io::Result<Bytes>>,
+}
+
+impl Write for ChunkWriter {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.buf.extend_from_slice(data);
+        if self.buf.len() >= CHUNK_SIZE {
+            self.flush()?;
+        }
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.buf.is_empty() {
+            return Ok(());
+        }
+        let chunk = std::mem::replace(&mut self.buf, Vec::with_capacity(CHUNK_SIZE));
+        self.tx
+            .blocking_send(Ok(Bytes::from(chunk)))
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "client disconnected"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::to_bytes;
+
+    #[tokio::test]
+    async fn test_streamed_body_matches_buffered() {
+        let body = serde_json::json!({
+            "processes": (0..5000)
+                .map(|pid| serde_json::json!({ "pid": pid, "cpu_percent": pid as f64 / 3.0 }))
+                .collect::<Vec<_>>(),
+        });
+
+        let response = streamed_json(body.clone(), None);
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert!(bytes.len() > CHUNK_SIZE);
+        assert_eq!(bytes, serde_json::to_vec(&body).unwrap());
+
+        let response = streamed_json(serde_json::json!({ "load": 0.456, "count": 3 }), Some(1));
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(&bytes[..], br#"{"count":3,"load":0.5}"#);
+    }
+
+    #[tokio::test]
+    async fn test_streamed_body_error_fails_the_transfer() {
+        let body = streamed_body(|writer| {
+            writer.write_all(&[b'x'; CHUNK_SIZE * 2])?;
+            Err(io::Error::other("disk went away"))
+        });
+        assert!(to_bytes(body, usize::MAX).await.is_err());
+    }
+}
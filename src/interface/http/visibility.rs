@@ -0,0 +1,127 @@
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use super::handlers::AppState;
+use super::login::LoggedIn;
+
+/// Routes a scoped viewer may read, filtered to its containers by the handlers;
+/// everything else describes the whole host
+const SCOPED_ROUTES: &[&str] = &[
+    "/containers",
+    "/containers/{name}",
+    "/containers/{name}/volumes",
+    "/containers/{name}/recommendations",
+    "/stacks/{name}/history",
+    "/processes",
+];
+
+/// Probes and endpoints checking a token of their own
+const OPEN_ROUTES: &[&str] = &["/health", "/health/ready", "/ingest", "/heartbeats/{name}"];
+
+/// `route` relative to the API version prefix, `None` outside the API
+pub(super) fn api_route<'a>(base_path: &str, route: &'a str) -> Option<&'a str> {
+    let route = route.strip_prefix(base_path).unwrap_or(route);
+    route
+        .strip_prefix("/api/v1")
+        .or_else(|| route.strip_prefix("/api"))
+}
+
+/// Whether an API path (not route) is one of `OPEN_ROUTES`
+pub(super) fn is_open_path(path: &str) -> bool {
+    OPEN_ROUTES.iter().any(|route| {
+        let (mut route, mut path) = (route.split('/'), path.split('/'));
+        loop {
+            match (route.next(), path.next()) {
+                (None, None) => return true,
+                (Some(r), Some(p)) if r == p || (r.starts_with('{') && !p.is_empty()) => {}
+                _ => return false,
+            }
+        }
+    })
+}
+
+/// Bearer token, or a `token` query parameter for the dashboard's first load
+pub(super) fn request_token(request: &Request) -> Option<&str> {
+    request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .or_else(|| {
+            request
+                .uri()
+                .query()?
+                .split('&')
+                .find_map(|pair| pair.strip_prefix("token="))
+        })
+}
+
+/// Once `[[viewers]]` are configured, API reads and `/metrics` need a viewer
+/// (or the admin) token, or a dashboard login. A scoped viewer only reaches
+/// `SCOPED_ROUTES`, with its `Viewer` attached for the handlers to filter by.
+pub async fn enforce_visibility(
+    State(state): State<AppState>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    if state.viewers.is_empty() || request.extensions().get::<LoggedIn>().is_some() {
+        return next.run(request).await;
+    }
+    let Some(route) = request.extensions().get::<MatchedPath>().cloned() else {
+        return next.run(request).await;
+    };
+    let route = route.as_str();
+    let api = api_route(&state.base_path, route);
+    let metrics = route.strip_prefix(state.base_path.as_str()) == Some("/metrics");
+    if !(api.is_some() || metrics) || api.is_some_and(|r| OPEN_ROUTES.contains(&r)) {
+        return next.run(request).await;
+    }
+
+    let token = request_token(&request);
+    if token.is_some() && token == state.admin_token.as_deref() {
+        return next.run(request).await;
+    }
+    let Some(viewer) = state
+        .viewers
+        .iter()
+        .find(|v| Some(v.token.as_str()) == token)
+    else {
+        return (StatusCode::UNAUTHORIZED, "Invalid or missing viewer token").into_response();
+    };
+    if !viewer.is_scoped() {
+        return next.run(request).await;
+    }
+    if !api.is_some_and(|r| SCOPED_ROUTES.contains(&r)) {
+        return (StatusCode::FORBIDDEN, "Not visible to this viewer").into_response();
+    }
+    request.extensions_mut().insert(viewer.clone());
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_api_route_strips_prefixes() {
+        assert_eq!(api_route("", "/api/v1/containers"), Some("/containers"));
+        assert_eq!(
+            api_route("/nanomon", "/nanomon/api/processes"),
+            Some("/processes")
+        );
+        assert_eq!(api_route("", "/metrics"), None);
+        assert!(is_open_path("/heartbeats/nightly-backup"));
+        assert!(!is_open_path("/heartbeats"));
+        assert!(!is_open_path("/heartbeats/"));
+
+        let request = Request::builder()
+            .uri("/api/v1/containers?duration=60&token=abc")
+            .body(axum::body::Body::empty())
+            .unwrap();
+        assert_eq!(request_token(&request), Some("abc"));
+    }
+}
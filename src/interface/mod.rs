@@ -1 +1,9 @@
+pub mod backup;
+pub mod config_check;
+pub mod doctor;
 pub mod http;
+pub mod nagios;
+#[cfg(feature = "snmp")]
+pub mod snmp;
+#[cfg(feature = "otel")]
+pub mod telemetry;
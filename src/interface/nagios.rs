@@ -0,0 +1,77 @@
+use crate::adapters::{DockerAdapter, ProcfsAdapter, ProcfsConfig};
+use crate::config::Config;
+use crate::domain::{CheckResult, Host, NagiosCheck};
+use crate::ports::{ContainerSource, SystemSource};
+
+const USAGE: &str =
+    "usage: nanomon check <disk|memory|containers> [--warn PERCENT] [--crit PERCENT]";
+
+/// `nanomon check <name>`: collect once, print Nagios plugin output for NRPE or
+/// Icinga and return the plugin exit code
+pub async fn run(config: &Config, args: &[String]) -> i32 {
+    let result = match parse_args(args) {
+        Ok((check, warn, crit)) => match collect(config, check).await {
+            Ok(host) => check.evaluate(&host, warn, crit),
+            Err(e) => CheckResult::unknown(check.service(), e.to_string()),
+        },
+        Err(e) => CheckResult::unknown("NANOMON", e),
+    };
+    println!("{}", result.render());
+    result.state.exit_code()
+}
+
+fn parse_args(args: &[String]) -> Result<(NagiosCheck, Option<f64>, Option<f64>), String> {
+    let mut args = args.iter();
+    let check = args.next().ok_or(USAGE)?.parse::<NagiosCheck>()?;
+    let (mut warn, mut crit) = (None, None);
+    while let Some(flag) = args.next() {
+        let target = match flag.as_str() {
+            "--warn" | "-w" => &mut warn,
+            "--crit" | "-c" => &mut crit,
+            _ => return Err(USAGE.to_string()),
+        };
+        let value = args.next().and_then(|v| v.parse::<f64>().ok());
+        *target = Some(value.ok_or_else(|| format!("{} expects a percentage", flag))?);
+    }
+    Ok((check, warn, crit))
+}
+
+/// Only what `check` looks at
+async fn collect(
+    config: &Config,
+    check: NagiosCheck,
+) -> Result<Host, Box<dyn std::error::Error + Send + Sync>> {
+    let procfs = ProcfsAdapter::new(
+        ProcfsConfig::new(config.proc_path.clone(), config.sys_path.clone())
+            .with_etc_path(config.etc_path.clone()),
+    );
+    let system = procfs.system_source();
+    let mut host = Host::new(system.get_host_info().await?.hostname);
+    match check {
+        NagiosCheck::Disk => host.disks = system.list_disks().await?,
+        NagiosCheck::Memory => host.memory = system.get_memory_metrics().await?,
+        NagiosCheck::Containers => {
+            host.containers = DockerAdapter::new()?.list_containers().await?;
+        }
+    }
+    Ok(host)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_args() {
+        let args: Vec<String> = ["disk", "--warn", "70", "-c", "85"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(
+            parse_args(&args).unwrap(),
+            (NagiosCheck::Disk, Some(70.0), Some(85.0))
+        );
+        assert!(parse_args(&args[..2]).is_err());
+        assert!(parse_args(&[]).is_err());
+    }
+}
@@ -0,0 +1,301 @@
+//! The subset of BER (X.690) that SNMPv1/v2c messages use
+
+pub type Oid = Vec<u32>;
+
+pub const GET_REQUEST: u8 = 0xA0;
+pub const GET_NEXT_REQUEST: u8 = 0xA1;
+pub const RESPONSE: u8 = 0xA2;
+pub const SET_REQUEST: u8 = 0xA3;
+pub const GET_BULK_REQUEST: u8 = 0xA5;
+
+const INTEGER: u8 = 0x02;
+const OCTET_STRING: u8 = 0x04;
+const NULL: u8 = 0x05;
+const OBJECT_ID: u8 = 0x06;
+const SEQUENCE: u8 = 0x30;
+const COUNTER32: u8 = 0x41;
+const GAUGE32: u8 = 0x42;
+const TIME_TICKS: u8 = 0x43;
+const COUNTER64: u8 = 0x46;
+const NO_SUCH_OBJECT: u8 = 0x80;
+const NO_SUCH_INSTANCE: u8 = 0x81;
+const END_OF_MIB_VIEW: u8 = 0x82;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Integer(i64),
+    OctetString(Vec<u8>),
+    Null,
+    ObjectId(Oid),
+    Counter32(u32),
+    Gauge32(u32),
+    TimeTicks(u32),
+    Counter64(u64),
+    NoSuchObject,
+    NoSuchInstance,
+    EndOfMibView,
+}
+
+/// An SNMP message; for GetBulk, `error_status` and `error_index` carry
+/// non-repeaters and max-repetitions
+#[derive(Debug, Clone, PartialEq)]
+pub struct Message {
+    pub version: i64,
+    pub community: Vec<u8>,
+    pub pdu_type: u8,
+    pub request_id: i64,
+    pub error_status: i64,
+    pub error_index: i64,
+    pub varbinds: Vec<(Oid, Value)>,
+}
+
+/// `1.3.6.1.4.1.8072` or `.1.3.6...`
+pub fn parse_oid(s: &str) -> Option<Oid> {
+    let oid: Option<Oid> = s
+        .trim_start_matches('.')
+        .split('.')
+        .map(|arc| arc.parse().ok())
+        .collect();
+    oid.filter(|o| o.len() >= 2 && o[0] <= 2)
+}
+
+impl Message {
+    pub fn decode(bytes: &[u8]) -> Result<Self, String> {
+        let mut outer = Reader::new(bytes);
+        let mut message = Reader::new(outer.expect(SEQUENCE)?);
+        let version = decode_integer(message.expect(INTEGER)?)?;
+        let community = message.expect(OCTET_STRING)?.to_vec();
+        let (pdu_type, pdu) = message.read()?;
+
+        let mut pdu = Reader::new(pdu);
+        let request_id = decode_integer(pdu.expect(INTEGER)?)?;
+        let error_status = decode_integer(pdu.expect(INTEGER)?)?;
+        let error_index = decode_integer(pdu.expect(INTEGER)?)?;
+        let mut list = Reader::new(pdu.expect(SEQUENCE)?);
+        let mut varbinds = Vec::new();
+        while !list.is_empty() {
+            let mut varbind = Reader::new(list.expect(SEQUENCE)?);
+            let oid = decode_oid(varbind.expect(OBJECT_ID)?)?;
+            let (tag, content) = varbind.read()?;
+            varbinds.push((oid, decode_value(tag, content)?));
+        }
+
+        Ok(Self {
+            version,
+            community,
+            pdu_type,
+            request_id,
+            error_status,
+            error_index,
+            varbinds,
+        })
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut list = Vec::new();
+        for (oid, value) in &self.varbinds {
+            let mut varbind = tlv(OBJECT_ID, &encode_oid(oid));
+            varbind.extend(encode_value(value));
+            list.extend(tlv(SEQUENCE, &varbind));
+        }
+
+        let mut pdu = tlv(INTEGER, &encode_integer(self.request_id));
+        pdu.extend(tlv(INTEGER, &encode_integer(self.error_status)));
+        pdu.extend(tlv(INTEGER, &encode_integer(self.error_index)));
+        pdu.extend(tlv(SEQUENCE, &list));
+
+        let mut message = tlv(INTEGER, &encode_integer(self.version));
+        message.extend(tlv(OCTET_STRING, &self.community));
+        message.extend(tlv(self.pdu_type, &pdu));
+        tlv(SEQUENCE, &message)
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Next tag and its content
+    fn read(&mut self) -> Result<(u8, &'a [u8]), String> {
+        let (&tag, rest) = self.data.split_first().ok_or("truncated message")?;
+        let (&first, rest) = rest.split_first().ok_or("truncated length")?;
+        let (len, rest) = if first < 0x80 {
+            (first as usize, rest)
+        } else {
+            let count = (first & 0x7f) as usize;
+            if count == 0 || count > 4 || rest.len() < count {
+                return Err("unsupported length".to_string());
+            }
+            let len = rest[..count]
+                .iter()
+                .fold(0usize, |acc, &b| (acc << 8) | b as usize);
+            (len, &rest[count..])
+        };
+        if rest.len() < len {
+            return Err("truncated content".to_string());
+        }
+        self.data = &rest[len..];
+        Ok((tag, &rest[..len]))
+    }
+
+    fn expect(&mut self, expected: u8) -> Result<&'a [u8], String> {
+        match self.read()? {
+            (tag, content) if tag == expected => Ok(content),
+            (tag, _) => Err(format!(
+                "expected tag {:#04x}, found {:#04x}",
+                expected, tag
+            )),
+        }
+    }
+}
+
+fn tlv(tag: u8, content: &[u8]) -> Vec<u8> {
+    let mut out = vec![tag];
+    let len = content.len();
+    if len < 0x80 {
+        out.push(len as u8);
+    } else {
+        let bytes: Vec<u8> = len
+            .to_be_bytes()
+            .into_iter()
+            .skip_while(|&b| b == 0)
+            .collect();
+        out.push(0x80 | bytes.len() as u8);
+        out.extend(bytes);
+    }
+    out.extend_from_slice(content);
+    out
+}
+
+/// Two's complement, shortest form
+fn encode_integer(value: i64) -> Vec<u8> {
+    let bytes = value.to_be_bytes();
+    let mut start = 0;
+    while start < 7 {
+        let redundant = (bytes[start] == 0x00 && bytes[start + 1] & 0x80 == 0)
+            || (bytes[start] == 0xff && bytes[start + 1] & 0x80 != 0);
+        if !redundant {
+            break;
+        }
+        start += 1;
+    }
+    bytes[start..].to_vec()
+}
+
+/// Shortest form with a leading zero when the high bit is set
+fn encode_unsigned(value: u64) -> Vec<u8> {
+    let mut bytes: Vec<u8> = value
+        .to_be_bytes()
+        .into_iter()
+        .skip_while(|&b| b == 0)
+        .collect();
+    if bytes.first().is_none_or(|&b| b & 0x80 != 0) {
+        bytes.insert(0, 0);
+    }
+    bytes
+}
+
+fn decode_integer(content: &[u8]) -> Result<i64, String> {
+    if content.is_empty() || content.len() > 8 {
+        return Err("invalid integer".to_string());
+    }
+    let negative = content[0] & 0x80 != 0;
+    Ok(content
+        .iter()
+        .fold(if negative { -1i64 } else { 0 }, |acc, &b| {
+            (acc << 8) | b as i64
+        }))
+}
+
+fn decode_unsigned(content: &[u8]) -> Result<u64, String> {
+    if content.is_empty() || content.len() > 9 {
+        return Err("invalid unsigned integer".to_string());
+    }
+    Ok(content.iter().fold(0u64, |acc, &b| (acc << 8) | b as u64))
+}
+
+fn encode_oid(oid: &[u32]) -> Vec<u8> {
+    let mut out = Vec::new();
+    let (first, rest) = match oid {
+        [a, b, rest @ ..] => (a * 40 + b, rest),
+        [a] => (a * 40, &[][..]),
+        [] => (0, &[][..]),
+    };
+    for arc in std::iter::once(first).chain(rest.iter().copied()) {
+        let mut chunk = vec![(arc & 0x7f) as u8];
+        let mut arc = arc >> 7;
+        while arc > 0 {
+            chunk.push((arc & 0x7f) as u8 | 0x80);
+            arc >>= 7;
+        }
+        out.extend(chunk.iter().rev());
+    }
+    out
+}
+
+fn decode_oid(content: &[u8]) -> Result<Oid, String> {
+    let mut arcs = Vec::new();
+    let mut arc: u32 = 0;
+    for &b in content {
+        arc = arc
+            .checked_mul(128)
+            .ok_or("object identifier arc overflow")?
+            | (b & 0x7f) as u32;
+        if b & 0x80 == 0 {
+            arcs.push(arc);
+            arc = 0;
+        }
+    }
+    let first = *arcs.first().ok_or("empty object identifier")?;
+    let (a, b) = if first < 80 {
+        (first / 40, first % 40)
+    } else {
+        (2, first - 80)
+    };
+    Ok([a, b]
+        .into_iter()
+        .chain(arcs[1..].iter().copied())
+        .collect())
+}
+
+fn encode_value(value: &Value) -> Vec<u8> {
+    match value {
+        Value::Integer(v) => tlv(INTEGER, &encode_integer(*v)),
+        Value::OctetString(v) => tlv(OCTET_STRING, v),
+        Value::Null => tlv(NULL, &[]),
+        Value::ObjectId(v) => tlv(OBJECT_ID, &encode_oid(v)),
+        Value::Counter32(v) => tlv(COUNTER32, &encode_unsigned(*v as u64)),
+        Value::Gauge32(v) => tlv(GAUGE32, &encode_unsigned(*v as u64)),
+        Value::TimeTicks(v) => tlv(TIME_TICKS, &encode_unsigned(*v as u64)),
+        Value::Counter64(v) => tlv(COUNTER64, &encode_unsigned(*v)),
+        Value::NoSuchObject => tlv(NO_SUCH_OBJECT, &[]),
+        Value::NoSuchInstance => tlv(NO_SUCH_INSTANCE, &[]),
+        Value::EndOfMibView => tlv(END_OF_MIB_VIEW, &[]),
+    }
+}
+
+fn decode_value(tag: u8, content: &[u8]) -> Result<Value, String> {
+    Ok(match tag {
+        INTEGER => Value::Integer(decode_integer(content)?),
+        OCTET_STRING => Value::OctetString(content.to_vec()),
+        NULL => Value::Null,
+        OBJECT_ID => Value::ObjectId(decode_oid(content)?),
+        COUNTER32 => Value::Counter32(decode_unsigned(content)? as u32),
+        GAUGE32 => Value::Gauge32(decode_unsigned(content)? as u32),
+        TIME_TICKS => Value::TimeTicks(decode_unsigned(content)? as u32),
+        COUNTER64 => Value::Counter64(decode_unsigned(content)?),
+        NO_SUCH_OBJECT => Value::NoSuchObject,
+        NO_SUCH_INSTANCE => Value::NoSuchInstance,
+        END_OF_MIB_VIEW => Value::EndOfMibView,
+        other => return Err(format!("unsupported value tag {:#04x}", other)),
+    })
+}
@@ -0,0 +1,95 @@
+//! Objects served under the base OID (`B`), from the latest snapshot:
+//!
+//! - `B.1` host scalars: `.1.0` hostname, `.2.0` uptime (TimeTicks), `.3.0` CPU %
+//!   x100, `.4.0`/`.5.0`/`.6.0` memory total/used/available KiB, `.7.0`/`.8.0`/`.9.0`
+//!   load average x100
+//! - `B.2.1.<column>.<n>` disks: `.1` index, `.2` mount point, `.3` device,
+//!   `.4` total MiB, `.5` used MiB, `.6` used % x100
+//! - `B.3.1.<column>.<n>` containers: `.1` index, `.2` name, `.3` state (1 running,
+//!   2 stopped, 3 paused, 4 restarting, 5 dead, 6 created), `.4` CPU % x100,
+//!   `.5` memory used KiB
+//! - `B.4.1.<column>.<n>` interfaces: `.1` index, `.2` name, `.3` status (1 up,
+//!   2 down), `.4` received bytes, `.5` sent bytes (Counter64)
+//!
+//! Table rows are numbered from 1 in the snapshot's order.
+
+use std::collections::BTreeMap;
+
+use crate::domain::{ContainerState, Host};
+
+use super::ber::{Oid, Value};
+
+pub type Mib = BTreeMap<Oid, Value>;
+
+fn gauge(value: f64) -> Value {
+    Value::Gauge32(value.clamp(0.0, u32::MAX as f64) as u32)
+}
+
+fn scaled(bytes: u64, unit: u64) -> Value {
+    Value::Gauge32((bytes / unit).min(u32::MAX as u64) as u32)
+}
+
+fn string(s: &str) -> Value {
+    Value::OctetString(s.as_bytes().to_vec())
+}
+
+pub fn build(base: &[u32], host: &Host) -> Mib {
+    let mut mib = Mib::new();
+    let mut put = |suffix: &[u32], value: Value| {
+        mib.insert(base.iter().chain(suffix).copied().collect(), value);
+    };
+
+    put(&[1, 1, 0], string(&host.hostname));
+    put(
+        &[1, 2, 0],
+        Value::TimeTicks(host.uptime_seconds.saturating_mul(100).min(u32::MAX as u64) as u32),
+    );
+    put(&[1, 3, 0], gauge(host.cpu.usage_percent * 100.0));
+    put(&[1, 4, 0], scaled(host.memory.total_bytes, 1024));
+    put(&[1, 5, 0], scaled(host.memory.used_bytes, 1024));
+    put(&[1, 6, 0], scaled(host.memory.available_bytes, 1024));
+    put(&[1, 7, 0], gauge(host.load_average.one * 100.0));
+    put(&[1, 8, 0], gauge(host.load_average.five * 100.0));
+    put(&[1, 9, 0], gauge(host.load_average.fifteen * 100.0));
+
+    for (i, disk) in host.disks.iter().enumerate() {
+        let n = i as u32 + 1;
+        put(&[2, 1, 1, n], Value::Integer(n as i64));
+        put(&[2, 1, 2, n], string(&disk.mount_point));
+        put(&[2, 1, 3, n], string(&disk.device));
+        put(&[2, 1, 4, n], scaled(disk.total_bytes, 1024 * 1024));
+        put(&[2, 1, 5, n], scaled(disk.used_bytes, 1024 * 1024));
+        put(&[2, 1, 6, n], gauge(disk.usage_percent() * 100.0));
+    }
+
+    for (i, container) in host.containers.iter().enumerate() {
+        let n = i as u32 + 1;
+        let state = match container.state {
+            ContainerState::Running => 1,
+            ContainerState::Stopped => 2,
+            ContainerState::Paused => 3,
+            ContainerState::Restarting => 4,
+            ContainerState::Dead => 5,
+            ContainerState::Created => 6,
+        };
+        put(&[3, 1, 1, n], Value::Integer(n as i64));
+        put(&[3, 1, 2, n], string(&container.name));
+        put(&[3, 1, 3, n], Value::Integer(state));
+        put(&[3, 1, 4, n], gauge(container.cpu.usage_percent * 100.0));
+        put(&[3, 1, 5, n], scaled(container.memory.used_bytes, 1024));
+    }
+
+    for (i, interface) in host.network_interfaces.iter().enumerate() {
+        let n = i as u32 + 1;
+        put(&[4, 1, 1, n], Value::Integer(n as i64));
+        put(&[4, 1, 2, n], string(&interface.name));
+        put(
+            &[4, 1, 3, n],
+            Value::Integer(if interface.is_up { 1 } else { 2 }),
+        );
+        put(&[4, 1, 4, n], Value::Counter64(interface.metrics.rx_bytes));
+        put(&[4, 1, 5, n], Value::Counter64(interface.metrics.tx_bytes));
+    }
+
+    mib
+}
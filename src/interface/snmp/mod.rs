@@ -0,0 +1,228 @@
+//! Read-only SNMPv1/v2c agent serving the latest snapshot, for pollers such as
+//! LibreNMS. Requests with another community are dropped without an answer.
+
+mod ber;
+mod mib;
+
+use std::ops::Bound;
+use std::sync::Arc;
+
+use tokio::net::UdpSocket;
+use tracing::{debug, info};
+
+use crate::application::MonitoringService;
+
+pub use ber::{parse_oid, Oid};
+use ber::{Message, Value};
+use mib::Mib;
+
+/// Bounds GetBulk responses to what fits a UDP datagram
+const MAX_BULK_VARBINDS: usize = 200;
+
+const SNMP_V1: i64 = 0;
+const SNMP_V2C: i64 = 1;
+
+// Error statuses
+const NO_SUCH_NAME: i64 = 2;
+const READ_ONLY: i64 = 4;
+const NOT_WRITABLE: i64 = 17;
+
+pub async fn serve(
+    bind: &str,
+    community: String,
+    base: Oid,
+    monitoring: Arc<MonitoringService>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let socket = UdpSocket::bind(bind).await?;
+    info!("SNMP agent listening on udp://{}", socket.local_addr()?);
+
+    let mut buf = vec![0u8; 65535];
+    loop {
+        let (len, peer) = match socket.recv_from(&mut buf).await {
+            Ok(received) => received,
+            Err(e) => {
+                debug!("SNMP receive failed: {}", e);
+                continue;
+            }
+        };
+        let response = respond(&buf[..len], community.as_bytes(), || {
+            monitoring
+                .get_latest_snapshot()
+                .map(|host| mib::build(&base, &host))
+                .unwrap_or_default()
+        });
+        match response {
+            Ok(Some(bytes)) => {
+                if let Err(e) = socket.send_to(&bytes, peer).await {
+                    debug!("SNMP response to {} failed: {}", peer, e);
+                }
+            }
+            Ok(None) => {}
+            Err(e) => debug!("Malformed SNMP request from {}: {}", peer, e),
+        }
+    }
+}
+
+/// The encoded response, `None` for requests that get no answer
+fn respond(
+    request: &[u8],
+    community: &[u8],
+    mib: impl FnOnce() -> Mib,
+) -> Result<Option<Vec<u8>>, String> {
+    let request = Message::decode(request)?;
+    if request.community != community || !matches!(request.version, SNMP_V1 | SNMP_V2C) {
+        return Ok(None);
+    }
+    let v1 = request.version == SNMP_V1;
+
+    let mut response = Message {
+        pdu_type: ber::RESPONSE,
+        error_status: 0,
+        error_index: 0,
+        ..request.clone()
+    };
+    let mib = mib();
+    let next = |oid: &Oid| {
+        mib.range::<Oid, _>((Bound::Excluded(oid), Bound::Unbounded))
+            .next()
+            .map(|(oid, value)| (oid.clone(), value.clone()))
+    };
+
+    match request.pdu_type {
+        ber::GET_REQUEST => {
+            for (i, (oid, _)) in request.varbinds.iter().enumerate() {
+                let value = match mib.get(oid) {
+                    Some(value) => value.clone(),
+                    None if v1 => return Ok(Some(error(request, NO_SUCH_NAME, i))),
+                    // Scalars are `.0` instances of a known object
+                    None if mib.keys().any(|k| k.starts_with(&oid[..oid.len() - 1])) => {
+                        Value::NoSuchInstance
+                    }
+                    None => Value::NoSuchObject,
+                };
+                response.varbinds[i] = (oid.clone(), value);
+            }
+        }
+        ber::GET_NEXT_REQUEST => {
+            for (i, (oid, _)) in request.varbinds.iter().enumerate() {
+                response.varbinds[i] = match next(oid) {
+                    Some(found) => found,
+                    None if v1 => return Ok(Some(error(request, NO_SUCH_NAME, i))),
+                    None => (oid.clone(), Value::EndOfMibView),
+                };
+            }
+        }
+        ber::GET_BULK_REQUEST if !v1 => {
+            let non_repeaters = request.error_status.clamp(0, i64::MAX) as usize;
+            let max_repetitions = request.error_index.clamp(0, i64::MAX) as usize;
+            let (single, repeated) = request
+                .varbinds
+                .split_at(non_repeaters.min(request.varbinds.len()));
+
+            let walk = |oid: &Oid| next(oid).unwrap_or_else(|| (oid.clone(), Value::EndOfMibView));
+            let mut varbinds: Vec<_> = single.iter().map(|(oid, _)| walk(oid)).collect();
+            let mut cursors: Vec<Oid> = repeated.iter().map(|(oid, _)| oid.clone()).collect();
+            for _ in 0..max_repetitions {
+                if varbinds.len() + cursors.len() > MAX_BULK_VARBINDS || cursors.is_empty() {
+                    break;
+                }
+                let mut finished = true;
+                for cursor in &mut cursors {
+                    let (oid, value) = walk(cursor);
+                    finished &= value == Value::EndOfMibView;
+                    *cursor = oid.clone();
+                    varbinds.push((oid, value));
+                }
+                if finished {
+                    break;
+                }
+            }
+            response.varbinds = varbinds;
+        }
+        ber::SET_REQUEST => {
+            let status = if v1 { READ_ONLY } else { NOT_WRITABLE };
+            return Ok(Some(error(request, status, 0)));
+        }
+        _ => return Ok(None),
+    }
+    Ok(Some(response.encode()))
+}
+
+/// The request echoed back with an error status on its `index`th varbind
+fn error(request: Message, status: i64, index: usize) -> Vec<u8> {
+    Message {
+        pdu_type: ber::RESPONSE,
+        error_status: status,
+        error_index: index as i64 + 1,
+        ..request
+    }
+    .encode()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::Host;
+
+    fn request(version: i64, pdu_type: u8, oids: &[Oid]) -> Vec<u8> {
+        Message {
+            version,
+            community: b"public".to_vec(),
+            pdu_type,
+            request_id: 42,
+            error_status: 0,
+            error_index: 0,
+            varbinds: oids.iter().map(|o| (o.clone(), Value::Null)).collect(),
+        }
+        .encode()
+    }
+
+    #[test]
+    fn test_get_and_walk() {
+        let base = parse_oid("1.3.6.1.4.1.8072.9999.9999.1").unwrap();
+        let mut host = Host::new("nas".to_string());
+        host.uptime_seconds = 60;
+        let mib = || mib::build(&base, &host);
+        let oid = |suffix: &[u32]| -> Oid { base.iter().chain(suffix).copied().collect() };
+
+        let bytes = request(
+            SNMP_V2C,
+            ber::GET_REQUEST,
+            &[oid(&[1, 1, 0]), oid(&[1, 1, 7])],
+        );
+        let response = Message::decode(&respond(&bytes, b"public", mib).unwrap().unwrap()).unwrap();
+        assert_eq!(response.pdu_type, ber::RESPONSE);
+        assert_eq!(response.request_id, 42);
+        assert_eq!(response.varbinds[0].1, Value::OctetString(b"nas".to_vec()));
+        assert_eq!(response.varbinds[1].1, Value::NoSuchInstance);
+
+        let bytes = request(SNMP_V2C, ber::GET_NEXT_REQUEST, &[oid(&[1])]);
+        let response = Message::decode(&respond(&bytes, b"public", mib).unwrap().unwrap()).unwrap();
+        assert_eq!(response.varbinds[0].0, oid(&[1, 1, 0]));
+
+        let bytes = request(SNMP_V2C, ber::GET_NEXT_REQUEST, &[oid(&[1, 9, 0])]);
+        let response = Message::decode(&respond(&bytes, b"public", mib).unwrap().unwrap()).unwrap();
+        assert_eq!(response.varbinds[0].1, Value::EndOfMibView);
+
+        let bytes = request(SNMP_V1, ber::GET_REQUEST, &[oid(&[1, 2, 0])]);
+        let response = Message::decode(&respond(&bytes, b"public", mib).unwrap().unwrap()).unwrap();
+        assert_eq!(response.varbinds[0].1, Value::TimeTicks(6000));
+
+        assert_eq!(respond(&bytes, b"secret", mib).unwrap(), None);
+
+        // snmpget -v2c -c public host sysDescr.0, request id 0x1234
+        let captured = [
+            0x30, 0x27, 0x02, 0x01, 0x01, 0x04, 0x06, b'p', b'u', b'b', b'l', b'i', b'c', 0xa0,
+            0x1a, 0x02, 0x02, 0x12, 0x34, 0x02, 0x01, 0x00, 0x02, 0x01, 0x00, 0x30, 0x0e, 0x30,
+            0x0c, 0x06, 0x08, 0x2b, 0x06, 0x01, 0x02, 0x01, 0x01, 0x01, 0x00, 0x05, 0x00,
+        ];
+        let message = Message::decode(&captured).unwrap();
+        assert_eq!(message.request_id, 0x1234);
+        assert_eq!(
+            message.varbinds[0].0,
+            parse_oid("1.3.6.1.2.1.1.1.0").unwrap()
+        );
+        assert_eq!(message.encode(), captured);
+        assert!(respond(&bytes[..10], b"public", mib).is_err());
+    }
+}
@@ -0,0 +1,60 @@
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+use opentelemetry_sdk::trace::{SdkTracerProvider, Tracer};
+use opentelemetry_sdk::Resource;
+use tracing::Subscriber;
+use tracing_opentelemetry::OpenTelemetryLayer;
+use tracing_subscriber::registry::LookupSpan;
+
+/// Path the OTLP/HTTP receiver takes traces on
+const TRACES_PATH: &str = "/v1/traces";
+
+/// Layer sending spans to the OTLP/HTTP collector at `endpoint`, and the
+/// provider to shut down on exit so the last batch is flushed
+pub fn layer<S>(
+    endpoint: &str,
+) -> Result<
+    (OpenTelemetryLayer<S, Tracer>, SdkTracerProvider),
+    Box<dyn std::error::Error + Send + Sync>,
+>
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+{
+    let exporter = SpanExporter::builder()
+        .with_http()
+        .with_endpoint(traces_url(endpoint))
+        .build()?;
+    let provider = SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(Resource::builder().with_service_name("nanomon").build())
+        .build();
+    let tracer = provider.tracer("nanomon");
+    Ok((tracing_opentelemetry::layer().with_tracer(tracer), provider))
+}
+
+/// `http://tempo:4318` and `http://tempo:4318/v1/traces` both send to the latter
+fn traces_url(endpoint: &str) -> String {
+    let endpoint = endpoint.trim_end_matches('/');
+    if endpoint.ends_with(TRACES_PATH) {
+        endpoint.to_string()
+    } else {
+        format!("{}{}", endpoint, TRACES_PATH)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_traces_url() {
+        assert_eq!(
+            traces_url("http://tempo:4318/"),
+            "http://tempo:4318/v1/traces"
+        );
+        assert_eq!(
+            traces_url("http://tempo:4318/v1/traces"),
+            "http://tempo:4318/v1/traces"
+        );
+    }
+}
@@ -7,12 +7,23 @@ let intervalId = null;
 // Cache containers data for detail views
 let cachedContainersData = null;
 
+// A viewer token given once as ?token= is kept for the session and sent with every API call
+const viewerToken = new URLSearchParams(location.search).get('token') || sessionStorage.getItem('nanomonToken');
+if (viewerToken) {
+    sessionStorage.setItem('nanomonToken', viewerToken);
+    const plainFetch = window.fetch.bind(window);
+    window.fetch = (url, options = {}) => plainFetch(url, {
+        ...options,
+        headers: { ...options.headers, Authorization: `Bearer ${viewerToken}` },
+    });
+}
+
 // Initialize
 document.addEventListener('DOMContentLoaded', () => {
     initTabs();
     initControls();
     window.addEventListener('hashchange', onHashChange);
-    checkServicesAvailable();
+    checkCapabilities();
     onHashChange(); // Route from initial URL
     startRefresh();
 });
@@ -114,7 +125,7 @@ async function loadData() {
 // ---- Host ----
 
 async function loadHost() {
-    const response = await fetch('/api/host');
+    const response = await fetch('api/v1/host?detail=network');
     const data = await response.json();
 
     document.getElementById('hostname').textContent = data.hostname;
@@ -138,8 +149,134 @@ async function loadHost() {
 
     document.getElementById('net-rx').textContent = '0 MB/s';
     document.getElementById('net-tx').textContent = '0 MB/s';
+    renderNetworkLinks(data.interfaces || []);
 
     renderTemperatures(data.temperatures || []);
+    renderKernelLimits(data.kernel_limits || {});
+    await loadVms();
+    await loadTopology();
+}
+
+function formatSpeed(mbps) {
+    return mbps >= 1000 ? `${mbps / 1000} Gbps` : `${mbps} Mbps`;
+}
+
+function renderNetworkLinks(interfaces) {
+    const links = interfaces.filter(i => i.is_up && i.name !== 'lo' && (i.speed_mbps || i.addresses || i.wireless));
+    document.getElementById('net-links').innerHTML = links.map(i => {
+        const parts = [i.name];
+        if (i.speed_mbps) parts.push(formatSpeed(i.speed_mbps));
+        if (i.addresses) parts.push(i.addresses[0]);
+        if (i.wireless) {
+            const w = i.wireless;
+            if (w.ssid) parts.push(w.ssid);
+            if (w.signal_dbm !== undefined) parts.push(`${w.signal_dbm} dBm`);
+            if (w.quality_percent !== undefined) parts.push(`${w.quality_percent.toFixed(0)}%`);
+        }
+        return `<div>${parts.join(' \u00b7 ')}</div>`;
+    }).join('');
+}
+
+async function loadVms() {
+    const response = await fetch('api/v1/vms');
+    const section = document.getElementById('vms-section');
+    if (!response.ok) {
+        section.style.display = 'none';
+        return;
+    }
+    const data = await response.json();
+
+    if (data.vms.length === 0) {
+        section.style.display = 'none';
+        return;
+    }
+
+    section.style.display = 'block';
+    document.getElementById('vms-grid').innerHTML = data.vms.map(vm => {
+        const stateClass = vm.state === 'running' ? 'text-success'
+            : vm.state === 'paused' ? 'text-warning'
+            : 'text-danger';
+        const cpu = vm.cpu_percent != null ? `${vm.cpu_percent.toFixed(1)}% CPU` : vm.state;
+        const memory = vm.memory_used_bytes != null
+            ? `${formatBytes(vm.memory_used_bytes)} / ${formatBytes(vm.memory_max_bytes)}`
+            : formatBytes(vm.memory_max_bytes);
+
+        return `
+            <div class="temp-card">
+                <div class="temp-label">${vm.name}</div>
+                <div class="temp-value ${stateClass}">${cpu}</div>
+                <div class="temp-thresholds">${vm.vcpus} vCPU \u00b7 ${memory}</div>
+            </div>
+        `;
+    }).join('');
+}
+
+async function loadTopology() {
+    const response = await fetch('api/v1/topology');
+    const data = await response.json();
+    const section = document.getElementById('topology-section');
+    const hostCount = data.groups.reduce((n, g) => n + g.hosts.length, 0);
+
+    if (hostCount <= 1) {
+        section.style.display = 'none';
+        return;
+    }
+
+    section.style.display = 'block';
+    document.getElementById('topology-groups').innerHTML = data.groups.map(group => `
+        <div class="temp-label">${group.name}</div>
+        <div class="temperatures-grid">
+            ${group.hosts.map(h => {
+                const stateClass = h.state === 'up' ? 'text-success'
+                    : h.state === 'down' && !h.suppressed_by ? 'text-danger'
+                    : 'text-warning';
+                const detail = h.suppressed_by ? `via ${h.suppressed_by}`
+                    : h.parent ? `on ${h.parent}`
+                    : h.children ? `${h.children.length} guests`
+                    : '';
+                return `
+                    <div class="temp-card">
+                        <div class="temp-label">${h.name}</div>
+                        <div class="temp-value ${stateClass}">${h.state}</div>
+                        ${detail ? `<div class="temp-thresholds">${detail}</div>` : ''}
+                    </div>
+                `;
+            }).join('')}
+        </div>
+    `).join('');
+}
+
+const KERNEL_LIMIT_LABELS = {
+    file_handles: 'File handles',
+    pids: 'PIDs',
+    conntrack: 'Conntrack',
+};
+
+function renderKernelLimits(limits) {
+    const section = document.getElementById('kernel-limits-section');
+    const grid = document.getElementById('kernel-limits-grid');
+    const entries = Object.entries(KERNEL_LIMIT_LABELS).filter(([key]) => limits[key]);
+
+    if (entries.length === 0) {
+        section.style.display = 'none';
+        return;
+    }
+
+    section.style.display = 'block';
+    grid.innerHTML = entries.map(([key, label]) => {
+        const l = limits[key];
+        const limitClass = l.usage_percent >= 90 ? 'text-danger'
+            : l.usage_percent >= 80 ? 'text-warning'
+            : 'text-success';
+
+        return `
+            <div class="temp-card">
+                <div class="temp-label">${label}</div>
+                <div class="temp-value ${limitClass}">${l.usage_percent.toFixed(1)}%</div>
+                <div class="temp-thresholds">${l.used.toLocaleString()} / ${l.max.toLocaleString()}</div>
+            </div>
+        `;
+    }).join('');
 }
 
 function renderTemperatures(temperatures) {
@@ -174,7 +311,7 @@ function renderTemperatures(temperatures) {
 // ---- Charts ----
 
 async function loadCharts() {
-    const response = await fetch('/api/history?duration=3600');
+    const response = await fetch('api/v1/history?window=1h&resolution=30s');
     const data = await response.json();
 
     if (data.timestamps.length === 0) {
@@ -189,28 +326,106 @@ async function loadCharts() {
         return;
     }
 
-    const labels = data.timestamps.map(t => {
-        const d = new Date(t);
-        return `${d.getHours().toString().padStart(2, '0')}:${d.getMinutes().toString().padStart(2, '0')}`;
-    });
+    const labels = timeLabels(data.timestamps);
+    const markers = annotationMarkers(data.timestamps, data.annotations || []);
 
     drawChart('chart-cpu', labels, [
         { values: data.cpu, color: '#58a6ff', label: 'CPU %' }
-    ], { min: 0, max: 100, suffix: '%' });
+    ], { min: 0, max: 100, suffix: '%', markers });
 
     const memPercent = data.memory_used.map(used =>
         data.memory_total > 0 ? (used / data.memory_total * 100) : 0
     );
     drawChart('chart-memory', labels, [
         { values: memPercent, color: '#3fb950', label: 'RAM %' }
-    ], { min: 0, max: 100, suffix: '%' });
+    ], { min: 0, max: 100, suffix: '%', markers });
 
     const maxLoad = Math.max(...data.load_1, ...data.load_5, ...data.load_15, 1);
     drawChart('chart-load', labels, [
         { values: data.load_1, color: '#f85149', label: '1m' },
         { values: data.load_5, color: '#d29922', label: '5m' },
         { values: data.load_15, color: '#58a6ff', label: '15m' },
-    ], { min: 0, max: Math.ceil(maxLoad * 1.2) });
+    ], { min: 0, max: Math.ceil(maxLoad * 1.2), markers });
+
+    await loadCustomCharts();
+    await loadFirewallCounters();
+}
+
+// Packets per second of each named firewall rule, when sampling is opted in
+async function loadFirewallCounters() {
+    const container = document.getElementById('firewall-counters');
+    const response = await fetch('api/v1/security/firewall/counters');
+    const data = await response.json();
+    const counters = data.counters.filter(c => c.timestamps.length > 0);
+    if (counters.length === 0) {
+        container.innerHTML = '';
+        return;
+    }
+
+    container.innerHTML = `
+        <div class="chart-card">
+            <h3>Firewall Rule Hits (packets/s)</h3>
+            <canvas id="chart-firewall-counters" width="600" height="200"></canvas>
+        </div>
+    `;
+    const colors = ['#f85149', '#d29922', '#58a6ff', '#3fb950', '#bc8cff'];
+    const longest = counters.reduce((a, b) => (b.timestamps.length > a.timestamps.length ? b : a));
+    const max = Math.max(...counters.flatMap(c => c.packets_per_second), 1);
+    drawChart('chart-firewall-counters', timeLabels(longest.timestamps), counters.map((c, i) => ({
+        values: c.packets_per_second,
+        color: colors[i % colors.length],
+        label: c.name,
+    })), { min: 0, max: Math.ceil(max * 1.2) });
+}
+
+// One chart per custom metric name, one line per label set
+async function loadCustomCharts() {
+    const container = document.getElementById('custom-charts');
+    const response = await fetch('api/v1/custom');
+    const data = await response.json();
+    const names = [...new Set(data.metrics.map(m => m.name))];
+
+    container.innerHTML = names.map((name, i) => `
+        <div class="chart-card">
+            <h3>${name}</h3>
+            <canvas id="chart-custom-${i}" width="600" height="200"></canvas>
+        </div>
+    `).join('');
+
+    const colors = ['#58a6ff', '#3fb950', '#d29922', '#f85149', '#bc8cff'];
+    for (const [i, name] of names.entries()) {
+        const history = await fetch(`api/v1/custom/${encodeURIComponent(name)}/history?duration=3600`);
+        if (!history.ok) continue;
+        const series = (await history.json()).series;
+        const values = series.flatMap(s => s.values);
+        const min = Math.min(...values, 0);
+        const max = Math.max(...values, 1);
+        drawChart(`chart-custom-${i}`, timeLabels(series[0].timestamps), series.map((s, j) => ({
+            values: s.values,
+            color: colors[j % colors.length],
+            label: Object.values(s.labels).join(',') || name,
+        })), { min: Math.floor(min), max: Math.ceil(max * 1.2) });
+    }
+}
+
+function timeLabels(timestamps) {
+    return timestamps.map(t => {
+        const d = new Date(t);
+        return `${d.getHours().toString().padStart(2, '0')}:${d.getMinutes().toString().padStart(2, '0')}`;
+    });
+}
+
+// Place each annotation at the sample closest to its timestamp
+function annotationMarkers(timestamps, annotations) {
+    const times = timestamps.map(t => new Date(t).getTime());
+    return annotations.map(a => {
+        const at = new Date(a.timestamp).getTime();
+        let index = 0;
+        times.forEach((t, i) => {
+            if (Math.abs(t - at) < Math.abs(times[index] - at)) index = i;
+        });
+        return { index, label: a.note };
+    });
 }
 
 function drawChart(canvasId, labels, datasets, options) {
@@ -261,6 +476,22 @@ function drawChart(canvasId, labels, datasets, options) {
         ctx.fillText(labels[i], x, height - 8);
     }
 
+    (options.markers || []).forEach(marker => {
+        const x = padding.left + (marker.index / (labels.length - 1 || 1)) * chartWidth;
+        ctx.strokeStyle = '#a371f7';
+        ctx.lineWidth = 1;
+        ctx.setLineDash([4, 3]);
+        ctx.beginPath();
+        ctx.moveTo(x, padding.top);
+        ctx.lineTo(x, padding.top + chartHeight);
+        ctx.stroke();
+        ctx.setLineDash([]);
+        ctx.fillStyle = '#a371f7';
+        ctx.font = '10px sans-serif';
+        ctx.textAlign = 'left';
+        ctx.fillText(marker.label, x + 3, padding.top + 10);
+    });
+
     datasets.forEach(dataset => {
         ctx.strokeStyle = dataset.color;
         ctx.lineWidth = 2;
@@ -301,7 +532,7 @@ function drawChart(canvasId, labels, datasets, options) {
 // ---- Containers list ----
 
 async function loadContainers() {
-    const response = await fetch('/api/containers');
+    const response = await fetch('api/v1/containers');
     const data = await response.json();
     cachedContainersData = data;
 
@@ -357,6 +588,8 @@ function renderContainerRow(container, isTopConsumer) {
             <div class="container-name">
                 <span class="status-dot ${statusClass}"></span>
                 <span>${container.name}</span>
+                ${container.runtime === 'lxc' ? '<span class="text-secondary">LXC</span>' : ''}
+                ${container.memory_trend && container.memory_trend.likely_leak ? `<span class="text-warning" title="Memory growing ${formatBytes(container.memory_trend.growth_bytes_per_hour)}/h">&#9888; leak?</span>` : ''}
             </div>
             <div class="container-metrics">
                 ${container.state === 'running' ? `
@@ -401,7 +634,7 @@ function sortContainers(containers, sortBy) {
 // ---- Container detail ----
 
 async function loadContainerDetail(name) {
-    const response = await fetch(`/api/containers/${encodeURIComponent(name)}`);
+    const response = await fetch(`api/v1/containers/${encodeURIComponent(name)}`);
     if (!response.ok) {
         renderDetailView(`
             <nav class="breadcrumb"><a href="#containers">Containers</a> / <span>${name}</span></nav>
@@ -433,8 +666,10 @@ async function loadContainerDetail(name) {
         <div class="detail-info">
             <div class="info-row"><span class="info-label">Image</span><span class="info-value">${c.image}</span></div>
             <div class="info-row"><span class="info-label">ID</span><span class="info-value mono">${c.id}</span></div>
+            <div class="info-row"><span class="info-label">Runtime</span><span class="info-value">${c.runtime || 'docker'}</span></div>
             ${c.stack ? `<div class="info-row"><span class="info-label">Stack</span><span class="info-value"><a href="#stack/${encodeURIComponent(c.stack)}">${c.stack}</a></span></div>` : ''}
             <div class="info-row"><span class="info-label">Created</span><span class="info-value">${createdStr}</span></div>
+            ${(c.ports || []).map(p => `<div class="info-row"><span class="info-label">Port ${p.host_port}/${p.protocol}</span><span class="info-value">\u2192 ${p.container_port}${p.connections !== undefined ? ` \u00b7 ${p.connections} connection${p.connections === 1 ? '' : 's'}` : ''}</span></div>`).join('')}
         </div>
     `;
 
@@ -465,6 +700,16 @@ async function loadContainerDetail(name) {
                     ` : `
                         <div class="metric-details"><span>${formatBytes(c.memory.used_bytes)}</span></div>
                     `}
+                    ${c.memory.rss_bytes != null ? `
+                        <div class="metric-details text-secondary">
+                            rss ${formatBytes(c.memory.rss_bytes)} \u00b7 cache ${formatBytes(c.memory.cached_bytes || 0)}${c.memory.swap_used_bytes != null ? ` \u00b7 swap ${formatBytes(c.memory.swap_used_bytes)}` : ''}
+                        </div>
+                    ` : ''}
+                    ${(c.network.rx_dropped > 0 || c.network.tx_dropped > 0) ? `
+                        <div class="text-secondary" style="text-align:center; margin-top:4px; font-size:12px;">
+                            dropped: rx ${c.network.rx_dropped} / tx ${c.network.tx_dropped}
+                        </div>
+                    ` : ''}
                 </div>
 
                 <div class="metric-card">
@@ -478,6 +723,11 @@ async function loadContainerDetail(name) {
                             errors: rx ${c.network.rx_errors} / tx ${c.network.tx_errors}
                         </div>
                     ` : ''}
+                    ${(c.network.rx_dropped > 0 || c.network.tx_dropped > 0) ? `
+                        <div class="text-secondary" style="text-align:center; margin-top:4px; font-size:12px;">
+                            dropped: rx ${c.network.rx_dropped} / tx ${c.network.tx_dropped}
+                        </div>
+                    ` : ''}
                 </div>
 
                 <div class="metric-card">
@@ -497,7 +747,7 @@ async function loadContainerDetail(name) {
 // ---- Stack detail ----
 
 async function loadStackDetail(stackName) {
-    const response = await fetch('/api/containers');
+    const response = await fetch('api/v1/containers');
     const data = await response.json();
 
     const stack = data.stacks.find(s => s.name === stackName);
@@ -575,8 +825,39 @@ async function loadStackDetail(stackName) {
         `;
     });
 
-    html += '</div>';
+    html += `
+        </div>
+
+        <div class="charts-grid">
+            <div class="chart-card">
+                <h3>Stack CPU (last hour)</h3>
+                <canvas id="chart-stack-cpu" width="600" height="200"></canvas>
+            </div>
+            <div class="chart-card">
+                <h3>Stack RAM (last hour)</h3>
+                <canvas id="chart-stack-memory" width="600" height="200"></canvas>
+            </div>
+        </div>
+    `;
     renderDetailView(html);
+    await loadStackCharts(stackName);
+}
+
+async function loadStackCharts(stackName) {
+    const response = await fetch(`api/v1/stacks/${encodeURIComponent(stackName)}/history?duration=3600`);
+    if (!response.ok) return;
+    const data = await response.json();
+
+    const labels = timeLabels(data.timestamps);
+    const maxCpu = Math.max(...data.cpu_percent, 1);
+    drawChart('chart-stack-cpu', labels, [
+        { values: data.cpu_percent, color: '#58a6ff', label: 'CPU %' }
+    ], { min: 0, max: Math.ceil(maxCpu * 1.2), suffix: '%' });
+
+    const memoryMb = data.memory_bytes.map(b => b / (1024 * 1024));
+    drawChart('chart-stack-memory', labels, [
+        { values: memoryMb, color: '#3fb950', label: 'RAM MB' }
+    ], { min: 0, max: Math.ceil(Math.max(...memoryMb, 1) * 1.2), suffix: 'M' });
 }
 
 function renderDetailView(html) {
@@ -586,21 +867,22 @@ function renderDetailView(html) {
 // ---- Processes ----
 
 async function loadProcesses() {
-    const response = await fetch(`/api/processes?sort=${currentSort}&limit=20`);
+    const response = await fetch(`api/v1/processes?sort=${currentSort}&limit=20`);
     const data = await response.json();
     const tbody = document.getElementById('processes-list');
 
     if (data.processes.length === 0) {
-        tbody.innerHTML = '<tr><td colspan="5" class="loading">No processes found</td></tr>';
+        tbody.innerHTML = '<tr><td colspan="6" class="loading">No processes found</td></tr>';
         return;
     }
 
     tbody.innerHTML = data.processes.map(p => `
         <tr>
             <td>${p.pid}</td>
-            <td>${p.user}</td>
+            <td>${p.user ?? '—'}</td>
             <td>${p.cpu_percent.toFixed(1)}%</td>
             <td>${p.memory_percent.toFixed(1)}%</td>
+            <td title="${p.started_at ?? ''}">${p.age_seconds != null ? formatAge(p.age_seconds) : '—'}</td>
             <td>
                 ${p.command}
                 ${p.container_id ? '<span class="text-secondary"> [C]</span>' : ''}
@@ -612,7 +894,7 @@ async function loadProcesses() {
 // ---- Disks ----
 
 async function loadDisks() {
-    const response = await fetch('/api/disks');
+    const response = await fetch('api/v1/disks');
     const data = await response.json();
     const tbody = document.getElementById('disks-list');
 
@@ -638,7 +920,7 @@ async function loadDisks() {
 // ---- Services ----
 
 async function loadServices() {
-    const response = await fetch('/api/services');
+    const response = await fetch('api/v1/services');
     const data = await response.json();
     const content = document.getElementById('services-content');
 
@@ -698,13 +980,26 @@ function renderServiceTable(services) {
 
 // ---- Feature detection ----
 
-async function checkServicesAvailable() {
+// Tabs backed by a collector that may lack permissions
+const CAPABILITY_TABS = {
+    containers: 'containers',
+    processes: 'processes',
+    services: 'services',
+};
+
+async function checkCapabilities() {
     try {
-        const response = await fetch('/api/services');
+        const response = await fetch('api/v1/health');
         const data = await response.json();
-        if (!data.available) {
-            const btn = document.querySelector('.tab-btn[data-tab="services"]');
-            if (btn) btn.style.display = 'none';
+        const capabilities = data.capabilities || {};
+        for (const [name, tab] of Object.entries(CAPABILITY_TABS)) {
+            if (capabilities[name] && !capabilities[name].available) {
+                const btn = document.querySelector(`.tab-btn[data-tab="${tab}"]`);
+                if (btn) {
+                    btn.style.display = 'none';
+                    btn.title = capabilities[name].reason || '';
+                }
+            }
         }
     } catch (_) {
         // ignore
@@ -731,6 +1026,13 @@ function formatUptime(seconds) {
     return `up ${hours}:${minutes.toString().padStart(2, '0')}`;
 }
 
+function formatAge(seconds) {
+    if (seconds < 60) return `${seconds}s`;
+    if (seconds < 3600) return `${Math.floor(seconds / 60)}m`;
+    if (seconds < 86400) return `${Math.floor(seconds / 3600)}h`;
+    return `${Math.floor(seconds / 86400)}d`;
+}
+
 function getUsageClass(percent) {
     if (percent < 70) return 'text-success';
     if (percent < 90) return 'text-warning';
@@ -0,0 +1,10 @@
+//! NanoMon as a library, for the benchmarks in `benches/`; the binary is `main.rs`
+
+pub mod adapters;
+pub mod application;
+pub mod config;
+pub mod domain;
+pub mod interface;
+pub mod ports;
+#[cfg(feature = "sandbox")]
+pub mod sandbox;
@@ -1,49 +1,155 @@
-mod adapters;
-mod application;
-mod config;
-mod domain;
-mod interface;
-mod ports;
+#[cfg(feature = "sandbox")]
+use nanomon::sandbox;
+use nanomon::{adapters, application, config, domain, interface, ports};
 
 use std::sync::Arc;
 use std::time::Duration;
 
+use async_trait::async_trait;
 use tracing::{info, warn};
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, Layer};
 
+#[cfg(feature = "zstd")]
+use adapters::ZstdCodec;
 use adapters::{
-    DockerAdapter, MemoryStore, ProcfsAdapter, ProcfsConfig, SystemctlAdapter, WebhookSink,
+    ArchiveStore, AuthLogReader, ChannelSink, CircuitBreaker, CircuitBreakerContainerSource,
+    CollectorRegistry, CombinedContainerSource, CommandSpeedtestRunner, CommandVmSource,
+    CommandVulnerabilitySource, DockerAdapter, FileInventoryStore, FsBackupSource,
+    FsDirectoryScanner, HttpSnapshotPublisher, JsonCodec, LogReclaimSource, LxcCgroupSource,
+    MemoryStore, NetfilterFirewall, OidcClient, PingCommandProber, ProcfsAdapter, ProcfsConfig,
+    ReportDirVulnerabilitySource, RetryingContainerSource, ScannerTool, ScriptRunner,
+    SystemctlAdapter, TimeoutSystemSource, WebhookSink, WolSshPowerControl,
 };
-use application::{AlertEvaluator, MonitoringService};
-use config::Config;
-use domain::AlertRule;
-use interface::http::create_router;
+use application::{
+    AlertEvaluator, AuthGuard, BackupMonitor, BurnInService, CapabilityMonitor, CollectionBudget,
+    ContainerSupervisor, CustomMetricsService, EventBus, EventLog, EventPusher, FirewallMonitor,
+    FootprintMonitor, HeartbeatMonitor, LatencyMonitor, LoginService, MaintenanceService,
+    MonitoringService, PowerService, ProcessControlService, ReactionService, RebootDetector,
+    ReclaimService, RemoteHostService, SecurityMonitor, SnapshotObserver, SpeedtestMonitor,
+    SshMonitor, TopologyService, VmMonitor, VolumeUsageService, BUS_CAPACITY,
+};
+use config::{AlertConfig, Config};
+use domain::{
+    Capability, CollectorConfig, CollectorKind, CommandSanitizer, EventSubscription, Host,
+    Hypervisor, SpeedtestTool, Topology,
+};
+use interface::backup::DataFiles;
+use interface::http::{create_router, AccessLog, AppState, HttpMetrics, ResponseCache};
 
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     // Load configuration
     let config = Config::from_env();
 
-    // Initialize logging
+    if std::env::args().nth(1).as_deref() == Some("--check-config") {
+        std::process::exit(interface::config_check::run(&config));
+    }
+    if std::env::args().nth(1).as_deref() == Some("doctor") {
+        let runtime = tokio::runtime::Runtime::new()?;
+        std::process::exit(runtime.block_on(interface::doctor::run(&config)));
+    }
+    if std::env::args().nth(1).as_deref() == Some("restore") {
+        let args: Vec<String> = std::env::args().skip(2).collect();
+        std::process::exit(interface::backup::run(&config, &args));
+    }
+    if std::env::args().nth(1).as_deref() == Some("check") {
+        let args: Vec<String> = std::env::args().skip(2).collect();
+        let runtime = tokio::runtime::Runtime::new()?;
+        std::process::exit(runtime.block_on(interface::nagios::run(&config, &args)));
+    }
+
+    // Initialize logging; spans only go to the OTLP exporter, so log lines
+    // look the same with or without it
+    let json_logs = config.log_format.eq_ignore_ascii_case("json");
+    #[cfg(feature = "otel")]
+    let (otel_layer, tracer_provider) = match config
+        .otlp_endpoint
+        .as_deref()
+        .map(interface::telemetry::layer)
+    {
+        Some(Ok((layer, provider))) => (Some(layer), Some(provider)),
+        Some(Err(e)) => {
+            eprintln!("NANOMON_OTLP_ENDPOINT ignored: {}", e);
+            (None, None)
+        }
+        None => (None, None),
+    };
+    #[cfg(not(feature = "otel"))]
+    let otel_layer: Option<tracing_subscriber::layer::Identity> = None;
+    let events_only = || tracing_subscriber::filter::filter_fn(|metadata| !metadata.is_span());
     tracing_subscriber::registry()
         .with(
             tracing_subscriber::EnvFilter::try_from_default_env()
                 .unwrap_or_else(|_| format!("nanomon={},tower_http=info", config.log_level).into()),
         )
-        .with(tracing_subscriber::fmt::layer())
+        .with(otel_layer)
+        .with(json_logs.then(|| {
+            tracing_subscriber::fmt::layer()
+                .json()
+                .flatten_event(true)
+                .with_current_span(false)
+                .with_filter(events_only())
+        }))
+        .with((!json_logs).then(|| tracing_subscriber::fmt::layer().with_filter(events_only())))
         .init();
 
     info!("Starting NanoMon v{}", env!("CARGO_PKG_VERSION"));
-    info!("Configuration: {:?}", config);
+    info!("Configuration: {:?}", config.redacted());
+    for problem in interface::config_check::check_env(std::env::vars()) {
+        warn!("{}", problem);
+    }
+
+    // Load alert rules and heartbeat checks if configured
+    let alert_config = load_alert_config(&config);
+
+    // Landlock and seccomp only cover threads created afterwards
+    if config.sandbox {
+        #[cfg(feature = "sandbox")]
+        sandbox::apply(&config, &alert_config)?;
+        #[cfg(not(feature = "sandbox"))]
+        warn!("NANOMON_SANDBOX ignored: built without the sandbox feature");
+    }
+
+    if let Some(endpoint) = &config.otlp_endpoint {
+        #[cfg(feature = "otel")]
+        info!("Exporting traces to {}", endpoint);
+        #[cfg(not(feature = "otel"))]
+        warn!(
+            "NANOMON_OTLP_ENDPOINT={} ignored: built without the otel feature",
+            endpoint
+        );
+    }
 
+    let result = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()?
+        .block_on(run(config, alert_config));
+    #[cfg(feature = "otel")]
+    if let Some(provider) = tracer_provider {
+        if let Err(e) = provider.shutdown() {
+            warn!("Failed to flush traces: {}", e);
+        }
+    }
+    result
+}
+
+async fn run(
+    config: Config,
+    alert_config: AlertConfig,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     // Initialize adapters
-    let procfs_config = ProcfsConfig::new(config.proc_path.clone(), config.sys_path.clone());
+    let procfs_config = ProcfsConfig::new(config.proc_path.clone(), config.sys_path.clone())
+        .with_etc_path(config.etc_path.clone());
     let procfs_adapter = ProcfsAdapter::new(procfs_config);
 
     let docker_adapter = match DockerAdapter::new() {
         Ok(adapter) => {
-            info!("Connected to Docker daemon");
-            Arc::new(adapter) as Arc<dyn ports::ContainerSource>
+            let adapter = adapter.with_cgroup_root(config.sys_path.join("fs/cgroup"));
+            if cfg!(feature = "docker") {
+                info!("Connected to Docker daemon");
+            } else {
+                info!("Built without the docker feature, container monitoring disabled");
+            }
+            Arc::new(adapter)
         }
         Err(e) => {
             warn!(
@@ -54,16 +160,158 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         }
     };
 
+    // Transient Docker errors are retried; a daemon that keeps failing trips the breaker
+    let mut container_source: Arc<dyn ports::ContainerSource> =
+        Arc::new(CircuitBreakerContainerSource::new(
+            Arc::new(RetryingContainerSource::new(
+                docker_adapter.clone(),
+                config.docker_retries,
+                Duration::from_millis(config.docker_retry_backoff_ms),
+            )),
+            CircuitBreaker::new(
+                "docker",
+                config.docker_breaker_threshold,
+                Duration::from_secs(config.docker_breaker_cooldown),
+            ),
+        ));
+
+    if config.enable_lxc {
+        info!(
+            "Listing LXC containers from {:?}",
+            config.sys_path.join("fs/cgroup")
+        );
+        container_source = Arc::new(CombinedContainerSource::new(vec![
+            ("docker", container_source),
+            (
+                "lxc",
+                Arc::new(LxcCgroupSource::new(
+                    config.proc_path.clone(),
+                    config.sys_path.clone(),
+                    config.etc_path.clone(),
+                )),
+            ),
+        ]));
+    }
+
     // Initialize metric store
-    let metric_store = Arc::new(MemoryStore::new(config.history_size));
+    let mut memory_store = MemoryStore::new(config.history_size);
+    if config.history_forget_after > 0 {
+        memory_store =
+            memory_store.with_forget_after(Duration::from_secs(config.history_forget_after));
+    }
+    let mut metric_store: Arc<dyn ports::MetricStore> = Arc::new(memory_store);
+    if let Some(path) = config.archive_path.clone() {
+        let codec: Box<dyn ports::SnapshotCodec> = match config.archive_compression.as_str() {
+            "none" => Box::new(JsonCodec),
+            #[cfg(feature = "zstd")]
+            _ => Box::new(ZstdCodec::new(config.archive_compression_level)),
+            #[cfg(not(feature = "zstd"))]
+            _ => {
+                warn!("Built without the zstd feature, archiving uncompressed");
+                Box::new(JsonCodec)
+            }
+        };
+        let archive = ArchiveStore::new(metric_store, codec, &path, config.history_size);
+        info!(
+            "Restored {} snapshots from archive {:?}",
+            archive.restore(),
+            path
+        );
+        metric_store = Arc::new(archive);
+    }
+    // Third-party plugins, used by `[[collectors]]` and `plugin://` notification URLs
+    let registry = CollectorRegistry::new(alert_config.plugins);
+    // Webhooks, and `mailto:`, `telegram:` and `plugin://` destinations
+    let alert_sink = registry.notifier(Arc::new(
+        ChannelSink::new(Arc::new(WebhookSink::new().with_timezone(config.timezone)))
+            .with_sendmail(config.sendmail_path.clone())
+            .with_telegram_token(config.telegram_bot_token.clone()),
+    ));
+
+    let mut event_webhooks = alert_config.event_webhooks;
+    if let Some(url) = config.event_webhook_url.clone() {
+        event_webhooks.push(EventSubscription::all(url));
+    }
+    // Snapshots, log entries and alert incidents, for whoever subscribes
+    let bus = Arc::new(EventBus::new(BUS_CAPACITY));
+    if !event_webhooks.is_empty() {
+        tokio::spawn(EventPusher::new(alert_sink.clone(), event_webhooks).run(bus.subscribe()));
+    }
+    let event_log = Arc::new(
+        EventLog::new(config.event_log_size)
+            .with_timezone(config.timezone)
+            .with_bus(bus.clone()),
+    );
+
+    let volumes = Arc::new(VolumeUsageService::new(
+        container_source.clone(),
+        Arc::new(FsDirectoryScanner::new(config.host_root.clone())),
+        Duration::from_secs(config.volume_cache_ttl),
+        config.volume_scan_limit,
+    ));
+    let reclaim = Arc::new(ReclaimService::new(
+        vec![
+            ("docker", docker_adapter.clone()),
+            (
+                "logs",
+                Arc::new(LogReclaimSource::new(
+                    config.host_root.clone(),
+                    config.volume_scan_limit,
+                )),
+            ),
+        ],
+        Duration::from_secs(config.volume_cache_ttl),
+    ));
+
+    // User-defined collectors, each on its own interval; values join every snapshot
+    let textfile = config
+        .textfile_dir
+        .clone()
+        .map(|directory| CollectorConfig {
+            name: "textfile".to_string(),
+            kind: CollectorKind::Textfile { directory },
+            interval_seconds: config.poll_interval,
+            timeout_seconds: 10,
+        });
+    let custom_metrics = Arc::new(CustomMetricsService::new(
+        alert_config
+            .collectors
+            .into_iter()
+            .chain(textfile)
+            .filter_map(|c| match registry.build(&c) {
+                Ok(collector) => Some((c, collector)),
+                Err(e) => {
+                    warn!("Collector '{}' disabled: {}", c.name, e);
+                    None
+                }
+            })
+            .collect(),
+    ));
+    if custom_metrics.has_collectors() {
+        tokio::spawn(custom_metrics.clone().run());
+        info!("Custom collectors started");
+    }
 
     // Create monitoring service
     let mut monitoring_service = MonitoringService::new(
-        Arc::new(procfs_adapter.system_source()),
-        docker_adapter,
-        Arc::new(procfs_adapter.process_source()),
+        Arc::new(TimeoutSystemSource::new(
+            Arc::new(procfs_adapter.system_source()),
+            Duration::from_secs(config.system_timeout),
+        )),
+        container_source,
+        Arc::new(
+            procfs_adapter
+                .process_source()
+                .with_sanitizer(CommandSanitizer::new(
+                    config.cmdline_redact.clone(),
+                    config.cmdline_max_len,
+                ))
+                .with_sampling(config.process_sampling, config.process_sampling_above),
+        ),
         metric_store,
-    );
+        event_log.clone(),
+    )
+    .with_bus(bus.clone());
 
     // Optionally enable systemd monitoring
     if config.enable_systemd {
@@ -71,90 +319,529 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         monitoring_service = monitoring_service.with_service_source(systemd_adapter);
     }
 
-    let monitoring_service = Arc::new(monitoring_service);
+    let maintenance = Arc::new(MaintenanceService::new(event_log.clone()));
+    let mut monitoring_service = monitoring_service
+        .with_custom_metrics(custom_metrics.clone())
+        .with_maintenance(maintenance.clone());
+    if config.collection_budget_percent > 0 {
+        monitoring_service = monitoring_service.with_budget(CollectionBudget::new(
+            Duration::from_secs(config.poll_interval),
+            config.collection_budget_percent as f64 / 100.0,
+        ));
+    }
+    let monitoring_service =
+        Arc::new(monitoring_service.with_poll_interval(Duration::from_secs(config.poll_interval)));
 
     info!("Monitoring service initialized");
 
-    // Load alert rules if configured
-    let alert_evaluator = load_alert_evaluator(&config);
-    if let Some(ref evaluator) = alert_evaluator {
-        if evaluator.has_rules() {
-            info!("Alert rules loaded");
+    if let Some(bind) = config.snmp_bind.clone() {
+        #[cfg(feature = "snmp")]
+        match interface::snmp::parse_oid(&config.snmp_oid) {
+            Some(base) => {
+                let community = config.snmp_community.clone();
+                let service = monitoring_service.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = interface::snmp::serve(&bind, community, base, service).await {
+                        warn!("SNMP agent stopped: {}", e);
+                    }
+                });
+            }
+            None => warn!(
+                "Invalid NANOMON_SNMP_OID '{}', SNMP agent disabled",
+                config.snmp_oid
+            ),
         }
+        #[cfg(not(feature = "snmp"))]
+        warn!(
+            "NANOMON_SNMP_BIND={} ignored: built without the snmp feature",
+            bind
+        );
     }
 
-    // Start background polling loop
-    let poll_service = monitoring_service.clone();
-    let poll_interval = config.poll_interval;
-    tokio::spawn(async move {
-        let mut interval = tokio::time::interval(Duration::from_secs(poll_interval));
-        loop {
-            interval.tick().await;
-            match poll_service.collect_all().await {
-                Ok(snapshot) => {
-                    // Evaluate alerts before storing
-                    if let Some(ref evaluator) = alert_evaluator {
-                        evaluator.evaluate(&snapshot).await;
-                    }
-                    poll_service.store_snapshot(snapshot);
-                }
-                Err(e) => {
-                    tracing::error!("Failed to collect metrics: {}", e);
+    let service_status = if monitoring_service.has_services() {
+        Capability::available()
+    } else if config.enable_systemd {
+        Capability::unavailable("systemctl not usable (needs the host D-Bus socket)")
+    } else {
+        Capability::unavailable("disabled (NANOMON_ENABLE_SYSTEMD)")
+    };
+    let volume_usage = match &config.host_root {
+        Some(root) if root.is_dir() => Capability::available(),
+        Some(root) => Capability::unavailable(format!("{:?} is not mounted", root)),
+        None => Capability::unavailable("NANOMON_HOST_ROOT not set"),
+    };
+    let capabilities = Arc::new(
+        CapabilityMonitor::new(vec![
+            Arc::new(procfs_adapter.clone()),
+            docker_adapter.clone(),
+        ])
+        .with_capability("services", service_status)
+        .with_capability("volumes", volume_usage),
+    );
+    for (name, capability) in capabilities.refresh().await {
+        if let Some(reason) = capability.reason.filter(|_| !capability.available) {
+            warn!("Collector '{}' unavailable: {}", name, reason);
+        }
+    }
+
+    let mut alerts = AlertEvaluator::new(alert_config.rules, alert_sink.clone())
+        .with_routes(alert_config.routes)
+        .with_digest(config.alert_digest)
+        .with_bus(bus.clone());
+    let burn_in = Arc::new(BurnInService::new(event_log.clone()));
+    alerts = alerts.with_burn_in(burn_in.clone());
+    if !alert_config.reactions.is_empty() {
+        info!(
+            "{} alert reactions configured",
+            alert_config.reactions.len()
+        );
+        alerts = alerts.with_reactions(Arc::new(ReactionService::new(
+            alert_config.reactions,
+            Arc::new(ScriptRunner),
+            event_log.clone(),
+        )));
+    }
+    let alerts = Arc::new(alerts);
+    if !alert_config.supervise.is_empty() {
+        info!(
+            "Supervising containers with {} restart policies",
+            alert_config.supervise.len()
+        );
+    }
+    let supervisor = Arc::new(ContainerSupervisor::new(
+        alert_config.supervise,
+        docker_adapter.clone(),
+        alert_sink.clone(),
+        event_log.clone(),
+    ));
+    if config.admin_token.is_none() {
+        for check in alert_config.heartbeats.iter().filter(|c| c.token.is_none()) {
+            warn!(
+                "Heartbeat '{}' has no token and NANOMON_ADMIN_TOKEN is unset; its pings will be rejected",
+                check.name
+            );
+        }
+    }
+    let heartbeats = Arc::new(HeartbeatMonitor::new(
+        alert_config.heartbeats,
+        alert_sink.clone(),
+        event_log.clone(),
+    ));
+    let backups = Arc::new(BackupMonitor::new(
+        alert_config.backups,
+        Arc::new(FsBackupSource::new()),
+        alert_sink.clone(),
+        event_log.clone(),
+    ));
+
+    // Reboot detection survives NanoMon restarts only with a state file
+    let inventory_store = config
+        .state_file
+        .clone()
+        .map(|path| Arc::new(FileInventoryStore::new(path)) as Arc<dyn ports::InventoryStore>);
+    let reboot_detector = RebootDetector::new(
+        inventory_store,
+        event_log.clone(),
+        config.reboot_grace_seconds,
+    );
+
+    let power = Arc::new(PowerService::new(
+        alert_config.hosts,
+        Arc::new(WolSshPowerControl::new()),
+        event_log.clone(),
+    ));
+    let process_control = Arc::new(ProcessControlService::new(
+        Arc::new(procfs_adapter.process_control()),
+        event_log.clone(),
+    ));
+    if !power.targets().is_empty() && config.admin_token.is_none() {
+        warn!("Power targets configured but NANOMON_ADMIN_TOKEN is unset; power actions disabled");
+    }
+
+    let latency = Arc::new(LatencyMonitor::new(
+        alert_config.ping,
+        Arc::new(PingCommandProber::new()),
+        config.history_size,
+    ));
+    if !latency.targets().is_empty() {
+        tokio::spawn(
+            latency
+                .clone()
+                .run(Duration::from_secs(config.ping_interval)),
+        );
+        info!(
+            "Latency probing started for {} targets (interval: {}s)",
+            latency.targets().len(),
+            config.ping_interval
+        );
+    }
+
+    let speedtest = match config.speedtest_tool.as_deref().map(SpeedtestTool::parse) {
+        Some(Some(tool)) => {
+            let monitor = Arc::new(
+                SpeedtestMonitor::new(
+                    Arc::new(CommandSpeedtestRunner::new(tool)),
+                    Arc::new(procfs_adapter.system_source()),
+                    config.speedtest_interval,
+                    config.history_size,
+                )
+                .with_metered_interfaces(config.metered_interfaces.clone()),
+            );
+            tokio::spawn(monitor.clone().run());
+            info!("Speedtest scheduled every {}s", monitor.interval_seconds());
+            Some(monitor)
+        }
+        Some(None) => {
+            warn!(
+                "Unknown NANOMON_SPEEDTEST_TOOL '{}', speedtest disabled",
+                config.speedtest_tool.as_deref().unwrap_or_default()
+            );
+            None
+        }
+        None => None,
+    };
+
+    // Reports directory wins over invoking a scanner
+    let vuln_source: Option<Arc<dyn ports::VulnerabilitySource>> =
+        match (&config.vuln_reports_dir, config.vuln_scanner.as_deref()) {
+            (Some(dir), _) => Some(Arc::new(ReportDirVulnerabilitySource::new(dir.clone()))),
+            (None, Some(name)) => match ScannerTool::parse(name) {
+                Some(tool) => Some(Arc::new(CommandVulnerabilitySource::new(tool))),
+                None => {
+                    warn!(
+                        "Unknown NANOMON_VULN_SCANNER '{}', image scans disabled",
+                        name
+                    );
+                    None
                 }
+            },
+            (None, None) => None,
+        };
+    let security = Arc::new(SecurityMonitor::new(
+        vuln_source,
+        Duration::from_secs(config.vuln_scan_interval),
+    ));
+    if security.is_enabled() {
+        // Short tick: only images without a fresh report are scanned
+        tokio::spawn(security.clone().run(Duration::from_secs(300)));
+    }
+
+    let ssh = alert_config.ssh.map(|watch| {
+        info!("SSH login monitoring enabled ({})", watch.log);
+        let source = Arc::new(AuthLogReader::new(&watch.log, watch.fail2ban));
+        Arc::new(SshMonitor::new(
+            watch,
+            source,
+            alert_sink.clone(),
+            event_log.clone(),
+        ))
+    });
+
+    let firewall = config.enable_firewall.then(|| {
+        let mut monitor =
+            FirewallMonitor::new(Arc::new(NetfilterFirewall::new(config.proc_path.clone())));
+        if config.firewall_counters {
+            monitor = monitor.with_counter_history(config.history_size);
+        }
+        let monitor = Arc::new(monitor);
+        if monitor.counters_enabled() {
+            tokio::spawn(
+                monitor
+                    .clone()
+                    .run_counters(Duration::from_secs(config.poll_interval)),
+            );
+            info!("Sampling firewall rule counters");
+        }
+        monitor
+    });
+    if config.firewall_counters && firewall.is_none() {
+        warn!("NANOMON_FIREWALL_COUNTERS needs NANOMON_ENABLE_FIREWALL; counters disabled");
+    }
+
+    let vms = match config.hypervisor.as_deref().map(Hypervisor::parse) {
+        Some(Some(hypervisor)) => {
+            info!("Listing {:?} virtual machines", hypervisor);
+            Some(Arc::new(VmMonitor::new(Arc::new(
+                CommandVmSource::new(hypervisor).with_libvirt_uri(config.libvirt_uri.clone()),
+            ))))
+        }
+        Some(None) => {
+            warn!(
+                "Unknown NANOMON_HYPERVISOR '{}', VM listing disabled",
+                config.hypervisor.as_deref().unwrap_or_default()
+            );
+            None
+        }
+        None => None,
+    };
+
+    let footprint = Arc::new(FootprintMonitor::new(
+        Arc::new(procfs_adapter.clone()),
+        Some(config.memory_budget_mb * 1024 * 1024).filter(|&b| b > 0),
+    ));
+
+    // Aggregator side: per-host history of snapshots pushed by agents
+    let history_size = config.history_size;
+    let forget_after = Duration::from_secs(config.history_forget_after);
+    let remote_hosts = Arc::new(
+        RemoteHostService::new(move || {
+            let store = MemoryStore::new(history_size);
+            if forget_after.is_zero() {
+                Arc::new(store)
+            } else {
+                Arc::new(store.with_forget_after(forget_after))
             }
+        })
+        .with_skew_warning(config.remote_skew_seconds),
+    );
+    if config.ingest_token.is_some() {
+        info!("Accepting pushed snapshots at /api/v1/ingest");
+    }
+
+    if !alert_config.viewers.is_empty() {
+        info!(
+            "{} viewer token(s) configured, API reads need a token",
+            alert_config.viewers.len()
+        );
+    }
+    let viewers = Arc::new(alert_config.viewers);
+
+    let logins = match (
+        config.oidc_issuer.clone(),
+        config.oidc_client_id.clone(),
+        config.oidc_redirect_url.clone(),
+    ) {
+        (Some(issuer), Some(client_id), Some(redirect_url)) => {
+            info!("Dashboard login through {}, API calls need a token", issuer);
+            let provider = OidcClient::new(
+                issuer,
+                client_id,
+                config.oidc_client_secret.clone().unwrap_or_default(),
+                redirect_url,
+            );
+            Some(Arc::new(
+                LoginService::new(
+                    Arc::new(provider),
+                    Duration::from_secs(config.oidc_session_hours * 3600),
+                )
+                .with_allowed_users(config.oidc_allowed_users.clone()),
+            ))
+        }
+        (Some(_), _, _) => {
+            warn!("NANOMON_OIDC_ISSUER needs NANOMON_OIDC_CLIENT_ID and NANOMON_OIDC_REDIRECT_URL, login disabled");
+            None
         }
+        _ => None,
+    };
+
+    let topology = Topology::new(alert_config.topology).unwrap_or_else(|e| {
+        warn!("Ignoring topology config: {}", e);
+        Topology::default()
+    });
+    let topology = Arc::new(TopologyService::new(
+        topology,
+        remote_hosts.clone(),
+        config.remote_stale_seconds,
+        event_log.clone(),
+    ));
+
+    // Agent side: forward every snapshot to an aggregator
+    let publisher: Option<Arc<dyn ports::SnapshotPublisher>> = config.push_url.clone().map(|url| {
+        info!("Pushing snapshots to {}", url);
+        Arc::new(HttpSnapshotPublisher::new(url, config.push_token.clone())) as _
     });
 
-    info!("Background polling started (interval: {}s)", poll_interval);
+    // Start background polling loop
+    tokio::spawn(monitoring_service.clone().run(PollObserver {
+        monitoring: monitoring_service.clone(),
+        alerts: alerts.clone(),
+        footprint: footprint.clone(),
+        heartbeats: heartbeats.clone(),
+        backups: backups.clone(),
+        security: security.clone(),
+        supervisor: supervisor.clone(),
+        ssh: ssh.clone(),
+        topology: topology.clone(),
+        burn_in: burn_in.clone(),
+        reboot_detector,
+        publisher,
+    }));
+
+    info!(
+        "Background polling started (interval: {}s)",
+        config.poll_interval
+    );
 
     // Create HTTP server
-    let app = create_router(monitoring_service);
+    let app = create_router(AppState {
+        monitoring_service,
+        heartbeats,
+        backups,
+        power,
+        process_control,
+        latency,
+        custom_metrics,
+        speedtest,
+        volumes,
+        reclaim,
+        security,
+        ssh,
+        firewall,
+        vms,
+        timezone: config.timezone,
+        json_precision: config.json_precision,
+        privacy_mode: config.privacy_mode,
+        capabilities,
+        base_path: config.base_path.clone(),
+        access_log: config.access_log.then(|| {
+            Arc::new(AccessLog::new(
+                config.base_path.clone(),
+                config.access_log_exclude.clone(),
+            ))
+        }),
+        response_cache: (config.response_cache_ms > 0).then(|| {
+            Arc::new(ResponseCache::new(Duration::from_millis(
+                config.response_cache_ms,
+            )))
+        }),
+        http_metrics: Arc::new(HttpMetrics::new()),
+        footprint,
+        admin_token: config.admin_token.clone(),
+        data_files: Arc::new(DataFiles::from_config(&config)),
+        viewers,
+        logins,
+        auth_guard: Arc::new(AuthGuard::new(
+            config.auth_max_failures,
+            Duration::from_secs(config.auth_lockout),
+            event_log.clone(),
+        )),
+        trusted_proxies: Arc::new(config.trusted_proxies.clone()),
+        secure_cookies: config
+            .oidc_redirect_url
+            .as_deref()
+            .is_some_and(|url| url.starts_with("https://")),
+        remote_hosts,
+        topology,
+        ingest_token: config.ingest_token.clone(),
+        alerts,
+        maintenance,
+        burn_in,
+    });
     let addr = format!("0.0.0.0:{}", config.port);
     let listener = tokio::net::TcpListener::bind(&addr).await?;
 
     info!("NanoMon listening on {}", addr);
-    info!("  Dashboard: http://localhost:{}", config.port);
-    info!("  API: http://localhost:{}/api/dashboard", config.port);
-    info!("  Prometheus: http://localhost:{}/metrics", config.port);
+    let root = format!("http://localhost:{}{}", config.port, config.base_path);
+    info!("  Dashboard: {}/", root);
+    info!("  API: {}/api/v1/dashboard", root);
+    info!("  Prometheus: {}/metrics", root);
 
-    axum::serve(listener, app).await?;
+    // Client addresses for lockouts after repeated bad tokens
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+    )
+    .await?;
 
     Ok(())
 }
 
-fn load_alert_evaluator(config: &Config) -> Option<AlertEvaluator> {
-    let path = config.alert_config_path.as_ref()?;
+/// What the rest of NanoMon does with each polled snapshot before it is stored
+struct PollObserver {
+    monitoring: Arc<MonitoringService>,
+    alerts: Arc<AlertEvaluator>,
+    footprint: Arc<FootprintMonitor>,
+    heartbeats: Arc<HeartbeatMonitor>,
+    backups: Arc<BackupMonitor>,
+    security: Arc<SecurityMonitor>,
+    supervisor: Arc<ContainerSupervisor>,
+    ssh: Option<Arc<SshMonitor>>,
+    topology: Arc<TopologyService>,
+    burn_in: Arc<BurnInService>,
+    reboot_detector: RebootDetector,
+    /// Agent side: forwards every snapshot to an aggregator
+    publisher: Option<Arc<dyn ports::SnapshotPublisher>>,
+}
+
+#[async_trait]
+impl SnapshotObserver for PollObserver {
+    async fn observe(&self, snapshot: &Host) {
+        // Evaluate alerts before storing
+        if self.alerts.has_rules() {
+            self.alerts.evaluate(snapshot).await;
+        }
+        let services = self.monitoring.get_services().await.unwrap_or_default();
+        self.reboot_detector.observe(snapshot, &services);
+        if self.heartbeats.has_checks() {
+            self.heartbeats.check(&snapshot.hostname).await;
+        }
+        if self.backups.has_jobs() {
+            self.backups.refresh(&snapshot.hostname).await;
+        }
+        if self.security.is_enabled() {
+            self.security.observe(&snapshot.containers);
+        }
+        if self.supervisor.is_enabled() {
+            self.supervisor
+                .check(&snapshot.hostname, &snapshot.containers);
+        }
+        if let Some(ref ssh) = self.ssh {
+            ssh.refresh(&snapshot.hostname).await;
+        }
+        if let Some(ref publisher) = self.publisher {
+            let publisher = publisher.clone();
+            let pushed = snapshot.clone();
+            tokio::spawn(async move {
+                if let Err(e) = publisher.publish(&pushed).await {
+                    warn!("Failed to push snapshot: {}", e);
+                }
+            });
+        }
+        self.topology.check(&snapshot.hostname);
+        if self.burn_in.running().is_some() {
+            let throttles = self
+                .monitoring
+                .get_thermal_throttle_count()
+                .await
+                .unwrap_or_default();
+            self.burn_in.observe(snapshot, throttles);
+        }
+        self.footprint.check();
+    }
+}
+
+fn load_alert_config(config: &Config) -> AlertConfig {
+    let Some(path) = config.alert_config_path.as_ref() else {
+        return AlertConfig::default();
+    };
 
     let content = match std::fs::read_to_string(path) {
         Ok(c) => c,
         Err(e) => {
             warn!("Failed to read alert config at {:?}: {}", path, e);
-            return None;
+            return AlertConfig::default();
         }
     };
 
-    let rules: Vec<AlertRule> = match toml::from_str(&content) {
-        Ok(config) => {
-            let parsed: AlertConfig = config;
-            parsed.rules
+    let (parsed, problems) =
+        interface::config_check::parse_alert_config(&path.display().to_string(), &content);
+    for problem in &problems {
+        warn!("{}", problem);
+    }
+    match parsed {
+        Some(parsed) => {
+            info!(
+                "Loaded {} alert rules, {} heartbeat checks and {} backup jobs from {:?}",
+                parsed.rules.len(),
+                parsed.heartbeats.len(),
+                parsed.backups.len(),
+                path
+            );
+            parsed
         }
-        Err(e) => {
-            warn!("Failed to parse alert config: {}", e);
-            return None;
+        None => {
+            warn!("Alert config not loaded, see above (nanomon --check-config)");
+            AlertConfig::default()
         }
-    };
-
-    if rules.is_empty() {
-        return None;
     }
-
-    info!("Loaded {} alert rules from {:?}", rules.len(), path);
-    let sink = Arc::new(WebhookSink::new());
-    Some(AlertEvaluator::new(rules, sink))
-}
-
-#[derive(serde::Deserialize)]
-struct AlertConfig {
-    #[serde(default)]
-    rules: Vec<AlertRule>,
 }
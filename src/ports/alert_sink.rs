@@ -1,6 +1,6 @@
 use async_trait::async_trait;
 
-use crate::domain::AlertEvent;
+use crate::domain::{AlertEvent, Event};
 
 /// Port for sending alert notifications
 #[async_trait]
@@ -11,4 +11,26 @@ pub trait AlertSink: Send + Sync {
         url: &str,
         event: &AlertEvent,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Send alerts grouped by a notification route as one message; one by
+    /// one unless the sink knows better
+    async fn send_alerts(
+        &self,
+        url: &str,
+        events: &[AlertEvent],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        for event in events {
+            self.send_alert(url, event).await?;
+        }
+        Ok(())
+    }
+
+    /// Send an entry of the events log to the given webhook URL, signing the
+    /// payload with `secret` when given
+    async fn send_event(
+        &self,
+        url: &str,
+        event: &Event,
+        secret: Option<&str>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
 }
@@ -0,0 +1,19 @@
+use async_trait::async_trait;
+
+use crate::domain::AuthFailure;
+
+/// Port for reading SSH authentication failures
+#[async_trait]
+pub trait AuthLogSource: Send + Sync {
+    /// Failures logged since the previous call (the first call returns recent history)
+    async fn read_failures(
+        &self,
+    ) -> Result<Vec<AuthFailure>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Addresses currently banned (fail2ban), `None` if unavailable
+    async fn banned_ips(
+        &self,
+    ) -> Result<Option<Vec<String>>, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(None)
+    }
+}
@@ -0,0 +1,13 @@
+use async_trait::async_trait;
+
+use crate::domain::{BackupArtifact, BackupJob};
+
+/// Port for inspecting what a backup job left behind
+#[async_trait]
+pub trait BackupSource: Send + Sync {
+    /// Find the most recent artifact for the job, `None` if nothing was found
+    async fn latest_artifact(
+        &self,
+        job: &BackupJob,
+    ) -> Result<Option<BackupArtifact>, Box<dyn std::error::Error + Send + Sync>>;
+}
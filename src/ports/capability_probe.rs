@@ -0,0 +1,9 @@
+use async_trait::async_trait;
+
+use crate::domain::Capability;
+
+/// Port for adapters to report which of their collectors can work right now
+#[async_trait]
+pub trait CapabilityProbe: Send + Sync {
+    async fn probe(&self) -> Vec<(&'static str, Capability)>;
+}
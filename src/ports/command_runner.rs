@@ -0,0 +1,16 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+/// Port for running configured local commands
+#[async_trait]
+pub trait CommandRunner: Send + Sync {
+    /// Run `command` without a shell, with `env` added to NanoMon's own
+    /// environment; fails when it exits unsuccessfully or outlives `timeout`
+    async fn run(
+        &self,
+        command: &[String],
+        env: &[(String, String)],
+        timeout: Duration,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+}
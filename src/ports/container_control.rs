@@ -0,0 +1,13 @@
+use async_trait::async_trait;
+
+use crate::domain::ContainerId;
+
+/// Port for acting on containers
+#[async_trait]
+pub trait ContainerControl: Send + Sync {
+    /// Restart a container, stopping it first when it runs
+    async fn restart(
+        &self,
+        id: &ContainerId,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+}
@@ -1,6 +1,9 @@
 use async_trait::async_trait;
 
-use crate::domain::{Container, ContainerId, CpuMetrics, IoMetrics, MemoryMetrics, NetworkMetrics};
+use crate::domain::{
+    Container, ContainerId, ContainerMount, ContainerPids, CpuMetrics, IoMetrics, MemoryMetrics,
+    NetworkMetrics,
+};
 
 /// Stats for a single container
 #[derive(Debug, Clone)]
@@ -9,6 +12,7 @@ pub struct ContainerStats {
     pub memory: MemoryMetrics,
     pub network: NetworkMetrics,
     pub block_io: IoMetrics,
+    pub pids: Option<ContainerPids>,
 }
 
 /// Port for fetching container information
@@ -24,4 +28,10 @@ pub trait ContainerSource: Send + Sync {
         &self,
         id: &ContainerId,
     ) -> Result<ContainerStats, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// List the mounts of a container (by ID or name), `None` if it does not exist
+    async fn list_mounts(
+        &self,
+        id: &ContainerId,
+    ) -> Result<Option<Vec<ContainerMount>>, Box<dyn std::error::Error + Send + Sync>>;
 }
@@ -0,0 +1,14 @@
+use async_trait::async_trait;
+
+use crate::domain::DirectoryUsage;
+
+/// Port for measuring disk usage of a host directory
+#[async_trait]
+pub trait DirectoryScanner: Send + Sync {
+    /// Walk `path` (a host path) and sum allocated space, visiting at most `max_entries`
+    async fn usage(
+        &self,
+        path: &str,
+        max_entries: u64,
+    ) -> Result<DirectoryUsage, Box<dyn std::error::Error + Send + Sync>>;
+}
@@ -0,0 +1,22 @@
+use async_trait::async_trait;
+
+use crate::domain::{FirewallRuleset, ListeningSocket, RuleCounter};
+
+/// Port for inspecting the host firewall and listening sockets
+#[async_trait]
+pub trait FirewallSource: Send + Sync {
+    /// Current ruleset, `None` if no supported firewall tool is available
+    async fn ruleset(
+        &self,
+    ) -> Result<Option<FirewallRuleset>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Counters of commented rules and named counter objects, `None` without nftables
+    async fn rule_counters(
+        &self,
+    ) -> Result<Option<Vec<RuleCounter>>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// TCP sockets in LISTEN state in the host network namespace
+    async fn listening_sockets(
+        &self,
+    ) -> Result<Vec<ListeningSocket>, Box<dyn std::error::Error + Send + Sync>>;
+}
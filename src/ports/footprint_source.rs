@@ -0,0 +1,7 @@
+use crate::domain::MemoryFootprint;
+
+/// Port for measuring NanoMon's own resource usage
+pub trait FootprintSource: Send + Sync {
+    /// Current and peak resident memory of this process, without a budget
+    fn memory(&self) -> Result<MemoryFootprint, Box<dyn std::error::Error + Send + Sync>>;
+}
@@ -0,0 +1,21 @@
+use async_trait::async_trait;
+
+/// Port for logging users in through an external single sign-on provider
+#[async_trait]
+pub trait IdentityProvider: Send + Sync {
+    /// Where to send the browser to log in; the provider hands `state` back
+    /// to the callback and puts `nonce` in the identity it issues
+    async fn authorization_url(
+        &self,
+        state: &str,
+        nonce: &str,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// The user name the callback's `code` was issued for, once the identity
+    /// behind it is checked to carry `nonce`
+    async fn exchange(
+        &self,
+        code: &str,
+        nonce: &str,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>>;
+}
@@ -0,0 +1,11 @@
+use crate::domain::Inventory;
+
+/// Port for persisting the last known inventory across NanoMon restarts
+/// (a host reboot restarts NanoMon too, so memory alone is not enough).
+pub trait InventoryStore: Send + Sync {
+    /// Load the last saved inventory, if any
+    fn load(&self) -> Option<Inventory>;
+
+    /// Save the current inventory
+    fn save(&self, inventory: &Inventory) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+}
@@ -0,0 +1,10 @@
+use async_trait::async_trait;
+
+use crate::domain::CustomMetric;
+
+/// Port for a user-defined collector: one instance per configured collector
+#[async_trait]
+pub trait MetricCollector: Send + Sync {
+    /// Gather the collector's current values
+    async fn collect(&self) -> Result<Vec<CustomMetric>, Box<dyn std::error::Error + Send + Sync>>;
+}
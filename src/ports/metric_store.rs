@@ -16,11 +16,9 @@ pub trait MetricStore: Send + Sync {
     fn get_history(&self, duration: Duration) -> Vec<Arc<Host>>;
 
     /// Get the number of stored snapshots
-    #[allow(dead_code)]
     fn len(&self) -> usize;
 
     /// Check if the store is empty
-    #[allow(dead_code)]
     fn is_empty(&self) -> bool {
         self.len() == 0
     }
@@ -1,13 +1,53 @@
 pub mod alert_sink;
+pub mod auth_log_source;
+pub mod backup_source;
+pub mod capability_probe;
+pub mod command_runner;
+pub mod container_control;
 pub mod container_source;
+pub mod directory_scanner;
+pub mod firewall_source;
+pub mod footprint_source;
+pub mod identity_provider;
+pub mod inventory_store;
+pub mod metric_collector;
 pub mod metric_store;
+pub mod ping_prober;
+pub mod power_control;
+pub mod process_control;
 pub mod process_source;
+pub mod reclaim_source;
 pub mod service_source;
+pub mod snapshot_codec;
+pub mod snapshot_publisher;
+pub mod speedtest_runner;
 pub mod system_source;
+pub mod vm_source;
+pub mod vulnerability_source;
 
 pub use alert_sink::AlertSink;
+pub use auth_log_source::AuthLogSource;
+pub use backup_source::BackupSource;
+pub use capability_probe::CapabilityProbe;
+pub use command_runner::CommandRunner;
+pub use container_control::ContainerControl;
 pub use container_source::{ContainerSource, ContainerStats};
+pub use directory_scanner::DirectoryScanner;
+pub use firewall_source::FirewallSource;
+pub use footprint_source::FootprintSource;
+pub use identity_provider::IdentityProvider;
+pub use inventory_store::InventoryStore;
+pub use metric_collector::MetricCollector;
 pub use metric_store::MetricStore;
+pub use ping_prober::PingProber;
+pub use power_control::PowerControl;
+pub use process_control::ProcessControl;
 pub use process_source::ProcessSource;
+pub use reclaim_source::ReclaimSource;
 pub use service_source::ServiceSource;
+pub use snapshot_codec::SnapshotCodec;
+pub use snapshot_publisher::SnapshotPublisher;
+pub use speedtest_runner::SpeedtestRunner;
 pub use system_source::{HostInfo, SystemSource};
+pub use vm_source::VmSource;
+pub use vulnerability_source::VulnerabilitySource;
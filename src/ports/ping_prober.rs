@@ -0,0 +1,14 @@
+use async_trait::async_trait;
+
+use crate::domain::PingSample;
+
+/// Port for measuring ICMP latency and packet loss to a host
+#[async_trait]
+pub trait PingProber: Send + Sync {
+    /// Send `count` echo requests to `host` and summarize the replies
+    async fn probe(
+        &self,
+        host: &str,
+        count: u32,
+    ) -> Result<PingSample, Box<dyn std::error::Error + Send + Sync>>;
+}
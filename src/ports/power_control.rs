@@ -0,0 +1,19 @@
+use async_trait::async_trait;
+
+use crate::domain::PowerTarget;
+
+/// Port for changing the power state of remote machines
+#[async_trait]
+pub trait PowerControl: Send + Sync {
+    /// Send a Wake-on-LAN magic packet to the target
+    async fn wake(
+        &self,
+        target: &PowerTarget,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Shut the target down remotely
+    async fn shutdown(
+        &self,
+        target: &PowerTarget,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+}
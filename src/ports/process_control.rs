@@ -0,0 +1,12 @@
+use async_trait::async_trait;
+
+/// Port for changing how local processes are scheduled
+#[async_trait]
+pub trait ProcessControl: Send + Sync {
+    /// Set the nice value of `pid`
+    async fn renice(
+        &self,
+        pid: u32,
+        nice: i64,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+}
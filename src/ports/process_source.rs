@@ -1,6 +1,6 @@
 use async_trait::async_trait;
 
-use crate::domain::Process;
+use crate::domain::{Process, ProcessFilter, ProcessScanStats};
 
 /// Port for fetching process information
 #[async_trait]
@@ -10,15 +10,29 @@ pub trait ProcessSource: Send + Sync {
         &self,
     ) -> Result<Vec<Process>, Box<dyn std::error::Error + Send + Sync>>;
 
-    /// Get top N processes sorted by CPU usage
+    /// Get top N matching processes sorted by CPU usage
     async fn get_top_by_cpu(
         &self,
         n: usize,
+        filter: &ProcessFilter,
     ) -> Result<Vec<Process>, Box<dyn std::error::Error + Send + Sync>>;
 
-    /// Get top N processes sorted by memory usage
+    /// Get top N matching processes sorted by memory usage
     async fn get_top_by_memory(
         &self,
         n: usize,
+        filter: &ProcessFilter,
     ) -> Result<Vec<Process>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Get top N matching processes from lowest to highest nice, busiest first within a level
+    async fn get_top_by_nice(
+        &self,
+        n: usize,
+        filter: &ProcessFilter,
+    ) -> Result<Vec<Process>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// How many PIDs the latest listing read, lost to exit races or failed on
+    fn last_scan(&self) -> Option<ProcessScanStats> {
+        None
+    }
 }
@@ -0,0 +1,11 @@
+use async_trait::async_trait;
+
+use crate::domain::ReclaimSuggestion;
+
+/// Port for finding disk space that could be freed; only looks, never deletes
+#[async_trait]
+pub trait ReclaimSource: Send + Sync {
+    async fn suggestions(
+        &self,
+    ) -> Result<Vec<ReclaimSuggestion>, Box<dyn std::error::Error + Send + Sync>>;
+}
@@ -0,0 +1,11 @@
+use crate::domain::Host;
+
+/// Port for (de)serializing host snapshots written to persistent storage
+pub trait SnapshotCodec: Send + Sync {
+    /// Identifies the encoding of a stored record, so archives survive codec changes
+    fn tag(&self) -> u8;
+
+    fn encode(&self, snapshot: &Host) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>;
+
+    fn decode(&self, bytes: &[u8]) -> Result<Host, Box<dyn std::error::Error + Send + Sync>>;
+}
@@ -0,0 +1,12 @@
+use async_trait::async_trait;
+
+use crate::domain::Host;
+
+/// Port for forwarding local snapshots to an aggregator
+#[async_trait]
+pub trait SnapshotPublisher: Send + Sync {
+    async fn publish(
+        &self,
+        snapshot: &Host,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+}
@@ -0,0 +1,10 @@
+use async_trait::async_trait;
+
+use crate::domain::SpeedtestResult;
+
+/// Port for running a bandwidth test
+#[async_trait]
+pub trait SpeedtestRunner: Send + Sync {
+    /// Run one test; this can take a minute and saturates the link
+    async fn run(&self) -> Result<SpeedtestResult, Box<dyn std::error::Error + Send + Sync>>;
+}
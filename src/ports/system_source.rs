@@ -1,6 +1,11 @@
+use std::collections::HashMap;
+
 use async_trait::async_trait;
 
-use crate::domain::{CpuMetrics, Disk, LoadAverage, MemoryMetrics, NetworkInterface, Temperature};
+use crate::domain::{
+    CpuMetrics, Disk, KernelLimits, LoadAverage, MemoryMetrics, MemoryTopology, NetworkInterface,
+    Temperature,
+};
 
 /// Host information
 #[derive(Debug, Clone)]
@@ -44,4 +49,43 @@ pub trait SystemSource: Send + Sync {
     ) -> Result<Vec<Temperature>, Box<dyn std::error::Error + Send + Sync>> {
         Ok(Vec::new())
     }
+
+    /// Get kernel table usage (file handles, PIDs, conntrack).
+    /// Returns defaults if unavailable (graceful degradation).
+    async fn get_kernel_limits(
+        &self,
+    ) -> Result<KernelLimits, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(KernelLimits::default())
+    }
+
+    /// Hugepages and per-NUMA-node memory, collected on request only.
+    /// Returns defaults if unavailable (graceful degradation).
+    async fn get_memory_topology(
+        &self,
+    ) -> Result<MemoryTopology, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(MemoryTopology::default())
+    }
+
+    /// Tracked connections by protocol and original destination port, i.e. the
+    /// host port a client connected to. `None` without netfilter conntrack.
+    async fn connections_by_port(
+        &self,
+    ) -> Result<Option<HashMap<(String, u16), u64>>, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(None)
+    }
+
+    /// Interface carrying the IPv4 default route, if any
+    async fn default_route_interface(
+        &self,
+    ) -> Result<Option<String>, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(None)
+    }
+
+    /// Thermal throttling events counted by all CPU cores since boot.
+    /// `None` where the CPUs don't report them.
+    async fn thermal_throttle_count(
+        &self,
+    ) -> Result<Option<u64>, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(None)
+    }
 }
@@ -0,0 +1,11 @@
+use async_trait::async_trait;
+
+use crate::domain::VirtualMachine;
+
+/// Port for listing the virtual machines of a hypervisor host
+#[async_trait]
+pub trait VmSource: Send + Sync {
+    async fn list_vms(
+        &self,
+    ) -> Result<Vec<VirtualMachine>, Box<dyn std::error::Error + Send + Sync>>;
+}
@@ -0,0 +1,14 @@
+use async_trait::async_trait;
+
+use crate::domain::ImageScan;
+
+/// Port for obtaining vulnerability reports of container images.
+/// NanoMon does not scan itself; implementations run or read an external scanner.
+#[async_trait]
+pub trait VulnerabilitySource: Send + Sync {
+    /// Report for the image, `None` if none is available
+    async fn report(
+        &self,
+        image: &str,
+    ) -> Result<Option<ImageScan>, Box<dyn std::error::Error + Send + Sync>>;
+}
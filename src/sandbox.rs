@@ -0,0 +1,160 @@
+//! Optional self-sandboxing (`NANOMON_SANDBOX=true`): a Landlock ruleset limiting
+//! filesystem access and a seccomp filter denying syscalls a monitor never needs.
+//! Both are inherited by threads and child processes created afterwards, so this
+//! must run before the async runtime starts its workers.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use landlock::{
+    path_beneath_rules, Access, AccessFs, Ruleset, RulesetAttr, RulesetCreatedAttr, RulesetStatus,
+    ABI,
+};
+use seccompiler::{BpfProgram, SeccompAction, SeccompFilter, TargetArch};
+use tracing::{info, warn};
+
+use crate::config::AlertConfig;
+use crate::config::Config;
+use crate::domain::CollectorKind;
+
+/// Kernel, namespace and debugging syscalls; denying them turns most container
+/// escapes and host tampering into EPERM
+const DENIED_SYSCALLS: &[libc::c_long] = &[
+    libc::SYS_ptrace,
+    libc::SYS_process_vm_writev,
+    libc::SYS_mount,
+    libc::SYS_umount2,
+    libc::SYS_pivot_root,
+    libc::SYS_setns,
+    libc::SYS_unshare,
+    libc::SYS_open_by_handle_at,
+    libc::SYS_kexec_load,
+    libc::SYS_init_module,
+    libc::SYS_finit_module,
+    libc::SYS_delete_module,
+    libc::SYS_bpf,
+    libc::SYS_perf_event_open,
+    libc::SYS_userfaultfd,
+    libc::SYS_keyctl,
+    libc::SYS_add_key,
+    libc::SYS_request_key,
+    libc::SYS_swapon,
+    libc::SYS_swapoff,
+    libc::SYS_reboot,
+];
+
+pub fn apply(
+    config: &Config,
+    alert_config: &AlertConfig,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    apply_landlock(config, alert_config)?;
+    apply_seccomp()?;
+    info!(
+        "Sandbox enabled: seccomp denies {} syscalls",
+        DENIED_SYSCALLS.len()
+    );
+    Ok(())
+}
+
+fn apply_landlock(
+    config: &Config,
+    alert_config: &AlertConfig,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let abi = ABI::V5;
+    let (read_only, read_write) = allowed_paths(config, alert_config);
+
+    // Missing paths are skipped by path_beneath_rules
+    let status = Ruleset::default()
+        .handle_access(AccessFs::from_all(abi))?
+        .create()?
+        .add_rules(path_beneath_rules(&read_only, AccessFs::from_read(abi)))?
+        .add_rules(path_beneath_rules(&read_write, AccessFs::from_all(abi)))?
+        .restrict_self()?;
+
+    match status.ruleset {
+        RulesetStatus::FullyEnforced => info!("Landlock ruleset enforced"),
+        RulesetStatus::PartiallyEnforced => {
+            warn!("Landlock only partially enforced (older kernel ABI)")
+        }
+        RulesetStatus::NotEnforced => warn!("Landlock not supported by this kernel"),
+    }
+    Ok(())
+}
+
+/// Read-only (and executable) paths, then writable paths
+fn allowed_paths(config: &Config, alert_config: &AlertConfig) -> (Vec<PathBuf>, Vec<PathBuf>) {
+    let mut read_only: Vec<PathBuf> = [
+        "/proc", "/sys", "/etc", "/usr", "/lib", "/lib64", "/bin", "/sbin", "/var/log", "/run/log",
+    ]
+    .iter()
+    .map(PathBuf::from)
+    .collect();
+    read_only.push(config.proc_path.clone());
+    read_only.push(config.sys_path.clone());
+    read_only.push(config.etc_path.clone());
+    // Static web assets are served relative to the working directory
+    read_only.extend(std::env::current_dir().ok());
+    read_only.extend(config.host_root.clone());
+    read_only.extend(config.alert_config_path.clone());
+    read_only.extend(config.vuln_reports_dir.clone());
+    read_only.extend(config.textfile_dir.clone());
+    read_only.extend(alert_config.backups.iter().map(|b| PathBuf::from(&b.path)));
+    // Collector scripts may live outside the system directories
+    read_only.extend(
+        alert_config
+            .collectors
+            .iter()
+            .filter_map(|c| match &c.kind {
+                CollectorKind::Script { command } => command.first().map(PathBuf::from),
+                CollectorKind::Plugin { .. } => None,
+                CollectorKind::Textfile { directory } => Some(directory.clone()),
+            }),
+    );
+    read_only.extend(
+        alert_config
+            .plugins
+            .iter()
+            .filter_map(|p| p.command.first().map(PathBuf::from)),
+    );
+    read_only.extend(
+        alert_config
+            .reactions
+            .iter()
+            .filter_map(|r| r.command.first().map(PathBuf::from)),
+    );
+    read_only.extend(
+        alert_config
+            .ssh
+            .iter()
+            .filter(|ssh| ssh.log != "journald")
+            .map(|ssh| PathBuf::from(&ssh.log)),
+    );
+
+    let mut read_write: Vec<PathBuf> = vec![PathBuf::from("/tmp"), PathBuf::from("/dev/null")];
+    // Data files are replaced via a temp file in the same directory
+    for file in [&config.state_file, &config.archive_path]
+        .into_iter()
+        .flatten()
+    {
+        let dir = file.parent().filter(|p| !p.as_os_str().is_empty());
+        read_write.push(dir.unwrap_or(Path::new(".")).to_path_buf());
+    }
+
+    (read_only, read_write)
+}
+
+fn apply_seccomp() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let rules = DENIED_SYSCALLS
+        .iter()
+        .map(|&nr| (nr, Vec::new()))
+        .collect::<BTreeMap<_, _>>();
+    let filter = SeccompFilter::new(
+        rules,
+        SeccompAction::Allow,
+        SeccompAction::Errno(libc::EPERM as u32),
+        TargetArch::try_from(std::env::consts::ARCH)?,
+    )?;
+    let program: BpfProgram = filter.try_into()?;
+    seccompiler::apply_filter_all_threads(&program)?;
+    Ok(())
+}